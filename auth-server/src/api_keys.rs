@@ -0,0 +1,279 @@
+//! Scoped API keys for clients that cannot sign in interactively (browser
+//! extension, scripts). Keys are issued and revoked through the Data API
+//! under the owner's JWT, so row-level security stamps `user_id`; requests
+//! that present `x-api-key` are resolved against `DATABASE_URL` instead,
+//! since there is no JWT to hand the Data API.
+
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Path, Request, State},
+    http::{HeaderMap, Method, StatusCode, Uri, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+use le_client::{ApiErrorBody, ApiKeyInfo, ApiKeyScope, CreateApiKeyRequest, CreatedApiKey};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+use crate::validation::{ApiError, Valid};
+use crate::{AppState, bearer_token, data_api, database, upstream_status};
+
+const KEY_PREFIX: &str = "le_";
+/// Characters of the secret kept readable so a listed key can be recognised.
+const VISIBLE_CHARS: usize = 8;
+const INFO_COLUMNS: &str = "id,name,scope,prefix,created_at,last_used_at,revoked_at";
+/// Data API tables a read key may `GET` through the proxy.
+const READABLE_TABLES: &[&str] = &["words", "cards"];
+
+/// Owner of the key a `/data` request authenticated with, set by
+/// [`api_key_auth`] once the key's scope has been checked.
+#[derive(Debug, Clone)]
+pub struct ApiKeyCaller {
+    pub user_id: String,
+}
+
+/// Resolves `x-api-key` on every route, the proxy included, and enforces its
+/// scope. Requests without the header pass through and authenticate with
+/// their bearer token.
+pub async fn api_key_auth(
+    State(state): State<Arc<AppState>>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let Some(key) = req
+        .headers()
+        .get("x-api-key")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string())
+    else {
+        return Ok(next.run(req).await);
+    };
+    let row = database(&state)
+        .await?
+        .query_opt(
+            "UPDATE api_keys SET last_used_at = $2
+             WHERE key_hash = $1 AND revoked_at IS NULL
+             RETURNING user_id, scope",
+            &[&hash_key(&key), &Utc::now().to_rfc3339()],
+        )
+        .await
+        .map_err(|err| {
+            eprintln!("[api-keys] lookup failed: {err}");
+            StatusCode::BAD_GATEWAY
+        })?
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+    let scope = parse_scope(row.get(1)).ok_or(StatusCode::UNAUTHORIZED)?;
+    if !allows(scope, req.method(), req.uri().path()) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    req.extensions_mut().insert(ApiKeyCaller {
+        user_id: row.get(0),
+    });
+    Ok(next.run(req).await)
+}
+
+fn allows(scope: ApiKeyScope, method: &Method, path: &str) -> bool {
+    match scope {
+        ApiKeyScope::Read => {
+            method == Method::GET
+                && (path.starts_with("/data/")
+                    || READABLE_TABLES.contains(&path.trim_start_matches('/')))
+        }
+        ApiKeyScope::Capture => method == Method::POST && path == "/data/capture",
+    }
+}
+
+/// Answers a read key's `GET /words` or `GET /cards` from `DATABASE_URL`,
+/// since the Data API behind the proxy only takes a JWT. Every row comes
+/// back as a JSON array; PostgREST filters are not supported and get a 400.
+pub async fn read_table(state: &AppState, uri: &Uri) -> Result<Response, StatusCode> {
+    let table = uri.path().trim_start_matches('/');
+    if !READABLE_TABLES.contains(&table) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    if uri
+        .query()
+        .is_some_and(|query| !query.is_empty() && query != "select=*")
+    {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    // `table` is one of READABLE_TABLES, never caller input.
+    let row = database(state)
+        .await?
+        .query_one(
+            &format!("SELECT COALESCE(json_agg(t), '[]'::json)::text FROM {table} t"),
+            &[],
+        )
+        .await
+        .map_err(|err| {
+            eprintln!("[api-keys] read {table} failed: {err}");
+            StatusCode::BAD_GATEWAY
+        })?;
+    let body: String = row.get(0);
+    Ok(([(header::CONTENT_TYPE, "application/json")], body).into_response())
+}
+
+fn parse_scope(value: &str) -> Option<ApiKeyScope> {
+    match value {
+        "capture" => Some(ApiKeyScope::Capture),
+        "read" => Some(ApiKeyScope::Read),
+        _ => None,
+    }
+}
+
+fn scope_name(scope: ApiKeyScope) -> &'static str {
+    match scope {
+        ApiKeyScope::Capture => "capture",
+        ApiKeyScope::Read => "read",
+    }
+}
+
+fn hash_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+fn generate_key() -> String {
+    let mut bytes = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    format!("{KEY_PREFIX}{}", hex::encode(bytes))
+}
+
+/// Issues a key for the signed-in user. The secret is returned once and
+/// only its SHA-256 is stored.
+#[utoipa::path(
+    post,
+    path = "/auth/api-keys",
+    tag = "auth",
+    security(("bearer" = [])),
+    request_body = CreateApiKeyRequest,
+    responses(
+        (status = 200, body = CreatedApiKey),
+        (status = 401, description = "Missing or rejected bearer token"),
+        (status = 422, body = ApiErrorBody, description = "Missing name or unknown scope")
+    )
+)]
+pub async fn create_api_key(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Valid(payload): Valid<CreateApiKeyRequest>,
+) -> Result<Json<CreatedApiKey>, ApiError> {
+    let (target, client) = data_api(&state)?;
+    let authorization = bearer_token(&headers)?;
+    let key = generate_key();
+    let prefix = key[..KEY_PREFIX.len() + VISIBLE_CHARS].to_string();
+    let row = serde_json::json!({
+        "id": uuid::Uuid::new_v4().to_string(),
+        "name": payload.name.trim(),
+        "scope": scope_name(payload.scope),
+        "key_hash": hash_key(&key),
+        "prefix": prefix,
+        "created_at": Utc::now().to_rfc3339(),
+    });
+    let resp = client
+        .post(format!("{}/api_keys", target.trim_end_matches('/')))
+        .header("authorization", authorization)
+        .header("accept", "application/json")
+        .header("prefer", "return=representation")
+        .json(&row)
+        .send()
+        .await
+        .map_err(|err| {
+            eprintln!("[api-keys] upstream error: {err}");
+            StatusCode::BAD_GATEWAY
+        })?;
+    let info = first_row(resp).await?.ok_or(StatusCode::BAD_GATEWAY)?;
+    Ok(Json(CreatedApiKey { key, info }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/auth/api-keys",
+    tag = "auth",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, body = Vec<ApiKeyInfo>),
+        (status = 401, description = "Missing or rejected bearer token")
+    )
+)]
+pub async fn list_api_keys(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<ApiKeyInfo>>, StatusCode> {
+    let (target, client) = data_api(&state)?;
+    let authorization = bearer_token(&headers)?;
+    let url = format!(
+        "{}/api_keys?select={INFO_COLUMNS}&order=created_at.desc",
+        target.trim_end_matches('/')
+    );
+    let resp = client
+        .get(url)
+        .header("authorization", authorization)
+        .header("accept", "application/json")
+        .send()
+        .await
+        .map_err(|err| {
+            eprintln!("[api-keys] upstream error: {err}");
+            StatusCode::BAD_GATEWAY
+        })?;
+    Ok(Json(rows(resp).await?))
+}
+
+/// Revokes a key. It stays listed with `revoked_at` set.
+#[utoipa::path(
+    delete,
+    path = "/auth/api-keys/{id}",
+    tag = "auth",
+    security(("bearer" = [])),
+    params(("id" = String, Path, description = "Key id from the listing")),
+    responses(
+        (status = 200, body = ApiKeyInfo),
+        (status = 401, description = "Missing or rejected bearer token"),
+        (status = 404, description = "No key with that id belongs to the caller")
+    )
+)]
+pub async fn revoke_api_key(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<ApiKeyInfo>, StatusCode> {
+    let (target, client) = data_api(&state)?;
+    let authorization = bearer_token(&headers)?;
+    let id = uuid::Uuid::parse_str(&id).map_err(|_| StatusCode::NOT_FOUND)?;
+    let url = format!(
+        "{}/api_keys?id=eq.{id}&select={INFO_COLUMNS}",
+        target.trim_end_matches('/')
+    );
+    let resp = client
+        .patch(url)
+        .header("authorization", authorization)
+        .header("accept", "application/json")
+        .header("prefer", "return=representation")
+        .json(&serde_json::json!({ "revoked_at": Utc::now().to_rfc3339() }))
+        .send()
+        .await
+        .map_err(|err| {
+            eprintln!("[api-keys] upstream error: {err}");
+            StatusCode::BAD_GATEWAY
+        })?;
+    first_row(resp)
+        .await?
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+async fn rows(resp: reqwest::Response) -> Result<Vec<ApiKeyInfo>, StatusCode> {
+    if !resp.status().is_success() {
+        eprintln!("[api-keys] upstream status={}", resp.status());
+        return Err(upstream_status(resp.status()));
+    }
+    resp.json::<Vec<ApiKeyInfo>>()
+        .await
+        .map_err(|_| StatusCode::BAD_GATEWAY)
+}
+
+async fn first_row(resp: reqwest::Response) -> Result<Option<ApiKeyInfo>, StatusCode> {
+    Ok(rows(resp).await?.into_iter().next())
+}
@@ -0,0 +1,354 @@
+//! Trusted devices. A client registers itself with the access token it just
+//! obtained (on sign-in, or with `POST /auth/devices` after an OAuth login or
+//! a token refresh); the row keeps the device's name, platform, last-seen
+//! time, the SHA-256 of that token, and the Neon Auth session token of an
+//! email sign-in. With `DATABASE_URL` set, [`device_guard`] only lets
+//! registered tokens through. Revoking a device through `/auth/devices/{id}`
+//! ends its Neon Auth session and makes the guard turn its token away on
+//! every route, and the device cannot register a new token afterwards.
+//! Listing, registering, and revoking go through the Data API under the
+//! owner's JWT; the guard runs on every request and checks tokens over
+//! `DATABASE_URL`.
+
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Path, Request, State},
+    http::{HeaderMap, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use chrono::{Duration, Utc};
+use le_client::{ApiErrorBody, DeviceInfo, DeviceRegistration};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::validation::{ApiError, Valid};
+use crate::{AppState, bearer_token, data_api, database, join_url, upstream_status};
+
+const INFO_COLUMNS: &str = "id,name,platform,created_at,last_seen_at,revoked_at";
+/// How stale `last_seen_at` may get before a request refreshes it, so the
+/// guard does not write on every request.
+const LAST_SEEN_MINUTES: i64 = 5;
+/// `AuthResponse::error` for a sign-in from a revoked device.
+pub const REVOKED_MESSAGE: &str = "This device was signed out remotely";
+/// `AuthResponse::error` for a sign-in whose device could not be registered.
+pub const UNREGISTERED_MESSAGE: &str = "This device could not be registered; try again";
+
+fn hash_token(authorization: &HeaderValue) -> Option<String> {
+    let token = authorization.to_str().ok()?.strip_prefix("Bearer ")?.trim();
+    Some(hex::encode(Sha256::digest(token.as_bytes())))
+}
+
+/// Rejects bearer tokens registered to a revoked device, and tokens that were
+/// never registered except on `POST /auth/devices`, which registers them.
+/// Records when the other tokens were last seen, at most every
+/// [`LAST_SEEN_MINUTES`]. Every request passes while `DATABASE_URL` is unset.
+pub async fn device_guard(
+    State(state): State<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let (Some(_), Some(hash)) = (
+        state.db.as_ref(),
+        bearer_token(req.headers())
+            .ok()
+            .as_ref()
+            .and_then(hash_token),
+    ) else {
+        return Ok(next.run(req).await);
+    };
+    let now = Utc::now();
+    let row = database(&state)
+        .await?
+        .query_one(
+            "WITH seen AS (
+                 UPDATE devices SET last_seen_at = $2
+                 WHERE token_hash = $1 AND revoked_at IS NULL
+                   AND (last_seen_at IS NULL OR last_seen_at < $3)
+             )
+             SELECT bool_or(revoked_at IS NOT NULL) FROM devices WHERE token_hash = $1",
+            &[
+                &hash,
+                &now.to_rfc3339(),
+                &(now - Duration::minutes(LAST_SEEN_MINUTES)).to_rfc3339(),
+            ],
+        )
+        .await
+        .map_err(|err| {
+            eprintln!("[devices] lookup failed: {err}");
+            StatusCode::BAD_GATEWAY
+        })?;
+    let registering = req.method() == Method::POST && req.uri().path() == "/auth/devices";
+    match row.get::<_, Option<bool>>(0) {
+        Some(false) => Ok(next.run(req).await),
+        None if registering => Ok(next.run(req).await),
+        _ => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+/// Sign-ins must name their device once the guard is on, since the token
+/// they get is refused until it is registered.
+pub fn require_device(
+    state: &AppState,
+    device: Option<&DeviceRegistration>,
+) -> Result<(), ApiError> {
+    if state.db.is_some() && device.is_none() {
+        return Err(ApiError::field("device", "is required"));
+    }
+    Ok(())
+}
+
+/// Registers `device` for the token in `authorization`, or moves an already
+/// registered device onto it, keeping `session_token` for a later revoke when
+/// given. Fails with 403 when the device was revoked.
+pub async fn register(
+    state: &AppState,
+    authorization: HeaderValue,
+    device: &DeviceRegistration,
+    session_token: Option<&str>,
+) -> Result<DeviceInfo, ApiError> {
+    let (target, client) = data_api(state)?;
+    let id = uuid::Uuid::parse_str(device.id.trim())
+        .map_err(|_| ApiError::field("device.id", "must be a UUID"))?;
+    let token_hash = hash_token(&authorization).ok_or(StatusCode::UNAUTHORIZED)?;
+    let existing = rows(
+        client
+            .get(format!(
+                "{}/devices?id=eq.{id}&select={INFO_COLUMNS}",
+                target.trim_end_matches('/')
+            ))
+            .header("authorization", authorization.clone())
+            .header("accept", "application/json")
+            .send()
+            .await
+            .map_err(upstream_error)?,
+    )
+    .await?;
+    if existing.iter().any(|device| device.revoked_at.is_some()) {
+        return Err(StatusCode::FORBIDDEN.into());
+    }
+    let now = Utc::now().to_rfc3339();
+    let mut row = serde_json::json!({
+        "id": id.to_string(),
+        "name": device.name.trim(),
+        "platform": device.platform.trim().to_lowercase(),
+        "token_hash": token_hash,
+        "last_seen_at": now,
+    });
+    if existing.is_empty() {
+        row["created_at"] = serde_json::json!(now);
+    }
+    if let Some(session_token) = session_token {
+        row["session_token"] = serde_json::json!(session_token);
+    }
+    let resp = client
+        .post(format!(
+            "{}/devices?on_conflict=id&select={INFO_COLUMNS}",
+            target.trim_end_matches('/')
+        ))
+        .header("authorization", authorization)
+        .header("accept", "application/json")
+        .header(
+            "prefer",
+            "resolution=merge-duplicates,return=representation",
+        )
+        .json(&row)
+        .send()
+        .await
+        .map_err(upstream_error)?;
+    Ok(rows(resp)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or(StatusCode::BAD_GATEWAY)?)
+}
+
+/// Registers `device` during sign-in. The error is the `AuthResponse::error`
+/// message for a sign-in that gets no token: the device was revoked, or it
+/// could not be registered while the guard is on. Either way the new Neon
+/// Auth session is ended again. Without `DATABASE_URL`, failures are only
+/// logged.
+pub async fn register_on_sign_in(
+    state: &AppState,
+    token: &str,
+    device: &DeviceRegistration,
+    session_token: Option<&str>,
+) -> Result<(), &'static str> {
+    let registered = match HeaderValue::from_str(&format!("Bearer {token}")) {
+        Ok(authorization) => register(state, authorization, device, session_token).await,
+        Err(_) => Err(StatusCode::UNAUTHORIZED.into()),
+    };
+    let message = match registered {
+        Ok(_) => return Ok(()),
+        Err(ApiError::Status(StatusCode::FORBIDDEN)) => REVOKED_MESSAGE,
+        Err(err) => {
+            eprintln!("[devices] registration on sign-in failed: {err:?}");
+            if state.db.is_none() {
+                return Ok(());
+            }
+            UNREGISTERED_MESSAGE
+        }
+    };
+    if let Some(session_token) = session_token {
+        revoke_session(state, session_token).await;
+    }
+    Err(message)
+}
+
+/// Registers the calling device for the bearer token it sends.
+#[utoipa::path(
+    post,
+    path = "/auth/devices",
+    tag = "auth",
+    security(("bearer" = [])),
+    request_body = DeviceRegistration,
+    responses(
+        (status = 200, body = DeviceInfo),
+        (status = 401, description = "Missing or rejected bearer token"),
+        (status = 403, description = "The device was signed out remotely"),
+        (status = 422, body = ApiErrorBody, description = "Missing name or platform, or an id that is not a UUID")
+    )
+)]
+pub async fn register_device(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Valid(payload): Valid<DeviceRegistration>,
+) -> Result<Json<DeviceInfo>, ApiError> {
+    let authorization = bearer_token(&headers)?;
+    Ok(Json(register(&state, authorization, &payload, None).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/auth/devices",
+    tag = "auth",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, body = Vec<DeviceInfo>),
+        (status = 401, description = "Missing or rejected bearer token")
+    )
+)]
+pub async fn list_devices(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<DeviceInfo>>, StatusCode> {
+    let (target, client) = data_api(&state)?;
+    let authorization = bearer_token(&headers)?;
+    let url = format!(
+        "{}/devices?select={INFO_COLUMNS}&order=last_seen_at.desc.nullslast",
+        target.trim_end_matches('/')
+    );
+    let resp = client
+        .get(url)
+        .header("authorization", authorization)
+        .header("accept", "application/json")
+        .send()
+        .await
+        .map_err(upstream_error)?;
+    Ok(Json(rows(resp).await?))
+}
+
+/// Signs a device out remotely. Its Neon Auth session is ended and its token
+/// refused from now on; the row stays listed with `revoked_at` set so the
+/// device cannot register again.
+#[utoipa::path(
+    delete,
+    path = "/auth/devices/{id}",
+    tag = "auth",
+    security(("bearer" = [])),
+    params(("id" = String, Path, description = "Device id from the listing")),
+    responses(
+        (status = 200, body = DeviceInfo),
+        (status = 401, description = "Missing or rejected bearer token"),
+        (status = 404, description = "No device with that id belongs to the caller")
+    )
+)]
+pub async fn revoke_device(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<DeviceInfo>, StatusCode> {
+    let (target, client) = data_api(&state)?;
+    let authorization = bearer_token(&headers)?;
+    let id = uuid::Uuid::parse_str(&id).map_err(|_| StatusCode::NOT_FOUND)?;
+    let url = format!("{}/devices?id=eq.{id}", target.trim_end_matches('/'));
+    let resp = client
+        .get(format!("{url}&select=session_token"))
+        .header("authorization", authorization.clone())
+        .header("accept", "application/json")
+        .send()
+        .await
+        .map_err(upstream_error)?;
+    if !resp.status().is_success() {
+        eprintln!("[devices] upstream status={}", resp.status());
+        return Err(upstream_status(resp.status()));
+    }
+    let session_token = resp
+        .json::<Vec<SessionRow>>()
+        .await
+        .map_err(|_| StatusCode::BAD_GATEWAY)?
+        .into_iter()
+        .next()
+        .ok_or(StatusCode::NOT_FOUND)?
+        .session_token;
+    let resp = client
+        .patch(format!("{url}&select={INFO_COLUMNS}"))
+        .header("authorization", authorization)
+        .header("accept", "application/json")
+        .header("prefer", "return=representation")
+        .json(&serde_json::json!({
+            "revoked_at": Utc::now().to_rfc3339(),
+            "session_token": null,
+        }))
+        .send()
+        .await
+        .map_err(upstream_error)?;
+    let device = rows(resp)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or(StatusCode::NOT_FOUND)?;
+    if let Some(session_token) = session_token {
+        revoke_session(&state, &session_token).await;
+    }
+    Ok(Json(device))
+}
+
+#[derive(Deserialize)]
+struct SessionRow {
+    session_token: Option<String>,
+}
+
+/// Ends a Neon Auth session, so it can no longer mint JWTs. Failures are
+/// logged: the device's registered token is refused either way.
+async fn revoke_session(state: &AppState, session_token: &str) {
+    let mut req = reqwest::Client::new()
+        .post(join_url(&state.auth_url, "/revoke-session"))
+        .bearer_auth(session_token)
+        .json(&serde_json::json!({ "token": session_token }));
+    if let Some(origin) = state.allowed_origin.first() {
+        req = req.header("origin", origin);
+    }
+    match req.send().await {
+        Ok(resp) if resp.status().is_success() => {}
+        Ok(resp) => eprintln!("[devices] session revoke status={}", resp.status()),
+        Err(err) => eprintln!("[devices] session revoke failed: {err}"),
+    }
+}
+
+fn upstream_error(err: reqwest::Error) -> StatusCode {
+    eprintln!("[devices] upstream error: {err}");
+    StatusCode::BAD_GATEWAY
+}
+
+async fn rows(resp: reqwest::Response) -> Result<Vec<DeviceInfo>, StatusCode> {
+    if !resp.status().is_success() {
+        eprintln!("[devices] upstream status={}", resp.status());
+        return Err(upstream_status(resp.status()));
+    }
+    resp.json::<Vec<DeviceInfo>>()
+        .await
+        .map_err(|_| StatusCode::BAD_GATEWAY)
+}
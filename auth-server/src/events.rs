@@ -0,0 +1,152 @@
+//! Per-user event stream. Clients open `/ws` with their JWT and receive a
+//! [`ServerEvent`] whenever a request to this server changes their data, so
+//! they can sync right away instead of on a timer. Events only cover writes
+//! that pass through here; changes made straight against Postgres still
+//! arrive with the next sync.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::{
+    extract::{
+        Query, State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::Response,
+};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use le_client::ServerEvent;
+use serde::Deserialize;
+use tokio::sync::broadcast;
+
+use crate::{AppState, bearer_token, data_api, upstream_status};
+
+/// Events buffered per user; a connection further behind is told to resync.
+const CHANNEL_CAPACITY: usize = 32;
+/// Keeps idle connections open through proxies that drop silent sockets.
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Broadcast channels keyed by user id, created on the first subscription.
+#[derive(Default)]
+pub struct EventHub {
+    channels: Mutex<HashMap<String, broadcast::Sender<ServerEvent>>>,
+}
+
+impl EventHub {
+    fn subscribe(&self, user_id: &str) -> broadcast::Receiver<ServerEvent> {
+        let mut channels = self.channels.lock().unwrap_or_else(|err| err.into_inner());
+        channels
+            .entry(user_id.to_string())
+            .or_insert_with(|| broadcast::channel(CHANNEL_CAPACITY).0)
+            .subscribe()
+    }
+
+    /// Sends `event` to every open connection of `user_id`. Channels nobody
+    /// listens to any more are dropped here.
+    pub fn publish(&self, user_id: &str, event: ServerEvent) {
+        let mut channels = self.channels.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(sender) = channels.get(user_id)
+            && sender.send(event).is_err()
+        {
+            channels.remove(user_id);
+        }
+    }
+
+    /// Publishes for the owner of a JWT the Data API has just accepted.
+    pub fn publish_for_token(&self, authorization: &HeaderValue, event: ServerEvent) {
+        if let Some(user_id) = jwt_subject(authorization) {
+            self.publish(&user_id, event);
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StreamQuery {
+    /// Browsers cannot set headers on a WebSocket, so the JWT may come here.
+    access_token: Option<String>,
+}
+
+/// Upgrades to the event stream. The token is checked against the Data API
+/// before its subject is trusted.
+pub async fn event_stream(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<StreamQuery>,
+    upgrade: WebSocketUpgrade,
+) -> Result<Response, StatusCode> {
+    let authorization = match query.access_token {
+        Some(token) => HeaderValue::from_str(&format!("Bearer {}", token.trim()))
+            .map_err(|_| StatusCode::UNAUTHORIZED)?,
+        None => bearer_token(&headers)?,
+    };
+    let user_id = jwt_subject(&authorization).ok_or(StatusCode::UNAUTHORIZED)?;
+    verify_token(&state, &authorization).await?;
+    let events = state.events.subscribe(&user_id);
+    Ok(upgrade.on_upgrade(move |socket| stream(socket, events)))
+}
+
+async fn stream(mut socket: WebSocket, mut events: broadcast::Receiver<ServerEvent>) {
+    let mut ping = tokio::time::interval(PING_INTERVAL);
+    loop {
+        let event = tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(_)) => ServerEvent::SyncRecommended {
+                    reason: "missed events".to_string(),
+                },
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => continue,
+            },
+            _ = ping.tick() => {
+                if socket.send(Message::Ping(Vec::new())).await.is_err() {
+                    return;
+                }
+                continue;
+            }
+        };
+        let Ok(text) = serde_json::to_string(&event) else {
+            continue;
+        };
+        if socket.send(Message::Text(text)).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Any authenticated Data API read fails on a forged or expired token.
+async fn verify_token(state: &AppState, authorization: &HeaderValue) -> Result<(), StatusCode> {
+    let (target, client) = data_api(state)?;
+    let resp = client
+        .get(format!(
+            "{}/settings?select=key&limit=1",
+            target.trim_end_matches('/')
+        ))
+        .header("authorization", authorization.clone())
+        .header("accept", "application/json")
+        .send()
+        .await
+        .map_err(|err| {
+            eprintln!("[events] upstream error: {err}");
+            StatusCode::BAD_GATEWAY
+        })?;
+    if !resp.status().is_success() {
+        return Err(upstream_status(resp.status()));
+    }
+    Ok(())
+}
+
+/// The `sub` claim of a bearer JWT, read without checking the signature;
+/// callers only use it once the Data API has accepted the token.
+fn jwt_subject(authorization: &HeaderValue) -> Option<String> {
+    let token = authorization.to_str().ok()?.strip_prefix("Bearer ")?;
+    let payload = token.split('.').nth(1)?;
+    let claims: serde_json::Value =
+        serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).ok()?).ok()?;
+    claims.get("sub")?.as_str().map(str::to_string)
+}
@@ -1,18 +1,44 @@
+use api_keys::ApiKeyCaller;
 use axum::{
     Json, Router,
     body::{Body, to_bytes},
-    extract::State,
+    extract::{Extension, Query, State},
     http::{HeaderMap, HeaderValue, Request, StatusCode},
-    middleware::{Next, from_fn},
+    middleware::{Next, from_fn, from_fn_with_state},
     response::Response,
-    routing::post,
+    routing::{get, post},
 };
 use dotenvy::dotenv;
+use le_client::{
+    ApiErrorBody, AuthResponse, CaptureRequest, CaptureResponse, CefrLevel, CleanupRequest,
+    CleanupResponse, CleanupSuggestion, EmailAuthRequest, GenerateQuestionRequest,
+    GenerateSentenceRequest, GeneratedQuestion, GeneratedSentence, GradeSentenceRequest,
+    SentenceAttempt, SentenceGrade, SentenceHistory, SentenceMonth, SentenceRubric, ServerEvent,
+    SettingEntry, SettingsPayload,
+};
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
+use std::collections::BTreeMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+use validation::{ApiError, MIN_PASSWORD_CHARS, ModelReply, Valid, model_reply};
+
+mod api_keys;
+mod devices;
+mod events;
+mod prompts;
+mod social;
+mod tts;
+mod validation;
+
+/// Tries at generating a sentence within the requested level before giving up.
+const SENTENCE_ATTEMPTS: usize = 3;
+/// Calls allowed for one structured reply: the first, plus corrections
+/// after a reply that failed its schema or validation.
+const REPLY_ATTEMPTS: usize = 3;
 
 #[derive(Clone)]
 struct AppState {
@@ -23,85 +49,115 @@ struct AppState {
     anthropic_key: Option<String>,
     anthropic_model: String,
     anthropic_client: reqwest::Client,
+    /// Connections to `DATABASE_URL`, used for requests that carry an API key
+    /// instead of a JWT. Dropped connections are replaced on the next request.
+    db: Option<deadpool_postgres::Pool>,
+    events: Arc<events::EventHub>,
+    prompts: Arc<prompts::PromptCache>,
+    tts: Arc<tts::Tts>,
 }
 
-#[derive(Debug, Deserialize)]
-struct EmailAuthRequest {
-    email: String,
-    password: String,
-    name: Option<String>,
-}
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Language Enforcer API"),
+    paths(
+        sign_in,
+        sign_up,
+        generate_sentence,
+        generate_question,
+        cleanup_translations,
+        grade_sentence,
+        tts::speak,
+        get_settings,
+        put_settings,
+        capture_word,
+        sentence_history,
+        api_keys::create_api_key,
+        api_keys::list_api_keys,
+        api_keys::revoke_api_key,
+        devices::register_device,
+        devices::list_devices,
+        devices::revoke_device,
+        social::join_leaderboard,
+        social::leave_leaderboard,
+        social::weekly_leaderboard,
+    ),
+    modifiers(&BearerAuth)
+)]
+struct ApiDoc;
 
-#[derive(Debug, Deserialize)]
-struct GenerateSentenceRequest {
-    word: String,
-    translation: Option<String>,
-    source_language: String,
-    target_language: String,
-    concept: Option<String>,
-}
+struct BearerAuth;
 
-#[derive(Debug, Deserialize)]
-struct GenerateQuestionRequest {
-    word: String,
-    translation: Option<String>,
-    source_language: String,
-    target_language: String,
-    concept: Option<String>,
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("x-api-key"))),
+        );
+    }
 }
 
-#[derive(Debug, Deserialize)]
-struct GradeSentenceRequest {
-    word: String,
-    target_language: String,
-    user_sentence: String,
-    question: Option<String>,
-    concept: Option<String>,
+#[derive(Debug, Clone, Serialize)]
+struct AnthropicMessage {
+    role: String,
+    /// A string, or an array of content blocks.
+    content: Value,
 }
 
-#[derive(Debug, Deserialize)]
-struct CleanupEntry {
-    word_id: String,
-    text: String,
-    translation: Option<String>,
-    language: String,
-    notes: Option<String>,
+#[derive(Debug, Serialize)]
+struct AnthropicRequest {
+    model: String,
+    max_tokens: u32,
+    messages: Vec<AnthropicMessage>,
+    temperature: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<String>,
+    tools: Vec<Value>,
+    tool_choice: Value,
 }
 
-#[derive(Debug, Deserialize)]
-struct CleanupRequest {
-    entries: Vec<CleanupEntry>,
+/// The tool call a structured request forces the model to make.
+struct ToolUse {
+    id: String,
+    input: Value,
 }
 
-#[derive(Debug, Serialize)]
-struct CleanupSuggestion {
-    word_id: String,
-    text: String,
-    language: String,
-    current_translation: Option<String>,
+/// What the cleanup prompt asks the model to return for each entry.
+#[derive(Debug, Deserialize)]
+struct CleanupReply {
     suggestion: String,
     notes: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
-struct CleanupResponse {
-    suggestions: Vec<CleanupSuggestion>,
-}
+impl ModelReply for CleanupReply {
+    const TOOL: &'static str = "submit_translation";
+    const DESCRIPTION: &'static str = "Submit the suggested translation and notes.";
 
-#[derive(Debug, Serialize)]
-struct AnthropicMessage {
-    role: String,
-    content: String,
+    fn input_schema() -> Value {
+        validation::object_schema(json!({
+            "suggestion": { "type": "string", "description": "The suggested translation." },
+            "notes": {
+                "type": ["string", "null"],
+                "description": "Nuance differences, or null."
+            }
+        }))
+    }
 }
 
-#[derive(Debug, Serialize)]
-struct AnthropicRequest {
-    model: String,
-    max_tokens: u32,
-    messages: Vec<AnthropicMessage>,
-    temperature: f32,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    system: Option<String>,
+impl validation::Validate for CleanupReply {
+    fn validate(&self, issues: &mut Vec<le_client::FieldIssue>) {
+        if self.suggestion.trim().is_empty() {
+            issues.push(le_client::FieldIssue {
+                field: "suggestion".to_string(),
+                message: "must not be empty".to_string(),
+            });
+        }
+    }
 }
 
 #[tokio::main]
@@ -161,6 +217,14 @@ async fn main() {
         .build()
         .expect("failed to build anthropic client");
 
+    // Speech requests share the AI client's timeouts and pool settings.
+    let tts = tts::Tts::from_env(anthropic_client.clone());
+
+    let db = match std::env::var("DATABASE_URL") {
+        Ok(url) => connect_database(&url).await,
+        Err(_) => None,
+    };
+
     let state = Arc::new(AppState {
         auth_url,
         proxy_target: proxy_target.clone(),
@@ -169,6 +233,10 @@ async fn main() {
         anthropic_key,
         anthropic_model,
         anthropic_client,
+        db,
+        events: Arc::default(),
+        prompts: Arc::default(),
+        tts: Arc::new(tts),
     });
 
     let app = Router::new()
@@ -178,14 +246,49 @@ async fn main() {
         .route("/ai/generate-question", post(generate_question))
         .route("/ai/cleanup", post(cleanup_translations))
         .route("/ai/grade-sentence", post(grade_sentence))
+        .route("/ai/tts", post(tts::speak))
+        .route(
+            "/auth/api-keys",
+            get(api_keys::list_api_keys).post(api_keys::create_api_key),
+        )
+        .route(
+            "/auth/api-keys/:id",
+            axum::routing::delete(api_keys::revoke_api_key),
+        )
+        .route(
+            "/auth/devices",
+            get(devices::list_devices).post(devices::register_device),
+        )
+        .route(
+            "/auth/devices/:id",
+            axum::routing::delete(devices::revoke_device),
+        )
+        .route(
+            "/social/leaderboard/me",
+            axum::routing::put(social::join_leaderboard).delete(social::leave_leaderboard),
+        )
+        .route(
+            "/social/leaderboard/weekly",
+            get(social::weekly_leaderboard),
+        )
+        .merge(
+            Router::new()
+                .route("/data/settings", get(get_settings).put(put_settings))
+                .route("/data/capture", post(capture_word))
+                .route("/data/sentence-history", get(sentence_history)),
+        )
+        .route("/ws", get(events::event_stream))
+        .route("/openapi.json", get(openapi_json))
         .fallback(proxy_request)
+        .layer(from_fn_with_state(state.clone(), api_keys::api_key_auth))
+        .layer(from_fn_with_state(state.clone(), devices::device_guard))
         .with_state(state.clone())
         .layer(from_fn(log_request))
         .layer(cors);
 
     let addr: SocketAddr = bind_addr.parse().expect("invalid BIND_ADDR");
     println!(
-        "auth-server listening on http://{addr} (proxy_target={}, insecure={proxy_insecure}, allowed_origin={})",
+        "auth-server listening on http://{addr} (proxy_target={}, insecure={proxy_insecure}, allowed_origin={}, tts={})",
         proxy_target
             .clone()
             .unwrap_or_else(|| "disabled".to_string()),
@@ -193,26 +296,45 @@ async fn main() {
             "any".to_string()
         } else {
             state.allowed_origin.join(",")
-        }
+        },
+        state.tts.describe()
     );
     axum::serve(tokio::net::TcpListener::bind(addr).await.unwrap(), app)
         .await
         .unwrap();
 }
 
+/// Logs the method, path, and status of every request. The query string is
+/// left out: `/ws` takes the caller's JWT as `?access_token=`.
 async fn log_request(req: Request<Body>, next: Next) -> Response {
     let method = req.method().clone();
-    let uri = req.uri().clone();
+    let path = req.uri().path().to_string();
     let response = next.run(req).await;
-    println!("[request] {} {} -> {}", method, uri, response.status());
+    println!("[request] {} {} -> {}", method, path, response.status());
     response
 }
 
+async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+/// Signs in with Neon Auth and exchanges the session for a JWT.
+#[utoipa::path(
+    post,
+    path = "/auth/sign-in",
+    tag = "auth",
+    request_body = EmailAuthRequest,
+    responses(
+        (status = 200, body = AuthResponse, description = "Signed in, or `error` set; a `device` signed out remotely or not registered gets no token"),
+        (status = 422, body = ApiErrorBody, description = "Missing or malformed email or password, or no `device` while devices are checked")
+    )
+)]
 async fn sign_in(
     State(state): State<Arc<AppState>>,
-    Json(payload): Json<EmailAuthRequest>,
-) -> Result<Json<Value>, StatusCode> {
+    Valid(payload): Valid<EmailAuthRequest>,
+) -> Result<Json<AuthResponse>, ApiError> {
     println!("[auth] sign-in request");
+    devices::require_device(&state, payload.device.as_ref())?;
     let client = reqwest::Client::builder()
         .cookie_store(true)
         .build()
@@ -235,33 +357,62 @@ async fn sign_in(
         .unwrap_or_else(|_| json!({ "error": "invalid response from auth server" }));
 
     if !status.is_success() {
-        return Ok(Json(json!({
-            "error": raw,
-            "access_token": null,
-            "user": null,
-            "raw": raw
-        })));
+        return Ok(Json(AuthResponse {
+            access_token: None,
+            user: None,
+            error: Some(raw.clone()),
+            raw,
+        }));
     }
 
     let access_token = fetch_jwt(&client, &state.auth_url).await;
+    if let (Some(token), Some(device)) = (&access_token, &payload.device)
+        && let Err(message) =
+            devices::register_on_sign_in(&state, token, device, session_token(&raw)).await
+    {
+        return Ok(Json(AuthResponse {
+            access_token: None,
+            user: None,
+            error: Some(json!({ "error": message })),
+            raw,
+        }));
+    }
     let user = raw
         .get("data")
         .and_then(|data| data.get("user"))
         .cloned()
         .or_else(|| raw.get("user").cloned());
 
-    Ok(Json(json!({
-        "access_token": access_token,
-        "user": user,
-        "raw": raw
-    })))
+    Ok(Json(AuthResponse {
+        access_token,
+        user,
+        error: None,
+        raw,
+    }))
 }
 
+#[utoipa::path(
+    post,
+    path = "/auth/sign-up",
+    tag = "auth",
+    request_body = EmailAuthRequest,
+    responses(
+        (status = 200, body = AuthResponse, description = "Signed in, or `error` set; a `device` signed out remotely or not registered gets no token"),
+        (status = 422, body = ApiErrorBody, description = "Missing or malformed email or password, or no `device` while devices are checked")
+    )
+)]
 async fn sign_up(
     State(state): State<Arc<AppState>>,
-    Json(payload): Json<EmailAuthRequest>,
-) -> Result<Json<Value>, StatusCode> {
+    Valid(payload): Valid<EmailAuthRequest>,
+) -> Result<Json<AuthResponse>, ApiError> {
     println!("[auth] sign-up request");
+    devices::require_device(&state, payload.device.as_ref())?;
+    if payload.password.chars().count() < MIN_PASSWORD_CHARS {
+        return Err(ApiError::field(
+            "password",
+            &format!("must be at least {MIN_PASSWORD_CHARS} characters"),
+        ));
+    }
     let client = reqwest::Client::builder()
         .cookie_store(true)
         .build()
@@ -285,26 +436,46 @@ async fn sign_up(
         .unwrap_or_else(|_| json!({ "error": "invalid response from auth server" }));
 
     if !status.is_success() {
-        return Ok(Json(json!({
-            "error": raw,
-            "access_token": null,
-            "user": null,
-            "raw": raw
-        })));
+        return Ok(Json(AuthResponse {
+            access_token: None,
+            user: None,
+            error: Some(raw.clone()),
+            raw,
+        }));
     }
 
     let access_token = fetch_jwt(&client, &state.auth_url).await;
+    if let (Some(token), Some(device)) = (&access_token, &payload.device)
+        && let Err(message) =
+            devices::register_on_sign_in(&state, token, device, session_token(&raw)).await
+    {
+        return Ok(Json(AuthResponse {
+            access_token: None,
+            user: None,
+            error: Some(json!({ "error": message })),
+            raw,
+        }));
+    }
     let user = raw
         .get("data")
         .and_then(|data| data.get("user"))
         .cloned()
         .or_else(|| raw.get("user").cloned());
 
-    Ok(Json(json!({
-        "access_token": access_token,
-        "user": user,
-        "raw": raw
-    })))
+    Ok(Json(AuthResponse {
+        access_token,
+        user,
+        error: None,
+        raw,
+    }))
+}
+
+/// The Neon Auth session token in a sign-in or sign-up reply, kept with the
+/// device so revoking it can end the session.
+fn session_token(raw: &Value) -> Option<&str> {
+    raw.get("token")
+        .or_else(|| raw.get("data").and_then(|data| data.get("token")))
+        .and_then(Value::as_str)
 }
 
 async fn fetch_jwt(client: &reqwest::Client, auth_url: &str) -> Option<String> {
@@ -319,12 +490,24 @@ async fn fetch_jwt(client: &reqwest::Client, auth_url: &str) -> Option<String> {
         .map(|v| v.to_string())
 }
 
+#[utoipa::path(
+    post,
+    path = "/ai/generate-sentence",
+    tag = "ai",
+    request_body = GenerateSentenceRequest,
+    responses(
+        (status = 200, body = GeneratedSentence),
+        (status = 422, body = ApiErrorBody, description = "The request body is missing fields or has invalid values"),
+        (status = 502, body = ApiErrorBody, description = "The model returned an unusable response"),
+        (status = 503, description = "ANTHROPIC_API_KEY is not configured")
+    )
+)]
 async fn generate_sentence(
     State(state): State<Arc<AppState>>,
-    Json(payload): Json<GenerateSentenceRequest>,
-) -> Result<Json<Value>, StatusCode> {
+    Valid(payload): Valid<GenerateSentenceRequest>,
+) -> Result<Json<GeneratedSentence>, ApiError> {
     let Some(key) = state.anthropic_key.as_ref() else {
-        return Err(StatusCode::SERVICE_UNAVAILABLE);
+        return Err(StatusCode::SERVICE_UNAVAILABLE.into());
     };
     let translation_hint = payload
         .translation
@@ -341,103 +524,174 @@ async fn generate_sentence(
             )
         })
         .unwrap_or_default();
-    let system = "Return ONLY a raw JSON object with keys \"sentence\" and \"translation\". Do NOT use markdown code blocks or formatting. Return pure JSON only. Both the sentence and translation should read like a CEFR B1-level example.";
-    let user = format!(
-        "Create a natural {source} sentence using the word \"{word}\" at CEFR B1 level. Provide its {target} translation written with B1-level vocabulary and grammar. Translation hint: {hint}.{concept_note}",
-        source = payload.source_language,
-        target = payload.target_language,
-        word = payload.word,
-        hint = translation_hint,
-        concept_note = concept_note
-    );
-    let content = call_anthropic(&state, key, system, &user).await?;
-    let data: Value = serde_json::from_str(&content).map_err(|_| StatusCode::BAD_GATEWAY)?;
-    Ok(Json(data))
+    let level = payload.level.as_str();
+    let max_words = payload
+        .max_words
+        .unwrap_or_else(|| payload.level.default_max_words());
+    let max_words_text = max_words.to_string();
+    let prompt = prompts::prompt(&state, prompts::GENERATE_SENTENCE).await?;
+    let values = [
+        ("level", level),
+        ("max_words", max_words_text.as_str()),
+        ("source", payload.source_language.as_str()),
+        ("target", payload.target_language.as_str()),
+        ("word", payload.word.as_str()),
+        ("hint", translation_hint),
+        ("concept_note", concept_note.as_str()),
+    ];
+    let system = prompt.system(&values);
+    let mut user = prompt.user(&values);
+    let mut rejected = String::new();
+    for _ in 0..SENTENCE_ATTEMPTS {
+        let mut reply: GeneratedSentence = ask_model(&state, key, &prompt, &system, &user).await?;
+        let Some(reason) = sentence_too_complex(&reply.sentence, payload.level, max_words) else {
+            reply.prompt_version = Some(prompt.label());
+            return Ok(Json(reply));
+        };
+        eprintln!("[anthropic] regenerating {level} sentence: {reason}");
+        user.push_str(&format!(
+            " Your previous sentence \"{sentence}\" was too hard ({reason}); write a simpler one.",
+            sentence = reply.sentence
+        ));
+        rejected = reason;
+    }
+    Err(ApiError::Model(format!(
+        "no CEFR {level} sentence after {SENTENCE_ATTEMPTS} attempts: {rejected}"
+    )))
 }
 
+/// Why `sentence` is above `level`, judged by length and clause count.
+fn sentence_too_complex(sentence: &str, level: CefrLevel, max_words: usize) -> Option<String> {
+    let words = sentence.split_whitespace().count();
+    if words > max_words {
+        return Some(format!("{words} words, limit {max_words}"));
+    }
+    let clauses = sentence
+        .split([',', ';', ':'])
+        .filter(|part| !part.trim().is_empty())
+        .count();
+    if clauses > level.max_clauses() {
+        return Some(format!("{clauses} clauses, limit {}", level.max_clauses()));
+    }
+    None
+}
+
+#[utoipa::path(
+    post,
+    path = "/ai/generate-question",
+    tag = "ai",
+    request_body = GenerateQuestionRequest,
+    responses(
+        (status = 200, body = GeneratedQuestion),
+        (status = 422, body = ApiErrorBody, description = "The request body is missing fields or has invalid values"),
+        (status = 502, body = ApiErrorBody, description = "The model returned an unusable response"),
+        (status = 503, description = "ANTHROPIC_API_KEY is not configured")
+    )
+)]
 async fn generate_question(
     State(state): State<Arc<AppState>>,
-    Json(payload): Json<GenerateQuestionRequest>,
-) -> Result<Json<Value>, StatusCode> {
+    Valid(payload): Valid<GenerateQuestionRequest>,
+) -> Result<Json<GeneratedQuestion>, ApiError> {
     let Some(key) = state.anthropic_key.as_ref() else {
-        return Err(StatusCode::SERVICE_UNAVAILABLE);
+        return Err(StatusCode::SERVICE_UNAVAILABLE.into());
     };
     let concept = sanitize_concept(&payload.concept);
     let concept_note = concept
         .as_ref()
         .map(|value| format!(" Include the concept \"{value}\" in the question so the learner can use both the word and that construction.", value = value))
         .unwrap_or_default();
-    let system = "Return ONLY a raw JSON object with key \"question\". Do NOT use markdown code blocks or formatting. Return pure JSON only. Compose the question in Dutch at CEFR B1 level and ensure it clearly asks the learner to respond with a sentence that uses the provided word and, when available, the highlighted concept.";
-    let user = format!(
-        "Using the word \"{word}\" ({source}), craft a Dutch CEFR B1 question that mentions both the word and the concept, then ask the learner to reply with a Dutch sentence featuring them. {concept_note} Respond only with the question itself.",
-        source = payload.source_language,
-        word = payload.word,
-        concept_note = concept_note
-    );
-    let content = call_anthropic(&state, key, system, &user).await?;
-    let data: Value = serde_json::from_str(&content).map_err(|_| StatusCode::BAD_GATEWAY)?;
-    Ok(Json(data))
+    let prompt = prompts::prompt(&state, prompts::GENERATE_QUESTION).await?;
+    let values = [
+        ("source", payload.source_language.as_str()),
+        ("word", payload.word.as_str()),
+        ("concept_note", concept_note.as_str()),
+    ];
+    let mut reply: GeneratedQuestion = ask_model(
+        &state,
+        key,
+        &prompt,
+        &prompt.system(&values),
+        &prompt.user(&values),
+    )
+    .await?;
+    reply.prompt_version = Some(prompt.label());
+    Ok(Json(reply))
 }
 
+#[utoipa::path(
+    post,
+    path = "/ai/cleanup",
+    tag = "ai",
+    request_body = CleanupRequest,
+    responses(
+        (status = 200, body = CleanupResponse),
+        (status = 422, body = ApiErrorBody, description = "The request body is missing fields or has invalid values"),
+        (status = 502, body = ApiErrorBody, description = "The model returned an unusable response"),
+        (status = 503, description = "ANTHROPIC_API_KEY is not configured")
+    )
+)]
 async fn cleanup_translations(
     State(state): State<Arc<AppState>>,
-    Json(payload): Json<CleanupRequest>,
-) -> Result<Json<CleanupResponse>, StatusCode> {
+    Valid(payload): Valid<CleanupRequest>,
+) -> Result<Json<CleanupResponse>, ApiError> {
     let Some(key) = state.anthropic_key.as_ref() else {
-        return Err(StatusCode::SERVICE_UNAVAILABLE);
+        return Err(StatusCode::SERVICE_UNAVAILABLE.into());
     };
-    if payload.entries.is_empty() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
+    let prompt = prompts::prompt(&state, prompts::CLEANUP).await?;
     let mut suggestions = Vec::new();
     for entry in payload.entries.iter().take(10) {
-        let translation_hint = entry
-            .translation
-            .as_ref()
-            .map(|value| value.as_str())
-            .unwrap_or("none");
-        let context = entry
-            .notes
-            .as_ref()
-            .map(|value| value.as_str())
-            .unwrap_or("no notes provided");
-        let system = "Return ONLY a raw JSON object with keys \"suggestion\" and \"notes\" only. Do NOT use markdown code blocks or formatting. Return pure JSON only. Keep the translation text CEFR B1-level, fully in English, and avoid repeating the Dutch input or wrapping it in parentheses. Focus on the most common uses and renderings rather than every rare meaning.";
-        let user = format!(
-            "Review the current translation for the Dutch word \"{word}\" ({language}) with the existing suggestion \"{translation}\". Context: {context}. Provide the most natural English phrasing, keeping articles/adverbs in their English positions (e.g., \"het woord\" → \"the word\", \"lopen\" → \"to walk\"), and optionally include a second very common rendering separated by a slash when the word clearly serves two primary roles. Keep the translation keys simple and note any nuance differences under \"notes\".",
-            word = entry.text,
-            language = entry.language,
-            translation = translation_hint,
-            context = context
-        );
-        let content = call_anthropic(&state, key, system, &user).await?;
-        let data: Value = serde_json::from_str(&content).map_err(|_| StatusCode::BAD_GATEWAY)?;
-        let suggestion = data
-            .get("suggestion")
-            .and_then(|value| value.as_str())
-            .unwrap_or("")
-            .to_string();
-        let notes = data
-            .get("notes")
-            .and_then(|value| value.as_str())
-            .map(|value| value.to_string());
+        let values = [
+            ("word", entry.text.as_str()),
+            ("language", entry.language.as_str()),
+            (
+                "translation",
+                entry.translation.as_deref().unwrap_or("none"),
+            ),
+            (
+                "context",
+                entry.notes.as_deref().unwrap_or("no notes provided"),
+            ),
+        ];
+        let reply: CleanupReply = ask_model(
+            &state,
+            key,
+            &prompt,
+            &prompt.system(&values),
+            &prompt.user(&values),
+        )
+        .await?;
         suggestions.push(CleanupSuggestion {
             word_id: entry.word_id.clone(),
             text: entry.text.clone(),
             language: entry.language.clone(),
             current_translation: entry.translation.clone(),
-            suggestion,
-            notes,
+            suggestion: reply.suggestion,
+            notes: reply.notes,
+            prompt_version: Some(prompt.label()),
         });
     }
     Ok(Json(CleanupResponse { suggestions }))
 }
 
+#[utoipa::path(
+    post,
+    path = "/ai/grade-sentence",
+    tag = "ai",
+    request_body = GradeSentenceRequest,
+    responses(
+        (status = 200, body = SentenceGrade),
+        (status = 422, body = ApiErrorBody, description = "The request body is missing fields or has invalid values"),
+        (status = 502, body = ApiErrorBody, description = "The model returned an unusable response"),
+        (status = 503, description = "ANTHROPIC_API_KEY is not configured")
+    )
+)]
 async fn grade_sentence(
     State(state): State<Arc<AppState>>,
-    Json(payload): Json<GradeSentenceRequest>,
-) -> Result<Json<Value>, StatusCode> {
+    headers: HeaderMap,
+    Valid(payload): Valid<GradeSentenceRequest>,
+) -> Result<Json<SentenceGrade>, ApiError> {
     let Some(key) = state.anthropic_key.as_ref() else {
-        return Err(StatusCode::SERVICE_UNAVAILABLE);
+        return Err(StatusCode::SERVICE_UNAVAILABLE.into());
     };
     let question_context = payload
         .question
@@ -454,37 +708,596 @@ async fn grade_sentence(
             )
         })
         .unwrap_or_default();
-    let system = "Return ONLY a raw JSON object with keys \"score\" (1-10), \"feedback\" (very short), and \"correction\" (a corrected sentence). Do NOT use markdown code blocks or formatting. Return pure JSON only. Focus on a CEFR B1-level evaluation.";
-    let user = format!(
-        "Evaluate the user's {language} sentence for correct use of the word \"{word}\". Sentence: \"{sentence}\".{question_context}{concept_context} Provide a B1-level score (1-10), describe the issue in a concise rubric, and, if needed, offer a B1-level corrected sentence as the \"correction\" value.",
-        language = payload.target_language,
-        word = payload.word,
-        sentence = payload.user_sentence,
-        question_context = question_context,
-        concept_context = concept_context
+    let prompt = match payload.source_sentence {
+        Some(_) => prompts::prompt(&state, prompts::GRADE_TRANSLATION).await?,
+        None => prompts::prompt(&state, prompts::GRADE_SENTENCE).await?,
+    };
+    let values = [
+        ("language", payload.target_language.as_str()),
+        ("word", payload.word.as_str()),
+        ("reference", payload.word.as_str()),
+        ("source", payload.source_sentence.as_deref().unwrap_or("")),
+        ("sentence", payload.user_sentence.as_str()),
+        ("question_context", question_context.as_str()),
+        ("concept_context", concept_context.as_str()),
+    ];
+    let mut grade: SentenceGrade = ask_model(
+        &state,
+        key,
+        &prompt,
+        &prompt.system(&values),
+        &prompt.user(&values),
+    )
+    .await?;
+    grade.prompt_version = Some(prompt.label());
+    if let Ok(authorization) = bearer_token(&headers) {
+        // History is a side record; a failed write does not cost the grade.
+        if let Err(status) = store_sentence_attempt(&state, authorization, &payload, &grade).await {
+            eprintln!("[sentence-history] not stored: {status}");
+        }
+    }
+    Ok(Json(grade))
+}
+
+/// Saves a graded attempt through the Data API, which stamps `user_id`.
+async fn store_sentence_attempt(
+    state: &AppState,
+    authorization: HeaderValue,
+    payload: &GradeSentenceRequest,
+    grade: &SentenceGrade,
+) -> Result<(), StatusCode> {
+    let (target, client) = data_api(state)?;
+    let attempt = SentenceAttempt {
+        id: uuid::Uuid::new_v4().to_string(),
+        word: payload.word.clone(),
+        target_language: payload.target_language.clone(),
+        user_sentence: payload.user_sentence.clone(),
+        source_sentence: payload.source_sentence.clone(),
+        score: grade.score,
+        rubric: grade.rubric,
+        feedback: grade.feedback.clone(),
+        correction: grade.correction.clone(),
+        prompt_version: grade.prompt_version.clone(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+    let resp = client
+        .post(format!(
+            "{}/sentence_attempts",
+            target.trim_end_matches('/')
+        ))
+        .header("authorization", authorization)
+        .json(&attempt)
+        .send()
+        .await
+        .map_err(|err| {
+            eprintln!("[sentence-history] upstream error: {err}");
+            StatusCode::BAD_GATEWAY
+        })?;
+    if !resp.status().is_success() {
+        eprintln!("[sentence-history] upstream status={}", resp.status());
+        return Err(upstream_status(resp.status()));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+struct SentenceHistoryQuery {
+    /// Only attempts graded at or after this RFC 3339 time.
+    since: Option<String>,
+}
+
+/// The caller's graded sentence attempts, newest first, with per-month
+/// averages for tracking production skills over time.
+#[utoipa::path(
+    get,
+    path = "/data/sentence-history",
+    tag = "data",
+    security(("bearer" = []), ("api_key" = [])),
+    params(SentenceHistoryQuery),
+    responses(
+        (status = 200, body = SentenceHistory),
+        (status = 400, description = "`since` is not an RFC 3339 time"),
+        (status = 401, description = "Missing or rejected bearer token or API key")
+    )
+)]
+async fn sentence_history(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    caller: Option<Extension<ApiKeyCaller>>,
+    Query(query): Query<SentenceHistoryQuery>,
+) -> Result<Json<SentenceHistory>, StatusCode> {
+    let since = query
+        .since
+        .as_deref()
+        .map(|since| {
+            chrono::DateTime::parse_from_rfc3339(since)
+                .map(|since| since.with_timezone(&chrono::Utc).to_rfc3339())
+                .map_err(|_| StatusCode::BAD_REQUEST)
+        })
+        .transpose()?;
+    let attempts = if let Some(Extension(caller)) = caller {
+        let db = database(&state).await?;
+        let rows = db
+            .query(
+                "SELECT id, word, target_language, user_sentence, source_sentence, score,
+                        word_usage, grammar, spelling, naturalness, feedback, correction,
+                        created_at, prompt_version
+                 FROM sentence_attempts
+                 WHERE user_id = $1 AND ($2::text IS NULL OR created_at >= $2)
+                 ORDER BY created_at DESC",
+                &[&caller.user_id, &since],
+            )
+            .await
+            .map_err(|err| {
+                eprintln!("[sentence-history] database error: {err}");
+                StatusCode::BAD_GATEWAY
+            })?;
+        let score = |row: &tokio_postgres::Row, idx: usize| row.get::<_, i16>(idx) as u8;
+        rows.iter()
+            .map(|row| SentenceAttempt {
+                id: row.get(0),
+                word: row.get(1),
+                target_language: row.get(2),
+                user_sentence: row.get(3),
+                source_sentence: row.get(4),
+                score: score(row, 5),
+                rubric: SentenceRubric {
+                    word_usage: score(row, 6),
+                    grammar: score(row, 7),
+                    spelling: score(row, 8),
+                    naturalness: score(row, 9),
+                },
+                feedback: row.get(10),
+                correction: row.get(11),
+                created_at: row.get(12),
+                prompt_version: row.get(13),
+            })
+            .collect()
+    } else {
+        let (target, client) = data_api(&state)?;
+        let authorization = bearer_token(&headers)?;
+        let mut request = client
+            .get(format!(
+                "{}/sentence_attempts",
+                target.trim_end_matches('/')
+            ))
+            .query(&[("select", "*"), ("order", "created_at.desc")]);
+        if let Some(since) = &since {
+            request = request.query(&[("created_at", format!("gte.{since}"))]);
+        }
+        let resp = request
+            .header("authorization", authorization)
+            .header("accept", "application/json")
+            .send()
+            .await
+            .map_err(|err| {
+                eprintln!("[sentence-history] upstream error: {err}");
+                StatusCode::BAD_GATEWAY
+            })?;
+        if !resp.status().is_success() {
+            eprintln!("[sentence-history] upstream status={}", resp.status());
+            return Err(upstream_status(resp.status()));
+        }
+        resp.json::<Vec<SentenceAttempt>>()
+            .await
+            .map_err(|_| StatusCode::BAD_GATEWAY)?
+    };
+    let months = monthly_sentence_averages(&attempts);
+    Ok(Json(SentenceHistory { attempts, months }))
+}
+
+/// Groups attempts by the `YYYY-MM` prefix of their UTC timestamp.
+fn monthly_sentence_averages(attempts: &[SentenceAttempt]) -> Vec<SentenceMonth> {
+    let mut by_month: BTreeMap<&str, Vec<&SentenceAttempt>> = BTreeMap::new();
+    for attempt in attempts {
+        if let Some(month) = attempt.created_at.get(..7) {
+            by_month.entry(month).or_default().push(attempt);
+        }
+    }
+    by_month
+        .into_iter()
+        .map(|(month, attempts)| {
+            let average = |value: fn(&SentenceAttempt) -> u8| {
+                attempts.iter().map(|a| f64::from(value(a))).sum::<f64>() / attempts.len() as f64
+            };
+            SentenceMonth {
+                month: month.to_string(),
+                attempts: attempts.len(),
+                score: average(|a| a.score),
+                word_usage: average(|a| a.rubric.word_usage),
+                grammar: average(|a| a.rubric.grammar),
+                spelling: average(|a| a.rubric.spelling),
+                naturalness: average(|a| a.rubric.naturalness),
+            }
+        })
+        .collect()
+}
+
+#[utoipa::path(
+    get,
+    path = "/data/settings",
+    tag = "data",
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, body = SettingsPayload),
+        (status = 401, description = "Missing or rejected bearer token or API key")
+    )
+)]
+async fn get_settings(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    caller: Option<Extension<ApiKeyCaller>>,
+) -> Result<Json<SettingsPayload>, StatusCode> {
+    if let Some(Extension(caller)) = caller {
+        let db = database(&state).await?;
+        let rows = db
+            .query(
+                "SELECT key, value::text, updated_at FROM settings WHERE user_id = $1 ORDER BY key",
+                &[&caller.user_id],
+            )
+            .await
+            .map_err(|err| {
+                eprintln!("[settings] database error: {err}");
+                StatusCode::BAD_GATEWAY
+            })?;
+        let settings = rows
+            .iter()
+            .map(|row| SettingEntry {
+                key: row.get(0),
+                value: serde_json::from_str(row.get(1)).unwrap_or(Value::Null),
+                updated_at: row.get(2),
+            })
+            .collect();
+        return Ok(Json(SettingsPayload { settings }));
+    }
+    let (target, client) = data_api(&state)?;
+    let authorization = bearer_token(&headers)?;
+    let url = format!(
+        "{}/settings?select=key,value,updated_at&order=key.asc",
+        target.trim_end_matches('/')
     );
-    let content = call_anthropic(&state, key, system, &user).await?;
-    let data: Value = serde_json::from_str(&content).map_err(|_| StatusCode::BAD_GATEWAY)?;
-    Ok(Json(data))
+    let resp = client
+        .get(url)
+        .header("authorization", authorization)
+        .header("accept", "application/json")
+        .send()
+        .await
+        .map_err(|err| {
+            eprintln!("[settings] upstream error: {err}");
+            StatusCode::BAD_GATEWAY
+        })?;
+    if !resp.status().is_success() {
+        eprintln!("[settings] upstream status={}", resp.status());
+        return Err(upstream_status(resp.status()));
+    }
+    let settings = resp
+        .json::<Vec<SettingEntry>>()
+        .await
+        .map_err(|_| StatusCode::BAD_GATEWAY)?;
+    Ok(Json(SettingsPayload { settings }))
 }
 
-async fn call_anthropic(
+/// Upserts the caller's settings as given. Conflict resolution is left to the
+/// client, which merges with what it pulled and sends back every setting it
+/// syncs, not only the ones it changed.
+#[utoipa::path(
+    put,
+    path = "/data/settings",
+    tag = "data",
+    security(("bearer" = [])),
+    request_body = SettingsPayload,
+    responses(
+        (status = 200, body = SettingsPayload),
+        (status = 400, description = "A key is empty or longer than 64 characters"),
+        (status = 401, description = "Missing or rejected bearer token")
+    )
+)]
+async fn put_settings(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(payload): Json<SettingsPayload>,
+) -> Result<Json<SettingsPayload>, StatusCode> {
+    let (target, client) = data_api(&state)?;
+    let authorization = bearer_token(&headers)?;
+    if payload
+        .settings
+        .iter()
+        .any(|entry| entry.key.trim().is_empty() || entry.key.len() > 64)
+    {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if payload.settings.is_empty() {
+        return Ok(Json(payload));
+    }
+    let url = format!(
+        "{}/settings?on_conflict=user_id,key",
+        target.trim_end_matches('/')
+    );
+    let resp = client
+        .post(url)
+        .header("authorization", authorization.clone())
+        .header("accept", "application/json")
+        .header(
+            "prefer",
+            "resolution=merge-duplicates,return=representation",
+        )
+        .json(&payload.settings)
+        .send()
+        .await
+        .map_err(|err| {
+            eprintln!("[settings] upstream error: {err}");
+            StatusCode::BAD_GATEWAY
+        })?;
+    if !resp.status().is_success() {
+        eprintln!("[settings] upstream status={}", resp.status());
+        return Err(upstream_status(resp.status()));
+    }
+    let settings = resp
+        .json::<Vec<SettingEntry>>()
+        .await
+        .map_err(|_| StatusCode::BAD_GATEWAY)?;
+    state.events.publish_for_token(
+        &authorization,
+        ServerEvent::SyncRecommended {
+            reason: "settings".to_string(),
+        },
+    );
+    Ok(Json(SettingsPayload { settings }))
+}
+
+/// Saves a word and a new card due now. API-key callers need a `capture`
+/// key; signed-in callers go through the Data API like the GUI does.
+#[utoipa::path(
+    post,
+    path = "/data/capture",
+    tag = "data",
+    security(("bearer" = []), ("api_key" = [])),
+    request_body = CaptureRequest,
+    responses(
+        (status = 200, body = CaptureResponse),
+        (status = 401, description = "Missing or rejected bearer token or API key"),
+        (status = 403, description = "The API key is not a capture key"),
+        (status = 422, body = ApiErrorBody, description = "Missing text or an over-long field")
+    )
+)]
+async fn capture_word(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    caller: Option<Extension<ApiKeyCaller>>,
+    Valid(payload): Valid<CaptureRequest>,
+) -> Result<Json<CaptureResponse>, ApiError> {
+    let now = chrono::Utc::now();
+    let word_id = uuid::Uuid::new_v4();
+    let card = le_core::default_new_card(word_id, now);
+    let text = payload.text.trim();
+    let created_at = now.to_rfc3339();
+    let kind = format!("{:?}", le_core::EntryKind::detect(text));
+    let source_type = format!("{:?}", le_core::SourceKind::Web);
+    let word = json!({
+        "id": word_id.to_string(),
+        "text": text,
+        "language": le_core::Language::dutch().to_column(),
+        "translation": payload.translation,
+        "chapter": payload.chapter,
+        "notes": payload.notes,
+        "created_at": created_at,
+        "source_type": source_type,
+        "source_name": payload.source_name,
+        "source_page": payload.source_page,
+        "captured_at": created_at,
+        "kind": kind,
+    });
+    let card_row = json!({
+        "id": card.id.to_string(),
+        "word_id": word_id.to_string(),
+        "due_at": card.due_at.to_rfc3339(),
+        "interval_days": card.interval_days,
+        "ease": card.ease,
+        "reps": card.reps,
+        "lapses": card.lapses,
+    });
+
+    let event = ServerEvent::CaptureReceived {
+        word_id: word_id.to_string(),
+    };
+    if let Some(Extension(caller)) = caller {
+        let db = database(&state).await?;
+        // One statement so a failed card insert never leaves an orphan word.
+        db.execute(
+            "WITH word AS (
+                 INSERT INTO words (id, text, language, translation, chapter, notes, created_at,
+                                    source_type, source_name, source_page, captured_at, kind)
+                 SELECT * FROM json_to_record($1::json) AS w(
+                     id TEXT, text TEXT, language TEXT, translation TEXT, chapter TEXT,
+                     notes TEXT, created_at TEXT, source_type TEXT, source_name TEXT,
+                     source_page TEXT, captured_at TEXT, kind TEXT)
+                 RETURNING id
+             )
+             INSERT INTO cards (id, word_id, due_at, interval_days, ease, reps, lapses)
+             SELECT c.id, word.id, c.due_at, c.interval_days, c.ease, c.reps, c.lapses
+             FROM word, json_to_record($2::json) AS c(
+                 id TEXT, due_at TEXT, interval_days INT, ease DOUBLE PRECISION,
+                 reps INT, lapses INT)",
+            &[&word.to_string(), &card_row.to_string()],
+        )
+        .await
+        .map_err(|err| {
+            eprintln!("[capture] database error: {err}");
+            StatusCode::BAD_GATEWAY
+        })?;
+        state.events.publish(&caller.user_id, event);
+    } else {
+        let (target, client) = data_api(&state)?;
+        let authorization = bearer_token(&headers)?;
+        for (table, row) in [("words", &word), ("cards", &card_row)] {
+            let resp = client
+                .post(format!("{}/{table}", target.trim_end_matches('/')))
+                .header("authorization", authorization.clone())
+                .header("prefer", "return=minimal")
+                .json(row)
+                .send()
+                .await
+                .map_err(|err| {
+                    eprintln!("[capture] upstream error: {err}");
+                    StatusCode::BAD_GATEWAY
+                })?;
+            if !resp.status().is_success() {
+                eprintln!("[capture] upstream status={} table={table}", resp.status());
+                return Err(upstream_status(resp.status()).into());
+            }
+        }
+        state.events.publish_for_token(&authorization, event);
+    }
+    println!("[capture] saved word_id={word_id}");
+    Ok(Json(CaptureResponse {
+        word_id: word_id.to_string(),
+        card_id: card.id.to_string(),
+    }))
+}
+
+async fn connect_database(url: &str) -> Option<deadpool_postgres::Pool> {
+    let config: tokio_postgres::Config = url
+        .parse()
+        .map_err(|err| eprintln!("[db] invalid DATABASE_URL, API keys disabled: {err}"))
+        .ok()?;
+    let connector = native_tls::TlsConnector::new()
+        .map_err(|err| eprintln!("[db] TLS setup failed: {err}"))
+        .ok()?;
+    let manager = deadpool_postgres::Manager::from_config(
+        config,
+        postgres_native_tls::MakeTlsConnector::new(connector),
+        deadpool_postgres::ManagerConfig {
+            recycling_method: deadpool_postgres::RecyclingMethod::Fast,
+        },
+    );
+    let pool = deadpool_postgres::Pool::builder(manager)
+        .max_size(8)
+        .build()
+        .map_err(|err| eprintln!("[db] pool setup failed: {err}"))
+        .ok()?;
+    // A database that is down at startup is retried on each request.
+    if let Err(err) = pool.get().await {
+        eprintln!("[db] connect failed, will retry: {err}");
+    }
+    Some(pool)
+}
+
+/// A pooled `DATABASE_URL` connection: 503 when it is not configured, 502
+/// when no connection can be made.
+async fn database(state: &AppState) -> Result<deadpool_postgres::Object, StatusCode> {
+    let pool = state.db.as_ref().ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+    pool.get().await.map_err(|err| {
+        eprintln!("[db] connect failed: {err}");
+        StatusCode::BAD_GATEWAY
+    })
+}
+
+fn data_api(state: &AppState) -> Result<(&str, &reqwest::Client), StatusCode> {
+    match (state.proxy_target.as_deref(), state.proxy_client.as_ref()) {
+        (Some(target), Some(client)) => Ok((target, client)),
+        _ => Err(StatusCode::SERVICE_UNAVAILABLE),
+    }
+}
+
+fn bearer_token(headers: &HeaderMap) -> Result<HeaderValue, StatusCode> {
+    headers
+        .get("authorization")
+        .filter(|value| {
+            value
+                .to_str()
+                .map(|value| value.starts_with("Bearer "))
+                .unwrap_or(false)
+        })
+        .cloned()
+        .ok_or(StatusCode::UNAUTHORIZED)
+}
+
+fn upstream_status(status: reqwest::StatusCode) -> StatusCode {
+    match status.as_u16() {
+        401 | 403 => StatusCode::UNAUTHORIZED,
+        _ => StatusCode::BAD_GATEWAY,
+    }
+}
+
+/// Asks the model for a `T`, forcing it to answer with `T`'s tool so the
+/// reply follows its schema. A reply that still fails to parse or validate
+/// goes back to the model as the tool's error result, with every problem
+/// listed, and the model is asked again, up to [`REPLY_ATTEMPTS`] calls.
+async fn ask_model<T: ModelReply>(
     state: &AppState,
     key: &str,
+    prompt: &prompts::Prompt,
     system: &str,
     user: &str,
-) -> Result<String, StatusCode> {
+) -> Result<T, ApiError> {
+    let tool = json!({
+        "name": T::TOOL,
+        "description": T::DESCRIPTION,
+        "input_schema": T::input_schema(),
+    });
+    let mut messages = vec![AnthropicMessage {
+        role: "user".to_string(),
+        content: Value::String(user.to_string()),
+    }];
+    let mut problems = String::new();
+    for _ in 0..REPLY_ATTEMPTS {
+        let tool_use = call_anthropic(state, key, prompt, system, &messages, &tool).await?;
+        match model_reply::<T>(&tool_use.input) {
+            Ok(reply) => return Ok(reply),
+            Err(message) => {
+                eprintln!("[anthropic] asking for a corrected {}: {message}", T::TOOL);
+                messages.push(AnthropicMessage {
+                    role: "assistant".to_string(),
+                    content: json!([{
+                        "type": "tool_use",
+                        "id": tool_use.id,
+                        "name": T::TOOL,
+                        "input": tool_use.input,
+                    }]),
+                });
+                messages.push(AnthropicMessage {
+                    role: "user".to_string(),
+                    content: json!([{
+                        "type": "tool_result",
+                        "tool_use_id": tool_use.id,
+                        "is_error": true,
+                        "content": format!(
+                            "Rejected: {message}. Call {} again with these fields fixed.",
+                            T::TOOL
+                        ),
+                    }]),
+                });
+                problems = message;
+            }
+        }
+    }
+    Err(ApiError::Model(format!(
+        "still invalid after {REPLY_ATTEMPTS} attempts: {problems}"
+    )))
+}
+
+async fn call_anthropic(
+    state: &AppState,
+    key: &str,
+    prompt: &prompts::Prompt,
+    system: &str,
+    messages: &[AnthropicMessage],
+    tool: &Value,
+) -> Result<ToolUse, StatusCode> {
     let req = AnthropicRequest {
         model: state.anthropic_model.clone(),
         max_tokens: 1024,
-        messages: vec![AnthropicMessage {
-            role: "user".to_string(),
-            content: user.to_string(),
-        }],
+        messages: messages.to_vec(),
         temperature: 0.7,
         system: Some(system.to_string()),
+        tools: vec![tool.clone()],
+        tool_choice: json!({ "type": "tool", "name": tool["name"] }),
     };
-    eprintln!("[anthropic] calling model={}", state.anthropic_model);
+    eprintln!(
+        "[anthropic] calling model={} prompt={}",
+        state.anthropic_model,
+        prompt.label()
+    );
     let request_json = serde_json::to_string(&req).unwrap_or_default();
     eprintln!("[anthropic] request body: {}", request_json);
 
@@ -524,28 +1337,26 @@ async fn call_anthropic(
         StatusCode::BAD_GATEWAY
     })?;
 
-    let content = data
+    let block = data
         .get("content")
-        .and_then(|content| content.get(0))
-        .and_then(|block| block.get("text"))
-        .and_then(|value| value.as_str())
+        .and_then(|content| content.as_array())
+        .and_then(|blocks| {
+            blocks
+                .iter()
+                .find(|block| block.get("type").and_then(Value::as_str) == Some("tool_use"))
+        })
         .ok_or_else(|| {
-            eprintln!("[anthropic] failed to extract text from response");
+            eprintln!("[anthropic] no tool call in response");
             StatusCode::BAD_GATEWAY
         })?;
-
-    // Strip markdown code blocks if present (Claude sometimes wraps JSON in ```json ... ```)
-    let cleaned_content = content
-        .trim()
-        .strip_prefix("```json")
-        .or_else(|| content.trim().strip_prefix("```"))
-        .unwrap_or(content.trim())
-        .strip_suffix("```")
-        .unwrap_or(content.trim())
-        .trim();
-
-    eprintln!("[anthropic] cleaned content: {}", cleaned_content);
-    Ok(cleaned_content.to_string())
+    Ok(ToolUse {
+        id: block
+            .get("id")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        input: block.get("input").cloned().unwrap_or(Value::Null),
+    })
 }
 
 fn join_url(base: &str, path: &str) -> String {
@@ -568,6 +1379,9 @@ async fn proxy_request(
     State(state): State<Arc<AppState>>,
     req: Request<Body>,
 ) -> Result<Response, StatusCode> {
+    if req.extensions().get::<ApiKeyCaller>().is_some() {
+        return api_keys::read_table(&state, req.uri()).await;
+    }
     let Some(proxy_target) = state.proxy_target.as_ref() else {
         return Err(StatusCode::NOT_FOUND);
     };
@@ -587,6 +1401,18 @@ async fn proxy_request(
         .await
         .map_err(|_| StatusCode::BAD_REQUEST)?;
 
+    let deck_write = method != http::Method::GET
+        && method != http::Method::HEAD
+        && [
+            "/words",
+            "/cards",
+            "/reviews",
+            "/concepts",
+            "/tags",
+            "/word_tags",
+        ]
+        .iter()
+        .any(|table| uri.path().starts_with(table));
     let mut builder = proxy_client.request(method, target);
     builder = builder.headers(filter_proxy_headers(&headers));
     let resp = builder.body(body_bytes).send().await.map_err(|err| {
@@ -595,6 +1421,14 @@ async fn proxy_request(
     })?;
 
     let status = resp.status();
+    if deck_write
+        && status.is_success()
+        && let Ok(authorization) = bearer_token(&headers)
+    {
+        state
+            .events
+            .publish_for_token(&authorization, ServerEvent::DeckUpdated);
+    }
     let resp_headers = resp.headers().clone();
     let resp_body = resp.bytes().await.map_err(|_| StatusCode::BAD_GATEWAY)?;
 
@@ -0,0 +1,188 @@
+//! AI prompts, editable without a redeploy. Each handler asks for its
+//! prompt by name: the highest `version` of that name in the `ai_prompts`
+//! table (read over `DATABASE_URL`) wins, and the text built in below is
+//! version 0, used for names without a row and while the database is unset.
+//! Rows are re-read at most every [`CACHE_SECONDS`], so an edited prompt
+//! reaches the handlers within a minute. Templates fill `{name}`
+//! placeholders from the handler's values; unknown ones are left as they
+//! are. Every response and stored result carries the prompt's
+//! [`Prompt::label`], so a drop in quality can be traced to the edit behind it.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use axum::http::StatusCode;
+
+use crate::{AppState, database};
+
+pub const GENERATE_SENTENCE: &str = "generate_sentence";
+pub const GENERATE_QUESTION: &str = "generate_question";
+pub const CLEANUP: &str = "cleanup";
+/// Grading a sentence written with a given word.
+pub const GRADE_SENTENCE: &str = "grade_sentence";
+/// Grading a translation of a sentence card.
+pub const GRADE_TRANSLATION: &str = "grade_translation";
+
+/// How long prompts read from the database are reused.
+const CACHE_SECONDS: u64 = 60;
+
+const GRADE_SYSTEM: &str = "Return ONLY a raw JSON object with keys \"score\" (1-10), \"feedback\" (very short), and \"correction\" (a corrected sentence), and \"rubric\", an object with integer keys \"word_usage\", \"grammar\", \"spelling\", and \"naturalness\" (each 1-10). Do NOT use markdown code blocks or formatting. Return pure JSON only. Focus on a CEFR B1-level evaluation.";
+
+/// Name, system prompt, and user prompt of every version 0 prompt.
+const BUILT_IN: [(&str, &str, &str); 5] = [
+    (
+        GENERATE_SENTENCE,
+        "Return ONLY a raw JSON object with keys \"sentence\" and \"translation\". Do NOT use markdown code blocks or formatting. Return pure JSON only. Both the sentence and translation should read like a CEFR {level}-level example. The sentence must have at most {max_words} words.",
+        "Create a natural {source} sentence of at most {max_words} words using the word \"{word}\" at CEFR {level} level, with vocabulary and grammar a {level} learner knows. Provide its {target} translation written at the same level. Translation hint: {hint}.{concept_note}",
+    ),
+    (
+        GENERATE_QUESTION,
+        "Return ONLY a raw JSON object with key \"question\". Do NOT use markdown code blocks or formatting. Return pure JSON only. Compose the question in Dutch at CEFR B1 level and ensure it clearly asks the learner to respond with a sentence that uses the provided word and, when available, the highlighted concept.",
+        "Using the word \"{word}\" ({source}), craft a Dutch CEFR B1 question that mentions both the word and the concept, then ask the learner to reply with a Dutch sentence featuring them. {concept_note} Respond only with the question itself.",
+    ),
+    (
+        CLEANUP,
+        "Return ONLY a raw JSON object with keys \"suggestion\" and \"notes\" only. Do NOT use markdown code blocks or formatting. Return pure JSON only. Keep the translation text CEFR B1-level, fully in English, and avoid repeating the Dutch input or wrapping it in parentheses. Focus on the most common uses and renderings rather than every rare meaning.",
+        "Review the current translation for the Dutch word \"{word}\" ({language}) with the existing suggestion \"{translation}\". Context: {context}. Provide the most natural English phrasing, keeping articles/adverbs in their English positions (e.g., \"het woord\" → \"the word\", \"lopen\" → \"to walk\"), and optionally include a second very common rendering separated by a slash when the word clearly serves two primary roles. Keep the translation keys simple and note any nuance differences under \"notes\".",
+    ),
+    (
+        GRADE_SENTENCE,
+        GRADE_SYSTEM,
+        "Evaluate the user's {language} sentence for correct use of the word \"{word}\". Sentence: \"{sentence}\".{question_context}{concept_context} Provide a B1-level score (1-10), describe the issue in a concise rubric, and, if needed, offer a B1-level corrected sentence as the \"correction\" value.",
+    ),
+    (
+        GRADE_TRANSLATION,
+        GRADE_SYSTEM,
+        "Evaluate the user's {language} translation of \"{source}\". Translation: \"{sentence}\". A reference translation is \"{reference}\"; other faithful, natural translations are equally correct.{concept_context} Provide a B1-level score (1-10), describe the issue in a concise rubric, and, if needed, offer a corrected translation as the \"correction\" value.",
+    ),
+];
+
+#[derive(Debug, Clone)]
+pub struct Prompt {
+    name: String,
+    version: i32,
+    system: String,
+    user: String,
+}
+
+impl Prompt {
+    /// `name@version`, as recorded next to the results the prompt produced.
+    pub fn label(&self) -> String {
+        format!("{}@{}", self.name, self.version)
+    }
+
+    pub fn system(&self, values: &[(&str, &str)]) -> String {
+        render(&self.system, values)
+    }
+
+    pub fn user(&self, values: &[(&str, &str)]) -> String {
+        render(&self.user, values)
+    }
+}
+
+/// Prompts read from the database and when; part of [`AppState`].
+#[derive(Debug, Default)]
+pub struct PromptCache {
+    loaded: Mutex<Option<(Instant, HashMap<String, Prompt>)>>,
+}
+
+/// The current version of prompt `name`. A name that is neither stored nor
+/// built in is a bug in the caller and fails with 500.
+pub async fn prompt(state: &AppState, name: &str) -> Result<Prompt, StatusCode> {
+    let cached = state.prompts.loaded.lock().ok().and_then(|loaded| {
+        loaded
+            .as_ref()
+            .filter(|(at, _)| at.elapsed() < Duration::from_secs(CACHE_SECONDS))
+            .map(|(_, prompts)| prompts.get(name).cloned())
+    });
+    let stored = match cached {
+        Some(stored) => stored,
+        None => {
+            let prompts = load(state).await;
+            let stored = prompts.get(name).cloned();
+            if let Ok(mut loaded) = state.prompts.loaded.lock() {
+                *loaded = Some((Instant::now(), prompts));
+            }
+            stored
+        }
+    };
+    stored.or_else(|| built_in(name)).ok_or_else(|| {
+        eprintln!("[prompts] no prompt named {name}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+/// The newest version of each prompt in `ai_prompts`. A failed read is
+/// logged and leaves the built-in prompts in use until the next attempt.
+async fn load(state: &AppState) -> HashMap<String, Prompt> {
+    let Ok(db) = database(state).await else {
+        return HashMap::new();
+    };
+    let rows = match db
+        .query(
+            "SELECT DISTINCT ON (name) name, version, system, user_template
+             FROM ai_prompts
+             ORDER BY name, version DESC",
+            &[],
+        )
+        .await
+    {
+        Ok(rows) => rows,
+        Err(err) => {
+            eprintln!("[prompts] load failed: {err}");
+            return HashMap::new();
+        }
+    };
+    rows.iter()
+        .map(|row| {
+            let prompt = Prompt {
+                name: row.get(0),
+                version: row.get(1),
+                system: row.get(2),
+                user: row.get(3),
+            };
+            (prompt.name.clone(), prompt)
+        })
+        .collect()
+}
+
+fn built_in(name: &str) -> Option<Prompt> {
+    let (name, system, user) = BUILT_IN.iter().find(|(built_in, _, _)| *built_in == name)?;
+    Some(Prompt {
+        name: name.to_string(),
+        version: 0,
+        system: system.to_string(),
+        user: user.to_string(),
+    })
+}
+
+/// Replaces each `{name}` in `template` with its value, in one pass so
+/// values are never expanded themselves.
+fn render(template: &str, values: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let value = after.find('}').and_then(|close| {
+            let key = &after[..close];
+            values
+                .iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, value)| (*value, close))
+        });
+        match value {
+            Some((value, close)) => {
+                out.push_str(value);
+                rest = &after[close + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
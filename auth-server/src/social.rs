@@ -0,0 +1,212 @@
+//! Opt-in weekly leaderboard. Reviews are not stored per user on the server,
+//! so members report their own weekly review count and streak; joining,
+//! updating, and leaving go through the Data API under the member's JWT.
+//! Ranking reads every member's row, which row-level security would hide,
+//! so the board itself is read over `DATABASE_URL` once the caller's own
+//! membership has been confirmed.
+
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::State,
+    http::{HeaderMap, HeaderValue, StatusCode},
+};
+use chrono::{Datelike, Duration, Utc};
+use le_client::{ApiErrorBody, LeaderboardEntry, LeaderboardStats, WeeklyLeaderboard};
+use serde::Deserialize;
+
+use crate::validation::{ApiError, Valid};
+use crate::{AppState, bearer_token, data_api, database, upstream_status};
+
+/// Entries returned; the caller is appended when ranked below this.
+const BOARD_SIZE: i64 = 50;
+
+#[derive(Debug, Deserialize)]
+struct Membership {
+    user_id: String,
+}
+
+/// Monday of the current UTC week.
+fn week_start() -> String {
+    let today = Utc::now().date_naive();
+    let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    monday.format("%Y-%m-%d").to_string()
+}
+
+/// Joins the leaderboard or replaces the caller's numbers for this week.
+/// The counts are self-reported by the client and stored as sent; the
+/// server only checks that they are not negative.
+#[utoipa::path(
+    put,
+    path = "/social/leaderboard/me",
+    tag = "social",
+    security(("bearer" = [])),
+    request_body = LeaderboardStats,
+    responses(
+        (status = 200, body = LeaderboardStats),
+        (status = 401, description = "Missing or rejected bearer token"),
+        (status = 422, body = ApiErrorBody, description = "Missing display name or negative counts")
+    )
+)]
+pub async fn join_leaderboard(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Valid(payload): Valid<LeaderboardStats>,
+) -> Result<Json<LeaderboardStats>, ApiError> {
+    let (target, client) = data_api(&state)?;
+    let authorization = bearer_token(&headers)?;
+    let stats = LeaderboardStats {
+        display_name: payload.display_name.trim().to_string(),
+        ..payload
+    };
+    let row = serde_json::json!({
+        "display_name": stats.display_name,
+        "week_start": week_start(),
+        "reviews": stats.reviews,
+        "streak_days": stats.streak_days,
+        "updated_at": Utc::now().to_rfc3339(),
+    });
+    let resp = client
+        .post(format!(
+            "{}/leaderboard_members?on_conflict=user_id",
+            target.trim_end_matches('/')
+        ))
+        .header("authorization", authorization)
+        .header("accept", "application/json")
+        .header("prefer", "resolution=merge-duplicates,return=minimal")
+        .json(&row)
+        .send()
+        .await
+        .map_err(|err| {
+            eprintln!("[social] upstream error: {err}");
+            StatusCode::BAD_GATEWAY
+        })?;
+    if !resp.status().is_success() {
+        eprintln!("[social] upstream status={}", resp.status());
+        return Err(upstream_status(resp.status()).into());
+    }
+    Ok(Json(stats))
+}
+
+/// Leaves the leaderboard. The caller's row is deleted, not hidden.
+#[utoipa::path(
+    delete,
+    path = "/social/leaderboard/me",
+    tag = "social",
+    security(("bearer" = [])),
+    responses(
+        (status = 204, description = "Left the leaderboard, or was not a member"),
+        (status = 401, description = "Missing or rejected bearer token")
+    )
+)]
+pub async fn leave_leaderboard(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<StatusCode, StatusCode> {
+    let (target, client) = data_api(&state)?;
+    let authorization = bearer_token(&headers)?;
+    // Row-level security limits the delete to the caller's own row.
+    let resp = client
+        .delete(format!(
+            "{}/leaderboard_members?user_id=not.is.null",
+            target.trim_end_matches('/')
+        ))
+        .header("authorization", authorization)
+        .send()
+        .await
+        .map_err(|err| {
+            eprintln!("[social] upstream error: {err}");
+            StatusCode::BAD_GATEWAY
+        })?;
+    if !resp.status().is_success() {
+        eprintln!("[social] upstream status={}", resp.status());
+        return Err(upstream_status(resp.status()));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Members ranked by reviews this week, then by streak. Only members can
+/// see the board; members who have not reported this week are left out.
+/// Every count is what the member's client reported, not verified by the
+/// server.
+#[utoipa::path(
+    get,
+    path = "/social/leaderboard/weekly",
+    tag = "social",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, body = WeeklyLeaderboard),
+        (status = 401, description = "Missing or rejected bearer token"),
+        (status = 403, description = "The caller has not joined the leaderboard")
+    )
+)]
+pub async fn weekly_leaderboard(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<WeeklyLeaderboard>, StatusCode> {
+    let authorization = bearer_token(&headers)?;
+    let user_id = membership(&state, authorization).await?;
+    let db = database(&state).await?;
+    let week_start = week_start();
+    let rows = db
+        .query(
+            "SELECT user_id, display_name, reviews, streak_days,
+                    RANK() OVER (ORDER BY reviews DESC, streak_days DESC) AS rank
+             FROM leaderboard_members
+             WHERE week_start = $1
+             ORDER BY rank, display_name",
+            &[&week_start],
+        )
+        .await
+        .map_err(|err| {
+            eprintln!("[social] leaderboard query failed: {err}");
+            StatusCode::BAD_GATEWAY
+        })?;
+    let entries = rows
+        .iter()
+        .enumerate()
+        .filter(|(index, row)| *index < BOARD_SIZE as usize || row.get::<_, String>(0) == user_id)
+        .map(|(_, row)| LeaderboardEntry {
+            rank: row.get::<_, i64>(4) as u32,
+            display_name: row.get(1),
+            reviews: row.get(2),
+            streak_days: row.get(3),
+            you: row.get::<_, String>(0) == user_id,
+        })
+        .collect();
+    Ok(Json(WeeklyLeaderboard {
+        week_start,
+        entries,
+    }))
+}
+
+/// The caller's user id, read through the Data API so the JWT is checked
+/// and only a member's own row can come back.
+async fn membership(state: &AppState, authorization: HeaderValue) -> Result<String, StatusCode> {
+    let (target, client) = data_api(state)?;
+    let resp = client
+        .get(format!(
+            "{}/leaderboard_members?select=user_id",
+            target.trim_end_matches('/')
+        ))
+        .header("authorization", authorization)
+        .header("accept", "application/json")
+        .send()
+        .await
+        .map_err(|err| {
+            eprintln!("[social] upstream error: {err}");
+            StatusCode::BAD_GATEWAY
+        })?;
+    if !resp.status().is_success() {
+        eprintln!("[social] upstream status={}", resp.status());
+        return Err(upstream_status(resp.status()));
+    }
+    resp.json::<Vec<Membership>>()
+        .await
+        .map_err(|_| StatusCode::BAD_GATEWAY)?
+        .into_iter()
+        .next()
+        .map(|member| member.user_id)
+        .ok_or(StatusCode::FORBIDDEN)
+}
@@ -0,0 +1,256 @@
+//! Text to speech for `/ai/tts`. Providers are tried in order until one
+//! returns audio: OpenAI's speech API when `OPENAI_API_KEY` is set (MP3),
+//! then the host's own synthesizer, `say` on macOS and `espeak` elsewhere
+//! (WAV). The response's `Content-Type` says which format came back, and
+//! `x-tts-provider` which provider made it.
+
+use std::path::Path;
+use std::process::Command;
+use std::sync::Arc;
+
+use axum::{
+    extract::State,
+    http::{HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use le_client::TtsRequest;
+use le_core::Language;
+use serde::Serialize;
+
+use crate::AppState;
+use crate::validation::{ApiError, Valid};
+
+const OPENAI_SPEECH_URL: &str = "https://api.openai.com/v1/audio/speech";
+const DEFAULT_OPENAI_MODEL: &str = "gpt-4o-mini-tts";
+const DEFAULT_OPENAI_VOICE: &str = "alloy";
+/// Speaking rate `say` and `espeak` use at a rate multiplier of 1.
+const DEFAULT_WORDS_PER_MINUTE: f32 = 175.0;
+/// `espeak`'s default pitch on its 0–99 scale.
+const ESPEAK_DEFAULT_PITCH: f32 = 50.0;
+/// Clips depend only on the request, so clients may keep them.
+const CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+
+/// The providers `/ai/tts` tries, in order; part of [`AppState`].
+#[derive(Debug)]
+pub struct Tts {
+    providers: Vec<Provider>,
+}
+
+#[derive(Debug)]
+enum Provider {
+    OpenAi {
+        key: String,
+        model: String,
+        voice: String,
+        client: reqwest::Client,
+    },
+    Local,
+}
+
+#[derive(Debug, Serialize)]
+struct SpeechRequest<'a> {
+    model: &'a str,
+    input: &'a str,
+    voice: &'a str,
+    response_format: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    speed: Option<f32>,
+}
+
+struct Audio {
+    bytes: Vec<u8>,
+    content_type: &'static str,
+}
+
+impl Tts {
+    /// OpenAI first when `OPENAI_API_KEY` is set (`OPENAI_TTS_MODEL` and
+    /// `OPENAI_TTS_VOICE` override its defaults), the local synthesizer
+    /// always last.
+    pub fn from_env(client: reqwest::Client) -> Self {
+        let mut providers = Vec::new();
+        if let Ok(key) = std::env::var("OPENAI_API_KEY") {
+            providers.push(Provider::OpenAi {
+                key,
+                model: std::env::var("OPENAI_TTS_MODEL")
+                    .unwrap_or_else(|_| DEFAULT_OPENAI_MODEL.to_string()),
+                voice: std::env::var("OPENAI_TTS_VOICE")
+                    .unwrap_or_else(|_| DEFAULT_OPENAI_VOICE.to_string()),
+                client,
+            });
+        }
+        providers.push(Provider::Local);
+        Tts { providers }
+    }
+
+    /// Names of the configured providers, for the startup log.
+    pub fn describe(&self) -> String {
+        self.providers
+            .iter()
+            .map(Provider::name)
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+impl Provider {
+    fn name(&self) -> &'static str {
+        match self {
+            Provider::OpenAi { .. } => "openai",
+            Provider::Local => {
+                if cfg!(target_os = "macos") {
+                    "say"
+                } else {
+                    "espeak"
+                }
+            }
+        }
+    }
+
+    async fn speak(&self, request: &TtsRequest) -> Result<Audio, String> {
+        match self {
+            Provider::OpenAi {
+                key,
+                model,
+                voice,
+                client,
+            } => {
+                let body = SpeechRequest {
+                    model,
+                    input: request.text.trim(),
+                    voice: request.voice.as_deref().unwrap_or(voice),
+                    response_format: "mp3",
+                    speed: request.rate,
+                };
+                let response = client
+                    .post(OPENAI_SPEECH_URL)
+                    .bearer_auth(key)
+                    .json(&body)
+                    .send()
+                    .await
+                    .map_err(|err| err.to_string())?;
+                let status = response.status();
+                if !status.is_success() {
+                    let body = response.text().await.unwrap_or_default();
+                    return Err(format!("HTTP {status}: {body}"));
+                }
+                let bytes = response.bytes().await.map_err(|err| err.to_string())?;
+                Ok(Audio {
+                    bytes: bytes.to_vec(),
+                    content_type: "audio/mpeg",
+                })
+            }
+            Provider::Local => {
+                let text = request.text.trim().to_string();
+                let language = Language::new(&request.language);
+                let voice = Voice {
+                    name: request.voice.clone(),
+                    rate: request.rate.unwrap_or(1.0),
+                    pitch: request.pitch.unwrap_or(1.0),
+                };
+                let bytes =
+                    tokio::task::spawn_blocking(move || synthesize(&text, &language, &voice))
+                        .await
+                        .map_err(|err| err.to_string())??;
+                Ok(Audio {
+                    bytes,
+                    content_type: "audio/wav",
+                })
+            }
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/ai/tts",
+    tag = "ai",
+    request_body = TtsRequest,
+    responses(
+        (status = 200, description = "The spoken text: MP3 from OpenAI or WAV from the local synthesizer, as the Content-Type says"),
+        (status = 422, body = le_client::ApiErrorBody, description = "The request body is missing fields or has invalid values"),
+        (status = 503, description = "No provider could synthesize the text")
+    )
+)]
+pub async fn speak(
+    State(state): State<Arc<AppState>>,
+    Valid(payload): Valid<TtsRequest>,
+) -> Result<Response, ApiError> {
+    for provider in &state.tts.providers {
+        match provider.speak(&payload).await {
+            Ok(audio) => {
+                let mut response = audio.bytes.into_response();
+                let headers = response.headers_mut();
+                headers.insert(
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static(audio.content_type),
+                );
+                headers.insert(
+                    header::CACHE_CONTROL,
+                    HeaderValue::from_static(CACHE_CONTROL),
+                );
+                headers.insert("x-tts-provider", HeaderValue::from_static(provider.name()));
+                return Ok(response);
+            }
+            Err(err) => eprintln!("[tts] {} failed: {err}", provider.name()),
+        }
+    }
+    Err(StatusCode::SERVICE_UNAVAILABLE.into())
+}
+
+/// Voice options for the local synthesizer; `rate` and `pitch` are
+/// multipliers of its defaults. `say` has no pitch control.
+struct Voice {
+    name: Option<String>,
+    rate: f32,
+    pitch: f32,
+}
+
+/// `text` as WAV from `say` or `espeak`, through a temporary file that is
+/// removed afterwards.
+fn synthesize(text: &str, language: &Language, voice: &Voice) -> Result<Vec<u8>, String> {
+    let path = std::env::temp_dir().join(format!("le-tts-{}.wav", uuid::Uuid::new_v4()));
+    let result = run_synthesizer(&path, text, language, voice)
+        .and_then(|()| std::fs::read(&path).map_err(|err| err.to_string()));
+    let _ = std::fs::remove_file(&path);
+    result
+}
+
+fn run_synthesizer(
+    path: &Path,
+    text: &str,
+    language: &Language,
+    voice: &Voice,
+) -> Result<(), String> {
+    let words_per_minute = (DEFAULT_WORDS_PER_MINUTE * voice.rate).round().to_string();
+    let (program, output) = if cfg!(target_os = "macos") {
+        let mut command = Command::new("say");
+        if let Some(name) = &voice.name {
+            command.args(["-v", name]);
+        }
+        let output = command
+            .args(["-r", &words_per_minute, "--data-format=LEI16@22050", "-o"])
+            .arg(path)
+            .arg(text)
+            .output();
+        ("say", output)
+    } else {
+        let pitch = (ESPEAK_DEFAULT_PITCH * voice.pitch)
+            .clamp(0.0, 99.0)
+            .round();
+        let output = Command::new("espeak")
+            .args(["-v", voice.name.as_deref().unwrap_or(language.code())])
+            .args(["-s", &words_per_minute, "-p", &pitch.to_string(), "-w"])
+            .arg(path)
+            .arg(text)
+            .output();
+        ("espeak", output)
+    };
+    let output = output.map_err(|err| format!("failed to run {program}: {err}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{program} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
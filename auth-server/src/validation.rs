@@ -0,0 +1,526 @@
+//! Request and model-output checks for the `/auth` and `/ai` routes. Bodies
+//! that fail to parse or validate are answered with 422 and the offending
+//! fields instead of a bare status, so a malformed client request is not
+//! mistaken for an upstream failure. Model outputs also carry the JSON schema
+//! the model is held to ([`ModelReply`]).
+
+use axum::{
+    Json, async_trait,
+    body::Bytes,
+    extract::{FromRequest, Request},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use le_client::{
+    ApiErrorBody, CaptureRequest, CleanupRequest, CreateApiKeyRequest, DeviceRegistration,
+    EmailAuthRequest, FieldIssue, GenerateQuestionRequest, GenerateSentenceRequest,
+    GeneratedQuestion, GeneratedSentence, GradeSentenceRequest, LeaderboardStats,
+    SENTENCE_MAX_WORDS_RANGE, SentenceGrade, TTS_PITCH_RANGE, TTS_RATE_RANGE, TtsRequest,
+};
+use serde::Deserializer;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+
+const MAX_WORD_CHARS: usize = 200;
+const MAX_LANGUAGE_CHARS: usize = 32;
+const MAX_NAME_CHARS: usize = 64;
+const MAX_SENTENCE_CHARS: usize = 1000;
+/// Neon Auth rejects shorter passwords on sign-up with a less specific error.
+pub const MIN_PASSWORD_CHARS: usize = 8;
+
+/// Error returned by the `/auth` and `/ai` handlers; always answers with an
+/// [`ApiErrorBody`].
+#[derive(Debug)]
+pub enum ApiError {
+    Status(StatusCode),
+    Invalid(Vec<FieldIssue>),
+    /// The model answered, but not with the JSON the route promises.
+    Model(String),
+}
+
+impl ApiError {
+    pub fn field(field: &str, message: &str) -> Self {
+        ApiError::Invalid(vec![issue(field, message)])
+    }
+}
+
+impl From<StatusCode> for ApiError {
+    fn from(status: StatusCode) -> Self {
+        ApiError::Status(status)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, body) = match self {
+            ApiError::Status(status) => (
+                status,
+                ApiErrorBody {
+                    error: status
+                        .canonical_reason()
+                        .unwrap_or("request failed")
+                        .to_string(),
+                    fields: Vec::new(),
+                },
+            ),
+            ApiError::Invalid(fields) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                ApiErrorBody {
+                    error: "invalid request body".to_string(),
+                    fields,
+                },
+            ),
+            ApiError::Model(message) => (
+                StatusCode::BAD_GATEWAY,
+                ApiErrorBody {
+                    error: format!("the model returned an unusable response: {message}"),
+                    fields: Vec::new(),
+                },
+            ),
+        };
+        (status, Json(body)).into_response()
+    }
+}
+
+/// Checks a parsed body, pushing one [`FieldIssue`] per problem.
+pub trait Validate {
+    fn validate(&self, issues: &mut Vec<FieldIssue>);
+}
+
+/// JSON body extractor that runs [`Validate`] after deserializing. Type and
+/// missing-field errors are reported with the JSON path that caused them.
+pub struct Valid<T>(pub T);
+
+#[async_trait]
+impl<S, T> FromRequest<S> for Valid<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned + Validate,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let is_json = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| {
+                let mime = value.split(';').next().unwrap_or("").trim();
+                mime == "application/json" || mime.ends_with("+json")
+            });
+        if !is_json {
+            return Err(ApiError::Status(StatusCode::UNSUPPORTED_MEDIA_TYPE));
+        }
+        let bytes = Bytes::from_request(req, state)
+            .await
+            .map_err(|rejection| ApiError::Status(rejection.status()))?;
+        let value = parse_json::<T>(&bytes).map_err(ApiError::Invalid)?;
+        let mut issues = Vec::new();
+        value.validate(&mut issues);
+        if issues.is_empty() {
+            Ok(Valid(value))
+        } else {
+            Err(ApiError::Invalid(issues))
+        }
+    }
+}
+
+/// A structured model output. The model is made to answer by calling a tool
+/// named [`ModelReply::TOOL`] whose input must match
+/// [`ModelReply::input_schema`]; the input is then parsed and validated like
+/// a request body.
+pub trait ModelReply: DeserializeOwned + Validate {
+    const TOOL: &'static str;
+    const DESCRIPTION: &'static str;
+    fn input_schema() -> Value;
+}
+
+/// Parses and validates a model's tool input. The error names every problem
+/// found, ready to be sent back to the model for a corrected call.
+pub fn model_reply<T: ModelReply>(input: &Value) -> Result<T, String> {
+    let describe = |issues: Vec<FieldIssue>| {
+        issues
+            .iter()
+            .map(|issue| format!("{}: {}", issue.field, issue.message))
+            .collect::<Vec<_>>()
+            .join("; ")
+    };
+    let value = deserialize::<_, T>(input).map_err(|issues| {
+        eprintln!("[anthropic] unusable reply: {input}");
+        describe(issues)
+    })?;
+    let mut issues = Vec::new();
+    value.validate(&mut issues);
+    if issues.is_empty() {
+        Ok(value)
+    } else {
+        eprintln!("[anthropic] rejected reply: {input}");
+        Err(describe(issues))
+    }
+}
+
+fn parse_json<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, Vec<FieldIssue>> {
+    deserialize(&mut serde_json::Deserializer::from_slice(bytes))
+}
+
+fn deserialize<'de, D: Deserializer<'de>, T: DeserializeOwned>(
+    deserializer: D,
+) -> Result<T, Vec<FieldIssue>> {
+    serde_path_to_error::deserialize(deserializer).map_err(|err| {
+        let path = err.path().to_string();
+        let message = err.inner().to_string();
+        // A missing field is reported against its parent; name the field itself.
+        let missing = message
+            .strip_prefix("missing field `")
+            .and_then(|rest| rest.split('`').next());
+        let field = match (path.as_str(), missing) {
+            (".", Some(name)) => name.to_string(),
+            (_, Some(name)) => format!("{path}.{name}"),
+            (".", None) => "body".to_string(),
+            _ => path,
+        };
+        vec![FieldIssue { field, message }]
+    })
+}
+
+/// A schema for an object with exactly `properties`, all required.
+pub fn object_schema(properties: Value) -> Value {
+    let required: Vec<&String> = properties
+        .as_object()
+        .map(|properties| properties.keys().collect())
+        .unwrap_or_default();
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false
+    })
+}
+
+fn issue(field: &str, message: &str) -> FieldIssue {
+    FieldIssue {
+        field: field.to_string(),
+        message: message.to_string(),
+    }
+}
+
+fn require(issues: &mut Vec<FieldIssue>, field: &str, value: &str, max_chars: usize) {
+    if value.trim().is_empty() {
+        issues.push(issue(field, "must not be empty"));
+    } else if value.chars().count() > max_chars {
+        issues.push(issue(
+            field,
+            &format!("must be at most {max_chars} characters"),
+        ));
+    }
+}
+
+fn optional(issues: &mut Vec<FieldIssue>, field: &str, value: Option<&str>, max_chars: usize) {
+    if let Some(value) = value
+        && value.chars().count() > max_chars
+    {
+        issues.push(issue(
+            field,
+            &format!("must be at most {max_chars} characters"),
+        ));
+    }
+}
+
+impl Validate for EmailAuthRequest {
+    fn validate(&self, issues: &mut Vec<FieldIssue>) {
+        let email = self.email.trim();
+        match email.split_once('@') {
+            _ if email.is_empty() => issues.push(issue("email", "must not be empty")),
+            Some((local, domain)) if !local.is_empty() && domain.contains('.') => {}
+            _ => issues.push(issue("email", "must be an email address")),
+        }
+        if self.password.is_empty() {
+            issues.push(issue("password", "must not be empty"));
+        }
+        optional(issues, "name", self.name.as_deref(), MAX_WORD_CHARS);
+        if let Some(device) = &self.device {
+            device_issues(issues, "device.", device);
+        }
+    }
+}
+
+impl Validate for DeviceRegistration {
+    fn validate(&self, issues: &mut Vec<FieldIssue>) {
+        device_issues(issues, "", self);
+    }
+}
+
+fn device_issues(issues: &mut Vec<FieldIssue>, prefix: &str, device: &DeviceRegistration) {
+    if uuid::Uuid::parse_str(device.id.trim()).is_err() {
+        issues.push(issue(&format!("{prefix}id"), "must be a UUID"));
+    }
+    require(
+        issues,
+        &format!("{prefix}name"),
+        &device.name,
+        MAX_NAME_CHARS,
+    );
+    require(
+        issues,
+        &format!("{prefix}platform"),
+        &device.platform,
+        MAX_LANGUAGE_CHARS,
+    );
+}
+
+impl Validate for GenerateSentenceRequest {
+    fn validate(&self, issues: &mut Vec<FieldIssue>) {
+        require(issues, "word", &self.word, MAX_WORD_CHARS);
+        require(
+            issues,
+            "source_language",
+            &self.source_language,
+            MAX_LANGUAGE_CHARS,
+        );
+        require(
+            issues,
+            "target_language",
+            &self.target_language,
+            MAX_LANGUAGE_CHARS,
+        );
+        optional(
+            issues,
+            "translation",
+            self.translation.as_deref(),
+            MAX_WORD_CHARS,
+        );
+        optional(issues, "concept", self.concept.as_deref(), MAX_WORD_CHARS);
+        let (min, max) = SENTENCE_MAX_WORDS_RANGE;
+        if let Some(max_words) = self.max_words
+            && !(min..=max).contains(&max_words)
+        {
+            issues.push(issue(
+                "max_words",
+                &format!("must be between {min} and {max}"),
+            ));
+        }
+    }
+}
+
+impl Validate for GenerateQuestionRequest {
+    fn validate(&self, issues: &mut Vec<FieldIssue>) {
+        require(issues, "word", &self.word, MAX_WORD_CHARS);
+        require(
+            issues,
+            "source_language",
+            &self.source_language,
+            MAX_LANGUAGE_CHARS,
+        );
+        require(
+            issues,
+            "target_language",
+            &self.target_language,
+            MAX_LANGUAGE_CHARS,
+        );
+        optional(
+            issues,
+            "translation",
+            self.translation.as_deref(),
+            MAX_WORD_CHARS,
+        );
+        optional(issues, "concept", self.concept.as_deref(), MAX_WORD_CHARS);
+    }
+}
+
+impl Validate for GradeSentenceRequest {
+    fn validate(&self, issues: &mut Vec<FieldIssue>) {
+        require(issues, "word", &self.word, MAX_SENTENCE_CHARS);
+        require(
+            issues,
+            "target_language",
+            &self.target_language,
+            MAX_LANGUAGE_CHARS,
+        );
+        require(
+            issues,
+            "user_sentence",
+            &self.user_sentence,
+            MAX_SENTENCE_CHARS,
+        );
+        optional(
+            issues,
+            "question",
+            self.question.as_deref(),
+            MAX_SENTENCE_CHARS,
+        );
+        optional(issues, "concept", self.concept.as_deref(), MAX_WORD_CHARS);
+        optional(
+            issues,
+            "source_sentence",
+            self.source_sentence.as_deref(),
+            MAX_SENTENCE_CHARS,
+        );
+    }
+}
+
+impl Validate for CleanupRequest {
+    fn validate(&self, issues: &mut Vec<FieldIssue>) {
+        if self.entries.is_empty() {
+            issues.push(issue("entries", "must contain at least one entry"));
+        }
+        for (idx, entry) in self.entries.iter().enumerate() {
+            require(
+                issues,
+                &format!("entries[{idx}].word_id"),
+                &entry.word_id,
+                MAX_WORD_CHARS,
+            );
+            require(
+                issues,
+                &format!("entries[{idx}].text"),
+                &entry.text,
+                MAX_WORD_CHARS,
+            );
+            require(
+                issues,
+                &format!("entries[{idx}].language"),
+                &entry.language,
+                MAX_LANGUAGE_CHARS,
+            );
+        }
+    }
+}
+
+impl Validate for GeneratedSentence {
+    fn validate(&self, issues: &mut Vec<FieldIssue>) {
+        require(issues, "sentence", &self.sentence, MAX_SENTENCE_CHARS);
+        require(issues, "translation", &self.translation, MAX_SENTENCE_CHARS);
+    }
+}
+
+impl ModelReply for GeneratedSentence {
+    const TOOL: &'static str = "submit_sentence";
+    const DESCRIPTION: &'static str = "Submit the example sentence and its translation.";
+
+    fn input_schema() -> Value {
+        object_schema(json!({
+            "sentence": { "type": "string", "description": "The example sentence." },
+            "translation": { "type": "string", "description": "Its translation." }
+        }))
+    }
+}
+
+impl Validate for GeneratedQuestion {
+    fn validate(&self, issues: &mut Vec<FieldIssue>) {
+        require(issues, "question", &self.question, MAX_SENTENCE_CHARS);
+    }
+}
+
+impl ModelReply for GeneratedQuestion {
+    const TOOL: &'static str = "submit_question";
+    const DESCRIPTION: &'static str = "Submit the question for the learner.";
+
+    fn input_schema() -> Value {
+        object_schema(json!({
+            "question": { "type": "string", "description": "The question itself, nothing else." }
+        }))
+    }
+}
+
+impl Validate for SentenceGrade {
+    fn validate(&self, issues: &mut Vec<FieldIssue>) {
+        let rubric = &self.rubric;
+        for (field, value) in [
+            ("score", self.score),
+            ("rubric.word_usage", rubric.word_usage),
+            ("rubric.grammar", rubric.grammar),
+            ("rubric.spelling", rubric.spelling),
+            ("rubric.naturalness", rubric.naturalness),
+        ] {
+            if !(1..=10).contains(&value) {
+                issues.push(issue(field, "must be between 1 and 10"));
+            }
+        }
+    }
+}
+
+impl ModelReply for SentenceGrade {
+    const TOOL: &'static str = "submit_grade";
+    const DESCRIPTION: &'static str = "Submit the grade for the learner's sentence.";
+
+    fn input_schema() -> Value {
+        let score = json!({ "type": "integer", "minimum": 1, "maximum": 10 });
+        object_schema(json!({
+            "score": score,
+            "feedback": { "type": "string", "description": "Very short feedback." },
+            "correction": {
+                "type": ["string", "null"],
+                "description": "The corrected sentence, or null when nothing needs fixing."
+            },
+            "rubric": object_schema(json!({
+                "word_usage": score,
+                "grammar": score,
+                "spelling": score,
+                "naturalness": score
+            }))
+        }))
+    }
+}
+
+impl Validate for CreateApiKeyRequest {
+    fn validate(&self, issues: &mut Vec<FieldIssue>) {
+        require(issues, "name", &self.name, MAX_NAME_CHARS);
+    }
+}
+
+impl Validate for LeaderboardStats {
+    fn validate(&self, issues: &mut Vec<FieldIssue>) {
+        require(issues, "display_name", &self.display_name, MAX_NAME_CHARS);
+        if self.reviews < 0 {
+            issues.push(issue("reviews", "must not be negative"));
+        }
+        // Ten years; anything longer is a client bug, not a streak.
+        if !(0..=3650).contains(&self.streak_days) {
+            issues.push(issue("streak_days", "must be between 0 and 3650"));
+        }
+    }
+}
+
+impl Validate for CaptureRequest {
+    fn validate(&self, issues: &mut Vec<FieldIssue>) {
+        require(issues, "text", &self.text, MAX_SENTENCE_CHARS);
+        optional(
+            issues,
+            "translation",
+            self.translation.as_deref(),
+            MAX_SENTENCE_CHARS,
+        );
+        optional(issues, "notes", self.notes.as_deref(), MAX_SENTENCE_CHARS);
+        optional(issues, "chapter", self.chapter.as_deref(), MAX_WORD_CHARS);
+        optional(
+            issues,
+            "source_name",
+            self.source_name.as_deref(),
+            MAX_SENTENCE_CHARS,
+        );
+        optional(
+            issues,
+            "source_page",
+            self.source_page.as_deref(),
+            MAX_WORD_CHARS,
+        );
+    }
+}
+
+impl Validate for TtsRequest {
+    fn validate(&self, issues: &mut Vec<FieldIssue>) {
+        require(issues, "text", &self.text, MAX_SENTENCE_CHARS);
+        require(issues, "language", &self.language, MAX_LANGUAGE_CHARS);
+        optional(issues, "voice", self.voice.as_deref(), MAX_NAME_CHARS);
+        for (field, value, (min, max)) in [
+            ("rate", self.rate, TTS_RATE_RANGE),
+            ("pitch", self.pitch, TTS_PITCH_RANGE),
+        ] {
+            if let Some(value) = value
+                && !(min..=max).contains(&value)
+            {
+                issues.push(issue(field, &format!("must be between {min} and {max}")));
+            }
+        }
+    }
+}
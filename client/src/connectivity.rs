@@ -0,0 +1,124 @@
+//! Offline detection shared by everything that talks to the network: the
+//! auth server client, the translation API, and sync. Requests go through
+//! the proxy named by `HTTPS_PROXY` (or `HTTP_PROXY`/`ALL_PROXY`) unless
+//! `NO_PROXY` exempts the host, and the reachability probe follows the same
+//! rules, so a machine that can only get out through a proxy is not reported
+//! as offline.
+
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use reqwest::Url;
+
+/// How long the probe waits for a TCP connection.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+/// Probe results are reused for this long so a burst of failing calls does
+/// not each wait out the timeout.
+const PROBE_CACHE: Duration = Duration::from_secs(15);
+
+/// Shown when work is kept locally until the network is back.
+pub const OFFLINE_QUEUED: &str = "Offline — queued until the connection is back";
+
+static LAST_PROBE: Mutex<Option<(String, Instant, bool)>> = Mutex::new(None);
+
+/// A blocking HTTP client with the given timeout that honors the proxy
+/// environment variables.
+pub fn http_client(timeout: Duration) -> reqwest::Result<reqwest::blocking::Client> {
+    reqwest::blocking::Client::builder()
+        .timeout(timeout)
+        .build()
+}
+
+/// Whether a request failed because the host could not be reached at all,
+/// as opposed to answering with an error.
+pub fn is_offline_error(err: &reqwest::Error) -> bool {
+    err.is_connect() || err.is_timeout()
+}
+
+/// Whether `url` cannot be reached right now: a TCP connection to its host,
+/// or to the proxy in front of it, does not open within a few seconds.
+/// Unparseable URLs are not treated as offline; the request itself reports
+/// what is wrong with them.
+pub fn is_offline(url: &str) -> bool {
+    let Some(target) = probe_target(url) else {
+        return false;
+    };
+    let mut last = LAST_PROBE.lock().unwrap_or_else(|err| err.into_inner());
+    if let Some((host, at, offline)) = last.as_ref()
+        && *host == target
+        && at.elapsed() < PROBE_CACHE
+    {
+        return *offline;
+    }
+    let offline = !reachable(&target);
+    *last = Some((target, Instant::now(), offline));
+    offline
+}
+
+/// `host:port` the probe connects to for `url`.
+fn probe_target(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    let host = url.host_str()?;
+    let proxy = proxy_for(url.scheme(), host).and_then(|proxy| Url::parse(&proxy).ok());
+    let target = proxy.as_ref().unwrap_or(&url);
+    let port = target
+        .port_or_known_default()
+        .or_else(|| default_port(target.scheme()))?;
+    Some(format!("{}:{port}", target.host_str()?))
+}
+
+fn default_port(scheme: &str) -> Option<u16> {
+    match scheme {
+        "postgres" | "postgresql" => Some(5432),
+        _ => None,
+    }
+}
+
+/// The proxy URL requests to `host` go through, mirroring reqwest's lookup.
+fn proxy_for(scheme: &str, host: &str) -> Option<String> {
+    let names: &[&str] = match scheme {
+        "https" => &["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"],
+        "http" => &["HTTP_PROXY", "http_proxy", "ALL_PROXY", "all_proxy"],
+        _ => return None,
+    };
+    let proxy = names
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.trim().is_empty())?;
+    if bypasses_proxy(host) {
+        return None;
+    }
+    Some(if proxy.contains("://") {
+        proxy
+    } else {
+        format!("http://{proxy}")
+    })
+}
+
+fn bypasses_proxy(host: &str) -> bool {
+    let Some(no_proxy) = ["NO_PROXY", "no_proxy"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok())
+    else {
+        return false;
+    };
+    no_proxy
+        .split(',')
+        .map(|entry| entry.trim().trim_start_matches('.'))
+        .filter(|entry| !entry.is_empty())
+        .any(|entry| {
+            entry == "*"
+                || host.eq_ignore_ascii_case(entry)
+                || host.to_ascii_lowercase().ends_with(&format!(".{entry}"))
+        })
+}
+
+fn reachable(target: &str) -> bool {
+    let Ok(addrs) = target.to_socket_addrs() else {
+        return false;
+    };
+    addrs
+        .into_iter()
+        .any(|addr| TcpStream::connect_timeout(&addr, PROBE_TIMEOUT).is_ok())
+}
@@ -0,0 +1,328 @@
+//! Blocking client for the Language Enforcer auth server. The server's
+//! OpenAPI document at `/openapi.json` describes the same endpoints.
+
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+use reqwest::blocking::{RequestBuilder, Response};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+pub mod connectivity;
+pub mod types;
+
+pub use types::*;
+
+pub const DEFAULT_BASE_URL: &str = "http://127.0.0.1:8787";
+const REQUEST_TIMEOUT_SECS: u64 = 30;
+const PING_TIMEOUT_SECS: u64 = 5;
+
+#[derive(Debug)]
+pub enum ClientError {
+    /// The base URL is not an absolute http(s) URL.
+    InvalidUrl(String),
+    /// The server could not be reached; carries the base URL.
+    Offline(String),
+    Http(reqwest::Error),
+    /// The server answered with a non-success status; carries the body text.
+    Status(u16, String),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::InvalidUrl(message) => write!(f, "invalid server URL: {message}"),
+            ClientError::Offline(base_url) => write!(f, "offline: {base_url} is unreachable"),
+            ClientError::Http(err) => write!(f, "{err}"),
+            ClientError::Status(status, body) if body.is_empty() => write!(f, "HTTP {status}"),
+            ClientError::Status(status, body) => write!(f, "HTTP {status}: {body}"),
+        }
+    }
+}
+
+impl ClientError {
+    pub fn is_offline(&self) -> bool {
+        matches!(self, ClientError::Offline(_))
+    }
+}
+
+impl Error for ClientError {}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(err: reqwest::Error) -> Self {
+        ClientError::Http(err)
+    }
+}
+
+pub type ClientResult<T> = Result<T, ClientError>;
+
+/// Audio returned by [`Client::tts`].
+#[derive(Debug, Clone)]
+pub struct TtsAudio {
+    pub bytes: Vec<u8>,
+    /// `audio/mpeg` or `audio/wav`.
+    pub content_type: String,
+}
+
+impl TtsAudio {
+    /// File extension matching `content_type`.
+    pub fn extension(&self) -> &'static str {
+        if self.content_type.contains("wav") {
+            "wav"
+        } else {
+            "mp3"
+        }
+    }
+}
+
+pub struct Client {
+    base_url: String,
+    access_token: Option<String>,
+    api_key: Option<String>,
+    http: reqwest::blocking::Client,
+}
+
+impl Client {
+    pub fn new(base_url: impl Into<String>) -> ClientResult<Self> {
+        let base_url = base_url.into();
+        let parsed = reqwest::Url::parse(&base_url)
+            .map_err(|err| ClientError::InvalidUrl(format!("{base_url}: {err}")))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(ClientError::InvalidUrl(format!(
+                "{base_url}: expected http or https"
+            )));
+        }
+        let http = connectivity::http_client(Duration::from_secs(REQUEST_TIMEOUT_SECS))?;
+        Ok(Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            access_token: None,
+            api_key: None,
+            http,
+        })
+    }
+
+    /// Uses `AUTH_SERVER_URL`, falling back to `DEFAULT_BASE_URL`, and
+    /// `AUTH_SERVER_API_KEY` when set.
+    pub fn from_env() -> ClientResult<Self> {
+        let client = Self::new(
+            std::env::var("AUTH_SERVER_URL").unwrap_or_else(|_| DEFAULT_BASE_URL.to_string()),
+        )?;
+        Ok(match std::env::var("AUTH_SERVER_API_KEY") {
+            Ok(key) if !key.trim().is_empty() => client.with_api_key(key.trim()),
+            _ => client,
+        })
+    }
+
+    /// Bearer token sent with `/data` requests.
+    pub fn with_access_token(mut self, token: impl Into<String>) -> Self {
+        self.access_token = Some(token.into());
+        self
+    }
+
+    /// Scoped key sent as `x-api-key` with `/data` requests, for clients that
+    /// cannot sign in interactively.
+    pub fn with_api_key(mut self, key: impl Into<String>) -> Self {
+        self.api_key = Some(key.into());
+        self
+    }
+
+    /// Checks that the server answers, using a short timeout.
+    pub fn ping(&self) -> ClientResult<()> {
+        let request = self
+            .http
+            .get(self.url("/openapi.json"))
+            .timeout(Duration::from_secs(PING_TIMEOUT_SECS));
+        self.send::<serde_json::Value>(request).map(|_| ())
+    }
+
+    pub fn sign_in(&self, request: &EmailAuthRequest) -> ClientResult<AuthResponse> {
+        self.post_json("/auth/sign-in", request)
+    }
+
+    pub fn sign_up(&self, request: &EmailAuthRequest) -> ClientResult<AuthResponse> {
+        self.post_json("/auth/sign-up", request)
+    }
+
+    pub fn generate_sentence(
+        &self,
+        request: &GenerateSentenceRequest,
+    ) -> ClientResult<GeneratedSentence> {
+        self.post_json("/ai/generate-sentence", request)
+    }
+
+    pub fn generate_question(
+        &self,
+        request: &GenerateQuestionRequest,
+    ) -> ClientResult<GeneratedQuestion> {
+        self.post_json("/ai/generate-question", request)
+    }
+
+    pub fn grade_sentence(&self, request: &GradeSentenceRequest) -> ClientResult<SentenceGrade> {
+        self.post_json("/ai/grade-sentence", request)
+    }
+
+    pub fn cleanup(&self, request: &CleanupRequest) -> ClientResult<CleanupResponse> {
+        self.post_json("/ai/cleanup", request)
+    }
+
+    /// Speaks `request.text`; the audio is MP3 or WAV depending on the
+    /// server's provider.
+    pub fn tts(&self, request: &TtsRequest) -> ClientResult<TtsAudio> {
+        let response = self.response(self.http.post(self.url("/ai/tts")).json(request))?;
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("audio/mpeg")
+            .to_string();
+        Ok(TtsAudio {
+            bytes: response.bytes()?.to_vec(),
+            content_type,
+        })
+    }
+
+    pub fn get_settings(&self) -> ClientResult<SettingsPayload> {
+        self.send(self.authorized(self.http.get(self.url("/data/settings"))))
+    }
+
+    pub fn put_settings(&self, payload: &SettingsPayload) -> ClientResult<SettingsPayload> {
+        self.send(self.authorized(self.http.put(self.url("/data/settings")).json(payload)))
+    }
+
+    /// Graded sentence attempts since `since` (RFC 3339), with monthly
+    /// averages over the same attempts.
+    pub fn sentence_history(&self, since: Option<&str>) -> ClientResult<SentenceHistory> {
+        let mut request = self.http.get(self.url("/data/sentence-history"));
+        if let Some(since) = since {
+            request = request.query(&[("since", since)]);
+        }
+        self.send(self.authorized(request))
+    }
+
+    pub fn capture(&self, request: &CaptureRequest) -> ClientResult<CaptureResponse> {
+        self.send(self.authorized(self.http.post(self.url("/data/capture")).json(request)))
+    }
+
+    pub fn create_api_key(&self, request: &CreateApiKeyRequest) -> ClientResult<CreatedApiKey> {
+        self.send(self.authorized(self.http.post(self.url("/auth/api-keys")).json(request)))
+    }
+
+    pub fn list_api_keys(&self) -> ClientResult<Vec<ApiKeyInfo>> {
+        self.send(self.authorized(self.http.get(self.url("/auth/api-keys"))))
+    }
+
+    /// Revokes a key; it stops working immediately but stays listed.
+    pub fn revoke_api_key(&self, id: &str) -> ClientResult<ApiKeyInfo> {
+        let url = self.url(&format!("/auth/api-keys/{id}"));
+        self.send(self.authorized(self.http.delete(url)))
+    }
+
+    /// Registers (or refreshes) this device for the current access token.
+    pub fn register_device(&self, device: &DeviceRegistration) -> ClientResult<DeviceInfo> {
+        self.send(self.authorized(self.http.post(self.url("/auth/devices")).json(device)))
+    }
+
+    pub fn list_devices(&self) -> ClientResult<Vec<DeviceInfo>> {
+        self.send(self.authorized(self.http.get(self.url("/auth/devices"))))
+    }
+
+    /// Signs a device out: its registered token stops working at once and it
+    /// cannot register again.
+    pub fn revoke_device(&self, id: &str) -> ClientResult<DeviceInfo> {
+        let url = self.url(&format!("/auth/devices/{id}"));
+        self.send(self.authorized(self.http.delete(url)))
+    }
+
+    /// Opts in to the weekly leaderboard, or updates the caller's numbers.
+    pub fn join_leaderboard(&self, stats: &LeaderboardStats) -> ClientResult<LeaderboardStats> {
+        let url = self.url("/social/leaderboard/me");
+        self.send(self.authorized(self.http.put(url).json(stats)))
+    }
+
+    /// Opts out; the caller's row is deleted.
+    pub fn leave_leaderboard(&self) -> ClientResult<()> {
+        let request = self.http.delete(self.url("/social/leaderboard/me"));
+        let response = self.authorized(request).send()?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().unwrap_or_default();
+            return Err(ClientError::Status(
+                status.as_u16(),
+                describe_error_body(body),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn weekly_leaderboard(&self) -> ClientResult<WeeklyLeaderboard> {
+        let url = self.url("/social/leaderboard/weekly");
+        self.send(self.authorized(self.http.get(url)))
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.base_url)
+    }
+
+    fn authorized(&self, request: RequestBuilder) -> RequestBuilder {
+        let request = match &self.api_key {
+            Some(key) => request.header("x-api-key", key),
+            None => request,
+        };
+        match &self.access_token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    fn post_json<B: Serialize, T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> ClientResult<T> {
+        self.send(self.http.post(self.url(path)).json(body))
+    }
+
+    fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> ClientResult<T> {
+        Ok(self.response(request)?.json::<T>()?)
+    }
+
+    /// Sends `request`, turning failures and non-success statuses into
+    /// errors.
+    fn response(&self, request: RequestBuilder) -> ClientResult<Response> {
+        let response = request.send().map_err(|err| {
+            if connectivity::is_offline_error(&err) {
+                ClientError::Offline(self.base_url.clone())
+            } else {
+                ClientError::Http(err)
+            }
+        })?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().unwrap_or_default();
+            return Err(ClientError::Status(
+                status.as_u16(),
+                describe_error_body(body),
+            ));
+        }
+        Ok(response)
+    }
+}
+
+/// Flattens an [`ApiErrorBody`] into one line so field errors read well in a
+/// status bar; any other body passes through unchanged.
+fn describe_error_body(body: String) -> String {
+    let Ok(parsed) = serde_json::from_str::<ApiErrorBody>(&body) else {
+        return body;
+    };
+    if parsed.fields.is_empty() {
+        return parsed.error;
+    }
+    let fields = parsed
+        .fields
+        .iter()
+        .map(|issue| format!("{}: {}", issue.field, issue.message))
+        .collect::<Vec<_>>()
+        .join("; ");
+    format!("{} ({fields})", parsed.error)
+}
@@ -0,0 +1,440 @@
+//! Request and response bodies for the auth server's `/auth`, `/ai`, and
+//! `/data` endpoints.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct EmailAuthRequest {
+    pub email: String,
+    pub password: String,
+    pub name: Option<String>,
+    /// Registers the signing-in device, so it can later be listed and signed
+    /// out remotely. Required when the server checks devices; a device that
+    /// was revoked cannot sign in again.
+    #[serde(default)]
+    pub device: Option<DeviceRegistration>,
+}
+
+/// Result of a sign-in or sign-up. Failures still return 200 with `error`
+/// set and `access_token`/`user` null; `raw` echoes the Neon Auth payload.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AuthResponse {
+    pub access_token: Option<String>,
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<Object>))]
+    pub user: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<Object>))]
+    pub error: Option<Value>,
+    #[cfg_attr(feature = "openapi", schema(value_type = Object))]
+    pub raw: Value,
+}
+
+/// Bounds for [`GenerateSentenceRequest::max_words`].
+pub const SENTENCE_MAX_WORDS_RANGE: (usize, usize) = (4, 40);
+
+/// CEFR level generated sentences are pitched at.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum CefrLevel {
+    A1,
+    A2,
+    #[default]
+    B1,
+    B2,
+}
+
+impl CefrLevel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CefrLevel::A1 => "A1",
+            CefrLevel::A2 => "A2",
+            CefrLevel::B1 => "B1",
+            CefrLevel::B2 => "B2",
+        }
+    }
+
+    /// Sentence length used when a request sets no `max_words`.
+    pub fn default_max_words(self) -> usize {
+        match self {
+            CefrLevel::A1 => 8,
+            CefrLevel::A2 => 12,
+            CefrLevel::B1 => 18,
+            CefrLevel::B2 => 25,
+        }
+    }
+
+    /// Most clauses (split on commas, semicolons, and colons) a sentence at
+    /// this level may have.
+    pub fn max_clauses(self) -> usize {
+        match self {
+            CefrLevel::A1 => 1,
+            CefrLevel::A2 => 2,
+            CefrLevel::B1 => 3,
+            CefrLevel::B2 => 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct GenerateSentenceRequest {
+    pub word: String,
+    pub translation: Option<String>,
+    pub source_language: String,
+    pub target_language: String,
+    pub concept: Option<String>,
+    #[serde(default)]
+    pub level: CefrLevel,
+    /// Longest sentence, in words, the reply may contain; defaults by level.
+    #[serde(default)]
+    pub max_words: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct GeneratedSentence {
+    pub sentence: String,
+    pub translation: String,
+    /// The server prompt behind this result, as `name@version`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_version: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct GenerateQuestionRequest {
+    pub word: String,
+    pub translation: Option<String>,
+    pub source_language: String,
+    pub target_language: String,
+    pub concept: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct GeneratedQuestion {
+    pub question: String,
+    /// The server prompt behind this result, as `name@version`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_version: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct GradeSentenceRequest {
+    /// The word the sentence must use, or for sentence cards the reference
+    /// translation.
+    pub word: String,
+    pub target_language: String,
+    pub user_sentence: String,
+    pub question: Option<String>,
+    pub concept: Option<String>,
+    /// Set for sentence cards: the sentence the user translated into
+    /// `target_language`.
+    #[serde(default)]
+    pub source_sentence: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SentenceGrade {
+    /// 1-10.
+    pub score: u8,
+    pub feedback: String,
+    pub correction: Option<String>,
+    pub rubric: SentenceRubric,
+    /// The server prompt behind this result, as `name@version`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_version: Option<String>,
+}
+
+/// Per-skill scores behind a [`SentenceGrade`], each 1-10.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SentenceRubric {
+    /// Whether the target word (or the source meaning) is used correctly.
+    pub word_usage: u8,
+    pub grammar: u8,
+    pub spelling: u8,
+    pub naturalness: u8,
+}
+
+/// One graded sentence, stored for signed-in callers of `/ai/grade-sentence`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SentenceAttempt {
+    pub id: String,
+    pub word: String,
+    pub target_language: String,
+    pub user_sentence: String,
+    pub source_sentence: Option<String>,
+    pub score: u8,
+    #[serde(flatten)]
+    pub rubric: SentenceRubric,
+    pub feedback: String,
+    pub correction: Option<String>,
+    /// The grading prompt, as `name@version`; `None` for older attempts.
+    #[serde(default)]
+    pub prompt_version: Option<String>,
+    pub created_at: String,
+}
+
+/// Average scores for the attempts in one calendar month (`YYYY-MM`, UTC).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SentenceMonth {
+    pub month: String,
+    pub attempts: usize,
+    pub score: f64,
+    pub word_usage: f64,
+    pub grammar: f64,
+    pub spelling: f64,
+    pub naturalness: f64,
+}
+
+/// Response of `GET /data/sentence-history`: attempts newest first, and
+/// monthly averages oldest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SentenceHistory {
+    pub attempts: Vec<SentenceAttempt>,
+    pub months: Vec<SentenceMonth>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CleanupEntry {
+    pub word_id: String,
+    pub text: String,
+    pub translation: Option<String>,
+    pub language: String,
+    pub notes: Option<String>,
+}
+
+/// At most the first 10 entries are reviewed per request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CleanupRequest {
+    pub entries: Vec<CleanupEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CleanupSuggestion {
+    pub word_id: String,
+    pub text: String,
+    pub language: String,
+    pub current_translation: Option<String>,
+    pub suggestion: String,
+    pub notes: Option<String>,
+    /// The server prompt behind this result, as `name@version`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_version: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CleanupResponse {
+    pub suggestions: Vec<CleanupSuggestion>,
+}
+
+/// What an API key may do: `capture` keys can only post to `/data/capture`,
+/// `read` keys can only make GET requests under `/data`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum ApiKeyScope {
+    Capture,
+    Read,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreateApiKeyRequest {
+    /// Label shown when listing keys, e.g. "firefox extension".
+    pub name: String,
+    pub scope: ApiKeyScope,
+}
+
+/// A stored key. Only `prefix` (the first characters of the secret) is kept
+/// in readable form.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ApiKeyInfo {
+    pub id: String,
+    pub name: String,
+    pub scope: ApiKeyScope,
+    pub prefix: String,
+    pub created_at: String,
+    #[serde(default)]
+    pub last_used_at: Option<String>,
+    #[serde(default)]
+    pub revoked_at: Option<String>,
+}
+
+/// Returned once on creation; `key` cannot be retrieved again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreatedApiKey {
+    pub key: String,
+    pub info: ApiKeyInfo,
+}
+
+/// Identifies the device a token is used from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DeviceRegistration {
+    /// Generated once on the device (a UUID) and kept across sign-ins.
+    pub id: String,
+    /// Label shown when listing devices, e.g. "Pixel 8".
+    pub name: String,
+    /// `ios`, `android`, `macos`, `windows`, `linux`, or `web`.
+    pub platform: String,
+}
+
+/// A registered device. Revoked devices stay listed with `revoked_at` set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DeviceInfo {
+    pub id: String,
+    pub name: String,
+    pub platform: String,
+    pub created_at: String,
+    #[serde(default)]
+    pub last_seen_at: Option<String>,
+    #[serde(default)]
+    pub revoked_at: Option<String>,
+}
+
+/// A word sent by a non-interactive client. It is saved as a Dutch word with
+/// a `Web` source and a new card due immediately.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CaptureRequest {
+    pub text: String,
+    #[serde(default)]
+    pub translation: Option<String>,
+    #[serde(default)]
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub chapter: Option<String>,
+    /// Page title or URL the word was captured from.
+    #[serde(default)]
+    pub source_name: Option<String>,
+    #[serde(default)]
+    pub source_page: Option<String>,
+}
+
+/// Bounds for [`TtsRequest::rate`].
+pub const TTS_RATE_RANGE: (f32, f32) = (0.25, 4.0);
+/// Bounds for [`TtsRequest::pitch`].
+pub const TTS_PITCH_RANGE: (f32, f32) = (0.5, 2.0);
+
+/// Text to speak. `voice` overrides the provider's default voice and is
+/// passed through unchecked. `rate` and `pitch` are multipliers of the
+/// provider's default; OpenAI has no pitch control and ignores it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TtsRequest {
+    pub text: String,
+    /// Code or name of the language the text is in ("nl", "dutch").
+    pub language: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voice: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pitch: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CaptureResponse {
+    pub word_id: String,
+    pub card_id: String,
+}
+
+/// Error body returned by the `/auth` and `/ai` routes. `fields` lists every
+/// rejected request field for 422 responses and is omitted otherwise.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ApiErrorBody {
+    pub error: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldIssue>,
+}
+
+/// One invalid field, named by its JSON path such as `entries[2].text`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FieldIssue {
+    pub field: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SettingEntry {
+    pub key: String,
+    #[cfg_attr(feature = "openapi", schema(value_type = Object))]
+    pub value: Value,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SettingsPayload {
+    pub settings: Vec<SettingEntry>,
+}
+
+/// The caller's own numbers for the current week, sent when opting in to the
+/// leaderboard and again whenever they change. The server cannot check them
+/// against reviews it never sees, so every board shows self-reported counts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LeaderboardStats {
+    /// Name shown to other members instead of the account email.
+    pub display_name: String,
+    /// Reviews since Monday 00:00 UTC.
+    pub reviews: i64,
+    /// Consecutive days, up to today, with at least one review.
+    pub streak_days: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LeaderboardEntry {
+    pub rank: u32,
+    pub display_name: String,
+    pub reviews: i64,
+    pub streak_days: i64,
+    /// Set on the caller's own entry.
+    #[serde(default)]
+    pub you: bool,
+}
+
+/// Members who reported stats this week, most reviews first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct WeeklyLeaderboard {
+    /// Monday of the current week as `YYYY-MM-DD`.
+    pub week_start: String,
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+/// Sent as a JSON text frame on the auth server's `/ws` stream to every
+/// connection of the user the change belongs to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerEvent {
+    /// Words, cards, reviews, or concepts were written through the server.
+    DeckUpdated,
+    /// A word arrived through `/data/capture`.
+    CaptureReceived { word_id: String },
+    /// Something else changed, or events were missed; pull everything.
+    SyncRecommended { reason: String },
+}
@@ -0,0 +1,203 @@
+//! Typed answers checked against a card's answer, forgiving what a learner
+//! should not lose a card over: case, accents, a leading article, spacing,
+//! and small typos. The result suggests a grade; the learner still confirms
+//! or changes it.
+
+use serde::{Deserialize, Serialize};
+
+use crate::Grade;
+use crate::rules::{self, LanguageRules};
+
+/// Least similarity (1 minus edit distance over the longer length) that
+/// still counts as a typo rather than a wrong answer.
+pub const TYPO_SIMILARITY: f64 = 0.8;
+/// Answers this short must match exactly but for accents: one letter off in
+/// a three-letter word is usually another word.
+const MIN_TYPO_CHARS: usize = 4;
+const EXACT_GRADE: u8 = 4;
+const TYPO_GRADE: u8 = 3;
+const WRONG_GRADE: u8 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    /// Matches but for case, spacing, or a leading article.
+    Exact,
+    /// Matches once accents are ignored ("cafe" for "café").
+    Accents,
+    /// Within [`TYPO_SIMILARITY`] of an accepted answer.
+    Typo,
+    Wrong,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnswerCheck {
+    pub verdict: Verdict,
+    /// The accepted answer closest to what was typed.
+    pub closest: String,
+    /// 0 to 1 against `closest`, 1 being identical once normalized.
+    pub similarity: f64,
+    pub suggested_grade: Grade,
+}
+
+/// Checks `typed` against every accepted answer (each `/`, `,`, or `;`
+/// separated alternative of each entry in `accepted`) and keeps the best
+/// match. `None` when nothing was typed or there is nothing to accept.
+pub fn check_answer(
+    rules: &dyn LanguageRules,
+    typed: &str,
+    accepted: &[&str],
+) -> Option<AnswerCheck> {
+    let typed_key = normalize(rules, typed);
+    if typed_key.is_empty() {
+        return None;
+    }
+    accepted
+        .iter()
+        .flat_map(|answer| answer.split(['/', ',', ';']))
+        .map(str::trim)
+        .filter(|answer| !answer.is_empty())
+        .map(|answer| check_one(rules, typed, &typed_key, answer))
+        .max_by(|a, b| {
+            rank(a.verdict)
+                .cmp(&rank(b.verdict))
+                .then(a.similarity.total_cmp(&b.similarity))
+        })
+}
+
+fn check_one(rules: &dyn LanguageRules, typed: &str, typed_key: &str, answer: &str) -> AnswerCheck {
+    let answer_key = normalize(rules, answer);
+    let folded_typed = fold_diacritics(typed_key);
+    let folded_answer = fold_diacritics(&answer_key);
+    let similarity = similarity(&folded_typed, &folded_answer);
+    let verdict = if typed_key == answer_key || rules::answer_matches(rules, typed, answer) {
+        Verdict::Exact
+    } else if folded_typed == folded_answer {
+        Verdict::Accents
+    } else if folded_answer.chars().count() >= MIN_TYPO_CHARS && similarity >= TYPO_SIMILARITY {
+        Verdict::Typo
+    } else {
+        Verdict::Wrong
+    };
+    AnswerCheck {
+        verdict,
+        closest: answer.to_string(),
+        similarity: if verdict == Verdict::Exact {
+            1.0
+        } else {
+            similarity
+        },
+        suggested_grade: suggested_grade(verdict),
+    }
+}
+
+/// Exact and accent-only matches are good answers, typos hard ones.
+pub fn suggested_grade(verdict: Verdict) -> Grade {
+    Grade(match verdict {
+        Verdict::Exact | Verdict::Accents => EXACT_GRADE,
+        Verdict::Typo => TYPO_GRADE,
+        Verdict::Wrong => WRONG_GRADE,
+    })
+}
+
+fn rank(verdict: Verdict) -> u8 {
+    match verdict {
+        Verdict::Exact => 3,
+        Verdict::Accents => 2,
+        Verdict::Typo => 1,
+        Verdict::Wrong => 0,
+    }
+}
+
+/// Lowercase, single-spaced, without a leading article or the punctuation
+/// around words.
+fn normalize(rules: &dyn LanguageRules, text: &str) -> String {
+    let words: Vec<&str> = text
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c: char| c.is_ascii_punctuation() && c != '\''))
+        .filter(|word| !word.is_empty())
+        .collect();
+    rules
+        .strip_article(&words.join(" ").to_lowercase())
+        .to_string()
+}
+
+/// 1 minus the edit distance over the longer length; two empty strings are
+/// identical.
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let longest = a.chars().count().max(b.chars().count());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - levenshtein(a, b) as f64 / longest as f64
+}
+
+/// Insertions, deletions, and substitutions needed to turn `a` into `b`,
+/// counted in characters.
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// `text` with accented Latin letters replaced by their base letter
+/// ("één" -> "een", "ß" -> "ss").
+pub fn fold_diacritics(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => folded.push('a'),
+            'À' | 'Á' | 'Â' | 'Ã' | 'Ä' | 'Å' | 'Ā' | 'Ă' | 'Ą' => folded.push('A'),
+            'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => folded.push('c'),
+            'Ç' | 'Ć' | 'Ĉ' | 'Ċ' | 'Č' => folded.push('C'),
+            'ď' | 'đ' => folded.push('d'),
+            'Ď' | 'Đ' => folded.push('D'),
+            'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => folded.push('e'),
+            'È' | 'É' | 'Ê' | 'Ë' | 'Ē' | 'Ĕ' | 'Ė' | 'Ę' | 'Ě' => folded.push('E'),
+            'ĝ' | 'ğ' | 'ġ' | 'ģ' => folded.push('g'),
+            'Ĝ' | 'Ğ' | 'Ġ' | 'Ģ' => folded.push('G'),
+            'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => folded.push('i'),
+            'Ì' | 'Í' | 'Î' | 'Ï' | 'Ĩ' | 'Ī' | 'Ĭ' | 'Į' | 'İ' => folded.push('I'),
+            'ł' | 'ĺ' | 'ļ' | 'ľ' => folded.push('l'),
+            'Ł' | 'Ĺ' | 'Ļ' | 'Ľ' => folded.push('L'),
+            'ñ' | 'ń' | 'ņ' | 'ň' => folded.push('n'),
+            'Ñ' | 'Ń' | 'Ņ' | 'Ň' => folded.push('N'),
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => folded.push('o'),
+            'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ö' | 'Ø' | 'Ō' | 'Ŏ' | 'Ő' => folded.push('O'),
+            'ŕ' | 'ŗ' | 'ř' => folded.push('r'),
+            'Ŕ' | 'Ŗ' | 'Ř' => folded.push('R'),
+            'ś' | 'ŝ' | 'ş' | 'š' => folded.push('s'),
+            'Ś' | 'Ŝ' | 'Ş' | 'Š' => folded.push('S'),
+            'ß' => folded.push_str("ss"),
+            'ţ' | 'ť' => folded.push('t'),
+            'Ţ' | 'Ť' => folded.push('T'),
+            'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => folded.push('u'),
+            'Ù' | 'Ú' | 'Û' | 'Ü' | 'Ũ' | 'Ū' | 'Ŭ' | 'Ů' | 'Ű' | 'Ų' => folded.push('U'),
+            'ý' | 'ÿ' => folded.push('y'),
+            'Ý' | 'Ÿ' => folded.push('Y'),
+            'ź' | 'ż' | 'ž' => folded.push('z'),
+            'Ź' | 'Ż' | 'Ž' => folded.push('Z'),
+            'æ' => folded.push_str("ae"),
+            'Æ' => folded.push_str("AE"),
+            'œ' => folded.push_str("oe"),
+            'Œ' => folded.push_str("OE"),
+            'ĳ' => folded.push_str("ij"),
+            'Ĳ' => folded.push_str("IJ"),
+            other => folded.push(other),
+        }
+    }
+    folded
+}
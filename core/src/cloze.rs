@@ -0,0 +1,55 @@
+//! Cloze deletion: one of a word's example sentences with the word blanked
+//! out, answered by typing the missing words. The blank covers the form the
+//! sentence uses ("liep" for "lopen"), and both halves of a split separable
+//! verb.
+
+use crate::Grade;
+use crate::rules::{self, LanguageRules};
+
+/// What replaces each occurrence of the word in the prompt.
+pub const BLANK: &str = "____";
+/// Grades a typed answer maps to: right counts as "good", wrong as a lapse.
+const CORRECT_GRADE: u8 = 4;
+const WRONG_GRADE: u8 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cloze {
+    /// The sentence with every occurrence of the word blanked.
+    pub prompt: String,
+    /// The blanked text, parts separated by a space ("bel op").
+    pub answer: String,
+}
+
+/// Blanks `word` out of `sentence`, or `None` if the sentence does not
+/// contain it.
+pub fn make_cloze(rules: &dyn LanguageRules, sentence: &str, word: &str) -> Option<Cloze> {
+    let ranges = rules::find_word(rules, sentence, word);
+    if ranges.is_empty() {
+        return None;
+    }
+    let mut prompt = String::with_capacity(sentence.len());
+    let mut answer = Vec::with_capacity(ranges.len());
+    let mut last = 0;
+    for range in ranges {
+        prompt.push_str(&sentence[last..range.start]);
+        prompt.push_str(BLANK);
+        answer.push(&sentence[range.clone()]);
+        last = range.end;
+    }
+    prompt.push_str(&sentence[last..]);
+    Some(Cloze {
+        prompt,
+        answer: answer.join(" "),
+    })
+}
+
+/// Whether `typed` fills the blanks: the blanked text itself, ignoring case,
+/// extra spaces, and a leading article.
+pub fn is_correct(rules: &dyn LanguageRules, cloze: &Cloze, typed: &str) -> bool {
+    let typed = typed.split_whitespace().collect::<Vec<_>>().join(" ");
+    rules::answer_matches(rules, &typed, &cloze.answer)
+}
+
+pub fn grade(correct: bool) -> Grade {
+    Grade(if correct { CORRECT_GRADE } else { WRONG_GRADE })
+}
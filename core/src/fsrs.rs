@@ -0,0 +1,308 @@
+//! FSRS v4 (Free Spaced Repetition Scheduler). Each card carries a memory
+//! stability (days until recall probability falls to 90%) and a difficulty
+//! from 1 to 10; every review updates both from the rating and from how well
+//! the card was likely remembered, and the next interval is the time until
+//! recall is expected to drop to [`Fsrs::desired_retention`].
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{Card, Grade, ScheduleExplanation, Scheduler};
+
+/// Published FSRS v4 default parameters.
+pub const DEFAULT_WEIGHTS: [f64; 17] = [
+    0.4, 0.6, 2.4, 5.8, 4.93, 0.94, 0.86, 0.01, 1.49, 0.14, 0.94, 2.18, 0.05, 0.34, 1.26, 0.29,
+    2.61,
+];
+pub const DEFAULT_RETENTION: f64 = 0.9;
+const MAX_INTERVAL_DAYS: i32 = 36_500;
+const MIN_STABILITY: f64 = 0.1;
+
+/// FSRS ratings. The review buttons send SM-2 grades: 0-2 are Again, 3 Hard,
+/// 4 Good, and 5 Easy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Rating {
+    Again = 1,
+    Hard = 2,
+    Good = 3,
+    Easy = 4,
+}
+
+impl Rating {
+    fn from_grade(grade: Grade) -> Self {
+        match grade.value() {
+            0..=2 => Rating::Again,
+            3 => Rating::Hard,
+            4 => Rating::Good,
+            _ => Rating::Easy,
+        }
+    }
+
+    fn value(self) -> f64 {
+        self as u8 as f64
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Fsrs {
+    pub weights: [f64; 17],
+    /// Recall probability the next review is timed for.
+    pub desired_retention: f64,
+}
+
+impl Default for Fsrs {
+    fn default() -> Self {
+        Self {
+            weights: DEFAULT_WEIGHTS,
+            desired_retention: DEFAULT_RETENTION,
+        }
+    }
+}
+
+impl Fsrs {
+    fn initial_stability(&self, rating: Rating) -> f64 {
+        self.weights[rating as usize - 1].max(MIN_STABILITY)
+    }
+
+    fn initial_difficulty(&self, rating: Rating) -> f64 {
+        let w = &self.weights;
+        (w[4] - (rating.value() - 3.0) * w[5]).clamp(1.0, 10.0)
+    }
+
+    /// Probability of recall `elapsed_days` after a review left `stability`.
+    fn retrievability(elapsed_days: f64, stability: f64) -> f64 {
+        1.0 / (1.0 + elapsed_days / (9.0 * stability))
+    }
+
+    fn interval_days(&self, stability: f64) -> i32 {
+        let days = 9.0 * stability * (1.0 / self.desired_retention - 1.0);
+        (days.round() as i32).clamp(1, MAX_INTERVAL_DAYS)
+    }
+
+    /// Moves difficulty by the rating, then pulls it slightly back towards
+    /// the difficulty of a first "Good" so it cannot drift to an extreme.
+    fn next_difficulty(&self, difficulty: f64, rating: Rating) -> f64 {
+        let w = &self.weights;
+        let moved = difficulty - w[6] * (rating.value() - 3.0);
+        (w[7] * self.initial_difficulty(Rating::Good) + (1.0 - w[7]) * moved).clamp(1.0, 10.0)
+    }
+
+    fn recall_stability(
+        &self,
+        difficulty: f64,
+        stability: f64,
+        retrievability: f64,
+        rating: Rating,
+    ) -> f64 {
+        let w = &self.weights;
+        let hard_penalty = if rating == Rating::Hard { w[15] } else { 1.0 };
+        let easy_bonus = if rating == Rating::Easy { w[16] } else { 1.0 };
+        stability
+            * (1.0
+                + w[8].exp()
+                    * (11.0 - difficulty)
+                    * stability.powf(-w[9])
+                    * ((w[10] * (1.0 - retrievability)).exp() - 1.0)
+                    * hard_penalty
+                    * easy_bonus)
+    }
+
+    /// Stability after a lapse; never more than before it.
+    fn forget_stability(&self, difficulty: f64, stability: f64, retrievability: f64) -> f64 {
+        let w = &self.weights;
+        let relearned = w[11]
+            * difficulty.powf(-w[12])
+            * ((stability + 1.0).powf(w[13]) - 1.0)
+            * (w[14] * (1.0 - retrievability)).exp();
+        relearned.min(stability).max(MIN_STABILITY)
+    }
+
+    /// Memory state of a card reviewed before, estimated from its SM-2 state
+    /// when it has never been scheduled by FSRS: the interval stands in for
+    /// stability, and ease 1.3 (hardest) to 3.3 maps onto difficulty 10 to 1.
+    fn memory_state(card: &Card) -> (f64, f64) {
+        match (card.stability, card.difficulty) {
+            (Some(stability), Some(difficulty)) => (stability, difficulty),
+            _ => (
+                (card.interval_days as f64).max(MIN_STABILITY),
+                (10.0 - (card.ease - 1.3) * 4.5).clamp(1.0, 10.0),
+            ),
+        }
+    }
+
+    fn is_new(card: &Card) -> bool {
+        card.stability.is_none() && card.reps == 0 && card.lapses == 0
+    }
+
+    /// Days since the card was last reviewed. Without a recorded review,
+    /// assume the last interval was kept to.
+    fn elapsed_days(card: &Card, now: DateTime<Utc>) -> f64 {
+        card.last_reviewed_at
+            .map(|at| (now - at).num_seconds() as f64 / 86_400.0)
+            .unwrap_or(card.interval_days as f64)
+            .max(0.0)
+    }
+}
+
+impl Scheduler for Fsrs {
+    fn schedule(&self, card: &mut Card, grade: Grade, now: DateTime<Utc>) -> DateTime<Utc> {
+        let rating = Rating::from_grade(grade);
+        let (stability, difficulty) = if Self::is_new(card) {
+            (
+                self.initial_stability(rating),
+                self.initial_difficulty(rating),
+            )
+        } else {
+            let (stability, difficulty) = Self::memory_state(card);
+            let retrievability = Self::retrievability(Self::elapsed_days(card, now), stability);
+            let next_stability = if rating == Rating::Again {
+                self.forget_stability(difficulty, stability, retrievability)
+            } else {
+                self.recall_stability(difficulty, stability, retrievability, rating)
+            };
+            (next_stability, self.next_difficulty(difficulty, rating))
+        };
+
+        if rating == Rating::Again {
+            card.reps = 0;
+            card.lapses += 1;
+        } else {
+            card.reps += 1;
+        }
+        card.stability = Some(stability);
+        card.difficulty = Some(difficulty);
+        card.interval_days = self.interval_days(stability);
+        card.last_reviewed_at = Some(now);
+        card.due_at = now + Duration::days(card.interval_days.into());
+        card.due_at
+    }
+
+    fn explain(&self, card: &Card, grade: Grade, now: DateTime<Utc>) -> ScheduleExplanation {
+        let rating = Rating::from_grade(grade);
+        let mut after = card.clone();
+        let due_at = self.schedule(&mut after, grade, now);
+        let stability = after.stability.unwrap_or(MIN_STABILITY);
+        let difficulty = after.difficulty.unwrap_or(1.0);
+
+        let mut steps = vec![format!("Graded {grade}/5, an FSRS rating of {rating:?}.")];
+        if Self::is_new(card) {
+            steps.push(format!(
+                "First review: stability starts at {stability:.2} days, difficulty at {difficulty:.2}."
+            ));
+        } else {
+            let (stability_before, difficulty_before) = Self::memory_state(card);
+            if card.stability.is_none() || card.difficulty.is_none() {
+                steps.push(
+                    "No FSRS state yet: estimated from the card's SM-2 interval and ease."
+                        .to_string(),
+                );
+            }
+            let elapsed_days = Self::elapsed_days(card, now);
+            steps.push(format!(
+                "{elapsed_days:.1} days since the last review: recall was {:.0}% likely.",
+                Self::retrievability(elapsed_days, stability_before) * 100.0
+            ));
+            if rating == Rating::Again {
+                steps.push(format!(
+                    "A grade below 3 is a lapse: repetitions reset to 0, lapses now {}.",
+                    after.lapses
+                ));
+            }
+            steps.push(format!(
+                "Stability {stability_before:.2} -> {stability:.2} days, difficulty {difficulty_before:.2} -> {difficulty:.2}."
+            ));
+        }
+        steps.push(format!(
+            "Interval {} day(s): when recall is expected to fall to {:.0}%.",
+            after.interval_days,
+            self.desired_retention * 100.0
+        ));
+        steps.push(format!("Next due {}.", due_at.format("%Y-%m-%d %H:%M UTC")));
+
+        ScheduleExplanation {
+            grade,
+            ease_before: card.ease,
+            ease_after: after.ease,
+            interval_before: card.interval_days,
+            interval_after: after.interval_days,
+            reps_after: after.reps,
+            lapses_after: after.lapses,
+            due_at,
+            steps,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::default_new_card;
+    use uuid::Uuid;
+
+    // Expected values follow the FSRS v4 formulas with DEFAULT_WEIGHTS,
+    // worked out independently of this implementation.
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-3,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    fn grade(value: u8) -> Grade {
+        Grade::new(value).unwrap()
+    }
+
+    fn at(days: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000, 0).unwrap() + Duration::days(days)
+    }
+
+    #[test]
+    fn first_review_uses_initial_stability_and_difficulty() {
+        let fsrs = Fsrs::default();
+        for (value, stability, difficulty) in [
+            (1, 0.4, 6.81),
+            (3, 0.6, 5.87),
+            (4, 2.4, 4.93),
+            (5, 5.8, 3.99),
+        ] {
+            let mut card = default_new_card(Uuid::nil(), at(0));
+            fsrs.schedule(&mut card, grade(value), at(0));
+            assert_close(card.stability.unwrap(), stability);
+            assert_close(card.difficulty.unwrap(), difficulty);
+        }
+    }
+
+    #[test]
+    fn first_good_review_is_due_in_two_days() {
+        let mut card = default_new_card(Uuid::nil(), at(0));
+        let due_at = Fsrs::default().schedule(&mut card, grade(4), at(0));
+        assert_eq!(card.interval_days, 2);
+        assert_eq!(due_at, at(2));
+    }
+
+    #[test]
+    fn recall_updates_stability_and_difficulty() {
+        let fsrs = Fsrs::default();
+        for (value, stability, difficulty) in
+            [(3, 3.7751, 5.7814), (4, 7.1416, 4.93), (5, 14.7757, 4.0786)]
+        {
+            let mut card = default_new_card(Uuid::nil(), at(0));
+            fsrs.schedule(&mut card, grade(4), at(0));
+            fsrs.schedule(&mut card, grade(value), at(2));
+            assert_close(card.stability.unwrap(), stability);
+            assert_close(card.difficulty.unwrap(), difficulty);
+        }
+    }
+
+    #[test]
+    fn lapse_shrinks_stability_and_raises_difficulty() {
+        let fsrs = Fsrs::default();
+        let mut card = default_new_card(Uuid::nil(), at(0));
+        fsrs.schedule(&mut card, grade(4), at(0));
+        fsrs.schedule(&mut card, grade(4), at(2));
+        fsrs.schedule(&mut card, grade(1), at(12));
+        assert_close(card.stability.unwrap(), 2.4805);
+        assert_close(card.difficulty.unwrap(), 6.6328);
+        assert_eq!((card.reps, card.lapses), (0, 1));
+    }
+}
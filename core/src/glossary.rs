@@ -0,0 +1,65 @@
+//! The learner's glossary: preferred translations for terms a translation
+//! API keeps rendering differently, such as a course's own vocabulary. An
+//! entry replaces the machine translation whenever the whole text being
+//! translated is its term, compared without case, surrounding punctuation,
+//! or extra spaces.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::Language;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GlossaryEntry {
+    /// As the learner typed it.
+    pub term: String,
+    /// Language of `term`; the translation is into the other language of
+    /// the pair.
+    pub language: Language,
+    pub translation: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Entries looked up by [`glossary_key`].
+#[derive(Debug, Clone, Default)]
+pub struct Glossary {
+    entries: Vec<GlossaryEntry>,
+}
+
+impl Glossary {
+    pub fn new(entries: Vec<GlossaryEntry>) -> Self {
+        Self { entries }
+    }
+
+    pub fn entries(&self) -> &[GlossaryEntry] {
+        &self.entries
+    }
+
+    pub fn lookup(&self, text: &str, language: &Language) -> Option<&GlossaryEntry> {
+        let key = glossary_key(text);
+        if key.is_empty() {
+            return None;
+        }
+        self.entries
+            .iter()
+            .find(|entry| entry.language == *language && glossary_key(&entry.term) == key)
+    }
+
+    /// The glossary's translation of `text` when it has one, else
+    /// `machine`, the translation API's answer.
+    pub fn apply(&self, text: &str, language: &Language, machine: String) -> String {
+        self.lookup(text, language)
+            .map_or(machine, |entry| entry.translation.clone())
+    }
+}
+
+/// What entries are matched and stored by: lowercase, single-spaced, and
+/// without punctuation at either end ("  Het Huis! " -> "het huis").
+pub fn glossary_key(term: &str) -> String {
+    term.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_matches(|c: char| c.is_ascii_punctuation())
+        .trim()
+        .to_lowercase()
+}
@@ -0,0 +1,148 @@
+//! Dry-run statistics shown before an import is confirmed. Every importer
+//! feeds its parsed items through an [`ImportPlanBuilder`], so the numbers
+//! mean the same thing whether the words came from a photo or a bundle.
+//! After the fact, [`group_additions`] sorts recently added words back into
+//! the imports they came from, so they can be checked before reviews.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{SourceKind, Word};
+
+/// What an import would do, worked out before anything is written.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportPlan {
+    /// Items parsed from the source.
+    pub items: usize,
+    /// Items that would become new words and cards.
+    pub new_words: usize,
+    /// Items skipped because the word is already saved or appears earlier in
+    /// the same import.
+    pub duplicates: usize,
+    /// New words without a translation yet.
+    pub missing_translation: usize,
+    /// Groups the new words fall into, in order of first appearance.
+    pub groups: Vec<String>,
+    /// New cards introduced per day under the current limit; `None` when
+    /// there is no limit and every card is served right away.
+    pub new_per_day: Option<usize>,
+    /// Days until the last imported card has been introduced, counting new
+    /// cards already waiting ahead of it.
+    pub days_to_introduce: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct ImportPlanBuilder {
+    plan: ImportPlan,
+    seen: HashSet<String>,
+    backlog: usize,
+}
+
+impl ImportPlanBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The new-card limit from the session settings.
+    pub fn new_per_day(mut self, limit: Option<usize>) -> Self {
+        self.plan.new_per_day = limit.filter(|limit| *limit > 0);
+        self
+    }
+
+    /// New cards already in the deck that will be introduced first.
+    pub fn backlog(mut self, new_cards: usize) -> Self {
+        self.backlog = new_cards;
+        self
+    }
+
+    /// Counts one parsed item. `saved` says whether the deck already has the
+    /// word; repeats within the import are caught here. Returns whether the
+    /// item would be imported.
+    pub fn add(
+        &mut self,
+        text: &str,
+        translation: Option<&str>,
+        group: Option<&str>,
+        saved: bool,
+    ) -> bool {
+        self.plan.items += 1;
+        let key = text.trim().to_lowercase();
+        if saved || key.is_empty() || !self.seen.insert(key) {
+            self.plan.duplicates += 1;
+            return false;
+        }
+        self.plan.new_words += 1;
+        if translation.is_none_or(|translation| translation.trim().is_empty()) {
+            self.plan.missing_translation += 1;
+        }
+        if let Some(group) = group.map(str::trim).filter(|group| !group.is_empty())
+            && !self.plan.groups.iter().any(|known| known == group)
+        {
+            self.plan.groups.push(group.to_string());
+        }
+        true
+    }
+
+    pub fn build(self) -> ImportPlan {
+        let mut plan = self.plan;
+        plan.days_to_introduce = match (plan.new_words, plan.new_per_day) {
+            (0, _) => 0,
+            (_, None) => 1,
+            (new_words, Some(limit)) => (self.backlog + new_words).div_ceil(limit),
+        };
+        plan
+    }
+}
+
+/// Recently added words that share a source and chapter, usually one import.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdditionGroup {
+    /// `None` for words saved without a recorded source.
+    pub source: Option<SourceKind>,
+    /// The image, file, or page the words came from.
+    pub source_name: Option<String>,
+    pub chapter: Option<String>,
+    /// Oldest first, the order they were added in.
+    pub words: Vec<Word>,
+}
+
+impl AdditionGroup {
+    /// "Photo IMG_2041.jpg • Hoofdstuk 3", for headers.
+    pub fn label(&self) -> String {
+        let source = match (&self.source, &self.source_name) {
+            (Some(kind), Some(name)) => format!("{kind:?} {name}"),
+            (Some(kind), None) => format!("{kind:?}"),
+            (None, _) => "No source".to_string(),
+        };
+        let chapter = self.chapter.as_deref().unwrap_or("Unassigned");
+        format!("{source} • {chapter}")
+    }
+}
+
+/// Groups `words` by source (kind and name) and chapter. Groups come most
+/// recently added first; words within a group oldest first.
+pub fn group_additions(mut words: Vec<Word>) -> Vec<AdditionGroup> {
+    words.sort_by_key(|word| word.created_at);
+    let mut groups: Vec<AdditionGroup> = Vec::new();
+    for word in words {
+        let source = word.source.as_ref().map(|source| source.kind);
+        let source_name = word.source.as_ref().and_then(|source| source.name.clone());
+        let existing = groups.iter_mut().find(|group| {
+            group.source == source
+                && group.source_name == source_name
+                && group.chapter == word.chapter
+        });
+        match existing {
+            Some(group) => group.words.push(word),
+            None => groups.push(AdditionGroup {
+                source,
+                source_name,
+                chapter: word.chapter.clone(),
+                words: vec![word],
+            }),
+        }
+    }
+    groups.sort_by_key(|group| std::cmp::Reverse(group.words.last().map(|word| word.created_at)));
+    groups
+}
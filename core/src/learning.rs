@@ -0,0 +1,142 @@
+//! Learning steps for new cards. A card's first answers are spaced minutes
+//! apart rather than days: each correct answer moves it to the next step
+//! (1m, then 10m by default) and a correct answer on the last step, or an
+//! easy one on any, graduates it to the scheduler's first interval. A wrong
+//! answer starts the steps over.
+//!
+//! The step a card is on and when it is next due are kept on the card as
+//! `learning_step` and `learning_due_at`. `due_at` follows `learning_due_at`
+//! while the card is learning, so the usual due-card queries find it.
+
+use std::fmt;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{Card, Grade, is_unreviewed};
+
+/// Most steps a card can be taken through before it graduates.
+pub const MAX_LEARNING_STEPS: usize = 10;
+
+/// A delay between learning answers, written as minutes, hours, or days
+/// ("10m", "1h", "1d").
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct LearningStep {
+    minutes: u32,
+}
+
+impl LearningStep {
+    pub const fn minutes(minutes: u32) -> Self {
+        Self { minutes }
+    }
+
+    /// `None` for anything but a positive whole number followed by `m`,
+    /// `h`, or `d`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let unit = text.chars().last()?;
+        let scale = match unit.to_ascii_lowercase() {
+            'm' => 1,
+            'h' => 60,
+            'd' => 24 * 60,
+            _ => return None,
+        };
+        let count: u32 = text[..text.len() - unit.len_utf8()].trim().parse().ok()?;
+        let minutes = count.checked_mul(scale)?;
+        (minutes > 0).then_some(Self { minutes })
+    }
+
+    pub fn duration(self) -> Duration {
+        Duration::minutes(self.minutes.into())
+    }
+}
+
+impl fmt::Display for LearningStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const DAY: u32 = 24 * 60;
+        if self.minutes.is_multiple_of(DAY) {
+            write!(f, "{}d", self.minutes / DAY)
+        } else if self.minutes.is_multiple_of(60) {
+            write!(f, "{}h", self.minutes / 60)
+        } else {
+            write!(f, "{}m", self.minutes)
+        }
+    }
+}
+
+impl TryFrom<String> for LearningStep {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        Self::parse(&text).ok_or_else(|| {
+            format!(
+                "invalid learning step '{text}'; use minutes, hours, or days such as 10m, 1h, 1d"
+            )
+        })
+    }
+}
+
+impl From<LearningStep> for String {
+    fn from(step: LearningStep) -> Self {
+        step.to_string()
+    }
+}
+
+pub fn default_learning_steps() -> Vec<LearningStep> {
+    vec![LearningStep::minutes(1), LearningStep::minutes(10)]
+}
+
+/// Why `steps` cannot be used, if they cannot. No steps at all is fine and
+/// sends new cards straight to the scheduler.
+pub fn learning_steps_problem(steps: &[LearningStep]) -> Option<String> {
+    if steps.len() > MAX_LEARNING_STEPS {
+        return Some(format!(
+            "At most {MAX_LEARNING_STEPS} learning steps are allowed"
+        ));
+    }
+    if steps.windows(2).any(|pair| pair[0] > pair[1]) {
+        return Some("Learning steps must not get shorter".to_string());
+    }
+    None
+}
+
+/// Whether `card`'s next answer is a learning step rather than a review for
+/// the scheduler: it is partway through the steps, or has never been
+/// answered and there are steps to take it through.
+pub fn is_learning(card: &Card, steps: &[LearningStep]) -> bool {
+    card.learning_step.is_some() || (!steps.is_empty() && is_unreviewed(card))
+}
+
+/// Moves a learning card along `steps` for `grade` and returns when it is
+/// due again. `None` when it graduates, leaving the card for the scheduler
+/// to take from here.
+pub fn schedule_learning(
+    card: &mut Card,
+    grade: Grade,
+    now: DateTime<Utc>,
+    steps: &[LearningStep],
+) -> Option<DateTime<Utc>> {
+    // A card that was never answered is on the first step.
+    let next = if !grade.is_correct() {
+        Some(0)
+    } else if grade.value() == Grade::MAX {
+        None
+    } else {
+        Some(card.learning_step.map_or(1, |step| step as usize + 1))
+    };
+    match next.and_then(|next| Some((next, steps.get(next)?))) {
+        Some((next, step)) => {
+            let due_at = now + step.duration();
+            card.learning_step = Some(next as u32);
+            card.learning_due_at = Some(due_at);
+            card.due_at = due_at;
+            Some(due_at)
+        }
+        None => {
+            card.learning_step = None;
+            card.learning_due_at = None;
+            None
+        }
+    }
+}
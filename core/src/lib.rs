@@ -1,11 +1,124 @@
 use chrono::{DateTime, Duration, Utc};
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
-pub enum Language {
-    Dutch,
-    English,
+pub mod answer_check;
+pub mod cloze;
+pub mod fsrs;
+pub mod glossary;
+pub mod import;
+pub mod learning;
+pub mod rules;
+pub mod search;
+pub mod session;
+pub mod stats;
+
+/// A language as a BCP-47 tag ("nl", "pt-BR"), which is also how it is
+/// serialized. The `language` column keeps the names rows were saved with
+/// before other languages existed; see [`Language::to_column`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub struct Language(String);
+
+impl Language {
+    /// Reads a tag or one of the stored names, in any case. The primary
+    /// subtag is lowercased and a region uppercased ("EN-gb" -> "en-GB").
+    pub fn new(tag: &str) -> Self {
+        let tag = tag.trim();
+        if let Some(rules) = rules::ALL
+            .iter()
+            .find(|rules| rules.name().eq_ignore_ascii_case(tag))
+        {
+            return Self(rules.code().to_string());
+        }
+        let mut parts = tag.split(['-', '_']).filter(|part| !part.is_empty());
+        let mut normalized = parts.next().unwrap_or("und").to_lowercase();
+        for part in parts {
+            normalized.push('-');
+            normalized.push_str(&if part.len() == 2 {
+                part.to_uppercase()
+            } else {
+                part.to_string()
+            });
+        }
+        Self(normalized)
+    }
+
+    pub fn dutch() -> Self {
+        Self::new("nl")
+    }
+
+    pub fn english() -> Self {
+        Self::new("en")
+    }
+
+    /// The full BCP-47 tag.
+    pub fn code(&self) -> &str {
+        &self.0
+    }
+
+    /// The language subtag without region or script ("pt" for "pt-BR").
+    pub fn primary(&self) -> &str {
+        self.0.split('-').next().unwrap_or(&self.0)
+    }
+
+    /// Label for the UI and the `language` column: the rules' name ("Dutch")
+    /// for a language with rules of its own, else the tag.
+    pub fn to_column(&self) -> String {
+        match rules::for_name(&self.0) {
+            Some(rules) if rules.code() == self.0 => rules.name().to_string(),
+            _ => self.0.clone(),
+        }
+    }
+}
+
+impl From<String> for Language {
+    fn from(value: String) -> Self {
+        Self::new(&value)
+    }
+}
+
+impl From<Language> for String {
+    fn from(language: Language) -> Self {
+        language.0
+    }
+}
+
+impl std::fmt::Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_column())
+    }
+}
+
+/// The language being learned and the one it is translated into. Stored in
+/// config; defaults to Dutch into English.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LanguagePair {
+    pub source: Language,
+    pub target: Language,
+}
+
+impl Default for LanguagePair {
+    fn default() -> Self {
+        Self {
+            source: Language::dutch(),
+            target: Language::english(),
+        }
+    }
+}
+
+impl LanguagePair {
+    /// The language a word in `language` is translated into: the target for
+    /// source words and the source for anything else.
+    pub fn other(&self, language: &Language) -> &Language {
+        if *language == self.source {
+            &self.target
+        } else {
+            &self.source
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +131,170 @@ pub struct Word {
     pub language: Language,
     pub notes: Option<String>,
     pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub source: Option<WordSource>,
+    /// Inflected forms attached to this entry instead of saved as words of
+    /// their own (e.g. "loopt", "liep" on "lopen").
+    #[serde(default)]
+    pub forms: Vec<String>,
+    /// Other translations that count as correct, e.g. "harbor" next to
+    /// "harbour" or "walk" next to "to walk".
+    #[serde(default)]
+    pub alt_answers: Vec<String>,
+    #[serde(default)]
+    pub kind: EntryKind,
+    /// The deck the word belongs to, such as one textbook; `None` for
+    /// words outside every deck.
+    #[serde(default)]
+    pub deck_id: Option<Uuid>,
+}
+
+/// Words are flipped and self-graded; sentences show the English and are
+/// answered by writing the Dutch, which the grade-sentence endpoint scores.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum EntryKind {
+    #[default]
+    Word,
+    Sentence,
+}
+
+impl EntryKind {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "Word" => Some(EntryKind::Word),
+            "Sentence" => Some(EntryKind::Sentence),
+            _ => None,
+        }
+    }
+
+    /// Mined text with at least three words and closing punctuation is a
+    /// sentence; anything else is a word or phrase.
+    pub fn detect(text: &str) -> Self {
+        let text = text.trim();
+        if text.split_whitespace().count() >= 3 && text.ends_with(['.', '!', '?']) {
+            EntryKind::Sentence
+        } else {
+            EntryKind::Word
+        }
+    }
+}
+
+/// What a card asks. Basic cards show the Dutch and ask for the
+/// translation; reverse cards show the translation and ask for the Dutch;
+/// cloze cards blank the word out of one of its example sentences, see
+/// [`cloze`]. Cards stored without a type are basic.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CardType {
+    #[default]
+    Basic,
+    Reverse,
+    Cloze,
+}
+
+impl CardType {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "Basic" => Some(CardType::Basic),
+            "Reverse" => Some(CardType::Reverse),
+            "Cloze" => Some(CardType::Cloze),
+            _ => None,
+        }
+    }
+
+    pub fn from_column(value: Option<&str>) -> Self {
+        value.and_then(Self::parse).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SourceKind {
+    Manual,
+    Clipboard,
+    Photo,
+    /// Sent by a browser extension or script through `/data/capture`.
+    Web,
+    /// Imported from a spreadsheet's CSV export; `page` is the row.
+    Csv,
+}
+
+impl SourceKind {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "Manual" => Some(SourceKind::Manual),
+            "Clipboard" => Some(SourceKind::Clipboard),
+            "Photo" => Some(SourceKind::Photo),
+            "Web" => Some(SourceKind::Web),
+            "Csv" => Some(SourceKind::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// Where a word was captured, so a disputed translation can be checked
+/// against the original page.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WordSource {
+    pub kind: SourceKind,
+    /// Image or file name the word was read from.
+    pub name: Option<String>,
+    /// Page or chapter label within the source.
+    pub page: Option<String>,
+    pub captured_at: DateTime<Utc>,
+    /// Where on the image the word was printed, when OCR reported it.
+    #[serde(default)]
+    pub region: Option<SourceRegion>,
+}
+
+/// Bounding box as fractions of the image size, origin at the top left.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct SourceRegion {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+impl SourceRegion {
+    /// Parses the `x,y,w,h` form stored in the `source_region` column.
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.split(',').map(|part| part.trim().parse::<f32>());
+        let region = Self {
+            x: parts.next()?.ok()?,
+            y: parts.next()?.ok()?,
+            w: parts.next()?.ok()?,
+            h: parts.next()?.ok()?,
+        };
+        parts.next().is_none().then_some(region)
+    }
+
+    pub fn to_column(&self) -> String {
+        format!("{},{},{},{}", self.x, self.y, self.w, self.h)
+    }
+}
+
+impl WordSource {
+    /// Rebuilds a source from the `source_type`, `source_name`, `source_page`,
+    /// `captured_at` and `source_region` columns. Words saved before provenance
+    /// existed have no type and yield `None`.
+    pub fn from_columns(
+        kind: Option<String>,
+        name: Option<String>,
+        page: Option<String>,
+        captured_at: Option<String>,
+        region: Option<String>,
+    ) -> Option<Self> {
+        let kind = SourceKind::parse(kind.as_deref()?)?;
+        let captured_at = DateTime::parse_from_rfc3339(captured_at.as_deref()?)
+            .ok()?
+            .with_timezone(&Utc);
+        Some(Self {
+            kind,
+            name,
+            page,
+            captured_at,
+            region: region.as_deref().and_then(SourceRegion::parse),
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,6 +306,22 @@ pub struct Card {
     pub ease: f64,
     pub reps: i32,
     pub lapses: i32,
+    /// FSRS memory state; `None` until FSRS first schedules the card.
+    #[serde(default)]
+    pub stability: Option<f64>,
+    #[serde(default)]
+    pub difficulty: Option<f64>,
+    #[serde(default)]
+    pub last_reviewed_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub card_type: CardType,
+    /// The learning step a new card is on; `None` before its first answer
+    /// and once it has graduated. See [`learning`].
+    #[serde(default)]
+    pub learning_step: Option<u32>,
+    /// When a card in its learning steps is next due.
+    #[serde(default)]
+    pub learning_due_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,8 +336,69 @@ pub struct Review {
 pub struct SessionConfig {
     pub max_cards: usize,
     pub max_new_cards: usize,
+    /// Ends the session once this many answers were correct; 0 never does.
     pub stop_after_correct: usize,
     pub max_minutes: Option<u64>,
+    #[serde(default)]
+    pub order: CardOrder,
+}
+
+/// Where new cards go in a session's queue relative to the reviews
+/// (learning and due cards).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CardOrder {
+    /// Spread evenly between the reviews.
+    #[default]
+    Spread,
+    /// All new cards before the first review.
+    NewFirst,
+    /// All reviews before the first new card.
+    DueFirst,
+    /// One new card after every `due_per_new` reviews, so 3 serves three
+    /// reviews per new card. Once either kind runs out the other follows.
+    Mixed { due_per_new: usize },
+}
+
+/// Running tally of a review session, checked against its correct-answer
+/// goal (see [`SessionConfig::stop_after_correct`]) after every grade.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionProgress {
+    pub reviewed: usize,
+    pub correct: usize,
+    pub goal: Option<usize>,
+}
+
+impl SessionProgress {
+    pub fn new(goal: Option<usize>) -> Self {
+        Self {
+            goal: goal.filter(|goal| *goal > 0),
+            ..Self::default()
+        }
+    }
+
+    /// Counts one graded answer (grade 3 or above is correct). Returns `true`
+    /// only for the answer that reaches the goal.
+    pub fn record(&mut self, grade: Grade) -> bool {
+        let was_reached = self.goal_reached();
+        self.reviewed += 1;
+        if grade.is_correct() {
+            self.correct += 1;
+        }
+        !was_reached && self.goal_reached()
+    }
+
+    /// Takes back an answer counted by [`SessionProgress::record`].
+    pub fn undo(&mut self, grade: Grade) {
+        self.reviewed = self.reviewed.saturating_sub(1);
+        if grade.is_correct() {
+            self.correct = self.correct.saturating_sub(1);
+        }
+    }
+
+    pub fn goal_reached(&self) -> bool {
+        self.goal.is_some_and(|goal| self.correct >= goal)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -60,6 +414,7 @@ impl Default for SessionConfig {
             max_new_cards: 10,
             stop_after_correct: 15,
             max_minutes: None,
+            order: CardOrder::Spread,
         }
     }
 }
@@ -73,6 +428,179 @@ impl Default for ApiConfig {
     }
 }
 
+/// Settings key holding [`UiPrefs`], shared by the GUI and the TUI.
+pub const UI_PREFS_KEY: &str = "ui";
+/// Smallest and largest allowed [`UiPrefs::font_scale`].
+pub const FONT_SCALE_RANGE: (f32, f32) = (0.75, 2.0);
+
+/// Accessibility preferences stored in the `ui` setting. The terminal has no
+/// font size, so the TUI reads a larger scale as extra line spacing and high
+/// contrast as bold text.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct UiPrefs {
+    #[serde(default = "default_font_scale")]
+    pub font_scale: f32,
+    #[serde(default)]
+    pub high_contrast: bool,
+    #[serde(default)]
+    pub reduce_motion: bool,
+}
+
+fn default_font_scale() -> f32 {
+    1.0
+}
+
+impl Default for UiPrefs {
+    fn default() -> Self {
+        Self {
+            font_scale: default_font_scale(),
+            high_contrast: false,
+            reduce_motion: false,
+        }
+    }
+}
+
+impl UiPrefs {
+    /// Blank lines between list rows in the TUI.
+    pub fn line_spacing(&self) -> usize {
+        if self.font_scale >= 1.25 { 1 } else { 0 }
+    }
+
+    pub fn bold_text(&self) -> bool {
+        self.high_contrast
+    }
+}
+
+/// Settings key holding [`CardSettings`], shared by the GUI and the TUI.
+pub const CARD_SETTINGS_KEY: &str = "cards";
+
+/// Which cards a new word gets, stored in the `cards` setting.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CardSettings {
+    /// Also create a reverse card for words with a translation. Sentences
+    /// are already asked from the translation and never get one.
+    #[serde(default)]
+    pub reverse: bool,
+    /// Also create a cloze card for words once one of their example
+    /// sentences contains them.
+    #[serde(default)]
+    pub cloze: bool,
+}
+
+impl CardSettings {
+    pub fn wants_reverse(&self, kind: EntryKind, translation: Option<&str>) -> bool {
+        self.reverse && has_reverse_side(kind, translation)
+    }
+}
+
+/// Whether a word can be asked the other way round: it is a word rather than
+/// a sentence, and has a translation to show.
+pub fn has_reverse_side(kind: EntryKind, translation: Option<&str>) -> bool {
+    kind == EntryKind::Word && translation.is_some_and(|translation| !translation.trim().is_empty())
+}
+
+/// Every answer accepted for a word: each slash-separated part of the
+/// translation followed by the alternate answers, without duplicates.
+pub fn accepted_answers(translation: Option<&str>, alt_answers: &[String]) -> Vec<String> {
+    let mut answers: Vec<String> = Vec::new();
+    let parts = translation
+        .into_iter()
+        .flat_map(|translation| translation.split('/'))
+        .chain(alt_answers.iter().map(String::as_str));
+    for part in parts {
+        let part = part.trim();
+        if !part.is_empty() && !answers.iter().any(|known| known.eq_ignore_ascii_case(part)) {
+            answers.push(part.to_string());
+        }
+    }
+    answers
+}
+
+/// A review grade on the SM-2 scale, from 0 (complete blackout) to 5
+/// (perfect recall). Out-of-range values are rejected, also when
+/// deserializing, rather than clamped: a client sending 255 has a bug that
+/// clamping would hide by scoring it as a perfect answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub struct Grade(u8);
+
+impl Grade {
+    pub const MAX: u8 = 5;
+
+    pub fn new(value: u8) -> Result<Self, GradeError> {
+        if value > Self::MAX {
+            return Err(GradeError(value));
+        }
+        Ok(Self(value))
+    }
+
+    pub fn value(self) -> u8 {
+        self.0
+    }
+
+    /// Grades of 3 and above count as remembered; lower ones are lapses.
+    pub fn is_correct(self) -> bool {
+        self.0 >= 3
+    }
+}
+
+impl TryFrom<u8> for Grade {
+    type Error = GradeError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl From<Grade> for u8 {
+    fn from(grade: Grade) -> Self {
+        grade.0
+    }
+}
+
+impl std::fmt::Display for Grade {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A grade outside 0–5, holding the rejected value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GradeError(pub u8);
+
+impl std::fmt::Display for GradeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "grade must be between 0 and {}, got {}",
+            Grade::MAX,
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for GradeError {}
+
+/// Maps a grade-sentence score (1-10) onto the SM-2 grade scale used by the
+/// review buttons, so a score below 6 counts as a lapse.
+pub fn grade_from_sentence_score(score: u8) -> Grade {
+    Grade(match score {
+        0..=3 => 1,
+        4..=5 => 2,
+        6..=7 => 3,
+        8..=9 => 4,
+        _ => 5,
+    })
+}
+
+/// 64-bit FNV-1a. Stable across builds and platforms, unlike
+/// `std::hash::DefaultHasher`, so it suits cache keys and persisted seeds.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
 pub fn default_new_card(word_id: Uuid, now: DateTime<Utc>) -> Card {
     Card {
         id: Uuid::new_v4(),
@@ -82,29 +610,559 @@ pub fn default_new_card(word_id: Uuid, now: DateTime<Utc>) -> Card {
         ease: 2.5,
         reps: 0,
         lapses: 0,
+        stability: None,
+        difficulty: None,
+        last_reviewed_at: None,
+        card_type: CardType::Basic,
+        learning_step: None,
+        learning_due_at: None,
+    }
+}
+
+/// A spaced-repetition algorithm: updates `card` for a review graded
+/// `grade` at `now` and returns when it is next due.
+pub trait Scheduler {
+    fn schedule(&self, card: &mut Card, grade: Grade, now: DateTime<Utc>) -> DateTime<Utc>;
+
+    /// Step-by-step account of what [`Scheduler::schedule`] would do to
+    /// `card`, leaving it unchanged.
+    fn explain(&self, card: &Card, grade: Grade, now: DateTime<Utc>) -> ScheduleExplanation;
+}
+
+/// [`schedule_sm2`] with the given parameters as a [`Scheduler`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sm2(pub Sm2Params);
+
+impl Scheduler for Sm2 {
+    fn schedule(&self, card: &mut Card, grade: Grade, now: DateTime<Utc>) -> DateTime<Utc> {
+        schedule_sm2(card, grade, now, &self.0)
     }
+
+    fn explain(&self, card: &Card, grade: Grade, now: DateTime<Utc>) -> ScheduleExplanation {
+        explain_sm2(card, grade, now, &self.0)
+    }
+}
+
+/// Tuning for [`schedule_sm2`]. The defaults are classic SM-2: ease starts
+/// at 2.5 and never drops below 1.3, a lapse restarts at 1 day, and nothing
+/// is capped or fuzzed.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Sm2Params {
+    /// Ease of a card on its first scheduler review, after any learning steps.
+    pub starting_ease: f64,
+    pub minimum_ease: f64,
+    /// Interval after the first correct answer in a row.
+    pub first_interval_days: i32,
+    /// Interval after the second correct answer in a row.
+    pub second_interval_days: i32,
+    /// Share of the interval kept after a lapse; 0 restarts at 1 day.
+    pub lapse_interval_multiplier: f64,
+    pub max_interval_days: i32,
+    /// Intervals longer than [`FUZZ_MIN_DAYS`] move by up to this percentage
+    /// either way, so cards learned together do not stay due together.
+    pub fuzz_percent: u8,
 }
 
-pub fn schedule_sm2(card: &mut Card, grade: u8, now: DateTime<Utc>) -> DateTime<Utc> {
-    let clamped = grade.min(5);
-    let quality = clamped as f32;
+/// Intervals this short are never fuzzed.
+pub const FUZZ_MIN_DAYS: i32 = 2;
+/// Largest [`Sm2Params::fuzz_percent`].
+pub const MAX_FUZZ_PERCENT: u8 = 25;
 
+impl Default for Sm2Params {
+    fn default() -> Self {
+        Self {
+            starting_ease: 2.5,
+            minimum_ease: 1.3,
+            first_interval_days: 1,
+            second_interval_days: 6,
+            lapse_interval_multiplier: 0.0,
+            max_interval_days: 36_500,
+            fuzz_percent: 0,
+        }
+    }
+}
+
+impl Sm2Params {
+    /// Field name and message for every out-of-range parameter; empty when
+    /// the parameters can be used as they are.
+    pub fn problems(&self) -> Vec<(&'static str, String)> {
+        let mut problems = Vec::new();
+        if !(1.3..=5.0).contains(&self.minimum_ease) {
+            problems.push((
+                "minimum_ease",
+                "Minimum ease must be between 1.3 and 5.0".to_string(),
+            ));
+        }
+        if !(self.minimum_ease..=5.0).contains(&self.starting_ease) {
+            problems.push((
+                "starting_ease",
+                "Starting ease must be between the minimum ease and 5.0".to_string(),
+            ));
+        }
+        if self.first_interval_days < 1 {
+            problems.push((
+                "first_interval_days",
+                "First interval must be at least 1 day".to_string(),
+            ));
+        }
+        if self.second_interval_days < self.first_interval_days {
+            problems.push((
+                "second_interval_days",
+                "Second interval must be at least the first".to_string(),
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.lapse_interval_multiplier) {
+            problems.push((
+                "lapse_interval_multiplier",
+                "Lapse interval multiplier must be between 0 and 1".to_string(),
+            ));
+        }
+        if self.max_interval_days < self.second_interval_days {
+            problems.push((
+                "max_interval_days",
+                "Maximum interval must be at least the second interval".to_string(),
+            ));
+        }
+        if self.fuzz_percent > MAX_FUZZ_PERCENT {
+            problems.push((
+                "fuzz_percent",
+                format!("Interval fuzz must be at most {MAX_FUZZ_PERCENT}%"),
+            ));
+        }
+        problems
+    }
+
+    /// `interval_days` moved by up to [`Sm2Params::fuzz_percent`] either
+    /// way. The offset comes from the card and its repetition count, so
+    /// replaying a review lands on the same day.
+    fn fuzz(&self, card: &Card, interval_days: i32) -> i32 {
+        if self.fuzz_percent == 0 || interval_days <= FUZZ_MIN_DAYS {
+            return interval_days;
+        }
+        let spread =
+            (f64::from(interval_days) * f64::from(self.fuzz_percent) / 100.0).round() as i32;
+        if spread == 0 {
+            return interval_days;
+        }
+        let seed = fnv1a(&[&card.id.as_bytes()[..], &card.reps.to_le_bytes()].concat());
+        let offset = (seed % (2 * spread as u64 + 1)) as i32 - spread;
+        interval_days + offset
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SchedulerKind {
+    #[default]
+    Sm2,
+    Fsrs,
+}
+
+impl SchedulerKind {
+    /// `sm2` tunes SM-2 and is ignored by FSRS.
+    pub fn scheduler(self, sm2: Sm2Params) -> Box<dyn Scheduler> {
+        match self {
+            SchedulerKind::Sm2 => Box::new(Sm2(sm2)),
+            SchedulerKind::Fsrs => Box::new(fsrs::Fsrs::default()),
+        }
+    }
+}
+
+/// Settings key holding [`SchedulerConfig`].
+pub const SCHEDULER_KEY: &str = "scheduler";
+/// Hours until a lapsed card comes back for relearning, whatever interval
+/// its scheduler gave it.
+pub const RELEARN_DELAY_HOURS: i64 = 2;
+
+/// Which algorithm schedules reviews: `algorithm` for every card, with
+/// per-deck overrides in `decks` keyed by deck id, how SM-2 is tuned, and
+/// the learning steps new cards go through before either algorithm takes
+/// them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchedulerConfig {
+    #[serde(default)]
+    pub algorithm: SchedulerKind,
+    #[serde(default)]
+    pub decks: BTreeMap<String, SchedulerKind>,
+    #[serde(default)]
+    pub sm2: Sm2Params,
+    #[serde(default = "learning::default_learning_steps")]
+    pub learning_steps: Vec<learning::LearningStep>,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            algorithm: SchedulerKind::default(),
+            decks: BTreeMap::new(),
+            sm2: Sm2Params::default(),
+            learning_steps: learning::default_learning_steps(),
+        }
+    }
+}
+
+impl SchedulerConfig {
+    /// The algorithm for cards in the deck with id `deck`; cards outside
+    /// every deck use `algorithm`.
+    pub fn for_deck(&self, deck: Option<&str>) -> SchedulerKind {
+        deck.and_then(|deck| self.decks.get(deck))
+            .copied()
+            .unwrap_or(self.algorithm)
+    }
+
+    /// The scheduler for cards in the deck with id `deck`.
+    pub fn scheduler(&self, deck: Option<&str>) -> Box<dyn Scheduler> {
+        self.for_deck(deck).scheduler(self.sm2)
+    }
+
+    /// Schedules `card` through the learning steps while it is learning,
+    /// and with its deck's scheduler once it graduates. A lapse comes back
+    /// after [`RELEARN_DELAY_HOURS`]; a wrong learning answer already
+    /// restarts the steps.
+    pub fn schedule_card(
+        &self,
+        card: &mut Card,
+        grade: Grade,
+        now: DateTime<Utc>,
+        deck: Option<&str>,
+    ) -> DateTime<Utc> {
+        if learning::is_learning(card, &self.learning_steps)
+            && let Some(due_at) =
+                learning::schedule_learning(card, grade, now, &self.learning_steps)
+        {
+            return due_at;
+        }
+        self.scheduler(deck).schedule(card, grade, now);
+        if !grade.is_correct() {
+            card.due_at = now + Duration::hours(RELEARN_DELAY_HOURS);
+        }
+        card.due_at
+    }
+
+    /// Explains what [`SchedulerConfig::schedule_card`] does to `card` for
+    /// `grade` without mutating it.
+    pub fn explain_card(
+        &self,
+        card: &Card,
+        grade: Grade,
+        now: DateTime<Utc>,
+        deck: Option<&str>,
+    ) -> ScheduleExplanation {
+        let steps = &self.learning_steps;
+        let mut card = card.clone();
+        let mut graduated = false;
+        if learning::is_learning(&card, steps) {
+            let mut after = card.clone();
+            if let Some(due_at) = learning::schedule_learning(&mut after, grade, now, steps) {
+                let step = after.learning_step.unwrap_or(0) as usize;
+                let delay = steps[step];
+                let message = if grade.is_correct() {
+                    format!(
+                        "Learning step {} of {}: due again in {delay}.",
+                        step + 1,
+                        steps.len()
+                    )
+                } else {
+                    format!("A grade below 3 starts the learning steps over: due again in {delay}.")
+                };
+                return ScheduleExplanation {
+                    grade,
+                    ease_before: card.ease,
+                    ease_after: card.ease,
+                    interval_before: card.interval_days,
+                    interval_after: card.interval_days,
+                    reps_after: card.reps,
+                    lapses_after: card.lapses,
+                    due_at,
+                    steps: vec![format!("Graded {grade}/5."), message],
+                };
+            }
+            card = after;
+            graduated = true;
+        }
+        let mut explanation = self.scheduler(deck).explain(&card, grade, now);
+        if graduated {
+            let at = explanation.steps.len().min(1);
+            explanation.steps.insert(
+                at,
+                "The card graduates from its learning steps to the scheduler.".to_string(),
+            );
+        }
+        if !grade.is_correct() {
+            explanation.due_at = now + Duration::hours(RELEARN_DELAY_HOURS);
+            explanation.steps.pop();
+            explanation.steps.push(format!(
+                "Missed cards come back after {RELEARN_DELAY_HOURS} hours for relearning: due {}.",
+                explanation.due_at.format("%Y-%m-%d %H:%M UTC")
+            ));
+        }
+        explanation
+    }
+}
+
+/// Whether `card` has never been through a scheduler, and so starts from
+/// [`Sm2Params::starting_ease`] whatever ease it was created with. Answers
+/// on the learning steps leave `reps` and `lapses` alone, so a card
+/// graduating from them still counts.
+fn is_unreviewed(card: &Card) -> bool {
+    card.reps == 0 && card.lapses == 0
+}
+
+pub fn schedule_sm2(
+    card: &mut Card,
+    grade: Grade,
+    now: DateTime<Utc>,
+    params: &Sm2Params,
+) -> DateTime<Utc> {
+    let quality = grade.value() as f32;
+
+    if is_unreviewed(card) {
+        card.ease = params.starting_ease;
+    }
     let ease_delta = 0.1 - (5.0 - quality) * (0.08 + (5.0 - quality) * 0.02);
-    card.ease = (card.ease + ease_delta as f64).max(1.3);
+    card.ease = (card.ease + ease_delta as f64).max(params.minimum_ease);
 
-    if clamped < 3 {
+    let interval_days = if !grade.is_correct() {
         card.reps = 0;
         card.lapses += 1;
-        card.interval_days = 1;
+        ((card.interval_days as f64) * params.lapse_interval_multiplier).round() as i32
     } else {
         card.reps += 1;
-        card.interval_days = match card.reps {
-            1 => 1,
-            2 => 6,
-            _ => ((card.interval_days as f64) * card.ease).round() as i32,
-        };
-    }
+        match card.reps {
+            1 => params.first_interval_days,
+            2 => params.second_interval_days,
+            _ => params.fuzz(
+                card,
+                ((card.interval_days as f64) * card.ease).round() as i32,
+            ),
+        }
+    };
+    card.interval_days = interval_days.clamp(1, params.max_interval_days.max(1));
 
+    // An SM-2 review leaves any FSRS state stale, so FSRS re-derives it.
+    card.stability = None;
+    card.difficulty = None;
+    card.last_reviewed_at = Some(now);
     card.due_at = now + Duration::days(card.interval_days.max(1).into());
     card.due_at
 }
+
+/// Step-by-step account of a single `schedule_sm2` decision.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleExplanation {
+    pub grade: Grade,
+    pub ease_before: f64,
+    pub ease_after: f64,
+    pub interval_before: i32,
+    pub interval_after: i32,
+    pub reps_after: i32,
+    pub lapses_after: i32,
+    pub due_at: DateTime<Utc>,
+    pub steps: Vec<String>,
+}
+
+/// Explains what `schedule_sm2` does to `card` for `grade` without mutating it.
+pub fn explain_sm2(
+    card: &Card,
+    grade: Grade,
+    now: DateTime<Utc>,
+    params: &Sm2Params,
+) -> ScheduleExplanation {
+    let mut after = card.clone();
+    let due_at = schedule_sm2(&mut after, grade, now, params);
+    let ease_before = if is_unreviewed(card) {
+        params.starting_ease
+    } else {
+        card.ease
+    };
+
+    let mut steps = vec![format!("Graded {grade}/5.")];
+    if is_unreviewed(card) {
+        steps.push(format!("First review: ease starts at {ease_before:.2}."));
+    }
+    let delta = after.ease - ease_before;
+    if after.ease <= params.minimum_ease && grade.value() < 4 {
+        steps.push(format!(
+            "Ease {ease_before:.2} -> {:.2} (held at the {:.2} floor).",
+            after.ease, params.minimum_ease
+        ));
+    } else {
+        steps.push(format!(
+            "Ease {ease_before:.2} -> {:.2} ({delta:+.2}).",
+            after.ease
+        ));
+    }
+    let days = |days: i32| {
+        if days == 1 {
+            "1 day".to_string()
+        } else {
+            format!("{days} days")
+        }
+    };
+    if !grade.is_correct() {
+        let restart = if params.lapse_interval_multiplier > 0.0 {
+            format!(
+                "interval {} x {:.2} = {}",
+                days(card.interval_days),
+                params.lapse_interval_multiplier,
+                days(after.interval_days)
+            )
+        } else {
+            format!("interval reset to {}", days(after.interval_days))
+        };
+        steps.push(format!(
+            "A grade below 3 is a lapse: repetitions reset to 0, lapses now {}, {restart}.",
+            after.lapses
+        ));
+    } else {
+        match after.reps {
+            1 => steps.push(format!(
+                "First successful repetition: interval set to {}.",
+                days(after.interval_days)
+            )),
+            2 => steps.push(format!(
+                "Second successful repetition: interval set to {}.",
+                days(after.interval_days)
+            )),
+            reps => {
+                let raw = ((card.interval_days as f64) * after.ease).round() as i32;
+                steps.push(format!(
+                    "Repetition {reps}: {} x ease {:.2} = {}.",
+                    days(card.interval_days),
+                    after.ease,
+                    days(raw)
+                ));
+                if raw > params.max_interval_days {
+                    steps.push(format!(
+                        "Capped at the {} maximum.",
+                        days(params.max_interval_days)
+                    ));
+                } else if after.interval_days != raw {
+                    steps.push(format!(
+                        "Fuzzed by {:+} to {} (up to {}% either way).",
+                        after.interval_days - raw,
+                        days(after.interval_days),
+                        params.fuzz_percent
+                    ));
+                }
+            }
+        }
+    }
+    steps.push(format!("Next due {}.", due_at.format("%Y-%m-%d %H:%M UTC")));
+
+    ScheduleExplanation {
+        grade,
+        ease_before,
+        ease_after: after.ease,
+        interval_before: card.interval_days,
+        interval_after: after.interval_days,
+        reps_after: after.reps,
+        lapses_after: after.lapses,
+        due_at,
+        steps,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::de::value::{Error as ValueError, U8Deserializer};
+
+    fn grade(value: u8) -> Grade {
+        Grade::new(value).unwrap()
+    }
+
+    fn now() -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000, 0).unwrap()
+    }
+
+    /// Cards in every combination of ease (0.1 apart), interval, and
+    /// repetition count, already past their first review.
+    fn cards(params: &Sm2Params) -> Vec<Card> {
+        let mut cards = Vec::new();
+        let mut ease = params.minimum_ease;
+        while ease <= 5.0 {
+            for interval_days in [1, 2, 6, 15, 40, 120, 365, 2_000] {
+                for reps in 0..4 {
+                    let mut card = default_new_card(Uuid::nil(), now());
+                    card.ease = ease;
+                    card.interval_days = interval_days;
+                    card.reps = reps;
+                    card.lapses = 1;
+                    cards.push(card);
+                }
+            }
+            ease += 0.1;
+        }
+        cards
+    }
+
+    fn tuned() -> Vec<Sm2Params> {
+        vec![
+            Sm2Params::default(),
+            Sm2Params {
+                minimum_ease: 2.0,
+                starting_ease: 3.0,
+                second_interval_days: 3,
+                lapse_interval_multiplier: 0.5,
+                max_interval_days: 180,
+                ..Sm2Params::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn interval_never_shrinks_as_a_correct_grade_rises() {
+        for params in tuned() {
+            for card in cards(&params) {
+                let intervals: Vec<i32> = (3..=Grade::MAX)
+                    .map(|value| {
+                        let mut card = card.clone();
+                        schedule_sm2(&mut card, grade(value), now(), &params);
+                        card.interval_days
+                    })
+                    .collect();
+                assert!(
+                    intervals.windows(2).all(|pair| pair[0] <= pair[1]),
+                    "intervals {intervals:?} for {card:?} with {params:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn ease_never_drops_below_the_minimum() {
+        for params in tuned() {
+            for card in cards(&params) {
+                for value in 0..=Grade::MAX {
+                    let mut card = card.clone();
+                    for _ in 0..20 {
+                        schedule_sm2(&mut card, grade(value), now(), &params);
+                        assert!(
+                            card.ease >= params.minimum_ease,
+                            "ease {} below {} after grade {value}",
+                            card.ease,
+                            params.minimum_ease
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn out_of_range_grades_are_rejected() {
+        for value in 0..=Grade::MAX {
+            assert_eq!(Grade::try_from(value).map(Grade::value), Ok(value));
+        }
+        for value in Grade::MAX + 1..=u8::MAX {
+            assert_eq!(Grade::try_from(value), Err(GradeError(value)));
+        }
+        assert!(Grade::deserialize(U8Deserializer::<ValueError>::new(255)).is_err());
+        assert_eq!(
+            Grade::deserialize(U8Deserializer::<ValueError>::new(5)).ok(),
+            Some(grade(5))
+        );
+    }
+}
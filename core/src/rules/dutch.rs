@@ -0,0 +1,342 @@
+//! Dutch rules. Lemmatization is rough: an inflected verb or noun form maps
+//! to the dictionary forms it could come from. Candidates are over-generated
+//! on purpose; callers keep the ones that match a saved word.
+
+use super::LanguageRules;
+
+pub struct Dutch;
+
+impl LanguageRules for Dutch {
+    fn name(&self) -> &'static str {
+        "Dutch"
+    }
+
+    fn code(&self) -> &'static str {
+        "nl"
+    }
+
+    fn articles(&self) -> &'static [&'static str] {
+        &["de", "het", "een", "'t"]
+    }
+
+    fn lemma_candidates(&self, form: &str) -> Vec<String> {
+        lemma_candidates(form)
+    }
+
+    fn separable_verb(&self, lemma: &str) -> Option<(&'static str, String)> {
+        separable_verb(lemma)
+    }
+
+    fn join_separable(&self, text: &str) -> Option<String> {
+        join_separable(text)
+    }
+
+    fn is_chapter_line(&self, lowered: &str) -> bool {
+        // "hoolastuk" and other "hoo...stuk" shapes are common OCR misreads.
+        lowered.contains("hoofdstuk")
+            || lowered.contains("hoolastuk")
+            || (lowered.starts_with("hoo") && lowered.contains("stuk"))
+    }
+}
+
+/// Strong and irregular verbs whose forms the suffix rules cannot recover.
+const IRREGULAR_VERBS: &[(&str, &[&str])] = &[
+    ("zijn", &["ben", "bent", "is", "was", "waren", "geweest"]),
+    (
+        "hebben",
+        &["heb", "hebt", "heeft", "had", "hadden", "gehad"],
+    ),
+    ("gaan", &["ga", "gaat", "ging", "gingen", "gegaan"]),
+    ("staan", &["sta", "staat", "stond", "stonden", "gestaan"]),
+    ("slaan", &["sla", "slaat", "sloeg", "sloegen", "geslagen"]),
+    ("doen", &["doe", "doet", "deed", "deden", "gedaan"]),
+    ("zien", &["zie", "ziet", "zag", "zagen", "gezien"]),
+    ("komen", &["kom", "komt", "kwam", "kwamen", "gekomen"]),
+    ("lopen", &["liep", "liepen", "gelopen"]),
+    ("eten", &["eet", "at", "aten", "gegeten"]),
+    ("drinken", &["dronk", "dronken", "gedronken"]),
+    ("schrijven", &["schreef", "schreven", "geschreven"]),
+    ("lezen", &["las", "lazen", "gelezen"]),
+    ("geven", &["gaf", "gaven", "gegeven"]),
+    ("nemen", &["nam", "namen", "genomen"]),
+    ("spreken", &["sprak", "spraken", "gesproken"]),
+    ("vinden", &["vond", "vonden", "gevonden"]),
+    ("zitten", &["zat", "zaten", "gezeten"]),
+    ("liggen", &["lag", "lagen", "gelegen"]),
+    ("worden", &["werd", "werden", "geworden"]),
+    ("kunnen", &["kan", "kunt", "kon", "konden", "gekund"]),
+    (
+        "willen",
+        &["wil", "wilt", "wou", "wilde", "wilden", "gewild"],
+    ),
+    ("moeten", &["moest", "moesten", "gemoeten"]),
+    ("mogen", &["mag", "mocht", "mochten", "gemogen"]),
+    ("zullen", &["zal", "zult", "zou", "zouden"]),
+    ("weten", &["weet", "wist", "wisten", "geweten"]),
+    ("denken", &["dacht", "dachten", "gedacht"]),
+    ("brengen", &["bracht", "brachten", "gebracht"]),
+    ("kopen", &["kocht", "kochten", "gekocht"]),
+    ("zoeken", &["zocht", "zochten", "gezocht"]),
+    ("slapen", &["sliep", "sliepen", "geslapen"]),
+    ("rijden", &["reed", "reden", "gereden"]),
+    ("blijven", &["bleef", "bleven", "gebleven"]),
+    ("beginnen", &["begon", "begonnen"]),
+    ("helpen", &["hielp", "hielpen", "geholpen"]),
+    ("vragen", &["vroeg", "vroegen"]),
+    ("zeggen", &["zei", "zeiden"]),
+    ("krijgen", &["kreeg", "kregen", "gekregen"]),
+    ("houden", &["hield", "hielden", "gehouden"]),
+    ("vallen", &["viel", "vielen", "gevallen"]),
+    ("laten", &["liet", "lieten", "gelaten"]),
+    ("roepen", &["riep", "riepen", "geroepen"]),
+    ("zingen", &["zong", "zongen", "gezongen"]),
+    ("zwemmen", &["zwom", "zwommen", "gezwommen"]),
+    ("vliegen", &["vloog", "vlogen", "gevlogen"]),
+    ("trekken", &["trok", "trokken", "getrokken"]),
+    ("kiezen", &["koos", "kozen", "gekozen"]),
+    ("verliezen", &["verloor", "verloren"]),
+    ("sterven", &["stierf", "stierven", "gestorven"]),
+    ("sluiten", &["sloot", "sloten", "gesloten"]),
+    ("vergeten", &["vergat", "vergaten"]),
+    ("begrijpen", &["begreep", "begrepen"]),
+    ("dragen", &["droeg", "droegen", "gedragen"]),
+    ("wassen", &["waste", "wasten", "gewassen"]),
+];
+
+/// Particles that split off a verb in main clauses ("ik bel je op"),
+/// longest first so "terug" is tried before shorter prefixes.
+const SEPARABLE_PARTICLES: &[&str] = &[
+    "achter", "binnen", "buiten", "samen", "terug", "thuis", "langs", "tegen", "door", "over",
+    "voor", "neer", "vast", "aan", "mee", "weg", "toe", "bij", "uit", "los", "af", "in", "na",
+    "om", "op",
+];
+
+/// Verbs that start like a separable one but never split.
+const INSEPARABLE_VERBS: &[&str] = &[
+    "aanbidden",
+    "achtervolgen",
+    "doorgronden",
+    "doorzoeken",
+    "informeren",
+    "inspireren",
+    "installeren",
+    "interesseren",
+    "omarmen",
+    "omringen",
+    "omschrijven",
+    "opereren",
+    "overdrijven",
+    "overleven",
+    "overnachten",
+    "overtuigen",
+    "overwegen",
+    "overwinnen",
+    "voorspellen",
+    "voorzien",
+];
+
+const VOWELS: &[char] = &['a', 'e', 'i', 'o', 'u'];
+
+fn separable_verb(lemma: &str) -> Option<(&'static str, String)> {
+    let lemma = lemma.trim().to_lowercase();
+    // Infinitives end in "-en", or "-n" for gaan, staan, zien, and the like.
+    if !lemma.ends_with('n') || INSEPARABLE_VERBS.contains(&lemma.as_str()) {
+        return None;
+    }
+    SEPARABLE_PARTICLES.iter().find_map(|particle| {
+        let verb = lemma.strip_prefix(particle)?;
+        // The rest must be a verb of its own: "opbellen", but not "open".
+        (verb.chars().count() >= 4).then(|| (*particle, verb.to_string()))
+    })
+}
+
+fn join_separable(text: &str) -> Option<String> {
+    let lowered = text.trim().to_lowercase();
+    let parts: Vec<&str> = lowered
+        .split(|c: char| c.is_whitespace() || matches!(c, '|' | '·' | '.' | '…'))
+        .filter(|part| !part.is_empty())
+        .collect();
+    let infinitive = match parts.as_slice() {
+        // "op|bellen", "op bellen", "bellen op"
+        [particle, verb] | [verb, particle]
+            if SEPARABLE_PARTICLES.contains(particle) && verb.ends_with('n') =>
+        {
+            format!("{particle}{verb}")
+        }
+        // "bel op", "bel ... op": a conjugated verb goes before its particle;
+        // a particle up front ("op tijd") is a preposition.
+        [verb, particle] if SEPARABLE_PARTICLES.contains(particle) => {
+            let lemma = lemma_candidates(verb).into_iter().next()?;
+            format!("{particle}{lemma}")
+        }
+        _ => return None,
+    };
+    separable_verb(&infinitive).map(|_| infinitive)
+}
+
+/// Possible dictionary forms for `form`, most likely first. Never includes
+/// `form` itself.
+fn lemma_candidates(form: &str) -> Vec<String> {
+    let form = form.trim().to_lowercase();
+    let mut candidates = Vec::new();
+    if form.is_empty() || form.contains(char::is_whitespace) {
+        return candidates;
+    }
+    for (lemma, forms) in IRREGULAR_VERBS {
+        if forms.contains(&form.as_str()) {
+            candidates.push(lemma.to_string());
+        }
+    }
+
+    // Present tense: "werk", "werkt" -> "werken". Stems end in a consonant
+    // and never in "en", which is already an infinitive or plural ending.
+    if !form.ends_with(VOWELS) && !form.ends_with("en") && !form.contains('\'') {
+        if let Some(stem) = form.strip_suffix('t') {
+            candidates.extend(infinitives_from_stem(stem));
+        }
+        candidates.extend(infinitives_from_stem(&form));
+    }
+    // Weak past tense: "werkte", "werkten", "woonde" -> stem.
+    for suffix in ["ten", "den", "te", "de"] {
+        if let Some(stem) = form.strip_suffix(suffix) {
+            candidates.extend(infinitives_from_stem(stem));
+        }
+    }
+    // Weak participle: "gewerkt", "gewoond" -> stem.
+    if let Some(rest) = form.strip_prefix("ge")
+        && let Some(stem) = rest.strip_suffix('t').or_else(|| rest.strip_suffix('d'))
+    {
+        candidates.extend(infinitives_from_stem(stem));
+    }
+    // Separable verbs with the particle attached: "opgebeld", "opbelt" -> "opbellen".
+    for particle in SEPARABLE_PARTICLES {
+        if let Some(rest) = form.strip_prefix(particle)
+            && rest.chars().count() >= 3
+        {
+            candidates.extend(
+                lemma_candidates(rest)
+                    .into_iter()
+                    .filter(|lemma| lemma.ends_with('n'))
+                    .map(|lemma| format!("{particle}{lemma}")),
+            );
+        }
+    }
+    // Noun plurals and diminutives: "katten", "bomen", "huizen", "auto's", "huisje".
+    if let Some(base) = form.strip_suffix("en") {
+        candidates.extend(singulars_from_base(base));
+    }
+    if let Some(base) = form.strip_suffix("'s") {
+        candidates.push(base.to_string());
+    } else if let Some(base) = form.strip_suffix('s') {
+        candidates.push(base.to_string());
+    }
+    for suffix in ["etje", "tje", "pje", "je"] {
+        if let Some(base) = form.strip_suffix(suffix) {
+            candidates.push(base.to_string());
+            break;
+        }
+    }
+
+    let mut seen = Vec::new();
+    candidates.retain(|candidate| {
+        let keep = candidate.len() > 1 && *candidate != form && !seen.contains(candidate);
+        if keep {
+            seen.push(candidate.clone());
+        }
+        keep
+    });
+    candidates
+}
+
+/// Infinitives that conjugate to `stem`, covering the spelling changes
+/// between stem and infinitive (loop/lopen, zit/zitten, leef/leven, reis/reizen).
+fn infinitives_from_stem(stem: &str) -> Vec<String> {
+    if stem.chars().count() < 3 {
+        return Vec::new();
+    }
+    let mut bases = vec![stem.to_string()];
+    if let Some(rest) = stem.strip_suffix('f') {
+        bases.push(format!("{rest}v"));
+    }
+    if let Some(rest) = stem.strip_suffix('s') {
+        bases.push(format!("{rest}z"));
+    }
+    let mut infinitives = Vec::new();
+    for base in bases {
+        if let Some(single) = undouble_vowel(&base) {
+            infinitives.push(format!("{single}en"));
+        }
+        if let Some(doubled) = double_consonant(&base) {
+            infinitives.push(format!("{doubled}en"));
+        }
+        infinitives.push(format!("{base}en"));
+    }
+    infinitives
+}
+
+/// Singular nouns whose plural is `base` + "en" (katten, bomen, huizen, brieven).
+fn singulars_from_base(base: &str) -> Vec<String> {
+    if base.len() < 2 {
+        return Vec::new();
+    }
+    let mut bases = vec![base.to_string()];
+    if let Some(rest) = base.strip_suffix('z') {
+        bases.push(format!("{rest}s"));
+    }
+    if let Some(rest) = base.strip_suffix('v') {
+        bases.push(format!("{rest}f"));
+    }
+    let mut singulars = Vec::new();
+    for base in bases {
+        let chars: Vec<char> = base.chars().collect();
+        let n = chars.len();
+        if n >= 2 && chars[n - 1] == chars[n - 2] && !VOWELS.contains(&chars[n - 1]) {
+            singulars.push(chars[..n - 1].iter().collect());
+        }
+        if n >= 3
+            && !VOWELS.contains(&chars[n - 1])
+            && VOWELS.contains(&chars[n - 2])
+            && !VOWELS.contains(&chars[n - 3])
+        {
+            let mut doubled: String = chars[..n - 1].iter().collect();
+            doubled.push(chars[n - 2]);
+            doubled.push(chars[n - 1]);
+            singulars.push(doubled);
+        }
+        singulars.push(base);
+    }
+    singulars
+}
+
+/// "loop" -> "lop": a double vowel before a final consonant becomes single
+/// in the open syllable of the infinitive.
+fn undouble_vowel(stem: &str) -> Option<String> {
+    let chars: Vec<char> = stem.chars().collect();
+    let n = chars.len();
+    if n >= 3
+        && !VOWELS.contains(&chars[n - 1])
+        && chars[n - 2] == chars[n - 3]
+        && VOWELS.contains(&chars[n - 2])
+    {
+        let mut single: String = chars[..n - 2].iter().collect();
+        single.push(chars[n - 1]);
+        return Some(single);
+    }
+    None
+}
+
+/// "zit" -> "zitt": a short vowel before a single final consonant doubles it.
+fn double_consonant(stem: &str) -> Option<String> {
+    let chars: Vec<char> = stem.chars().collect();
+    let n = chars.len();
+    if n >= 2
+        && !VOWELS.contains(&chars[n - 1])
+        && VOWELS.contains(&chars[n - 2])
+        && (n < 3 || !VOWELS.contains(&chars[n - 3]))
+    {
+        let mut doubled = stem.to_string();
+        doubled.push(chars[n - 1]);
+        return Some(doubled);
+    }
+    None
+}
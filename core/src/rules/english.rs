@@ -0,0 +1,24 @@
+//! English rules. Translations are never lemmatized, so only the entry and
+//! heading conventions are defined here.
+
+use super::LanguageRules;
+
+pub struct English;
+
+impl LanguageRules for English {
+    fn name(&self) -> &'static str {
+        "English"
+    }
+
+    fn code(&self) -> &'static str {
+        "en"
+    }
+
+    fn articles(&self) -> &'static [&'static str] {
+        &["the", "a", "an", "to"]
+    }
+
+    fn is_chapter_line(&self, lowered: &str) -> bool {
+        lowered.contains("chapter")
+    }
+}
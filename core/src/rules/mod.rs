@@ -0,0 +1,219 @@
+//! Language-specific heuristics behind one trait, so supporting another
+//! language means adding a module here rather than editing every place that
+//! strips articles, lemmatizes, or reads textbook headings.
+
+use std::ops::Range;
+
+use crate::Language;
+
+mod dutch;
+mod english;
+
+pub use dutch::Dutch;
+pub use english::English;
+
+/// Every language with rules of its own.
+pub const ALL: &[&dyn LanguageRules] = &[&Dutch, &English];
+
+pub trait LanguageRules: Sync {
+    /// English name, also what the `language` column stores ("Dutch").
+    fn name(&self) -> &'static str;
+
+    /// ISO 639-1 code, lowercase ("nl").
+    fn code(&self) -> &'static str;
+
+    /// Words dropped from the front of an entry when comparing or locating
+    /// it: articles, and the "to" of English infinitives.
+    fn articles(&self) -> &'static [&'static str];
+
+    /// Possible dictionary forms for an inflected `form`, most likely first.
+    /// Never includes `form` itself.
+    fn lemma_candidates(&self, _form: &str) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// A separable verb's particle and the verb it splits off from
+    /// ("opbellen" -> ("op", "bellen")), or `None` for any other word.
+    fn separable_verb(&self, _lemma: &str) -> Option<(&'static str, String)> {
+        None
+    }
+
+    /// The infinitive an entry written apart stands for ("bel op",
+    /// "bel ... op", "op|bellen" -> "opbellen"), if it is a separable verb.
+    fn join_separable(&self, _text: &str) -> Option<String> {
+        None
+    }
+
+    /// Whether a lowercased OCR line is a chapter title ("hoofdstuk 3") rather
+    /// than vocabulary.
+    fn is_chapter_line(&self, lowered: &str) -> bool;
+
+    /// Whether a textbook line reads like a group heading: one capital
+    /// letter up front and no list punctuation. Languages that capitalize
+    /// nouns need their own rule.
+    fn is_heading_text(&self, text: &str) -> bool {
+        let text = text.trim();
+        if text.contains([',', '-', '(', ')']) {
+            return false;
+        }
+        let mut chars = text.chars();
+        chars.next().is_some_and(char::is_uppercase) && !chars.any(char::is_uppercase)
+    }
+
+    /// `text` without a leading article ("de haven" -> "haven").
+    fn strip_article<'a>(&self, text: &'a str) -> &'a str {
+        let trimmed = text.trim_start();
+        for article in self.articles() {
+            if let Some(rest) = trimmed
+                .get(..article.len())
+                .filter(|head| head.eq_ignore_ascii_case(article))
+                .and_then(|_| trimmed[article.len()..].strip_prefix(' '))
+            {
+                return rest.trim();
+            }
+        }
+        text.trim()
+    }
+}
+
+/// Fallback for languages without rules of their own: no articles, no
+/// lemmatization, and no chapter lines recognised.
+pub struct Generic;
+
+impl LanguageRules for Generic {
+    fn name(&self) -> &'static str {
+        "Other"
+    }
+
+    fn code(&self) -> &'static str {
+        "und"
+    }
+
+    fn articles(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn is_chapter_line(&self, _lowered: &str) -> bool {
+        false
+    }
+}
+
+impl Language {
+    /// Rules for the language, matched on its primary subtag so "nl-BE"
+    /// uses the Dutch ones; [`Generic`] when it has none.
+    pub fn rules(&self) -> &'static dyn LanguageRules {
+        for_name(self.primary()).unwrap_or(&Generic)
+    }
+}
+
+/// Rules for a language given by code ("nl") or name ("Dutch"), in any case.
+pub fn for_name(name: &str) -> Option<&'static dyn LanguageRules> {
+    let name = name.trim();
+    ALL.iter().copied().find(|rules| {
+        rules.code().eq_ignore_ascii_case(name) || rules.name().eq_ignore_ascii_case(name)
+    })
+}
+
+/// Case-insensitive comparison key for an entry: no leading article, and a
+/// separable verb written apart joined back into its infinitive.
+pub fn entry_key(rules: &dyn LanguageRules, text: &str) -> String {
+    let key = rules.strip_article(&text.trim().to_lowercase()).to_string();
+    rules.join_separable(&key).unwrap_or(key)
+}
+
+/// Whether a typed answer matches `expected`, ignoring case and a leading
+/// article. A separable verb also matches when written apart ("bel op") or
+/// split across a sentence ("ik bel je morgen op").
+pub fn answer_matches(rules: &dyn LanguageRules, typed: &str, expected: &str) -> bool {
+    let typed_key = entry_key(rules, typed);
+    let expected_key = entry_key(rules, expected);
+    if typed_key.is_empty() || expected_key.is_empty() {
+        return false;
+    }
+    typed_key == expected_key
+        || (rules.separable_verb(&expected_key).is_some()
+            && !find_word(rules, typed, &expected_key).is_empty())
+}
+
+/// Byte ranges in `sentence` where `word` appears, either as written or as
+/// an inflection `rules` maps back to it ("liep" for "lopen", "katten" for
+/// "de kat"). A leading article on `word` is ignored; a multi-word entry
+/// matches only as the whole phrase. A separable verb also matches split in
+/// two ("bel" and "op" in "ik bel je op" for "opbellen").
+pub fn find_word(rules: &dyn LanguageRules, sentence: &str, word: &str) -> Vec<Range<usize>> {
+    let target = rules.strip_article(&word.trim().to_lowercase()).to_string();
+    if target.is_empty() {
+        return Vec::new();
+    }
+    let tokens = word_tokens(sentence);
+    let target_tokens: Vec<&str> = target.split_whitespace().collect();
+    if target_tokens.len() > 1 {
+        return tokens
+            .windows(target_tokens.len())
+            .filter(|window| {
+                window
+                    .iter()
+                    .zip(&target_tokens)
+                    .all(|(token, expected)| sentence[token.clone()].to_lowercase() == *expected)
+            })
+            .map(|window| window[0].start..window[window.len() - 1].end)
+            .collect();
+    }
+    let matches_lemma = |token: &Range<usize>, lemma: &str| {
+        let form = sentence[token.clone()].to_lowercase().replace('’', "'");
+        form == lemma || rules.lemma_candidates(&form).iter().any(|c| c == lemma)
+    };
+    let mut ranges: Vec<Range<usize>> = tokens
+        .iter()
+        .filter(|token| matches_lemma(token, &target))
+        .cloned()
+        .collect();
+    if let Some((particle, verb)) = rules.separable_verb(&target) {
+        let is_particle =
+            |token: &Range<usize>| sentence[token.clone()].eq_ignore_ascii_case(particle);
+        for (index, token) in tokens.iter().enumerate() {
+            if !matches_lemma(token, &verb) {
+                continue;
+            }
+            // "bel ... op" in main clauses, "op te bellen" in infinitive ones.
+            let particle_token = tokens[index + 1..]
+                .iter()
+                .find(|token| is_particle(token))
+                .or_else(|| {
+                    tokens[..index]
+                        .iter()
+                        .rev()
+                        .find(|token| is_particle(token))
+                });
+            if let Some(particle_token) = particle_token {
+                ranges.push(token.clone());
+                ranges.push(particle_token.clone());
+            }
+        }
+        ranges.sort_by_key(|range| range.start);
+        ranges.dedup();
+    }
+    ranges
+}
+
+/// Letters with inner apostrophes and hyphens ("auto's", "e-mail") form one
+/// token; everything else separates tokens.
+fn word_tokens(sentence: &str) -> Vec<Range<usize>> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    let mut chars = sentence.char_indices().peekable();
+    while let Some((idx, ch)) = chars.next() {
+        let joins = matches!(ch, '\'' | '’' | '-')
+            && start.is_some()
+            && chars.peek().is_some_and(|(_, next)| next.is_alphabetic());
+        if ch.is_alphabetic() || joins {
+            start.get_or_insert(idx);
+        } else if let Some(begin) = start.take() {
+            tokens.push(begin..idx);
+        }
+    }
+    if let Some(begin) = start {
+        tokens.push(begin..sentence.len());
+    }
+    tokens
+}
@@ -0,0 +1,89 @@
+//! Matching for search as you type. A query is split into terms, and each
+//! term matches the start of a word with case and accents ignored, the way
+//! the GUI's full-text index does, so "cafe" finds "Het café". Match ranges
+//! are byte offsets into the original text, for front ends to highlight.
+
+use std::ops::Range;
+
+use crate::answer_check::fold_diacritics;
+
+/// `text` folded for matching, keeping where each folded character came
+/// from in the original.
+struct Folded {
+    chars: Vec<char>,
+    /// Byte range in the original text of each entry in `chars`.
+    spans: Vec<Range<usize>>,
+}
+
+impl Folded {
+    fn new(text: &str) -> Self {
+        let mut chars = Vec::with_capacity(text.len());
+        let mut spans = Vec::with_capacity(text.len());
+        for (start, c) in text.char_indices() {
+            let span = start..start + c.len_utf8();
+            for folded in fold_diacritics(&c.to_lowercase().collect::<String>()).chars() {
+                chars.push(folded);
+                spans.push(span.clone());
+            }
+        }
+        Self { chars, spans }
+    }
+
+    /// Where `term` starts a word.
+    fn find<'a>(&'a self, term: &'a [char]) -> impl Iterator<Item = Range<usize>> + 'a {
+        (0..self.chars.len())
+            .filter(move |&at| {
+                (at == 0 || !self.chars[at - 1].is_alphanumeric())
+                    && self.chars[at..].starts_with(term)
+            })
+            .map(move |at| self.spans[at].start..self.spans[at + term.len() - 1].end)
+    }
+}
+
+/// The terms of `query`, folded like the text they are matched against.
+/// Empty when there is nothing to search for.
+pub fn search_terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for term in query.split_whitespace() {
+        let term = fold_diacritics(&term.to_lowercase());
+        if !terms.contains(&term) {
+            terms.push(term);
+        }
+    }
+    terms
+}
+
+/// Byte ranges of `text` matched by any of `terms`, in order, with
+/// overlapping and touching matches merged.
+pub fn match_ranges(text: &str, terms: &[String]) -> Vec<Range<usize>> {
+    let folded = Folded::new(text);
+    let mut ranges: Vec<Range<usize>> = terms
+        .iter()
+        .filter(|term| !term.is_empty())
+        .flat_map(|term| {
+            let term: Vec<char> = term.chars().collect();
+            folded.find(&term).collect::<Vec<_>>()
+        })
+        .collect();
+    ranges.sort_by_key(|range| (range.start, range.end));
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// Whether every term matches in at least one of `fields`.
+pub fn matches_all(fields: &[&str], terms: &[String]) -> bool {
+    let fields: Vec<Folded> = fields.iter().map(|field| Folded::new(field)).collect();
+    terms.iter().all(|term| {
+        let term: Vec<char> = term.chars().collect();
+        term.is_empty()
+            || fields
+                .iter()
+                .any(|field| field.find(&term).next().is_some())
+    })
+}
@@ -0,0 +1,523 @@
+//! Review session queues built from a [`SessionConfig`]. Front ends pick
+//! which cards are eligible and in what priority; [`SessionBuilder`] applies
+//! the session limits and places new cards between the reviews as the
+//! config's [`CardOrder`] says, so every front end runs sessions the same
+//! way.
+//!
+//! A [`Session`] then runs that queue: it serves one card at a time, brings
+//! failed cards back a few cards later until they are answered correctly,
+//! and ends on the correct-answer goal, the time limit, or an empty queue.
+//! Cards in their learning steps come back once their step is up, or a
+//! little early when nothing else is left. It only holds cards; front ends
+//! load them and save the grades.
+//!
+//! After a break, a [`RecoveryPlan`] brings the overdue backlog back a day's
+//! share at a time, and [`SessionBuilder::recovering`] keeps each session
+//! within that day's allowance.
+
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{Card, CardOrder, Grade, SessionConfig, SessionProgress};
+
+/// Rough time one card takes, used to fit a session into `max_minutes` and
+/// for session length estimates.
+pub const SECONDS_PER_CARD: u64 = 30;
+/// Days without a review that count as a break worth a welcome-back plan.
+pub const BREAK_DAYS: i64 = 14;
+/// Reviews a day a recovery plan allows unless the learner picks another cap.
+pub const RECOVERY_DAILY_REVIEWS: usize = 30;
+/// Other cards served between a failed card and its next attempt.
+pub const RELEARN_GAP: usize = 3;
+/// How early a learning card is served when nothing else is left, and so
+/// how soon it must be due again to stay in the session.
+pub const LEARN_AHEAD_MINUTES: i64 = 20;
+
+#[derive(Debug, Clone)]
+pub struct SessionBuilder<T> {
+    config: SessionConfig,
+    due: Vec<T>,
+    new: Vec<T>,
+    reviews_left: Option<usize>,
+}
+
+impl<T> SessionBuilder<T> {
+    pub fn new(config: SessionConfig) -> Self {
+        Self {
+            config,
+            due: Vec::new(),
+            new: Vec::new(),
+            reviews_left: None,
+        }
+    }
+
+    /// Cards already in review (learning or due), most important first.
+    pub fn due(mut self, cards: impl IntoIterator<Item = T>) -> Self {
+        self.due.extend(cards);
+        self
+    }
+
+    /// Cards never seen before, in the order they should be introduced.
+    pub fn new_cards(mut self, cards: impl IntoIterator<Item = T>) -> Self {
+        self.new.extend(cards);
+        self
+    }
+
+    /// Welcome-back strategy: no new cards, and no more than `reviews_left`
+    /// cards, what is left of the recovery plan's allowance for today.
+    pub fn recovering(mut self, reviews_left: usize) -> Self {
+        self.reviews_left = Some(reviews_left);
+        self
+    }
+
+    /// Takes up to `max_new_cards` new cards, fills the rest of the session
+    /// up to `max_cards` (or what fits into `max_minutes`, if less) with due
+    /// cards, and orders the two by `order`. Room left by missing new cards
+    /// goes to due cards.
+    pub fn build(self) -> SessionQueue<T> {
+        let SessionBuilder {
+            config,
+            mut due,
+            mut new,
+            reviews_left,
+        } = self;
+        let mut limit = config.max_cards;
+        if let Some(minutes) = config.max_minutes {
+            limit = limit.min((minutes * 60 / SECONDS_PER_CARD).max(1) as usize);
+        }
+        if let Some(left) = reviews_left {
+            limit = limit.min(left);
+            new.clear();
+        }
+        let new_count = new.len().min(config.max_new_cards).min(limit);
+        let due_count = due.len().min(limit - new_count);
+        let new_count = new.len().min(config.max_new_cards).min(limit - due_count);
+        due.truncate(due_count);
+        new.truncate(new_count);
+        SessionQueue {
+            cards: match config.order {
+                CardOrder::Spread => interleave(due, new),
+                CardOrder::NewFirst => new.into_iter().chain(due).collect(),
+                CardOrder::DueFirst => due.into_iter().chain(new).collect(),
+                CardOrder::Mixed { due_per_new } => mix(due, new, due_per_new.max(1)),
+            },
+            stop_after_correct: Some(config.stop_after_correct).filter(|goal| *goal > 0),
+            max_minutes: config.max_minutes,
+        }
+    }
+}
+
+/// A session ready to run, in serving order.
+#[derive(Debug, Clone)]
+pub struct SessionQueue<T> {
+    pub cards: Vec<T>,
+    /// The session ends once this many answers were correct.
+    pub stop_after_correct: Option<usize>,
+    pub max_minutes: Option<u64>,
+}
+
+impl<T> SessionQueue<T> {
+    /// When a session started at `started_at` runs out of time.
+    pub fn deadline(&self, started_at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.max_minutes
+            .map(|minutes| started_at + Duration::minutes(minutes as i64))
+    }
+
+    /// Starts the session at `now`, with the time limit counted from then.
+    pub fn start(self, now: DateTime<Utc>) -> Session<T> {
+        Session {
+            deadline: self.deadline(now),
+            progress: SessionProgress::new(self.stop_after_correct),
+            queue: self.cards.into(),
+            started_at: Some(now),
+            ..Session::default()
+        }
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> SessionQueue<U> {
+        SessionQueue {
+            cards: self.cards.into_iter().map(f).collect(),
+            stop_after_correct: self.stop_after_correct,
+            max_minutes: self.max_minutes,
+        }
+    }
+}
+
+/// What a [`Session`] holds: anything that knows which card it is, such as
+/// a whole [`Card`] or just its id.
+pub trait SessionCard {
+    type Id: PartialEq;
+
+    fn card_id(&self) -> Self::Id;
+}
+
+impl SessionCard for Card {
+    type Id = Uuid;
+
+    fn card_id(&self) -> Uuid {
+        self.id
+    }
+}
+
+impl SessionCard for String {
+    type Id = String;
+
+    fn card_id(&self) -> String {
+        self.clone()
+    }
+}
+
+/// Why a session stopped serving cards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionEnd {
+    /// Every card was answered, failed ones included.
+    Finished,
+    GoalReached,
+    TimeUp,
+    /// The front end ended it, such as on enforced fatigue.
+    Stopped,
+}
+
+/// A running review session. [`Session::next`] serves the next card and
+/// [`Session::grade`] takes its answer; a failed card goes to a relearn
+/// queue and comes back [`RELEARN_GAP`] cards later (or once nothing else
+/// is left) until it is answered correctly. [`Session::grade_learning`]
+/// brings a card in its learning steps back when its step is up instead.
+#[derive(Debug, Clone)]
+pub struct Session<T> {
+    queue: VecDeque<T>,
+    /// Failed cards, each with the serve count it is due back at.
+    relearn: VecDeque<(T, usize)>,
+    /// Cards in their learning steps, soonest due first.
+    learning: Vec<(T, DateTime<Utc>)>,
+    /// The card on screen and when it was served.
+    serving: Option<(T, DateTime<Utc>)>,
+    served: usize,
+    progress: SessionProgress,
+    /// Answers that sent a card to the relearn queue.
+    relearned: usize,
+    started_at: Option<DateTime<Utc>>,
+    deadline: Option<DateTime<Utc>>,
+    ended: Option<SessionEnd>,
+}
+
+impl<T> Default for Session<T> {
+    /// An empty session with nothing to serve.
+    fn default() -> Self {
+        Self {
+            queue: VecDeque::new(),
+            relearn: VecDeque::new(),
+            learning: Vec::new(),
+            serving: None,
+            served: 0,
+            progress: SessionProgress::default(),
+            relearned: 0,
+            started_at: None,
+            deadline: None,
+            ended: None,
+        }
+    }
+}
+
+/// What [`Session::grade`] did with an answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Graded {
+    /// When the card was served, if it was the one on screen.
+    pub served_at: Option<DateTime<Utc>>,
+    /// This answer reached the correct-answer goal.
+    pub goal_reached: bool,
+    /// Nothing is left to serve.
+    pub complete: bool,
+}
+
+/// How a session went, for its end screen.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub reviewed: usize,
+    pub correct: usize,
+    /// Answers that sent a card back for relearning.
+    pub relearned: usize,
+    /// Cards left unanswered, failed ones waiting to come back included.
+    pub remaining: usize,
+    pub elapsed_seconds: u64,
+    pub ended: Option<SessionEnd>,
+}
+
+impl<T: SessionCard> Session<T> {
+    /// Puts `cards` ahead of the queue, in order, outside the session limits.
+    pub fn warm_up(mut self, cards: impl IntoIterator<Item = T>) -> Self {
+        let cards: Vec<T> = cards.into_iter().collect();
+        for card in cards.into_iter().rev() {
+            self.queue.push_front(card);
+        }
+        self
+    }
+
+    /// Serves the next card: a learning card whose step is up, else a failed
+    /// one if it is due back. A card still on screen counts as skipped.
+    /// `None` once the session has ended.
+    pub fn next(&mut self, now: DateTime<Utc>) -> Option<&T> {
+        self.serving = None;
+        if self.ended.is_none() && self.deadline.is_some_and(|deadline| now >= deadline) {
+            self.ended = Some(SessionEnd::TimeUp);
+        }
+        if self.ended.is_some() {
+            return None;
+        }
+        let learning_due = self.learning.first().is_some_and(|(_, due_at)| {
+            *due_at <= now || (self.queue.is_empty() && self.relearn.is_empty())
+        });
+        let relearn_due = self
+            .relearn
+            .front()
+            .is_some_and(|(_, due)| *due <= self.served || self.queue.is_empty());
+        let card = if learning_due {
+            Some(self.learning.remove(0).0)
+        } else if relearn_due {
+            self.relearn.pop_front().map(|(card, _)| card)
+        } else {
+            self.queue.pop_front()
+        };
+        let Some(card) = card else {
+            self.ended = Some(SessionEnd::Finished);
+            return None;
+        };
+        self.served += 1;
+        Some(&self.serving.insert((card, now)).0)
+    }
+
+    /// The card [`Session::next`] last served, while it is unanswered.
+    pub fn current(&self) -> Option<&T> {
+        self.serving.as_ref().map(|(card, _)| card)
+    }
+
+    /// Counts an answer to `card` (as it is after scheduling) and takes it
+    /// out of the queue. A wrong answer sends it to the relearn queue.
+    pub fn grade(&mut self, card: T, grade: Grade) -> Graded {
+        let served_at = self.take(&card);
+        let goal_reached = self.progress.record(grade);
+        if !grade.is_correct() {
+            self.relearned += 1;
+            self.relearn.push_back((card, self.served + RELEARN_GAP));
+        }
+        self.graded(served_at, goal_reached)
+    }
+
+    /// Like [`Session::grade`], for a card still in its learning steps after
+    /// this answer: it comes back once `due_at` has passed. A card not due
+    /// again within [`LEARN_AHEAD_MINUTES`] of `now` is left for a later
+    /// session.
+    pub fn grade_learning(
+        &mut self,
+        card: T,
+        grade: Grade,
+        due_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Graded {
+        let served_at = self.take(&card);
+        let goal_reached = self.progress.record(grade);
+        if !grade.is_correct() {
+            self.relearned += 1;
+        }
+        if due_at <= now + Duration::minutes(LEARN_AHEAD_MINUTES) {
+            let at = self
+                .learning
+                .partition_point(|(_, queued)| *queued <= due_at);
+            self.learning.insert(at, (card, due_at));
+        }
+        self.graded(served_at, goal_reached)
+    }
+
+    /// Takes `card` out of the session, returning when it was served if it
+    /// was the one on screen.
+    fn take(&mut self, card: &T) -> Option<DateTime<Utc>> {
+        let id = card.card_id();
+        self.queue.retain(|queued| queued.card_id() != id);
+        self.relearn.retain(|(queued, _)| queued.card_id() != id);
+        self.learning.retain(|(queued, _)| queued.card_id() != id);
+        self.serving
+            .take_if(|(serving, _)| serving.card_id() == id)
+            .map(|(_, served_at)| served_at)
+    }
+
+    fn graded(&mut self, served_at: Option<DateTime<Utc>>, goal_reached: bool) -> Graded {
+        if goal_reached {
+            self.ended = Some(SessionEnd::GoalReached);
+        }
+        Graded {
+            served_at,
+            goal_reached,
+            complete: self.is_complete(),
+        }
+    }
+
+    /// Takes back an answer counted by [`Session::grade`] or
+    /// [`Session::grade_learning`]: `card` is served next, followed by the
+    /// one on screen, and a session that ended on the goal or an empty queue
+    /// picks up again.
+    pub fn undo(&mut self, card: T, grade: Grade) {
+        let id = card.card_id();
+        if let Some((serving, _)) = self.serving.take()
+            && serving.card_id() != id
+        {
+            self.queue.push_front(serving);
+        }
+        self.queue.retain(|queued| queued.card_id() != id);
+        self.relearn.retain(|(queued, _)| queued.card_id() != id);
+        self.learning.retain(|(queued, _)| queued.card_id() != id);
+        if !grade.is_correct() {
+            self.relearned = self.relearned.saturating_sub(1);
+        }
+        self.queue.push_front(card);
+        self.progress.undo(grade);
+        self.ended = self.ended.filter(|end| match end {
+            SessionEnd::Finished => false,
+            SessionEnd::GoalReached => self.progress.goal_reached(),
+            SessionEnd::TimeUp | SessionEnd::Stopped => true,
+        });
+    }
+
+    /// Drops the cards `remove` picks, such as suspended or deleted ones,
+    /// wherever they are in the session.
+    pub fn remove(&mut self, mut remove: impl FnMut(&T) -> bool) {
+        self.queue.retain(|card| !remove(card));
+        self.relearn.retain(|(card, _)| !remove(card));
+        self.learning.retain(|(card, _)| !remove(card));
+        if self.serving.as_ref().is_some_and(|(card, _)| remove(card)) {
+            self.serving = None;
+        }
+    }
+
+    /// Ends the session; nothing more is served.
+    pub fn stop(&mut self) {
+        self.serving = None;
+        self.ended.get_or_insert(SessionEnd::Stopped);
+    }
+
+    /// No card is left to serve, or the session has ended.
+    pub fn is_complete(&self) -> bool {
+        self.ended.is_some()
+            || (self.queue.is_empty() && self.relearn.is_empty() && self.learning.is_empty())
+    }
+
+    pub fn progress(&self) -> &SessionProgress {
+        &self.progress
+    }
+
+    pub fn summary(&self, now: DateTime<Utc>) -> SessionSummary {
+        SessionSummary {
+            reviewed: self.progress.reviewed,
+            correct: self.progress.correct,
+            relearned: self.relearned,
+            remaining: self.queue.len()
+                + self.relearn.len()
+                + self.learning.len()
+                + usize::from(self.serving.is_some()),
+            elapsed_seconds: self.started_at.map_or(0, |started_at| {
+                (now - started_at).num_seconds().max(0) as u64
+            }),
+            ended: self.ended,
+        }
+    }
+}
+
+/// How a learner coming back from a break works through the backlog: the
+/// overdue cards are spread over `days` days from `starts_on`, at most
+/// `daily_reviews` a day, and no new cards are introduced until it ends.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecoveryPlan {
+    /// Days between the last review and the start of the plan.
+    pub away_days: i64,
+    /// Reviewed cards that were due when the plan started.
+    pub overdue: usize,
+    /// Of those, cards that had reached a mature interval before the break.
+    pub mature: usize,
+    pub daily_reviews: usize,
+    pub days: usize,
+    pub starts_on: NaiveDate,
+}
+
+impl RecoveryPlan {
+    pub fn new(
+        away_days: i64,
+        overdue: usize,
+        mature: usize,
+        daily_reviews: usize,
+        starts_on: NaiveDate,
+    ) -> Self {
+        let daily_reviews = daily_reviews.max(1);
+        Self {
+            away_days,
+            overdue,
+            mature,
+            daily_reviews,
+            days: overdue.div_ceil(daily_reviews).max(1),
+            starts_on,
+        }
+    }
+
+    /// The last day of the plan.
+    pub fn ends_on(&self) -> NaiveDate {
+        self.starts_on + Duration::days(self.days as i64 - 1)
+    }
+
+    pub fn is_active(&self, today: NaiveDate) -> bool {
+        (self.starts_on..=self.ends_on()).contains(&today)
+    }
+
+    /// Days after `starts_on` the overdue card at `position` (in the order
+    /// the backlog comes back) is due again.
+    pub fn day_offset(&self, position: usize) -> usize {
+        position / self.daily_reviews
+    }
+}
+
+/// The calendar day `at` falls on in local time, which daily limits such as
+/// the new-card allowance are counted by.
+pub fn local_day(at: DateTime<Utc>) -> NaiveDate {
+    at.with_timezone(&Local).date_naive()
+}
+
+/// Whole days since `last_review` if they amount to a break.
+pub fn break_days(last_review: DateTime<Utc>, now: DateTime<Utc>) -> Option<i64> {
+    Some((now - last_review).num_days()).filter(|days| *days >= BREAK_DAYS)
+}
+
+/// One new card after every `due_per_new` due cards, then whatever is left.
+fn mix<T>(due: Vec<T>, new: Vec<T>, due_per_new: usize) -> Vec<T> {
+    let mut cards = Vec::with_capacity(due.len() + new.len());
+    let mut due = due.into_iter().peekable();
+    let mut new = new.into_iter();
+    while due.peek().is_some() {
+        cards.extend(due.by_ref().take(due_per_new));
+        cards.extend(new.next());
+    }
+    cards.extend(new);
+    cards
+}
+
+/// Places the new cards evenly between the due ones, each in the middle of
+/// its share of the queue, so a session neither opens nor ends on a run of
+/// unfamiliar words.
+fn interleave<T>(due: Vec<T>, new: Vec<T>) -> Vec<T> {
+    let total = due.len() + new.len();
+    let new_len = new.len();
+    let mut due = due.into_iter();
+    let mut new = new.into_iter();
+    let mut placed = 0;
+    let mut cards = Vec::with_capacity(total);
+    for position in 0..total {
+        let take_new = (2 * placed + 1) * total <= 2 * (position + 1) * new_len;
+        let card = if take_new {
+            new.next().inspect(|_| placed += 1)
+        } else {
+            due.next()
+        };
+        cards.extend(card.or_else(|| new.next()).or_else(|| due.next()));
+    }
+    cards
+}
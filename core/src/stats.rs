@@ -0,0 +1,124 @@
+//! Statistics derived from review history for plotting.
+
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{Grade, SchedulerConfig, default_new_card};
+
+/// Weight of the newest review in [`CurvePoint::accuracy`]; higher follows
+/// recent answers more closely.
+pub const ACCURACY_SMOOTHING: f64 = 0.3;
+
+/// One review on a word's learning curve.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurvePoint {
+    pub reviewed_at: DateTime<Utc>,
+    pub grade: Grade,
+    /// Interval the scheduler chose after this review.
+    pub interval_days: i32,
+    /// Exponential moving average of correct answers (grade 3 or above), from
+    /// 0.0 to 1.0.
+    pub accuracy: f64,
+}
+
+/// Replays `reviews` (grade and time, in any order) from a new card in the
+/// deck with id `deck`, learning steps included, as grading schedules it,
+/// and returns one point per review, oldest first.
+pub fn learning_curve(
+    reviews: &[(Grade, DateTime<Utc>)],
+    config: &SchedulerConfig,
+    deck: Option<&str>,
+) -> Vec<CurvePoint> {
+    let mut reviews = reviews.to_vec();
+    reviews.sort_by_key(|(_, reviewed_at)| *reviewed_at);
+    let Some((_, first_at)) = reviews.first() else {
+        return Vec::new();
+    };
+    let mut card = default_new_card(Uuid::nil(), *first_at);
+    let mut accuracy = None;
+    reviews
+        .into_iter()
+        .map(|(grade, reviewed_at)| {
+            config.schedule_card(&mut card, grade, reviewed_at, deck);
+            let correct = if grade.is_correct() { 1.0 } else { 0.0 };
+            let smoothed = match accuracy {
+                Some(previous) => {
+                    ACCURACY_SMOOTHING * correct + (1.0 - ACCURACY_SMOOTHING) * previous
+                }
+                None => correct,
+            };
+            accuracy = Some(smoothed);
+            CurvePoint {
+                reviewed_at,
+                grade,
+                interval_days: card.interval_days,
+                accuracy: smoothed,
+            }
+        })
+        .collect()
+}
+
+/// Reviews on one UTC day with at least one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReviewDay {
+    pub date: NaiveDate,
+    pub reviews: usize,
+    /// Reviews graded 3 or above.
+    pub correct: usize,
+}
+
+/// Review totals for the words of one chapter; `None` for words without one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChapterAccuracy {
+    pub chapter: Option<String>,
+    pub reviews: usize,
+    pub correct: usize,
+}
+
+impl ChapterAccuracy {
+    /// Share of reviews graded 3 or above, from 0.0 to 1.0.
+    pub fn accuracy(&self) -> f64 {
+        if self.reviews == 0 {
+            0.0
+        } else {
+            self.correct as f64 / self.reviews as f64
+        }
+    }
+}
+
+/// Current and longest runs of consecutive days with reviews.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Streak {
+    pub current: usize,
+    pub longest: usize,
+}
+
+/// Streaks over `days` (in any order). The current run still counts until a
+/// whole day passes without a review, so it includes yesterday when nothing
+/// was reviewed `today`.
+pub fn review_streak(days: &[ReviewDay], today: NaiveDate) -> Streak {
+    let mut dates: Vec<NaiveDate> = days
+        .iter()
+        .filter(|day| day.reviews > 0)
+        .map(|day| day.date)
+        .collect();
+    dates.sort();
+    dates.dedup();
+    let mut streak = Streak::default();
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for date in &dates {
+        run = match previous {
+            Some(previous) if previous.checked_add_days(Days::new(1)) == Some(*date) => run + 1,
+            _ => 1,
+        };
+        streak.longest = streak.longest.max(run);
+        previous = Some(*date);
+    }
+    let yesterday = today.checked_sub_days(Days::new(1));
+    if previous == Some(today) || (previous.is_some() && previous == yesterday) {
+        streak.current = run;
+    }
+    streak
+}
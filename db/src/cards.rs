@@ -0,0 +1,108 @@
+//! Card creation on the local SQLite database, shared by [`crate::SqliteDb`]
+//! and the GUI, so every way of adding a word gives it the cards the `cards`
+//! setting asks for. A word's basic card is inserted by the caller; these
+//! add the reverse one. Cloze cards depend on the example sentences only the
+//! GUI keeps, so it decides when to add them.
+
+use chrono::{DateTime, Utc};
+use le_core::{
+    CARD_SETTINGS_KEY, CardSettings, CardType, EntryKind, default_new_card, has_reverse_side,
+};
+use rusqlite::{Connection, OptionalExtension, params};
+use uuid::Uuid;
+
+use crate::store::{DbResult, parse_entry_kind, parse_setting};
+
+pub fn card_settings(conn: &Connection) -> DbResult<CardSettings> {
+    let value = conn
+        .query_row(
+            "SELECT value FROM settings WHERE key = ?1",
+            params![CARD_SETTINGS_KEY],
+            |row| row.get(0),
+        )
+        .optional()?;
+    parse_setting(CARD_SETTINGS_KEY, value)
+}
+
+/// Adds a reverse card for a word just saved, when `settings` asks for one
+/// and the word can be asked that way round. Returns whether it did.
+pub fn add_reverse_card(
+    conn: &Connection,
+    settings: &CardSettings,
+    word_id: Uuid,
+    kind: EntryKind,
+    translation: Option<&str>,
+    now: DateTime<Utc>,
+) -> DbResult<bool> {
+    if !settings.wants_reverse(kind, translation) {
+        return Ok(false);
+    }
+    insert_new_card(conn, word_id, CardType::Reverse, None, now)?;
+    Ok(true)
+}
+
+/// Gives every word that could have a reverse card but has none one, due
+/// `now` and in the word's deck, whatever the setting says. Returns how many
+/// were added.
+pub fn add_missing_reverse_cards(conn: &Connection, now: DateTime<Utc>) -> DbResult<usize> {
+    let tx = conn.unchecked_transaction()?;
+    let words = {
+        let mut stmt = tx.prepare(
+            "SELECT w.id, w.text, w.translation, w.kind, w.deck_id
+             FROM words w
+             WHERE NOT EXISTS (
+                 SELECT 1 FROM cards c WHERE c.word_id = w.id AND c.card_type = ?1)",
+        )?;
+        let rows = stmt.query_map(params![format!("{:?}", CardType::Reverse)], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+            ))
+        })?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()?
+    };
+    let mut added = 0;
+    for (word_id, text, translation, kind, deck_id) in words {
+        let Ok(word_id) = Uuid::parse_str(&word_id) else {
+            continue;
+        };
+        if !has_reverse_side(parse_entry_kind(kind, &text), translation.as_deref()) {
+            continue;
+        }
+        insert_new_card(&tx, word_id, CardType::Reverse, deck_id.as_deref(), now)?;
+        added += 1;
+    }
+    tx.commit()?;
+    Ok(added)
+}
+
+/// Inserts a new card of `card_type`, due `now`, in `deck_id`.
+pub fn insert_new_card(
+    conn: &Connection,
+    word_id: Uuid,
+    card_type: CardType,
+    deck_id: Option<&str>,
+    now: DateTime<Utc>,
+) -> DbResult<()> {
+    let card = default_new_card(word_id, now);
+    conn.execute(
+        "INSERT INTO cards (id, word_id, due_at, interval_days, ease, reps, lapses, seen_count,
+                            deck_id, card_type)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 0, ?8, ?9)",
+        params![
+            card.id.to_string(),
+            card.word_id.to_string(),
+            card.due_at.to_rfc3339(),
+            card.interval_days,
+            card.ease,
+            card.reps,
+            card.lapses,
+            deck_id,
+            format!("{:?}", card_type)
+        ],
+    )?;
+    Ok(())
+}
@@ -0,0 +1,125 @@
+//! Deck queries on the local SQLite database, shared by [`crate::SqliteDb`]
+//! and the GUI. A word's deck is kept on both the word and its card, so
+//! session queries can filter cards without another join; moving a word
+//! updates both.
+
+use chrono::Utc;
+use rusqlite::{Connection, OptionalExtension, params};
+use uuid::Uuid;
+
+use crate::store::{DbError, DbResult, Deck, normalize_deck_name, parse_timestamp};
+
+pub fn list_decks(conn: &Connection) -> DbResult<Vec<Deck>> {
+    let mut stmt = conn.prepare(
+        "SELECT d.id, d.name, d.created_at,
+                (SELECT COUNT(*) FROM words w WHERE w.deck_id = d.id)
+         FROM decks d
+         ORDER BY lower(d.name)",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(Deck {
+            id: Uuid::parse_str(&row.get::<_, String>(0)?).unwrap_or_else(|_| Uuid::new_v4()),
+            name: row.get(1)?,
+            created_at: parse_timestamp(&row.get::<_, String>(2)?),
+            words: row.get::<_, i64>(3)? as usize,
+        })
+    })?;
+    let mut decks = Vec::new();
+    for deck in rows {
+        decks.push(deck?);
+    }
+    Ok(decks)
+}
+
+pub fn create_deck(conn: &Connection, name: &str) -> DbResult<Deck> {
+    let name = normalize_deck_name(name)?;
+    ensure_unique(conn, &name, None)?;
+    let deck = Deck {
+        id: Uuid::new_v4(),
+        name,
+        created_at: Utc::now(),
+        words: 0,
+    };
+    conn.execute(
+        "INSERT INTO decks (id, name, created_at) VALUES (?1, ?2, ?3)",
+        params![deck.id.to_string(), deck.name, deck.created_at.to_rfc3339()],
+    )?;
+    Ok(deck)
+}
+
+pub fn rename_deck(conn: &Connection, deck_id: Uuid, name: &str) -> DbResult<()> {
+    let name = normalize_deck_name(name)?;
+    ensure_unique(conn, &name, Some(deck_id))?;
+    let updated = conn.execute(
+        "UPDATE decks SET name = ?2 WHERE id = ?1",
+        params![deck_id.to_string(), name],
+    )?;
+    if updated == 0 {
+        return Err(DbError::Config(format!("No deck with id {deck_id}")));
+    }
+    Ok(())
+}
+
+pub fn delete_deck(conn: &Connection, deck_id: Uuid) -> DbResult<()> {
+    let tx = conn.unchecked_transaction()?;
+    let deck_id = deck_id.to_string();
+    tx.execute(
+        "UPDATE words SET deck_id = NULL WHERE deck_id = ?1",
+        params![deck_id],
+    )?;
+    tx.execute(
+        "UPDATE cards SET deck_id = NULL WHERE deck_id = ?1",
+        params![deck_id],
+    )?;
+    tx.execute("DELETE FROM decks WHERE id = ?1", params![deck_id])?;
+    tx.commit()?;
+    Ok(())
+}
+
+pub fn move_to_deck(conn: &Connection, word_ids: &[Uuid], deck_id: Option<Uuid>) -> DbResult<()> {
+    let deck_id = deck_id.map(|id| id.to_string());
+    let tx = conn.unchecked_transaction()?;
+    if let Some(deck_id) = &deck_id {
+        let exists = tx
+            .query_row(
+                "SELECT 1 FROM decks WHERE id = ?1",
+                params![deck_id],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some();
+        if !exists {
+            return Err(DbError::Config(format!("No deck with id {deck_id}")));
+        }
+    }
+    for word_id in word_ids {
+        let word_id = word_id.to_string();
+        tx.execute(
+            "UPDATE words SET deck_id = ?2 WHERE id = ?1",
+            params![word_id, deck_id],
+        )?;
+        tx.execute(
+            "UPDATE cards SET deck_id = ?2 WHERE word_id = ?1",
+            params![word_id, deck_id],
+        )?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+fn ensure_unique(conn: &Connection, name: &str, except: Option<Uuid>) -> DbResult<()> {
+    let taken = conn
+        .query_row(
+            "SELECT 1 FROM decks WHERE lower(name) = lower(?1) AND (?2 IS NULL OR id != ?2)",
+            params![name, except.map(|id| id.to_string())],
+            |_| Ok(()),
+        )
+        .optional()?
+        .is_some();
+    if taken {
+        return Err(DbError::Config(format!(
+            "A deck named '{name}' already exists"
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,58 @@
+//! Glossary queries on the local SQLite database, shared by
+//! [`crate::SqliteDb`] and the GUI. Entries are keyed by language and
+//! [`glossary_key`], so saving a term again in another case replaces it.
+
+use le_core::Language;
+use le_core::glossary::{GlossaryEntry, glossary_key};
+use rusqlite::{Connection, params};
+
+use crate::store::{DbResult, glossary_columns, parse_timestamp};
+
+pub fn list_glossary(conn: &Connection) -> DbResult<Vec<GlossaryEntry>> {
+    let mut stmt = conn.prepare(
+        "SELECT term, language, translation, updated_at FROM glossary
+         ORDER BY language, term_key",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(GlossaryEntry {
+            term: row.get(0)?,
+            language: Language::new(&row.get::<_, String>(1)?),
+            translation: row.get(2)?,
+            updated_at: parse_timestamp(&row.get::<_, String>(3)?),
+        })
+    })?;
+    let mut entries = Vec::new();
+    for entry in rows {
+        entries.push(entry?);
+    }
+    Ok(entries)
+}
+
+pub fn save_glossary_entry(conn: &Connection, entry: &GlossaryEntry) -> DbResult<()> {
+    let (key, translation) = glossary_columns(entry)?;
+    conn.execute(
+        "INSERT INTO glossary (language, term_key, term, translation, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(language, term_key) DO UPDATE SET
+            term = excluded.term,
+            translation = excluded.translation,
+            updated_at = excluded.updated_at",
+        params![
+            entry.language.code(),
+            key,
+            entry.term.trim(),
+            translation,
+            entry.updated_at.to_rfc3339()
+        ],
+    )?;
+    Ok(())
+}
+
+/// Removes the entry for `term`; `false` when there was none.
+pub fn delete_glossary_entry(conn: &Connection, language: &Language, term: &str) -> DbResult<bool> {
+    let deleted = conn.execute(
+        "DELETE FROM glossary WHERE language = ?1 AND term_key = ?2",
+        params![language.code(), glossary_key(term)],
+    )?;
+    Ok(deleted > 0)
+}
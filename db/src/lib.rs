@@ -0,0 +1,189 @@
+//! Versioned schema migrations shared by every binary, so the TUI, the GUI,
+//! and Postgres all end up with the same tables and columns. Each database
+//! records the migrations it has run in `schema_version`; the runners apply
+//! the missing ones from [`MIGRATIONS`] in order, each in its own
+//! transaction. Databases created before this table existed start at
+//! version 0: their tables already exist, and adding a column is skipped
+//! where the column is already there, so they are brought up to date
+//! without losing data.
+//!
+//! On top of the schema sit the [`Db`] word store with its SQLite and
+//! Postgres backends, used by the TUI, and the card queries review sessions
+//! are built from ([`session`]), used by the GUI. The SQLite deck queries
+//! ([`decks`]), glossary queries ([`glossary`]), and card creation
+//! ([`cards`]) are shared the same way.
+
+#[cfg(feature = "sqlite")]
+pub mod cards;
+#[cfg(feature = "sqlite")]
+pub mod decks;
+#[cfg(feature = "sqlite")]
+pub mod glossary;
+mod migrations;
+#[cfg(feature = "postgres")]
+mod postgres;
+#[cfg(feature = "sqlite")]
+pub mod session;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod store;
+
+pub use migrations::MIGRATIONS;
+#[cfg(feature = "postgres")]
+pub use postgres::PostgresDb;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteDb;
+pub use store::{
+    CleanupEntryRow, Db, DbError, DbResult, Deck, ImportProfile, NewWord, duplicate_key,
+    duplicate_pattern, find_import_profile, log_error, log_import, normalize_deck_name,
+    normalize_tag, source_pattern,
+};
+
+/// One schema change. Versions start at 1 and increase by one; a released
+/// migration is never edited, only followed by a new one.
+#[derive(Debug)]
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    pub steps: &'static [Step],
+}
+
+#[derive(Debug)]
+pub enum Step {
+    /// Statements run as they are on both backends.
+    Sql(&'static str),
+    /// Statements only run on SQLite.
+    Sqlite(&'static str),
+    /// Statements only run on Postgres.
+    Postgres(&'static str),
+    /// Adds `column` to `table` unless it exists, with the given type and
+    /// constraints per backend. `None` leaves that backend without it, for
+    /// columns only the local database keeps.
+    AddColumn {
+        table: &'static str,
+        column: &'static str,
+        sqlite: Option<&'static str>,
+        postgres: Option<&'static str>,
+    },
+}
+
+/// The version a fully migrated database is at.
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |migration| migration.version)
+}
+
+#[cfg(feature = "sqlite")]
+const SQLITE_SCHEMA_VERSION: &str = "CREATE TABLE IF NOT EXISTS schema_version (
+    version INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    applied_at TEXT NOT NULL
+)";
+
+/// Brings a SQLite database up to [`latest_version`] and returns it. A
+/// database already migrated by a newer binary is left as it is.
+#[cfg(feature = "sqlite")]
+pub fn migrate_sqlite(conn: &rusqlite::Connection) -> rusqlite::Result<u32> {
+    conn.execute(SQLITE_SCHEMA_VERSION, [])?;
+    let current: u32 = conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_version",
+        [],
+        |row| row.get(0),
+    )?;
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        let tx = conn.unchecked_transaction()?;
+        for step in migration.steps {
+            match step {
+                Step::Sql(sql) | Step::Sqlite(sql) => tx.execute_batch(sql)?,
+                Step::Postgres(_) => {}
+                Step::AddColumn {
+                    table,
+                    column,
+                    sqlite: Some(definition),
+                    ..
+                } => {
+                    let exists: bool = tx.query_row(
+                        &format!(
+                            "SELECT EXISTS(SELECT 1 FROM pragma_table_info('{table}') WHERE name = ?1)"
+                        ),
+                        [column],
+                        |row| row.get(0),
+                    )?;
+                    if !exists {
+                        tx.execute_batch(&format!(
+                            "ALTER TABLE {table} ADD COLUMN {column} {definition}"
+                        ))?;
+                    }
+                }
+                Step::AddColumn { sqlite: None, .. } => {}
+            }
+        }
+        tx.execute(
+            "INSERT INTO schema_version (version, name, applied_at) VALUES (?1, ?2, ?3)",
+            rusqlite::params![
+                migration.version,
+                migration.name,
+                chrono::Utc::now().to_rfc3339()
+            ],
+        )?;
+        tx.commit()?;
+    }
+    Ok(current.max(latest_version()))
+}
+
+/// Key of the advisory lock that keeps two clients starting at once from
+/// running the same Postgres migration twice.
+#[cfg(feature = "postgres")]
+const POSTGRES_LOCK_KEY: i64 = 0x006c_655f_6462;
+
+/// Brings a Postgres database up to [`latest_version`] and returns it.
+/// Pending migrations run under an advisory lock and are re-checked once it
+/// is held, so concurrent clients apply each one exactly once.
+#[cfg(feature = "postgres")]
+pub fn migrate_postgres(client: &mut ::postgres::Client) -> Result<u32, ::postgres::Error> {
+    client.batch_execute(
+        "CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            applied_at TEXT NOT NULL
+        )",
+    )?;
+    let current: i32 = client
+        .query_one("SELECT COALESCE(MAX(version), 0) FROM schema_version", &[])?
+        .get(0);
+    let current = current as u32;
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        let version = migration.version as i32;
+        let mut tx = client.transaction()?;
+        tx.execute("SELECT pg_advisory_xact_lock($1)", &[&POSTGRES_LOCK_KEY])?;
+        if tx
+            .query_opt(
+                "SELECT 1 FROM schema_version WHERE version = $1",
+                &[&version],
+            )?
+            .is_some()
+        {
+            continue;
+        }
+        for step in migration.steps {
+            match step {
+                Step::Sql(sql) | Step::Postgres(sql) => tx.batch_execute(sql)?,
+                Step::Sqlite(_) => {}
+                Step::AddColumn {
+                    table,
+                    column,
+                    postgres: Some(definition),
+                    ..
+                } => tx.batch_execute(&format!(
+                    "ALTER TABLE {table} ADD COLUMN IF NOT EXISTS {column} {definition}"
+                ))?,
+                Step::AddColumn { postgres: None, .. } => {}
+            }
+        }
+        tx.execute(
+            "INSERT INTO schema_version (version, name, applied_at) VALUES ($1, $2, $3)",
+            &[&version, &migration.name, &chrono::Utc::now().to_rfc3339()],
+        )?;
+        tx.commit()?;
+    }
+    Ok(current.max(latest_version()))
+}
@@ -0,0 +1,426 @@
+//! The schema's history, oldest first. Columns added by a later migration
+//! are left out of the tables the initial one creates.
+
+use crate::{Migration, Step};
+
+/// A nullable `TEXT` column on both backends.
+const fn text(table: &'static str, column: &'static str) -> Step {
+    Step::AddColumn {
+        table,
+        column,
+        sqlite: Some("TEXT"),
+        postgres: Some("TEXT"),
+    }
+}
+
+/// A column only the local SQLite database keeps, such as per-device
+/// scheduling state that is never synced.
+const fn local(table: &'static str, column: &'static str, definition: &'static str) -> Step {
+    Step::AddColumn {
+        table,
+        column,
+        sqlite: Some(definition),
+        postgres: None,
+    }
+}
+
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial_schema",
+        steps: &[
+            Step::Sqlite(
+                "CREATE TABLE IF NOT EXISTS words (
+                    id TEXT PRIMARY KEY,
+                    text TEXT NOT NULL,
+                    language TEXT NOT NULL,
+                    created_at TEXT NOT NULL
+                );
+                CREATE TABLE IF NOT EXISTS cards (
+                    id TEXT PRIMARY KEY,
+                    word_id TEXT NOT NULL,
+                    due_at TEXT NOT NULL,
+                    interval_days INTEGER NOT NULL,
+                    ease REAL NOT NULL,
+                    reps INTEGER NOT NULL,
+                    lapses INTEGER NOT NULL,
+                    FOREIGN KEY(word_id) REFERENCES words(id)
+                );
+                CREATE TABLE IF NOT EXISTS reviews (
+                    id TEXT PRIMARY KEY,
+                    card_id TEXT NOT NULL,
+                    grade INTEGER NOT NULL,
+                    reviewed_at TEXT NOT NULL,
+                    FOREIGN KEY(card_id) REFERENCES cards(id)
+                );
+                CREATE TABLE IF NOT EXISTS settings (
+                    key TEXT PRIMARY KEY,
+                    value TEXT NOT NULL,
+                    updated_at TEXT NOT NULL
+                );",
+            ),
+            Step::Postgres(
+                "CREATE TABLE IF NOT EXISTS words (
+                    id TEXT PRIMARY KEY,
+                    text TEXT NOT NULL,
+                    language TEXT NOT NULL,
+                    created_at TEXT NOT NULL
+                );
+                CREATE TABLE IF NOT EXISTS cards (
+                    id TEXT PRIMARY KEY,
+                    word_id TEXT NOT NULL REFERENCES words(id),
+                    due_at TEXT NOT NULL,
+                    interval_days INTEGER NOT NULL,
+                    ease DOUBLE PRECISION NOT NULL,
+                    reps INTEGER NOT NULL,
+                    lapses INTEGER NOT NULL
+                );
+                CREATE TABLE IF NOT EXISTS reviews (
+                    id TEXT PRIMARY KEY,
+                    card_id TEXT NOT NULL REFERENCES cards(id),
+                    grade INTEGER NOT NULL,
+                    reviewed_at TEXT NOT NULL
+                );",
+            ),
+            text("words", "translation"),
+            text("words", "chapter"),
+            text("words", "group_name"),
+            text("words", "notes"),
+        ],
+    },
+    Migration {
+        version: 2,
+        name: "concepts_and_tags",
+        steps: &[
+            Step::Sqlite(
+                "CREATE TABLE IF NOT EXISTS concepts (
+                    id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL UNIQUE,
+                    created_at TEXT NOT NULL
+                );
+                CREATE TABLE IF NOT EXISTS tags (
+                    id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL UNIQUE,
+                    created_at TEXT NOT NULL
+                );
+                CREATE TABLE IF NOT EXISTS word_tags (
+                    word_id TEXT NOT NULL,
+                    tag_id TEXT NOT NULL,
+                    PRIMARY KEY(word_id, tag_id),
+                    FOREIGN KEY(word_id) REFERENCES words(id),
+                    FOREIGN KEY(tag_id) REFERENCES tags(id)
+                );",
+            ),
+            Step::Postgres(
+                "CREATE TABLE IF NOT EXISTS concepts (
+                    id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL UNIQUE,
+                    created_at TEXT NOT NULL
+                );
+                CREATE TABLE IF NOT EXISTS tags (
+                    id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL UNIQUE,
+                    created_at TEXT NOT NULL
+                );
+                CREATE TABLE IF NOT EXISTS word_tags (
+                    word_id TEXT NOT NULL REFERENCES words(id),
+                    tag_id TEXT NOT NULL REFERENCES tags(id),
+                    PRIMARY KEY (word_id, tag_id)
+                );",
+            ),
+        ],
+    },
+    Migration {
+        version: 3,
+        name: "word_details",
+        steps: &[
+            text("words", "cleanup_at"),
+            text("words", "source_type"),
+            text("words", "source_name"),
+            text("words", "source_page"),
+            text("words", "captured_at"),
+            text("words", "source_region"),
+            text("words", "forms"),
+            text("words", "alt_answers"),
+            text("words", "kind"),
+        ],
+    },
+    Migration {
+        version: 4,
+        name: "local_card_state",
+        steps: &[
+            local("cards", "seen_count", "INTEGER NOT NULL DEFAULT 0"),
+            local("cards", "batch_id", "INTEGER NOT NULL DEFAULT -1"),
+            local("cards", "stability", "REAL"),
+            local("cards", "difficulty", "REAL"),
+        ],
+    },
+    Migration {
+        version: 5,
+        name: "card_hiding",
+        steps: &[
+            Step::AddColumn {
+                table: "cards",
+                column: "suspended",
+                sqlite: Some("INTEGER NOT NULL DEFAULT 0"),
+                postgres: Some("BOOLEAN NOT NULL DEFAULT FALSE"),
+            },
+            text("cards", "buried_until"),
+        ],
+    },
+    Migration {
+        version: 6,
+        name: "import_profiles",
+        steps: &[Step::Sql(
+            "CREATE TABLE IF NOT EXISTS import_profiles (
+                pattern TEXT PRIMARY KEY,
+                columns TEXT,
+                ocr_profile TEXT,
+                chapter TEXT,
+                updated_at TEXT NOT NULL
+            );",
+        )],
+    },
+    Migration {
+        version: 7,
+        name: "word_search",
+        steps: &[
+            // FTS5 index kept in step with `words` by triggers, rebuilt here
+            // in case an earlier build created it already. Accents are
+            // folded so "cafe" finds "café".
+            Step::Sqlite(
+                "CREATE VIRTUAL TABLE IF NOT EXISTS words_fts USING fts5(
+                    word_id UNINDEXED,
+                    text,
+                    translation,
+                    notes,
+                    tokenize = 'unicode61 remove_diacritics 2'
+                );
+                CREATE TRIGGER IF NOT EXISTS words_fts_insert AFTER INSERT ON words BEGIN
+                    INSERT INTO words_fts (word_id, text, translation, notes)
+                    VALUES (new.id, new.text, new.translation, new.notes);
+                END;
+                CREATE TRIGGER IF NOT EXISTS words_fts_update AFTER UPDATE ON words BEGIN
+                    DELETE FROM words_fts WHERE word_id = old.id;
+                    INSERT INTO words_fts (word_id, text, translation, notes)
+                    VALUES (new.id, new.text, new.translation, new.notes);
+                END;
+                CREATE TRIGGER IF NOT EXISTS words_fts_delete AFTER DELETE ON words BEGIN
+                    DELETE FROM words_fts WHERE word_id = old.id;
+                END;
+                DELETE FROM words_fts;
+                INSERT INTO words_fts (word_id, text, translation, notes)
+                    SELECT id, text, translation, notes FROM words;",
+            ),
+            Step::Postgres(
+                "CREATE EXTENSION IF NOT EXISTS pg_trgm;
+                CREATE INDEX IF NOT EXISTS words_search_trgm ON words USING gin (
+                    (text || ' ' || COALESCE(translation, '') || ' ' || COALESCE(notes, ''))
+                    gin_trgm_ops
+                );",
+            ),
+        ],
+    },
+    Migration {
+        version: 8,
+        name: "decks",
+        steps: &[
+            Step::Sql(
+                "CREATE TABLE IF NOT EXISTS decks (
+                    id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL UNIQUE,
+                    created_at TEXT NOT NULL
+                );",
+            ),
+            text("words", "deck_id"),
+            text("cards", "deck_id"),
+        ],
+    },
+    Migration {
+        version: 9,
+        name: "card_type",
+        steps: &[
+            // NULL is a basic forward card, as every card was before.
+            text("cards", "card_type"),
+        ],
+    },
+    Migration {
+        version: 10,
+        name: "glossary",
+        steps: &[Step::Sql(
+            "CREATE TABLE IF NOT EXISTS glossary (
+                language TEXT NOT NULL,
+                term_key TEXT NOT NULL,
+                term TEXT NOT NULL,
+                translation TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                PRIMARY KEY (language, term_key)
+            );",
+        )],
+    },
+    Migration {
+        version: 11,
+        name: "learning_steps",
+        steps: &[
+            local("cards", "learning_step", "INTEGER"),
+            local("cards", "learning_due_at", "TEXT"),
+            // Per local calendar day, so the new-card limit holds across
+            // sessions.
+            Step::Sql(
+                "CREATE TABLE IF NOT EXISTS daily_stats (
+                    day TEXT PRIMARY KEY,
+                    new_cards INTEGER NOT NULL DEFAULT 0,
+                    reviews INTEGER NOT NULL DEFAULT 0
+                );",
+            ),
+        ],
+    },
+    Migration {
+        version: 12,
+        name: "device_tables",
+        // Tables the GUI keeps on this device only: timings, usage, undo
+        // history, the sync base, and generated sentences and quizzes.
+        steps: &[Step::Sqlite(
+            "CREATE TABLE IF NOT EXISTS perf_log (
+                id INTEGER PRIMARY KEY,
+                command TEXT NOT NULL,
+                started_at TEXT NOT NULL,
+                total_ms REAL NOT NULL,
+                db_ms REAL NOT NULL,
+                network_ms REAL NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS perf_log_started_at ON perf_log(started_at);
+            CREATE TABLE IF NOT EXISTS usage_sessions (
+                id INTEGER PRIMARY KEY,
+                started_at TEXT NOT NULL,
+                last_review_at TEXT,
+                reviews INTEGER NOT NULL DEFAULT 0
+            );
+            CREATE TABLE IF NOT EXISTS usage_features (
+                feature TEXT PRIMARY KEY,
+                uses INTEGER NOT NULL,
+                last_used_at TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS word_corrections (
+                id INTEGER PRIMARY KEY,
+                word_id TEXT NOT NULL,
+                old_text TEXT NOT NULL,
+                old_translation TEXT,
+                old_alt_answers TEXT,
+                new_text TEXT NOT NULL,
+                new_translation TEXT,
+                new_alt_answers TEXT,
+                synced INTEGER NOT NULL,
+                applied_at TEXT NOT NULL,
+                reverted_at TEXT
+            );
+            CREATE TABLE IF NOT EXISTS card_state_edits (
+                id INTEGER PRIMARY KEY,
+                card_id TEXT NOT NULL,
+                before TEXT NOT NULL,
+                after TEXT NOT NULL,
+                edited_at TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS examples (
+                word_id TEXT PRIMARY KEY,
+                sentence TEXT NOT NULL,
+                translation TEXT,
+                created_at TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS sentences (
+                id INTEGER PRIMARY KEY,
+                word_id TEXT NOT NULL,
+                sentence TEXT NOT NULL,
+                translation TEXT,
+                created_at TEXT NOT NULL,
+                last_shown_at TEXT,
+                UNIQUE (word_id, sentence)
+            );
+            INSERT OR IGNORE INTO sentences (word_id, sentence, translation, created_at)
+                SELECT word_id, sentence, translation, created_at FROM examples;
+            CREATE TABLE IF NOT EXISTS sync_base (
+                table_name TEXT NOT NULL,
+                row_id TEXT NOT NULL,
+                row TEXT NOT NULL,
+                synced_at TEXT NOT NULL,
+                PRIMARY KEY (table_name, row_id)
+            );
+            CREATE TABLE IF NOT EXISTS card_history (
+                id INTEGER PRIMARY KEY,
+                review_id TEXT NOT NULL,
+                card_id TEXT NOT NULL,
+                grade INTEGER NOT NULL,
+                due_at TEXT NOT NULL,
+                interval_days INTEGER NOT NULL,
+                ease REAL NOT NULL,
+                reps INTEGER NOT NULL,
+                lapses INTEGER NOT NULL,
+                stability REAL,
+                difficulty REAL,
+                seen_count INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS tests (
+                id INTEGER PRIMARY KEY,
+                quiz_id TEXT NOT NULL,
+                word_id TEXT NOT NULL,
+                chapter TEXT NOT NULL,
+                prompt TEXT NOT NULL,
+                answer TEXT NOT NULL,
+                choices TEXT NOT NULL,
+                chosen TEXT,
+                correct INTEGER,
+                created_at TEXT NOT NULL,
+                answered_at TEXT
+            );
+            CREATE TABLE IF NOT EXISTS batch_meta (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );
+            INSERT OR IGNORE INTO batch_meta (key, value) VALUES ('active_batch', '0');",
+        )],
+    },
+    Migration {
+        version: 13,
+        name: "card_history_learning_steps",
+        // Undo history keeps the learning step the card was on.
+        steps: &[
+            local("card_history", "learning_step", "INTEGER"),
+            local("card_history", "learning_due_at", "TEXT"),
+        ],
+    },
+    Migration {
+        version: 14,
+        name: "learning_steps_everywhere",
+        // The TUI can review straight against Postgres, which has to keep
+        // the learning step too. SQLite already has both columns.
+        steps: &[
+            Step::AddColumn {
+                table: "cards",
+                column: "learning_step",
+                sqlite: Some("INTEGER"),
+                postgres: Some("INTEGER"),
+            },
+            text("cards", "learning_due_at"),
+        ],
+    },
+    Migration {
+        version: 15,
+        name: "fsrs_state_everywhere",
+        // Likewise FSRS memory state, so FSRS in the TUI against Postgres
+        // builds on its last review instead of re-estimating it.
+        steps: &[
+            Step::AddColumn {
+                table: "cards",
+                column: "stability",
+                sqlite: Some("REAL"),
+                postgres: Some("DOUBLE PRECISION"),
+            },
+            Step::AddColumn {
+                table: "cards",
+                column: "difficulty",
+                sqlite: Some("REAL"),
+                postgres: Some("DOUBLE PRECISION"),
+            },
+        ],
+    },
+];
@@ -0,0 +1,1255 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use le_core::glossary::{GlossaryEntry, glossary_key};
+use le_core::session::local_day;
+use le_core::stats::{ChapterAccuracy, ReviewDay};
+use le_core::{
+    CARD_SETTINGS_KEY, Card, CardSettings, CardType, EntryKind, Grade, Language, UI_PREFS_KEY,
+    UiPrefs, Word, WordSource, default_new_card, has_reverse_side,
+};
+use postgres::{Client, GenericClient};
+use postgres_native_tls::MakeTlsConnector;
+use uuid::Uuid;
+
+use crate::store::{
+    CleanupEntryRow, Db, DbError, DbResult, Deck, ImportProfile, NewWord, duplicate_key,
+    duplicate_pattern, escape_like, glossary_columns, is_lemma, lemma_pattern, merge_forms,
+    normalize_deck_name, normalize_tag, parse_entry_kind, parse_setting, parse_string_list,
+    parse_timestamp, review_day, source_columns,
+};
+
+pub struct PostgresDb {
+    client: Mutex<Client>,
+}
+
+fn sql_log_path() -> Option<String> {
+    std::env::var("LOG_SQL_PATH").ok()
+}
+
+fn log_sql(query: &str, params: &[(&str, String)]) {
+    let Some(path) = sql_log_path() else {
+        return;
+    };
+    let mut line = String::new();
+    line.push_str("[sql] ");
+    line.push_str(query);
+    for (name, value) in params {
+        line.push_str("\n  - ");
+        line.push_str(name);
+        line.push_str(" = ");
+        line.push_str(value);
+    }
+    line.push('\n');
+    if let Ok(mut file) = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+    {
+        use std::io::Write;
+        let _ = file.write_all(line.as_bytes());
+    }
+}
+
+fn card_from_row(row: &postgres::Row) -> Card {
+    Card {
+        id: Uuid::parse_str(row.get::<_, String>(0).as_str()).unwrap_or_else(|_| Uuid::new_v4()),
+        word_id: Uuid::parse_str(row.get::<_, String>(1).as_str())
+            .unwrap_or_else(|_| Uuid::new_v4()),
+        due_at: parse_timestamp(row.get::<_, String>(2).as_str()),
+        interval_days: row.get(3),
+        ease: row.get(4),
+        reps: row.get(5),
+        lapses: row.get(6),
+        stability: row.get(10),
+        difficulty: row.get(11),
+        last_reviewed_at: row
+            .get::<_, Option<String>>(12)
+            .map(|at| parse_timestamp(&at)),
+        card_type: CardType::from_column(row.get::<_, Option<String>>(7).as_deref()),
+        learning_step: row
+            .get::<_, Option<i32>>(8)
+            .and_then(|step| u32::try_from(step).ok()),
+        learning_due_at: row
+            .get::<_, Option<String>>(9)
+            .map(|at| parse_timestamp(&at)),
+    }
+}
+
+fn word_from_row(row: &postgres::Row) -> Word {
+    let language = Language::new(&row.get::<_, String>(2));
+    let created_at = DateTime::parse_from_rfc3339(row.get::<_, String>(7).as_str())
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now());
+    let text: String = row.get(1);
+    let kind = parse_entry_kind(row.get(15), &text);
+    Word {
+        id: Uuid::parse_str(row.get::<_, String>(0).as_str()).unwrap_or_else(|_| Uuid::new_v4()),
+        text,
+        language,
+        translation: row.get(3),
+        chapter: row.get(4),
+        group: row.get(5),
+        notes: row.get(6),
+        created_at,
+        source: WordSource::from_columns(
+            row.get(8),
+            row.get(9),
+            row.get(10),
+            row.get(11),
+            row.get(12),
+        ),
+        forms: parse_string_list(row.get(13)),
+        alt_answers: parse_string_list(row.get(14)),
+        kind,
+        deck_id: row
+            .get::<_, Option<String>>(16)
+            .and_then(|id| Uuid::parse_str(&id).ok()),
+    }
+}
+
+impl PostgresDb {
+    pub fn connect(url: &str, tls: MakeTlsConnector) -> DbResult<Self> {
+        let client = Client::connect(url, tls)?;
+        Ok(Self {
+            client: Mutex::new(client),
+        })
+    }
+
+    pub fn open(_path: &Path) -> DbResult<Self> {
+        Err(crate::DbError::Config(
+            "Postgres backend requires DATABASE_URL".to_string(),
+        ))
+    }
+
+    fn update_card(&self, sql: &str, word_id: Uuid, until: Option<String>) -> DbResult<()> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        let word_id = word_id.to_string();
+        match until {
+            Some(until) => client.execute(sql, &[&word_id, &until])?,
+            None => client.execute(sql, &[&word_id])?,
+        };
+        Ok(())
+    }
+}
+
+impl Db for PostgresDb {
+    fn init(&self) -> DbResult<()> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        crate::migrate_postgres(&mut client)?;
+        client.batch_execute(
+            "GRANT USAGE ON SCHEMA public TO authenticated;
+            GRANT SELECT, INSERT, UPDATE, DELETE ON public.words TO authenticated;
+            GRANT SELECT, INSERT, UPDATE, DELETE ON public.cards TO authenticated;
+            GRANT SELECT, INSERT, UPDATE, DELETE ON public.reviews TO authenticated;
+            GRANT SELECT, INSERT, UPDATE, DELETE ON public.concepts TO authenticated;
+            GRANT SELECT, INSERT, UPDATE, DELETE ON public.tags TO authenticated;
+            GRANT SELECT, INSERT, UPDATE, DELETE ON public.word_tags TO authenticated;
+            GRANT SELECT, INSERT, UPDATE, DELETE ON public.import_profiles TO authenticated;
+            GRANT SELECT, INSERT, UPDATE, DELETE ON public.decks TO authenticated;
+            ",
+        )?;
+        Ok(())
+    }
+
+    fn save_word(
+        &self,
+        text: &str,
+        translation: &str,
+        language: &Language,
+        chapter: Option<&str>,
+        group: Option<&str>,
+        source: Option<&WordSource>,
+    ) -> DbResult<()> {
+        let now = Utc::now();
+        let word = Word {
+            id: Uuid::new_v4(),
+            text: text.to_string(),
+            translation: Some(translation.to_string()),
+            chapter: chapter.map(|value| value.to_string()),
+            group: group.map(|value| value.to_string()),
+            language: language.clone(),
+            notes: None,
+            created_at: now,
+            source: source.cloned(),
+            forms: Vec::new(),
+            alt_answers: Vec::new(),
+            kind: EntryKind::detect(text),
+            deck_id: None,
+        };
+
+        let card = default_new_card(word.id, now);
+        let language_value = word.language.to_column();
+        let created_at = word.created_at.to_rfc3339();
+        let due_at = card.due_at.to_rfc3339();
+        let interval_days = card.interval_days;
+        let ease = card.ease;
+        let reps = card.reps;
+        let lapses = card.lapses;
+
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        let translation = word.translation.clone();
+        let chapter = word.chapter.clone();
+        let group = word.group.clone();
+        let (source_type, source_name, source_page, captured_at, source_region) =
+            source_columns(word.source.as_ref());
+
+        client
+            .execute(
+            "INSERT INTO words (id, text, language, translation, chapter, group_name, notes, created_at, source_type, source_name, source_page, captured_at, source_region, kind)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)",
+                &[
+                    &word.id.to_string(),
+                    &word.text,
+                    &language_value,
+                    &translation,
+                    &chapter,
+                    &group,
+                    &word.notes,
+                    &created_at,
+                    &source_type,
+                    &source_name,
+                    &source_page,
+                    &captured_at,
+                    &source_region,
+                    &format!("{:?}", word.kind),
+                ],
+            )
+            .map_err(|err| {
+                let message = format!("Postgres words insert failed: {err}");
+                crate::log_error(&message);
+                crate::DbError::Config(message)
+            })?;
+
+        log_sql(
+            "INSERT INTO words (...) VALUES ($1..$7)",
+            &[
+                ("id", word.id.to_string()),
+                ("text", word.text.clone()),
+                ("language", language_value.clone()),
+                ("translation", word.translation.clone().unwrap_or_default()),
+                ("chapter", word.chapter.clone().unwrap_or_default()),
+                ("group_name", word.group.clone().unwrap_or_default()),
+                ("created_at", created_at.clone()),
+            ],
+        );
+
+        log_sql(
+            "INSERT INTO cards (...) VALUES ($1..$7)",
+            &[
+                ("id", card.id.to_string()),
+                ("word_id", card.word_id.to_string()),
+                ("due_at", due_at.clone()),
+                ("interval_days", interval_days.to_string()),
+                ("ease", ease.to_string()),
+                ("reps", reps.to_string()),
+                ("lapses", lapses.to_string()),
+            ],
+        );
+
+        let card_id = card.id.to_string();
+        let word_id = card.word_id.to_string();
+
+        client
+            .execute(
+                "INSERT INTO cards (id, word_id, due_at, interval_days, ease, reps, lapses)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7)",
+                &[
+                    &card_id,
+                    &word_id,
+                    &due_at,
+                    &interval_days,
+                    &ease,
+                    &reps,
+                    &lapses,
+                ],
+            )
+            .map_err(|err| {
+                let message = format!("Postgres cards insert failed: {err}");
+                crate::log_error(&message);
+                crate::DbError::Config(message)
+            })?;
+        if load_card_settings(&mut *client)?.wants_reverse(word.kind, word.translation.as_deref()) {
+            insert_reverse_card(&mut *client, word.id, None, now)?;
+        }
+
+        Ok(())
+    }
+
+    fn save_words_bulk(&self, words: &[NewWord]) -> DbResult<usize> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        let settings = load_card_settings(&mut *client)?;
+        let mut tx = client.transaction()?;
+        let now = Utc::now();
+        let created_at = now.to_rfc3339();
+        let mut inserted = 0;
+        for word in words {
+            let language = word.language.to_column();
+            let existing = tx.query(
+                "SELECT 1 FROM words WHERE lower(text) = lower($1) AND language = $2 LIMIT 1",
+                &[&word.text, &language],
+            )?;
+            if !existing.is_empty() {
+                continue;
+            }
+            let card = default_new_card(Uuid::new_v4(), now);
+            let word_id = card.word_id.to_string();
+            let kind = EntryKind::detect(&word.text);
+            let (source_type, source_name, source_page, captured_at, source_region) =
+                source_columns(word.source.as_ref());
+            tx.execute(
+                "INSERT INTO words (id, text, language, translation, chapter, group_name, notes, created_at, source_type, source_name, source_page, captured_at, source_region, kind)
+                 VALUES ($1, $2, $3, $4, $5, $6, NULL, $7, $8, $9, $10, $11, $12, $13)",
+                &[
+                    &word_id,
+                    &word.text,
+                    &language,
+                    &word.translation,
+                    &word.chapter,
+                    &word.group,
+                    &created_at,
+                    &source_type,
+                    &source_name,
+                    &source_page,
+                    &captured_at,
+                    &source_region,
+                    &format!("{:?}", kind),
+                ],
+            )
+            .map_err(|err| {
+                let message = format!("Postgres bulk words insert failed: {err}");
+                crate::log_error(&message);
+                crate::DbError::Config(message)
+            })?;
+            tx.execute(
+                "INSERT INTO cards (id, word_id, due_at, interval_days, ease, reps, lapses)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7)",
+                &[
+                    &card.id.to_string(),
+                    &word_id,
+                    &card.due_at.to_rfc3339(),
+                    &card.interval_days,
+                    &card.ease,
+                    &card.reps,
+                    &card.lapses,
+                ],
+            )
+            .map_err(|err| {
+                let message = format!("Postgres bulk cards insert failed: {err}");
+                crate::log_error(&message);
+                crate::DbError::Config(message)
+            })?;
+            if settings.wants_reverse(kind, Some(word.translation.as_str())) {
+                insert_reverse_card(&mut tx, card.word_id, None, now)?;
+            }
+            inserted += 1;
+        }
+        tx.commit()?;
+        log_sql(
+            "INSERT INTO words/cards (bulk)",
+            &[("inserted", inserted.to_string())],
+        );
+        Ok(inserted)
+    }
+
+    fn word_exists(&self, text: &str, language: &Language) -> DbResult<bool> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        let rows = client.query(
+            "SELECT 1 FROM words WHERE lower(text) = lower($1) AND language = $2 LIMIT 1",
+            &[&text, &language.to_column()],
+        )?;
+        Ok(!rows.is_empty())
+    }
+
+    fn update_translation(
+        &self,
+        word_id: Uuid,
+        translation: &str,
+        notes: Option<&str>,
+    ) -> DbResult<()> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        client
+            .execute(
+                "UPDATE words SET translation = $1, notes = $2 WHERE id = $3",
+                &[&translation, &notes, &word_id.to_string()],
+            )
+            .map_err(|err| {
+                let message = format!("Postgres update translation failed: {err}");
+                crate::log_error(&message);
+                crate::DbError::Config(message)
+            })?;
+        Ok(())
+    }
+
+    fn find_duplicates(&self, text: &str, language: &Language) -> DbResult<Vec<Word>> {
+        let key = duplicate_key(text, language);
+        if key.is_empty() {
+            return Ok(Vec::new());
+        }
+        let pattern = duplicate_pattern(&key, language);
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        let mut words = Vec::new();
+        for row in client.query(
+            "SELECT id, text, language, translation, chapter, group_name, notes, created_at,
+                    source_type, source_name, source_page, captured_at, source_region, forms,
+                    alt_answers, kind, deck_id
+             FROM words
+             WHERE language = $1 AND lower(text) LIKE $2 ESCAPE '\\'
+             ORDER BY created_at",
+            &[&language.to_column(), &pattern],
+        )? {
+            let word = word_from_row(&row);
+            if duplicate_key(&word.text, language) == key {
+                words.push(word);
+            }
+        }
+        Ok(words)
+    }
+
+    fn find_lemma(&self, lemma: &str, language: &Language) -> DbResult<Option<Word>> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        for row in client.query(
+            "SELECT id, text, language, translation, chapter, group_name, notes, created_at,
+                    source_type, source_name, source_page, captured_at, source_region, forms,
+                    alt_answers, kind, deck_id
+             FROM words
+             WHERE language = $1 AND (lower(text) = lower($2) OR lower(text) LIKE $3 ESCAPE '\\')
+             ORDER BY created_at",
+            &[&language.to_column(), &lemma.trim(), &lemma_pattern(lemma)],
+        )? {
+            let word = word_from_row(&row);
+            if is_lemma(&word.text, lemma, language) {
+                return Ok(Some(word));
+            }
+        }
+        Ok(None)
+    }
+
+    fn count_words(&self) -> DbResult<usize> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        let row = client.query_one("SELECT COUNT(*) FROM words", &[])?;
+        let count: i64 = row.get(0);
+        Ok(count as usize)
+    }
+
+    fn load_all_words(&self) -> DbResult<Vec<Word>> {
+        let mut words = Vec::new();
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        for row in client.query(
+            "SELECT id, text, language, translation, chapter, group_name, notes, created_at,
+                    source_type, source_name, source_page, captured_at, source_region, forms,
+                    alt_answers, kind, deck_id
+             FROM words
+             ORDER BY chapter, group_name, created_at",
+            &[],
+        )? {
+            words.push(word_from_row(&row));
+        }
+        Ok(words)
+    }
+
+    fn words_added_since(&self, since: DateTime<Utc>) -> DbResult<Vec<Word>> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        let rows = client.query(
+            "SELECT id, text, language, translation, chapter, group_name, notes, created_at,
+                    source_type, source_name, source_page, captured_at, source_region, forms,
+                    alt_answers, kind, deck_id
+             FROM words
+             WHERE created_at >= $1
+             ORDER BY created_at",
+            &[&since.to_rfc3339()],
+        )?;
+        Ok(rows.iter().map(word_from_row).collect())
+    }
+
+    fn words_missing_translation(&self) -> DbResult<Vec<Word>> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        let rows = client.query(
+            "SELECT id, text, language, translation, chapter, group_name, notes, created_at,
+                    source_type, source_name, source_page, captured_at, source_region, forms,
+                    alt_answers, kind, deck_id
+             FROM words
+             WHERE translation IS NULL OR trim(translation) = ''
+             ORDER BY created_at",
+            &[],
+        )?;
+        Ok(rows.iter().map(word_from_row).collect())
+    }
+
+    fn list_chapters(&self) -> DbResult<Vec<String>> {
+        let mut chapters = Vec::new();
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        for row in client.query(
+            "SELECT DISTINCT chapter
+             FROM words
+             WHERE chapter IS NOT NULL AND trim(chapter) != ''
+             ORDER BY chapter",
+            &[],
+        )? {
+            let value: String = row.get(0);
+            chapters.push(value);
+        }
+        Ok(chapters)
+    }
+
+    fn last_group_for_chapter(&self, chapter: &str) -> DbResult<Option<String>> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        let rows = client.query(
+            "SELECT group_name
+             FROM words
+             WHERE chapter = $1 AND group_name IS NOT NULL AND trim(group_name) != ''
+             ORDER BY created_at DESC
+             LIMIT 1",
+            &[&chapter],
+        )?;
+        Ok(rows.get(0).map(|row| row.get(0)))
+    }
+
+    fn delete_word(&self, word_id: Uuid) -> DbResult<()> {
+        let id = word_id.to_string();
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        client.execute(
+            "DELETE FROM reviews WHERE card_id IN (SELECT id FROM cards WHERE word_id = $1)",
+            &[&id],
+        )?;
+        client.execute("DELETE FROM cards WHERE word_id = $1", &[&id])?;
+        client.execute("DELETE FROM word_tags WHERE word_id = $1", &[&id])?;
+        client.execute("DELETE FROM words WHERE id = $1", &[&id])?;
+        Ok(())
+    }
+
+    fn delete_all_words(&self) -> DbResult<()> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        client.batch_execute(
+            "DELETE FROM reviews;
+             DELETE FROM cards;
+             DELETE FROM word_tags;
+             DELETE FROM words;",
+        )?;
+        Ok(())
+    }
+
+    fn cleanup_candidates(
+        &self,
+        limit: usize,
+        cutoff: DateTime<Utc>,
+    ) -> DbResult<Vec<CleanupEntryRow>> {
+        let cutoff_str = cutoff.to_rfc3339();
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        let rows = client.query(
+            "SELECT id, text, language, translation, notes, cleanup_at
+             FROM words
+             WHERE translation IS NOT NULL
+               AND (cleanup_at IS NULL OR cleanup_at <= $1)
+             ORDER BY cleanup_at NULLS FIRST, cleanup_at ASC, created_at ASC
+             LIMIT $2",
+            &[&cutoff_str, &(limit as i64)],
+        )?;
+        let mut entries = Vec::new();
+        for row in rows {
+            let word_id_str: String = row.get(0);
+            let word_id = Uuid::parse_str(&word_id_str)
+                .map_err(|err| DbError::Config(format!("Invalid word_id: {err}")))?;
+            let text: String = row.get(1);
+            let language = Language::new(&row.get::<_, String>(2));
+            let translation: Option<String> = row.get(3);
+            let notes: Option<String> = row.get(4);
+            let cleanup_at = match row.get::<_, Option<String>>(5) {
+                Some(value) => Some(
+                    DateTime::parse_from_rfc3339(&value)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .map_err(|err| {
+                            DbError::Config(format!("Invalid cleanup timestamp: {err}"))
+                        })?,
+                ),
+                None => None,
+            };
+            entries.push(CleanupEntryRow {
+                word_id,
+                text,
+                language,
+                translation,
+                notes,
+                cleanup_at,
+            });
+        }
+        Ok(entries)
+    }
+
+    fn record_cleanup(&self, word_id: Uuid, cleaned_at: DateTime<Utc>) -> DbResult<()> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        client
+            .execute(
+                "UPDATE words SET cleanup_at = $1 WHERE id = $2",
+                &[&cleaned_at.to_rfc3339(), &word_id.to_string()],
+            )
+            .map_err(|err| {
+                let message = format!("Postgres record cleanup failed: {err}");
+                crate::log_error(&message);
+                DbError::Config(message)
+            })?;
+        Ok(())
+    }
+
+    fn add_word_forms(&self, word_id: Uuid, forms: &[String]) -> DbResult<()> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        let mut tx = client.transaction()?;
+        let id = word_id.to_string();
+        let row = tx.query_one("SELECT forms FROM words WHERE id = $1 FOR UPDATE", &[&id])?;
+        let merged = merge_forms(row.get(0), forms);
+        tx.execute("UPDATE words SET forms = $1 WHERE id = $2", &[&merged, &id])
+            .map_err(|err| {
+                let message = format!("Postgres add word forms failed: {err}");
+                crate::log_error(&message);
+                DbError::Config(message)
+            })?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Reads the copy synced through `/data/settings`, latest first.
+    fn ui_prefs(&self) -> DbResult<UiPrefs> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        let row = client.query_opt(
+            "SELECT value::text FROM settings WHERE key = $1 ORDER BY updated_at DESC LIMIT 1",
+            &[&UI_PREFS_KEY],
+        )?;
+        parse_setting(UI_PREFS_KEY, row.map(|row| row.get(0)))
+    }
+
+    fn card_settings(&self) -> DbResult<CardSettings> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        load_card_settings(&mut *client)
+    }
+
+    fn add_reverse_cards(&self) -> DbResult<usize> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        let now = Utc::now();
+        let mut tx = client.transaction()?;
+        let rows = tx.query(
+            "SELECT w.id, w.text, w.translation, w.kind, w.deck_id
+             FROM words w
+             WHERE NOT EXISTS (
+                 SELECT 1 FROM cards c WHERE c.word_id = w.id AND c.card_type = $1)",
+            &[&format!("{:?}", CardType::Reverse)],
+        )?;
+        let mut added = 0;
+        for row in rows {
+            let Ok(word_id) = Uuid::parse_str(row.get::<_, String>(0).as_str()) else {
+                continue;
+            };
+            let text: String = row.get(1);
+            let translation: Option<String> = row.get(2);
+            if !has_reverse_side(parse_entry_kind(row.get(3), &text), translation.as_deref()) {
+                continue;
+            }
+            let deck_id: Option<String> = row.get(4);
+            insert_reverse_card(&mut tx, word_id, deck_id.as_deref(), now)?;
+            added += 1;
+        }
+        tx.commit()?;
+        Ok(added)
+    }
+
+    fn add_tag(&self, word_id: Uuid, tag: &str) -> DbResult<()> {
+        let name = normalize_tag(tag)?;
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        let mut tx = client.transaction()?;
+        tx.execute(
+            "INSERT INTO tags (id, name, created_at) VALUES ($1, $2, $3)
+             ON CONFLICT (name) DO NOTHING",
+            &[&Uuid::new_v4().to_string(), &name, &Utc::now().to_rfc3339()],
+        )?;
+        tx.execute(
+            "INSERT INTO word_tags (word_id, tag_id)
+             SELECT $1, id FROM tags WHERE name = $2
+             ON CONFLICT DO NOTHING",
+            &[&word_id.to_string(), &name],
+        )
+        .map_err(|err| {
+            let message = format!("Postgres add tag failed: {err}");
+            crate::log_error(&message);
+            DbError::Config(message)
+        })?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn remove_tag(&self, word_id: Uuid, tag: &str) -> DbResult<()> {
+        let name = normalize_tag(tag)?;
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        client.execute(
+            "DELETE FROM word_tags
+             WHERE word_id = $1 AND tag_id IN (SELECT id FROM tags WHERE name = $2)",
+            &[&word_id.to_string(), &name],
+        )?;
+        Ok(())
+    }
+
+    fn list_by_tag(&self, tag: &str) -> DbResult<Vec<Word>> {
+        let name = normalize_tag(tag)?;
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        let rows = client.query(
+            "SELECT w.id, w.text, w.language, w.translation, w.chapter, w.group_name, w.notes,
+                    w.created_at, w.source_type, w.source_name, w.source_page, w.captured_at,
+                    w.source_region, w.forms, w.alt_answers, w.kind, w.deck_id
+             FROM words w
+             JOIN word_tags wt ON wt.word_id = w.id
+             JOIN tags t ON t.id = wt.tag_id
+             WHERE t.name = $1
+             ORDER BY w.chapter, w.group_name, w.created_at",
+            &[&name],
+        )?;
+        Ok(rows.iter().map(word_from_row).collect())
+    }
+
+    fn word_tags(&self) -> DbResult<HashMap<Uuid, Vec<String>>> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        let rows = client.query(
+            "SELECT wt.word_id, t.name FROM word_tags wt
+             JOIN tags t ON t.id = wt.tag_id
+             ORDER BY t.name",
+            &[],
+        )?;
+        let mut tags: HashMap<Uuid, Vec<String>> = HashMap::new();
+        for row in rows {
+            if let Ok(word_id) = Uuid::parse_str(row.get::<_, &str>(0)) {
+                tags.entry(word_id).or_default().push(row.get(1));
+            }
+        }
+        Ok(tags)
+    }
+
+    fn suspend_card(&self, word_id: Uuid) -> DbResult<()> {
+        self.update_card(
+            "UPDATE cards SET suspended = TRUE WHERE word_id = $1",
+            word_id,
+            None,
+        )
+    }
+
+    fn bury_card(&self, word_id: Uuid, until: DateTime<Utc>) -> DbResult<()> {
+        self.update_card(
+            "UPDATE cards SET buried_until = $2 WHERE word_id = $1",
+            word_id,
+            Some(until.to_rfc3339()),
+        )
+    }
+
+    fn import_profiles(&self) -> DbResult<Vec<ImportProfile>> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        let rows = client.query(
+            "SELECT pattern, columns, ocr_profile, chapter, updated_at FROM import_profiles
+             ORDER BY updated_at DESC",
+            &[],
+        )?;
+        Ok(rows
+            .iter()
+            .map(|row| ImportProfile {
+                pattern: row.get(0),
+                columns: row.get(1),
+                ocr_profile: row.get(2),
+                chapter: row.get(3),
+                updated_at: parse_timestamp(row.get::<_, &str>(4)),
+            })
+            .collect())
+    }
+
+    fn search_words(&self, query: &str) -> DbResult<Vec<Word>> {
+        let patterns = query
+            .split_whitespace()
+            .map(|term| format!("%{}%", escape_like(term)))
+            .collect::<Vec<_>>();
+        if patterns.is_empty() {
+            return Ok(Vec::new());
+        }
+        let query = query.split_whitespace().collect::<Vec<_>>().join(" ");
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        let rows = client.query(
+            "SELECT id, text, language, translation, chapter, group_name, notes, created_at,
+                    source_type, source_name, source_page, captured_at, source_region, forms,
+                    alt_answers, kind, deck_id
+             FROM words
+             WHERE (text || ' ' || COALESCE(translation, '') || ' ' || COALESCE(notes, ''))
+                   ILIKE ALL($1)
+             ORDER BY GREATEST(
+                        word_similarity($2, text) * 3,
+                        word_similarity($2, COALESCE(translation, '')) * 2,
+                        word_similarity($2, COALESCE(notes, ''))
+                      ) DESC,
+                      created_at",
+            &[&patterns, &query],
+        )?;
+        Ok(rows.iter().map(word_from_row).collect())
+    }
+
+    fn due_cards(&self, word_ids: &[Uuid], now: DateTime<Utc>) -> DbResult<Vec<Card>> {
+        let word_ids = word_ids.iter().map(Uuid::to_string).collect::<Vec<_>>();
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        let rows = client.query(
+            "SELECT id, word_id, due_at, interval_days, ease, reps, lapses, card_type,
+                    learning_step, learning_due_at, stability, difficulty,
+                    (SELECT MAX(reviewed_at) FROM reviews WHERE card_id = cards.id)
+             FROM cards
+             WHERE word_id = ANY($1)
+               AND due_at <= $2
+               AND NOT suspended
+               AND (buried_until IS NULL OR buried_until <= $2)
+               AND card_type IS DISTINCT FROM $3
+             ORDER BY due_at",
+            &[
+                &word_ids,
+                &now.to_rfc3339(),
+                &format!("{:?}", CardType::Cloze),
+            ],
+        )?;
+        Ok(rows.iter().map(card_from_row).collect())
+    }
+
+    fn record_review(&self, card: &Card, grade: Grade, reviewed_at: DateTime<Utc>) -> DbResult<()> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        let mut tx = client.transaction()?;
+        let first_review: bool = tx
+            .query_one(
+                "SELECT NOT EXISTS(SELECT 1 FROM reviews WHERE card_id = $1)",
+                &[&card.id.to_string()],
+            )?
+            .get(0);
+        tx.execute(
+            "UPDATE cards SET due_at = $2, interval_days = $3, ease = $4, reps = $5, lapses = $6,
+                              learning_step = $7, learning_due_at = $8, stability = $9,
+                              difficulty = $10
+             WHERE id = $1",
+            &[
+                &card.id.to_string(),
+                &card.due_at.to_rfc3339(),
+                &card.interval_days,
+                &card.ease,
+                &card.reps,
+                &card.lapses,
+                &card.learning_step.and_then(|step| i32::try_from(step).ok()),
+                &card.learning_due_at.map(|at| at.to_rfc3339()),
+                &card.stability,
+                &card.difficulty,
+            ],
+        )?;
+        tx.execute(
+            "INSERT INTO reviews (id, card_id, grade, reviewed_at) VALUES ($1, $2, $3, $4)",
+            &[
+                &Uuid::new_v4().to_string(),
+                &card.id.to_string(),
+                &i32::from(grade.value()),
+                &reviewed_at.to_rfc3339(),
+            ],
+        )?;
+        tx.execute(
+            "INSERT INTO daily_stats (day, new_cards, reviews) VALUES ($1, $2, 1)
+             ON CONFLICT (day) DO UPDATE SET
+                new_cards = daily_stats.new_cards + excluded.new_cards,
+                reviews = daily_stats.reviews + 1",
+            &[
+                &local_day(reviewed_at).to_string(),
+                &i32::from(first_review),
+            ],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn new_cards_on(&self, day: NaiveDate) -> DbResult<usize> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        let row = client.query_opt(
+            "SELECT new_cards FROM daily_stats WHERE day = $1",
+            &[&day.to_string()],
+        )?;
+        Ok(row.map_or(0, |row| row.get::<_, i32>(0).max(0) as usize))
+    }
+
+    fn reviews_per_day(&self) -> DbResult<Vec<ReviewDay>> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        let rows = client.query(
+            "SELECT substr(reviewed_at, 1, 10) AS day, COUNT(*),
+                    SUM(CASE WHEN grade >= 3 THEN 1 ELSE 0 END)
+             FROM reviews
+             GROUP BY day
+             ORDER BY day",
+            &[],
+        )?;
+        Ok(rows
+            .iter()
+            .filter_map(|row| review_day(row.get(0), row.get(1), row.get(2)))
+            .collect())
+    }
+
+    fn chapter_accuracy(&self) -> DbResult<Vec<ChapterAccuracy>> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        let rows = client.query(
+            "SELECT w.chapter, COUNT(*), SUM(CASE WHEN r.grade >= 3 THEN 1 ELSE 0 END)
+             FROM reviews r
+             JOIN cards c ON c.id = r.card_id
+             JOIN words w ON w.id = c.word_id
+             GROUP BY w.chapter
+             ORDER BY w.chapter IS NULL, w.chapter",
+            &[],
+        )?;
+        Ok(rows
+            .iter()
+            .map(|row| ChapterAccuracy {
+                chapter: row.get(0),
+                reviews: row.get::<_, i64>(1).max(0) as usize,
+                correct: row.get::<_, i64>(2).max(0) as usize,
+            })
+            .collect())
+    }
+
+    fn save_import_profile(&self, profile: &ImportProfile) -> DbResult<()> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        client.execute(
+            "INSERT INTO import_profiles (pattern, columns, ocr_profile, chapter, updated_at)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (pattern) DO UPDATE SET
+                columns = EXCLUDED.columns,
+                ocr_profile = EXCLUDED.ocr_profile,
+                chapter = EXCLUDED.chapter,
+                updated_at = EXCLUDED.updated_at",
+            &[
+                &profile.pattern,
+                &profile.columns,
+                &profile.ocr_profile,
+                &profile.chapter,
+                &profile.updated_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn unsuspend_card(&self, word_id: Uuid) -> DbResult<()> {
+        self.update_card(
+            "UPDATE cards SET suspended = FALSE, buried_until = NULL WHERE word_id = $1",
+            word_id,
+            None,
+        )
+    }
+
+    fn list_decks(&self) -> DbResult<Vec<Deck>> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        let rows = client.query(
+            "SELECT d.id, d.name, d.created_at,
+                    (SELECT COUNT(*) FROM words w WHERE w.deck_id = d.id)
+             FROM decks d
+             ORDER BY lower(d.name)",
+            &[],
+        )?;
+        Ok(rows
+            .iter()
+            .map(|row| Deck {
+                id: Uuid::parse_str(row.get::<_, String>(0).as_str())
+                    .unwrap_or_else(|_| Uuid::new_v4()),
+                name: row.get(1),
+                created_at: parse_timestamp(row.get::<_, String>(2).as_str()),
+                words: row.get::<_, i64>(3) as usize,
+            })
+            .collect())
+    }
+
+    fn create_deck(&self, name: &str) -> DbResult<Deck> {
+        let name = normalize_deck_name(name)?;
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        ensure_unique_deck(&mut client, &name, None)?;
+        let deck = Deck {
+            id: Uuid::new_v4(),
+            name,
+            created_at: Utc::now(),
+            words: 0,
+        };
+        client.execute(
+            "INSERT INTO decks (id, name, created_at) VALUES ($1, $2, $3)",
+            &[
+                &deck.id.to_string(),
+                &deck.name,
+                &deck.created_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(deck)
+    }
+
+    fn rename_deck(&self, deck_id: Uuid, name: &str) -> DbResult<()> {
+        let name = normalize_deck_name(name)?;
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        ensure_unique_deck(&mut client, &name, Some(deck_id))?;
+        let updated = client.execute(
+            "UPDATE decks SET name = $2 WHERE id = $1",
+            &[&deck_id.to_string(), &name],
+        )?;
+        if updated == 0 {
+            return Err(DbError::Config(format!("No deck with id {deck_id}")));
+        }
+        Ok(())
+    }
+
+    fn delete_deck(&self, deck_id: Uuid) -> DbResult<()> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        let deck_id = deck_id.to_string();
+        let mut tx = client.transaction()?;
+        tx.execute(
+            "UPDATE words SET deck_id = NULL WHERE deck_id = $1",
+            &[&deck_id],
+        )?;
+        tx.execute(
+            "UPDATE cards SET deck_id = NULL WHERE deck_id = $1",
+            &[&deck_id],
+        )?;
+        tx.execute("DELETE FROM decks WHERE id = $1", &[&deck_id])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn move_to_deck(&self, word_ids: &[Uuid], deck_id: Option<Uuid>) -> DbResult<()> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        let deck_id = deck_id.map(|id| id.to_string());
+        if let Some(deck_id) = &deck_id
+            && client
+                .query_opt("SELECT 1 FROM decks WHERE id = $1", &[deck_id])?
+                .is_none()
+        {
+            return Err(DbError::Config(format!("No deck with id {deck_id}")));
+        }
+        let word_ids = word_ids.iter().map(Uuid::to_string).collect::<Vec<_>>();
+        let mut tx = client.transaction()?;
+        tx.execute(
+            "UPDATE words SET deck_id = $2 WHERE id = ANY($1)",
+            &[&word_ids, &deck_id],
+        )?;
+        tx.execute(
+            "UPDATE cards SET deck_id = $2 WHERE word_id = ANY($1)",
+            &[&word_ids, &deck_id],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn glossary(&self) -> DbResult<Vec<GlossaryEntry>> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        let rows = client.query(
+            "SELECT term, language, translation, updated_at FROM glossary
+             ORDER BY language, term_key",
+            &[],
+        )?;
+        Ok(rows
+            .iter()
+            .map(|row| GlossaryEntry {
+                term: row.get(0),
+                language: Language::new(row.get::<_, &str>(1)),
+                translation: row.get(2),
+                updated_at: parse_timestamp(row.get::<_, &str>(3)),
+            })
+            .collect())
+    }
+
+    fn save_glossary_entry(&self, entry: &GlossaryEntry) -> DbResult<()> {
+        let (key, translation) = glossary_columns(entry)?;
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        client.execute(
+            "INSERT INTO glossary (language, term_key, term, translation, updated_at)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (language, term_key) DO UPDATE SET
+                term = EXCLUDED.term,
+                translation = EXCLUDED.translation,
+                updated_at = EXCLUDED.updated_at",
+            &[
+                &entry.language.code(),
+                &key,
+                &entry.term.trim(),
+                &translation,
+                &entry.updated_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn delete_glossary_entry(&self, language: &Language, term: &str) -> DbResult<bool> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| crate::DbError::Config("Postgres client lock poisoned".to_string()))?;
+        let deleted = client.execute(
+            "DELETE FROM glossary WHERE language = $1 AND term_key = $2",
+            &[&language.code(), &glossary_key(term)],
+        )?;
+        Ok(deleted > 0)
+    }
+}
+
+fn ensure_unique_deck(client: &mut Client, name: &str, except: Option<Uuid>) -> DbResult<()> {
+    let except = except.map(|id| id.to_string());
+    let taken = client.query_opt(
+        "SELECT 1 FROM decks WHERE lower(name) = lower($1) AND ($2::TEXT IS NULL OR id != $2)",
+        &[&name, &except],
+    )?;
+    if taken.is_some() {
+        return Err(DbError::Config(format!(
+            "A deck named '{name}' already exists"
+        )));
+    }
+    Ok(())
+}
+
+/// Reads the copy synced through `/data/settings`, latest first.
+fn load_card_settings(client: &mut impl GenericClient) -> DbResult<CardSettings> {
+    let row = client.query_opt(
+        "SELECT value::text FROM settings WHERE key = $1 ORDER BY updated_at DESC LIMIT 1",
+        &[&CARD_SETTINGS_KEY],
+    )?;
+    parse_setting(CARD_SETTINGS_KEY, row.map(|row| row.get(0)))
+}
+
+fn insert_reverse_card(
+    client: &mut impl GenericClient,
+    word_id: Uuid,
+    deck_id: Option<&str>,
+    now: DateTime<Utc>,
+) -> DbResult<()> {
+    let card = default_new_card(word_id, now);
+    client.execute(
+        "INSERT INTO cards (id, word_id, due_at, interval_days, ease, reps, lapses, deck_id,
+                            card_type)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+        &[
+            &card.id.to_string(),
+            &card.word_id.to_string(),
+            &card.due_at.to_rfc3339(),
+            &card.interval_days,
+            &card.ease,
+            &card.reps,
+            &card.lapses,
+            &deck_id,
+            &format!("{:?}", CardType::Reverse),
+        ],
+    )?;
+    Ok(())
+}
@@ -0,0 +1,221 @@
+//! Card queries review sessions are built from: the cards eligible right
+//! now with their weight and kind, the ones failed since a given time for
+//! warm-up, and the day's review tallies. Suspended cards and cards buried
+//! past `now` are never eligible.
+//!
+//! `daily_stats` counts each local calendar day's reviews and new cards as
+//! they are graded, for the daily new-card limit.
+
+use chrono::NaiveDate;
+use rusqlite::{Connection, params};
+
+/// Narrows the queries to one deck, chapter, group, or tag. `None` matches
+/// every card; tags are compared trimmed and lowercased, as they are stored.
+/// `deck` is a deck id.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CardScope<'a> {
+    pub deck: Option<&'a str>,
+    pub chapter: Option<&'a str>,
+    pub group: Option<&'a str>,
+    pub tag: Option<&'a str>,
+}
+
+impl CardScope<'_> {
+    fn tag_name(&self) -> Option<String> {
+        self.tag
+            .map(|tag| tag.trim().to_lowercase())
+            .filter(|tag| !tag.is_empty())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CardCandidate {
+    pub id: String,
+    pub batch_id: i32,
+    /// How strongly weighted picking should favour the card; see
+    /// [`card_weight`].
+    pub weight: f64,
+    pub kind: CardKind,
+    pub chapter: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CardKind {
+    New,
+    Learning,
+    Due,
+}
+
+pub fn classify_card(interval_days: i32, reps: i32, lapses: i32, seen_count: i32) -> CardKind {
+    if reps == 0 && lapses == 0 && seen_count == 0 {
+        CardKind::New
+    } else if interval_days <= 1 {
+        CardKind::Learning
+    } else {
+        CardKind::Due
+    }
+}
+
+/// Harder, shorter-interval, often-lapsed, and rarely seen cards weigh more.
+pub fn card_weight(interval_days: i32, ease: f64, lapses: i32, seen_count: i32) -> f64 {
+    let difficulty = (3.5 - ease).max(0.2);
+    let interval_factor = 1.0 / ((interval_days.max(1) as f64) + 1.0);
+    let lapse_bonus = (lapses as f64) * 0.15;
+    let seen_bonus = 1.0 / ((seen_count.max(1) as f64) + 1.0);
+    (difficulty + interval_factor + lapse_bonus + seen_bonus * 0.3).max(0.05)
+}
+
+/// Cards in `scope` due at `now` (RFC 3339), unordered.
+pub fn due_candidates(
+    conn: &Connection,
+    scope: CardScope<'_>,
+    now: &str,
+) -> rusqlite::Result<Vec<CardCandidate>> {
+    let mut stmt = conn.prepare(
+        "SELECT c.id, c.batch_id, c.interval_days, c.ease, c.reps, c.lapses, c.seen_count, w.chapter
+         FROM cards c
+         JOIN words w ON w.id = c.word_id
+         WHERE c.due_at <= ?1
+           AND c.suspended = 0
+           AND (c.buried_until IS NULL OR c.buried_until <= ?1)
+           AND (?2 IS NULL OR w.chapter = ?2)
+           AND (?3 IS NULL OR w.group_name = ?3)
+           AND (?4 IS NULL OR EXISTS (
+                SELECT 1 FROM word_tags wt JOIN tags t ON t.id = wt.tag_id
+                WHERE wt.word_id = w.id AND t.name = ?4))
+           AND (?5 IS NULL OR c.deck_id = ?5)",
+    )?;
+    let rows = stmt.query_map(
+        params![
+            now,
+            scope.chapter,
+            scope.group,
+            scope.tag_name(),
+            scope.deck
+        ],
+        |row| {
+            let interval_days = row.get::<_, i32>(2)?;
+            let reps = row.get::<_, i32>(4)?;
+            let lapses = row.get::<_, i32>(5)?;
+            let seen_count = row.get::<_, i32>(6)?;
+            Ok(CardCandidate {
+                id: row.get::<_, String>(0)?,
+                batch_id: row.get::<_, i32>(1)?,
+                weight: card_weight(interval_days, row.get::<_, f64>(3)?, lapses, seen_count),
+                kind: classify_card(interval_days, reps, lapses, seen_count),
+                chapter: row.get::<_, Option<String>>(7)?,
+            })
+        },
+    )?;
+    rows.collect()
+}
+
+/// Ids of cards in `scope` graded 2 or lower since `since`, most recently
+/// failed first.
+pub fn failed_since(
+    conn: &Connection,
+    scope: CardScope<'_>,
+    since: &str,
+    now: &str,
+    limit: usize,
+) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT r.card_id, MAX(r.reviewed_at) AS failed_at
+         FROM reviews r
+         JOIN cards c ON c.id = r.card_id
+         JOIN words w ON w.id = c.word_id
+         WHERE r.grade <= 2
+           AND r.reviewed_at >= ?1
+           AND c.suspended = 0
+           AND (c.buried_until IS NULL OR c.buried_until <= ?6)
+           AND (?2 IS NULL OR w.chapter = ?2)
+           AND (?3 IS NULL OR w.group_name = ?3)
+           AND (?5 IS NULL OR EXISTS (
+                SELECT 1 FROM word_tags wt JOIN tags t ON t.id = wt.tag_id
+                WHERE wt.word_id = w.id AND t.name = ?5))
+           AND (?7 IS NULL OR c.deck_id = ?7)
+         GROUP BY r.card_id
+         ORDER BY failed_at DESC
+         LIMIT ?4",
+    )?;
+    let rows = stmt.query_map(
+        params![
+            since,
+            scope.chapter,
+            scope.group,
+            limit as i64,
+            scope.tag_name(),
+            now,
+            scope.deck
+        ],
+        |row| row.get::<_, String>(0),
+    )?;
+    rows.collect()
+}
+
+/// Reviews since `since`, and how many of them were a card's first ever.
+pub fn reviews_since(conn: &Connection, since: &str) -> rusqlite::Result<(usize, usize)> {
+    conn.query_row(
+        "SELECT COUNT(*),
+                COALESCE(SUM(r.reviewed_at = (
+                    SELECT MIN(first.reviewed_at) FROM reviews first
+                    WHERE first.card_id = r.card_id
+                )), 0)
+         FROM reviews r
+         WHERE r.reviewed_at >= ?1",
+        params![since],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+}
+
+/// Every card, and the ones a session could serve at `now`.
+pub fn card_counts(conn: &Connection, now: &str) -> rusqlite::Result<(usize, usize)> {
+    conn.query_row(
+        "SELECT COUNT(*),
+                COALESCE(SUM(due_at <= ?1 AND suspended = 0
+                             AND (buried_until IS NULL OR buried_until <= ?1)), 0)
+         FROM cards",
+        params![now],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+}
+
+/// Counts a review toward `day` in `daily_stats`; `new_card` when it was the
+/// card's first answer ever.
+pub fn count_daily_review(
+    conn: &Connection,
+    day: NaiveDate,
+    new_card: bool,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO daily_stats (day, new_cards, reviews) VALUES (?1, ?2, 1)
+         ON CONFLICT(day) DO UPDATE SET
+            new_cards = new_cards + excluded.new_cards,
+            reviews = reviews + 1",
+        params![day.to_string(), i64::from(new_card)],
+    )?;
+    Ok(())
+}
+
+/// Takes back a review [`count_daily_review`] counted, as on undo.
+pub fn uncount_daily_review(
+    conn: &Connection,
+    day: NaiveDate,
+    new_card: bool,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE daily_stats SET new_cards = MAX(new_cards - ?2, 0), reviews = MAX(reviews - 1, 0)
+         WHERE day = ?1",
+        params![day.to_string(), i64::from(new_card)],
+    )?;
+    Ok(())
+}
+
+/// New cards first answered on `day`.
+pub fn new_cards_on(conn: &Connection, day: NaiveDate) -> rusqlite::Result<usize> {
+    conn.query_row(
+        "SELECT COALESCE(MAX(new_cards), 0) FROM daily_stats WHERE day = ?1",
+        params![day.to_string()],
+        |row| row.get(0),
+    )
+}
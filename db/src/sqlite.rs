@@ -0,0 +1,806 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::store::{
+    CleanupEntryRow, Db, DbError, DbResult, Deck, ImportProfile, NewWord, duplicate_key,
+    duplicate_pattern, is_lemma, lemma_pattern, merge_forms, normalize_tag, parse_entry_kind,
+    parse_setting, parse_string_list, parse_timestamp, review_day, source_columns,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use le_core::glossary::GlossaryEntry;
+use le_core::session::local_day;
+use le_core::stats::{ChapterAccuracy, ReviewDay};
+use le_core::{
+    Card, CardSettings, CardType, EntryKind, Grade, Language, UI_PREFS_KEY, UiPrefs, Word,
+    WordSource, default_new_card,
+};
+use rusqlite::{Connection, OptionalExtension, params};
+use uuid::Uuid;
+
+pub struct SqliteDb {
+    conn: Connection,
+}
+
+impl SqliteDb {
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        let conn = Connection::open(path)?;
+        Ok(Self { conn })
+    }
+}
+
+/// `query` as an FTS5 match expression: every term must appear, each as a
+/// prefix so results show up while a word is still being typed. Quotes keep
+/// FTS5 syntax characters in the input literal.
+fn fts_query(query: &str) -> Option<String> {
+    let terms = query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"*", term.replace('"', "\"\"")))
+        .collect::<Vec<_>>();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+fn card_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Card> {
+    Ok(Card {
+        id: Uuid::parse_str(&row.get::<_, String>(0)?).unwrap_or_else(|_| Uuid::new_v4()),
+        word_id: Uuid::parse_str(&row.get::<_, String>(1)?).unwrap_or_else(|_| Uuid::new_v4()),
+        due_at: parse_timestamp(&row.get::<_, String>(2)?),
+        interval_days: row.get(3)?,
+        ease: row.get(4)?,
+        reps: row.get(5)?,
+        lapses: row.get(6)?,
+        stability: row.get(7)?,
+        difficulty: row.get(8)?,
+        last_reviewed_at: row
+            .get::<_, Option<String>>(12)?
+            .map(|at| parse_timestamp(&at)),
+        card_type: CardType::from_column(row.get::<_, Option<String>>(9)?.as_deref()),
+        learning_step: row.get(10)?,
+        learning_due_at: row
+            .get::<_, Option<String>>(11)?
+            .map(|at| parse_timestamp(&at)),
+    })
+}
+
+fn word_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Word> {
+    let language = Language::new(&row.get::<_, String>(2)?);
+    let created_at = DateTime::parse_from_rfc3339(row.get::<_, String>(7)?.as_str())
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now());
+    let text: String = row.get(1)?;
+    let kind = parse_entry_kind(row.get(15)?, &text);
+    Ok(Word {
+        id: Uuid::parse_str(row.get::<_, String>(0)?.as_str()).unwrap_or_else(|_| Uuid::new_v4()),
+        text,
+        language,
+        translation: row.get(3)?,
+        chapter: row.get(4)?,
+        group: row.get(5)?,
+        notes: row.get(6)?,
+        created_at,
+        source: WordSource::from_columns(
+            row.get(8)?,
+            row.get(9)?,
+            row.get(10)?,
+            row.get(11)?,
+            row.get(12)?,
+        ),
+        forms: parse_string_list(row.get(13)?),
+        alt_answers: parse_string_list(row.get(14)?),
+        kind,
+        deck_id: row
+            .get::<_, Option<String>>(16)?
+            .and_then(|id| Uuid::parse_str(&id).ok()),
+    })
+}
+
+impl Db for SqliteDb {
+    fn init(&self) -> DbResult<()> {
+        crate::migrate_sqlite(&self.conn)?;
+        Ok(())
+    }
+
+    fn save_word(
+        &self,
+        text: &str,
+        translation: &str,
+        language: &Language,
+        chapter: Option<&str>,
+        group: Option<&str>,
+        source: Option<&WordSource>,
+    ) -> DbResult<()> {
+        let now = Utc::now();
+        let word = Word {
+            id: Uuid::new_v4(),
+            text: text.to_string(),
+            translation: Some(translation.to_string()),
+            chapter: chapter.map(|value| value.to_string()),
+            group: group.map(|value| value.to_string()),
+            language: language.clone(),
+            notes: None,
+            created_at: now,
+            source: source.cloned(),
+            forms: Vec::new(),
+            alt_answers: Vec::new(),
+            kind: EntryKind::detect(text),
+            deck_id: None,
+        };
+        let (source_type, source_name, source_page, captured_at, source_region) =
+            source_columns(word.source.as_ref());
+
+        let card = default_new_card(word.id, now);
+
+        self.conn.execute(
+            "INSERT INTO words (id, text, language, translation, chapter, group_name, notes, created_at, source_type, source_name, source_page, captured_at, source_region, kind) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+            params![
+                word.id.to_string(),
+                word.text,
+                word.language.to_column(),
+                word.translation,
+                word.chapter,
+                word.group,
+                word.notes,
+                word.created_at.to_rfc3339(),
+                source_type,
+                source_name,
+                source_page,
+                captured_at,
+                source_region,
+                format!("{:?}", word.kind)
+            ],
+        )?;
+
+        self.conn.execute(
+            "INSERT INTO cards (id, word_id, due_at, interval_days, ease, reps, lapses) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                card.id.to_string(),
+                card.word_id.to_string(),
+                card.due_at.to_rfc3339(),
+                card.interval_days,
+                card.ease,
+                card.reps,
+                card.lapses
+            ],
+        )?;
+        crate::cards::add_reverse_card(
+            &self.conn,
+            &crate::cards::card_settings(&self.conn)?,
+            word.id,
+            word.kind,
+            word.translation.as_deref(),
+            now,
+        )?;
+
+        Ok(())
+    }
+
+    fn save_words_bulk(&self, words: &[NewWord]) -> DbResult<usize> {
+        let settings = crate::cards::card_settings(&self.conn)?;
+        let tx = self.conn.unchecked_transaction()?;
+        let now = Utc::now();
+        let mut inserted = 0;
+        for word in words {
+            let language = word.language.to_column();
+            let exists = tx
+                .prepare_cached(
+                    "SELECT 1 FROM words WHERE lower(text) = lower(?1) AND language = ?2 LIMIT 1",
+                )?
+                .exists(params![word.text, language])?;
+            if exists {
+                continue;
+            }
+            let word_id = Uuid::new_v4();
+            let kind = EntryKind::detect(&word.text);
+            let card = default_new_card(word_id, now);
+            let (source_type, source_name, source_page, captured_at, source_region) =
+                source_columns(word.source.as_ref());
+            tx.execute(
+                "INSERT INTO words (id, text, language, translation, chapter, group_name, notes, created_at, source_type, source_name, source_page, captured_at, source_region, kind) VALUES (?1, ?2, ?3, ?4, ?5, ?6, NULL, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+                params![
+                    word_id.to_string(),
+                    word.text,
+                    language,
+                    word.translation,
+                    word.chapter,
+                    word.group,
+                    now.to_rfc3339(),
+                    source_type,
+                    source_name,
+                    source_page,
+                    captured_at,
+                    source_region,
+                    format!("{:?}", kind)
+                ],
+            )?;
+            tx.execute(
+                "INSERT INTO cards (id, word_id, due_at, interval_days, ease, reps, lapses) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    card.id.to_string(),
+                    card.word_id.to_string(),
+                    card.due_at.to_rfc3339(),
+                    card.interval_days,
+                    card.ease,
+                    card.reps,
+                    card.lapses
+                ],
+            )?;
+            crate::cards::add_reverse_card(
+                &tx,
+                &settings,
+                word_id,
+                kind,
+                Some(word.translation.as_str()),
+                now,
+            )?;
+            inserted += 1;
+        }
+        tx.commit()?;
+        Ok(inserted)
+    }
+
+    fn word_exists(&self, text: &str, language: &Language) -> DbResult<bool> {
+        let mut stmt = self.conn.prepare(
+            "SELECT 1 FROM words WHERE lower(text) = lower(?1) AND language = ?2 LIMIT 1",
+        )?;
+        let mut rows = stmt.query(params![text, language.to_column()])?;
+        Ok(rows.next()?.is_some())
+    }
+
+    fn find_duplicates(&self, text: &str, language: &Language) -> DbResult<Vec<Word>> {
+        let key = duplicate_key(text, language);
+        if key.is_empty() {
+            return Ok(Vec::new());
+        }
+        let pattern = duplicate_pattern(&key, language);
+        let mut stmt = self.conn.prepare(
+            "SELECT id, text, language, translation, chapter, group_name, notes, created_at,
+                    source_type, source_name, source_page, captured_at, source_region, forms,
+                    alt_answers, kind, deck_id
+             FROM words
+             WHERE language = ?1 AND lower(text) LIKE ?2 ESCAPE '\\'
+             ORDER BY created_at",
+        )?;
+        let rows = stmt.query_map(params![language.to_column(), pattern], word_from_row)?;
+        let mut words = Vec::new();
+        for word in rows {
+            let word = word?;
+            if duplicate_key(&word.text, language) == key {
+                words.push(word);
+            }
+        }
+        Ok(words)
+    }
+
+    fn find_lemma(&self, lemma: &str, language: &Language) -> DbResult<Option<Word>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, text, language, translation, chapter, group_name, notes, created_at,
+                    source_type, source_name, source_page, captured_at, source_region, forms,
+                    alt_answers, kind, deck_id
+             FROM words
+             WHERE language = ?1 AND (lower(text) = lower(?2) OR lower(text) LIKE ?3 ESCAPE '\\')
+             ORDER BY created_at",
+        )?;
+        let rows = stmt.query_map(
+            params![language.to_column(), lemma.trim(), lemma_pattern(lemma)],
+            word_from_row,
+        )?;
+        for word in rows {
+            let word = word?;
+            if is_lemma(&word.text, lemma, language) {
+                return Ok(Some(word));
+            }
+        }
+        Ok(None)
+    }
+
+    fn count_words(&self) -> DbResult<usize> {
+        let count: i64 = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM words", [], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    fn load_all_words(&self) -> DbResult<Vec<Word>> {
+        let mut words = Vec::new();
+        let mut stmt = self.conn.prepare(
+            "SELECT id, text, language, translation, chapter, group_name, notes, created_at,
+                    source_type, source_name, source_page, captured_at, source_region, forms,
+                    alt_answers, kind, deck_id
+             FROM words
+             ORDER BY chapter, group_name, created_at",
+        )?;
+        let rows = stmt.query_map([], word_from_row)?;
+
+        for word in rows {
+            words.push(word?);
+        }
+
+        Ok(words)
+    }
+
+    fn words_added_since(&self, since: DateTime<Utc>) -> DbResult<Vec<Word>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, text, language, translation, chapter, group_name, notes, created_at,
+                    source_type, source_name, source_page, captured_at, source_region, forms,
+                    alt_answers, kind, deck_id
+             FROM words
+             WHERE created_at >= ?1
+             ORDER BY created_at",
+        )?;
+        let rows = stmt.query_map(params![since.to_rfc3339()], word_from_row)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    fn words_missing_translation(&self) -> DbResult<Vec<Word>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, text, language, translation, chapter, group_name, notes, created_at,
+                    source_type, source_name, source_page, captured_at, source_region, forms,
+                    alt_answers, kind, deck_id
+             FROM words
+             WHERE translation IS NULL OR trim(translation) = ''
+             ORDER BY created_at",
+        )?;
+        let rows = stmt.query_map([], word_from_row)?;
+        let mut words = Vec::new();
+        for word in rows {
+            words.push(word?);
+        }
+        Ok(words)
+    }
+
+    fn list_chapters(&self) -> DbResult<Vec<String>> {
+        let mut chapters = Vec::new();
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT chapter
+             FROM words
+             WHERE chapter IS NOT NULL AND trim(chapter) != ''
+             ORDER BY chapter",
+        )?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        for row in rows {
+            chapters.push(row?);
+        }
+        Ok(chapters)
+    }
+
+    fn last_group_for_chapter(&self, chapter: &str) -> DbResult<Option<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT group_name
+             FROM words
+             WHERE chapter = ?1 AND group_name IS NOT NULL AND trim(group_name) != ''
+             ORDER BY created_at DESC
+             LIMIT 1",
+        )?;
+        let mut rows = stmt.query(params![chapter])?;
+        if let Some(row) = rows.next()? {
+            let value: String = row.get(0)?;
+            Ok(Some(value))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn delete_word(&self, word_id: Uuid) -> DbResult<()> {
+        let id = word_id.to_string();
+        self.conn.execute(
+            "DELETE FROM reviews WHERE card_id IN (SELECT id FROM cards WHERE word_id = ?1)",
+            params![id],
+        )?;
+        self.conn.execute(
+            "DELETE FROM cards WHERE word_id = ?1",
+            params![word_id.to_string()],
+        )?;
+        self.conn
+            .execute("DELETE FROM word_tags WHERE word_id = ?1", params![id])?;
+        self.conn.execute(
+            "DELETE FROM words WHERE id = ?1",
+            params![word_id.to_string()],
+        )?;
+        Ok(())
+    }
+
+    fn update_translation(
+        &self,
+        word_id: Uuid,
+        translation: &str,
+        notes: Option<&str>,
+    ) -> DbResult<()> {
+        self.conn.execute(
+            "UPDATE words SET translation = ?1, notes = ?2 WHERE id = ?3",
+            params![translation, notes, word_id.to_string()],
+        )?;
+        Ok(())
+    }
+
+    fn delete_all_words(&self) -> DbResult<()> {
+        self.conn.execute_batch(
+            "DELETE FROM reviews;
+             DELETE FROM cards;
+             DELETE FROM word_tags;
+             DELETE FROM words;",
+        )?;
+        Ok(())
+    }
+
+    fn cleanup_candidates(
+        &self,
+        limit: usize,
+        cutoff: DateTime<Utc>,
+    ) -> DbResult<Vec<CleanupEntryRow>> {
+        let cutoff_str = cutoff.to_rfc3339();
+        let mut stmt = self.conn.prepare(
+            "SELECT id, text, language, translation, notes, cleanup_at
+             FROM words
+             WHERE translation IS NOT NULL
+               AND (cleanup_at IS NULL OR cleanup_at <= ?1)
+             ORDER BY CASE WHEN cleanup_at IS NULL THEN 0 ELSE 1 END, cleanup_at ASC, created_at ASC
+             LIMIT ?2",
+        )?;
+        let mut rows = stmt.query(params![cutoff_str, limit as i64])?;
+        let mut entries = Vec::new();
+        while let Some(row) = rows.next()? {
+            let word_id_str: String = row.get(0)?;
+            let word_id = Uuid::parse_str(&word_id_str)
+                .map_err(|err| DbError::Config(format!("Invalid word_id: {err}")))?;
+            let text: String = row.get(1)?;
+            let language = Language::new(&row.get::<_, String>(2)?);
+            let translation: Option<String> = row.get(3)?;
+            let notes: Option<String> = row.get(4)?;
+            let cleanup_at = match row.get::<_, Option<String>>(5)? {
+                Some(value) => Some(
+                    DateTime::parse_from_rfc3339(&value)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .map_err(|err| {
+                            DbError::Config(format!("Invalid cleanup timestamp: {err}"))
+                        })?,
+                ),
+                None => None,
+            };
+            entries.push(CleanupEntryRow {
+                word_id,
+                text,
+                language,
+                translation,
+                notes,
+                cleanup_at,
+            });
+        }
+        Ok(entries)
+    }
+
+    fn record_cleanup(&self, word_id: Uuid, cleaned_at: DateTime<Utc>) -> DbResult<()> {
+        self.conn.execute(
+            "UPDATE words SET cleanup_at = ?1 WHERE id = ?2",
+            params![cleaned_at.to_rfc3339(), word_id.to_string()],
+        )?;
+        Ok(())
+    }
+
+    fn add_word_forms(&self, word_id: Uuid, forms: &[String]) -> DbResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        let existing: Option<String> = tx.query_row(
+            "SELECT forms FROM words WHERE id = ?1",
+            params![word_id.to_string()],
+            |row| row.get(0),
+        )?;
+        tx.execute(
+            "UPDATE words SET forms = ?1 WHERE id = ?2",
+            params![merge_forms(existing, forms), word_id.to_string()],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn ui_prefs(&self) -> DbResult<UiPrefs> {
+        let value = self
+            .conn
+            .query_row(
+                "SELECT value FROM settings WHERE key = ?1",
+                params![UI_PREFS_KEY],
+                |row| row.get(0),
+            )
+            .optional()?;
+        parse_setting(UI_PREFS_KEY, value)
+    }
+
+    fn card_settings(&self) -> DbResult<CardSettings> {
+        crate::cards::card_settings(&self.conn)
+    }
+
+    fn add_reverse_cards(&self) -> DbResult<usize> {
+        crate::cards::add_missing_reverse_cards(&self.conn, Utc::now())
+    }
+
+    fn add_tag(&self, word_id: Uuid, tag: &str) -> DbResult<()> {
+        let name = normalize_tag(tag)?;
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT OR IGNORE INTO tags (id, name, created_at) VALUES (?1, ?2, ?3)",
+            params![Uuid::new_v4().to_string(), name, Utc::now().to_rfc3339()],
+        )?;
+        tx.execute(
+            "INSERT OR IGNORE INTO word_tags (word_id, tag_id)
+             SELECT ?1, id FROM tags WHERE name = ?2",
+            params![word_id.to_string(), name],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn remove_tag(&self, word_id: Uuid, tag: &str) -> DbResult<()> {
+        let name = normalize_tag(tag)?;
+        self.conn.execute(
+            "DELETE FROM word_tags
+             WHERE word_id = ?1 AND tag_id IN (SELECT id FROM tags WHERE name = ?2)",
+            params![word_id.to_string(), name],
+        )?;
+        Ok(())
+    }
+
+    fn list_by_tag(&self, tag: &str) -> DbResult<Vec<Word>> {
+        let name = normalize_tag(tag)?;
+        let mut stmt = self.conn.prepare(
+            "SELECT w.id, w.text, w.language, w.translation, w.chapter, w.group_name, w.notes,
+                    w.created_at, w.source_type, w.source_name, w.source_page, w.captured_at,
+                    w.source_region, w.forms, w.alt_answers, w.kind, w.deck_id
+             FROM words w
+             JOIN word_tags wt ON wt.word_id = w.id
+             JOIN tags t ON t.id = wt.tag_id
+             WHERE t.name = ?1
+             ORDER BY w.chapter, w.group_name, w.created_at",
+        )?;
+        let rows = stmt.query_map(params![name], word_from_row)?;
+        let mut words = Vec::new();
+        for word in rows {
+            words.push(word?);
+        }
+        Ok(words)
+    }
+
+    fn word_tags(&self) -> DbResult<HashMap<Uuid, Vec<String>>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT wt.word_id, t.name FROM word_tags wt
+             JOIN tags t ON t.id = wt.tag_id
+             ORDER BY t.name",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let mut tags: HashMap<Uuid, Vec<String>> = HashMap::new();
+        for row in rows {
+            let (word_id, name) = row?;
+            if let Ok(word_id) = Uuid::parse_str(&word_id) {
+                tags.entry(word_id).or_default().push(name);
+            }
+        }
+        Ok(tags)
+    }
+
+    fn suspend_card(&self, word_id: Uuid) -> DbResult<()> {
+        self.conn.execute(
+            "UPDATE cards SET suspended = 1 WHERE word_id = ?1",
+            params![word_id.to_string()],
+        )?;
+        Ok(())
+    }
+
+    fn bury_card(&self, word_id: Uuid, until: DateTime<Utc>) -> DbResult<()> {
+        self.conn.execute(
+            "UPDATE cards SET buried_until = ?2 WHERE word_id = ?1",
+            params![word_id.to_string(), until.to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    fn unsuspend_card(&self, word_id: Uuid) -> DbResult<()> {
+        self.conn.execute(
+            "UPDATE cards SET suspended = 0, buried_until = NULL WHERE word_id = ?1",
+            params![word_id.to_string()],
+        )?;
+        Ok(())
+    }
+
+    fn import_profiles(&self) -> DbResult<Vec<ImportProfile>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT pattern, columns, ocr_profile, chapter, updated_at FROM import_profiles
+             ORDER BY updated_at DESC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(ImportProfile {
+                pattern: row.get(0)?,
+                columns: row.get(1)?,
+                ocr_profile: row.get(2)?,
+                chapter: row.get(3)?,
+                updated_at: parse_timestamp(&row.get::<_, String>(4)?),
+            })
+        })?;
+        let mut profiles = Vec::new();
+        for profile in rows {
+            profiles.push(profile?);
+        }
+        Ok(profiles)
+    }
+
+    fn search_words(&self, query: &str) -> DbResult<Vec<Word>> {
+        let Some(expression) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
+        let mut stmt = self.conn.prepare(
+            "SELECT w.id, w.text, w.language, w.translation, w.chapter, w.group_name, w.notes,
+                    w.created_at, w.source_type, w.source_name, w.source_page, w.captured_at,
+                    w.source_region, w.forms, w.alt_answers, w.kind, w.deck_id
+             FROM words_fts f
+             JOIN words w ON w.id = f.word_id
+             WHERE words_fts MATCH ?1
+             ORDER BY bm25(words_fts, 0.0, 10.0, 5.0, 1.0), w.created_at",
+        )?;
+        let rows = stmt.query_map(params![expression], word_from_row)?;
+        let mut words = Vec::new();
+        for word in rows {
+            words.push(word?);
+        }
+        Ok(words)
+    }
+
+    fn due_cards(&self, word_ids: &[Uuid], now: DateTime<Utc>) -> DbResult<Vec<Card>> {
+        let word_ids: HashSet<&Uuid> = word_ids.iter().collect();
+        let mut stmt = self.conn.prepare(
+            "SELECT id, word_id, due_at, interval_days, ease, reps, lapses, stability, difficulty,
+                    card_type, learning_step, learning_due_at,
+                    (SELECT MAX(reviewed_at) FROM reviews WHERE card_id = cards.id)
+             FROM cards
+             WHERE due_at <= ?1
+               AND suspended = 0
+               AND (buried_until IS NULL OR buried_until <= ?1)
+               AND card_type IS NOT ?2
+             ORDER BY due_at",
+        )?;
+        let rows = stmt.query_map(
+            params![now.to_rfc3339(), format!("{:?}", CardType::Cloze)],
+            card_from_row,
+        )?;
+        let mut cards = Vec::new();
+        for card in rows {
+            let card = card?;
+            if word_ids.contains(&card.word_id) {
+                cards.push(card);
+            }
+        }
+        Ok(cards)
+    }
+
+    fn record_review(&self, card: &Card, grade: Grade, reviewed_at: DateTime<Utc>) -> DbResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        let first_review: bool = tx.query_row(
+            "SELECT NOT EXISTS(SELECT 1 FROM reviews WHERE card_id = ?1)",
+            params![card.id.to_string()],
+            |row| row.get(0),
+        )?;
+        tx.execute(
+            "UPDATE cards SET due_at = ?2, interval_days = ?3, ease = ?4, reps = ?5, lapses = ?6,
+                              learning_step = ?7, learning_due_at = ?8, stability = ?9,
+                              difficulty = ?10, seen_count = seen_count + 1
+             WHERE id = ?1",
+            params![
+                card.id.to_string(),
+                card.due_at.to_rfc3339(),
+                card.interval_days,
+                card.ease,
+                card.reps,
+                card.lapses,
+                card.learning_step,
+                card.learning_due_at.map(|at| at.to_rfc3339()),
+                card.stability,
+                card.difficulty
+            ],
+        )?;
+        crate::session::count_daily_review(&tx, local_day(reviewed_at), first_review)?;
+        tx.execute(
+            "INSERT INTO reviews (id, card_id, grade, reviewed_at) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                Uuid::new_v4().to_string(),
+                card.id.to_string(),
+                grade.value(),
+                reviewed_at.to_rfc3339()
+            ],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn new_cards_on(&self, day: NaiveDate) -> DbResult<usize> {
+        Ok(crate::session::new_cards_on(&self.conn, day)?)
+    }
+
+    fn reviews_per_day(&self) -> DbResult<Vec<ReviewDay>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT substr(reviewed_at, 1, 10) AS day, COUNT(*), SUM(grade >= 3)
+             FROM reviews
+             GROUP BY day
+             ORDER BY day",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        })?;
+        let mut days = Vec::new();
+        for row in rows {
+            let (day, reviews, correct) = row?;
+            days.extend(review_day(&day, reviews, correct));
+        }
+        Ok(days)
+    }
+
+    fn chapter_accuracy(&self) -> DbResult<Vec<ChapterAccuracy>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT w.chapter, COUNT(*), SUM(r.grade >= 3)
+             FROM reviews r
+             JOIN cards c ON c.id = r.card_id
+             JOIN words w ON w.id = c.word_id
+             GROUP BY w.chapter
+             ORDER BY w.chapter IS NULL, w.chapter",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(ChapterAccuracy {
+                chapter: row.get(0)?,
+                reviews: row.get::<_, i64>(1)?.max(0) as usize,
+                correct: row.get::<_, i64>(2)?.max(0) as usize,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    fn save_import_profile(&self, profile: &ImportProfile) -> DbResult<()> {
+        self.conn.execute(
+            "INSERT INTO import_profiles (pattern, columns, ocr_profile, chapter, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(pattern) DO UPDATE SET
+                columns = excluded.columns,
+                ocr_profile = excluded.ocr_profile,
+                chapter = excluded.chapter,
+                updated_at = excluded.updated_at",
+            params![
+                profile.pattern,
+                profile.columns,
+                profile.ocr_profile,
+                profile.chapter,
+                profile.updated_at.to_rfc3339()
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn list_decks(&self) -> DbResult<Vec<Deck>> {
+        crate::decks::list_decks(&self.conn)
+    }
+
+    fn create_deck(&self, name: &str) -> DbResult<Deck> {
+        crate::decks::create_deck(&self.conn, name)
+    }
+
+    fn rename_deck(&self, deck_id: Uuid, name: &str) -> DbResult<()> {
+        crate::decks::rename_deck(&self.conn, deck_id, name)
+    }
+
+    fn delete_deck(&self, deck_id: Uuid) -> DbResult<()> {
+        crate::decks::delete_deck(&self.conn, deck_id)
+    }
+
+    fn move_to_deck(&self, word_ids: &[Uuid], deck_id: Option<Uuid>) -> DbResult<()> {
+        crate::decks::move_to_deck(&self.conn, word_ids, deck_id)
+    }
+
+    fn glossary(&self) -> DbResult<Vec<GlossaryEntry>> {
+        crate::glossary::list_glossary(&self.conn)
+    }
+
+    fn save_glossary_entry(&self, entry: &GlossaryEntry) -> DbResult<()> {
+        crate::glossary::save_glossary_entry(&self.conn, entry)
+    }
+
+    fn delete_glossary_entry(&self, language: &Language, term: &str) -> DbResult<bool> {
+        crate::glossary::delete_glossary_entry(&self.conn, language, term)
+    }
+}
@@ -0,0 +1,468 @@
+//! The word store both backends implement, with the helpers they share for
+//! duplicate detection, tags, import profiles, and the glossary.
+
+// The row helpers are only called by the backends.
+#![cfg_attr(not(any(feature = "sqlite", feature = "postgres")), allow(dead_code))]
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use le_core::glossary::{GlossaryEntry, glossary_key};
+use le_core::stats::{ChapterAccuracy, ReviewDay};
+use le_core::{Card, CardSettings, EntryKind, Grade, Language, UiPrefs, Word, WordSource};
+use serde::de::DeserializeOwned;
+use uuid::Uuid;
+
+pub type DbResult<T> = Result<T, DbError>;
+
+fn log_path() -> Option<String> {
+    std::env::var("LOG_SQL_PATH").ok()
+}
+
+pub fn log_error(message: &str) {
+    write_log("error", message);
+}
+
+/// Records the outcome of an import run, including cancelled and failed ones.
+pub fn log_import(message: &str) {
+    write_log("import", message);
+}
+
+fn write_log(tag: &str, message: &str) {
+    let Some(path) = log_path() else {
+        return;
+    };
+    let mut line = format!("[{tag}] ");
+    line.push_str(message);
+    line.push('\n');
+    if let Ok(mut file) = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+    {
+        use std::io::Write;
+        let _ = file.write_all(line.as_bytes());
+    }
+}
+
+#[derive(Debug)]
+pub enum DbError {
+    #[cfg(feature = "sqlite")]
+    Sqlite(rusqlite::Error),
+    #[cfg(feature = "postgres")]
+    Postgres(::postgres::Error),
+    Config(String),
+}
+
+impl fmt::Display for DbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "sqlite")]
+            DbError::Sqlite(err) => write!(f, "{err}"),
+            #[cfg(feature = "postgres")]
+            DbError::Postgres(err) => write!(f, "{err}"),
+            DbError::Config(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl Error for DbError {}
+
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for DbError {
+    fn from(err: rusqlite::Error) -> Self {
+        DbError::Sqlite(err)
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl From<::postgres::Error> for DbError {
+    fn from(err: ::postgres::Error) -> Self {
+        DbError::Postgres(err)
+    }
+}
+
+pub trait Db {
+    fn init(&self) -> DbResult<()>;
+    /// Saves a word with its forward card, and a reverse card when the
+    /// `cards` setting asks for one.
+    fn save_word(
+        &self,
+        text: &str,
+        translation: &str,
+        language: &Language,
+        chapter: Option<&str>,
+        group: Option<&str>,
+        source: Option<&WordSource>,
+    ) -> DbResult<()>;
+    /// Inserts `words` in one transaction, skipping any already saved in the
+    /// same language, with cards as [`Db::save_word`] gives them. Returns how
+    /// many were inserted; a failure writes nothing.
+    fn save_words_bulk(&self, words: &[NewWord]) -> DbResult<usize>;
+    fn word_exists(&self, text: &str, language: &Language) -> DbResult<bool>;
+    fn find_duplicates(&self, text: &str, language: &Language) -> DbResult<Vec<Word>>;
+    /// The oldest word saved as exactly `lemma`, with or without a leading
+    /// article. Unlike [`Db::find_duplicates`], a compound ending in `lemma`
+    /// ("weglopen" for "lopen") does not match.
+    fn find_lemma(&self, lemma: &str, language: &Language) -> DbResult<Option<Word>>;
+    fn count_words(&self) -> DbResult<usize>;
+    fn load_all_words(&self) -> DbResult<Vec<Word>>;
+    /// Words whose translation is NULL or blank, oldest first.
+    fn words_missing_translation(&self) -> DbResult<Vec<Word>>;
+    /// Words added at or after `since`, oldest first.
+    fn words_added_since(&self, since: DateTime<Utc>) -> DbResult<Vec<Word>>;
+    fn list_chapters(&self) -> DbResult<Vec<String>>;
+    fn last_group_for_chapter(&self, chapter: &str) -> DbResult<Option<String>>;
+    fn delete_word(&self, word_id: Uuid) -> DbResult<()>;
+    fn delete_all_words(&self) -> DbResult<()>;
+    fn update_translation(
+        &self,
+        word_id: Uuid,
+        translation: &str,
+        notes: Option<&str>,
+    ) -> DbResult<()>;
+    fn cleanup_candidates(
+        &self,
+        limit: usize,
+        cutoff: DateTime<Utc>,
+    ) -> DbResult<Vec<CleanupEntryRow>>;
+    fn record_cleanup(&self, word_id: Uuid, cleaned_at: DateTime<Utc>) -> DbResult<()>;
+    /// Adds inflected `forms` to a word's `forms` list, ignoring ones it
+    /// already has.
+    fn add_word_forms(&self, word_id: Uuid, forms: &[String]) -> DbResult<()>;
+    /// Accessibility preferences from the `ui` setting, or the defaults.
+    fn ui_prefs(&self) -> DbResult<UiPrefs>;
+    /// Which cards new words get, from the `cards` setting, or the defaults.
+    fn card_settings(&self) -> DbResult<CardSettings>;
+    /// Gives every word that could have a reverse card but has none one,
+    /// due now and in the word's deck. Returns how many were added.
+    fn add_reverse_cards(&self) -> DbResult<usize>;
+    /// Tags a word, creating the tag on first use. Tagging a word twice is a
+    /// no-op.
+    fn add_tag(&self, word_id: Uuid, tag: &str) -> DbResult<()>;
+    /// Removes a tag from a word; the tag itself is kept for other words.
+    fn remove_tag(&self, word_id: Uuid, tag: &str) -> DbResult<()>;
+    /// Words carrying `tag`, in the same order as [`Db::load_all_words`].
+    fn list_by_tag(&self, tag: &str) -> DbResult<Vec<Word>>;
+    /// Tag names per tagged word, each list alphabetical.
+    fn word_tags(&self) -> DbResult<HashMap<Uuid, Vec<String>>>;
+    /// Keeps a word's card out of review sessions until it is unsuspended.
+    fn suspend_card(&self, word_id: Uuid) -> DbResult<()>;
+    /// Keeps a word's card out of review sessions until `until`.
+    fn bury_card(&self, word_id: Uuid, until: DateTime<Utc>) -> DbResult<()>;
+    /// Lifts both a suspension and a burial.
+    fn unsuspend_card(&self, word_id: Uuid) -> DbResult<()>;
+    /// Every remembered import source, most recently used first.
+    fn import_profiles(&self) -> DbResult<Vec<ImportProfile>>;
+    /// Saves `profile`, replacing the one with the same pattern.
+    fn save_import_profile(&self, profile: &ImportProfile) -> DbResult<()>;
+    /// Words whose text, translation, or notes match every term of `query`,
+    /// best match first. Matches in the text rank above matches in the
+    /// translation, which rank above matches in the notes. An empty query
+    /// matches nothing.
+    fn search_words(&self, query: &str) -> DbResult<Vec<Word>>;
+    /// Cards of `word_ids` a review session may serve at `now`: due, not
+    /// suspended, and not buried past `now`. Cloze cards are left out, since
+    /// their sentences are only kept by the GUI.
+    fn due_cards(&self, word_ids: &[Uuid], now: DateTime<Utc>) -> DbResult<Vec<Card>>;
+    /// Saves the schedule `card` was given for `grade` and records the
+    /// review, in one transaction, counting it toward the day's
+    /// `daily_stats`.
+    fn record_review(&self, card: &Card, grade: Grade, reviewed_at: DateTime<Utc>) -> DbResult<()>;
+    /// New cards first answered on `day`, a local calendar day.
+    fn new_cards_on(&self, day: NaiveDate) -> DbResult<usize>;
+    /// Reviews and correct answers per UTC day, oldest first; days without
+    /// reviews are left out.
+    fn reviews_per_day(&self) -> DbResult<Vec<ReviewDay>>;
+    /// Reviews and correct answers per chapter, by chapter name with words
+    /// outside every chapter last.
+    fn chapter_accuracy(&self) -> DbResult<Vec<ChapterAccuracy>>;
+    /// Every deck with its word count, by name.
+    fn list_decks(&self) -> DbResult<Vec<Deck>>;
+    /// Creates an empty deck. Names are unique, ignoring case.
+    fn create_deck(&self, name: &str) -> DbResult<Deck>;
+    fn rename_deck(&self, deck_id: Uuid, name: &str) -> DbResult<()>;
+    /// Deletes a deck; its words are kept, outside every deck.
+    fn delete_deck(&self, deck_id: Uuid) -> DbResult<()>;
+    /// Moves words and their cards into `deck_id`, or out of every deck for
+    /// `None`.
+    fn move_to_deck(&self, word_ids: &[Uuid], deck_id: Option<Uuid>) -> DbResult<()>;
+    /// Every glossary entry, by language and term.
+    fn glossary(&self) -> DbResult<Vec<GlossaryEntry>>;
+    /// Adds the entry, or replaces the one for the same term and language.
+    fn save_glossary_entry(&self, entry: &GlossaryEntry) -> DbResult<()>;
+    /// `false` when there was no entry for `term`.
+    fn delete_glossary_entry(&self, language: &Language, term: &str) -> DbResult<bool>;
+}
+
+/// The JSON stored under setting `key`, or the defaults when it is unset.
+pub(crate) fn parse_setting<T: DeserializeOwned + Default>(
+    key: &str,
+    value: Option<String>,
+) -> DbResult<T> {
+    match value {
+        Some(value) => serde_json::from_str(&value)
+            .map_err(|err| DbError::Config(format!("Invalid '{key}' setting: {err}"))),
+        None => Ok(T::default()),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NewWord {
+    pub text: String,
+    pub translation: String,
+    pub language: Language,
+    pub chapter: Option<String>,
+    pub group: Option<String>,
+    pub source: Option<WordSource>,
+}
+
+/// A named set of words studied on its own, such as one textbook, across
+/// its chapters and groups.
+#[derive(Debug, Clone)]
+pub struct Deck {
+    pub id: Uuid,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub words: usize,
+}
+
+/// Settings remembered for files from one import source, such as the photos
+/// of one book or a spreadsheet exported every week, and applied the next
+/// time a file matching `pattern` is imported.
+#[derive(Debug, Clone)]
+pub struct ImportProfile {
+    /// Lowercase file name with `*` for the parts that change; see
+    /// [`source_pattern`].
+    pub pattern: String,
+    /// CSV column mapping, as understood by `csv_import::ColumnMapping`.
+    pub columns: Option<String>,
+    /// OCR pre-processing profile the photos were read with.
+    pub ocr_profile: Option<String>,
+    /// Chapter for words that do not name one.
+    pub chapter: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// The pattern a file belongs to: its lowercase name with each run of digits
+/// replaced by `*`, so `IMG_2041.jpg` and `IMG_2057.jpg`, or `week-3.csv` and
+/// `week-12.csv`, share one profile.
+pub fn source_pattern(file_name: &str) -> String {
+    let name = Path::new(file_name)
+        .file_name()
+        .map_or(file_name.to_string(), |name| {
+            name.to_string_lossy().into_owned()
+        })
+        .to_lowercase();
+    let mut pattern = String::new();
+    for ch in name.chars() {
+        if ch.is_ascii_digit() {
+            if !pattern.ends_with('*') {
+                pattern.push('*');
+            }
+        } else {
+            pattern.push(ch);
+        }
+    }
+    pattern
+}
+
+/// The profile whose pattern matches `file_name`, preferring the most
+/// specific (longest) pattern and then the most recently used.
+pub fn find_import_profile<'a>(
+    profiles: &'a [ImportProfile],
+    file_name: &str,
+) -> Option<&'a ImportProfile> {
+    let name = Path::new(file_name)
+        .file_name()
+        .map_or(file_name.to_string(), |name| {
+            name.to_string_lossy().into_owned()
+        })
+        .to_lowercase();
+    profiles
+        .iter()
+        .filter(|profile| glob_matches(&profile.pattern, &name))
+        .max_by(|a, b| {
+            a.pattern
+                .len()
+                .cmp(&b.pattern.len())
+                .then(a.updated_at.cmp(&b.updated_at))
+        })
+}
+
+/// Matches `name` against `pattern`, where `*` stands for any run of
+/// characters.
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[derive(Debug)]
+pub struct CleanupEntryRow {
+    pub word_id: Uuid,
+    pub text: String,
+    pub language: Language,
+    pub translation: Option<String>,
+    pub notes: Option<String>,
+    pub cleanup_at: Option<DateTime<Utc>>,
+}
+
+/// Case-insensitive comparison key with leading articles removed and
+/// separable verbs joined, so "haven" and "de haven", or "opbellen" and
+/// "bel op", are recognised as the same entry.
+pub fn duplicate_key(text: &str, language: &Language) -> String {
+    le_core::rules::entry_key(language.rules(), text)
+}
+
+/// `LIKE` pattern that narrows the rows worth comparing by [`duplicate_key`].
+/// A separable verb may be saved written apart, so any row containing its
+/// particle is a candidate. Wildcards typed by the user are escaped with `\`,
+/// so queries must use it with `ESCAPE '\'`.
+pub fn duplicate_pattern(key: &str, language: &Language) -> String {
+    match language.rules().separable_verb(key) {
+        Some((particle, _)) => format!("%{}%", escape_like(particle)),
+        None => format!("%{}", escape_like(key)),
+    }
+}
+
+/// `LIKE` pattern for rows that may be `lemma` behind an article; callers
+/// confirm with [`is_lemma`].
+pub(crate) fn lemma_pattern(lemma: &str) -> String {
+    format!("% {}", escape_like(&lemma.trim().to_lowercase()))
+}
+
+/// Whether a saved `text` is `lemma`, ignoring case and a leading article.
+pub(crate) fn is_lemma(text: &str, lemma: &str, language: &Language) -> bool {
+    language.rules().strip_article(&text.to_lowercase()) == lemma.trim().to_lowercase()
+}
+
+/// `term` with the `LIKE` wildcards and the escape character made literal.
+pub(crate) fn escape_like(term: &str) -> String {
+    term.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Tag names are compared case-insensitively and without surrounding
+/// whitespace, so "Verbs " and "verbs" are the same tag. Blank names are
+/// rejected.
+pub fn normalize_tag(tag: &str) -> DbResult<String> {
+    let tag = tag.trim().to_lowercase();
+    if tag.is_empty() {
+        return Err(DbError::Config("Tag name cannot be empty".to_string()));
+    }
+    Ok(tag)
+}
+
+/// Deck names are kept as typed, without surrounding whitespace. Blank names
+/// are rejected.
+pub fn normalize_deck_name(name: &str) -> DbResult<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(DbError::Config("Deck name cannot be empty".to_string()));
+    }
+    Ok(name.to_string())
+}
+
+/// Reads the `kind` column; rows saved before it existed are classified
+/// from their text.
+pub(crate) fn parse_entry_kind(value: Option<String>, text: &str) -> EntryKind {
+    value
+        .as_deref()
+        .and_then(EntryKind::parse)
+        .unwrap_or_else(|| EntryKind::detect(text))
+}
+
+/// Reads an `updated_at` column; unparseable values sort as oldest.
+pub(crate) fn parse_timestamp(value: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or(DateTime::<Utc>::MIN_UTC)
+}
+
+/// A row of the per-day review query, whose day is the date part of
+/// `reviewed_at`. Rows with an unreadable date are dropped.
+pub(crate) fn review_day(day: &str, reviews: i64, correct: i64) -> Option<ReviewDay> {
+    Some(ReviewDay {
+        date: NaiveDate::parse_from_str(day, "%Y-%m-%d").ok()?,
+        reviews: reviews.max(0) as usize,
+        correct: correct.max(0) as usize,
+    })
+}
+
+/// Reads a JSON array-of-strings column such as `forms` or `alt_answers`.
+pub(crate) fn parse_string_list(column: Option<String>) -> Vec<String> {
+    column
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default()
+}
+
+/// `existing` (a `forms` column value) plus `added`, case-insensitively
+/// deduplicated, serialized back to JSON.
+pub(crate) fn merge_forms(existing: Option<String>, added: &[String]) -> String {
+    let mut forms = parse_string_list(existing);
+    for form in added {
+        let form = form.trim();
+        if !form.is_empty() && !forms.iter().any(|known| known.eq_ignore_ascii_case(form)) {
+            forms.push(form.to_string());
+        }
+    }
+    serde_json::to_string(&forms).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// Column values for `source_type`, `source_name`, `source_page`,
+/// `captured_at`, `source_region`.
+pub(crate) type SourceColumns = (
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+);
+
+pub(crate) fn source_columns(source: Option<&WordSource>) -> SourceColumns {
+    match source {
+        Some(source) => (
+            Some(format!("{:?}", source.kind)),
+            source.name.clone(),
+            source.page.clone(),
+            Some(source.captured_at.to_rfc3339()),
+            source.region.map(|region| region.to_column()),
+        ),
+        None => (None, None, None, None, None),
+    }
+}
+
+/// The entry's key and trimmed translation, or an error when either is
+/// empty.
+pub(crate) fn glossary_columns(entry: &GlossaryEntry) -> DbResult<(String, &str)> {
+    let key = glossary_key(&entry.term);
+    if key.is_empty() {
+        return Err(DbError::Config("Glossary term cannot be empty".to_string()));
+    }
+    let translation = entry.translation.trim();
+    if translation.is_empty() {
+        return Err(DbError::Config(format!(
+            "Glossary translation for '{}' cannot be empty",
+            entry.term.trim()
+        )));
+    }
+    Ok((key, translation))
+}
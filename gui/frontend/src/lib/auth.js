@@ -20,6 +20,77 @@ const client = createClient({
 
 let authState = 'signed_out'
 let authToken = null
+let registeredToken = null
+
+const DEVICE_ID_KEY = 'le.deviceId'
+
+// Identifies this install to the auth server's `/auth/devices`. The id is
+// generated once and kept, so signing in again updates the same device.
+function deviceRegistration() {
+  let id = localStorage.getItem(DEVICE_ID_KEY)
+  if (!id) {
+    id = crypto.randomUUID()
+    localStorage.setItem(DEVICE_ID_KEY, id)
+  }
+  const ua = typeof navigator === 'undefined' ? '' : navigator.userAgent
+  const platform = isIos
+    ? 'ios'
+    : /Android/i.test(ua)
+      ? 'android'
+      : !isTauri
+        ? 'web'
+        : /Mac/i.test(ua)
+          ? 'macos'
+          : /Windows/i.test(ua)
+            ? 'windows'
+            : 'linux'
+  const name =
+    navigator.userAgentData?.platform || (isTauri ? `Language Enforcer (${platform})` : 'Browser')
+  return { id, name, platform }
+}
+
+// Registers an OAuth session's token, which does not pass through the auth
+// server's sign-in. A 403 means this device was signed out remotely.
+async function registerDevice(token) {
+  if (registeredToken === token) return
+  const response = await fetch(`${AUTH_SERVER_URL}/auth/devices`, {
+    method: 'POST',
+    headers: {
+      'content-type': 'application/json',
+      authorization: `Bearer ${token}`
+    },
+    body: JSON.stringify(deviceRegistration())
+  })
+  if (response.status === 401 || response.status === 403) {
+    clearAuth()
+    await client.auth.signOut?.()
+    throw new Error('This device was signed out remotely.')
+  }
+  if (response.ok) registeredToken = token
+}
+
+export async function listDevices() {
+  await requireSession()
+  const response = await fetch(`${AUTH_SERVER_URL}/auth/devices`, {
+    headers: { authorization: `Bearer ${authToken}` }
+  })
+  if (!response.ok) {
+    throw new Error(`Listing devices failed: ${response.status}`)
+  }
+  return response.json()
+}
+
+export async function revokeDevice(id) {
+  await requireSession()
+  const response = await fetch(`${AUTH_SERVER_URL}/auth/devices/${encodeURIComponent(id)}`, {
+    method: 'DELETE',
+    headers: { authorization: `Bearer ${authToken}` }
+  })
+  if (!response.ok) {
+    throw new Error(`Signing out the device failed: ${response.status}`)
+  }
+  return response.json()
+}
 
 export function getAuthState() {
   return authState
@@ -94,7 +165,15 @@ export async function refreshAuthState() {
   const sessionResult = await client.auth.getSession()
   const session = extractSession(sessionResult)
   const token = extractAccessToken(session)
-  if (token) authToken = token
+  if (token) {
+    try {
+      await registerDevice(token)
+    } catch (err) {
+      console.warn('[auth]', err.message)
+      return null
+    }
+    authToken = token
+  }
   authState = session || authToken ? 'signed_in' : 'signed_out'
   return session
 }
@@ -117,14 +196,14 @@ export async function requireSession() {
   return session
 }
 
-async function fetchAllWithToken(table, columns) {
+async function fetchAllWithToken(table, columns, orderBy = 'id') {
   const pageSize = 1000
   let offset = 0
   const rows = []
   for (;;) {
     const url = new URL(`${DATA_API_URL}/${table}`)
     url.searchParams.set('select', columns)
-    url.searchParams.set('order', 'id.asc')
+    url.searchParams.set('order', `${orderBy}.asc`)
     url.searchParams.set('limit', String(pageSize))
     url.searchParams.set('offset', String(offset))
     const response = await fetch(url, {
@@ -146,18 +225,18 @@ async function fetchAllWithToken(table, columns) {
   return rows
 }
 
-async function fetchAll(table, columns) {
+async function fetchAll(table, columns, orderBy = 'id') {
   const pageSize = 1000
   let from = 0
   const rows = []
   if (authToken) {
-    return fetchAllWithToken(table, columns)
+    return fetchAllWithToken(table, columns, orderBy)
   }
   for (;;) {
     const result = await client
       .from(table)
       .select(columns)
-      .order('id', { ascending: true })
+      .order(orderBy, { ascending: true })
       .range(from, from + pageSize - 1)
     if (result.error) {
       throw new Error(result.error.message)
@@ -176,15 +255,35 @@ export async function fetchDataApiSnapshot() {
   await requireSession()
   const words = await fetchAll(
     'words',
-    'id,text,language,translation,chapter,group_name,notes,created_at'
+    'id,text,language,translation,chapter,group_name,notes,created_at,source_type,source_name,source_page,captured_at,source_region,alt_answers,kind,deck_id'
+  )
+  const cards = await fetchAll(
+    'cards',
+    'id,word_id,due_at,interval_days,ease,reps,lapses,deck_id,card_type'
   )
-  const cards = await fetchAll('cards', 'id,word_id,due_at,interval_days,ease,reps,lapses')
   const reviews = await fetchAll('reviews', 'id,card_id,grade,reviewed_at')
   const concepts = await fetchAll('concepts', 'id,name,created_at')
-  return { words, cards, reviews, concepts }
+  // Tag tables are newer than the rest; without them the local tags are kept.
+  let tags = null
+  let word_tags = null
+  try {
+    tags = await fetchAll('tags', 'id,name,created_at')
+    word_tags = await fetchAll('word_tags', 'word_id,tag_id', 'word_id')
+  } catch (err) {
+    console.warn('Tags not available from the Data API', err)
+    tags = null
+    word_tags = null
+  }
+  let decks = null
+  try {
+    decks = await fetchAll('decks', 'id,name,created_at')
+  } catch (err) {
+    console.warn('Decks not available from the Data API', err)
+  }
+  return { words, cards, reviews, concepts, tags, word_tags, decks }
 }
 
-export async function updateWord({ wordId, text, translation }) {
+export async function updateWord({ wordId, text, translation, altAnswers }) {
   await requireSession()
   const updates = {}
   if (text !== null && text !== undefined) {
@@ -193,6 +292,9 @@ export async function updateWord({ wordId, text, translation }) {
   if (translation !== null && translation !== undefined) {
     updates.translation = translation
   }
+  if (altAnswers !== null && altAnswers !== undefined) {
+    updates.alt_answers = JSON.stringify(altAnswers)
+  }
   if (Object.keys(updates).length === 0) {
     return
   }
@@ -285,7 +387,9 @@ export async function addWord({ text, translation, language = 'Dutch' }) {
         chapter: null,
         group_name: null,
         notes: null,
-        created_at: createdAt
+        created_at: createdAt,
+        source_type: 'Manual',
+        captured_at: createdAt
     }
   const card = {
     id: cardId,
@@ -349,7 +453,9 @@ export async function generateSentence({
   translation,
   sourceLanguage,
   targetLanguage,
-  concept
+  concept,
+  level,
+  maxWords
 }) {
   const response = await fetch(`${AUTH_SERVER_URL}/ai/generate-sentence`, {
     method: 'POST',
@@ -361,7 +467,9 @@ export async function generateSentence({
       translation,
       source_language: sourceLanguage,
       target_language: targetLanguage,
-      concept
+      concept,
+      level,
+      max_words: maxWords
     })
   })
   if (!response.ok) {
@@ -401,6 +509,65 @@ export async function addConcept({ name }) {
   return { id: conceptId, name: payloadName, createdAt }
 }
 
+// Settings sync itself runs in the backend (`sync_settings`) via le_client.
+// `serverUrl` overrides the build-time auth server (see the `sync` setting).
+export async function settingsSyncTarget(serverUrl) {
+  await requireSession()
+  return { serverUrl: serverUrl || AUTH_SERVER_URL, accessToken: authToken }
+}
+
+// The auth server the backend should call: `serverUrl` from the `sync`
+// setting, or the build-time default.
+export function authServerUrl(serverUrl) {
+  return serverUrl || AUTH_SERVER_URL
+}
+
+// Opens the auth server's `/ws` event stream and calls `onEvent` with each
+// event. Reconnects with backoff (also while signed out) until the returned
+// function is called.
+export function openEventStream(serverUrl, onEvent) {
+  let socket = null
+  let retryTimer = null
+  let delay = 1000
+  let closed = false
+  const retry = () => {
+    if (closed) return
+    retryTimer = setTimeout(connect, delay)
+    delay = Math.min(delay * 2, 60 * 1000)
+  }
+  const connect = async () => {
+    if (closed) return
+    try {
+      await requireSession()
+    } catch {
+      retry()
+      return
+    }
+    const base = (serverUrl || AUTH_SERVER_URL).replace(/^http/, 'ws').replace(/\/$/, '')
+    socket = new WebSocket(`${base}/ws?access_token=${encodeURIComponent(authToken)}`)
+    socket.onopen = () => {
+      delay = 1000
+    }
+    socket.onmessage = (message) => {
+      try {
+        onEvent(JSON.parse(message.data))
+      } catch (err) {
+        console.warn('[events] bad event', err)
+      }
+    }
+    socket.onclose = () => {
+      socket = null
+      retry()
+    }
+  }
+  connect()
+  return () => {
+    closed = true
+    clearTimeout(retryTimer)
+    if (socket) socket.close()
+  }
+}
+
 export async function generateQuestion({
   word,
   translation,
@@ -427,18 +594,27 @@ export async function generateQuestion({
   return response.json()
 }
 
-export async function gradeSentence({ word, targetLanguage, userSentence, question, concept }) {
+export async function gradeSentence({
+  word,
+  targetLanguage,
+  userSentence,
+  question,
+  concept,
+  sourceSentence
+}) {
+  // Signed-in attempts are kept for /data/sentence-history.
+  const headers = { 'content-type': 'application/json' }
+  if (authToken) headers.authorization = `Bearer ${authToken}`
   const response = await fetch(`${AUTH_SERVER_URL}/ai/grade-sentence`, {
     method: 'POST',
-    headers: {
-      'content-type': 'application/json'
-    },
+    headers,
     body: JSON.stringify({
       word,
       target_language: targetLanguage,
       user_sentence: userSentence,
       question,
-      concept
+      concept,
+      source_sentence: sourceSentence
     })
   })
   if (!response.ok) {
@@ -476,7 +652,7 @@ export async function signInEmail(email, password) {
     headers: {
       'content-type': 'application/json'
     },
-    body: JSON.stringify({ email, password })
+    body: JSON.stringify({ email, password, device: deviceRegistration() })
   })
   const result = await response.json()
   if (typeof window !== 'undefined') {
@@ -488,7 +664,7 @@ export async function signInEmail(email, password) {
   if (!result?.access_token) {
     throw new Error('Sign-in failed')
   }
-  if (result?.access_token) authToken = result.access_token
+  if (result?.access_token) authToken = registeredToken = result.access_token
   authState = authToken ? 'signed_in' : 'signed_out'
   await refreshAuthState()
 }
@@ -500,7 +676,7 @@ export async function signUpEmail(email, password, name) {
     headers: {
       'content-type': 'application/json'
     },
-    body: JSON.stringify({ email, password, name })
+    body: JSON.stringify({ email, password, name, device: deviceRegistration() })
   })
   const result = await response.json()
   if (typeof window !== 'undefined') {
@@ -512,7 +688,7 @@ export async function signUpEmail(email, password, name) {
   if (!result?.access_token) {
     throw new Error('Sign-up failed')
   }
-  if (result?.access_token) authToken = result.access_token
+  if (result?.access_token) authToken = registeredToken = result.access_token
   authState = authToken ? 'signed_in' : 'signed_out'
   await refreshAuthState()
 }
@@ -0,0 +1,40 @@
+//! Typed answers for basic and reverse cards, checked with
+//! `le_core::answer_check` so the grade buttons can suggest a grade instead
+//! of leaving it all to self-grading. The learner still picks the grade.
+
+use le_core::answer_check::{self, AnswerCheck};
+use le_core::{CardType, Language, LanguagePair};
+use tauri::command;
+
+use crate::{SentencePick, get_setting, load_review_item, local_db, perf, usage};
+
+/// Checks `answer` against what the card asks for: its translation and
+/// alternative answers, or the word itself on a reverse card. `reversed`
+/// says the frontend flipped a basic card and asked for the word. `None`
+/// when the answer is blank or the card has nothing to check against.
+#[command]
+pub fn check_answer(
+    app: tauri::AppHandle,
+    card_id: String,
+    answer: String,
+    reversed: Option<bool>,
+) -> Result<Option<AnswerCheck>, String> {
+    let _perf = perf::track("check_answer");
+    let conn = local_db(&app)?;
+    let item = load_review_item(&conn, &card_id, SentencePick::Current)?
+        .ok_or_else(|| format!("No card with id {card_id}"))?;
+    let word_language = Language::new(&item.language);
+    let (language, accepted) = if reversed.unwrap_or(false) {
+        (word_language, vec![item.prompt.clone()])
+    } else if item.card_type != CardType::Basic {
+        (word_language, item.answer.clone().into_iter().collect())
+    } else {
+        let pair: LanguagePair = get_setting(&conn, "languages")?.unwrap_or_default();
+        let accepted = le_core::accepted_answers(item.answer.as_deref(), &item.alt_answers);
+        (pair.other(&word_language).clone(), accepted)
+    };
+    let accepted: Vec<&str> = accepted.iter().map(String::as_str).collect();
+    let check = answer_check::check_answer(language.rules(), &answer, &accepted);
+    usage::feature_used(&conn, "typed_answers");
+    Ok(check)
+}
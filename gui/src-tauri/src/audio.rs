@@ -0,0 +1,164 @@
+//! Audio-only review for the mobile build. `next_due_card_audio` serves the
+//! same queue as `next_due_card` but sends the word and its translation as
+//! spoken clips (WAV data URIs) with only the text needed to grade, so a
+//! session can run with the screen off and little bandwidth. Clips come from
+//! the system speech provider, honor the `tts` voice settings, and are
+//! cached on disk so each word is synthesized once per voice.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+use std::sync::Mutex;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use le_core::{Language, LanguagePair, fnv1a};
+use serde::Serialize;
+use tauri::{State, command};
+
+use crate::{
+    ReviewState, VoiceSettings, app_db_path, get_setting, local_db, log_error, next_due_card, perf,
+};
+
+const CACHE_DIR: &str = "audio-cache";
+/// Speaking rate `say` and `espeak` use at a rate multiplier of 1.
+const DEFAULT_WORDS_PER_MINUTE: f32 = 175.0;
+/// `espeak`'s default pitch on its 0–99 scale.
+const ESPEAK_DEFAULT_PITCH: f32 = 50.0;
+
+#[derive(Debug, Serialize)]
+pub struct AudioReviewItem {
+    card_id: String,
+    /// Passed back to `grade_card` like `ReviewItem::revision`.
+    revision: String,
+    text: String,
+    translation: Option<String>,
+    language: String,
+    /// The word spoken in the language being learned.
+    prompt_audio: Option<String>,
+    /// The translation spoken in the other language.
+    answer_audio: Option<String>,
+}
+
+/// Advances the session like `next_due_card` and returns the card with its
+/// clips. A clip that cannot be synthesized is left out rather than failing
+/// the card, so the session can fall back to text.
+#[command]
+pub fn next_due_card_audio(
+    app: tauri::AppHandle,
+    state: State<'_, Mutex<ReviewState>>,
+) -> Result<Option<AudioReviewItem>, String> {
+    let _perf = perf::track("next_due_card_audio");
+    let Some(item) = next_due_card(app.clone(), state)? else {
+        return Ok(None);
+    };
+    let (voices, pair) = {
+        let conn = local_db(&app)?;
+        let voices: HashMap<String, VoiceSettings> = get_setting(&conn, "tts")?.unwrap_or_default();
+        let pair: LanguagePair = get_setting(&conn, "languages")?.unwrap_or_default();
+        (voices, pair)
+    };
+    let cache = app_db_path(&app)?.with_file_name(CACHE_DIR);
+    let speak = |text: &str, language: &Language| {
+        let voice = voices
+            .get(&language.to_column())
+            .cloned()
+            .unwrap_or_default();
+        match clip(&cache, text, language, &voice) {
+            Ok(uri) => Some(uri),
+            Err(err) => {
+                log_error(&format!("next_due_card_audio: {err}"));
+                None
+            }
+        }
+    };
+    let prompt_audio = speak(&item.text, &Language::new(&item.language));
+    let answer_audio = item
+        .translation
+        .as_deref()
+        .filter(|translation| !translation.trim().is_empty())
+        .and_then(|translation| speak(translation, &pair.target));
+    Ok(Some(AudioReviewItem {
+        card_id: item.card_id,
+        revision: item.revision,
+        text: item.text,
+        translation: item.translation,
+        language: item.language,
+        prompt_audio,
+        answer_audio,
+    }))
+}
+
+/// `text` spoken with `voice`, as a `data:audio/wav` URI, synthesized on the
+/// first request and read from `cache` afterwards.
+fn clip(
+    cache: &Path,
+    text: &str,
+    language: &Language,
+    voice: &VoiceSettings,
+) -> Result<String, String> {
+    let path = cache.join(format!("{:016x}.wav", cache_key(text, language, voice)));
+    if !path.is_file() {
+        std::fs::create_dir_all(cache).map_err(|err| err.to_string())?;
+        synthesize(&path, text, language, voice)?;
+    }
+    let bytes = std::fs::read(&path).map_err(|err| err.to_string())?;
+    Ok(format!("data:audio/wav;base64,{}", BASE64.encode(bytes)))
+}
+
+/// Writes `text` to `path` as WAV with `say` on macOS and `espeak` elsewhere.
+/// A partial file is removed so it is not served from the cache later.
+fn synthesize(
+    path: &Path,
+    text: &str,
+    language: &Language,
+    voice: &VoiceSettings,
+) -> Result<(), String> {
+    let words_per_minute = (DEFAULT_WORDS_PER_MINUTE * voice.rate).round().to_string();
+    let (program, output) = if cfg!(target_os = "macos") {
+        let mut command = Command::new("say");
+        if let Some(name) = &voice.voice {
+            command.args(["-v", name]);
+        }
+        let output = command
+            .args(["-r", &words_per_minute, "--data-format=LEI16@22050", "-o"])
+            .arg(path)
+            .arg(text)
+            .output();
+        ("say", output)
+    } else {
+        let pitch = (ESPEAK_DEFAULT_PITCH * voice.pitch)
+            .clamp(0.0, 99.0)
+            .round();
+        let output = Command::new("espeak")
+            .args(["-v", voice.voice.as_deref().unwrap_or(language.code())])
+            .args(["-s", &words_per_minute, "-p", &pitch.to_string(), "-w"])
+            .arg(path)
+            .arg(text)
+            .output();
+        ("espeak", output)
+    };
+    let output = output.map_err(|err| format!("Failed to run {program}: {err}"))?;
+    if !output.status.success() || !path.is_file() {
+        let _ = std::fs::remove_file(path);
+        return Err(format!(
+            "{program} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// FNV-1a over everything that changes the clip, so edited voice settings
+/// get new files instead of stale ones.
+fn cache_key(text: &str, language: &Language, voice: &VoiceSettings) -> u64 {
+    let input = format!(
+        "{}|{}|{}|{}|{}",
+        language.code(),
+        voice.voice.as_deref().unwrap_or(""),
+        voice.rate,
+        voice.pitch,
+        text.trim()
+    );
+    fnv1a(input.as_bytes())
+}
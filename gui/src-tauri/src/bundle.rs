@@ -0,0 +1,406 @@
+//! Chapter bundles: one chapter's words, example sentences, and source
+//! photos in a single gzip-compressed JSON file, for sharing a chapter
+//! without a full-deck dump. Scheduling never leaves the deck; imported
+//! words start as new cards.
+
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::Utc;
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use le_core::import::{ImportPlan, ImportPlanBuilder};
+use le_core::{EntryKind, SourceKind, WordSource, default_new_card};
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::{Deserialize, Serialize};
+use tauri::{Manager, command};
+use uuid::Uuid;
+
+use crate::{SessionSettings, get_setting, local_db, perf, usage};
+
+const BUNDLE_FORMAT: &str = "language-enforcer/chapter";
+const BUNDLE_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ChapterBundle {
+    format: String,
+    version: u32,
+    chapter: String,
+    exported_at: String,
+    words: Vec<BundleWord>,
+    #[serde(default)]
+    media: Vec<BundleMedia>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BundleWord {
+    text: String,
+    language: String,
+    translation: Option<String>,
+    group: Option<String>,
+    notes: Option<String>,
+    #[serde(default)]
+    kind: EntryKind,
+    #[serde(default)]
+    alt_answers: Vec<String>,
+    #[serde(default)]
+    source: Option<WordSource>,
+    #[serde(default)]
+    example: Option<BundleExample>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BundleExample {
+    sentence: String,
+    translation: Option<String>,
+}
+
+/// A source photo, referenced by file name from `WordSource::name`.
+#[derive(Debug, Serialize, Deserialize)]
+struct BundleMedia {
+    name: String,
+    data: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BundleSummary {
+    chapter: String,
+    words: usize,
+    /// Words already in the deck under the same chapter, left untouched.
+    skipped: usize,
+    media: usize,
+}
+
+/// Writes `chapter` to `path`. Source photos are embedded when `media_dir`
+/// (the TUI's `img/` folder, for example) is given and holds them.
+#[command]
+pub fn export_chapter(
+    app: tauri::AppHandle,
+    chapter: String,
+    path: String,
+    media_dir: Option<String>,
+) -> Result<BundleSummary, String> {
+    let _perf = perf::track("export_chapter");
+    let conn = local_db(&app)?;
+    usage::feature_used(&conn, "chapter_export");
+    let words = load_chapter_words(&conn, &chapter)?;
+    drop(conn);
+    if words.is_empty() {
+        return Err(format!("Chapter '{chapter}' has no words"));
+    }
+
+    let mut media: Vec<BundleMedia> = Vec::new();
+    if let Some(dir) = media_dir.as_deref().map(Path::new) {
+        for word in &words {
+            let Some(name) = photo_name(word.source.as_ref()) else {
+                continue;
+            };
+            if media.iter().any(|item| item.name == name) {
+                continue;
+            }
+            let Ok(bytes) = std::fs::read(dir.join(&name)) else {
+                continue;
+            };
+            media.push(BundleMedia {
+                name,
+                data: BASE64.encode(bytes),
+            });
+        }
+    }
+
+    let summary = BundleSummary {
+        chapter: chapter.clone(),
+        words: words.len(),
+        skipped: 0,
+        media: media.len(),
+    };
+    let bundle = ChapterBundle {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        chapter,
+        exported_at: Utc::now().to_rfc3339(),
+        words,
+        media,
+    };
+    let json = serde_json::to_vec(&bundle).map_err(|err| err.to_string())?;
+    let file =
+        std::fs::File::create(&path).map_err(|err| format!("Failed to write {path}: {err}"))?;
+    let mut encoder = GzEncoder::new(file, Compression::default());
+    encoder
+        .write_all(&json)
+        .and_then(|()| encoder.finish().map(drop))
+        .map_err(|err| format!("Failed to write {path}: {err}"))?;
+    Ok(summary)
+}
+
+/// Adds a bundle's words to the deck as new cards under its chapter. Words
+/// already saved in that chapter are skipped, so importing twice is safe.
+/// Photos are written to `media_dir`, or to `media/` in the app data folder.
+#[command]
+pub fn import_chapter_bundle(
+    app: tauri::AppHandle,
+    path: String,
+    media_dir: Option<String>,
+) -> Result<BundleSummary, String> {
+    let _perf = perf::track("import_chapter_bundle");
+    let bundle = read_bundle(&path)?;
+
+    let media_dir = match media_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => app
+            .path()
+            .app_data_dir()
+            .map_err(|err| err.to_string())?
+            .join("media"),
+    };
+    let mut media = 0;
+    for item in &bundle.media {
+        // Names come from another machine; keep only the file name.
+        let Some(name) = Path::new(&item.name).file_name() else {
+            continue;
+        };
+        let bytes = BASE64
+            .decode(&item.data)
+            .map_err(|err| format!("Invalid media {}: {err}", item.name))?;
+        std::fs::create_dir_all(&media_dir).map_err(|err| err.to_string())?;
+        std::fs::write(media_dir.join(name), bytes)
+            .map_err(|err| format!("Failed to write {}: {err}", item.name))?;
+        media += 1;
+    }
+
+    let mut conn = local_db(&app)?;
+    usage::feature_used(&conn, "chapter_import");
+    let (words, skipped) = insert_bundle_words(&mut conn, &bundle)?;
+    Ok(BundleSummary {
+        chapter: bundle.chapter,
+        words,
+        skipped,
+        media,
+    })
+}
+
+/// What `import_chapter_bundle` would add, without writing anything.
+#[command]
+pub fn preview_chapter_bundle(app: tauri::AppHandle, path: String) -> Result<ImportPlan, String> {
+    let _perf = perf::track("preview_chapter_bundle");
+    let bundle = read_bundle(&path)?;
+    let conn = local_db(&app)?;
+    let settings: SessionSettings = get_setting(&conn, "session")?.unwrap_or_default();
+    let backlog: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM cards c
+             WHERE NOT EXISTS (SELECT 1 FROM reviews r WHERE r.card_id = c.id)",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|err| err.to_string())?;
+    let mut plan = ImportPlanBuilder::new()
+        .new_per_day(settings.max_new_cards)
+        .backlog(backlog as usize);
+    for word in &bundle.words {
+        let saved = word_saved(&conn, &bundle.chapter, word)?;
+        plan.add(
+            &word.text,
+            word.translation.as_deref(),
+            word.group.as_deref(),
+            saved,
+        );
+    }
+    Ok(plan.build())
+}
+
+fn read_bundle(path: &str) -> Result<ChapterBundle, String> {
+    let file = std::fs::File::open(path).map_err(|err| format!("Failed to open {path}: {err}"))?;
+    let mut json = Vec::new();
+    GzDecoder::new(file)
+        .read_to_end(&mut json)
+        .map_err(|err| format!("{path} is not a chapter bundle: {err}"))?;
+    let bundle: ChapterBundle =
+        serde_json::from_slice(&json).map_err(|err| format!("Invalid chapter bundle: {err}"))?;
+    if bundle.format != BUNDLE_FORMAT || bundle.version > BUNDLE_VERSION {
+        return Err(format!(
+            "Unsupported bundle format {} v{}",
+            bundle.format, bundle.version
+        ));
+    }
+    Ok(bundle)
+}
+
+/// Whether the chapter already has the word, matched case-insensitively.
+fn word_saved(conn: &Connection, chapter: &str, word: &BundleWord) -> Result<bool, String> {
+    conn.query_row(
+        "SELECT 1 FROM words
+         WHERE chapter = ?1 AND language = ?2 AND lower(text) = lower(?3)",
+        params![chapter, word.language, word.text],
+        |_| Ok(()),
+    )
+    .optional()
+    .map(|found| found.is_some())
+    .map_err(|err| err.to_string())
+}
+
+fn load_chapter_words(conn: &Connection, chapter: &str) -> Result<Vec<BundleWord>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT w.text, w.language, w.translation, w.group_name, w.notes, w.kind,
+                    w.alt_answers, w.source_type, w.source_name, w.source_page, w.captured_at,
+                    w.source_region, e.sentence, e.translation
+             FROM words w
+             LEFT JOIN examples e ON e.word_id = w.id
+             WHERE w.chapter = ?1
+             ORDER BY w.created_at",
+        )
+        .map_err(|err| err.to_string())?;
+    stmt.query_map(params![chapter], |row| {
+        let text: String = row.get(0)?;
+        let kind = row
+            .get::<_, Option<String>>(5)?
+            .as_deref()
+            .and_then(EntryKind::parse)
+            .unwrap_or_else(|| EntryKind::detect(&text));
+        let alt_answers = row
+            .get::<_, Option<String>>(6)?
+            .and_then(|value| serde_json::from_str(&value).ok())
+            .unwrap_or_default();
+        let example = row
+            .get::<_, Option<String>>(12)?
+            .map(|sentence| -> rusqlite::Result<BundleExample> {
+                Ok(BundleExample {
+                    sentence,
+                    translation: row.get(13)?,
+                })
+            })
+            .transpose()?;
+        Ok(BundleWord {
+            text,
+            language: row.get(1)?,
+            translation: row.get(2)?,
+            group: row.get(3)?,
+            notes: row.get(4)?,
+            kind,
+            alt_answers,
+            source: WordSource::from_columns(
+                row.get(7)?,
+                row.get(8)?,
+                row.get(9)?,
+                row.get(10)?,
+                row.get(11)?,
+            ),
+            example,
+        })
+    })
+    .map_err(|err| err.to_string())?
+    .collect::<rusqlite::Result<Vec<_>>>()
+    .map_err(|err| err.to_string())
+}
+
+fn insert_bundle_words(
+    conn: &mut Connection,
+    bundle: &ChapterBundle,
+) -> Result<(usize, usize), String> {
+    let tx = conn.transaction().map_err(|err| err.to_string())?;
+    let settings = le_db::cards::card_settings(&tx).map_err(|err| err.to_string())?;
+    let now = Utc::now();
+    let (mut added, mut skipped) = (0, 0);
+    for word in &bundle.words {
+        if word_saved(&tx, &bundle.chapter, word)? {
+            skipped += 1;
+            continue;
+        }
+        let word_id = Uuid::new_v4();
+        let source = word.source.as_ref();
+        let alt_answers = (!word.alt_answers.is_empty())
+            .then(|| serde_json::to_string(&word.alt_answers))
+            .transpose()
+            .map_err(|err| err.to_string())?;
+        tx.execute(
+            "INSERT INTO words (id, text, language, translation, chapter, group_name, notes,
+                                created_at, source_type, source_name, source_page, captured_at,
+                                source_region, alt_answers, kind)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+            params![
+                word_id.to_string(),
+                word.text,
+                word.language,
+                word.translation,
+                bundle.chapter,
+                word.group,
+                word.notes,
+                now.to_rfc3339(),
+                source.map(|source| format!("{:?}", source.kind)),
+                source.and_then(|source| source.name.clone()),
+                source.and_then(|source| source.page.clone()),
+                source.map(|source| source.captured_at.to_rfc3339()),
+                source
+                    .and_then(|source| source.region)
+                    .map(|region| region.to_column()),
+                alt_answers,
+                format!("{:?}", word.kind)
+            ],
+        )
+        .map_err(|err| err.to_string())?;
+        let card = default_new_card(word_id, now);
+        tx.execute(
+            "INSERT INTO cards (id, word_id, due_at, interval_days, ease, reps, lapses, seen_count)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 0)",
+            params![
+                card.id.to_string(),
+                word_id.to_string(),
+                card.due_at.to_rfc3339(),
+                card.interval_days,
+                card.ease,
+                card.reps,
+                card.lapses
+            ],
+        )
+        .map_err(|err| err.to_string())?;
+        le_db::cards::add_reverse_card(
+            &tx,
+            &settings,
+            word_id,
+            word.kind,
+            word.translation.as_deref(),
+            now,
+        )
+        .map_err(|err| err.to_string())?;
+        if let Some(example) = &word.example {
+            tx.execute(
+                "INSERT INTO examples (word_id, sentence, translation, created_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    word_id.to_string(),
+                    example.sentence,
+                    example.translation,
+                    now.to_rfc3339()
+                ],
+            )
+            .map_err(|err| err.to_string())?;
+            tx.execute(
+                "INSERT OR IGNORE INTO sentences (word_id, sentence, translation, created_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    word_id.to_string(),
+                    example.sentence,
+                    example.translation,
+                    now.to_rfc3339()
+                ],
+            )
+            .map_err(|err| err.to_string())?;
+            crate::cloze::add_if_wanted(&tx, &word_id.to_string())?;
+        }
+        added += 1;
+    }
+    tx.commit().map_err(|err| err.to_string())?;
+    Ok((added, skipped))
+}
+
+/// The image file behind a photo or clipboard source.
+fn photo_name(source: Option<&WordSource>) -> Option<String> {
+    let source = source?;
+    matches!(source.kind, SourceKind::Photo | SourceKind::Clipboard)
+        .then(|| source.name.clone())
+        .flatten()
+}
@@ -0,0 +1,233 @@
+//! Bulk edits of card scheduling state, for deck surgery after an import or
+//! a change to the algorithm (e.g. resetting a whole chapter to new). Every
+//! call previews by default; with `dry_run: false` the edit is applied in one
+//! transaction and each card's old and new state is kept in the local
+//! `card_state_edits` table, so nothing is lost the way a raw `UPDATE` would.
+
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, params};
+use serde::{Deserialize, Serialize};
+use tauri::command;
+
+use crate::{SessionFilter, local_db, perf, usage};
+
+/// SM-2 never lowers ease below this.
+const MIN_EASE: f64 = 1.3;
+const MAX_EASE: f64 = 5.0;
+const MAX_INTERVAL_DAYS: i32 = 36_500;
+/// Changes listed in the preview; `matched` still counts every card.
+const PREVIEW_CARDS: usize = 50;
+
+#[derive(Debug, Deserialize)]
+pub struct CardStateInput {
+    #[serde(default)]
+    filter: SessionFilter,
+    /// Puts cards back to the state of a new card, due now. The fields below
+    /// are applied on top.
+    #[serde(default)]
+    reset: bool,
+    ease: Option<f64>,
+    interval_days: Option<i32>,
+    /// RFC 3339.
+    due_at: Option<String>,
+    /// Only reports what would change. Defaults to `true`.
+    #[serde(default = "default_dry_run")]
+    dry_run: bool,
+}
+
+fn default_dry_run() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CardState {
+    due_at: String,
+    interval_days: i32,
+    ease: f64,
+    reps: i32,
+    lapses: i32,
+    seen_count: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CardStateChange {
+    card_id: String,
+    word: String,
+    before: CardState,
+    after: CardState,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CardStateReport {
+    /// Cards matching the filter whose state would change.
+    matched: usize,
+    changes: Vec<CardStateChange>,
+    applied: bool,
+}
+
+#[command]
+pub fn bulk_set_card_state(
+    app: tauri::AppHandle,
+    input: CardStateInput,
+) -> Result<CardStateReport, String> {
+    let _perf = perf::track("bulk_set_card_state");
+    let due_at = validate(&input)?;
+    let now = Utc::now().to_rfc3339();
+    let conn = local_db(&app)?;
+    let changes: Vec<CardStateChange> = load_cards(&conn, &input.filter)
+        .map_err(|err| err.to_string())?
+        .into_iter()
+        .filter_map(|(card_id, word, before)| {
+            let after = edited(&before, &input, due_at.as_deref(), &now);
+            (after != before).then_some(CardStateChange {
+                card_id,
+                word,
+                before,
+                after,
+            })
+        })
+        .collect();
+    let matched = changes.len();
+
+    if !input.dry_run && matched > 0 {
+        apply(&conn, &changes, &now).map_err(|err| format!("Card state edit failed: {err}"))?;
+        usage::feature_used(&conn, "bulk_card_edit");
+    }
+    Ok(CardStateReport {
+        matched,
+        changes: changes.into_iter().take(PREVIEW_CARDS).collect(),
+        applied: !input.dry_run && matched > 0,
+    })
+}
+
+/// Checks the requested values and returns `due_at` normalized to UTC.
+fn validate(input: &CardStateInput) -> Result<Option<String>, String> {
+    if !input.reset
+        && input.ease.is_none()
+        && input.interval_days.is_none()
+        && input.due_at.is_none()
+    {
+        return Err("Nothing to change: set reset, ease, interval_days, or due_at".to_string());
+    }
+    if let Some(ease) = input.ease
+        && !(MIN_EASE..=MAX_EASE).contains(&ease)
+    {
+        return Err(format!("Ease must be between {MIN_EASE} and {MAX_EASE}"));
+    }
+    if let Some(interval) = input.interval_days
+        && !(0..=MAX_INTERVAL_DAYS).contains(&interval)
+    {
+        return Err(format!(
+            "Interval must be between 0 and {MAX_INTERVAL_DAYS} days"
+        ));
+    }
+    input
+        .due_at
+        .as_deref()
+        .map(|due_at| {
+            DateTime::parse_from_rfc3339(due_at.trim())
+                .map(|due_at| due_at.with_timezone(&Utc).to_rfc3339())
+                .map_err(|err| format!("Invalid due_at: {err}"))
+        })
+        .transpose()
+}
+
+fn edited(
+    before: &CardState,
+    input: &CardStateInput,
+    due_at: Option<&str>,
+    now: &str,
+) -> CardState {
+    let mut after = before.clone();
+    if input.reset {
+        after = CardState {
+            due_at: now.to_string(),
+            interval_days: 0,
+            ease: 2.5,
+            reps: 0,
+            lapses: 0,
+            seen_count: 0,
+        };
+    }
+    if let Some(ease) = input.ease {
+        after.ease = ease;
+    }
+    if let Some(interval_days) = input.interval_days {
+        after.interval_days = interval_days;
+    }
+    if let Some(due_at) = due_at {
+        after.due_at = due_at.to_string();
+    }
+    after
+}
+
+fn load_cards(
+    conn: &Connection,
+    filter: &SessionFilter,
+) -> rusqlite::Result<Vec<(String, String, CardState)>> {
+    let mut stmt = conn.prepare(
+        "SELECT c.id, w.text, c.due_at, c.interval_days, c.ease, c.reps, c.lapses, c.seen_count
+         FROM cards c
+         JOIN words w ON w.id = c.word_id
+         WHERE (?1 IS NULL OR w.chapter = ?1)
+           AND (?2 IS NULL OR w.group_name = ?2)
+           AND (?3 IS NULL OR EXISTS (
+                SELECT 1 FROM word_tags wt JOIN tags t ON t.id = wt.tag_id
+                WHERE wt.word_id = w.id AND t.name = ?3))
+         ORDER BY w.text",
+    )?;
+    stmt.query_map(
+        params![filter.chapter, filter.group, filter.tag_name()],
+        |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                CardState {
+                    due_at: row.get(2)?,
+                    interval_days: row.get(3)?,
+                    ease: row.get(4)?,
+                    reps: row.get(5)?,
+                    lapses: row.get(6)?,
+                    seen_count: row.get(7)?,
+                },
+            ))
+        },
+    )?
+    .collect()
+}
+
+fn apply(conn: &Connection, changes: &[CardStateChange], now: &str) -> Result<(), String> {
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|err| err.to_string())?;
+    for change in changes {
+        let after = &change.after;
+        tx.execute(
+            "UPDATE cards SET due_at = ?1, interval_days = ?2, ease = ?3, reps = ?4, lapses = ?5,
+                 seen_count = ?6
+             WHERE id = ?7",
+            params![
+                after.due_at,
+                after.interval_days,
+                after.ease,
+                after.reps,
+                after.lapses,
+                after.seen_count,
+                change.card_id
+            ],
+        )
+        .map_err(|err| err.to_string())?;
+        tx.execute(
+            "INSERT INTO card_state_edits (card_id, before, after, edited_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                change.card_id,
+                serde_json::to_string(&change.before).map_err(|err| err.to_string())?,
+                serde_json::to_string(after).map_err(|err| err.to_string())?,
+                now
+            ],
+        )
+        .map_err(|err| err.to_string())?;
+    }
+    tx.commit().map_err(|err| err.to_string())
+}
@@ -0,0 +1,164 @@
+//! Cloze cards: one of a word's sentences with the word blanked out, typed
+//! in and checked here instead of self-graded. With `cloze` on in the
+//! `cards` setting a word gets one as soon as a saved sentence contains it;
+//! `add_cloze_cards` gives existing words theirs. `next_due_card` blanks the
+//! sentence it shows, and `grade_cloze_card` checks against that same one.
+
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use le_core::cloze::{self, make_cloze};
+use le_core::{CardType, EntryKind, Grade, Language};
+use rusqlite::{Connection, params};
+use serde::{Deserialize, Serialize};
+use tauri::{State, command};
+use uuid::Uuid;
+
+use crate::{
+    GradeInput, ReviewState, SentencePick, grade_card, local_db, perf, pick_sentence, usage,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct ClozeGradeInput {
+    card_id: String,
+    answer: String,
+    review_id: Option<String>,
+    revision: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClozeResult {
+    correct: bool,
+    /// The blanked words, to show after a wrong answer.
+    expected: String,
+    grade: Grade,
+}
+
+/// Gives every word whose sentences contain it a cloze card, if it has
+/// none, due now. Returns how many were added.
+#[command]
+pub fn add_cloze_cards(app: tauri::AppHandle) -> Result<usize, String> {
+    let _perf = perf::track("add_cloze_cards");
+    let conn = local_db(&app)?;
+    let added = add_missing(&conn, None, Utc::now())?;
+    usage::feature_used(&conn, "cloze_cards");
+    Ok(added)
+}
+
+/// Checks the typed answer against the sentence `next_due_card` showed and
+/// grades the card with the result; otherwise identical to `grade_card`.
+#[command]
+pub fn grade_cloze_card(
+    app: tauri::AppHandle,
+    input: ClozeGradeInput,
+    state: State<'_, Mutex<ReviewState>>,
+) -> Result<ClozeResult, String> {
+    let _perf = perf::track("grade_cloze_card");
+    let conn = local_db(&app)?;
+    let (word_id, text, language): (String, String, String) = conn
+        .query_row(
+            "SELECT w.id, w.text, w.language FROM cards c JOIN words w ON w.id = c.word_id
+             WHERE c.id = ?1",
+            params![input.card_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|err| format!("Card {} not found: {err}", input.card_id))?;
+    let rules = Language::new(&language).rules();
+    let blanked = pick_sentence(&conn, &word_id, SentencePick::Current)
+        .map_err(|err| err.to_string())?
+        .and_then(|(sentence, _)| make_cloze(rules, &sentence, &text))
+        .ok_or_else(|| format!("No sentence to check '{text}' against"))?;
+    let correct = cloze::is_correct(rules, &blanked, &input.answer);
+    let grade = cloze::grade(correct);
+    usage::feature_used(&conn, "cloze_cards");
+    drop(conn);
+    grade_card(
+        app,
+        GradeInput {
+            card_id: input.card_id,
+            grade,
+            review_id: input.review_id,
+            revision: input.revision,
+        },
+        state,
+    )?;
+    Ok(ClozeResult {
+        correct,
+        expected: blanked.answer,
+        grade,
+    })
+}
+
+/// Adds a cloze card for a word that just got a sentence, when the `cards`
+/// setting asks for them.
+pub(crate) fn add_if_wanted(conn: &Connection, word_id: &str) -> Result<bool, String> {
+    let settings = le_db::cards::card_settings(conn).map_err(|err| err.to_string())?;
+    if !settings.cloze {
+        return Ok(false);
+    }
+    Ok(add_missing(conn, Some(word_id), Utc::now())? > 0)
+}
+
+/// Adds cloze cards to the words (all, or just `only`) that have none yet
+/// and a sentence containing them. Sentence entries never get one.
+fn add_missing(conn: &Connection, only: Option<&str>, now: DateTime<Utc>) -> Result<usize, String> {
+    let words = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT w.id, w.text, w.language, w.kind, w.deck_id
+                 FROM words w
+                 WHERE (?1 IS NULL OR w.id = ?1)
+                   AND EXISTS (SELECT 1 FROM sentences s WHERE s.word_id = w.id)
+                   AND NOT EXISTS (
+                       SELECT 1 FROM cards c WHERE c.word_id = w.id AND c.card_type = ?2)",
+            )
+            .map_err(|err| err.to_string())?;
+        stmt.query_map(params![only, format!("{:?}", CardType::Cloze)], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+            ))
+        })
+        .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+        .map_err(|err| err.to_string())?
+    };
+    let mut added = 0;
+    for (word_id, text, language, kind, deck_id) in words {
+        let kind = kind
+            .as_deref()
+            .and_then(EntryKind::parse)
+            .unwrap_or_else(|| EntryKind::detect(&text));
+        if kind != EntryKind::Word || !has_cloze_sentence(conn, &word_id, &text, &language)? {
+            continue;
+        }
+        let Ok(id) = Uuid::parse_str(&word_id) else {
+            continue;
+        };
+        le_db::cards::insert_new_card(conn, id, CardType::Cloze, deck_id.as_deref(), now)
+            .map_err(|err| err.to_string())?;
+        added += 1;
+    }
+    Ok(added)
+}
+
+fn has_cloze_sentence(
+    conn: &Connection,
+    word_id: &str,
+    text: &str,
+    language: &str,
+) -> Result<bool, String> {
+    let rules = Language::new(language).rules();
+    let mut stmt = conn
+        .prepare("SELECT sentence FROM sentences WHERE word_id = ?1")
+        .map_err(|err| err.to_string())?;
+    let sentences = stmt
+        .query_map(params![word_id], |row| row.get::<_, String>(0))
+        .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+        .map_err(|err| err.to_string())?;
+    Ok(sentences
+        .iter()
+        .any(|sentence| make_cloze(rules, sentence, text).is_some()))
+}
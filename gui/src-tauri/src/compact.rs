@@ -0,0 +1,73 @@
+//! Compact review widget: a small always-on-top second window for reviewing
+//! in a screen corner while reading. It loads the same frontend, which
+//! switches to its compact layout by window label, and shares the managed
+//! `ReviewState`, so both windows work through one session (`current_card`
+//! and `card-served` keep them on the same card).
+
+use serde::Serialize;
+use tauri::{Emitter, Manager, command};
+
+use crate::{local_db, log_error, perf, usage};
+
+pub const WINDOW_LABEL: &str = "compact";
+const WIDTH: f64 = 360.0;
+const HEIGHT: f64 = 280.0;
+/// Gap between the widget and the screen edges, in logical pixels.
+const MARGIN: f64 = 24.0;
+
+/// Emitted as `compact-mode` whenever the widget opens or closes.
+#[derive(Debug, Clone, Serialize)]
+pub struct CompactMode {
+    open: bool,
+}
+
+/// Opens the compact window in the top-right corner of the primary monitor,
+/// or closes it when it is already open.
+#[command]
+pub fn toggle_compact_mode(app: tauri::AppHandle) -> Result<CompactMode, String> {
+    let _perf = perf::track("toggle_compact_mode");
+    let open = match app.get_webview_window(WINDOW_LABEL) {
+        Some(window) => {
+            window.destroy().map_err(|err| err.to_string())?;
+            false
+        }
+        None => {
+            open_window(&app)?;
+            if let Ok(conn) = local_db(&app) {
+                usage::feature_used(&conn, "compact_mode");
+            }
+            true
+        }
+    };
+    let state = CompactMode { open };
+    if let Err(err) = app.emit("compact-mode", state.clone()) {
+        log_error(&err.to_string());
+    }
+    Ok(state)
+}
+
+#[cfg(desktop)]
+fn open_window(app: &tauri::AppHandle) -> Result<(), String> {
+    let mut builder = tauri::WebviewWindowBuilder::new(
+        app,
+        WINDOW_LABEL,
+        tauri::WebviewUrl::App("index.html".into()),
+    )
+    .title("Language Enforcer Review")
+    .inner_size(WIDTH, HEIGHT)
+    .min_inner_size(280.0, 200.0)
+    .always_on_top(true)
+    .skip_taskbar(true);
+    if let Ok(Some(monitor)) = app.primary_monitor() {
+        let scale = monitor.scale_factor();
+        let width = f64::from(monitor.size().width) / scale;
+        builder = builder.position((width - WIDTH - MARGIN).max(0.0), MARGIN);
+    }
+    builder.build().map_err(|err| err.to_string())?;
+    Ok(())
+}
+
+#[cfg(not(desktop))]
+fn open_window(_app: &tauri::AppHandle) -> Result<(), String> {
+    Err("Compact mode is only available on desktop".to_string())
+}
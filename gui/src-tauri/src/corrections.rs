@@ -0,0 +1,197 @@
+//! History of corrections made to words, so a bad fix can be found and
+//! undone later. `apply_correction` and `apply_correction_local` record the
+//! word's text, translation, and alternate answers before and after each
+//! change in the local `word_corrections` table; `revert_correction` puts
+//! the old values back, in Postgres too when the correction went there.
+
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::Serialize;
+use tauri::command;
+
+use crate::{local_db, perf, remote};
+
+const DEFAULT_LIMIT: usize = 20;
+const MAX_LIMIT: usize = 200;
+
+/// The corrected fields of a word at one point in time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WordFields {
+    text: String,
+    translation: Option<String>,
+    /// JSON array, as stored in `words.alt_answers`.
+    alt_answers: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Correction {
+    id: i64,
+    word_id: String,
+    before: WordFields,
+    after: WordFields,
+    /// Also applied to Postgres, so a revert goes there too.
+    synced: bool,
+    applied_at: String,
+    reverted_at: Option<String>,
+}
+
+/// The most recent corrections, newest first, including reverted ones.
+#[command]
+pub fn recent_corrections(
+    app: tauri::AppHandle,
+    limit: Option<usize>,
+) -> Result<Vec<Correction>, String> {
+    let _perf = perf::track("recent_corrections");
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let conn = local_db(&app)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, word_id, old_text, old_translation, old_alt_answers,
+                    new_text, new_translation, new_alt_answers, synced, applied_at, reverted_at
+             FROM word_corrections ORDER BY id DESC LIMIT ?1",
+        )
+        .map_err(|err| err.to_string())?;
+    stmt.query_map(params![limit as i64], correction_from_row)
+        .map_err(|err| err.to_string())?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|err| err.to_string())
+}
+
+/// Restores the word to how it was before correction `id`. Refused when the
+/// word has changed since, so a later edit is never silently overwritten.
+#[command]
+pub fn revert_correction(app: tauri::AppHandle, id: i64) -> Result<Correction, String> {
+    let _perf = perf::track("revert_correction");
+    let conn = local_db(&app)?;
+    let correction = conn
+        .query_row(
+            "SELECT id, word_id, old_text, old_translation, old_alt_answers,
+                    new_text, new_translation, new_alt_answers, synced, applied_at, reverted_at
+             FROM word_corrections WHERE id = ?1",
+            params![id],
+            correction_from_row,
+        )
+        .optional()
+        .map_err(|err| err.to_string())?
+        .ok_or_else(|| format!("Correction {id} not found"))?;
+    if correction.reverted_at.is_some() {
+        return Err(format!("Correction {id} was already reverted"));
+    }
+    let current = snapshot(&conn, &correction.word_id)?
+        .ok_or_else(|| "The corrected word no longer exists".to_string())?;
+    if current != correction.after {
+        return Err(
+            "The word has changed since this correction; revert the newer one first".into(),
+        );
+    }
+
+    let before = &correction.before;
+    if correction.synced {
+        let found = remote::open()?.update_word(
+            &correction.word_id,
+            &remote::WordUpdate {
+                text: Some(&before.text),
+                translation: Some(before.translation.as_deref()),
+                alt_answers: Some(before.alt_answers.as_deref()),
+            },
+        )?;
+        if !found {
+            return Err("Word not found in Postgres".to_string());
+        }
+    }
+    let reverted_at = chrono::Utc::now().to_rfc3339();
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|err| err.to_string())?;
+    tx.execute(
+        "UPDATE words SET text = ?1, translation = ?2, alt_answers = ?3 WHERE id = ?4",
+        params![
+            before.text,
+            before.translation,
+            before.alt_answers,
+            correction.word_id
+        ],
+    )
+    .map_err(|err| err.to_string())?;
+    tx.execute(
+        "UPDATE word_corrections SET reverted_at = ?1 WHERE id = ?2",
+        params![reverted_at, id],
+    )
+    .map_err(|err| err.to_string())?;
+    tx.commit().map_err(|err| err.to_string())?;
+    Ok(Correction {
+        reverted_at: Some(reverted_at),
+        ..correction
+    })
+}
+
+/// The word's corrected fields as stored locally right now.
+pub fn snapshot(conn: &Connection, word_id: &str) -> Result<Option<WordFields>, String> {
+    conn.query_row(
+        "SELECT text, translation, alt_answers FROM words WHERE id = ?1",
+        params![word_id],
+        |row| {
+            Ok(WordFields {
+                text: row.get(0)?,
+                translation: row.get(1)?,
+                alt_answers: row.get(2)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|err| err.to_string())
+}
+
+/// Records a correction by comparing `before` with the word as it is now.
+/// Nothing is recorded when the word was missing or nothing changed.
+pub fn record(
+    conn: &Connection,
+    word_id: &str,
+    before: Option<WordFields>,
+    synced: bool,
+) -> Result<(), String> {
+    let (Some(before), Some(after)) = (before, snapshot(conn, word_id)?) else {
+        return Ok(());
+    };
+    if before == after {
+        return Ok(());
+    }
+    conn.execute(
+        "INSERT INTO word_corrections (word_id, old_text, old_translation, old_alt_answers,
+                                       new_text, new_translation, new_alt_answers, synced,
+                                       applied_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            word_id,
+            before.text,
+            before.translation,
+            before.alt_answers,
+            after.text,
+            after.translation,
+            after.alt_answers,
+            synced,
+            chrono::Utc::now().to_rfc3339()
+        ],
+    )
+    .map_err(|err| err.to_string())?;
+    Ok(())
+}
+
+fn correction_from_row(row: &rusqlite::Row) -> rusqlite::Result<Correction> {
+    Ok(Correction {
+        id: row.get(0)?,
+        word_id: row.get(1)?,
+        before: WordFields {
+            text: row.get(2)?,
+            translation: row.get(3)?,
+            alt_answers: row.get(4)?,
+        },
+        after: WordFields {
+            text: row.get(5)?,
+            translation: row.get(6)?,
+            alt_answers: row.get(7)?,
+        },
+        synced: row.get(8)?,
+        applied_at: row.get(9)?,
+        reverted_at: row.get(10)?,
+    })
+}
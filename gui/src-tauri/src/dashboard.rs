@@ -0,0 +1,161 @@
+//! Numbers for the statistics dashboard, computed in SQL against the local
+//! database. Day series use local dates and include days without activity,
+//! so the frontend can chart them as they are.
+
+use std::collections::HashMap;
+
+use chrono::{Duration, Local, NaiveDate};
+use rusqlite::{Connection, params};
+use serde::Serialize;
+use tauri::command;
+
+use crate::{local_db, perf, usage};
+
+const HISTORY_DAYS: i64 = 90;
+const FORECAST_DAYS: i64 = 30;
+/// Interval from which a card counts as mature rather than young.
+pub(crate) const MATURE_INTERVAL_DAYS: i64 = 21;
+
+#[derive(Debug, Serialize)]
+pub struct Stats {
+    /// The last 90 days, oldest first, ending today.
+    reviews_per_day: Vec<DayCount>,
+    retention_by_grade: Vec<GradeRetention>,
+    /// Mean SM-2 ease of cards reviewed at least once.
+    average_ease: Option<f64>,
+    maturity: Maturity,
+    /// Cards due on each of the next 30 days, starting today; overdue cards
+    /// count towards today.
+    due_forecast: Vec<DayCount>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DayCount {
+    /// `YYYY-MM-DD`.
+    date: String,
+    count: i64,
+}
+
+/// How well cards given `grade` were remembered at their next review.
+#[derive(Debug, Serialize)]
+pub struct GradeRetention {
+    grade: u8,
+    /// Times the grade was given.
+    reviews: i64,
+    /// Of those, how many were followed by another review of the card.
+    followed_up: i64,
+    /// Share of the follow-up reviews graded 3 or higher, 0–1.
+    retention: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Maturity {
+    /// Never reviewed.
+    new: i64,
+    /// Reviewed, but on an interval of a day or less (including relearning
+    /// after a lapse).
+    learning: i64,
+    /// Interval under three weeks.
+    young: i64,
+    /// Interval of three weeks or more.
+    mature: i64,
+}
+
+#[command]
+pub fn stats(app: tauri::AppHandle) -> Result<Stats, String> {
+    let _perf = perf::track("stats");
+    let conn = local_db(&app)?;
+    let stats = load_stats(&conn, Local::now().date_naive()).map_err(|err| err.to_string())?;
+    usage::feature_used(&conn, "stats_dashboard");
+    Ok(stats)
+}
+
+fn load_stats(conn: &Connection, today: NaiveDate) -> rusqlite::Result<Stats> {
+    let first_day = today - Duration::days(HISTORY_DAYS - 1);
+    let mut stmt = conn.prepare(
+        "SELECT date(reviewed_at, 'localtime') AS day, COUNT(*) FROM reviews
+         WHERE day >= ?1
+         GROUP BY day",
+    )?;
+    let reviews = stmt
+        .query_map(params![first_day.to_string()], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?
+        .collect::<rusqlite::Result<HashMap<String, i64>>>()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT grade, COUNT(*), COUNT(next_grade), COALESCE(SUM(next_grade >= 3), 0)
+         FROM (
+             SELECT grade,
+                    LEAD(grade) OVER (PARTITION BY card_id ORDER BY reviewed_at) AS next_grade
+             FROM reviews
+             WHERE grade BETWEEN 0 AND 5
+         )
+         GROUP BY grade
+         ORDER BY grade",
+    )?;
+    let retention_by_grade = stmt
+        .query_map([], |row| {
+            let followed_up: i64 = row.get(2)?;
+            let retained: i64 = row.get(3)?;
+            Ok(GradeRetention {
+                grade: row.get(0)?,
+                reviews: row.get(1)?,
+                followed_up,
+                retention: (followed_up > 0).then(|| retained as f64 / followed_up as f64),
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let average_ease = conn.query_row("SELECT AVG(ease) FROM cards WHERE reps > 0", [], |row| {
+        row.get(0)
+    })?;
+    let maturity = conn.query_row(
+        "SELECT COALESCE(SUM(reps = 0), 0),
+                COALESCE(SUM(reps > 0 AND interval_days <= 1), 0),
+                COALESCE(SUM(reps > 0 AND interval_days > 1 AND interval_days < ?1), 0),
+                COALESCE(SUM(reps > 0 AND interval_days >= ?1), 0)
+         FROM cards",
+        params![MATURE_INTERVAL_DAYS],
+        |row| {
+            Ok(Maturity {
+                new: row.get(0)?,
+                learning: row.get(1)?,
+                young: row.get(2)?,
+                mature: row.get(3)?,
+            })
+        },
+    )?;
+
+    let forecast_end = today + Duration::days(FORECAST_DAYS);
+    let mut stmt = conn.prepare(
+        "SELECT MAX(date(due_at, 'localtime'), ?1) AS day, COUNT(*) FROM cards
+         WHERE date(due_at, 'localtime') < ?2
+         GROUP BY day",
+    )?;
+    let due = stmt
+        .query_map(
+            params![today.to_string(), forecast_end.to_string()],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?
+        .collect::<rusqlite::Result<HashMap<String, i64>>>()?;
+
+    Ok(Stats {
+        reviews_per_day: day_series(&reviews, first_day, HISTORY_DAYS),
+        retention_by_grade,
+        average_ease,
+        maturity,
+        due_forecast: day_series(&due, today, FORECAST_DAYS),
+    })
+}
+
+/// `days` consecutive days from `start`, with zero for days not in `counts`.
+fn day_series(counts: &HashMap<String, i64>, start: NaiveDate, days: i64) -> Vec<DayCount> {
+    (0..days)
+        .map(|offset| {
+            let date = (start + Duration::days(offset)).to_string();
+            let count = counts.get(&date).copied().unwrap_or(0);
+            DayCount { date, count }
+        })
+        .collect()
+}
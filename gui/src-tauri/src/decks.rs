@@ -0,0 +1,83 @@
+//! Decks for the session filter: named sets of words, such as one textbook
+//! each, that are studied on their own. The queries live in `le_db::decks`
+//! so the TUI sees the same decks.
+
+use le_db::decks;
+use serde::Serialize;
+use tauri::command;
+use uuid::Uuid;
+
+use crate::{local_db, perf, usage};
+
+#[derive(Debug, Serialize)]
+pub struct DeckSummary {
+    id: String,
+    name: String,
+    words: usize,
+}
+
+impl From<le_db::Deck> for DeckSummary {
+    fn from(deck: le_db::Deck) -> Self {
+        Self {
+            id: deck.id.to_string(),
+            name: deck.name,
+            words: deck.words,
+        }
+    }
+}
+
+/// Every deck with its word count, by name.
+#[command]
+pub fn list_decks(app: tauri::AppHandle) -> Result<Vec<DeckSummary>, String> {
+    let _perf = perf::track("list_decks");
+    let conn = local_db(&app)?;
+    let decks = decks::list_decks(&conn).map_err(|err| err.to_string())?;
+    Ok(decks.into_iter().map(DeckSummary::from).collect())
+}
+
+#[command]
+pub fn create_deck(app: tauri::AppHandle, name: String) -> Result<DeckSummary, String> {
+    let _perf = perf::track("create_deck");
+    let conn = local_db(&app)?;
+    let deck = decks::create_deck(&conn, &name).map_err(|err| err.to_string())?;
+    usage::feature_used(&conn, "decks");
+    Ok(deck.into())
+}
+
+#[command]
+pub fn rename_deck(app: tauri::AppHandle, deck_id: String, name: String) -> Result<(), String> {
+    let _perf = perf::track("rename_deck");
+    let conn = local_db(&app)?;
+    decks::rename_deck(&conn, parse_id(&deck_id)?, &name).map_err(|err| err.to_string())
+}
+
+/// Deletes a deck; its words stay, outside every deck.
+#[command]
+pub fn delete_deck(app: tauri::AppHandle, deck_id: String) -> Result<(), String> {
+    let _perf = perf::track("delete_deck");
+    let conn = local_db(&app)?;
+    decks::delete_deck(&conn, parse_id(&deck_id)?).map_err(|err| err.to_string())
+}
+
+/// Moves words into `deck_id`, or out of every deck when it is `None`.
+#[command]
+pub fn move_words_to_deck(
+    app: tauri::AppHandle,
+    word_ids: Vec<String>,
+    deck_id: Option<String>,
+) -> Result<(), String> {
+    let _perf = perf::track("move_words_to_deck");
+    let conn = local_db(&app)?;
+    let word_ids = word_ids
+        .iter()
+        .map(|id| parse_id(id))
+        .collect::<Result<Vec<_>, _>>()?;
+    let deck_id = deck_id.as_deref().map(parse_id).transpose()?;
+    decks::move_to_deck(&conn, &word_ids, deck_id).map_err(|err| err.to_string())?;
+    usage::feature_used(&conn, "decks");
+    Ok(())
+}
+
+fn parse_id(id: &str) -> Result<Uuid, String> {
+    Uuid::parse_str(id).map_err(|err| format!("Invalid id {id}: {err}"))
+}
@@ -0,0 +1,55 @@
+//! The translation glossary: preferred translations for terms the
+//! translation API renders inconsistently. The queries live in
+//! `le_db::glossary` so the TUI, which applies the entries over every
+//! machine translation in its add and import flows, sees the same ones.
+
+use chrono::Utc;
+use le_core::Language;
+use le_core::glossary::GlossaryEntry;
+use le_db::glossary;
+use tauri::command;
+
+use crate::{local_db, perf, usage};
+
+/// Every entry, by language and term.
+#[command]
+pub fn list_glossary(app: tauri::AppHandle) -> Result<Vec<GlossaryEntry>, String> {
+    let _perf = perf::track("list_glossary");
+    let conn = local_db(&app)?;
+    glossary::list_glossary(&conn).map_err(|err| err.to_string())
+}
+
+/// Adds the entry, or replaces the one for the same term in `language`
+/// (compared without case or surrounding punctuation).
+#[command]
+pub fn set_glossary_entry(
+    app: tauri::AppHandle,
+    term: String,
+    language: String,
+    translation: String,
+) -> Result<GlossaryEntry, String> {
+    let _perf = perf::track("set_glossary_entry");
+    let entry = GlossaryEntry {
+        term: term.trim().to_string(),
+        language: Language::new(&language),
+        translation: translation.trim().to_string(),
+        updated_at: Utc::now(),
+    };
+    let conn = local_db(&app)?;
+    glossary::save_glossary_entry(&conn, &entry).map_err(|err| err.to_string())?;
+    usage::feature_used(&conn, "glossary");
+    Ok(entry)
+}
+
+/// Whether there was an entry to delete.
+#[command]
+pub fn delete_glossary_entry(
+    app: tauri::AppHandle,
+    term: String,
+    language: String,
+) -> Result<bool, String> {
+    let _perf = perf::track("delete_glossary_entry");
+    let conn = local_db(&app)?;
+    glossary::delete_glossary_entry(&conn, &Language::new(&language), &term)
+        .map_err(|err| err.to_string())
+}
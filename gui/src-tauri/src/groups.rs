@@ -0,0 +1,159 @@
+//! Per-chapter group statistics and group cleanup. Groups only exist as the
+//! `group_name` of their words, so a typo during import ("Vocabulair" next to
+//! "Vocabulaire") or a blank heading leaves a stray group behind. The report
+//! points those out, and `merge_groups` moves their words in one transaction.
+
+use std::collections::BTreeMap;
+
+use rusqlite::{Connection, params};
+use serde::{Deserialize, Serialize};
+use tauri::command;
+
+use crate::{local_db, log_error, perf, usage};
+
+/// Names this many edits apart or closer are flagged as likely typos of
+/// each other, provided both are longer than the distance itself.
+const TYPO_DISTANCE: usize = 2;
+
+#[derive(Debug, Serialize)]
+pub struct GroupReport {
+    chapter: Option<String>,
+    /// `None` for words saved without a group.
+    group: Option<String>,
+    words: i64,
+    last_added_at: Option<String>,
+    /// The name is empty or only whitespace.
+    blank: bool,
+    /// Other groups of the same chapter whose name is probably the same one
+    /// mistyped, such as "Vocabulair" for "Vocabulaire".
+    similar: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MergeGroupsInput {
+    /// `None` targets words without a chapter.
+    chapter: Option<String>,
+    /// Groups whose words are moved. `""` stands for words with a blank
+    /// group or none.
+    from: Vec<String>,
+    /// The group they end up in; `None` deletes the groups and leaves their
+    /// words ungrouped.
+    into: Option<String>,
+}
+
+/// Groups with their word counts and the date the last word was added,
+/// ordered by chapter and name. `chapter` limits the report to one chapter.
+#[command]
+pub fn groups_report(
+    app: tauri::AppHandle,
+    chapter: Option<String>,
+) -> Result<Vec<GroupReport>, String> {
+    let _perf = perf::track("groups_report");
+    let conn = local_db(&app)?;
+    let mut groups = load_groups(&conn, chapter.as_deref()).map_err(|err| err.to_string())?;
+    let mut by_chapter: BTreeMap<Option<String>, Vec<String>> = BTreeMap::new();
+    for group in &groups {
+        if let Some(name) = &group.group {
+            by_chapter
+                .entry(group.chapter.clone())
+                .or_default()
+                .push(name.clone());
+        }
+    }
+    for group in &mut groups {
+        let (Some(name), Some(others)) = (&group.group, by_chapter.get(&group.chapter)) else {
+            continue;
+        };
+        group.similar = others
+            .iter()
+            .filter(|other| *other != name && likely_typo(name, other))
+            .cloned()
+            .collect();
+    }
+    Ok(groups)
+}
+
+/// Moves every word of the `from` groups in one chapter into `into`, or out
+/// of any group when `into` is `None`. Returns how many words changed.
+#[command]
+pub fn merge_groups(app: tauri::AppHandle, input: MergeGroupsInput) -> Result<usize, String> {
+    let _perf = perf::track("merge_groups");
+    let into = input
+        .into
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty());
+    if input.from.is_empty() {
+        return Err("Choose the groups to merge".to_string());
+    }
+    let conn = local_db(&app)?;
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|err| err.to_string())?;
+    let mut changed = 0;
+    for from in &input.from {
+        if Some(from.as_str()) == into {
+            continue;
+        }
+        changed += tx
+            .execute(
+                "UPDATE words SET group_name = ?1
+                 WHERE chapter IS ?2 AND TRIM(COALESCE(group_name, '')) = ?3",
+                params![into, input.chapter, from.trim()],
+            )
+            .map_err(|err| {
+                let message = format!("merge_groups: update '{from}' failed: {err}");
+                log_error(&message);
+                message
+            })?;
+    }
+    tx.commit().map_err(|err| err.to_string())?;
+    usage::feature_used(&conn, "merge_groups");
+    Ok(changed)
+}
+
+fn load_groups(conn: &Connection, chapter: Option<&str>) -> rusqlite::Result<Vec<GroupReport>> {
+    let mut stmt = conn.prepare(
+        "SELECT chapter, group_name, COUNT(*), MAX(created_at)
+         FROM words
+         WHERE ?1 IS NULL OR chapter = ?1
+         GROUP BY chapter, group_name
+         ORDER BY chapter, group_name",
+    )?;
+    stmt.query_map(params![chapter], |row| {
+        let group: Option<String> = row.get(1)?;
+        Ok(GroupReport {
+            chapter: row.get(0)?,
+            blank: group.as_deref().is_some_and(|name| name.trim().is_empty()),
+            group,
+            words: row.get(2)?,
+            last_added_at: row.get(3)?,
+            similar: Vec::new(),
+        })
+    })?
+    .collect()
+}
+
+/// Whether two group names differ by no more than [`TYPO_DISTANCE`] edits,
+/// ignoring case and surrounding whitespace.
+fn likely_typo(a: &str, b: &str) -> bool {
+    let a: Vec<char> = a.trim().to_lowercase().chars().collect();
+    let b: Vec<char> = b.trim().to_lowercase().chars().collect();
+    if a.len().min(b.len()) <= TYPO_DISTANCE {
+        return false;
+    }
+    edit_distance(&a, &b) <= TYPO_DISTANCE
+}
+
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
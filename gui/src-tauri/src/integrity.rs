@@ -0,0 +1,147 @@
+//! Integrity checks for the local database. Besides SQLite's own consistency
+//! check, this audits review and card timestamps: older versions wrote a mix
+//! of local offsets, `Z` suffixes, and bare `YYYY-MM-DD HH:MM:SS` values,
+//! which break the string comparisons the scheduler relies on. With `repair`
+//! set, every value that can be read is rewritten as RFC 3339 UTC; values
+//! that cannot be read are left alone and listed.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use rusqlite::{Connection, params};
+use serde::Serialize;
+use tauri::command;
+
+use crate::{local_db, log_error, perf, usage};
+
+/// Timestamp columns audited, as (table, column).
+const TIMESTAMP_COLUMNS: [(&str, &str); 2] = [("reviews", "reviewed_at"), ("cards", "due_at")];
+/// Formats without an offset found in older databases; read as UTC.
+const NAIVE_FORMATS: [&str; 4] = [
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M",
+];
+
+#[derive(Debug, Serialize)]
+pub struct IntegrityReport {
+    /// Problems reported by `PRAGMA quick_check`; empty when healthy.
+    sqlite: Vec<String>,
+    timestamps: TimestampAudit,
+    checked_at: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct TimestampAudit {
+    checked: usize,
+    /// Readable values not already in RFC 3339 UTC.
+    non_utc: usize,
+    repaired: usize,
+    unfixable: Vec<BadTimestamp>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BadTimestamp {
+    table: String,
+    column: String,
+    id: String,
+    value: String,
+}
+
+/// Runs the checks and, when `repair` is set, normalizes the timestamps it
+/// can read in a single transaction.
+#[command]
+pub fn check_integrity(app: tauri::AppHandle, repair: bool) -> Result<IntegrityReport, String> {
+    let _perf = perf::track("check_integrity");
+    let mut conn = local_db(&app)?;
+    let sqlite = quick_check(&conn)?;
+    let timestamps = audit_timestamps(&mut conn, repair).map_err(|err| {
+        let message = format!("check_integrity: timestamp audit failed: {err}");
+        log_error(&message);
+        message
+    })?;
+    if repair {
+        usage::feature_used(&conn, "integrity_repair");
+    }
+    Ok(IntegrityReport {
+        sqlite,
+        timestamps,
+        checked_at: Utc::now().to_rfc3339(),
+    })
+}
+
+fn quick_check(conn: &Connection) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare("PRAGMA quick_check")
+        .map_err(|err| err.to_string())?;
+    let messages = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|err| err.to_string())?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|err| err.to_string())?;
+    Ok(messages
+        .into_iter()
+        .filter(|message| message != "ok")
+        .collect())
+}
+
+fn audit_timestamps(conn: &mut Connection, repair: bool) -> rusqlite::Result<TimestampAudit> {
+    let tx = conn.transaction()?;
+    let mut audit = TimestampAudit::default();
+    for (table, column) in TIMESTAMP_COLUMNS {
+        let rows = {
+            let mut stmt = tx.prepare(&format!("SELECT id, {column} FROM {table}"))?;
+            stmt.query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?
+        };
+        for (id, value) in rows {
+            audit.checked += 1;
+            let Some(parsed) = parse_timestamp(&value) else {
+                audit.unfixable.push(BadTimestamp {
+                    table: table.to_string(),
+                    column: column.to_string(),
+                    id,
+                    value,
+                });
+                continue;
+            };
+            let normalized = parsed.to_rfc3339();
+            if normalized == value {
+                continue;
+            }
+            audit.non_utc += 1;
+            if repair {
+                tx.execute(
+                    &format!("UPDATE {table} SET {column} = ?1 WHERE id = ?2"),
+                    params![normalized, id],
+                )?;
+                audit.repaired += 1;
+            }
+        }
+    }
+    tx.commit()?;
+    Ok(audit)
+}
+
+/// Reads RFC 3339 with any offset, the naive formats above, a bare date, or
+/// Unix seconds.
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(parsed) = DateTime::parse_from_rfc3339(value) {
+        return Some(parsed.with_timezone(&Utc));
+    }
+    if let Some(parsed) = NAIVE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+    {
+        return Some(parsed.and_utc());
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return date.and_hms_opt(0, 0, 0).map(|parsed| parsed.and_utc());
+    }
+    value
+        .parse::<i64>()
+        .ok()
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+}
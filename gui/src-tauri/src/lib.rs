@@ -1,22 +1,71 @@
-use std::path::PathBuf;
-
-use chrono::{DateTime, Duration, Utc};
-use le_core::{Card, default_new_card, schedule_sm2};
-use native_tls::TlsConnector;
-use postgres::Client;
-use postgres_native_tls::MakeTlsConnector;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use le_client::{
+    CefrLevel, SENTENCE_MAX_WORDS_RANGE, SettingEntry, SettingsPayload, TTS_PITCH_RANGE,
+    TTS_RATE_RANGE, connectivity,
+};
+use le_core::{
+    CARD_SETTINGS_KEY, Card, CardOrder, CardSettings, CardType, EntryKind, FONT_SCALE_RANGE, Grade,
+    Language, LanguagePair, SCHEDULER_KEY, ScheduleExplanation, SchedulerConfig, SessionConfig,
+    SessionProgress, SourceKind, UI_PREFS_KEY, UiPrefs, WordSource,
+    cloze::make_cloze,
+    default_new_card, fnv1a, grade_from_sentence_score, learning, rules,
+    session::{self, Session, SessionBuilder, SessionSummary},
+    stats::{self, CurvePoint},
+};
+use le_db::session::{self as session_queries, CardCandidate, CardKind, CardScope};
 use rand::{Rng, seq::SliceRandom};
 use rusqlite::{Connection, OptionalExtension, params};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::sync::Mutex;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Mutex, MutexGuard, TryLockError};
 use tauri::Emitter;
 use tauri::path::BaseDirectory;
 use tauri::{Manager, State, command};
 use uuid::Uuid;
 
+mod answer_check;
+mod audio;
+mod bundle;
+mod card_state;
+mod cloze;
+mod compact;
+mod corrections;
+mod dashboard;
+mod decks;
+mod glossary;
+mod groups;
+mod integrity;
+mod maintenance;
+mod onboarding;
+mod perf;
+#[cfg(feature = "remote-sync")]
+mod pg_remote;
+mod pronunciation;
+mod push;
+mod quiz;
+mod recovery;
+mod refresh;
+mod remote;
+mod report;
+mod search;
+mod seed;
+mod shortcuts;
+mod social;
+mod suspend;
+mod sync;
+mod usage;
+
 #[derive(Debug, Serialize)]
 struct ReviewItem {
     card_id: String,
+    /// The card's scheduling state when it was loaded; pass it back to
+    /// `grade_card` so a card changed by a sync in the meantime is not
+    /// graded from outdated values.
+    revision: String,
     word_id: String,
     text: String,
     translation: Option<String>,
@@ -25,12 +74,100 @@ struct ReviewItem {
     chapter: Option<String>,
     group: Option<String>,
     notes: Option<String>,
+    source: Option<WordSource>,
+    alt_answers: Vec<String>,
+    kind: EntryKind,
+    /// How the card is shown. A cloze card none of whose sentences contains
+    /// the word any more is shown as a basic one.
+    card_type: CardType,
+    /// What the card shows and asks for: the text and its translation on a
+    /// basic card, the other way round on a reverse one, and the sentence
+    /// with the word blanked and the missing words on a cloze one; answer
+    /// those with `grade_cloze_card`.
+    prompt: String,
+    answer: Option<String>,
+    /// Whether the word also has a card in the other direction, in which
+    /// case the frontend should not flip this one at random.
+    paired: bool,
+    /// One of the word's saved example sentences, if any, rotated so each
+    /// review shows the one shown least recently. Left out on cloze cards,
+    /// whose prompt is the sentence.
+    example: Option<SentencePayload>,
+}
+
+/// An example sentence with the reviewed word located in it, so the
+/// frontend can bold or blank it without matching inflections itself.
+#[derive(Debug, Serialize)]
+struct SentencePayload {
+    text: String,
+    translation: Option<String>,
+    /// Where the word occurs in `text`, as UTF-16 offsets for `String.slice`.
+    spans: Vec<TextSpan>,
+}
+
+#[derive(Debug, Serialize)]
+struct TextSpan {
+    start: usize,
+    end: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExampleInput {
+    word_id: String,
+    sentence: String,
+    translation: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 struct GradeInput {
+    card_id: String,
+    /// Rejected when outside 0–5, so a frontend bug surfaces as an error
+    /// instead of a silently clamped grade.
+    grade: Grade,
+    /// Client-generated id for this review. Repeating a call with the same id
+    /// is a no-op, so a retried invoke never grades the card twice.
+    review_id: Option<String>,
+    /// `ReviewItem::revision` of the card as shown. When it no longer matches,
+    /// the grade is rejected with [`STALE_CARD`].
+    revision: Option<String>,
+}
+
+/// Returned by `grade_card` when the card's state changed after it was
+/// shown, usually because a sync replaced it. Reload the card with
+/// `current_card` and grade it again.
+const STALE_CARD: &str = "Card changed since it was shown; reload it and grade again";
+
+/// One review performed outside the app (a paper session, another app),
+/// replayed by `record_reviews_batch`.
+#[derive(Debug, Deserialize)]
+struct ReviewRecord {
     card_id: String,
     grade: u8,
+    /// RFC 3339 time the review happened.
+    reviewed_at: String,
+    /// Stable id so re-importing the same file does not grade twice.
+    review_id: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct ReviewBatchResult {
+    applied: usize,
+    /// Already imported (same `review_id`).
+    duplicates: usize,
+    /// Older than the card's latest recorded review: kept in the history but
+    /// not rescheduled, since the card's state already reflects later reviews.
+    stale: usize,
+    unknown_cards: Vec<String>,
+}
+
+/// Grades a sentence card from its grade-sentence score (1-10) instead of a
+/// self-assessed grade.
+#[derive(Debug, Deserialize)]
+struct SentenceGradeInput {
+    card_id: String,
+    score: u8,
+    review_id: Option<String>,
+    revision: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -48,6 +185,9 @@ struct CorrectionInput {
     word_id: String,
     text: Option<String>,
     translation: Option<String>,
+    /// Replaces the word's alternate answers when present.
+    #[serde(default)]
+    alt_answers: Option<Vec<String>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -84,6 +224,23 @@ struct WordRow {
     group_name: Option<String>,
     notes: Option<String>,
     created_at: String,
+    #[serde(default)]
+    source_type: Option<String>,
+    #[serde(default)]
+    source_name: Option<String>,
+    #[serde(default)]
+    source_page: Option<String>,
+    #[serde(default)]
+    captured_at: Option<String>,
+    #[serde(default)]
+    source_region: Option<String>,
+    /// JSON array of alternate accepted answers.
+    #[serde(default)]
+    alt_answers: Option<String>,
+    #[serde(default)]
+    kind: Option<String>,
+    #[serde(default)]
+    deck_id: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -102,6 +259,10 @@ struct CardRow {
     ease: f64,
     reps: i32,
     lapses: i32,
+    #[serde(default)]
+    deck_id: Option<String>,
+    #[serde(default)]
+    card_type: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -112,30 +273,368 @@ struct ReviewRow {
     reviewed_at: String,
 }
 
+#[derive(Debug, Deserialize)]
+struct TagRow {
+    id: String,
+    name: String,
+    created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeckRow {
+    id: String,
+    name: String,
+    created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct WordTagRow {
+    word_id: String,
+    tag_id: String,
+}
+
 #[derive(Debug, Deserialize)]
 struct DataApiSnapshot {
     words: Vec<WordRow>,
     cards: Vec<CardRow>,
     reviews: Vec<ReviewRow>,
     concepts: Vec<ConceptRow>,
+    /// `None` when the Data API has no tag tables yet; local tags are kept.
+    #[serde(default)]
+    tags: Option<Vec<TagRow>>,
+    #[serde(default)]
+    word_tags: Option<Vec<WordTagRow>>,
+    /// `None` when the Data API has no `decks` table yet; local decks are kept.
+    #[serde(default)]
+    decks: Option<Vec<DeckRow>>,
+}
+
+#[derive(Debug, Serialize)]
+struct WordSummary {
+    id: String,
+    text: String,
+    translation: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct TranslationMismatch {
+    word_id: String,
+    text: String,
+    local: Option<String>,
+    remote: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct CardState {
+    due_at: String,
+    interval_days: i32,
+    ease: f64,
+    reps: i32,
+    lapses: i32,
+}
+
+#[derive(Debug, Serialize)]
+struct CardMismatch {
+    card_id: String,
+    word_id: String,
+    local: CardState,
+    remote: CardState,
+}
+
+/// Read-only comparison of the local mirror against Postgres or a data-API
+/// snapshot. "Remote" is whichever side `diff_against_remote` was given.
+#[derive(Debug, Default, Serialize)]
+struct WordlistDiff {
+    local_only_words: Vec<WordSummary>,
+    remote_only_words: Vec<WordSummary>,
+    translation_mismatches: Vec<TranslationMismatch>,
+    local_only_cards: Vec<String>,
+    remote_only_cards: Vec<String>,
+    card_mismatches: Vec<CardMismatch>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+struct SessionFilter {
+    /// Only words in this deck, by id.
+    #[serde(default)]
+    deck: Option<String>,
+    chapter: Option<String>,
+    group: Option<String>,
+    /// Only words carrying this tag, such as "verbs" or "exam-week-3".
+    #[serde(default)]
+    tag: Option<String>,
+}
+
+impl SessionFilter {
+    /// The tag as stored: trimmed and lowercased, `None` when blank.
+    fn tag_name(&self) -> Option<String> {
+        self.tag
+            .as_deref()
+            .map(|tag| tag.trim().to_lowercase())
+            .filter(|tag| !tag.is_empty())
+    }
+
+    fn scope(&self) -> CardScope<'_> {
+        CardScope {
+            deck: self.deck.as_deref(),
+            chapter: self.chapter.as_deref(),
+            group: self.group.as_deref(),
+            tag: self.tag.as_deref(),
+        }
+    }
+}
+
+/// How `start_session` orders the selected cards. `Interleaved` spreads
+/// chapters apart so consecutive cards rarely share one.
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum QueueStrategy {
+    #[default]
+    Interleaved,
+    Weighted,
+}
+
+#[derive(Debug, Serialize)]
+struct SessionPreview {
+    total: usize,
+    due: usize,
+    new: usize,
+    learning: usize,
+    chapters: Vec<String>,
+    estimated_minutes: u64,
+    warm_up: usize,
+}
+
+/// Deck totals and today's progress for the header. "Today" starts at local
+/// midnight.
+#[derive(Debug, Serialize)]
+struct ReviewCounts {
+    total: usize,
+    due: usize,
+    reviewed_today: usize,
+    /// Cards reviewed for the first time today.
+    new_today: usize,
+    /// New cards still allowed today; `None` without a `max_new_cards` cap.
+    new_left_today: Option<usize>,
+}
+
+/// Session-building options stored under the `session` setting.
+/// `warm_up_cards` caps how many cards failed in the previous session are
+/// served first, whether or not they are due yet. `max_new_cards` caps how
+/// many never-reviewed cards sessions introduce per local calendar day.
+/// `stop_after_correct` ends a session once that many answers were correct,
+/// unless `start_session` is given its own goal.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+struct SessionSettings {
+    #[serde(default)]
+    warm_up_cards: usize,
+    #[serde(default)]
+    stop_after_correct: Option<usize>,
+    #[serde(default)]
+    fatigue: FatigueMode,
+    /// New cards per day, counted in `daily_stats` as they are graded.
+    #[serde(default)]
+    max_new_cards: Option<usize>,
+    /// Sessions stop serving cards after this long.
+    #[serde(default)]
+    max_minutes: Option<u64>,
+    /// Where new cards go among the reviews.
+    #[serde(default)]
+    order: CardOrder,
+}
+
+/// Background refresh cadence and auth server override. An interval of 0
+/// turns the timer off; no `server_url` means the build-time default. `push`
+/// applies Postgres changes as they happen (see `push`).
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+struct SyncSettings {
+    #[serde(default)]
+    interval_minutes: u32,
+    #[serde(default)]
+    server_url: Option<String>,
+    #[serde(default)]
+    push: bool,
+}
+
+/// Difficulty of AI-generated example sentences, stored under the synced
+/// `ai` setting so the level follows the learner to every device. No
+/// `max_words` means the level's default length.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+struct AiSettings {
+    #[serde(default)]
+    sentence_level: CefrLevel,
+    #[serde(default)]
+    max_words: Option<usize>,
+}
+
+/// Unsaved edits from the settings panel; only the groups present are checked.
+#[derive(Debug, Default, Deserialize)]
+struct SettingsDraft {
+    #[serde(default)]
+    session: Option<SessionSettings>,
+    #[serde(default)]
+    sync: Option<SyncSettings>,
+    #[serde(default)]
+    tts: Option<HashMap<String, VoiceSettings>>,
+    #[serde(default)]
+    audio_cues: Option<AudioCueSettings>,
+    #[serde(default)]
+    ai: Option<AiSettings>,
+    #[serde(default)]
+    ui: Option<UiPrefs>,
+    #[serde(default)]
+    scheduler: Option<SchedulerConfig>,
+}
+
+#[derive(Debug, Serialize)]
+struct SettingIssue {
+    key: String,
+    field: String,
+    message: String,
+}
+
+impl SettingIssue {
+    fn new(key: &str, field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            key: key.to_string(),
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Emitted as `settings-changed` whenever a setting is stored, so running
+/// pieces (the sync timer, session defaults) reload without a restart.
+#[derive(Debug, Clone, Serialize)]
+struct SettingsChanged {
+    key: String,
+}
+
+/// What `next_due_card` does once accuracy and answer speed both drop off
+/// within a session. `Suggest` only emits `fatigue-detected`; `Enforce` also
+/// ends the session.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum FatigueMode {
+    Off,
+    #[default]
+    Suggest,
+    Enforce,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct FatigueEvent {
+    accuracy: f64,
+    baseline_accuracy: f64,
+    latency_ms: u64,
+    baseline_latency_ms: u64,
+    enforced: bool,
+}
+
+/// Emitted as `card-served` when `next_due_card` moves the session on;
+/// `card_id` is `None` once the queue is empty.
+#[derive(Debug, Clone, Serialize)]
+struct CardServed {
+    card_id: Option<String>,
+}
+
+/// Text-to-speech options for one language, stored in the `tts` setting
+/// keyed by language name. `rate` and `pitch` are multipliers of the
+/// provider's default; `voice` is a name from `list_voices`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct VoiceSettings {
+    voice: Option<String>,
+    #[serde(default = "default_voice_multiplier")]
+    rate: f32,
+    #[serde(default = "default_voice_multiplier")]
+    pitch: f32,
+}
+
+impl Default for VoiceSettings {
+    fn default() -> Self {
+        Self {
+            voice: None,
+            rate: default_voice_multiplier(),
+            pitch: default_voice_multiplier(),
+        }
+    }
+}
+
+fn default_voice_multiplier() -> f32 {
+    1.0
+}
+
+#[derive(Debug, Serialize)]
+struct VoiceInfo {
+    name: String,
+    locale: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ReviewSample {
+    correct: bool,
+    latency_ms: u64,
+}
+
+/// Sound files played for backend-emitted `audio-cue` events. Unset cues
+/// are not emitted.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+struct AudioCueSettings {
+    correct: Option<String>,
+    wrong: Option<String>,
+    session_complete: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum AudioCue {
+    Correct,
+    Wrong,
+    SessionComplete,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct AudioCueEvent {
+    cue: AudioCue,
+    path: String,
 }
 
 #[derive(Default)]
 struct ReviewState {
-    queue: Vec<String>,
+    /// Card ids; `next_due_card` serves them and grades are counted here.
+    session: Session<String>,
     session_limit: usize,
+    samples: Vec<ReviewSample>,
+    fatigue_reported: bool,
 }
 
 const BATCH_SIZE: usize = 10;
 const MASTERED_EASE: f64 = 3.8;
 const MASTERED_REPS: i32 = 3;
 const MASTERED_RATIO: f64 = 0.75;
-
-struct CardCandidate {
-    id: String,
-    batch_id: i32,
-    weight: f64,
-}
+const ICS_FORECAST_DAYS: i64 = 30;
+/// Reviews in each of the baseline (session start) and rolling windows.
+const FATIGUE_WINDOW: usize = 8;
+const FATIGUE_ACCURACY_DROP: f64 = 0.2;
+const FATIGUE_LATENCY_RATIO: f64 = 1.5;
+/// Longer answers are counted at this cap so a break does not read as fatigue.
+const FATIGUE_MAX_LATENCY_MS: u64 = 60_000;
+/// Settings that only make sense on this machine (file paths, maintenance
+/// runs, wizard progress, a recovery plan for this device's due dates,
+/// hardware keys, usage metrics) and never sync.
+const LOCAL_ONLY_SETTINGS: [&str; 6] = [
+    "audio_cues",
+    maintenance::SETTINGS_KEY,
+    onboarding::SETTINGS_KEY,
+    recovery::SETTINGS_KEY,
+    shortcuts::SETTINGS_KEY,
+    usage::SETTINGS_KEY,
+];
+const AUDIO_CUE_EXTENSIONS: [&str; 5] = ["wav", "mp3", "ogg", "flac", "m4a"];
+const MAX_WARM_UP_CARDS: usize = 50;
+const SYNC_INTERVAL_RANGE: (u32, u32) = (5, 24 * 60);
+/// Grades kept in `card_history` for `undo_grade`.
+const UNDO_HISTORY: i64 = 100;
 
 fn find_seed_db(app: &tauri::AppHandle) -> Option<PathBuf> {
     let candidates = [
@@ -180,59 +679,232 @@ fn app_db_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
     Ok(db_path)
 }
 
+/// The local SQLite mirror, opened and schema-checked by the first command
+/// that needs it and shared by every command after that.
+#[derive(Default)]
+struct LocalDb(Mutex<Option<Connection>>);
+
+/// Exclusive use of the shared connection for the rest of a command.
+struct DbGuard<'a>(MutexGuard<'a, Option<Connection>>);
+
+impl Deref for DbGuard<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.0
+            .as_ref()
+            .expect("local_db opens the connection before handing it out")
+    }
+}
+
+impl DerefMut for DbGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Connection {
+        self.0
+            .as_mut()
+            .expect("local_db opens the connection before handing it out")
+    }
+}
+
+fn local_db(app: &tauri::AppHandle) -> Result<DbGuard<'_>, String> {
+    // A panic mid-command leaves nothing half-written: open transactions
+    // roll back when dropped, so a poisoned lock is safe to reuse.
+    let mut guard = app
+        .state::<LocalDb>()
+        .inner()
+        .0
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if guard.is_none() {
+        let db_path = app_db_path(app)?;
+        *guard = Some(open_db(&db_path).map_err(|err| err.to_string())?);
+    }
+    Ok(DbGuard(guard))
+}
+
+/// Like [`local_db`], but `None` instead of waiting while another command
+/// holds the connection.
+fn try_local_db(app: &tauri::AppHandle) -> Result<Option<DbGuard<'_>>, String> {
+    let mut guard = match app.state::<LocalDb>().inner().0.try_lock() {
+        Ok(guard) => guard,
+        Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+        Err(TryLockError::WouldBlock) => return Ok(None),
+    };
+    if guard.is_none() {
+        let db_path = app_db_path(app)?;
+        *guard = Some(open_db(&db_path).map_err(|err| err.to_string())?);
+    }
+    Ok(Some(DbGuard(guard)))
+}
+
+/// Runs `work` on Tauri's blocking pool. The `le_client` and `postgres`
+/// clients are blocking and start their own runtime, which panics on the
+/// async runtime's worker threads, so async commands that use them hand the
+/// work off here.
+async fn run_blocking<T, F>(work: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, String> + Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(work)
+        .await
+        .map_err(|err| err.to_string())?
+}
+
 fn open_db(path: &PathBuf) -> rusqlite::Result<Connection> {
-    let conn = Connection::open(path)?;
-    conn.execute_batch(
-        "CREATE TABLE IF NOT EXISTS words (
-            id TEXT PRIMARY KEY,
-            text TEXT NOT NULL,
-            language TEXT NOT NULL,
-            translation TEXT,
-            chapter TEXT,
-            group_name TEXT,
-            notes TEXT,
-            created_at TEXT NOT NULL
-        );
-        CREATE TABLE IF NOT EXISTS cards (
-            id TEXT PRIMARY KEY,
-            word_id TEXT NOT NULL,
-            due_at TEXT NOT NULL,
-            interval_days INTEGER NOT NULL,
-            ease REAL NOT NULL,
-            reps INTEGER NOT NULL,
-            lapses INTEGER NOT NULL,
-            seen_count INTEGER NOT NULL DEFAULT 0,
-            FOREIGN KEY(word_id) REFERENCES words(id)
-        );
-        CREATE TABLE IF NOT EXISTS reviews (
-            id TEXT PRIMARY KEY,
-            card_id TEXT NOT NULL,
-            grade INTEGER NOT NULL,
-            reviewed_at TEXT NOT NULL,
-            FOREIGN KEY(card_id) REFERENCES cards(id)
-        );
-        CREATE TABLE IF NOT EXISTS concepts (
-            id TEXT PRIMARY KEY,
-            name TEXT NOT NULL UNIQUE,
-            created_at TEXT NOT NULL
-        );
-        ",
-    )?;
-    ensure_seen_count(&conn)?;
-    ensure_batch_schema(&conn)?;
+    let mut conn = Connection::open(path)?;
+    conn.profile(Some(perf::record_sql));
+    le_db::migrate_sqlite(&conn)?;
     Ok(conn)
 }
 
-fn postgres_url() -> Result<String, String> {
-    std::env::var("DATABASE_URL")
-        .map_err(|_| "DATABASE_URL is required for Postgres sync".to_string())
+fn get_setting<T: DeserializeOwned>(conn: &Connection, key: &str) -> Result<Option<T>, String> {
+    let value: Option<String> = conn
+        .query_row(
+            "SELECT value FROM settings WHERE key = ?1",
+            params![key],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|err| err.to_string())?;
+    match value {
+        Some(value) => serde_json::from_str(&value)
+            .map(Some)
+            .map_err(|err| format!("Invalid setting '{key}': {err}")),
+        None => Ok(None),
+    }
+}
+
+fn set_setting<T: Serialize>(conn: &Connection, key: &str, value: &T) -> Result<(), String> {
+    let value = serde_json::to_string(value).map_err(|err| err.to_string())?;
+    conn.execute(
+        "INSERT INTO settings (key, value, updated_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+        params![key, value, Utc::now().to_rfc3339()],
+    )
+    .map_err(|err| err.to_string())?;
+    Ok(())
+}
+
+fn emit_settings_changed(app: &tauri::AppHandle, key: &str) {
+    if key == "sync" {
+        push::restart(app);
+    }
+    let _ = app.emit(
+        "settings-changed",
+        SettingsChanged {
+            key: key.to_string(),
+        },
+    );
+}
+
+fn collect_synced_settings(conn: &Connection) -> Result<Vec<SettingEntry>, String> {
+    let mut stmt = conn
+        .prepare("SELECT key, value, updated_at FROM settings ORDER BY key")
+        .map_err(|err| err.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })
+        .map_err(|err| err.to_string())?;
+    let mut entries = Vec::new();
+    for row in rows {
+        let (key, value, updated_at) = row.map_err(|err| err.to_string())?;
+        if LOCAL_ONLY_SETTINGS.contains(&key.as_str()) {
+            continue;
+        }
+        let value = serde_json::from_str(&value)
+            .map_err(|err| format!("Invalid setting '{key}': {err}"))?;
+        entries.push(SettingEntry {
+            key,
+            value,
+            updated_at,
+        });
+    }
+    Ok(entries)
+}
+
+/// Applies settings pulled from `/data/settings`, keeping whichever side was
+/// updated last. Returns the keys overwritten locally.
+fn merge_settings(conn: &Connection, settings: Vec<SettingEntry>) -> Result<Vec<String>, String> {
+    let mut applied = Vec::new();
+    for entry in settings {
+        if LOCAL_ONLY_SETTINGS.contains(&entry.key.as_str()) {
+            continue;
+        }
+        let Ok(remote_at) = DateTime::parse_from_rfc3339(&entry.updated_at) else {
+            log_error(&format!(
+                "sync_settings: skipping '{}' with invalid updated_at",
+                entry.key
+            ));
+            continue;
+        };
+        let local_at: Option<String> = conn
+            .query_row(
+                "SELECT updated_at FROM settings WHERE key = ?1",
+                params![entry.key],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|err| err.to_string())?;
+        if let Some(local_at) = local_at
+            && let Ok(local_at) = DateTime::parse_from_rfc3339(&local_at)
+            && local_at >= remote_at
+        {
+            continue;
+        }
+        let value = serde_json::to_string(&entry.value).map_err(|err| err.to_string())?;
+        conn.execute(
+            "INSERT INTO settings (key, value, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+            params![entry.key, value, remote_at.with_timezone(&Utc).to_rfc3339()],
+        )
+        .map_err(|err| err.to_string())?;
+        applied.push(entry.key);
+    }
+    Ok(applied)
+}
+
+/// Pulls settings from the auth server, merges them with `merge_settings`,
+/// and pushes every synced setting back. Returns the number of settings
+/// overwritten locally.
+#[command]
+async fn sync_settings(
+    app: tauri::AppHandle,
+    server_url: String,
+    access_token: String,
+) -> Result<usize, String> {
+    run_blocking(move || {
+        let _perf = perf::track("sync_settings");
+        let client = le_client::Client::new(server_url)
+            .map_err(|err| err.to_string())?
+            .with_access_token(access_token);
+        let remote = perf::network(|| client.get_settings()).map_err(settings_error)?;
+        let conn = local_db(&app)?;
+        let applied = merge_settings(&conn, remote.settings)?;
+        let settings = collect_synced_settings(&conn)?;
+        // Let grading carry on while the push is in flight.
+        drop(conn);
+        perf::network(|| client.put_settings(&SettingsPayload { settings }))
+            .map_err(settings_error)?;
+        for key in &applied {
+            emit_settings_changed(&app, key);
+        }
+        Ok(applied.len())
+    })
+    .await
 }
 
-fn open_postgres() -> Result<Client, String> {
-    let url = postgres_url()?;
-    let connector = TlsConnector::new().map_err(|err| err.to_string())?;
-    let connector = MakeTlsConnector::new(connector);
-    Client::connect(&url, connector).map_err(|err| err.to_string())
+/// Local settings are kept either way and go up with the next sync.
+fn settings_error(err: le_client::ClientError) -> String {
+    if err.is_offline() {
+        connectivity::OFFLINE_QUEUED.to_string()
+    } else {
+        format!("Settings error: {err}")
+    }
 }
 
 fn sql_log_path() -> Option<String> {
@@ -281,51 +953,6 @@ fn log_error(message: &str) {
     }
 }
 
-fn ensure_seen_count(conn: &Connection) -> rusqlite::Result<()> {
-    let mut stmt = conn.prepare("PRAGMA table_info(cards)")?;
-    let columns = stmt.query_map([], |row| row.get::<_, String>(1))?;
-    for column in columns {
-        if column? == "seen_count" {
-            return Ok(());
-        }
-    }
-    conn.execute(
-        "ALTER TABLE cards ADD COLUMN seen_count INTEGER NOT NULL DEFAULT 0",
-        [],
-    )?;
-    Ok(())
-}
-
-fn ensure_batch_schema(conn: &Connection) -> rusqlite::Result<()> {
-    let mut stmt = conn.prepare("PRAGMA table_info(cards)")?;
-    let columns = stmt.query_map([], |row| row.get::<_, String>(1))?;
-    let mut has_batch = false;
-    for column in columns {
-        if column? == "batch_id" {
-            has_batch = true;
-            break;
-        }
-    }
-    if !has_batch {
-        conn.execute(
-            "ALTER TABLE cards ADD COLUMN batch_id INTEGER NOT NULL DEFAULT -1",
-            [],
-        )?;
-    }
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS batch_meta (
-            key TEXT PRIMARY KEY,
-            value TEXT NOT NULL
-        )",
-        [],
-    )?;
-    conn.execute(
-        "INSERT OR IGNORE INTO batch_meta (key, value) VALUES ('active_batch', '0')",
-        [],
-    )?;
-    Ok(())
-}
-
 fn get_active_batch(conn: &Connection) -> rusqlite::Result<i32> {
     let mut stmt = conn.prepare("SELECT value FROM batch_meta WHERE key = 'active_batch'")?;
     let value: String = stmt.query_row([], |row| row.get::<_, String>(0))?;
@@ -341,6 +968,24 @@ fn set_active_batch(conn: &Connection, batch: i32) -> rusqlite::Result<()> {
     Ok(())
 }
 
+fn get_last_session_start(conn: &Connection) -> rusqlite::Result<Option<String>> {
+    conn.query_row(
+        "SELECT value FROM batch_meta WHERE key = 'last_session_started_at'",
+        [],
+        |row| row.get::<_, String>(0),
+    )
+    .optional()
+}
+
+fn set_last_session_start(conn: &Connection, started_at: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO batch_meta (key, value) VALUES ('last_session_started_at', ?1)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        params![started_at],
+    )?;
+    Ok(())
+}
+
 fn assign_next_batch(conn: &Connection, batch: i32, size: usize) -> rusqlite::Result<usize> {
     let mut stmt = conn.prepare(
         "SELECT id FROM cards
@@ -415,61 +1060,223 @@ fn maybe_advance_batch(conn: &Connection) -> rusqlite::Result<i32> {
     Ok(active_batch)
 }
 
+/// Warm-up cards for a session starting now, based on when the previous
+/// session began. Empty when warm-up is disabled or no session has run yet.
+fn warm_up_for_session(conn: &Connection, filter: &SessionFilter) -> Result<Vec<String>, String> {
+    let settings: SessionSettings = get_setting(conn, "session")?.unwrap_or_default();
+    if settings.warm_up_cards == 0 {
+        return Ok(Vec::new());
+    }
+    let Some(since) = get_last_session_start(conn).map_err(|err| err.to_string())? else {
+        return Ok(Vec::new());
+    };
+    let now = Utc::now().to_rfc3339();
+    session_queries::failed_since(conn, filter.scope(), &since, &now, settings.warm_up_cards)
+        .map_err(|err| err.to_string())
+}
+
 #[command]
 fn start_session(
     app: tauri::AppHandle,
     state: State<'_, Mutex<ReviewState>>,
+    filter: Option<SessionFilter>,
+    strategy: Option<QueueStrategy>,
+    stop_after_correct: Option<usize>,
 ) -> Result<(), String> {
-    let db_path = app_db_path(&app)?;
-    let conn = open_db(&db_path).map_err(|err| err.to_string())?;
+    let _perf = perf::track("start_session");
+    let conn = local_db(&app)?;
+    let settings: SessionSettings = get_setting(&conn, "session")?.unwrap_or_default();
     let now = Utc::now().to_rfc3339();
     let active_batch = maybe_advance_batch(&conn).map_err(|err| err.to_string())?;
-    let mut stmt = conn
-        .prepare(
-            "SELECT id, batch_id, interval_days, ease, lapses, seen_count FROM cards
-             WHERE due_at <= ?1",
-        )
+    let filter = filter.unwrap_or_default();
+    let warm_up = warm_up_for_session(&conn, &filter)?;
+    set_last_session_start(&conn, &now).map_err(|err| err.to_string())?;
+    let mut candidates = session_queries::due_candidates(&conn, filter.scope(), &now)
         .map_err(|err| err.to_string())?;
-    let rows = stmt
-        .query_map(params![now], |row| {
-            Ok(CardCandidate {
-                id: row.get::<_, String>(0)?,
-                batch_id: row.get::<_, i32>(1)?,
-                weight: compute_card_weight(
-                    row.get::<_, i32>(2)?,
-                    row.get::<_, f64>(3)?,
-                    row.get::<_, i32>(4)?,
-                    row.get::<_, i32>(5)?,
-                ),
-            })
-        })
-        .map_err(|err| err.to_string())?;
-    let mut candidates: Vec<CardCandidate> = Vec::new();
-    for row in rows {
-        candidates.push(row.map_err(|err| err.to_string())?);
-    }
+    candidates.retain(|candidate| !warm_up.contains(&candidate.id));
     let mut guard = state
         .lock()
         .map_err(|_| "Failed to lock review state".to_string())?;
-    guard.queue.clear();
-    let limit = guard.session_limit;
-    guard.queue = select_weighted_cards(candidates, limit, active_batch);
-    Ok(())
-}
+    guard.samples.clear();
+    guard.fatigue_reported = false;
+    let limit = guard.session_limit.saturating_sub(warm_up.len());
+    let (new, due): (Vec<_>, Vec<_>) = candidates
+        .into_iter()
+        .partition(|candidate| candidate.kind == CardKind::New);
+    let mut new = select_weighted_cards(new, limit, active_batch);
+    let mut due = select_weighted_cards(due, limit, active_batch);
+    if let QueueStrategy::Interleaved = strategy.unwrap_or_default() {
+        new = interleave_by_chapter(new);
+        due = interleave_by_chapter(due);
+    }
+    let mut builder = SessionBuilder::new(session_config(
+        &settings,
+        limit,
+        stop_after_correct.or(settings.stop_after_correct),
+        new_cards_left_today(&conn)?,
+    ))
+    .due(due)
+    .new_cards(new);
+    if let Some(left) = recovery::reviews_left_today(&conn)? {
+        builder = builder.recovering(left);
+    }
+    guard.session = builder
+        .build()
+        .map(|candidate| candidate.id)
+        .start(Utc::now())
+        .warm_up(warm_up);
+    usage::session_started(&conn);
+    Ok(())
+}
+
+/// Describes the session `start_session` would build without touching the
+/// queue. The batch is picked the same way, inside a transaction that is
+/// rolled back, so an advance the session would make shows up here without
+/// being kept. Weighted picking is random, so the preview takes the heaviest
+/// candidates from each pool as a stand-in.
+#[command]
+fn peek_session(
+    app: tauri::AppHandle,
+    state: State<'_, Mutex<ReviewState>>,
+    filter: Option<SessionFilter>,
+) -> Result<SessionPreview, String> {
+    let _perf = perf::track("peek_session");
+    let conn = local_db(&app)?;
+    let now = Utc::now().to_rfc3339();
+    // Dropped without commit at the end of the command.
+    let conn = conn
+        .unchecked_transaction()
+        .map_err(|err| err.to_string())?;
+    let active_batch = maybe_advance_batch(&conn).map_err(|err| err.to_string())?;
+    let filter = filter.unwrap_or_default();
+    let warm_up = warm_up_for_session(&conn, &filter)?;
+    let mut candidates = session_queries::due_candidates(&conn, filter.scope(), &now)
+        .map_err(|err| err.to_string())?;
+    candidates.retain(|candidate| !warm_up.contains(&candidate.id));
+    let limit = state
+        .lock()
+        .map_err(|_| "Failed to lock review state".to_string())?
+        .session_limit
+        .saturating_sub(warm_up.len());
+    let settings: SessionSettings = get_setting(&conn, "session")?.unwrap_or_default();
+
+    // Heaviest first, active batch ahead of the rest.
+    candidates.sort_by(|a, b| {
+        (b.batch_id == active_batch)
+            .cmp(&(a.batch_id == active_batch))
+            .then(b.weight.total_cmp(&a.weight))
+    });
+    let (new, due): (Vec<_>, Vec<_>) = candidates
+        .into_iter()
+        .partition(|candidate| candidate.kind == CardKind::New);
+    let mut builder = SessionBuilder::new(session_config(
+        &settings,
+        limit,
+        None,
+        new_cards_left_today(&conn)?,
+    ))
+    .due(due)
+    .new_cards(new);
+    if let Some(left) = recovery::reviews_left_today(&conn)? {
+        builder = builder.recovering(left);
+    }
+    let session = builder.build();
+
+    let mut preview = SessionPreview {
+        total: warm_up.len(),
+        due: 0,
+        new: 0,
+        learning: 0,
+        chapters: Vec::new(),
+        estimated_minutes: 0,
+        warm_up: warm_up.len(),
+    };
+    for candidate in session.cards {
+        preview.total += 1;
+        match candidate.kind {
+            CardKind::New => preview.new += 1,
+            CardKind::Learning => preview.learning += 1,
+            CardKind::Due => preview.due += 1,
+        }
+        if let Some(chapter) = candidate.chapter
+            && !preview.chapters.contains(&chapter)
+        {
+            preview.chapters.push(chapter);
+        }
+    }
+    preview.chapters.sort();
+    preview.estimated_minutes = (preview.total as u64 * session::SECONDS_PER_CARD).div_ceil(60);
+    Ok(preview)
+}
+
+#[command]
+fn counts(app: tauri::AppHandle) -> Result<ReviewCounts, String> {
+    let _perf = perf::track("counts");
+    let conn = local_db(&app)?;
+    let (total, due) = session_queries::card_counts(&conn, &Utc::now().to_rfc3339())
+        .map_err(|err| err.to_string())?;
+    let (reviewed_today, _) = reviews_today(&conn)?;
+    let new_today = new_cards_today(&conn)?;
+    let settings: SessionSettings = get_setting(&conn, "session")?.unwrap_or_default();
+    Ok(ReviewCounts {
+        total,
+        due,
+        reviewed_today,
+        new_today,
+        new_left_today: settings
+            .max_new_cards
+            .map(|limit| limit.saturating_sub(new_today)),
+    })
+}
+
+/// Reviews since local midnight, and how many of them were a card's first.
+fn reviews_today(conn: &Connection) -> Result<(usize, usize), String> {
+    let midnight = Local::now()
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .and_then(|time| time.and_local_timezone(Local).earliest())
+        .map(|time| time.with_timezone(&Utc).to_rfc3339())
+        .ok_or_else(|| "Could not work out local midnight".to_string())?;
+    session_queries::reviews_since(conn, &midnight).map_err(|err| err.to_string())
+}
 
-fn compute_card_weight(interval_days: i32, ease: f64, lapses: i32, seen_count: i32) -> f64 {
-    let difficulty = (3.5 - ease).max(0.2);
-    let interval_factor = 1.0 / ((interval_days.max(1) as f64) + 1.0);
-    let lapse_bonus = (lapses as f64) * 0.15;
-    let seen_bonus = 1.0 / ((seen_count.max(1) as f64) + 1.0);
-    (difficulty + interval_factor + lapse_bonus + seen_bonus * 0.3).max(0.05)
+/// New cards first answered today, as counted in `daily_stats` when graded.
+fn new_cards_today(conn: &Connection) -> Result<usize, String> {
+    session_queries::new_cards_on(conn, Local::now().date_naive()).map_err(|err| err.to_string())
+}
+
+/// `max_new_cards` less the new cards already answered today, as the TUI
+/// counts it; `None` means no cap.
+fn new_cards_left_today(conn: &Connection) -> Result<Option<usize>, String> {
+    let settings: SessionSettings = get_setting(conn, "session")?.unwrap_or_default();
+    let Some(limit) = settings.max_new_cards else {
+        return Ok(None);
+    };
+    Ok(Some(limit.saturating_sub(new_cards_today(conn)?)))
+}
+
+/// The core session limits for a session of at most `limit` cards, with
+/// `new_left` new cards still allowed today.
+fn session_config(
+    settings: &SessionSettings,
+    limit: usize,
+    stop_after_correct: Option<usize>,
+    new_left: Option<usize>,
+) -> SessionConfig {
+    SessionConfig {
+        max_cards: limit,
+        max_new_cards: new_left.unwrap_or(limit),
+        stop_after_correct: stop_after_correct.unwrap_or(0),
+        max_minutes: settings.max_minutes,
+        order: settings.order,
+    }
 }
 
 fn select_weighted_cards(
     candidates: Vec<CardCandidate>,
     limit: usize,
     active_batch: i32,
-) -> Vec<String> {
+) -> Vec<CardCandidate> {
     let mut primary = Vec::new();
     let mut secondary = Vec::new();
     for candidate in candidates {
@@ -483,11 +1290,11 @@ fn select_weighted_cards(
     let mut rng = rand::thread_rng();
     while queue.len() < limit {
         if let Some(candidate) = pick_weighted_candidate(&mut primary, &mut rng) {
-            queue.push(candidate.id);
+            queue.push(candidate);
             continue;
         }
         if let Some(candidate) = pick_weighted_candidate(&mut secondary, &mut rng) {
-            queue.push(candidate.id);
+            queue.push(candidate);
             continue;
         }
         break;
@@ -495,6 +1302,45 @@ fn select_weighted_cards(
     queue
 }
 
+/// Reorders cards so neighbours come from different chapters where possible,
+/// always drawing next from the chapter with the most cards left. Cards keep
+/// their relative order within a chapter.
+fn interleave_by_chapter(cards: Vec<CardCandidate>) -> Vec<CardCandidate> {
+    let mut buckets: Vec<(Option<String>, VecDeque<CardCandidate>)> = Vec::new();
+    for card in cards {
+        match buckets
+            .iter_mut()
+            .find(|(chapter, _)| *chapter == card.chapter)
+        {
+            Some((_, bucket)) => bucket.push_back(card),
+            None => buckets.push((card.chapter.clone(), VecDeque::from([card]))),
+        }
+    }
+    if buckets.len() < 2 {
+        return buckets.into_iter().flat_map(|(_, bucket)| bucket).collect();
+    }
+
+    let mut ordered = Vec::new();
+    let mut last: Option<usize> = None;
+    loop {
+        let next = buckets
+            .iter()
+            .enumerate()
+            .filter(|(idx, (_, bucket))| !bucket.is_empty() && Some(*idx) != last)
+            .max_by_key(|(idx, (_, bucket))| (bucket.len(), std::cmp::Reverse(*idx)))
+            .map(|(idx, _)| idx)
+            .or_else(|| last.filter(|idx| !buckets[*idx].1.is_empty()));
+        let Some(idx) = next else {
+            break;
+        };
+        if let Some(card) = buckets[idx].1.pop_front() {
+            ordered.push(card);
+        }
+        last = Some(idx);
+    }
+    ordered
+}
+
 fn pick_weighted_candidate(
     candidates: &mut Vec<CardCandidate>,
     rng: &mut impl Rng,
@@ -523,20 +1369,96 @@ fn next_due_card(
     app: tauri::AppHandle,
     state: State<'_, Mutex<ReviewState>>,
 ) -> Result<Option<ReviewItem>, String> {
-    let db_path = app_db_path(&app)?;
-    let conn = open_db(&db_path).map_err(|err| err.to_string())?;
+    let _perf = perf::track("next_due_card");
+    let conn = local_db(&app)?;
     let mut guard = state
         .lock()
         .map_err(|_| "Failed to lock review state".to_string())?;
-    let Some(card_id) = guard.queue.pop() else {
+    if !guard.fatigue_reported
+        && let Some(mut event) = detect_fatigue(&guard.samples)
+    {
+        let settings: SessionSettings = get_setting(&conn, "session")?.unwrap_or_default();
+        if settings.fatigue != FatigueMode::Off {
+            guard.fatigue_reported = true;
+            event.enforced = settings.fatigue == FatigueMode::Enforce;
+            if event.enforced {
+                guard.session.stop();
+            }
+            if let Err(err) = app.emit("fatigue-detected", event) {
+                log_error(&err.to_string());
+            }
+        }
+    }
+    let Some(card_id) = guard.session.next(Utc::now()).cloned() else {
+        drop(guard);
+        emit_card_served(&app, None);
         return Ok(None);
     };
     drop(guard);
+    emit_card_served(&app, Some(&card_id));
+    load_review_item(&conn, &card_id, SentencePick::Rotate)
+}
+
+/// The card `next_due_card` last handed out, without advancing the queue, so
+/// a second window (the compact review widget) can join the session.
+#[command]
+fn current_card(
+    app: tauri::AppHandle,
+    state: State<'_, Mutex<ReviewState>>,
+) -> Result<Option<ReviewItem>, String> {
+    let _perf = perf::track("current_card");
+    let card_id = state
+        .lock()
+        .map_err(|_| "Failed to lock review state".to_string())?
+        .session
+        .current()
+        .cloned();
+    let Some(card_id) = card_id else {
+        return Ok(None);
+    };
+    let conn = local_db(&app)?;
+    load_review_item(&conn, &card_id, SentencePick::Current)
+}
+
+/// Tells every window which card the session moved to, so windows sharing the
+/// session stay on the same card.
+fn emit_card_served(app: &tauri::AppHandle, card_id: Option<&str>) {
+    if let Err(err) = app.emit(
+        "card-served",
+        CardServed {
+            card_id: card_id.map(str::to_string),
+        },
+    ) {
+        log_error(&err.to_string());
+    }
+}
+
+/// Which of a word's sentences a [`ReviewItem`] carries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SentencePick {
+    /// The one shown least recently (never-shown ones first), marked as shown.
+    Rotate,
+    /// The one shown last, so another window shows the same sentence.
+    Current,
+}
 
+fn load_review_item(
+    conn: &Connection,
+    card_id: &str,
+    pick: SentencePick,
+) -> Result<Option<ReviewItem>, String> {
     let mut stmt = conn
         .prepare(
             "SELECT c.id, c.word_id, c.due_at,
-                    w.text, w.translation, w.language, w.chapter, w.group_name, w.notes
+                    w.text, w.translation, w.language, w.chapter, w.group_name, w.notes,
+                    w.source_type, w.source_name, w.source_page, w.captured_at, w.source_region,
+                    w.alt_answers, w.kind, c.card_type,
+                    EXISTS (
+                        SELECT 1 FROM cards other
+                        WHERE other.word_id = c.word_id
+                          AND other.card_type IS NOT 'Cloze'
+                          AND (other.card_type IS 'Reverse') != (c.card_type IS 'Reverse')
+                    )
              FROM cards c
              JOIN words w ON w.id = c.word_id
              WHERE c.id = ?1
@@ -547,15 +1469,59 @@ fn next_due_card(
         .query(params![card_id])
         .map_err(|err| err.to_string())?;
     if let Some(row) = rows.next().map_err(|err| err.to_string())? {
+        let text: String = row.get(3).map_err(|err| err.to_string())?;
+        let kind = row
+            .get::<_, Option<String>>(15)
+            .map_err(|err| err.to_string())?
+            .as_deref()
+            .and_then(EntryKind::parse)
+            .unwrap_or_else(|| EntryKind::detect(&text));
+        let word_id: String = row.get(1).map_err(|err| err.to_string())?;
+        let language: String = row.get(5).map_err(|err| err.to_string())?;
+        let sentence = pick_sentence(conn, &word_id, pick).map_err(|err| err.to_string())?;
+        let translation = row
+            .get::<_, Option<String>>(4)
+            .map_err(|err| err.to_string())?;
+        let mut card_type = CardType::from_column(
+            row.get::<_, Option<String>>(16)
+                .map_err(|err| err.to_string())?
+                .as_deref(),
+        );
+        let blanked = match (&sentence, card_type) {
+            (Some((sentence, _)), CardType::Cloze) => {
+                make_cloze(Language::new(&language).rules(), sentence, &text)
+            }
+            _ => None,
+        };
+        let (prompt, answer) = match (card_type, blanked) {
+            (CardType::Cloze, Some(blanked)) => (blanked.prompt, Some(blanked.answer)),
+            (CardType::Reverse, _) => (
+                translation.clone().unwrap_or_else(|| text.clone()),
+                Some(text.clone()),
+            ),
+            (CardType::Basic | CardType::Cloze, _) => {
+                card_type = CardType::Basic;
+                (text.clone(), translation.clone())
+            }
+        };
+        let example =
+            sentence
+                .filter(|_| card_type != CardType::Cloze)
+                .map(|(sentence, translation)| {
+                    sentence_payload(sentence, translation, &text, &language)
+                });
+        let card_id: String = row.get(0).map_err(|err| err.to_string())?;
+        let revision = card_revision(conn, &card_id)
+            .map_err(|err| err.to_string())?
+            .unwrap_or_default();
         let item = ReviewItem {
-            card_id: row.get::<_, String>(0).map_err(|err| err.to_string())?,
-            word_id: row.get::<_, String>(1).map_err(|err| err.to_string())?,
+            card_id,
+            revision,
+            word_id,
             due_at: row.get::<_, String>(2).map_err(|err| err.to_string())?,
-            text: row.get::<_, String>(3).map_err(|err| err.to_string())?,
-            translation: row
-                .get::<_, Option<String>>(4)
-                .map_err(|err| err.to_string())?,
-            language: row.get::<_, String>(5).map_err(|err| err.to_string())?,
+            text,
+            translation,
+            language,
             chapter: row
                 .get::<_, Option<String>>(6)
                 .map_err(|err| err.to_string())?,
@@ -565,6 +1531,23 @@ fn next_due_card(
             notes: row
                 .get::<_, Option<String>>(8)
                 .map_err(|err| err.to_string())?,
+            source: WordSource::from_columns(
+                row.get(9).map_err(|err| err.to_string())?,
+                row.get(10).map_err(|err| err.to_string())?,
+                row.get(11).map_err(|err| err.to_string())?,
+                row.get(12).map_err(|err| err.to_string())?,
+                row.get(13).map_err(|err| err.to_string())?,
+            ),
+            alt_answers: parse_alt_answers(
+                row.get::<_, Option<String>>(14)
+                    .map_err(|err| err.to_string())?,
+            ),
+            kind,
+            card_type,
+            prompt,
+            answer,
+            paired: row.get(17).map_err(|err| err.to_string())?,
+            example,
         };
         Ok(Some(item))
     } else {
@@ -572,90 +1555,1420 @@ fn next_due_card(
     }
 }
 
+/// Picks one of the word's sentences so that a word with several is not
+/// always shown with the same one, which would let the sentence be learned
+/// instead of the word.
+fn pick_sentence(
+    conn: &Connection,
+    word_id: &str,
+    pick: SentencePick,
+) -> rusqlite::Result<Option<(String, Option<String>)>> {
+    let order = match pick {
+        SentencePick::Rotate => "last_shown_at IS NOT NULL, last_shown_at, id",
+        SentencePick::Current => "last_shown_at IS NULL, last_shown_at DESC, id",
+    };
+    let picked = conn
+        .query_row(
+            &format!(
+                "SELECT id, sentence, translation FROM sentences
+                 WHERE word_id = ?1 ORDER BY {order} LIMIT 1"
+            ),
+            params![word_id],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                ))
+            },
+        )
+        .optional()?;
+    let Some((id, sentence, translation)) = picked else {
+        return Ok(None);
+    };
+    if pick == SentencePick::Rotate {
+        conn.execute(
+            "UPDATE sentences SET last_shown_at = ?1 WHERE id = ?2",
+            params![Utc::now().to_rfc3339(), id],
+        )?;
+    }
+    Ok(Some((sentence, translation)))
+}
+
+/// Locates `word` in `sentence` and converts the byte ranges to UTF-16
+/// offsets, which is what JavaScript string indices count.
+fn sentence_payload(
+    sentence: String,
+    translation: Option<String>,
+    word: &str,
+    language: &str,
+) -> SentencePayload {
+    let utf16_offset = |byte: usize| sentence[..byte].encode_utf16().count();
+    let rules = Language::new(language).rules();
+    let spans = rules::find_word(rules, &sentence, word)
+        .into_iter()
+        .map(|range| TextSpan {
+            start: utf16_offset(range.start),
+            end: utf16_offset(range.end),
+        })
+        .collect();
+    SentencePayload {
+        text: sentence,
+        translation,
+        spans,
+    }
+}
+
+/// Stores a generated example sentence as the word's latest example, adds it
+/// to the sentences reviews rotate through (counted as just shown), and
+/// returns it with the word located, for highlighting.
+#[command]
+fn save_example(app: tauri::AppHandle, input: ExampleInput) -> Result<SentencePayload, String> {
+    let _perf = perf::track("save_example");
+    let conn = local_db(&app)?;
+    usage::feature_used(&conn, "example_sentences");
+    let sentence = input.sentence.trim().to_string();
+    if sentence.is_empty() {
+        return Err("Example sentence is empty".to_string());
+    }
+    let (word, language): (String, String) = conn
+        .query_row(
+            "SELECT text, language FROM words WHERE id = ?1",
+            params![input.word_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|err| err.to_string())?
+        .ok_or_else(|| format!("Unknown word {}", input.word_id))?;
+    let now = Utc::now().to_rfc3339();
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|err| err.to_string())?;
+    tx.execute(
+        "INSERT INTO examples (word_id, sentence, translation, created_at)
+         VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(word_id) DO UPDATE SET
+             sentence = excluded.sentence,
+             translation = excluded.translation,
+             created_at = excluded.created_at",
+        params![input.word_id, sentence, input.translation, now],
+    )
+    .map_err(|err| err.to_string())?;
+    tx.execute(
+        "INSERT INTO sentences (word_id, sentence, translation, created_at, last_shown_at)
+         VALUES (?1, ?2, ?3, ?4, ?4)
+         ON CONFLICT(word_id, sentence) DO UPDATE SET
+             translation = excluded.translation,
+             last_shown_at = excluded.last_shown_at",
+        params![input.word_id, sentence, input.translation, now],
+    )
+    .map_err(|err| err.to_string())?;
+    cloze::add_if_wanted(&tx, &input.word_id)?;
+    tx.commit().map_err(|err| err.to_string())?;
+    Ok(sentence_payload(
+        sentence,
+        input.translation,
+        &word,
+        &language,
+    ))
+}
+
+/// What [`apply_review`] did with one review.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReviewOutcome {
+    /// `learning_due_at` is when the card is next due if it is still in
+    /// its learning steps.
+    Applied {
+        learning_due_at: Option<DateTime<Utc>>,
+    },
+    /// A review with this id was already recorded; the card is untouched.
+    Duplicate,
+    UnknownCard,
+}
+
+/// A fingerprint (FNV-1a) of the card's scheduling state, which changes with
+/// every grade, undo, edit, or sync that touches the card. `None` for an
+/// unknown card.
+fn card_revision(conn: &Connection, card_id: &str) -> rusqlite::Result<Option<String>> {
+    let state: Option<String> = conn
+        .query_row(
+            "SELECT due_at || '|' || interval_days || '|' || ease || '|' || reps || '|' || lapses
+                    || '|' || COALESCE(stability, '') || '|' || COALESCE(difficulty, '')
+                    || '|' || seen_count
+             FROM cards WHERE id = ?1",
+            params![card_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(state.map(|state| format!("{:016x}", fnv1a(state.as_bytes()))))
+}
+
+/// Records a review and runs the card through its deck's scheduler as of
+/// `at`. Callers own the transaction so a batch can be applied atomically.
+/// An `undoable` review snapshots the card for `undo_grade` first; any other
+/// review (imported or synced, not graded here) drops the card's snapshots
+/// instead, so an undo never rewinds past it.
+fn apply_review(
+    tx: &Connection,
+    card_id: &str,
+    review_id: &str,
+    grade: Grade,
+    at: DateTime<Utc>,
+    undoable: bool,
+) -> Result<ReviewOutcome, String> {
+    let card = tx
+        .query_row(
+            "SELECT c.id, c.word_id, c.due_at, c.interval_days, c.ease, c.reps, c.lapses,
+                    c.stability, c.difficulty, w.deck_id,
+                    (SELECT MAX(reviewed_at) FROM reviews WHERE card_id = c.id),
+                    c.learning_step, c.learning_due_at
+             FROM cards c
+             LEFT JOIN words w ON w.id = c.word_id
+             WHERE c.id = ?1",
+            params![card_id],
+            |row| {
+                Ok((
+                    (
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, i32>(3)?,
+                        row.get::<_, f64>(4)?,
+                        row.get::<_, i32>(5)?,
+                        row.get::<_, i32>(6)?,
+                    ),
+                    (
+                        row.get::<_, Option<f64>>(7)?,
+                        row.get::<_, Option<f64>>(8)?,
+                        row.get::<_, Option<String>>(9)?,
+                        row.get::<_, Option<String>>(10)?,
+                    ),
+                    (
+                        row.get::<_, Option<u32>>(11)?,
+                        row.get::<_, Option<String>>(12)?,
+                    ),
+                ))
+            },
+        )
+        .optional()
+        .map_err(|err| err.to_string())?;
+    let Some((
+        (id, word_id, due_at, interval_days, ease, reps, lapses),
+        (stability, difficulty, deck_id, last_reviewed_at),
+        (learning_step, learning_due_at),
+    )) = card
+    else {
+        return Ok(ReviewOutcome::UnknownCard);
+    };
+    // The review row is the exactly-once guard: if this id was already
+    // recorded, the card is left untouched.
+    let inserted = tx
+        .execute(
+            "INSERT INTO reviews (id, card_id, grade, reviewed_at) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(id) DO NOTHING",
+            params![review_id, card_id, grade.value(), at.to_rfc3339()],
+        )
+        .map_err(|err| err.to_string())?;
+    if inserted == 0 {
+        return Ok(ReviewOutcome::Duplicate);
+    }
+    if undoable {
+        tx.execute(
+            "INSERT INTO card_history (review_id, card_id, grade, due_at, interval_days, ease,
+                                       reps, lapses, stability, difficulty, seen_count,
+                                       learning_step, learning_due_at)
+             SELECT ?1, id, ?2, due_at, interval_days, ease, reps, lapses, stability, difficulty,
+                    seen_count, learning_step, learning_due_at
+             FROM cards WHERE id = ?3",
+            params![review_id, grade.value(), card_id],
+        )
+        .map_err(|err| err.to_string())?;
+        tx.execute(
+            "DELETE FROM card_history WHERE id <= (SELECT MAX(id) FROM card_history) - ?1",
+            params![UNDO_HISTORY],
+        )
+        .map_err(|err| err.to_string())?;
+    } else {
+        tx.execute(
+            "DELETE FROM card_history WHERE card_id = ?1",
+            params![card_id],
+        )
+        .map_err(|err| err.to_string())?;
+    }
+
+    let mut card = Card {
+        id: Uuid::parse_str(&id).map_err(|err| err.to_string())?,
+        word_id: Uuid::parse_str(&word_id).map_err(|err| err.to_string())?,
+        due_at: DateTime::parse_from_rfc3339(&due_at)
+            .map(|dt| dt.with_timezone(&Utc))
+            .map_err(|err| err.to_string())?,
+        interval_days,
+        ease,
+        reps,
+        lapses,
+        stability,
+        difficulty,
+        last_reviewed_at: last_reviewed_at
+            .and_then(|at| DateTime::parse_from_rfc3339(&at).ok())
+            .map(|at| at.with_timezone(&Utc)),
+        // Scheduling is the same either way round.
+        card_type: CardType::default(),
+        learning_step,
+        learning_due_at: learning_due_at
+            .and_then(|at| DateTime::parse_from_rfc3339(&at).ok())
+            .map(|at| at.with_timezone(&Utc)),
+    };
+    let first_review = card.last_reviewed_at.is_none();
+
+    let config: SchedulerConfig = get_setting(tx, SCHEDULER_KEY)?.unwrap_or_default();
+    config.schedule_card(&mut card, grade, at, deck_id.as_deref());
+    tx.execute(
+        "UPDATE cards SET due_at = ?1, interval_days = ?2, ease = ?3, reps = ?4, lapses = ?5,
+             stability = ?6, difficulty = ?7, learning_step = ?8, learning_due_at = ?9,
+             seen_count = seen_count + 1
+         WHERE id = ?10",
+        params![
+            card.due_at.to_rfc3339(),
+            card.interval_days,
+            card.ease,
+            card.reps,
+            card.lapses,
+            card.stability,
+            card.difficulty,
+            card.learning_step,
+            card.learning_due_at.map(|at| at.to_rfc3339()),
+            card.id.to_string()
+        ],
+    )
+    .map_err(|err| err.to_string())?;
+    session_queries::count_daily_review(tx, session::local_day(at), first_review)
+        .map_err(|err| err.to_string())?;
+    Ok(ReviewOutcome::Applied {
+        learning_due_at: card.learning_due_at,
+    })
+}
+
+#[command]
+fn grade_card(
+    app: tauri::AppHandle,
+    input: GradeInput,
+    state: State<'_, Mutex<ReviewState>>,
+) -> Result<(), String> {
+    let _perf = perf::track("grade_card");
+    let mut conn = local_db(&app)?;
+    let now = Utc::now();
+    let review_id = match input.review_id.as_deref() {
+        Some(id) => Uuid::parse_str(id)
+            .map_err(|err| format!("Invalid review_id: {err}"))?
+            .to_string(),
+        None => Uuid::new_v4().to_string(),
+    };
+
+    let tx = conn.transaction().map_err(|err| err.to_string())?;
+    // A retried grade already changed the revision itself, so it must be
+    // recognised before the revision is compared.
+    let recorded: bool = tx
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM reviews WHERE id = ?1)",
+            params![review_id],
+            |row| row.get(0),
+        )
+        .map_err(|err| err.to_string())?;
+    if recorded {
+        return Ok(());
+    }
+    if let Some(expected) = input.revision.as_deref() {
+        let current = card_revision(&tx, &input.card_id).map_err(|err| err.to_string())?;
+        if current.is_some_and(|current| current != expected) {
+            return Err(STALE_CARD.to_string());
+        }
+    }
+    let ReviewOutcome::Applied { learning_due_at } =
+        apply_review(&tx, &input.card_id, &review_id, input.grade, now, true)?
+    else {
+        return Ok(());
+    };
+    tx.commit().map_err(|err| err.to_string())?;
+    usage::card_reviewed(&conn);
+
+    let mut goal_reached = None;
+    let session_complete = match state.lock() {
+        Ok(mut guard) => {
+            let graded = match learning_due_at {
+                Some(due_at) => {
+                    guard
+                        .session
+                        .grade_learning(input.card_id.clone(), input.grade, due_at, now)
+                }
+                None => guard.session.grade(input.card_id.clone(), input.grade),
+            };
+            if let Some(served_at) = graded.served_at {
+                let latency_ms = (now - served_at).num_milliseconds().max(0) as u64;
+                guard.samples.push(ReviewSample {
+                    correct: input.grade.is_correct(),
+                    latency_ms: latency_ms.min(FATIGUE_MAX_LATENCY_MS),
+                });
+            }
+            if graded.goal_reached {
+                goal_reached = Some(guard.session.progress().clone());
+            }
+            graded.complete
+        }
+        Err(_) => false,
+    };
+
+    let cue = if input.grade.is_correct() {
+        AudioCue::Correct
+    } else {
+        AudioCue::Wrong
+    };
+    emit_audio_cue(&app, &conn, cue);
+    if session_complete {
+        emit_audio_cue(&app, &conn, AudioCue::SessionComplete);
+    }
+    if let Some(progress) = goal_reached
+        && let Err(err) = app.emit("session-goal-reached", progress)
+    {
+        log_error(&err.to_string());
+    }
+
+    Ok(())
+}
+
+/// The grade taken back by `undo_grade`.
+#[derive(Debug, Serialize)]
+struct UndoneGrade {
+    card_id: String,
+    grade: Grade,
+}
+
+/// Reverts the most recent grade given on this device: deletes its review,
+/// puts the card's scheduling back as it was before, and makes the card the
+/// next one served. `None` when there is nothing left to undo.
+#[command]
+fn undo_grade(
+    app: tauri::AppHandle,
+    state: State<'_, Mutex<ReviewState>>,
+) -> Result<Option<UndoneGrade>, String> {
+    let _perf = perf::track("undo_grade");
+    let mut conn = local_db(&app)?;
+    let tx = conn.transaction().map_err(|err| err.to_string())?;
+    let last = tx
+        .query_row(
+            "SELECT id, review_id, card_id, grade FROM card_history ORDER BY id DESC LIMIT 1",
+            [],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, u8>(3)?,
+                ))
+            },
+        )
+        .optional()
+        .map_err(|err| err.to_string())?;
+    let Some((history_id, review_id, card_id, grade)) = last else {
+        return Ok(None);
+    };
+    let grade = Grade::new(grade).map_err(|err| err.to_string())?;
+    let reviewed_at: Option<String> = tx
+        .query_row(
+            "SELECT reviewed_at FROM reviews WHERE id = ?1",
+            params![review_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|err| err.to_string())?;
+    tx.execute("DELETE FROM reviews WHERE id = ?1", params![review_id])
+        .map_err(|err| err.to_string())?;
+    if let Some(reviewed_at) = reviewed_at
+        .and_then(|at| DateTime::parse_from_rfc3339(&at).ok())
+        .map(|at| at.with_timezone(&Utc))
+    {
+        let first_review: bool = tx
+            .query_row(
+                "SELECT NOT EXISTS(SELECT 1 FROM reviews WHERE card_id = ?1)",
+                params![card_id],
+                |row| row.get(0),
+            )
+            .map_err(|err| err.to_string())?;
+        session_queries::uncount_daily_review(&tx, session::local_day(reviewed_at), first_review)
+            .map_err(|err| err.to_string())?;
+    }
+    tx.execute(
+        "UPDATE cards SET (due_at, interval_days, ease, reps, lapses, stability, difficulty,
+                           seen_count, learning_step, learning_due_at) =
+             (SELECT due_at, interval_days, ease, reps, lapses, stability, difficulty, seen_count,
+                     learning_step, learning_due_at
+              FROM card_history WHERE id = ?1)
+         WHERE id = ?2",
+        params![history_id, card_id],
+    )
+    .map_err(|err| err.to_string())?;
+    tx.execute(
+        "DELETE FROM card_history WHERE id = ?1",
+        params![history_id],
+    )
+    .map_err(|err| err.to_string())?;
+    tx.commit().map_err(|err| err.to_string())?;
+    usage::feature_used(&conn, "undo_grade");
+
+    let mut guard = state
+        .lock()
+        .map_err(|_| "Failed to lock review state".to_string())?;
+    guard.session.undo(card_id.clone(), grade);
+    drop(guard);
+    Ok(Some(UndoneGrade { card_id, grade }))
+}
+
+/// Answers so far in the current session and its correct-answer goal.
+#[command]
+fn session_progress(state: State<'_, Mutex<ReviewState>>) -> Result<SessionProgress, String> {
+    let _perf = perf::track("session_progress");
+    Ok(state
+        .lock()
+        .map_err(|_| "Failed to lock review state".to_string())?
+        .session
+        .progress()
+        .clone())
+}
+
+/// How the current session has gone so far, for its end screen.
+#[command]
+fn session_summary(state: State<'_, Mutex<ReviewState>>) -> Result<SessionSummary, String> {
+    let _perf = perf::track("session_summary");
+    Ok(state
+        .lock()
+        .map_err(|_| "Failed to lock review state".to_string())?
+        .session
+        .summary(Utc::now()))
+}
+
+/// Compares the latest `FATIGUE_WINDOW` reviews against the session's first
+/// ones and reports fatigue when accuracy and speed have both slipped.
+fn detect_fatigue(samples: &[ReviewSample]) -> Option<FatigueEvent> {
+    if samples.len() < FATIGUE_WINDOW * 2 {
+        return None;
+    }
+    let summarize = |window: &[ReviewSample]| {
+        let correct = window.iter().filter(|sample| sample.correct).count();
+        let latency: u64 = window.iter().map(|sample| sample.latency_ms).sum();
+        (
+            correct as f64 / window.len() as f64,
+            latency / window.len() as u64,
+        )
+    };
+    let (baseline_accuracy, baseline_latency_ms) = summarize(&samples[..FATIGUE_WINDOW]);
+    let (accuracy, latency_ms) = summarize(&samples[samples.len() - FATIGUE_WINDOW..]);
+    let accuracy_dropped = baseline_accuracy - accuracy >= FATIGUE_ACCURACY_DROP;
+    let slowed = latency_ms as f64 >= baseline_latency_ms as f64 * FATIGUE_LATENCY_RATIO;
+    (accuracy_dropped && slowed).then_some(FatigueEvent {
+        accuracy,
+        baseline_accuracy,
+        latency_ms,
+        baseline_latency_ms,
+        enforced: false,
+    })
+}
+
+/// Grades a sentence card by mapping the grade-sentence score onto the SM-2
+/// scale; otherwise identical to `grade_card`.
+#[command]
+fn grade_sentence_card(
+    app: tauri::AppHandle,
+    input: SentenceGradeInput,
+    state: State<'_, Mutex<ReviewState>>,
+) -> Result<Grade, String> {
+    let _perf = perf::track("grade_sentence_card");
+    if !(1..=10).contains(&input.score) {
+        return Err(format!(
+            "Score must be between 1 and 10, got {}",
+            input.score
+        ));
+    }
+    let grade = grade_from_sentence_score(input.score);
+    if let Ok(conn) = local_db(&app) {
+        usage::feature_used(&conn, "sentence_grading");
+    }
+    grade_card(
+        app,
+        GradeInput {
+            card_id: input.card_id,
+            grade,
+            review_id: input.review_id,
+            revision: input.revision,
+        },
+        state,
+    )?;
+    Ok(grade)
+}
+
+/// Replays reviews done elsewhere through the scheduler, oldest first, in one
+/// transaction. The whole batch is rejected if any record is malformed.
+#[command]
+fn record_reviews_batch(
+    app: tauri::AppHandle,
+    reviews: Vec<ReviewRecord>,
+) -> Result<ReviewBatchResult, String> {
+    let _perf = perf::track("record_reviews_batch");
+    let now = Utc::now();
+    let mut parsed = Vec::with_capacity(reviews.len());
+    let mut problems = Vec::new();
+    for (idx, record) in reviews.into_iter().enumerate() {
+        let grade = match Grade::new(record.grade) {
+            Ok(grade) => grade,
+            Err(err) => {
+                problems.push(format!("#{idx}: {err}"));
+                continue;
+            }
+        };
+        let reviewed_at = match DateTime::parse_from_rfc3339(record.reviewed_at.trim()) {
+            Ok(at) => at.with_timezone(&Utc),
+            Err(err) => {
+                problems.push(format!("#{idx}: invalid reviewed_at: {err}"));
+                continue;
+            }
+        };
+        if reviewed_at > now {
+            problems.push(format!("#{idx}: reviewed_at is in the future"));
+            continue;
+        }
+        let review_id = match record.review_id.as_deref() {
+            Some(id) => match Uuid::parse_str(id) {
+                Ok(id) => id.to_string(),
+                Err(err) => {
+                    problems.push(format!("#{idx}: invalid review_id: {err}"));
+                    continue;
+                }
+            },
+            None => Uuid::new_v4().to_string(),
+        };
+        parsed.push((reviewed_at, record.card_id, grade, review_id));
+    }
+    if !problems.is_empty() {
+        return Err(format!("Review batch rejected: {}", problems.join("; ")));
+    }
+    // Stable sort keeps same-instant reviews in the order they were given.
+    parsed.sort_by_key(|(reviewed_at, ..)| *reviewed_at);
+
+    let mut conn = local_db(&app)?;
+    let tx = conn.transaction().map_err(|err| err.to_string())?;
+    let mut result = ReviewBatchResult::default();
+    for (reviewed_at, card_id, grade, review_id) in parsed {
+        let latest: Option<String> = tx
+            .query_row(
+                "SELECT MAX(reviewed_at) FROM reviews WHERE card_id = ?1",
+                params![card_id],
+                |row| row.get(0),
+            )
+            .map_err(|err| err.to_string())?;
+        let latest = latest
+            .and_then(|value| DateTime::parse_from_rfc3339(&value).ok())
+            .map(|value| value.with_timezone(&Utc));
+        if latest.is_some_and(|latest| reviewed_at < latest) {
+            let known: bool = tx
+                .query_row(
+                    "SELECT EXISTS(SELECT 1 FROM cards WHERE id = ?1)",
+                    params![card_id],
+                    |row| row.get(0),
+                )
+                .map_err(|err| err.to_string())?;
+            let inserted = known
+                && tx
+                    .execute(
+                        "INSERT INTO reviews (id, card_id, grade, reviewed_at)
+                         VALUES (?1, ?2, ?3, ?4)
+                         ON CONFLICT(id) DO NOTHING",
+                        params![review_id, card_id, grade.value(), reviewed_at.to_rfc3339()],
+                    )
+                    .map_err(|err| err.to_string())?
+                    > 0;
+            match (known, inserted) {
+                (false, _) => result.unknown_cards.push(card_id),
+                (true, true) => result.stale += 1,
+                (true, false) => result.duplicates += 1,
+            }
+            continue;
+        }
+        match apply_review(&tx, &card_id, &review_id, grade, reviewed_at, false)? {
+            ReviewOutcome::Applied { .. } => result.applied += 1,
+            ReviewOutcome::Duplicate => result.duplicates += 1,
+            ReviewOutcome::UnknownCard => result.unknown_cards.push(card_id),
+        }
+    }
+    tx.commit().map_err(|err| err.to_string())?;
+    result.unknown_cards.sort();
+    result.unknown_cards.dedup();
+    Ok(result)
+}
+
+/// Explains the most recent scheduling decision for a card by replaying its
+/// review history through its learning steps and deck's scheduler with the
+/// saved settings.
+/// Returns `None` for cards never reviewed.
+#[command]
+fn explain_schedule(
+    app: tauri::AppHandle,
+    card_id: String,
+) -> Result<Option<ScheduleExplanation>, String> {
+    let _perf = perf::track("explain_schedule");
+    let conn = local_db(&app)?;
+    usage::feature_used(&conn, "schedule_explainer");
+
+    let stored: Option<(String, i32, f64, Option<String>)> = conn
+        .query_row(
+            "SELECT c.word_id, c.interval_days, c.ease, w.deck_id FROM cards c
+             LEFT JOIN words w ON w.id = c.word_id
+             WHERE c.id = ?1",
+            params![card_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .optional()
+        .map_err(|err| err.to_string())?;
+    let Some((word_id, interval_days, ease, deck_id)) = stored else {
+        return Err(format!("Card {card_id} not found"));
+    };
+
+    let mut stmt = conn
+        .prepare("SELECT grade, reviewed_at FROM reviews WHERE card_id = ?1 ORDER BY reviewed_at")
+        .map_err(|err| err.to_string())?;
+    let rows = stmt
+        .query_map(params![card_id], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|err| err.to_string())?;
+    let mut history = Vec::new();
+    for row in rows {
+        let (grade, reviewed_at) = row.map_err(|err| err.to_string())?;
+        // Reviews saved with an out-of-range grade, from before grades were
+        // validated, cannot be replayed and are left out.
+        let (Ok(reviewed_at), Some(grade)) = (
+            DateTime::parse_from_rfc3339(&reviewed_at),
+            stored_grade(grade),
+        ) else {
+            continue;
+        };
+        history.push((grade, reviewed_at.with_timezone(&Utc)));
+    }
+    let Some((last_grade, last_reviewed_at)) = history.pop() else {
+        return Ok(None);
+    };
+
+    let config: SchedulerConfig = get_setting(&conn, SCHEDULER_KEY)?.unwrap_or_default();
+    let word_id = Uuid::parse_str(&word_id).map_err(|err| err.to_string())?;
+    let mut card = default_new_card(word_id, last_reviewed_at);
+    // Fuzz is derived from the card's id.
+    card.id = Uuid::parse_str(&card_id).map_err(|err| err.to_string())?;
+    for (grade, reviewed_at) in history {
+        config.schedule_card(&mut card, grade, reviewed_at, deck_id.as_deref());
+    }
+    let mut explanation =
+        config.explain_card(&card, last_grade, last_reviewed_at, deck_id.as_deref());
+    if explanation.interval_after != interval_days || (explanation.ease_after - ease).abs() > 0.01 {
+        explanation.steps.push(
+            "The card was also changed outside recorded reviews (for example by a sync), so its current state may differ."
+                .to_string(),
+        );
+    }
+    Ok(Some(explanation))
+}
+
+/// Points for the word's mastery graph: every review with the interval
+/// grading gave it and a smoothed accuracy, oldest first.
+#[command]
+fn learning_curve(app: tauri::AppHandle, word_id: String) -> Result<Vec<CurvePoint>, String> {
+    let _perf = perf::track("learning_curve");
+    let conn = local_db(&app)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT r.grade, r.reviewed_at FROM reviews r
+             JOIN cards c ON c.id = r.card_id
+             WHERE c.word_id = ?1",
+        )
+        .map_err(|err| err.to_string())?;
+    let rows = stmt
+        .query_map(params![word_id], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|err| err.to_string())?;
+    let mut reviews = Vec::new();
+    for row in rows {
+        let (grade, reviewed_at) = row.map_err(|err| err.to_string())?;
+        let (Ok(reviewed_at), Some(grade)) = (
+            DateTime::parse_from_rfc3339(&reviewed_at),
+            stored_grade(grade),
+        ) else {
+            continue;
+        };
+        reviews.push((grade, reviewed_at.with_timezone(&Utc)));
+    }
+    let deck_id: Option<String> = conn
+        .query_row(
+            "SELECT deck_id FROM words WHERE id = ?1",
+            params![word_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|err| err.to_string())?
+        .flatten();
+    let config: SchedulerConfig = get_setting(&conn, SCHEDULER_KEY)?.unwrap_or_default();
+    Ok(stats::learning_curve(&reviews, &config, deck_id.as_deref()))
+}
+
+/// A `reviews.grade` value as a [`Grade`], or `None` when it is out of range.
+fn stored_grade(value: i64) -> Option<Grade> {
+    u8::try_from(value)
+        .ok()
+        .and_then(|value| Grade::new(value).ok())
+}
+
+/// Resolves a user-supplied sound path to an absolute path, rejecting
+/// missing files and formats the webview cannot play.
+fn resolve_audio_cue_path(path: &str) -> Result<String, String> {
+    let path = Path::new(path.trim());
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+        .unwrap_or_default();
+    if !AUDIO_CUE_EXTENSIONS.contains(&extension.as_str()) {
+        return Err(format!(
+            "Unsupported sound file '{}' (expected one of: {})",
+            path.display(),
+            AUDIO_CUE_EXTENSIONS.join(", ")
+        ));
+    }
+    let resolved = path
+        .canonicalize()
+        .map_err(|err| format!("Sound file '{}' not found: {err}", path.display()))?;
+    if !resolved.is_file() {
+        return Err(format!("Sound file '{}' is not a file", path.display()));
+    }
+    Ok(resolved.to_string_lossy().to_string())
+}
+
+fn emit_audio_cue(app: &tauri::AppHandle, conn: &Connection, cue: AudioCue) {
+    let settings = match get_setting::<AudioCueSettings>(conn, "audio_cues") {
+        Ok(settings) => settings.unwrap_or_default(),
+        Err(err) => {
+            log_error(&err);
+            return;
+        }
+    };
+    let path = match cue {
+        AudioCue::Correct => settings.correct,
+        AudioCue::Wrong => settings.wrong,
+        AudioCue::SessionComplete => settings.session_complete,
+    };
+    let Some(path) = path else {
+        return;
+    };
+    // The file may have moved since it was configured.
+    if !Path::new(&path).is_file() {
+        log_error(&format!("Audio cue file missing: {path}"));
+        return;
+    }
+    let _ = app.emit("audio-cue", AudioCueEvent { cue, path });
+}
+
+#[command]
+fn get_audio_cues(app: tauri::AppHandle) -> Result<AudioCueSettings, String> {
+    let _perf = perf::track("get_audio_cues");
+    let conn = local_db(&app)?;
+    Ok(get_setting(&conn, "audio_cues")?.unwrap_or_default())
+}
+
+/// Validates and stores the sound files for each cue. Empty paths clear a cue.
+#[command]
+fn set_audio_cues(
+    app: tauri::AppHandle,
+    input: AudioCueSettings,
+) -> Result<AudioCueSettings, String> {
+    let _perf = perf::track("set_audio_cues");
+    let resolve = |path: Option<String>| -> Result<Option<String>, String> {
+        match path {
+            Some(path) if !path.trim().is_empty() => resolve_audio_cue_path(&path).map(Some),
+            _ => Ok(None),
+        }
+    };
+    let settings = AudioCueSettings {
+        correct: resolve(input.correct)?,
+        wrong: resolve(input.wrong)?,
+        session_complete: resolve(input.session_complete)?,
+    };
+    let conn = local_db(&app)?;
+    set_setting(&conn, "audio_cues", &settings)?;
+    emit_settings_changed(&app, "audio_cues");
+    Ok(settings)
+}
+
+#[command]
+fn get_session_settings(app: tauri::AppHandle) -> Result<SessionSettings, String> {
+    let _perf = perf::track("get_session_settings");
+    let conn = local_db(&app)?;
+    Ok(get_setting(&conn, "session")?.unwrap_or_default())
+}
+
+#[command]
+fn set_session_settings(
+    app: tauri::AppHandle,
+    input: SessionSettings,
+) -> Result<SessionSettings, String> {
+    let _perf = perf::track("set_session_settings");
+    reject_issues(session_issues(&input))?;
+    let conn = local_db(&app)?;
+    set_setting(&conn, "session", &input)?;
+    emit_settings_changed(&app, "session");
+    Ok(input)
+}
+
+#[command]
+fn get_ai_settings(app: tauri::AppHandle) -> Result<AiSettings, String> {
+    let _perf = perf::track("get_ai_settings");
+    let conn = local_db(&app)?;
+    Ok(get_setting(&conn, "ai")?.unwrap_or_default())
+}
+
+#[command]
+fn set_ai_settings(app: tauri::AppHandle, input: AiSettings) -> Result<AiSettings, String> {
+    let _perf = perf::track("set_ai_settings");
+    reject_issues(ai_issues(&input))?;
+    let conn = local_db(&app)?;
+    set_setting(&conn, "ai", &input)?;
+    emit_settings_changed(&app, "ai");
+    Ok(input)
+}
+
+/// Accessibility preferences; synced so the TUI and other devices see them.
+#[command]
+fn get_ui_prefs(app: tauri::AppHandle) -> Result<UiPrefs, String> {
+    let _perf = perf::track("get_ui_prefs");
+    let conn = local_db(&app)?;
+    Ok(get_setting(&conn, UI_PREFS_KEY)?.unwrap_or_default())
+}
+
+#[command]
+fn set_ui_prefs(app: tauri::AppHandle, input: UiPrefs) -> Result<UiPrefs, String> {
+    let _perf = perf::track("set_ui_prefs");
+    reject_issues(ui_issues(&input))?;
+    let conn = local_db(&app)?;
+    set_setting(&conn, UI_PREFS_KEY, &input)?;
+    emit_settings_changed(&app, UI_PREFS_KEY);
+    Ok(input)
+}
+
+/// Which cards new words get; synced so the TUI and other devices add the
+/// same ones.
+#[command]
+fn get_card_settings(app: tauri::AppHandle) -> Result<CardSettings, String> {
+    let _perf = perf::track("get_card_settings");
+    let conn = local_db(&app)?;
+    Ok(get_setting(&conn, CARD_SETTINGS_KEY)?.unwrap_or_default())
+}
+
+#[command]
+fn set_card_settings(app: tauri::AppHandle, input: CardSettings) -> Result<CardSettings, String> {
+    let _perf = perf::track("set_card_settings");
+    let conn = local_db(&app)?;
+    set_setting(&conn, CARD_SETTINGS_KEY, &input)?;
+    emit_settings_changed(&app, CARD_SETTINGS_KEY);
+    if input.reverse {
+        usage::feature_used(&conn, "reverse_cards");
+    }
+    Ok(input)
+}
+
+/// Gives existing words the reverse card they would get if added now, due
+/// straight away. Returns how many were added.
+#[command]
+fn add_reverse_cards(app: tauri::AppHandle) -> Result<usize, String> {
+    let _perf = perf::track("add_reverse_cards");
+    let conn = local_db(&app)?;
+    let added = le_db::cards::add_missing_reverse_cards(&conn, Utc::now())
+        .map_err(|err| err.to_string())?;
+    usage::feature_used(&conn, "reverse_cards");
+    Ok(added)
+}
+
+/// The language pair with the labels the `language` column uses for each
+/// side, which is what new words are saved under.
+#[derive(Debug, Serialize)]
+struct LanguagePairPayload {
+    source: Language,
+    target: Language,
+    source_name: String,
+    target_name: String,
+}
+
+impl From<LanguagePair> for LanguagePairPayload {
+    fn from(pair: LanguagePair) -> Self {
+        Self {
+            source_name: pair.source.to_column(),
+            target_name: pair.target.to_column(),
+            source: pair.source,
+            target: pair.target,
+        }
+    }
+}
+
+/// The language being learned and the one it is translated into, stored in
+/// the `languages` setting; Dutch into English until set.
+#[command]
+fn get_language_pair(app: tauri::AppHandle) -> Result<LanguagePairPayload, String> {
+    let _perf = perf::track("get_language_pair");
+    let conn = local_db(&app)?;
+    let pair: LanguagePair = get_setting(&conn, "languages")?.unwrap_or_default();
+    Ok(pair.into())
+}
+
+#[command]
+fn set_language_pair(
+    app: tauri::AppHandle,
+    input: LanguagePair,
+) -> Result<LanguagePairPayload, String> {
+    let _perf = perf::track("set_language_pair");
+    reject_issues(language_issues(&input))?;
+    let conn = local_db(&app)?;
+    set_setting(&conn, "languages", &input)?;
+    emit_settings_changed(&app, "languages");
+    Ok(input.into())
+}
+
+#[command]
+fn get_scheduler_settings(app: tauri::AppHandle) -> Result<SchedulerConfig, String> {
+    let _perf = perf::track("get_scheduler_settings");
+    let conn = local_db(&app)?;
+    Ok(get_setting(&conn, SCHEDULER_KEY)?.unwrap_or_default())
+}
+
+/// Picks SM-2 or FSRS for future reviews and tunes SM-2; existing due dates
+/// are kept.
+#[command]
+fn set_scheduler_settings(
+    app: tauri::AppHandle,
+    input: SchedulerConfig,
+) -> Result<SchedulerConfig, String> {
+    let _perf = perf::track("set_scheduler_settings");
+    reject_issues(scheduler_issues(&input))?;
+    let conn = local_db(&app)?;
+    set_setting(&conn, SCHEDULER_KEY, &input)?;
+    emit_settings_changed(&app, SCHEDULER_KEY);
+    Ok(input)
+}
+
+#[command]
+fn get_sync_settings(app: tauri::AppHandle) -> Result<SyncSettings, String> {
+    let _perf = perf::track("get_sync_settings");
+    let conn = local_db(&app)?;
+    Ok(get_setting(&conn, "sync")?.unwrap_or_default())
+}
+
+/// Stores the sync settings once the interval is in range and the server
+/// URL, when given, parses and answers.
+#[command]
+async fn set_sync_settings(
+    app: tauri::AppHandle,
+    input: SyncSettings,
+) -> Result<SyncSettings, String> {
+    run_blocking(move || {
+        let _perf = perf::track("set_sync_settings");
+        let input = SyncSettings {
+            server_url: input
+                .server_url
+                .map(|url| url.trim().to_string())
+                .filter(|url| !url.is_empty()),
+            ..input
+        };
+        reject_issues(sync_issues(&input))?;
+        let conn = local_db(&app)?;
+        set_setting(&conn, "sync", &input)?;
+        emit_settings_changed(&app, "sync");
+        Ok(input)
+    })
+    .await
+}
+
+#[command]
+fn get_tts_settings(app: tauri::AppHandle, language: String) -> Result<VoiceSettings, String> {
+    let _perf = perf::track("get_tts_settings");
+    let conn = local_db(&app)?;
+    let mut settings: HashMap<String, VoiceSettings> =
+        get_setting(&conn, "tts")?.unwrap_or_default();
+    Ok(settings.remove(&language).unwrap_or_default())
+}
+
+/// The `tts` voice settings for `language`, or the defaults.
+fn voice_settings(conn: &Connection, language: &Language) -> Result<VoiceSettings, String> {
+    let mut settings: HashMap<String, VoiceSettings> =
+        get_setting(conn, "tts")?.unwrap_or_default();
+    Ok(settings.remove(&language.to_column()).unwrap_or_default())
+}
+
+#[command]
+fn set_tts_settings(
+    app: tauri::AppHandle,
+    language: String,
+    input: VoiceSettings,
+) -> Result<VoiceSettings, String> {
+    let _perf = perf::track("set_tts_settings");
+    reject_issues(voice_issues(&language, &input))?;
+    let input = VoiceSettings {
+        voice: input.voice.filter(|voice| !voice.trim().is_empty()),
+        ..input
+    };
+    let conn = local_db(&app)?;
+    let mut settings: HashMap<String, VoiceSettings> =
+        get_setting(&conn, "tts")?.unwrap_or_default();
+    settings.insert(language, input.clone());
+    set_setting(&conn, "tts", &settings)?;
+    emit_settings_changed(&app, "tts");
+    Ok(input)
+}
+
+/// Checks a settings panel draft without saving it. Returns every problem
+/// found; an empty list means the draft can be saved as is.
+#[command]
+async fn validate_settings(draft: SettingsDraft) -> Result<Vec<SettingIssue>, String> {
+    run_blocking(move || {
+        let _perf = perf::track("validate_settings");
+        let mut issues = Vec::new();
+        if let Some(session) = &draft.session {
+            issues.extend(session_issues(session));
+        }
+        if let Some(sync) = &draft.sync {
+            issues.extend(sync_issues(sync));
+        }
+        if let Some(ai) = &draft.ai {
+            issues.extend(ai_issues(ai));
+        }
+        if let Some(ui) = &draft.ui {
+            issues.extend(ui_issues(ui));
+        }
+        if let Some(scheduler) = &draft.scheduler {
+            issues.extend(scheduler_issues(scheduler));
+        }
+        for (language, voice) in draft.tts.iter().flatten() {
+            issues.extend(voice_issues(language, voice));
+        }
+        if let Some(cues) = &draft.audio_cues {
+            for (field, path) in [
+                ("correct", &cues.correct),
+                ("wrong", &cues.wrong),
+                ("session_complete", &cues.session_complete),
+            ] {
+                if let Some(path) = path.as_deref().filter(|path| !path.trim().is_empty())
+                    && let Err(err) = resolve_audio_cue_path(path)
+                {
+                    issues.push(SettingIssue::new("audio_cues", field, err));
+                }
+            }
+        }
+        Ok(issues)
+    })
+    .await
+}
+
+fn reject_issues(issues: Vec<SettingIssue>) -> Result<(), String> {
+    if issues.is_empty() {
+        return Ok(());
+    }
+    Err(issues
+        .into_iter()
+        .map(|issue| issue.message)
+        .collect::<Vec<_>>()
+        .join("; "))
+}
+
+fn session_issues(settings: &SessionSettings) -> Vec<SettingIssue> {
+    let mut issues = Vec::new();
+    if settings.warm_up_cards > MAX_WARM_UP_CARDS {
+        issues.push(SettingIssue::new(
+            "session",
+            "warm_up_cards",
+            format!("Warm-up cards must be at most {MAX_WARM_UP_CARDS}"),
+        ));
+    }
+    if settings.stop_after_correct == Some(0) {
+        issues.push(SettingIssue::new(
+            "session",
+            "stop_after_correct",
+            "Correct-answer goal must be at least 1",
+        ));
+    }
+    if settings.max_minutes == Some(0) {
+        issues.push(SettingIssue::new(
+            "session",
+            "max_minutes",
+            "Session length must be at least 1 minute",
+        ));
+    }
+    if settings.order == (CardOrder::Mixed { due_per_new: 0 }) {
+        issues.push(SettingIssue::new(
+            "session",
+            "order",
+            "Mixed order needs at least 1 review per new card",
+        ));
+    }
+    issues
+}
+
+fn ai_issues(settings: &AiSettings) -> Vec<SettingIssue> {
+    let mut issues = Vec::new();
+    let (min, max) = SENTENCE_MAX_WORDS_RANGE;
+    if let Some(max_words) = settings.max_words
+        && !(min..=max).contains(&max_words)
+    {
+        issues.push(SettingIssue::new(
+            "ai",
+            "max_words",
+            format!("Sentence length must be between {min} and {max} words"),
+        ));
+    }
+    issues
+}
+
+fn ui_issues(prefs: &UiPrefs) -> Vec<SettingIssue> {
+    let mut issues = Vec::new();
+    let (min, max) = FONT_SCALE_RANGE;
+    if !(min..=max).contains(&prefs.font_scale) {
+        issues.push(SettingIssue::new(
+            UI_PREFS_KEY,
+            "font_scale",
+            format!("Font scale must be between {min} and {max}"),
+        ));
+    }
+    issues
+}
+
+fn scheduler_issues(config: &SchedulerConfig) -> Vec<SettingIssue> {
+    let mut issues: Vec<SettingIssue> = config
+        .sm2
+        .problems()
+        .into_iter()
+        .map(|(field, message)| SettingIssue::new(SCHEDULER_KEY, format!("sm2.{field}"), message))
+        .collect();
+    if let Some(message) = learning::learning_steps_problem(&config.learning_steps) {
+        issues.push(SettingIssue::new(SCHEDULER_KEY, "learning_steps", message));
+    }
+    issues
+}
+
+fn language_issues(pair: &LanguagePair) -> Vec<SettingIssue> {
+    let mut issues = Vec::new();
+    for (field, language) in [("source", &pair.source), ("target", &pair.target)] {
+        if language.primary() == "und" {
+            issues.push(SettingIssue::new(
+                "languages",
+                field,
+                "Enter a BCP-47 language tag such as \"nl\" or \"pt-BR\"",
+            ));
+        }
+    }
+    if pair.source == pair.target {
+        issues.push(SettingIssue::new(
+            "languages",
+            "target",
+            "Translate into a different language than the one being learned",
+        ));
+    }
+    issues
+}
+
+/// Range checks, then a ping so a typo in the server URL is caught before
+/// the next sync fails.
+fn sync_issues(settings: &SyncSettings) -> Vec<SettingIssue> {
+    let mut issues = Vec::new();
+    let (min, max) = SYNC_INTERVAL_RANGE;
+    if settings.interval_minutes != 0 && !(min..=max).contains(&settings.interval_minutes) {
+        issues.push(SettingIssue::new(
+            "sync",
+            "interval_minutes",
+            format!("Sync interval must be 0 (off) or between {min} and {max} minutes"),
+        ));
+    }
+    if let Some(url) = settings.server_url.as_deref().map(str::trim)
+        && !url.is_empty()
+    {
+        let reachable =
+            le_client::Client::new(url).and_then(|client| perf::network(|| client.ping()));
+        if let Err(err) = reachable {
+            issues.push(SettingIssue::new(
+                "sync",
+                "server_url",
+                format!("Server {url} is not reachable: {err}"),
+            ));
+        }
+    }
+    issues
+}
+
+fn voice_issues(language: &str, settings: &VoiceSettings) -> Vec<SettingIssue> {
+    let in_range = |value: f32, (min, max): (f32, f32)| (min..=max).contains(&value);
+    let mut issues = Vec::new();
+    if !in_range(settings.rate, TTS_RATE_RANGE) {
+        issues.push(SettingIssue::new(
+            "tts",
+            format!("{language}.rate"),
+            format!(
+                "Speech rate must be between {} and {}",
+                TTS_RATE_RANGE.0, TTS_RATE_RANGE.1
+            ),
+        ));
+    }
+    if !in_range(settings.pitch, TTS_PITCH_RANGE) {
+        issues.push(SettingIssue::new(
+            "tts",
+            format!("{language}.pitch"),
+            format!(
+                "Pitch must be between {} and {}",
+                TTS_PITCH_RANGE.0, TTS_PITCH_RANGE.1
+            ),
+        ));
+    }
+    issues
+}
+
+/// Lists the voices the system speech provider offers (`say` on macOS,
+/// `espeak` elsewhere), optionally only those for `language`.
 #[command]
-fn grade_card(
-    app: tauri::AppHandle,
-    input: GradeInput,
-    state: State<'_, Mutex<ReviewState>>,
-) -> Result<(), String> {
-    let db_path = app_db_path(&app)?;
-    let conn = open_db(&db_path).map_err(|err| err.to_string())?;
-    let now = Utc::now();
-
-    let mut stmt = conn
-        .prepare(
-            "SELECT id, word_id, due_at, interval_days, ease, reps, lapses
-             FROM cards WHERE id = ?1",
-        )
-        .map_err(|err| err.to_string())?;
-    let mut rows = stmt
-        .query(params![input.card_id])
-        .map_err(|err| err.to_string())?;
-    let row = rows.next().map_err(|err| err.to_string())?;
-    let Some(row) = row else {
-        return Ok(());
+fn list_voices(language: Option<String>) -> Result<Vec<VoiceInfo>, String> {
+    let _perf = perf::track("list_voices");
+    let voices = if cfg!(target_os = "macos") {
+        let output = std::process::Command::new("say")
+            .args(["-v", "?"])
+            .output()
+            .map_err(|err| format!("Failed to run say: {err}"))?;
+        parse_say_voices(&String::from_utf8_lossy(&output.stdout))
+    } else {
+        let output = std::process::Command::new("espeak")
+            .arg("--voices")
+            .output()
+            .map_err(|err| format!("Failed to run espeak: {err}"))?;
+        parse_espeak_voices(&String::from_utf8_lossy(&output.stdout))
     };
-
-    let mut card = Card {
-        id: Uuid::parse_str(&row.get::<_, String>(0).map_err(|err| err.to_string())?)
-            .map_err(|err| err.to_string())?,
-        word_id: Uuid::parse_str(&row.get::<_, String>(1).map_err(|err| err.to_string())?)
-            .map_err(|err| err.to_string())?,
-        due_at: DateTime::parse_from_rfc3339(
-            &row.get::<_, String>(2).map_err(|err| err.to_string())?,
-        )
-        .map(|dt| dt.with_timezone(&Utc))
-        .map_err(|err| err.to_string())?,
-        interval_days: row.get(3).map_err(|err| err.to_string())?,
-        ease: row.get(4).map_err(|err| err.to_string())?,
-        reps: row.get(5).map_err(|err| err.to_string())?,
-        lapses: row.get(6).map_err(|err| err.to_string())?,
+    let Some(language) = language else {
+        return Ok(voices);
     };
+    let code = language_code(&language);
+    Ok(voices
+        .into_iter()
+        .filter(|voice| voice.locale.to_lowercase().starts_with(&code))
+        .collect())
+}
 
-    schedule_sm2(&mut card, input.grade, now);
+/// Parses `say -v '?'` lines such as `Xander   nl_NL    # Hallo, mijn naam is Xander.`
+fn parse_say_voices(output: &str) -> Vec<VoiceInfo> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let description = line.split('#').next()?.trim();
+            let (name, locale) = description.rsplit_once(char::is_whitespace)?;
+            Some(VoiceInfo {
+                name: name.trim().to_string(),
+                locale: locale.to_string(),
+            })
+        })
+        .collect()
+}
 
-    if input.grade <= 2 {
-        card.due_at = now + Duration::hours(2);
-    }
-    conn.execute(
-    "UPDATE cards SET due_at = ?1, interval_days = ?2, ease = ?3, reps = ?4, lapses = ?5 WHERE id = ?6",
-        params![
-            card.due_at.to_rfc3339(),
-            card.interval_days,
-            card.ease,
-            card.reps,
-            card.lapses,
-            card.id.to_string()
-        ],
-    )
-    .map_err(|err| err.to_string())?;
+/// Parses `espeak --voices` rows: `Pty Language Age/Gender VoiceName File ...`.
+fn parse_espeak_voices(output: &str) -> Vec<VoiceInfo> {
+    output
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let mut columns = line.split_whitespace();
+            let locale = columns.nth(1)?;
+            let name = columns.nth(1)?;
+            Some(VoiceInfo {
+                name: name.to_string(),
+                locale: locale.to_string(),
+            })
+        })
+        .collect()
+}
 
-    conn.execute(
-        "INSERT INTO reviews (id, card_id, grade, reviewed_at) VALUES (?1, ?2, ?3, ?4)",
-        params![
-            Uuid::new_v4().to_string(),
-            card.id.to_string(),
-            input.grade,
-            now.to_rfc3339()
-        ],
-    )
-    .map_err(|err| err.to_string())?;
+fn language_code(language: &str) -> String {
+    Language::new(language).code().to_string()
+}
 
-    conn.execute(
-        "UPDATE cards SET seen_count = seen_count + 1 WHERE id = ?1",
-        params![card.id.to_string()],
-    )
-    .map_err(|err| err.to_string())?;
+/// Writes an iCalendar file with one all-day event per day of projected
+/// review load over the next `ICS_FORECAST_DAYS`. Overdue cards land on today.
+/// Returns the number of events written.
+#[command]
+fn export_ics(app: tauri::AppHandle, path: String) -> Result<usize, String> {
+    let _perf = perf::track("export_ics");
+    let conn = local_db(&app)?;
+    usage::feature_used(&conn, "calendar_export");
+    let today = Local::now().date_naive();
+    let horizon = today + Duration::days(ICS_FORECAST_DAYS);
 
-    if let Ok(mut guard) = state.lock() {
-        guard.queue.retain(|id| id != &input.card_id);
+    let mut stmt = conn
+        .prepare("SELECT due_at FROM cards")
+        .map_err(|err| err.to_string())?;
+    let rows = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|err| err.to_string())?;
+    let mut load: Vec<(NaiveDate, usize)> = Vec::new();
+    for row in rows {
+        let due_at = row.map_err(|err| err.to_string())?;
+        let Ok(due_at) = DateTime::parse_from_rfc3339(&due_at) else {
+            continue;
+        };
+        let day = due_at.with_timezone(&Local).date_naive().max(today);
+        if day >= horizon {
+            continue;
+        }
+        match load.iter_mut().find(|(date, _)| *date == day) {
+            Some((_, count)) => *count += 1,
+            None => load.push((day, 1)),
+        }
     }
+    load.sort_by_key(|(date, _)| *date);
 
-    Ok(())
+    let calendar = render_review_calendar(&load, Utc::now());
+    std::fs::write(&path, calendar).map_err(|err| err.to_string())?;
+    Ok(load.len())
+}
+
+fn render_review_calendar(load: &[(NaiveDate, usize)], stamp: DateTime<Utc>) -> String {
+    let stamp = stamp.format("%Y%m%dT%H%M%SZ").to_string();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Language Enforcer//Review Forecast//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "X-WR-CALNAME:Language Enforcer reviews".to_string(),
+    ];
+    for (date, count) in load {
+        let minutes = (*count as u64 * session::SECONDS_PER_CARD).div_ceil(60);
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!(
+            "UID:review-load-{}@language-enforcer",
+            date.format("%Y%m%d")
+        ));
+        lines.push(format!("DTSTAMP:{stamp}"));
+        lines.push(format!("DTSTART;VALUE=DATE:{}", date.format("%Y%m%d")));
+        lines.push(format!(
+            "DTEND;VALUE=DATE:{}",
+            (*date + Duration::days(1)).format("%Y%m%d")
+        ));
+        lines.push(format!(
+            "SUMMARY:Language Enforcer: {count} reviews (~{minutes} min)"
+        ));
+        lines.push("TRANSP:TRANSPARENT".to_string());
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+    let mut calendar = lines.join("\r\n");
+    calendar.push_str("\r\n");
+    calendar
 }
 
 #[command]
 fn report_issue(app: tauri::AppHandle, input: ReportInput) -> Result<(), String> {
+    let _perf = perf::track("report_issue");
     let mut path = app_db_path(&app)?;
     path.pop();
     path.push("reported_issues.jsonl");
@@ -670,61 +2983,48 @@ fn report_issue(app: tauri::AppHandle, input: ReportInput) -> Result<(), String>
     Ok(())
 }
 
+/// Stores alternate answers as a JSON array, trimmed and without blanks.
+fn alt_answers_json(alt_answers: Option<&[String]>) -> Result<Option<String>, String> {
+    let Some(alt_answers) = alt_answers else {
+        return Ok(None);
+    };
+    let cleaned: Vec<&str> = alt_answers
+        .iter()
+        .map(|answer| answer.trim())
+        .filter(|answer| !answer.is_empty())
+        .collect();
+    serde_json::to_string(&cleaned)
+        .map(Some)
+        .map_err(|err| err.to_string())
+}
+
+fn parse_alt_answers(raw: Option<String>) -> Vec<String> {
+    raw.and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
 #[command]
 fn apply_correction(app: tauri::AppHandle, input: CorrectionInput) -> Result<(), String> {
-    if input.text.is_none() && input.translation.is_none() {
+    let _perf = perf::track("apply_correction");
+    if input.text.is_none() && input.translation.is_none() && input.alt_answers.is_none() {
         return Ok(());
     }
-
-    let mut client = open_postgres()?;
-    let affected = match (input.text.as_ref(), input.translation.as_ref()) {
-        (Some(text), Some(translation)) => {
-            log_sql(
-                "UPDATE words SET text = $1, translation = $2 WHERE id = $3",
-                &[
-                    ("text", text.to_string()),
-                    ("translation", translation.to_string()),
-                    ("id", input.word_id.clone()),
-                ],
-            );
-            client.execute(
-                "UPDATE words SET text = $1, translation = $2 WHERE id = $3",
-                &[text, translation, &input.word_id],
-            )
-        }
-        (Some(text), None) => {
-            log_sql(
-                "UPDATE words SET text = $1 WHERE id = $2",
-                &[("text", text.to_string()), ("id", input.word_id.clone())],
-            );
-            client.execute(
-                "UPDATE words SET text = $1 WHERE id = $2",
-                &[text, &input.word_id],
-            )
-        }
-        (None, Some(translation)) => {
-            log_sql(
-                "UPDATE words SET translation = $1 WHERE id = $2",
-                &[
-                    ("translation", translation.to_string()),
-                    ("id", input.word_id.clone()),
-                ],
-            );
-            client.execute(
-                "UPDATE words SET translation = $1 WHERE id = $2",
-                &[translation, &input.word_id],
-            )
-        }
-        (None, None) => Ok(0),
-    }
-    .map_err(|err| err.to_string())?;
-
-    if affected == 0 {
+    let alt_answers = alt_answers_json(input.alt_answers.as_deref())?;
+
+    let found = remote::open()?.update_word(
+        &input.word_id,
+        &remote::WordUpdate {
+            text: input.text.as_deref(),
+            translation: input.translation.as_deref().map(Some),
+            alt_answers: alt_answers.as_deref().map(Some),
+        },
+    )?;
+    if !found {
         return Err("Word not found in Postgres".to_string());
     }
 
-    let db_path = app_db_path(&app)?;
-    let conn = open_db(&db_path).map_err(|err| err.to_string())?;
+    let conn = local_db(&app)?;
+    let before = corrections::snapshot(&conn, &input.word_id)?;
     if let Some(text) = input.text.as_ref() {
         conn.execute(
             "UPDATE words SET text = ?1 WHERE id = ?2",
@@ -739,16 +3039,25 @@ fn apply_correction(app: tauri::AppHandle, input: CorrectionInput) -> Result<(),
         )
         .map_err(|err| err.to_string())?;
     }
-    Ok(())
+    if let Some(alt_answers) = alt_answers.as_ref() {
+        conn.execute(
+            "UPDATE words SET alt_answers = ?1 WHERE id = ?2",
+            params![alt_answers, &input.word_id],
+        )
+        .map_err(|err| err.to_string())?;
+    }
+    corrections::record(&conn, &input.word_id, before, true)
 }
 
 #[command]
 fn apply_correction_local(app: tauri::AppHandle, input: CorrectionInput) -> Result<(), String> {
-    if input.text.is_none() && input.translation.is_none() {
+    let _perf = perf::track("apply_correction_local");
+    if input.text.is_none() && input.translation.is_none() && input.alt_answers.is_none() {
         return Ok(());
     }
-    let db_path = app_db_path(&app)?;
-    let conn = open_db(&db_path).map_err(|err| err.to_string())?;
+    let alt_answers = alt_answers_json(input.alt_answers.as_deref())?;
+    let conn = local_db(&app)?;
+    let before = corrections::snapshot(&conn, &input.word_id)?;
     if let Some(text) = input.text.as_ref() {
         conn.execute(
             "UPDATE words SET text = ?1 WHERE id = ?2",
@@ -763,13 +3072,20 @@ fn apply_correction_local(app: tauri::AppHandle, input: CorrectionInput) -> Resu
         )
         .map_err(|err| err.to_string())?;
     }
-    Ok(())
+    if let Some(alt_answers) = alt_answers.as_ref() {
+        conn.execute(
+            "UPDATE words SET alt_answers = ?1 WHERE id = ?2",
+            params![alt_answers, &input.word_id],
+        )
+        .map_err(|err| err.to_string())?;
+    }
+    corrections::record(&conn, &input.word_id, before, false)
 }
 
 #[command]
 fn add_word_local(app: tauri::AppHandle, input: AddWordInput) -> Result<(), String> {
-    let db_path = app_db_path(&app)?;
-    let conn = open_db(&db_path).map_err(|err| err.to_string())?;
+    let _perf = perf::track("add_word_local");
+    let conn = local_db(&app)?;
     if !input.allow_duplicate {
         let exists: Option<i64> = conn
             .query_row(
@@ -783,15 +3099,19 @@ fn add_word_local(app: tauri::AppHandle, input: AddWordInput) -> Result<(), Stri
             return Err("Word already exists".to_string());
         }
     }
+    let kind = EntryKind::detect(&input.text);
     conn.execute(
-        "INSERT INTO words (id, text, language, translation, chapter, group_name, notes, created_at)
-         VALUES (?1, ?2, ?3, ?4, NULL, NULL, NULL, ?5)",
+        "INSERT INTO words (id, text, language, translation, chapter, group_name, notes, created_at,
+                            source_type, source_name, source_page, captured_at, kind)
+         VALUES (?1, ?2, ?3, ?4, NULL, NULL, NULL, ?5, ?6, NULL, NULL, ?5, ?7)",
         params![
             input.word_id,
             input.text,
             input.language,
             input.translation,
-            input.created_at
+            input.created_at,
+            format!("{:?}", SourceKind::Manual),
+            format!("{:?}", kind)
         ],
     )
     .map_err(|err| err.to_string())?;
@@ -817,13 +3137,23 @@ fn add_word_local(app: tauri::AppHandle, input: AddWordInput) -> Result<(), Stri
         ],
     )
     .map_err(|err| err.to_string())?;
+    let settings = le_db::cards::card_settings(&conn).map_err(|err| err.to_string())?;
+    le_db::cards::add_reverse_card(
+        &conn,
+        &settings,
+        card.word_id,
+        kind,
+        input.translation.as_deref(),
+        card.due_at,
+    )
+    .map_err(|err| err.to_string())?;
     Ok(())
 }
 
 #[command]
 fn delete_word_local(app: tauri::AppHandle, input: DeleteWordInput) -> Result<(), String> {
-    let db_path = app_db_path(&app)?;
-    let mut conn = open_db(&db_path).map_err(|err| err.to_string())?;
+    let _perf = perf::track("delete_word_local");
+    let mut conn = local_db(&app)?;
     let tx = conn.transaction().map_err(|err| err.to_string())?;
     tx.execute(
         "DELETE FROM reviews WHERE card_id = ?1",
@@ -832,6 +3162,21 @@ fn delete_word_local(app: tauri::AppHandle, input: DeleteWordInput) -> Result<()
     .map_err(|err| err.to_string())?;
     tx.execute("DELETE FROM cards WHERE id = ?1", params![input.card_id])
         .map_err(|err| err.to_string())?;
+    tx.execute(
+        "DELETE FROM examples WHERE word_id = ?1",
+        params![input.word_id],
+    )
+    .map_err(|err| err.to_string())?;
+    tx.execute(
+        "DELETE FROM sentences WHERE word_id = ?1",
+        params![input.word_id],
+    )
+    .map_err(|err| err.to_string())?;
+    tx.execute(
+        "DELETE FROM word_tags WHERE word_id = ?1",
+        params![input.word_id],
+    )
+    .map_err(|err| err.to_string())?;
     tx.execute("DELETE FROM words WHERE id = ?1", params![input.word_id])
         .map_err(|err| err.to_string())?;
     tx.commit().map_err(|err| err.to_string())?;
@@ -840,8 +3185,8 @@ fn delete_word_local(app: tauri::AppHandle, input: DeleteWordInput) -> Result<()
 
 #[command]
 fn list_concepts(app: tauri::AppHandle) -> Result<Vec<String>, String> {
-    let db_path = app_db_path(&app)?;
-    let conn = open_db(&db_path).map_err(|err| err.to_string())?;
+    let _perf = perf::track("list_concepts");
+    let conn = local_db(&app)?;
     let mut stmt = conn
         .prepare("SELECT name FROM concepts ORDER BY name")
         .map_err(|err| err.to_string())?;
@@ -855,10 +3200,34 @@ fn list_concepts(app: tauri::AppHandle) -> Result<Vec<String>, String> {
     Ok(concepts)
 }
 
+/// Tag names for the session filter, with how many words carry each.
+#[command]
+fn list_tags(app: tauri::AppHandle) -> Result<Vec<(String, i64)>, String> {
+    let _perf = perf::track("list_tags");
+    let conn = local_db(&app)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT t.name, COUNT(wt.word_id)
+             FROM tags t
+             LEFT JOIN word_tags wt ON wt.tag_id = t.id
+             GROUP BY t.id
+             ORDER BY t.name",
+        )
+        .map_err(|err| err.to_string())?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|err| err.to_string())?;
+    let mut tags = Vec::new();
+    for row in rows {
+        tags.push(row.map_err(|err| err.to_string())?);
+    }
+    Ok(tags)
+}
+
 #[command]
 fn add_concept_local(app: tauri::AppHandle, input: ConceptInput) -> Result<(), String> {
-    let db_path = app_db_path(&app)?;
-    let conn = open_db(&db_path).map_err(|err| err.to_string())?;
+    let _perf = perf::track("add_concept_local");
+    let conn = local_db(&app)?;
     conn.execute(
         "INSERT OR IGNORE INTO concepts (id, name, created_at) VALUES (?1, ?2, ?3)",
         params![input.id, input.name, input.created_at],
@@ -867,182 +3236,14 @@ fn add_concept_local(app: tauri::AppHandle, input: ConceptInput) -> Result<(), S
     Ok(())
 }
 
-#[command]
-fn refresh_from_postgres(
-    app: tauri::AppHandle,
-    state: State<'_, Mutex<ReviewState>>,
-) -> Result<(i64, i64, i64), String> {
-    let mut client = open_postgres()?;
-    let db_path = app_db_path(&app)?;
-    let mut conn = open_db(&db_path).map_err(|err| err.to_string())?;
-
-    let tx = conn.transaction().map_err(|err| {
-        let message = format!("refresh_from_postgres: begin transaction failed: {err}");
-        log_error(&message);
-        message
-    })?;
-    let query = "DELETE FROM reviews; DELETE FROM cards; DELETE FROM words; DELETE FROM concepts;";
-    log_sql(query, &[]);
-    tx.execute_batch(query).map_err(|err| {
-        let message = format!("refresh_from_postgres: clear sqlite tables failed: {err}");
-        log_error(&message);
-        message
-    })?;
-
-    let mut word_count = 0i64;
-    let mut card_count = 0i64;
-    let mut review_count = 0i64;
-
-    log_sql(
-        "SELECT id, text, language, translation, chapter, group_name, notes, created_at FROM words",
-        &[],
-    );
-    let word_rows = client
-        .query(
-            "SELECT id, text, language, translation, chapter, group_name, notes, created_at FROM words",
-            &[],
-        )
-        .map_err(|err| {
-            let message = format!("refresh_from_postgres: select words failed: {err}");
-            log_error(&message);
-            message
-        })?;
-    for row in word_rows {
-        tx.execute(
-            "INSERT INTO words (id, text, language, translation, chapter, group_name, notes, created_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-            params![
-                row.get::<_, String>(0),
-                row.get::<_, String>(1),
-                row.get::<_, String>(2),
-                row.get::<_, Option<String>>(3),
-                row.get::<_, Option<String>>(4),
-                row.get::<_, Option<String>>(5),
-                row.get::<_, Option<String>>(6),
-                row.get::<_, String>(7),
-            ],
-        )
-        .map_err(|err| {
-            let message = format!("refresh_from_postgres: insert word failed: {err}");
-            log_error(&message);
-            message
-        })?;
-        word_count += 1;
-    }
-
-    log_sql(
-        "SELECT id, word_id, due_at, interval_days, ease, reps, lapses FROM cards",
-        &[],
-    );
-    let card_rows = client
-        .query(
-            "SELECT id, word_id, due_at, interval_days, ease, reps, lapses FROM cards",
-            &[],
-        )
-        .map_err(|err| {
-            let message = format!("refresh_from_postgres: select cards failed: {err}");
-            log_error(&message);
-            message
-        })?;
-    for row in card_rows {
-        tx.execute(
-            "INSERT INTO cards (id, word_id, due_at, interval_days, ease, reps, lapses, seen_count)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 0)",
-            params![
-                row.get::<_, String>(0),
-                row.get::<_, String>(1),
-                row.get::<_, String>(2),
-                row.get::<_, i32>(3),
-                row.get::<_, f64>(4),
-                row.get::<_, i32>(5),
-                row.get::<_, i32>(6),
-            ],
-        )
-        .map_err(|err| {
-            let message = format!("refresh_from_postgres: insert card failed: {err}");
-            log_error(&message);
-            message
-        })?;
-        card_count += 1;
-    }
-
-    log_sql("SELECT id, card_id, grade, reviewed_at FROM reviews", &[]);
-    let review_rows = client
-        .query("SELECT id, card_id, grade, reviewed_at FROM reviews", &[])
-        .map_err(|err| {
-            let message = format!("refresh_from_postgres: select reviews failed: {err}");
-            log_error(&message);
-            message
-        })?;
-    for row in review_rows {
-        tx.execute(
-            "INSERT INTO reviews (id, card_id, grade, reviewed_at) VALUES (?1, ?2, ?3, ?4)",
-            params![
-                row.get::<_, String>(0),
-                row.get::<_, String>(1),
-                row.get::<_, i32>(2),
-                row.get::<_, String>(3),
-            ],
-        )
-        .map_err(|err| {
-            let message = format!("refresh_from_postgres: insert review failed: {err}");
-            log_error(&message);
-            message
-        })?;
-        review_count += 1;
-    }
-    log_sql("DELETE FROM concepts", &[]);
-    tx.execute("DELETE FROM concepts", []).map_err(|err| {
-        let message = format!("refresh_from_postgres: clear concepts failed: {err}");
-        log_error(&message);
-        message
-    })?;
-    let concept_rows = client.query("SELECT id, name, created_at FROM concepts", &[]);
-    match concept_rows {
-        Ok(rows) => {
-            for row in rows {
-                tx.execute(
-                    "INSERT INTO concepts (id, name, created_at) VALUES (?1, ?2, ?3)",
-                    params![
-                        row.get::<_, String>(0),
-                        row.get::<_, String>(1),
-                        row.get::<_, String>(2)
-                    ],
-                )
-                .map_err(|err| {
-                    let message = format!("refresh_from_postgres: insert concept failed: {err}");
-                    log_error(&message);
-                    message
-                })?;
-            }
-        }
-        Err(err) => {
-            let message = format!("refresh_from_postgres: select concepts failed: {err}");
-            log_error(&message);
-        }
-    }
-
-    tx.commit().map_err(|err| {
-        let message = format!("refresh_from_postgres: commit failed: {err}");
-        log_error(&message);
-        message
-    })?;
-
-    if let Ok(mut guard) = state.lock() {
-        guard.queue.clear();
-    }
-
-    Ok((word_count, card_count, review_count))
-}
-
 #[command]
 fn refresh_from_data_api(
     app: tauri::AppHandle,
     state: State<'_, Mutex<ReviewState>>,
     snapshot: DataApiSnapshot,
 ) -> Result<(i64, i64, i64), String> {
-    let db_path = app_db_path(&app)?;
-    let mut conn = open_db(&db_path).map_err(|err| err.to_string())?;
+    let _perf = perf::track("refresh_from_data_api");
+    let mut conn = local_db(&app)?;
 
     let tx = conn.transaction().map_err(|err| {
         let message = format!("refresh_from_data_api: begin transaction failed: {err}");
@@ -1059,8 +3260,10 @@ fn refresh_from_data_api(
 
     for row in &snapshot.words {
         tx.execute(
-            "INSERT INTO words (id, text, language, translation, chapter, group_name, notes, created_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            "INSERT INTO words (id, text, language, translation, chapter, group_name, notes, created_at,
+                                source_type, source_name, source_page, captured_at, source_region,
+                                alt_answers, kind, deck_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
             params![
                 row.id,
                 row.text,
@@ -1070,6 +3273,14 @@ fn refresh_from_data_api(
                 row.group_name,
                 row.notes,
                 row.created_at,
+                row.source_type,
+                row.source_name,
+                row.source_page,
+                row.captured_at,
+                row.source_region,
+                row.alt_answers,
+                row.kind,
+                row.deck_id,
             ],
         )
         .map_err(|err| {
@@ -1081,8 +3292,9 @@ fn refresh_from_data_api(
 
     for row in &snapshot.cards {
         tx.execute(
-            "INSERT INTO cards (id, word_id, due_at, interval_days, ease, reps, lapses, seen_count)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 0)",
+            "INSERT INTO cards (id, word_id, due_at, interval_days, ease, reps, lapses, seen_count,
+                                deck_id, card_type)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 0, ?8, ?9)",
             params![
                 row.id,
                 row.word_id,
@@ -1091,6 +3303,8 @@ fn refresh_from_data_api(
                 row.ease,
                 row.reps,
                 row.lapses,
+                row.deck_id,
+                row.card_type,
             ],
         )
         .map_err(|err| {
@@ -1129,6 +3343,72 @@ fn refresh_from_data_api(
         })?;
     }
 
+    if let Some(tags) = &snapshot.tags {
+        tx.execute("DELETE FROM tags", []).map_err(|err| {
+            let message = format!("refresh_from_data_api: clear tags failed: {err}");
+            log_error(&message);
+            message
+        })?;
+        for row in tags {
+            tx.execute(
+                "INSERT INTO tags (id, name, created_at) VALUES (?1, ?2, ?3)",
+                params![row.id, row.name, row.created_at],
+            )
+            .map_err(|err| {
+                let message = format!("refresh_from_data_api: insert tag failed: {err}");
+                log_error(&message);
+                message
+            })?;
+        }
+    }
+    if let Some(word_tags) = &snapshot.word_tags {
+        tx.execute("DELETE FROM word_tags", []).map_err(|err| {
+            let message = format!("refresh_from_data_api: clear word tags failed: {err}");
+            log_error(&message);
+            message
+        })?;
+        for row in word_tags {
+            tx.execute(
+                "INSERT OR IGNORE INTO word_tags (word_id, tag_id) VALUES (?1, ?2)",
+                params![row.word_id, row.tag_id],
+            )
+            .map_err(|err| {
+                let message = format!("refresh_from_data_api: insert word tag failed: {err}");
+                log_error(&message);
+                message
+            })?;
+        }
+    }
+    if let Some(decks) = &snapshot.decks {
+        tx.execute("DELETE FROM decks", []).map_err(|err| {
+            let message = format!("refresh_from_data_api: clear decks failed: {err}");
+            log_error(&message);
+            message
+        })?;
+        for row in decks {
+            tx.execute(
+                "INSERT INTO decks (id, name, created_at) VALUES (?1, ?2, ?3)",
+                params![row.id, row.name, row.created_at],
+            )
+            .map_err(|err| {
+                let message = format!("refresh_from_data_api: insert deck failed: {err}");
+                log_error(&message);
+                message
+            })?;
+        }
+    }
+    // Tags kept from the local copy may point at words that are gone.
+    tx.execute(
+        "DELETE FROM word_tags WHERE word_id NOT IN (SELECT id FROM words)",
+        [],
+    )
+    .map_err(|err| {
+        let message = format!("refresh_from_data_api: prune word tags failed: {err}");
+        log_error(&message);
+        message
+    })?;
+
+    sync::reset_base(&tx)?;
     tx.commit().map_err(|err| {
         let message = format!("refresh_from_data_api: commit failed: {err}");
         log_error(&message);
@@ -1136,7 +3416,7 @@ fn refresh_from_data_api(
     })?;
 
     if let Ok(mut guard) = state.lock() {
-        guard.queue.clear();
+        guard.session = Session::default();
     }
 
     Ok((
@@ -1146,32 +3426,295 @@ fn refresh_from_data_api(
     ))
 }
 
+/// Compares the local SQLite words and cards against `snapshot` when given,
+/// otherwise against Postgres (`DATABASE_URL`). Nothing is written on either side.
+#[command]
+fn diff_against_remote(
+    app: tauri::AppHandle,
+    snapshot: Option<DataApiSnapshot>,
+) -> Result<WordlistDiff, String> {
+    let _perf = perf::track("diff_against_remote");
+    let (remote_words, remote_cards) = match snapshot {
+        Some(snapshot) => (
+            snapshot
+                .words
+                .into_iter()
+                .map(|word| WordSummary {
+                    id: word.id,
+                    text: word.text,
+                    translation: word.translation,
+                })
+                .collect(),
+            snapshot.cards,
+        ),
+        None => remote::open()?.wordlist()?,
+    };
+    let conn = local_db(&app)?;
+    let (local_words, local_cards) = load_local_wordlist(&conn).map_err(|err| err.to_string())?;
+    Ok(diff_wordlists(
+        local_words,
+        local_cards,
+        remote_words,
+        remote_cards,
+    ))
+}
+
+fn load_local_wordlist(conn: &Connection) -> rusqlite::Result<(Vec<WordSummary>, Vec<CardRow>)> {
+    let mut stmt = conn.prepare("SELECT id, text, translation FROM words")?;
+    let words = stmt
+        .query_map([], |row| {
+            Ok(WordSummary {
+                id: row.get(0)?,
+                text: row.get(1)?,
+                translation: row.get(2)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    let mut stmt = conn.prepare(
+        "SELECT id, word_id, due_at, interval_days, ease, reps, lapses, deck_id, card_type FROM cards",
+    )?;
+    let cards = stmt
+        .query_map([], |row| {
+            Ok(CardRow {
+                id: row.get(0)?,
+                word_id: row.get(1)?,
+                due_at: row.get(2)?,
+                interval_days: row.get(3)?,
+                ease: row.get(4)?,
+                reps: row.get(5)?,
+                lapses: row.get(6)?,
+                deck_id: row.get(7)?,
+                card_type: row.get(8)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok((words, cards))
+}
+
+fn diff_wordlists(
+    local_words: Vec<WordSummary>,
+    local_cards: Vec<CardRow>,
+    remote_words: Vec<WordSummary>,
+    remote_cards: Vec<CardRow>,
+) -> WordlistDiff {
+    let mut diff = WordlistDiff::default();
+
+    let mut remote_words: HashMap<String, WordSummary> = remote_words
+        .into_iter()
+        .map(|word| (word.id.clone(), word))
+        .collect();
+    for local in local_words {
+        match remote_words.remove(&local.id) {
+            None => diff.local_only_words.push(local),
+            Some(remote) if remote.translation != local.translation => {
+                diff.translation_mismatches.push(TranslationMismatch {
+                    word_id: local.id,
+                    text: local.text,
+                    local: local.translation,
+                    remote: remote.translation,
+                });
+            }
+            Some(_) => {}
+        }
+    }
+    diff.remote_only_words = remote_words.into_values().collect();
+
+    let mut remote_cards: HashMap<String, CardRow> = remote_cards
+        .into_iter()
+        .map(|card| (card.id.clone(), card))
+        .collect();
+    for local in local_cards {
+        match remote_cards.remove(&local.id) {
+            None => diff.local_only_cards.push(local.id),
+            Some(remote) if !same_card_state(&local, &remote) => {
+                diff.card_mismatches.push(CardMismatch {
+                    card_id: local.id.clone(),
+                    word_id: local.word_id.clone(),
+                    local: CardState::from(local),
+                    remote: CardState::from(remote),
+                });
+            }
+            Some(_) => {}
+        }
+    }
+    diff.remote_only_cards = remote_cards.into_keys().collect();
+
+    diff.local_only_words.sort_by(|a, b| a.text.cmp(&b.text));
+    diff.remote_only_words.sort_by(|a, b| a.text.cmp(&b.text));
+    diff.translation_mismatches
+        .sort_by(|a, b| a.text.cmp(&b.text));
+    diff.local_only_cards.sort();
+    diff.remote_only_cards.sort();
+    diff.card_mismatches
+        .sort_by(|a, b| a.card_id.cmp(&b.card_id));
+    diff
+}
+
+/// Timestamps are compared as instants since Postgres and SQLite format
+/// them differently.
+fn same_card_state(local: &CardRow, remote: &CardRow) -> bool {
+    let same_due = match (
+        DateTime::parse_from_rfc3339(&local.due_at),
+        DateTime::parse_from_rfc3339(&remote.due_at),
+    ) {
+        (Ok(local), Ok(remote)) => local == remote,
+        _ => local.due_at == remote.due_at,
+    };
+    same_due
+        && local.interval_days == remote.interval_days
+        && (local.ease - remote.ease).abs() < 1e-6
+        && local.reps == remote.reps
+        && local.lapses == remote.lapses
+}
+
+impl From<CardRow> for CardState {
+    fn from(card: CardRow) -> Self {
+        CardState {
+            due_at: card.due_at,
+            interval_days: card.interval_days,
+            ease: card.ease,
+            reps: card.reps,
+            lapses: card.lapses,
+        }
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let app = tauri::Builder::default()
-        .plugin(tauri_plugin_shell::init())
+    let builder = tauri::Builder::default().plugin(tauri_plugin_shell::init());
+    #[cfg(desktop)]
+    let builder = builder.plugin(
+        tauri_plugin_global_shortcut::Builder::new()
+            .with_handler(shortcuts::handle)
+            .build(),
+    );
+    let app = builder
+        .manage(LocalDb::default())
         .manage(Mutex::new(ReviewState {
-            queue: Vec::new(),
             session_limit: 10,
+            ..ReviewState::default()
         }))
+        .setup(|app| {
+            let handle = app.handle().clone();
+            perf::set_app(&handle);
+            shortcuts::register_saved(&handle);
+            push::restart(&handle);
+            std::thread::spawn(move || maintenance::run_if_due(&handle));
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             start_session,
+            session_progress,
+            session_summary,
+            undo_grade,
+            peek_session,
+            counts,
             next_due_card,
+            current_card,
             grade_card,
+            grade_sentence_card,
+            record_reviews_batch,
+            save_example,
+            explain_schedule,
+            card_state::bulk_set_card_state,
+            suspend::suspend_card,
+            suspend::bury_card,
+            suspend::unsuspend_card,
+            search::global_search,
+            search::search_words,
+            learning_curve,
+            export_ics,
+            get_audio_cues,
+            set_audio_cues,
+            get_session_settings,
+            set_session_settings,
+            get_ai_settings,
+            set_ai_settings,
+            get_ui_prefs,
+            get_card_settings,
+            get_language_pair,
+            set_language_pair,
+            set_ui_prefs,
+            set_card_settings,
+            add_reverse_cards,
+            cloze::add_cloze_cards,
+            cloze::grade_cloze_card,
+            answer_check::check_answer,
+            get_scheduler_settings,
+            set_scheduler_settings,
+            get_sync_settings,
+            set_sync_settings,
+            validate_settings,
+            get_tts_settings,
+            set_tts_settings,
+            list_voices,
+            pronunciation::fetch_audio,
+            sync_settings,
             report_issue,
             apply_correction,
             apply_correction_local,
+            corrections::recent_corrections,
+            corrections::revert_correction,
             add_word_local,
             delete_word_local,
             list_concepts,
+            list_tags,
             add_concept_local,
-            refresh_from_postgres,
+            integrity::check_integrity,
+            refresh::refresh_from_postgres,
+            sync::sync_with_postgres,
             refresh_from_data_api,
+            diff_against_remote,
+            perf::perf_report,
+            social::get_social_settings,
+            social::set_social_settings,
+            social::leaderboard,
+            usage::get_usage_settings,
+            usage::set_usage_settings,
+            usage::usage_report,
+            usage::export_usage,
+            usage::clear_usage,
+            maintenance::run_maintenance,
+            maintenance::get_maintenance_settings,
+            maintenance::set_maintenance_settings,
+            onboarding::onboarding_state,
+            onboarding::complete_onboarding_step,
+            onboarding::start_sample_session,
+            bundle::export_chapter,
+            bundle::import_chapter_bundle,
+            bundle::preview_chapter_bundle,
+            compact::toggle_compact_mode,
+            shortcuts::get_shortcut_settings,
+            shortcuts::set_shortcut_settings,
+            groups::groups_report,
+            groups::merge_groups,
+            seed::export_clean_db,
+            audio::next_due_card_audio,
+            report::export_report_pdf,
+            dashboard::stats,
+            decks::list_decks,
+            decks::create_deck,
+            decks::rename_deck,
+            decks::delete_deck,
+            decks::move_words_to_deck,
+            glossary::list_glossary,
+            glossary::set_glossary_entry,
+            glossary::delete_glossary_entry,
+            quiz::generate_quiz,
+            quiz::answer_quiz,
+            recovery::recovery_status,
+            recovery::start_recovery,
+            recovery::dismiss_recovery,
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application");
 
     app.run(|app, event| {
+        if let tauri::RunEvent::Exit = event
+            && let Err(err) = local_db(app).and_then(|conn| perf::flush(&conn))
+        {
+            log_error(&format!("perf_log flush failed: {err}"));
+        }
         #[cfg(any(target_os = "macos", target_os = "ios"))]
         if let tauri::RunEvent::Opened { urls } = event {
             let payload: Vec<String> = urls.into_iter().map(|url| url.to_string()).collect();
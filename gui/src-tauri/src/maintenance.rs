@@ -0,0 +1,172 @@
+//! Database housekeeping. Full-refresh syncs delete and reinsert every row,
+//! which leaves `words.db` full of free pages; `run_maintenance` compacts it,
+//! refreshes the planner statistics, and trims the local logs.
+
+use std::io::{Read, Seek, SeekFrom};
+
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use tauri::command;
+
+use crate::{
+    emit_settings_changed, get_setting, local_db, log_error, perf, set_setting, sql_log_path,
+};
+
+pub const SETTINGS_KEY: &str = "maintenance";
+const MONTHLY_INTERVAL_DAYS: i64 = 30;
+/// The SQL log is cut back to roughly this many of its most recent bytes.
+const SQL_LOG_KEEP_BYTES: u64 = 1024 * 1024;
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct MaintenanceSettings {
+    /// Run automatically at startup when the last run is a month old.
+    #[serde(default)]
+    monthly: bool,
+    #[serde(default)]
+    last_run_at: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MaintenanceReport {
+    size_before_bytes: u64,
+    size_after_bytes: u64,
+    reclaimed_bytes: u64,
+    perf_rows_pruned: usize,
+    sql_log_bytes_trimmed: u64,
+    /// Full-text indexes that were rebuilt.
+    fts_tables: Vec<String>,
+    ran_at: String,
+}
+
+/// Runs VACUUM, ANALYZE, and an FTS rebuild, prunes `perf_log` and the SQL
+/// log, and reports how much space was reclaimed.
+#[command]
+pub fn run_maintenance(app: tauri::AppHandle) -> Result<MaintenanceReport, String> {
+    let _perf = perf::track("run_maintenance");
+    let conn = local_db(&app)?;
+    // Buffered samples would otherwise land after the prune.
+    perf::flush(&conn)?;
+    maintain(&conn)
+}
+
+#[command]
+pub fn get_maintenance_settings(app: tauri::AppHandle) -> Result<MaintenanceSettings, String> {
+    let _perf = perf::track("get_maintenance_settings");
+    let conn = local_db(&app)?;
+    Ok(get_setting(&conn, SETTINGS_KEY)?.unwrap_or_default())
+}
+
+#[command]
+pub fn set_maintenance_settings(
+    app: tauri::AppHandle,
+    monthly: bool,
+) -> Result<MaintenanceSettings, String> {
+    let _perf = perf::track("set_maintenance_settings");
+    let conn = local_db(&app)?;
+    let mut settings: MaintenanceSettings = get_setting(&conn, SETTINGS_KEY)?.unwrap_or_default();
+    settings.monthly = monthly;
+    set_setting(&conn, SETTINGS_KEY, &settings)?;
+    emit_settings_changed(&app, SETTINGS_KEY);
+    Ok(settings)
+}
+
+/// Called once at startup, off the main thread.
+pub fn run_if_due(app: &tauri::AppHandle) {
+    let result = local_db(app).and_then(|conn| {
+        let settings: MaintenanceSettings = get_setting(&conn, SETTINGS_KEY)?.unwrap_or_default();
+        let last_run = settings
+            .last_run_at
+            .as_deref()
+            .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+            .map(|value| value.with_timezone(&Utc));
+        let due =
+            last_run.is_none_or(|last| Utc::now() - last >= Duration::days(MONTHLY_INTERVAL_DAYS));
+        if settings.monthly && due {
+            maintain(&conn)?;
+        }
+        Ok(())
+    });
+    if let Err(err) = result {
+        log_error(&format!("scheduled maintenance failed: {err}"));
+    }
+}
+
+fn maintain(conn: &Connection) -> Result<MaintenanceReport, String> {
+    let size_before_bytes = database_size(conn)?;
+    let perf_rows_pruned = perf::prune(conn)?;
+    let fts_tables = fts_tables(conn)?;
+    for table in &fts_tables {
+        conn.execute(
+            &format!("INSERT INTO \"{table}\"(\"{table}\") VALUES ('rebuild')"),
+            [],
+        )
+        .map_err(|err| format!("Rebuilding {table} failed: {err}"))?;
+    }
+    conn.execute_batch("VACUUM; ANALYZE;")
+        .map_err(|err| err.to_string())?;
+    let size_after_bytes = database_size(conn)?;
+    let sql_log_bytes_trimmed = trim_sql_log()?;
+
+    let ran_at = Utc::now().to_rfc3339();
+    let mut settings: MaintenanceSettings = get_setting(conn, SETTINGS_KEY)?.unwrap_or_default();
+    settings.last_run_at = Some(ran_at.clone());
+    set_setting(conn, SETTINGS_KEY, &settings)?;
+    Ok(MaintenanceReport {
+        size_before_bytes,
+        size_after_bytes,
+        reclaimed_bytes: size_before_bytes.saturating_sub(size_after_bytes),
+        perf_rows_pruned,
+        sql_log_bytes_trimmed,
+        fts_tables,
+        ran_at,
+    })
+}
+
+fn database_size(conn: &Connection) -> Result<u64, String> {
+    let pages: u64 = conn
+        .query_row("PRAGMA page_count", [], |row| row.get(0))
+        .map_err(|err| err.to_string())?;
+    let page_size: u64 = conn
+        .query_row("PRAGMA page_size", [], |row| row.get(0))
+        .map_err(|err| err.to_string())?;
+    Ok(pages * page_size)
+}
+
+fn fts_tables(conn: &Connection) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT name FROM sqlite_master
+             WHERE type = 'table' AND lower(sql) LIKE 'create virtual table%using fts%'
+             ORDER BY name",
+        )
+        .map_err(|err| err.to_string())?;
+    stmt.query_map([], |row| row.get(0))
+        .map_err(|err| err.to_string())?
+        .collect::<rusqlite::Result<Vec<String>>>()
+        .map_err(|err| err.to_string())
+}
+
+/// Keeps the tail of the `LOG_SQL_PATH` file, starting at a line boundary.
+fn trim_sql_log() -> Result<u64, String> {
+    let Some(path) = sql_log_path() else {
+        return Ok(0);
+    };
+    let Ok(mut file) = std::fs::File::open(&path) else {
+        return Ok(0);
+    };
+    let len = file.metadata().map_err(|err| err.to_string())?.len();
+    if len <= SQL_LOG_KEEP_BYTES {
+        return Ok(0);
+    }
+    file.seek(SeekFrom::Start(len - SQL_LOG_KEEP_BYTES))
+        .map_err(|err| err.to_string())?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail).map_err(|err| err.to_string())?;
+    let start = tail
+        .iter()
+        .position(|byte| *byte == b'\n')
+        .map_or(0, |idx| idx + 1);
+    std::fs::write(&path, &tail[start..]).map_err(|err| err.to_string())?;
+    Ok(len - (tail.len() - start) as u64)
+}
@@ -0,0 +1,276 @@
+//! First-run wizard state. Progress is a local-only setting; configuration
+//! checks are recomputed on every call so fixing a problem outside the app
+//! (editing `.env`, saving sync settings) shows up without a restart.
+
+use std::sync::Mutex;
+
+use chrono::Utc;
+use le_core::{EntryKind, SourceKind, default_new_card};
+use rusqlite::{Connection, params};
+use serde::{Deserialize, Serialize};
+use tauri::{State, command};
+use uuid::Uuid;
+
+use crate::{
+    ReviewState, SessionFilter, SyncSettings, get_setting, local_db, perf, set_setting,
+    start_session,
+};
+
+pub const SETTINGS_KEY: &str = "onboarding";
+/// Chapter the sample words are filed under, so the sample session and a
+/// later cleanup can find them.
+const SAMPLE_CHAPTER: &str = "Getting started";
+const SAMPLE_WORDS: [(&str, &str); 8] = [
+    ("de hond", "the dog"),
+    ("het huis", "the house"),
+    ("lopen", "to walk"),
+    ("eten", "to eat"),
+    ("de fiets", "the bicycle"),
+    ("mooi", "beautiful"),
+    ("morgen", "tomorrow"),
+    ("het water", "the water"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnboardingStep {
+    Welcome,
+    Sync,
+    FirstWords,
+    SampleSession,
+}
+
+const STEPS: [OnboardingStep; 4] = [
+    OnboardingStep::Welcome,
+    OnboardingStep::Sync,
+    OnboardingStep::FirstWords,
+    OnboardingStep::SampleSession,
+];
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct OnboardingProgress {
+    #[serde(default)]
+    completed: Vec<OnboardingStep>,
+    #[serde(default)]
+    completed_at: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OnboardingState {
+    steps: Vec<StepStatus>,
+    /// First step not yet completed; `None` once the wizard is finished.
+    next_step: Option<OnboardingStep>,
+    complete: bool,
+    word_count: i64,
+    checks: Vec<ConfigCheck>,
+}
+
+#[derive(Debug, Serialize)]
+struct StepStatus {
+    step: OnboardingStep,
+    done: bool,
+}
+
+/// One piece of configuration the app can run without, and what to do
+/// about it when it is missing.
+#[derive(Debug, Serialize)]
+struct ConfigCheck {
+    key: &'static str,
+    ok: bool,
+    message: String,
+    /// What to change; `None` when the check passes.
+    action: Option<String>,
+}
+
+#[command]
+pub fn onboarding_state(app: tauri::AppHandle) -> Result<OnboardingState, String> {
+    let _perf = perf::track("onboarding_state");
+    let conn = local_db(&app)?;
+    current_state(&conn)
+}
+
+/// Marks `step` done. Steps may be completed in any order; the wizard is
+/// finished once all of them are.
+#[command]
+pub fn complete_onboarding_step(
+    app: tauri::AppHandle,
+    step: OnboardingStep,
+) -> Result<OnboardingState, String> {
+    let _perf = perf::track("complete_onboarding_step");
+    let conn = local_db(&app)?;
+    let mut progress: OnboardingProgress = get_setting(&conn, SETTINGS_KEY)?.unwrap_or_default();
+    if !progress.completed.contains(&step) {
+        progress.completed.push(step);
+    }
+    if progress.completed_at.is_none() && STEPS.iter().all(|s| progress.completed.contains(s)) {
+        progress.completed_at = Some(Utc::now().to_rfc3339());
+    }
+    set_setting(&conn, SETTINGS_KEY, &progress)?;
+    current_state(&conn)
+}
+
+/// Adds a handful of common Dutch words under the "Getting started" chapter
+/// (once) and starts a session over just those. Returns how many sample
+/// words were added.
+#[command]
+pub fn start_sample_session(
+    app: tauri::AppHandle,
+    state: State<'_, Mutex<ReviewState>>,
+) -> Result<usize, String> {
+    let _perf = perf::track("start_sample_session");
+    let added = {
+        let mut conn = local_db(&app)?;
+        seed_sample_words(&mut conn)?
+    };
+    start_session(
+        app,
+        state,
+        Some(SessionFilter {
+            deck: None,
+            chapter: Some(SAMPLE_CHAPTER.to_string()),
+            group: None,
+            tag: None,
+        }),
+        None,
+        None,
+    )?;
+    Ok(added)
+}
+
+fn seed_sample_words(conn: &mut Connection) -> Result<usize, String> {
+    let tx = conn.transaction().map_err(|err| err.to_string())?;
+    let seeded: i64 = tx
+        .query_row(
+            "SELECT COUNT(*) FROM words WHERE chapter = ?1",
+            params![SAMPLE_CHAPTER],
+            |row| row.get(0),
+        )
+        .map_err(|err| err.to_string())?;
+    if seeded > 0 {
+        return Ok(0);
+    }
+    let settings = le_db::cards::card_settings(&tx).map_err(|err| err.to_string())?;
+    let now = Utc::now();
+    for (text, translation) in SAMPLE_WORDS {
+        let word_id = Uuid::new_v4();
+        tx.execute(
+            "INSERT INTO words (id, text, language, translation, chapter, group_name, notes,
+                                created_at, source_type, source_name, captured_at, kind)
+             VALUES (?1, ?2, 'Dutch', ?3, ?4, NULL, NULL, ?5, ?6, 'Sample deck', ?5, ?7)",
+            params![
+                word_id.to_string(),
+                text,
+                translation,
+                SAMPLE_CHAPTER,
+                now.to_rfc3339(),
+                format!("{:?}", SourceKind::Manual),
+                format!("{:?}", EntryKind::Word)
+            ],
+        )
+        .map_err(|err| err.to_string())?;
+        let card = default_new_card(word_id, now);
+        tx.execute(
+            "INSERT INTO cards (id, word_id, due_at, interval_days, ease, reps, lapses, seen_count)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 0)",
+            params![
+                card.id.to_string(),
+                word_id.to_string(),
+                card.due_at.to_rfc3339(),
+                card.interval_days,
+                card.ease,
+                card.reps,
+                card.lapses
+            ],
+        )
+        .map_err(|err| err.to_string())?;
+        le_db::cards::add_reverse_card(
+            &tx,
+            &settings,
+            word_id,
+            EntryKind::Word,
+            Some(translation),
+            now,
+        )
+        .map_err(|err| err.to_string())?;
+    }
+    tx.commit().map_err(|err| err.to_string())?;
+    Ok(SAMPLE_WORDS.len())
+}
+
+fn current_state(conn: &Connection) -> Result<OnboardingState, String> {
+    let progress: OnboardingProgress = get_setting(conn, SETTINGS_KEY)?.unwrap_or_default();
+    let steps: Vec<StepStatus> = STEPS
+        .iter()
+        .map(|&step| StepStatus {
+            step,
+            done: progress.completed.contains(&step),
+        })
+        .collect();
+    let next_step = steps
+        .iter()
+        .find(|status| !status.done)
+        .map(|status| status.step);
+    let word_count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM words", [], |row| row.get(0))
+        .map_err(|err| err.to_string())?;
+    Ok(OnboardingState {
+        steps,
+        next_step,
+        complete: next_step.is_none(),
+        word_count,
+        checks: config_checks(conn, word_count)?,
+    })
+}
+
+fn config_checks(conn: &Connection, word_count: i64) -> Result<Vec<ConfigCheck>, String> {
+    let env_set = |name: &str| std::env::var(name).is_ok_and(|value| !value.trim().is_empty());
+    let sync: SyncSettings = get_setting(conn, "sync")?.unwrap_or_default();
+    let server_url = sync
+        .server_url
+        .as_deref()
+        .map(str::trim)
+        .filter(|url| !url.is_empty());
+
+    Ok(vec![
+        check(
+            "deck",
+            word_count > 0,
+            format!("{word_count} words in the local deck"),
+            "Add a word, import a textbook page, or start the sample session",
+        ),
+        check(
+            "translation_api",
+            env_set("TRANSLATION_API_URL"),
+            "Automatic translations for imports".to_string(),
+            "Set TRANSLATION_API_URL (and TRANSLATION_API_KEY) in .env, e.g. the DeepL endpoint",
+        ),
+        check(
+            "sync_server",
+            server_url.is_some(),
+            match server_url {
+                Some(url) => format!("Settings sync with {url}"),
+                None => "Settings sync".to_string(),
+            },
+            "Enter the auth server URL under Settings > Sync",
+        ),
+        check(
+            "postgres",
+            cfg!(feature = "remote-sync") && env_set("DATABASE_URL"),
+            "Refreshing the deck from Postgres".to_string(),
+            if cfg!(feature = "remote-sync") {
+                "Set DATABASE_URL to the Neon connection string"
+            } else {
+                "Rebuild with the remote-sync feature and set DATABASE_URL"
+            },
+        ),
+    ])
+}
+
+fn check(key: &'static str, ok: bool, message: String, action: &str) -> ConfigCheck {
+    ConfigCheck {
+        key,
+        ok,
+        message,
+        action: (!ok).then(|| action.to_string()),
+    }
+}
@@ -0,0 +1,225 @@
+//! Per-command latency samples. Every command holds a [`Span`] while it runs;
+//! SQLite time (from the connection profiler installed by `open_db`) and
+//! time inside [`network`] calls on the same thread are attributed to it.
+//! Samples are buffered and written to the local `perf_log` table in batches
+//! through the shared connection, so timing does not add a write to every
+//! grade.
+
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use rusqlite::{Connection, params};
+use serde::Serialize;
+use tauri::command;
+
+use crate::try_local_db;
+
+const FLUSH_EVERY: usize = 20;
+const RETENTION_DAYS: i64 = 30;
+
+thread_local! {
+    static DB_TIME: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+    static NETWORK_TIME: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+}
+
+static PENDING: Mutex<Vec<PerfSample>> = Mutex::new(Vec::new());
+static APP: OnceLock<tauri::AppHandle> = OnceLock::new();
+
+struct PerfSample {
+    command: &'static str,
+    started_at: String,
+    total: Duration,
+    db: Duration,
+    network: Duration,
+}
+
+/// Latency summary for one command, in milliseconds.
+#[derive(Debug, Serialize)]
+pub struct PerfStat {
+    command: String,
+    calls: usize,
+    p50_ms: f64,
+    p95_ms: f64,
+    max_ms: f64,
+    avg_db_ms: f64,
+    avg_network_ms: f64,
+}
+
+/// Times a command from creation until drop.
+pub struct Span {
+    command: &'static str,
+    started: Instant,
+    started_at: String,
+    db_before: Duration,
+    network_before: Duration,
+}
+
+pub fn track(command: &'static str) -> Span {
+    Span {
+        command,
+        started: Instant::now(),
+        started_at: Utc::now().to_rfc3339(),
+        db_before: DB_TIME.get(),
+        network_before: NETWORK_TIME.get(),
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        let sample = PerfSample {
+            command: self.command,
+            started_at: std::mem::take(&mut self.started_at),
+            total: self.started.elapsed(),
+            db: DB_TIME.get().saturating_sub(self.db_before),
+            network: NETWORK_TIME.get().saturating_sub(self.network_before),
+        };
+        let Ok(mut pending) = PENDING.lock() else {
+            return;
+        };
+        pending.push(sample);
+        let full = pending.len() >= FLUSH_EVERY;
+        drop(pending);
+        if full && let Err(err) = flush_shared() {
+            crate::log_error(&format!("perf_log flush failed: {err}"));
+        }
+    }
+}
+
+/// Installed with `Connection::profile`; called after every statement.
+pub fn record_sql(_sql: &str, elapsed: Duration) {
+    DB_TIME.set(DB_TIME.get() + elapsed);
+}
+
+/// Runs `call` and counts its time as network time for the current command.
+pub fn network<T>(call: impl FnOnce() -> T) -> T {
+    let started = Instant::now();
+    let result = call();
+    NETWORK_TIME.set(NETWORK_TIME.get() + started.elapsed());
+    result
+}
+
+/// Remembers the app so buffered samples can be written from commands that
+/// never open the database.
+pub fn set_app(app: &tauri::AppHandle) {
+    let _ = APP.set(app.clone());
+}
+
+/// [`flush`] through the shared connection. When another command holds it,
+/// the samples stay buffered for the next span rather than waiting.
+fn flush_shared() -> Result<(), String> {
+    let Some(app) = APP.get() else {
+        return Ok(());
+    };
+    match try_local_db(app)? {
+        Some(conn) => flush(&conn),
+        None => Ok(()),
+    }
+}
+
+/// Writes buffered samples to `perf_log` and drops rows past the retention
+/// window.
+pub fn flush(conn: &Connection) -> Result<(), String> {
+    let samples = std::mem::take(&mut *PENDING.lock().map_err(|err| err.to_string())?);
+    if samples.is_empty() {
+        return Ok(());
+    }
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|err| err.to_string())?;
+    for sample in &samples {
+        tx.execute(
+            "INSERT INTO perf_log (command, started_at, total_ms, db_ms, network_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                sample.command,
+                sample.started_at,
+                millis(sample.total),
+                millis(sample.db),
+                millis(sample.network)
+            ],
+        )
+        .map_err(|err| err.to_string())?;
+    }
+    prune(&tx)?;
+    tx.commit().map_err(|err| err.to_string())
+}
+
+/// Deletes samples older than the retention window; returns how many.
+pub fn prune(conn: &Connection) -> Result<usize, String> {
+    let cutoff = (Utc::now() - chrono::Duration::days(RETENTION_DAYS)).to_rfc3339();
+    conn.execute(
+        "DELETE FROM perf_log WHERE started_at < ?1",
+        params![cutoff],
+    )
+    .map_err(|err| err.to_string())
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// p50/p95/max latency per command over the last `days` days (default 7),
+/// slowest p95 first.
+#[command]
+pub fn perf_report(app: tauri::AppHandle, days: Option<u32>) -> Result<Vec<PerfStat>, String> {
+    let conn = crate::local_db(&app)?;
+    flush(&conn)?;
+    let since = (Utc::now() - chrono::Duration::days(i64::from(days.unwrap_or(7)))).to_rfc3339();
+    let mut stmt = conn
+        .prepare(
+            "SELECT command, total_ms, db_ms, network_ms FROM perf_log
+             WHERE started_at >= ?1",
+        )
+        .map_err(|err| err.to_string())?;
+    let rows = stmt
+        .query_map(params![since], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, f64>(1)?,
+                row.get::<_, f64>(2)?,
+                row.get::<_, f64>(3)?,
+            ))
+        })
+        .map_err(|err| err.to_string())?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|err| err.to_string())?;
+
+    let mut by_command: BTreeMap<String, Vec<(f64, f64, f64)>> = BTreeMap::new();
+    for (command, total, db, network) in rows {
+        by_command
+            .entry(command)
+            .or_default()
+            .push((total, db, network));
+    }
+    let mut stats: Vec<PerfStat> = by_command
+        .into_iter()
+        .map(|(command, mut samples)| {
+            samples.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let calls = samples.len();
+            let totals: Vec<f64> = samples.iter().map(|sample| sample.0).collect();
+            PerfStat {
+                command,
+                calls,
+                p50_ms: percentile(&totals, 0.50),
+                p95_ms: percentile(&totals, 0.95),
+                max_ms: totals.last().copied().unwrap_or(0.0),
+                avg_db_ms: samples.iter().map(|sample| sample.1).sum::<f64>() / calls as f64,
+                avg_network_ms: samples.iter().map(|sample| sample.2).sum::<f64>() / calls as f64,
+            }
+        })
+        .collect();
+    stats.sort_by(|a, b| b.p95_ms.total_cmp(&a.p95_ms));
+    Ok(stats)
+}
+
+/// Nearest-rank percentile of an ascending slice.
+fn percentile(sorted: &[f64], fraction: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (fraction * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
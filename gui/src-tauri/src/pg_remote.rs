@@ -0,0 +1,605 @@
+//! [`RemoteSync`] over a direct Postgres connection to `DATABASE_URL`,
+//! built with the `remote-sync` feature.
+//!
+//! Push sync `LISTEN`s on [`CHANNEL`]; the triggers in the README
+//! `pg_notify` one `{table, op, id}` payload per changed row. Bursts are
+//! collected until they go quiet for [`DEBOUNCE`], then only the changed
+//! rows are fetched and applied locally, and the frontend gets a
+//! `remote-changed` event.
+//!
+//! A refresh copies each table with exponential backoff, reconnecting when
+//! the connection drops.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use le_client::connectivity;
+use native_tls::TlsConnector;
+use postgres::fallible_iterator::FallibleIterator;
+use postgres::types::ToSql;
+use postgres::{Client, Row};
+use postgres_native_tls::MakeTlsConnector;
+use rusqlite::{Connection, params};
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager};
+
+use crate::remote::{RemoteSync, WordUpdate};
+use crate::sync::{self, Field, Kind, Rows, Table, TableMerge};
+use crate::{CardRow, ReviewState, WordSummary, local_db, log_error, log_sql, perf};
+
+pub const CHANNEL: &str = "le_changes";
+const DEBOUNCE: Duration = Duration::from_secs(2);
+const MAX_ATTEMPTS: u32 = 5;
+/// Wait before the first retry of a refresh query; doubled after every
+/// failed attempt.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Refresh queries by table, in the column order the upserts below expect.
+const REFRESH_QUERIES: [(&str, &str); 7] = [
+    (
+        "words",
+        "SELECT id, text, language, translation, chapter, group_name, notes, created_at,
+                source_type, source_name, source_page, captured_at, source_region,
+                alt_answers, kind, deck_id
+         FROM words",
+    ),
+    (
+        "cards",
+        "SELECT id, word_id, due_at, interval_days, ease, reps, lapses, deck_id, card_type
+         FROM cards",
+    ),
+    (
+        "reviews",
+        "SELECT id, card_id, grade, reviewed_at FROM reviews",
+    ),
+    ("concepts", "SELECT id, name, created_at FROM concepts"),
+    ("tags", "SELECT id, name, created_at FROM tags"),
+    ("word_tags", "SELECT word_id, tag_id FROM word_tags"),
+    ("decks", "SELECT id, name, created_at FROM decks"),
+];
+
+pub struct PostgresRemote {
+    url: String,
+    client: Option<Client>,
+}
+
+impl PostgresRemote {
+    pub fn new() -> Result<Self, String> {
+        let url = std::env::var("DATABASE_URL")
+            .map_err(|_| "DATABASE_URL is required for Postgres sync".to_string())?;
+        Ok(Self { url, client: None })
+    }
+
+    fn connected(&mut self) -> Result<&mut Client, String> {
+        if self.client.is_none() {
+            self.client = Some(connect(&self.url)?);
+        }
+        self.client
+            .as_mut()
+            .ok_or_else(|| "postgres connection missing".to_string())
+    }
+
+    /// Runs `query`, reconnecting and retrying with exponential backoff when
+    /// the connection fails. Errors reported by the server, such as a missing
+    /// table, are returned straight away since a retry would not change them.
+    fn query_with_backoff(&mut self, table: &str, query: &str) -> Result<Vec<Row>, String> {
+        let mut delay = INITIAL_BACKOFF;
+        let mut attempt = 1;
+        loop {
+            let error = match self.connected() {
+                Ok(conn) => {
+                    log_sql(query, &[]);
+                    match perf::network(|| conn.query(query, &[])) {
+                        Ok(rows) => return Ok(rows),
+                        Err(err) if err.as_db_error().is_some() => {
+                            return Err(format!("select {table} failed: {err}"));
+                        }
+                        Err(err) => err.to_string(),
+                    }
+                }
+                Err(err) => err,
+            };
+            self.client = None;
+            if attempt >= MAX_ATTEMPTS {
+                return Err(format!(
+                    "select {table} failed after {attempt} attempts: {error}"
+                ));
+            }
+            log_error(&format!(
+                "refresh_from_postgres: select {table} failed (attempt {attempt}), retrying in {}s: {error}",
+                delay.as_secs()
+            ));
+            std::thread::sleep(delay);
+            delay *= 2;
+            attempt += 1;
+        }
+    }
+}
+
+fn connect(url: &str) -> Result<Client, String> {
+    let connector = TlsConnector::new().map_err(|err| err.to_string())?;
+    let connector = MakeTlsConnector::new(connector);
+    perf::network(|| Client::connect(url, connector)).map_err(|err| {
+        if connectivity::is_offline(url) {
+            "Offline — the database cannot be reached".to_string()
+        } else {
+            err.to_string()
+        }
+    })
+}
+
+impl RemoteSync for PostgresRemote {
+    fn is_offline(&self) -> bool {
+        connectivity::is_offline(&self.url)
+    }
+
+    fn load_rows(&mut self, table: &Table) -> Result<Rows, String> {
+        let query = format!("SELECT {} FROM {}", sync::columns(table), table.name);
+        log_sql(&query, &[]);
+        let client = self.connected()?;
+        let rows = perf::network(|| client.query(&query, &[]))
+            .map_err(|err| sync::fail(&format!("select {}", table.name), err))?;
+        let mut loaded = Rows::new();
+        for row in rows {
+            let mut fields = sync::Row::with_capacity(table.columns.len());
+            for (index, (_, kind)) in table.columns.iter().enumerate() {
+                let field = match kind {
+                    Kind::Text => row
+                        .try_get::<_, Option<String>>(index)
+                        .map(|v| v.map(Field::Text)),
+                    Kind::Int => row
+                        .try_get::<_, Option<i32>>(index)
+                        .map(|v| v.map(|v| Field::Int(v.into()))),
+                    Kind::Real => row
+                        .try_get::<_, Option<f64>>(index)
+                        .map(|v| v.map(Field::Real)),
+                };
+                fields.push(field.map_err(|err| sync::fail(&format!("read {}", table.name), err))?);
+            }
+            loaded.insert(sync::row_id(&fields), fields);
+        }
+        Ok(loaded)
+    }
+
+    fn push_merge(&mut self, merges: &[(&Table, &TableMerge)]) -> Result<(usize, usize), String> {
+        let (mut pushed, mut deleted) = (0, 0);
+        let client = self.connected()?;
+        let mut tx = client
+            .transaction()
+            .map_err(|err| sync::fail("begin postgres", err))?;
+        for (table, merge) in merges {
+            for row in &merge.upload {
+                let query = sync::upsert_sql(table, '$');
+                log_sql(&query, &[("id", sync::row_id(row))]);
+                let values = pg_params(table, row);
+                let refs: Vec<&(dyn ToSql + Sync)> =
+                    values.iter().map(|value| value.as_ref()).collect();
+                perf::network(|| tx.execute(&query, &refs))
+                    .map_err(|err| sync::fail(&format!("push {}", table.name), err))?;
+                pushed += 1;
+            }
+        }
+        for (table, merge) in merges.iter().rev() {
+            for id in &merge.remove_remote {
+                let query = format!("DELETE FROM {} WHERE id = $1", table.name);
+                log_sql(&query, &[("id", id.clone())]);
+                perf::network(|| tx.execute(&query, &[id]))
+                    .map_err(|err| sync::fail(&format!("delete remote {}", table.name), err))?;
+                deleted += 1;
+            }
+        }
+        perf::network(|| tx.commit()).map_err(|err| sync::fail("commit postgres", err))?;
+        Ok((pushed, deleted))
+    }
+
+    fn copy_table(&mut self, tx: &Connection, table: &str) -> Result<usize, String> {
+        let (_, query) = REFRESH_QUERIES
+            .iter()
+            .find(|(name, _)| *name == table)
+            .ok_or_else(|| format!("{table} is not refreshed"))?;
+        let rows = self.query_with_backoff(table, query)?;
+        let write: fn(&Connection, &Row) -> Result<(), String> = match table {
+            "words" => upsert_word,
+            "cards" => upsert_card,
+            "reviews" => upsert_review,
+            "tags" => upsert_tag,
+            "word_tags" => upsert_word_tag,
+            "decks" => upsert_deck,
+            _ => upsert_concept,
+        };
+        for row in &rows {
+            write(tx, row)?;
+        }
+        Ok(rows.len())
+    }
+
+    fn wordlist(&mut self) -> Result<(Vec<WordSummary>, Vec<CardRow>), String> {
+        let client = self.connected()?;
+        let words = perf::network(|| client.query("SELECT id, text, translation FROM words", &[]))
+            .map_err(|err| format!("diff_against_remote: select words failed: {err}"))?
+            .into_iter()
+            .map(|row| WordSummary {
+                id: row.get(0),
+                text: row.get(1),
+                translation: row.get(2),
+            })
+            .collect();
+        let cards = perf::network(|| {
+            client.query(
+                "SELECT id, word_id, due_at, interval_days, ease, reps, lapses, deck_id, card_type FROM cards",
+                &[],
+            )
+        })
+        .map_err(|err| format!("diff_against_remote: select cards failed: {err}"))?
+        .into_iter()
+        .map(|row| CardRow {
+            id: row.get(0),
+            word_id: row.get(1),
+            due_at: row.get(2),
+            interval_days: row.get(3),
+            ease: row.get(4),
+            reps: row.get(5),
+            lapses: row.get(6),
+            deck_id: row.get(7),
+            card_type: row.get(8),
+        })
+        .collect();
+        Ok((words, cards))
+    }
+
+    fn update_word(&mut self, word_id: &str, update: &WordUpdate) -> Result<bool, String> {
+        let mut sets = Vec::new();
+        let mut values: Vec<&(dyn ToSql + Sync)> = Vec::new();
+        let mut logged = Vec::new();
+        if let Some(text) = &update.text {
+            values.push(text);
+            sets.push(format!("text = ${}", values.len()));
+            logged.push(("text", text.to_string()));
+        }
+        if let Some(translation) = &update.translation {
+            values.push(translation);
+            sets.push(format!("translation = ${}", values.len()));
+            logged.push(("translation", translation.unwrap_or_default().to_string()));
+        }
+        if let Some(alt_answers) = &update.alt_answers {
+            values.push(alt_answers);
+            sets.push(format!("alt_answers = ${}", values.len()));
+            logged.push(("alt_answers", alt_answers.unwrap_or_default().to_string()));
+        }
+        if sets.is_empty() {
+            return Ok(true);
+        }
+        values.push(&word_id);
+        logged.push(("id", word_id.to_string()));
+        let query = format!(
+            "UPDATE words SET {} WHERE id = ${}",
+            sets.join(", "),
+            values.len()
+        );
+        log_sql(&query, &logged);
+        let client = self.connected()?;
+        perf::network(|| client.execute(&query, &values))
+            .map(|affected| affected > 0)
+            .map_err(|err| err.to_string())
+    }
+
+    /// Runs until `stop` is set or the connection drops.
+    fn listen(&mut self, app: &tauri::AppHandle, stop: &AtomicBool) -> Result<(), String> {
+        let client = self.connected()?;
+        client
+            .batch_execute(&format!("LISTEN {CHANNEL}"))
+            .map_err(|err| err.to_string())?;
+        while !stop.load(Ordering::Relaxed) {
+            let mut notices = Vec::new();
+            {
+                let mut pending = client.notifications();
+                let mut iter = pending.timeout_iter(DEBOUNCE);
+                while let Some(notification) = iter.next().map_err(|err| err.to_string())? {
+                    match serde_json::from_str::<ChangeNotice>(notification.payload()) {
+                        Ok(notice) => notices.push(notice),
+                        Err(err) => log_error(&format!("push sync: bad payload: {err}")),
+                    }
+                }
+            }
+            if client.is_closed() {
+                return Err("connection closed".to_string());
+            }
+            if notices.is_empty() {
+                continue;
+            }
+            let changes = apply(app, client, notices)?;
+            let _ = app.emit("remote-changed", changes);
+        }
+        Ok(())
+    }
+}
+
+fn pg_params(table: &Table, row: &sync::Row) -> Vec<Box<dyn ToSql + Sync>> {
+    table
+        .columns
+        .iter()
+        .zip(row)
+        .map(|((_, kind), value)| -> Box<dyn ToSql + Sync> {
+            match (kind, value) {
+                (Kind::Int, Some(Field::Int(value))) => Box::new(Some(*value as i32)),
+                (Kind::Int, _) => Box::new(None::<i32>),
+                (Kind::Real, Some(Field::Real(value))) => Box::new(Some(*value)),
+                (Kind::Real, Some(Field::Int(value))) => Box::new(Some(*value as f64)),
+                (Kind::Real, _) => Box::new(None::<f64>),
+                (Kind::Text, Some(Field::Text(value))) => Box::new(Some(value.clone())),
+                (Kind::Text, _) => Box::new(None::<String>),
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Deserialize)]
+struct ChangeNotice {
+    table: String,
+    op: String,
+    id: String,
+}
+
+/// Emitted as `remote-changed` after a batch of pushed changes is applied.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct RemoteChanges {
+    words: usize,
+    cards: usize,
+    reviews: usize,
+    concepts: usize,
+    deleted: usize,
+}
+
+/// A changed row as fetched from Postgres, or a deletion.
+enum RemoteRow {
+    Word(Row),
+    Card(Row),
+    Review(Row),
+    Concept(Row),
+    Deleted { table: String, id: String },
+}
+
+fn apply(
+    app: &tauri::AppHandle,
+    client: &mut Client,
+    notices: Vec<ChangeNotice>,
+) -> Result<RemoteChanges, String> {
+    // Only the last change to each row matters.
+    let mut latest: BTreeMap<(String, String), String> = BTreeMap::new();
+    for notice in notices {
+        latest.insert((notice.table, notice.id), notice.op);
+    }
+    // Fetch everything before taking the local database, so grading is not
+    // held up by the network.
+    let mut rows = Vec::new();
+    for ((table, id), op) in latest {
+        if op.eq_ignore_ascii_case("DELETE") {
+            rows.push(RemoteRow::Deleted { table, id });
+        } else if let Some(row) = fetch(client, &table, &id)? {
+            rows.push(row);
+        }
+    }
+
+    let mut changes = RemoteChanges::default();
+    let mut deleted_cards = Vec::new();
+    let mut conn = local_db(app)?;
+    let tx = conn.transaction().map_err(|err| err.to_string())?;
+    for row in &rows {
+        match row {
+            RemoteRow::Word(row) => {
+                upsert_word(&tx, row)?;
+                changes.words += 1;
+            }
+            RemoteRow::Card(row) => {
+                upsert_card(&tx, row)?;
+                changes.cards += 1;
+            }
+            RemoteRow::Review(row) => {
+                upsert_review(&tx, row)?;
+                changes.reviews += 1;
+            }
+            RemoteRow::Concept(row) => {
+                upsert_concept(&tx, row)?;
+                changes.concepts += 1;
+            }
+            RemoteRow::Deleted { table, id } => {
+                if delete_local(&tx, table, id)? {
+                    changes.deleted += 1;
+                    if table == "cards" {
+                        deleted_cards.push(id.clone());
+                    }
+                }
+            }
+        }
+    }
+    tx.commit().map_err(|err| err.to_string())?;
+    drop(conn);
+
+    if !deleted_cards.is_empty()
+        && let Ok(mut guard) = app.state::<Mutex<ReviewState>>().lock()
+    {
+        guard
+            .session
+            .remove(|card_id| deleted_cards.contains(card_id));
+    }
+    Ok(changes)
+}
+
+/// `None` when the row is gone again or the table is not synced.
+fn fetch(client: &mut Client, table: &str, id: &str) -> Result<Option<RemoteRow>, String> {
+    let (query, wrap): (&str, fn(Row) -> RemoteRow) = match table {
+        "words" => (
+            "SELECT id, text, language, translation, chapter, group_name, notes, created_at,
+                    source_type, source_name, source_page, captured_at, source_region,
+                    alt_answers, kind, deck_id
+             FROM words WHERE id = $1",
+            RemoteRow::Word,
+        ),
+        "cards" => (
+            "SELECT id, word_id, due_at, interval_days, ease, reps, lapses, deck_id, card_type
+             FROM cards WHERE id = $1",
+            RemoteRow::Card,
+        ),
+        "reviews" => (
+            "SELECT id, card_id, grade, reviewed_at FROM reviews WHERE id = $1",
+            RemoteRow::Review,
+        ),
+        "concepts" => (
+            "SELECT id, name, created_at FROM concepts WHERE id = $1",
+            RemoteRow::Concept,
+        ),
+        _ => return Ok(None),
+    };
+    log_sql(query, &[("$1", id.to_string())]);
+    perf::network(|| client.query_opt(query, &[&id]))
+        .map(|row| row.map(wrap))
+        .map_err(|err| format!("push sync: select {table} failed: {err}"))
+}
+
+fn delete_local(conn: &Connection, table: &str, id: &str) -> Result<bool, String> {
+    let query = match table {
+        "words" => "DELETE FROM words WHERE id = ?1",
+        "cards" => "DELETE FROM cards WHERE id = ?1",
+        "reviews" => "DELETE FROM reviews WHERE id = ?1",
+        "concepts" => "DELETE FROM concepts WHERE id = ?1",
+        _ => return Ok(false),
+    };
+    conn.execute(query, params![id])
+        .map(|rows| rows > 0)
+        .map_err(|err| err.to_string())
+}
+
+fn upsert_word(conn: &Connection, row: &Row) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO words (id, text, language, translation, chapter, group_name, notes, created_at,
+                            source_type, source_name, source_page, captured_at, source_region,
+                            alt_answers, kind, deck_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)
+         ON CONFLICT(id) DO UPDATE SET
+            text = excluded.text, language = excluded.language,
+            translation = excluded.translation, chapter = excluded.chapter,
+            group_name = excluded.group_name, notes = excluded.notes,
+            created_at = excluded.created_at, source_type = excluded.source_type,
+            source_name = excluded.source_name, source_page = excluded.source_page,
+            captured_at = excluded.captured_at, source_region = excluded.source_region,
+            alt_answers = excluded.alt_answers, kind = excluded.kind,
+            deck_id = excluded.deck_id",
+        params![
+            row.get::<_, String>(0),
+            row.get::<_, String>(1),
+            row.get::<_, String>(2),
+            row.get::<_, Option<String>>(3),
+            row.get::<_, Option<String>>(4),
+            row.get::<_, Option<String>>(5),
+            row.get::<_, Option<String>>(6),
+            row.get::<_, String>(7),
+            row.get::<_, Option<String>>(8),
+            row.get::<_, Option<String>>(9),
+            row.get::<_, Option<String>>(10),
+            row.get::<_, Option<String>>(11),
+            row.get::<_, Option<String>>(12),
+            row.get::<_, Option<String>>(13),
+            row.get::<_, Option<String>>(14),
+            row.get::<_, Option<String>>(15),
+        ],
+    )
+    .map(drop)
+    .map_err(|err| format!("update word failed: {err}"))
+}
+
+/// Keeps the local-only `seen_count` of cards that already exist.
+fn upsert_card(conn: &Connection, row: &Row) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO cards (id, word_id, due_at, interval_days, ease, reps, lapses, seen_count,
+                            deck_id, card_type)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 0, ?8, ?9)
+         ON CONFLICT(id) DO UPDATE SET
+            word_id = excluded.word_id, due_at = excluded.due_at,
+            interval_days = excluded.interval_days, ease = excluded.ease,
+            reps = excluded.reps, lapses = excluded.lapses, deck_id = excluded.deck_id,
+            card_type = excluded.card_type",
+        params![
+            row.get::<_, String>(0),
+            row.get::<_, String>(1),
+            row.get::<_, String>(2),
+            row.get::<_, i32>(3),
+            row.get::<_, f64>(4),
+            row.get::<_, i32>(5),
+            row.get::<_, i32>(6),
+            row.get::<_, Option<String>>(7),
+            row.get::<_, Option<String>>(8),
+        ],
+    )
+    .map(drop)
+    .map_err(|err| format!("update card failed: {err}"))
+}
+
+fn upsert_review(conn: &Connection, row: &Row) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO reviews (id, card_id, grade, reviewed_at) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(id) DO UPDATE SET
+            card_id = excluded.card_id, grade = excluded.grade,
+            reviewed_at = excluded.reviewed_at",
+        params![
+            row.get::<_, String>(0),
+            row.get::<_, String>(1),
+            row.get::<_, i32>(2),
+            row.get::<_, String>(3),
+        ],
+    )
+    .map(drop)
+    .map_err(|err| format!("update review failed: {err}"))
+}
+
+/// `OR REPLACE` also clears a local concept of the same name under another id.
+fn upsert_concept(conn: &Connection, row: &Row) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR REPLACE INTO concepts (id, name, created_at) VALUES (?1, ?2, ?3)",
+        params![
+            row.get::<_, String>(0),
+            row.get::<_, String>(1),
+            row.get::<_, String>(2),
+        ],
+    )
+    .map(drop)
+    .map_err(|err| format!("update concept failed: {err}"))
+}
+
+/// Tags are matched by name like concepts, so `OR REPLACE` here too.
+fn upsert_tag(conn: &Connection, row: &Row) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR REPLACE INTO tags (id, name, created_at) VALUES (?1, ?2, ?3)",
+        params![
+            row.get::<_, String>(0),
+            row.get::<_, String>(1),
+            row.get::<_, String>(2),
+        ],
+    )
+    .map(drop)
+    .map_err(|err| format!("update tag failed: {err}"))
+}
+
+/// Deck names are unique too, so `OR REPLACE` as for tags.
+fn upsert_deck(conn: &Connection, row: &Row) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR REPLACE INTO decks (id, name, created_at) VALUES (?1, ?2, ?3)",
+        params![
+            row.get::<_, String>(0),
+            row.get::<_, String>(1),
+            row.get::<_, String>(2),
+        ],
+    )
+    .map(drop)
+    .map_err(|err| format!("update deck failed: {err}"))
+}
+
+fn upsert_word_tag(conn: &Connection, row: &Row) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR IGNORE INTO word_tags (word_id, tag_id) VALUES (?1, ?2)",
+        params![row.get::<_, String>(0), row.get::<_, String>(1)],
+    )
+    .map(drop)
+    .map_err(|err| format!("update word tag failed: {err}"))
+}
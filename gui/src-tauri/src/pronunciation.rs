@@ -0,0 +1,96 @@
+//! Pronunciation clips from the auth server's `/ai/tts`. `fetch_audio`
+//! downloads a word's clip once and keeps it under the app data directory,
+//! one file per word, and returns its path for the webview to play through
+//! the asset protocol. Clips are spoken with the `tts` voice settings for the
+//! word's language, and fetched again only when the word's text or language
+//! or those settings change.
+
+use std::path::Path;
+
+use le_client::TtsRequest;
+use le_core::{Language, fnv1a};
+use rusqlite::params;
+use tauri::command;
+
+use crate::{VoiceSettings, app_db_path, local_db, perf, run_blocking, usage, voice_settings};
+
+const CACHE_DIR: &str = "tts-cache";
+/// Formats `/ai/tts` can answer with.
+const EXTENSIONS: [&str; 2] = ["mp3", "wav"];
+
+/// Path of the word's cached clip, fetched from `server_url` on first use.
+#[command]
+pub async fn fetch_audio(
+    app: tauri::AppHandle,
+    word_id: String,
+    server_url: String,
+) -> Result<String, String> {
+    run_blocking(move || {
+        let _perf = perf::track("fetch_audio");
+        let (text, language, voice) = {
+            let conn = local_db(&app)?;
+            usage::feature_used(&conn, "pronunciation");
+            let (text, language): (String, String) = conn
+                .query_row(
+                    "SELECT text, language FROM words WHERE id = ?1",
+                    params![word_id],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .map_err(|err| format!("Word {word_id} not found: {err}"))?;
+            let language = Language::new(&language);
+            let voice = voice_settings(&conn, &language)?;
+            (text, language, voice)
+        };
+        let cache = app_db_path(&app)?.with_file_name(CACHE_DIR);
+        let stem = format!("{word_id}-{:016x}", cache_key(&text, &language, &voice));
+        if let Some(path) = EXTENSIONS
+            .iter()
+            .map(|extension| cache.join(format!("{stem}.{extension}")))
+            .find(|path| path.is_file())
+        {
+            return Ok(path.to_string_lossy().to_string());
+        }
+        let client = le_client::Client::new(server_url).map_err(|err| err.to_string())?;
+        let request = TtsRequest {
+            text: text.trim().to_string(),
+            language: language.code().to_string(),
+            voice: voice.voice.clone(),
+            rate: Some(voice.rate),
+            pitch: Some(voice.pitch),
+        };
+        let audio = perf::network(|| client.tts(&request))
+            .map_err(|err| format!("Pronunciation error: {err}"))?;
+        std::fs::create_dir_all(&cache).map_err(|err| err.to_string())?;
+        remove_stale(&cache, &word_id);
+        let path = cache.join(format!("{stem}.{}", audio.extension()));
+        std::fs::write(&path, &audio.bytes).map_err(|err| err.to_string())?;
+        Ok(path.to_string_lossy().to_string())
+    })
+    .await
+}
+
+/// Drops the word's clips for an earlier text, language, or voice.
+fn remove_stale(cache: &Path, word_id: &str) {
+    let Ok(entries) = std::fs::read_dir(cache) else {
+        return;
+    };
+    let prefix = format!("{word_id}-");
+    for entry in entries.flatten() {
+        if entry.file_name().to_string_lossy().starts_with(&prefix) {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
+
+/// FNV-1a over the text, language, and voice, like the audio review cache.
+fn cache_key(text: &str, language: &Language, voice: &VoiceSettings) -> u64 {
+    let input = format!(
+        "{}|{}|{}|{}|{}",
+        language.code(),
+        voice.voice.as_deref().unwrap_or(""),
+        voice.rate,
+        voice.pitch,
+        text.trim()
+    );
+    fnv1a(input.as_bytes())
+}
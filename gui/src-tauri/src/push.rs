@@ -0,0 +1,55 @@
+//! Push sync from Postgres. With `push` on in the sync settings and
+//! `DATABASE_URL` set, a background thread keeps a [`RemoteSync::listen`]
+//! running, reconnecting after [`RECONNECT_DELAY`] whenever the connection
+//! drops. Builds without the `remote-sync` feature never start it.
+//!
+//! [`RemoteSync::listen`]: crate::remote::RemoteSync::listen
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::{SyncSettings, get_setting, local_db, log_error, remote};
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// Stop flag of the running listener thread, if any.
+static LISTENER: Mutex<Option<Arc<AtomicBool>>> = Mutex::new(None);
+
+/// Starts or stops the listener to match the saved sync settings. Called at
+/// startup and whenever the `sync` setting changes.
+pub fn restart(app: &tauri::AppHandle) {
+    let enabled = match local_db(app).and_then(|conn| get_setting::<SyncSettings>(&conn, "sync")) {
+        Ok(settings) => settings.is_some_and(|settings| settings.push),
+        Err(err) => {
+            log_error(&format!("push sync: {err}"));
+            return;
+        }
+    };
+    let Ok(mut listener) = LISTENER.lock() else {
+        return;
+    };
+    if let Some(stop) = listener.take() {
+        stop.store(true, Ordering::Relaxed);
+    }
+    if !enabled || remote::open().is_err() {
+        return;
+    }
+    let stop = Arc::new(AtomicBool::new(false));
+    *listener = Some(stop.clone());
+    let app = app.clone();
+    std::thread::spawn(move || listen(&app, &stop));
+}
+
+fn listen(app: &tauri::AppHandle, stop: &AtomicBool) {
+    while !stop.load(Ordering::Relaxed) {
+        let result = remote::open().and_then(|mut remote| remote.listen(app, stop));
+        if let Err(err) = result {
+            log_error(&format!("push sync: {err}"));
+        }
+        let retry_at = Instant::now() + RECONNECT_DELAY;
+        while !stop.load(Ordering::Relaxed) && Instant::now() < retry_at {
+            std::thread::sleep(Duration::from_millis(500));
+        }
+    }
+}
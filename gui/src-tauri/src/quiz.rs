@@ -0,0 +1,234 @@
+//! Multiple-choice gap-fill quizzes built from saved example sentences. The
+//! word is blanked out of its sentence and offered next to words from the
+//! same group, so the choices are close enough to make the quiz a test of
+//! meaning rather than of spotting the odd one out. Each question is a row in
+//! the local `tests` table; answering it fills in the result.
+
+use chrono::Utc;
+use le_core::{Language, rules};
+use rand::seq::SliceRandom;
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::Serialize;
+use tauri::command;
+use uuid::Uuid;
+
+use crate::{local_db, perf, usage};
+
+const DEFAULT_QUESTIONS: usize = 10;
+const MAX_QUESTIONS: usize = 50;
+/// Wrong choices offered next to the answer.
+const DISTRACTORS: usize = 3;
+const GAP: &str = "_____";
+
+#[derive(Debug, Serialize)]
+pub struct QuizQuestion {
+    /// Row in `tests`; pass it to `answer_quiz`.
+    test_id: i64,
+    word_id: String,
+    /// The sentence with the word replaced by a gap.
+    prompt: String,
+    translation: Option<String>,
+    choices: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Quiz {
+    id: String,
+    chapter: String,
+    questions: Vec<QuizQuestion>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QuizAnswer {
+    correct: bool,
+    answer: String,
+}
+
+/// A word with a saved example sentence that can become a question.
+struct Candidate {
+    word_id: String,
+    text: String,
+    language: String,
+    group: Option<String>,
+    sentence: String,
+    translation: Option<String>,
+}
+
+/// A word of the chapter that may be offered as a wrong choice.
+struct ChapterWord {
+    id: String,
+    text: String,
+    group: Option<String>,
+}
+
+/// Builds a quiz of up to `n` questions from the chapter's words that have
+/// an example sentence containing the word. Words without at least one
+/// other word to confuse them with are left out.
+#[command]
+pub fn generate_quiz(
+    app: tauri::AppHandle,
+    chapter: String,
+    n: Option<usize>,
+) -> Result<Quiz, String> {
+    let _perf = perf::track("generate_quiz");
+    let chapter = chapter.trim().to_string();
+    if chapter.is_empty() {
+        return Err("Choose a chapter for the quiz".to_string());
+    }
+    let n = n.unwrap_or(DEFAULT_QUESTIONS).clamp(1, MAX_QUESTIONS);
+    let conn = local_db(&app)?;
+    let mut candidates = load_candidates(&conn, &chapter).map_err(|err| err.to_string())?;
+    let words = load_words(&conn, &chapter).map_err(|err| err.to_string())?;
+    if candidates.is_empty() {
+        return Err(format!("No example sentences saved for chapter {chapter}"));
+    }
+    let mut rng = rand::thread_rng();
+    candidates.shuffle(&mut rng);
+
+    let quiz_id = Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|err| err.to_string())?;
+    let mut questions = Vec::new();
+    for candidate in &candidates {
+        if questions.len() == n {
+            break;
+        }
+        let rules = Language::new(&candidate.language).rules();
+        let Some(span) = rules::find_word(rules, &candidate.sentence, &candidate.text)
+            .into_iter()
+            .next()
+        else {
+            continue;
+        };
+        let answer = candidate.sentence[span.clone()].to_string();
+        let mut choices: Vec<String> = distractors(&words, candidate, &answer)
+            .choose_multiple(&mut rng, DISTRACTORS)
+            .cloned()
+            .collect();
+        if choices.is_empty() {
+            continue;
+        }
+        choices.push(answer.clone());
+        choices.shuffle(&mut rng);
+        let prompt = format!(
+            "{}{GAP}{}",
+            &candidate.sentence[..span.start],
+            &candidate.sentence[span.end..]
+        );
+        tx.execute(
+            "INSERT INTO tests (quiz_id, word_id, chapter, prompt, answer, choices, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                quiz_id,
+                candidate.word_id,
+                chapter,
+                prompt,
+                answer,
+                serde_json::to_string(&choices).map_err(|err| err.to_string())?,
+                now
+            ],
+        )
+        .map_err(|err| err.to_string())?;
+        questions.push(QuizQuestion {
+            test_id: tx.last_insert_rowid(),
+            word_id: candidate.word_id.clone(),
+            prompt,
+            translation: candidate.translation.clone(),
+            choices,
+        });
+    }
+    tx.commit().map_err(|err| err.to_string())?;
+    if questions.is_empty() {
+        return Err(format!(
+            "Chapter {chapter} needs more words per group to build a quiz"
+        ));
+    }
+    usage::feature_used(&conn, "gap_fill_quiz");
+    Ok(Quiz {
+        id: quiz_id,
+        chapter,
+        questions,
+    })
+}
+
+/// Records the choice made for one question and reports whether it was
+/// right. A question can only be answered once.
+#[command]
+pub fn answer_quiz(
+    app: tauri::AppHandle,
+    test_id: i64,
+    choice: String,
+) -> Result<QuizAnswer, String> {
+    let _perf = perf::track("answer_quiz");
+    let conn = local_db(&app)?;
+    let (answer, answered_at): (String, Option<String>) = conn
+        .query_row(
+            "SELECT answer, answered_at FROM tests WHERE id = ?1",
+            params![test_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|err| err.to_string())?
+        .ok_or_else(|| format!("Quiz question {test_id} not found"))?;
+    if answered_at.is_some() {
+        return Err("This question was already answered".to_string());
+    }
+    let correct = choice.trim() == answer;
+    conn.execute(
+        "UPDATE tests SET chosen = ?1, correct = ?2, answered_at = ?3 WHERE id = ?4",
+        params![choice.trim(), correct, Utc::now().to_rfc3339(), test_id],
+    )
+    .map_err(|err| err.to_string())?;
+    Ok(QuizAnswer { correct, answer })
+}
+
+fn load_candidates(conn: &Connection, chapter: &str) -> rusqlite::Result<Vec<Candidate>> {
+    let mut stmt = conn.prepare(
+        "SELECT w.id, w.text, w.language, w.group_name, e.sentence, e.translation
+         FROM words w
+         JOIN examples e ON e.word_id = w.id
+         WHERE w.chapter = ?1",
+    )?;
+    stmt.query_map(params![chapter], |row| {
+        Ok(Candidate {
+            word_id: row.get(0)?,
+            text: row.get(1)?,
+            language: row.get(2)?,
+            group: row.get(3)?,
+            sentence: row.get(4)?,
+            translation: row.get(5)?,
+        })
+    })?
+    .collect()
+}
+
+fn load_words(conn: &Connection, chapter: &str) -> rusqlite::Result<Vec<ChapterWord>> {
+    let mut stmt = conn.prepare("SELECT id, text, group_name FROM words WHERE chapter = ?1")?;
+    stmt.query_map(params![chapter], |row| {
+        Ok(ChapterWord {
+            id: row.get(0)?,
+            text: row.get(1)?,
+            group: row.get(2)?,
+        })
+    })?
+    .collect()
+}
+
+/// Wrong choices for `question`: the other words of its group, whether or
+/// not they have a sentence, or the rest of the chapter when the word has no
+/// group. Articles are dropped, since the gap never includes one.
+fn distractors(words: &[ChapterWord], question: &Candidate, answer: &str) -> Vec<String> {
+    let rules = Language::new(&question.language).rules();
+    let mut choices: Vec<String> = words
+        .iter()
+        .filter(|other| other.id != question.word_id)
+        .filter(|other| question.group.is_none() || other.group == question.group)
+        .map(|other| rules.strip_article(&other.text).trim().to_string())
+        .filter(|text| !text.is_empty() && !text.eq_ignore_ascii_case(answer))
+        .collect();
+    choices.sort();
+    choices.dedup();
+    choices
+}
@@ -0,0 +1,187 @@
+//! Welcome-back mode for a learner returning after a break. When the last
+//! review is at least `BREAK_DAYS` old, `recovery_status` offers a plan;
+//! `start_recovery` applies it once by spreading the overdue cards' due
+//! dates over the plan's days, and while it runs `start_session` caps the
+//! day's reviews and holds back new cards. The plan is kept in the
+//! `recovery` setting.
+
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use le_core::session::{RECOVERY_DAILY_REVIEWS, RecoveryPlan, break_days};
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::{Deserialize, Serialize};
+use tauri::command;
+
+use crate::dashboard::MATURE_INTERVAL_DAYS;
+use crate::{get_setting, local_db, perf, reviews_today, set_setting, usage};
+
+pub const SETTINGS_KEY: &str = "recovery";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RecoverySettings {
+    plan: Option<RecoveryPlan>,
+    /// Last review before a break whose offer was declined, so the same
+    /// break is not offered again.
+    declined_after: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecoveryStatus {
+    /// The plan `start_recovery` would apply, after an unanswered break.
+    offer: Option<RecoveryPlan>,
+    /// The plan in progress.
+    active: Option<RecoveryPlan>,
+    /// 1-based day of the active plan.
+    day: Option<usize>,
+    reviews_left_today: Option<usize>,
+}
+
+#[command]
+pub fn recovery_status(app: tauri::AppHandle) -> Result<RecoveryStatus, String> {
+    let _perf = perf::track("recovery_status");
+    let conn = local_db(&app)?;
+    let today = Local::now().date_naive();
+    let settings: RecoverySettings = get_setting(&conn, SETTINGS_KEY)?.unwrap_or_default();
+    if let Some(plan) = settings.plan.clone().filter(|plan| plan.is_active(today)) {
+        return Ok(RecoveryStatus {
+            day: Some((today - plan.starts_on).num_days() as usize + 1),
+            reviews_left_today: reviews_left_today(&conn)?,
+            active: Some(plan),
+            offer: None,
+        });
+    }
+    Ok(RecoveryStatus {
+        offer: propose(&conn, &settings, RECOVERY_DAILY_REVIEWS)?,
+        active: None,
+        day: None,
+        reviews_left_today: None,
+    })
+}
+
+/// Starts welcome-back mode with `daily_reviews` reviews a day. Overdue
+/// cards come back shortest interval first, so the mature cards most likely
+/// to have lapsed over the break return a share at a time on later days.
+#[command]
+pub fn start_recovery(
+    app: tauri::AppHandle,
+    daily_reviews: Option<usize>,
+) -> Result<RecoveryPlan, String> {
+    let _perf = perf::track("start_recovery");
+    let mut conn = local_db(&app)?;
+    let mut settings: RecoverySettings = get_setting(&conn, SETTINGS_KEY)?.unwrap_or_default();
+    let plan = propose(
+        &conn,
+        &settings,
+        daily_reviews.unwrap_or(RECOVERY_DAILY_REVIEWS),
+    )?
+    .ok_or_else(|| "No break to recover from".to_string())?;
+    let tx = conn.transaction().map_err(|err| err.to_string())?;
+    let overdue = {
+        let mut stmt = tx
+            .prepare(
+                "SELECT id FROM cards
+                 WHERE reps > 0 AND suspended = 0 AND due_at <= ?1
+                 ORDER BY interval_days, due_at",
+            )
+            .map_err(|err| err.to_string())?;
+        stmt.query_map(params![Utc::now().to_rfc3339()], |row| {
+            row.get::<_, String>(0)
+        })
+        .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+        .map_err(|err| err.to_string())?
+    };
+    for (position, card_id) in overdue.iter().enumerate() {
+        let offset = plan.day_offset(position);
+        if offset == 0 {
+            continue;
+        }
+        let due_at = local_midnight(plan.starts_on + Duration::days(offset as i64))?;
+        tx.execute(
+            "UPDATE cards SET due_at = ?2 WHERE id = ?1",
+            params![card_id, due_at.to_rfc3339()],
+        )
+        .map_err(|err| err.to_string())?;
+    }
+    settings.plan = Some(plan.clone());
+    set_setting(&tx, SETTINGS_KEY, &settings)?;
+    tx.commit().map_err(|err| err.to_string())?;
+    usage::feature_used(&conn, "recovery_mode");
+    Ok(plan)
+}
+
+/// Declines the offer, or ends a running plan early. Due dates already
+/// spread stay as they are.
+#[command]
+pub fn dismiss_recovery(app: tauri::AppHandle) -> Result<(), String> {
+    let _perf = perf::track("dismiss_recovery");
+    let conn = local_db(&app)?;
+    let settings = RecoverySettings {
+        plan: None,
+        declined_after: last_review(&conn)?.map(|at| at.to_rfc3339()),
+    };
+    set_setting(&conn, SETTINGS_KEY, &settings)
+}
+
+/// What is left of today's allowance while a plan runs, for `start_session`.
+pub(crate) fn reviews_left_today(conn: &Connection) -> Result<Option<usize>, String> {
+    let settings: RecoverySettings = get_setting(conn, SETTINGS_KEY)?.unwrap_or_default();
+    let Some(plan) = settings
+        .plan
+        .filter(|plan| plan.is_active(Local::now().date_naive()))
+    else {
+        return Ok(None);
+    };
+    let (reviewed, _) = reviews_today(conn)?;
+    Ok(Some(plan.daily_reviews.saturating_sub(reviewed)))
+}
+
+fn propose(
+    conn: &Connection,
+    settings: &RecoverySettings,
+    daily_reviews: usize,
+) -> Result<Option<RecoveryPlan>, String> {
+    let Some(last) = last_review(conn)? else {
+        return Ok(None);
+    };
+    if settings.declined_after.as_deref() == Some(last.to_rfc3339().as_str()) {
+        return Ok(None);
+    }
+    let Some(away_days) = break_days(last, Utc::now()) else {
+        return Ok(None);
+    };
+    let (overdue, mature): (i64, i64) = conn
+        .query_row(
+            "SELECT COUNT(*), COALESCE(SUM(interval_days >= ?2), 0) FROM cards
+             WHERE reps > 0 AND suspended = 0 AND due_at <= ?1",
+            params![Utc::now().to_rfc3339(), MATURE_INTERVAL_DAYS],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|err| err.to_string())?;
+    if overdue == 0 {
+        return Ok(None);
+    }
+    Ok(Some(RecoveryPlan::new(
+        away_days,
+        overdue as usize,
+        mature as usize,
+        daily_reviews,
+        Local::now().date_naive(),
+    )))
+}
+
+fn last_review(conn: &Connection) -> Result<Option<DateTime<Utc>>, String> {
+    let last: Option<String> = conn
+        .query_row("SELECT MAX(reviewed_at) FROM reviews", [], |row| row.get(0))
+        .optional()
+        .map_err(|err| err.to_string())?
+        .flatten();
+    Ok(last
+        .and_then(|at| DateTime::parse_from_rfc3339(&at).ok())
+        .map(|at| at.with_timezone(&Utc)))
+}
+
+fn local_midnight(date: NaiveDate) -> Result<DateTime<Utc>, String> {
+    date.and_hms_opt(0, 0, 0)
+        .and_then(|time| time.and_local_timezone(Local).earliest())
+        .map(|time| time.with_timezone(&Utc))
+        .ok_or_else(|| "Could not work out local midnight".to_string())
+}
@@ -0,0 +1,218 @@
+//! Full refresh of the local mirror from Postgres. Each table is copied into
+//! a snapshot SQLite file next to the real one by the [`RemoteSync`], which
+//! retries dropped connections. Only when every table has arrived are the
+//! synced tables swapped in, in one transaction, so a failed refresh leaves
+//! the local data exactly as it was. Tables already copied are kept in the
+//! snapshot, and a refresh started again within [`RESUME_WINDOW`] picks up
+//! where it stopped.
+//!
+//! [`RemoteSync`]: crate::remote::RemoteSync
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use le_core::session::Session;
+use rusqlite::{Connection, OptionalExtension, params};
+use tauri::{Manager, command};
+
+use crate::remote::{self, RemoteSync};
+use crate::{
+    ReviewState, app_db_path, local_db, log_error, log_sql, open_db, perf, run_blocking, sync,
+    usage,
+};
+
+const SNAPSHOT_FILE: &str = "refresh-snapshot.db";
+/// Older snapshots are discarded rather than resumed, so a refresh never
+/// mixes tables copied far apart in time.
+const RESUME_WINDOW: Duration = Duration::from_secs(15 * 60);
+
+/// Copy order matters for the swap: cards point at words and reviews at cards.
+const TABLES: [&str; 7] = [
+    "words",
+    "cards",
+    "reviews",
+    "concepts",
+    "tags",
+    "word_tags",
+    "decks",
+];
+
+/// Tables older Postgres databases may lack. When one is missing the local
+/// copy is kept instead of failing the refresh.
+const OPTIONAL_TABLES: [&str; 4] = ["concepts", "tags", "word_tags", "decks"];
+
+/// Replaces the local words, cards, reviews, concepts, tags, and decks with the
+/// Postgres copy. Returns the word, card, and review counts.
+#[command]
+pub async fn refresh_from_postgres(app: tauri::AppHandle) -> Result<(i64, i64, i64), String> {
+    run_blocking(move || {
+        let _perf = perf::track("refresh_from_postgres");
+        let mut remote = remote::open()?;
+        let snapshot_path = app_db_path(&app)?.with_file_name(SNAPSHOT_FILE);
+        let snapshot = open_snapshot(&snapshot_path).map_err(|err| fail("open snapshot", err))?;
+
+        for table in TABLES {
+            if copied_rows(&snapshot, table)?.is_some() {
+                continue;
+            }
+            match copy_table(remote.as_mut(), &snapshot, table) {
+                Ok(()) => {}
+                Err(err) if OPTIONAL_TABLES.contains(&table) => {
+                    log_error(&format!("refresh_from_postgres: {err}"))
+                }
+                Err(err) => return Err(fail("snapshot", err)),
+            }
+        }
+        drop(remote);
+
+        let counts = (
+            copied_rows(&snapshot, "words")?.unwrap_or(0),
+            copied_rows(&snapshot, "cards")?.unwrap_or(0),
+            copied_rows(&snapshot, "reviews")?.unwrap_or(0),
+        );
+        let mut optional = Vec::new();
+        for table in OPTIONAL_TABLES {
+            if copied_rows(&snapshot, table)?.is_some() {
+                optional.push(table);
+            }
+        }
+        drop(snapshot);
+
+        let mut conn = local_db(&app)?;
+        swap_in(&mut conn, &snapshot_path, &optional).map_err(|err| fail("swap", err))?;
+        sync::reset_base(&conn)?;
+        usage::feature_used(&conn, "full_refresh");
+        drop(conn);
+        if let Err(err) = std::fs::remove_file(&snapshot_path) {
+            log_error(&format!(
+                "refresh_from_postgres: remove snapshot failed: {err}"
+            ));
+        }
+
+        if let Ok(mut guard) = app.state::<Mutex<ReviewState>>().lock() {
+            guard.session = Session::default();
+        }
+        Ok(counts)
+    })
+    .await
+}
+
+fn fail(step: &str, err: impl std::fmt::Display) -> String {
+    let message = format!("refresh_from_postgres: {step} failed: {err}");
+    log_error(&message);
+    message
+}
+
+/// Opens the snapshot left by an earlier attempt if it is recent enough, or
+/// a fresh one otherwise.
+fn open_snapshot(path: &PathBuf) -> rusqlite::Result<Connection> {
+    let stale = std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .map(|modified| {
+            SystemTime::now()
+                .duration_since(modified)
+                .is_ok_and(|age| age > RESUME_WINDOW)
+        })
+        .unwrap_or(false);
+    if stale {
+        let _ = std::fs::remove_file(path);
+    }
+    let conn = open_db(path)?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS refresh_progress (
+            table_name TEXT PRIMARY KEY,
+            row_count INTEGER NOT NULL
+        );",
+    )?;
+    Ok(conn)
+}
+
+/// Row count of a table already copied into the snapshot.
+fn copied_rows(snapshot: &Connection, table: &str) -> Result<Option<i64>, String> {
+    snapshot
+        .query_row(
+            "SELECT row_count FROM refresh_progress WHERE table_name = ?1",
+            params![table],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|err| fail("read snapshot progress", err))
+}
+
+/// Copies one table in a single snapshot transaction, so a table is either
+/// complete in the snapshot or not marked as copied at all.
+fn copy_table(
+    remote: &mut dyn RemoteSync,
+    snapshot: &Connection,
+    table: &str,
+) -> Result<(), String> {
+    let tx = snapshot
+        .unchecked_transaction()
+        .map_err(|err| err.to_string())?;
+    tx.execute(&format!("DELETE FROM {table}"), [])
+        .map_err(|err| err.to_string())?;
+    let rows = remote.copy_table(&tx, table)?;
+    tx.execute(
+        "INSERT OR REPLACE INTO refresh_progress (table_name, row_count) VALUES (?1, ?2)",
+        params![table, rows as i64],
+    )
+    .map_err(|err| err.to_string())?;
+    tx.commit().map_err(|err| err.to_string())
+}
+
+/// Replaces the synced tables with the snapshot's in one transaction.
+/// `optional` names the [`OPTIONAL_TABLES`] the snapshot has. Local-only
+/// tables such as settings are left alone, and suspended or buried cards
+/// stay hidden.
+fn swap_in(conn: &mut Connection, snapshot_path: &Path, optional: &[&str]) -> rusqlite::Result<()> {
+    conn.execute(
+        "ATTACH DATABASE ?1 AS snapshot",
+        params![snapshot_path.to_string_lossy()],
+    )?;
+    let result = (|| {
+        let tx = conn.transaction()?;
+        let mut query = String::from(
+            "CREATE TEMP TABLE hidden_cards AS
+                SELECT id, suspended, buried_until FROM cards
+                WHERE suspended = 1 OR buried_until IS NOT NULL;
+             DELETE FROM reviews; DELETE FROM cards; DELETE FROM words;
+             INSERT INTO words (id, text, language, translation, chapter, group_name, notes,
+                                created_at, source_type, source_name, source_page, captured_at,
+                                source_region, alt_answers, kind, deck_id)
+                SELECT id, text, language, translation, chapter, group_name, notes, created_at,
+                       source_type, source_name, source_page, captured_at, source_region,
+                       alt_answers, kind, deck_id
+                FROM snapshot.words;
+             INSERT INTO cards (id, word_id, due_at, interval_days, ease, reps, lapses, seen_count,
+                                deck_id, card_type)
+                SELECT id, word_id, due_at, interval_days, ease, reps, lapses, 0, deck_id,
+                       card_type
+                FROM snapshot.cards;
+             UPDATE cards
+                SET suspended = (SELECT h.suspended FROM hidden_cards h WHERE h.id = cards.id),
+                    buried_until = (SELECT h.buried_until FROM hidden_cards h WHERE h.id = cards.id)
+                WHERE id IN (SELECT id FROM hidden_cards);
+             DROP TABLE hidden_cards;
+             INSERT INTO reviews (id, card_id, grade, reviewed_at)
+                SELECT id, card_id, grade, reviewed_at FROM snapshot.reviews;",
+        );
+        for table in optional {
+            let columns = match *table {
+                "word_tags" => "word_id, tag_id",
+                _ => "id, name, created_at",
+            };
+            query.push_str(&format!(
+                "DELETE FROM {table};
+                 INSERT INTO {table} ({columns}) SELECT {columns} FROM snapshot.{table};"
+            ));
+        }
+        // Tags kept from the local copy may point at words that are gone.
+        query.push_str("DELETE FROM word_tags WHERE word_id NOT IN (SELECT id FROM words);");
+        log_sql(&query, &[]);
+        tx.execute_batch(&query)?;
+        tx.commit()
+    })();
+    conn.execute("DETACH DATABASE snapshot", [])?;
+    result
+}
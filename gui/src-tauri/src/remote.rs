@@ -0,0 +1,65 @@
+//! The direct connection to Postgres behind two-way sync, full refresh, push
+//! sync, and corrections written straight through. The commands reach it
+//! only through [`RemoteSync`], implemented in `pg_remote`, which is built
+//! with the `remote-sync` cargo feature alone. Without the feature the data
+//! API (fetched by the frontend and applied by `refresh_from_data_api`) is
+//! the only remote, and [`open`] fails saying so, which the commands report
+//! rather than go missing.
+
+use std::sync::atomic::AtomicBool;
+
+use rusqlite::Connection;
+
+use crate::sync::{Rows, Table, TableMerge};
+use crate::{CardRow, WordSummary};
+
+/// Corrected word fields to write; `None` leaves a field as it is.
+#[cfg_attr(not(feature = "remote-sync"), allow(dead_code))]
+pub struct WordUpdate<'a> {
+    pub text: Option<&'a str>,
+    /// `Some(None)` clears the translation.
+    pub translation: Option<Option<&'a str>>,
+    /// JSON array, as stored in `words.alt_answers`; `Some(None)` clears it.
+    pub alt_answers: Option<Option<&'a str>>,
+}
+
+pub trait RemoteSync {
+    /// Whether the remote cannot be reached right now.
+    fn is_offline(&self) -> bool;
+
+    /// Every row of a two-way sync table, keyed by id.
+    fn load_rows(&mut self, table: &Table) -> Result<Rows, String>;
+
+    /// Writes the uploads and remote deletions of a two-way sync in one
+    /// transaction, parents first for uploads and last for deletions.
+    /// Returns the rows written and deleted.
+    fn push_merge(&mut self, merges: &[(&Table, &TableMerge)]) -> Result<(usize, usize), String>;
+
+    /// Inserts every remote row of the refresh table `table` through `tx`
+    /// and returns how many there were.
+    fn copy_table(&mut self, tx: &Connection, table: &str) -> Result<usize, String>;
+
+    /// The words and cards `diff_against_remote` compares.
+    fn wordlist(&mut self) -> Result<(Vec<WordSummary>, Vec<CardRow>), String>;
+
+    /// `false` when the remote has no word `word_id`.
+    fn update_word(&mut self, word_id: &str, update: &WordUpdate) -> Result<bool, String>;
+
+    /// Applies changes pushed by the remote as they arrive, until `stop` is
+    /// set or the connection drops.
+    fn listen(&mut self, app: &tauri::AppHandle, stop: &AtomicBool) -> Result<(), String>;
+}
+
+/// The configured remote. Nothing is connected until it is first used.
+#[cfg(feature = "remote-sync")]
+pub fn open() -> Result<Box<dyn RemoteSync>, String> {
+    Ok(Box::new(crate::pg_remote::PostgresRemote::new()?))
+}
+
+#[cfg(not(feature = "remote-sync"))]
+pub fn open() -> Result<Box<dyn RemoteSync>, String> {
+    Err(
+        "This build has no direct Postgres sync; rebuild with the remote-sync feature or use Refresh Data"
+            .to_string(),
+    )
+}
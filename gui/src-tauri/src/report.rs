@@ -0,0 +1,490 @@
+//! Progress report for a date range, written as a one- or two-page PDF to
+//! bring to a tutor: review volume and accuracy, new words, streaks, a
+//! weekly breakdown, and the words missed most often. The PDF is plain text
+//! in the standard Helvetica fonts, so no font files or PDF library are
+//! needed.
+
+use std::collections::BTreeSet;
+
+use chrono::{Datelike, Duration, NaiveDate, Utc};
+use rusqlite::{Connection, params};
+use serde::Serialize;
+use tauri::command;
+
+use crate::{local_db, log_error, perf, usage};
+
+/// Words listed under "Weakest words".
+const WEAKEST_WORDS: usize = 10;
+/// Reviews a word needs in the range before it can be called weak.
+const MIN_REVIEWS_FOR_WEAKEST: i64 = 2;
+
+#[derive(Debug, Serialize)]
+pub struct ReportSummary {
+    path: String,
+    reviews: i64,
+    /// Share of reviews graded 3 or higher, 0–1; `None` without reviews.
+    accuracy: Option<f64>,
+    /// Cards reviewed for the first time in the range.
+    new_words: i64,
+    /// Words added in the range.
+    words_added: i64,
+    days_active: usize,
+    longest_streak: usize,
+    /// Consecutive review days up to `to` (or the day before it).
+    streak_at_end: usize,
+    weakest: Vec<WeakWord>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WeakWord {
+    text: String,
+    translation: Option<String>,
+    reviews: i64,
+    misses: i64,
+}
+
+struct WeekRow {
+    start: NaiveDate,
+    reviews: i64,
+    correct: i64,
+}
+
+/// Writes the report for `from` to `to` (`YYYY-MM-DD`, both included, in
+/// UTC) to `path`, replacing any file there, and returns the numbers in it.
+#[command]
+pub fn export_report_pdf(
+    app: tauri::AppHandle,
+    from: String,
+    to: String,
+    path: String,
+) -> Result<ReportSummary, String> {
+    let _perf = perf::track("export_report_pdf");
+    let parse = |value: &str| {
+        NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+            .map_err(|_| format!("Invalid date '{value}', expected YYYY-MM-DD"))
+    };
+    let (from, to) = (parse(&from)?, parse(&to)?);
+    if from > to {
+        return Err("The report must start before it ends".to_string());
+    }
+    let conn = local_db(&app)?;
+    let (summary, weeks) = build_report(&conn, from, to, path).map_err(|err| {
+        let message = format!("export_report_pdf: {err}");
+        log_error(&message);
+        message
+    })?;
+    std::fs::write(&summary.path, render(&summary, &weeks, from, to))
+        .map_err(|err| format!("Failed to write {}: {err}", summary.path))?;
+    usage::feature_used(&conn, "report_pdf");
+    Ok(summary)
+}
+
+fn build_report(
+    conn: &Connection,
+    from: NaiveDate,
+    to: NaiveDate,
+    path: String,
+) -> rusqlite::Result<(ReportSummary, Vec<WeekRow>)> {
+    let start = from.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+    let end = (to + Duration::days(1))
+        .and_hms_opt(0, 0, 0)
+        .unwrap_or_default()
+        .and_utc();
+    let range = params![start.to_rfc3339(), end.to_rfc3339()];
+
+    let (reviews, correct): (i64, i64) = conn.query_row(
+        "SELECT COUNT(*), COALESCE(SUM(grade >= 3), 0) FROM reviews
+         WHERE reviewed_at >= ?1 AND reviewed_at < ?2",
+        range,
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    let new_words = conn.query_row(
+        "SELECT COUNT(*) FROM (
+             SELECT MIN(reviewed_at) AS first_review FROM reviews GROUP BY card_id
+         ) WHERE first_review >= ?1 AND first_review < ?2",
+        range,
+        |row| row.get(0),
+    )?;
+    let words_added = conn.query_row(
+        "SELECT COUNT(*) FROM words WHERE created_at >= ?1 AND created_at < ?2",
+        range,
+        |row| row.get(0),
+    )?;
+
+    let mut stmt = conn.prepare(
+        "SELECT DISTINCT substr(reviewed_at, 1, 10) FROM reviews WHERE reviewed_at < ?1",
+    )?;
+    let days: BTreeSet<NaiveDate> = stmt
+        .query_map(params![end.to_rfc3339()], |row| row.get::<_, String>(0))?
+        .filter_map(|day| NaiveDate::parse_from_str(&day.ok()?, "%Y-%m-%d").ok())
+        .collect();
+    let (days_active, longest_streak) = streaks_in(&days, from, to);
+    let streak_at_end = streak_ending(&days, to);
+
+    let mut weeks: Vec<WeekRow> = Vec::new();
+    let mut stmt = conn.prepare(
+        "SELECT substr(reviewed_at, 1, 10), COUNT(*), SUM(grade >= 3) FROM reviews
+         WHERE reviewed_at >= ?1 AND reviewed_at < ?2
+         GROUP BY 1 ORDER BY 1",
+    )?;
+    let per_day = stmt.query_map(range, |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, i64>(1)?,
+            row.get::<_, i64>(2)?,
+        ))
+    })?;
+    for row in per_day {
+        let (day, count, day_correct) = row?;
+        let Ok(day) = NaiveDate::parse_from_str(&day, "%Y-%m-%d") else {
+            continue;
+        };
+        let monday = day - Duration::days(day.weekday().num_days_from_monday() as i64);
+        match weeks.last_mut() {
+            Some(week) if week.start == monday => {
+                week.reviews += count;
+                week.correct += day_correct;
+            }
+            _ => weeks.push(WeekRow {
+                start: monday,
+                reviews: count,
+                correct: day_correct,
+            }),
+        }
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT w.text, w.translation, COUNT(*) AS total, SUM(r.grade < 3) AS misses
+         FROM reviews r
+         JOIN cards c ON c.id = r.card_id
+         JOIN words w ON w.id = c.word_id
+         WHERE r.reviewed_at >= ?1 AND r.reviewed_at < ?2
+         GROUP BY w.id
+         HAVING total >= ?3 AND misses > 0
+         ORDER BY CAST(misses AS REAL) / total DESC, misses DESC, w.text
+         LIMIT ?4",
+    )?;
+    let weakest = stmt
+        .query_map(
+            params![
+                start.to_rfc3339(),
+                end.to_rfc3339(),
+                MIN_REVIEWS_FOR_WEAKEST,
+                WEAKEST_WORDS as i64
+            ],
+            |row| {
+                Ok(WeakWord {
+                    text: row.get(0)?,
+                    translation: row.get(1)?,
+                    reviews: row.get(2)?,
+                    misses: row.get(3)?,
+                })
+            },
+        )?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let summary = ReportSummary {
+        path,
+        reviews,
+        accuracy: (reviews > 0).then(|| correct as f64 / reviews as f64),
+        new_words,
+        words_added,
+        days_active,
+        longest_streak,
+        streak_at_end,
+        weakest,
+    };
+    Ok((summary, weeks))
+}
+
+/// Days with reviews between `from` and `to`, and the longest run of
+/// consecutive ones among them.
+fn streaks_in(days: &BTreeSet<NaiveDate>, from: NaiveDate, to: NaiveDate) -> (usize, usize) {
+    let mut active = 0;
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for &day in days.range(from..=to) {
+        active += 1;
+        run = match previous {
+            Some(previous) if day - previous == Duration::days(1) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(day);
+    }
+    (active, longest)
+}
+
+/// The run of consecutive review days ending on `to`, or on the day before
+/// when `to` itself has none, matching how the leaderboard counts streaks.
+fn streak_ending(days: &BTreeSet<NaiveDate>, to: NaiveDate) -> usize {
+    let mut expected = if days.contains(&to) {
+        to
+    } else {
+        to - Duration::days(1)
+    };
+    let mut streak = 0;
+    while days.contains(&expected) {
+        streak += 1;
+        expected -= Duration::days(1);
+    }
+    streak
+}
+
+fn render(summary: &ReportSummary, weeks: &[WeekRow], from: NaiveDate, to: NaiveDate) -> Vec<u8> {
+    let percent = |correct: f64| format!("{:.0}%", correct * 100.0);
+    let mut pdf = PdfWriter::new();
+    pdf.text(MARGIN, "Progress report", 20.0, true);
+    pdf.text(
+        MARGIN,
+        &format!(
+            "{} to {} (generated {})",
+            from.format("%-d %B %Y"),
+            to.format("%-d %B %Y"),
+            Utc::now().format("%-d %B %Y")
+        ),
+        10.0,
+        false,
+    );
+    pdf.gap(14.0);
+
+    pdf.text(MARGIN, "Summary", 14.0, true);
+    let accuracy = summary.accuracy.map_or("-".to_string(), percent);
+    for (label, value) in [
+        ("Reviews", summary.reviews.to_string()),
+        ("Accuracy", accuracy),
+        ("New words learned", summary.new_words.to_string()),
+        ("Words added", summary.words_added.to_string()),
+        ("Days with reviews", summary.days_active.to_string()),
+        ("Longest streak", days(summary.longest_streak)),
+        ("Streak at end of range", days(summary.streak_at_end)),
+    ] {
+        pdf.row(&[(MARGIN, label), (MARGIN + 180.0, &value)], 11.0, false);
+    }
+    pdf.gap(14.0);
+
+    pdf.text(MARGIN, "Reviews by week", 14.0, true);
+    if weeks.is_empty() {
+        pdf.text(MARGIN, "No reviews in this range.", 11.0, false);
+    } else {
+        let columns = [MARGIN, MARGIN + 180.0, MARGIN + 260.0];
+        pdf.row(
+            &[
+                (columns[0], "Week of"),
+                (columns[1], "Reviews"),
+                (columns[2], "Accuracy"),
+            ],
+            11.0,
+            true,
+        );
+        for week in weeks {
+            let start = week.start.format("%-d %b %Y").to_string();
+            let accuracy = percent(week.correct as f64 / week.reviews.max(1) as f64);
+            pdf.row(
+                &[
+                    (columns[0], &start),
+                    (columns[1], &week.reviews.to_string()),
+                    (columns[2], &accuracy),
+                ],
+                11.0,
+                false,
+            );
+        }
+    }
+    pdf.gap(14.0);
+
+    pdf.text(MARGIN, "Weakest words", 14.0, true);
+    if summary.weakest.is_empty() {
+        pdf.text(
+            MARGIN,
+            &format!("No word was missed with at least {MIN_REVIEWS_FOR_WEAKEST} reviews."),
+            11.0,
+            false,
+        );
+    } else {
+        let columns = [MARGIN, MARGIN + 170.0, MARGIN + 360.0, MARGIN + 420.0];
+        pdf.row(
+            &[
+                (columns[0], "Word"),
+                (columns[1], "Translation"),
+                (columns[2], "Reviews"),
+                (columns[3], "Missed"),
+            ],
+            11.0,
+            true,
+        );
+        for word in &summary.weakest {
+            pdf.row(
+                &[
+                    (columns[0], &clip(&word.text, 30)),
+                    (
+                        columns[1],
+                        &clip(word.translation.as_deref().unwrap_or("-"), 32),
+                    ),
+                    (columns[2], &word.reviews.to_string()),
+                    (columns[3], &word.misses.to_string()),
+                ],
+                11.0,
+                false,
+            );
+        }
+    }
+    pdf.finish()
+}
+
+fn days(count: usize) -> String {
+    format!("{count} day{}", if count == 1 { "" } else { "s" })
+}
+
+/// Shortens `text` to `max` characters so table cells do not overlap.
+fn clip(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut clipped: String = text.chars().take(max - 1).collect();
+    clipped.push('…');
+    clipped
+}
+
+/// A4 in points.
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 56.0;
+
+/// Writes text-only pages top to bottom, starting a new page when one is full.
+struct PdfWriter {
+    pages: Vec<Vec<u8>>,
+    current: Vec<u8>,
+    /// Baseline of the next line, from the bottom of the page.
+    y: f32,
+}
+
+impl PdfWriter {
+    fn new() -> Self {
+        Self {
+            pages: Vec::new(),
+            current: Vec::new(),
+            y: PAGE_HEIGHT - MARGIN,
+        }
+    }
+
+    fn text(&mut self, x: f32, text: &str, size: f32, bold: bool) {
+        self.row(&[(x, text)], size, bold);
+    }
+
+    /// One line with each cell starting at its own x position.
+    fn row(&mut self, cells: &[(f32, &str)], size: f32, bold: bool) {
+        let height = size * 1.5;
+        if self.y - height < MARGIN {
+            self.pages.push(std::mem::take(&mut self.current));
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+        self.y -= height;
+        let font = if bold { "F2" } else { "F1" };
+        for (x, text) in cells {
+            self.current
+                .extend_from_slice(format!("BT /{font} {size} Tf {x} {} Td (", self.y).as_bytes());
+            self.current.extend(encode(text));
+            self.current.extend_from_slice(b") Tj ET\n");
+        }
+    }
+
+    fn gap(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    /// The finished file: catalog, page tree, the two fonts, then a page and
+    /// content stream per page, followed by the cross-reference table.
+    fn finish(mut self) -> Vec<u8> {
+        self.pages.push(self.current);
+        let page_count = self.pages.len();
+        let kids: Vec<String> = (0..page_count)
+            .map(|index| format!("{} 0 R", 5 + index * 2))
+            .collect();
+        let mut objects: Vec<Vec<u8>> = vec![
+            b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {page_count} >>",
+                kids.join(" ")
+            )
+            .into_bytes(),
+            font_object("Helvetica"),
+            font_object("Helvetica-Bold"),
+        ];
+        for (index, content) in self.pages.into_iter().enumerate() {
+            objects.push(
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {PAGE_WIDTH} {PAGE_HEIGHT}] \
+                     /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                    6 + index * 2
+                )
+                .into_bytes(),
+            );
+            let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+            stream.extend(content);
+            stream.extend_from_slice(b"endstream");
+            objects.push(stream);
+        }
+
+        let mut out = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (index, object) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend_from_slice(format!("{} 0 obj\n", index + 1).as_bytes());
+            out.extend_from_slice(object);
+            out.extend_from_slice(b"\nendobj\n");
+        }
+        let xref = out.len();
+        out.extend_from_slice(
+            format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes(),
+        );
+        for offset in offsets {
+            out.extend_from_slice(format!("{offset:010} 00000 n \n").as_bytes());
+        }
+        out.extend_from_slice(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+                objects.len() + 1
+            )
+            .as_bytes(),
+        );
+        out
+    }
+}
+
+fn font_object(name: &str) -> Vec<u8> {
+    format!("<< /Type /Font /Subtype /Type1 /BaseFont /{name} /Encoding /WinAnsiEncoding >>")
+        .into_bytes()
+}
+
+/// `text` as a PDF string body in WinAnsi (Windows-1252), which covers the
+/// accented letters of the supported languages. Characters outside it
+/// become `?`.
+fn encode(text: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(text.len());
+    for ch in text.chars() {
+        let byte = match ch {
+            '(' | ')' | '\\' => {
+                bytes.push(b'\\');
+                ch as u8
+            }
+            ' '..='~' => ch as u8,
+            '\u{a0}'..='\u{ff}' => ch as u32 as u8,
+            '€' => 0x80,
+            '…' => 0x85,
+            'Œ' => 0x8c,
+            '‘' => 0x91,
+            '’' => 0x92,
+            '“' => 0x93,
+            '”' => 0x94,
+            '–' => 0x96,
+            '—' => 0x97,
+            'œ' => 0x9c,
+            'Ÿ' => 0x9f,
+            _ => b'?',
+        };
+        bytes.push(byte);
+    }
+    bytes
+}
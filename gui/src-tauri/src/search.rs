@@ -0,0 +1,254 @@
+//! Full-text search over the local words. `words_fts` is an FTS5 index of
+//! each word's text, translation, and notes, created by the `word_search`
+//! migration in `le_db` and kept in step with `words` by triggers, so
+//! saves, deletes, and refreshes need no extra work. Accents are folded, so
+//! "cafe" finds "café". `global_search` also looks through saved sentences,
+//! which have no index, and says where each field matched.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use le_core::search::{match_ranges, matches_all, search_terms};
+use rusqlite::params;
+use serde::Serialize;
+use tauri::command;
+
+use crate::{local_db, perf, usage};
+
+/// Most results `search_words` returns.
+const MAX_RESULTS: i64 = 50;
+
+#[derive(Debug, Serialize)]
+pub struct SearchHit {
+    word_id: String,
+    text: String,
+    translation: Option<String>,
+    language: String,
+    chapter: Option<String>,
+    group_name: Option<String>,
+    notes: Option<String>,
+}
+
+/// Words matching every term of `query`, each term as a prefix, best match
+/// first: matches in the text rank above matches in the translation, which
+/// rank above matches in the notes.
+#[command]
+pub fn search_words(app: tauri::AppHandle, query: String) -> Result<Vec<SearchHit>, String> {
+    let _perf = perf::track("search_words");
+    let Some(expression) = match_expression(&query) else {
+        return Ok(Vec::new());
+    };
+    let conn = local_db(&app)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT w.id, w.text, w.translation, w.language, w.chapter, w.group_name, w.notes
+             FROM words_fts f
+             JOIN words w ON w.id = f.word_id
+             WHERE words_fts MATCH ?1
+             ORDER BY bm25(words_fts, 0.0, 10.0, 5.0, 1.0), w.created_at
+             LIMIT ?2",
+        )
+        .map_err(|err| err.to_string())?;
+    let hits = stmt
+        .query_map(params![expression, MAX_RESULTS], |row| {
+            Ok(SearchHit {
+                word_id: row.get(0)?,
+                text: row.get(1)?,
+                translation: row.get(2)?,
+                language: row.get(3)?,
+                chapter: row.get(4)?,
+                group_name: row.get(5)?,
+                notes: row.get(6)?,
+            })
+        })
+        .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+        .map_err(|err| err.to_string())?;
+    usage::feature_used(&conn, "search_words");
+    Ok(hits)
+}
+
+/// Most sentences `global_search` shows per word.
+const MAX_SENTENCES_PER_HIT: usize = 3;
+
+/// A sentence and its translation.
+type Sentence = (String, Option<String>);
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchField {
+    Text,
+    Translation,
+    Notes,
+    Sentence,
+    SentenceTranslation,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FieldMatch {
+    field: SearchField,
+    value: String,
+    /// `[start, end)` of each match in UTF-16 code units, the way
+    /// JavaScript indexes strings.
+    highlights: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GlobalHit {
+    word_id: String,
+    language: String,
+    chapter: Option<String>,
+    /// Name of the word's deck; `None` for words in no deck.
+    deck: Option<String>,
+    /// The text, then the translation and notes when set, then up to
+    /// [`MAX_SENTENCES_PER_HIT`] matching sentences, each followed by its
+    /// translation when it has one.
+    fields: Vec<FieldMatch>,
+}
+
+/// Words in every deck matching all terms of `query`, for the spotlight
+/// search. A word matches through its text, translation, and notes, ranked
+/// as in `search_words`, or else through one of its sentences (with the
+/// sentence's translation), listed after.
+#[command]
+pub fn global_search(app: tauri::AppHandle, query: String) -> Result<Vec<GlobalHit>, String> {
+    let _perf = perf::track("global_search");
+    let terms = search_terms(&query);
+    let Some(expression) = match_expression(&query) else {
+        return Ok(Vec::new());
+    };
+    let conn = local_db(&app)?;
+    let mut ids: Vec<String> = conn
+        .prepare(
+            "SELECT word_id FROM words_fts WHERE words_fts MATCH ?1
+             ORDER BY bm25(words_fts, 0.0, 10.0, 5.0, 1.0)
+             LIMIT ?2",
+        )
+        .and_then(|mut stmt| {
+            stmt.query_map(params![expression, MAX_RESULTS], |row| row.get(0))
+                .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+        })
+        .map_err(|err| err.to_string())?;
+    let sentences = matching_sentences(&conn, &terms)?;
+    for (word_id, _) in &sentences {
+        if !ids.contains(word_id) {
+            ids.push(word_id.clone());
+        }
+    }
+    ids.truncate(MAX_RESULTS as usize);
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT w.text, w.translation, w.notes, w.language, w.chapter, d.name
+             FROM words w LEFT JOIN decks d ON d.id = w.deck_id
+             WHERE w.id = ?1",
+        )
+        .map_err(|err| err.to_string())?;
+    let sentences: HashMap<String, Vec<Sentence>> = sentences.into_iter().collect();
+    let mut hits = Vec::with_capacity(ids.len());
+    for word_id in ids {
+        let row = stmt.query_row(params![word_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, Option<String>>(5)?,
+            ))
+        });
+        // The index can briefly list a word deleted since.
+        let Ok((text, translation, notes, language, chapter, deck)) = row else {
+            continue;
+        };
+        let mut fields = vec![field_match(SearchField::Text, text, &terms)];
+        if let Some(translation) = translation {
+            fields.push(field_match(SearchField::Translation, translation, &terms));
+        }
+        if let Some(notes) = notes.filter(|notes| !notes.trim().is_empty()) {
+            fields.push(field_match(SearchField::Notes, notes, &terms));
+        }
+        for (sentence, translation) in sentences.get(&word_id).into_iter().flatten() {
+            fields.push(field_match(SearchField::Sentence, sentence.clone(), &terms));
+            if let Some(translation) = translation {
+                fields.push(field_match(
+                    SearchField::SentenceTranslation,
+                    translation.clone(),
+                    &terms,
+                ));
+            }
+        }
+        hits.push(GlobalHit {
+            word_id,
+            language,
+            chapter,
+            deck,
+            fields,
+        });
+    }
+    usage::feature_used(&conn, "global_search");
+    Ok(hits)
+}
+
+/// Sentences matching every term in the sentence or its translation, up to
+/// [`MAX_SENTENCES_PER_HIT`] per word, grouped by word in the order each
+/// word's first match was saved.
+fn matching_sentences(
+    conn: &rusqlite::Connection,
+    terms: &[String],
+) -> Result<Vec<(String, Vec<Sentence>)>, String> {
+    let mut stmt = conn
+        .prepare("SELECT word_id, sentence, translation FROM sentences ORDER BY created_at, id")
+        .map_err(|err| err.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+            ))
+        })
+        .map_err(|err| err.to_string())?;
+    let mut grouped: Vec<(String, Vec<Sentence>)> = Vec::new();
+    for row in rows {
+        let (word_id, sentence, translation) = row.map_err(|err| err.to_string())?;
+        let fields = [
+            sentence.as_str(),
+            translation.as_deref().unwrap_or_default(),
+        ];
+        if !matches_all(&fields, terms) {
+            continue;
+        }
+        match grouped.iter_mut().find(|(id, _)| *id == word_id) {
+            Some((_, sentences)) if sentences.len() >= MAX_SENTENCES_PER_HIT => {}
+            Some((_, sentences)) => sentences.push((sentence, translation)),
+            None => grouped.push((word_id, vec![(sentence, translation)])),
+        }
+    }
+    Ok(grouped)
+}
+
+fn field_match(field: SearchField, value: String, terms: &[String]) -> FieldMatch {
+    let highlights = match_ranges(&value, terms)
+        .into_iter()
+        .map(|Range { start, end }| (utf16_offset(&value, start), utf16_offset(&value, end)))
+        .collect();
+    FieldMatch {
+        field,
+        value,
+        highlights,
+    }
+}
+
+fn utf16_offset(text: &str, byte: usize) -> usize {
+    text[..byte].encode_utf16().count()
+}
+
+/// `query` as an FTS5 match expression. Quoting each term keeps FTS5 syntax
+/// characters in the input literal.
+fn match_expression(query: &str) -> Option<String> {
+    let terms = query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"*", term.replace('"', "\"\"")))
+        .collect::<Vec<_>>();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
@@ -0,0 +1,98 @@
+//! Starter decks: a copy of the local database with everything personal
+//! taken out, so it can be handed to someone else and dropped in as their
+//! `data/words.db` seed. Words, examples, concepts, and tags are kept; review
+//! history, scheduling, notes, settings, and local logs are not.
+
+use std::path::Path;
+
+use chrono::Utc;
+use le_core::default_new_card;
+use rusqlite::{Connection, params};
+use serde::Serialize;
+use tauri::command;
+use uuid::Uuid;
+
+use crate::{local_db, log_error, perf, usage};
+
+/// Emptied in the copy: review history and everything derived from it, plus
+/// local-only settings and logs.
+const PERSONAL_TABLES: [&str; 10] = [
+    "reviews",
+    "card_history",
+    "card_state_edits",
+    "sync_base",
+    "settings",
+    "perf_log",
+    "usage_sessions",
+    "usage_features",
+    "word_corrections",
+    "tests",
+];
+
+#[derive(Debug, Serialize)]
+pub struct CleanDbSummary {
+    path: String,
+    words: i64,
+    cards: i64,
+}
+
+/// Writes a shareable copy of the database to `path`, replacing any file
+/// there. Every card starts over as new and due now. A copy that could not
+/// be fully cleaned is removed rather than left behind.
+#[command]
+pub fn export_clean_db(app: tauri::AppHandle, path: String) -> Result<CleanDbSummary, String> {
+    let _perf = perf::track("export_clean_db");
+    let target = Path::new(&path);
+    if target.exists() {
+        std::fs::remove_file(target).map_err(|err| format!("Failed to replace {path}: {err}"))?;
+    }
+    {
+        let conn = local_db(&app)?;
+        conn.execute("VACUUM INTO ?1", params![path])
+            .map_err(|err| format!("Failed to write {path}: {err}"))?;
+        usage::feature_used(&conn, "clean_db_export");
+    }
+    let summary = Connection::open(target).and_then(|copy| strip(&copy));
+    match summary {
+        Ok((words, cards)) => Ok(CleanDbSummary { path, words, cards }),
+        Err(err) => {
+            let message = format!("export_clean_db: clean copy failed: {err}");
+            log_error(&message);
+            let _ = std::fs::remove_file(target);
+            Err(message)
+        }
+    }
+}
+
+/// Removes the personal data from `copy` and returns its word and card
+/// counts. The final VACUUM keeps deleted rows out of the file's free pages.
+fn strip(copy: &Connection) -> rusqlite::Result<(i64, i64)> {
+    let fresh = default_new_card(Uuid::nil(), Utc::now());
+    let tx = copy.unchecked_transaction()?;
+    for table in PERSONAL_TABLES {
+        tx.execute(&format!("DELETE FROM {table}"), [])?;
+    }
+    tx.execute(
+        "UPDATE cards SET due_at = ?1, interval_days = ?2, ease = ?3, reps = ?4, lapses = ?5,
+                          seen_count = 0, stability = NULL, difficulty = NULL, batch_id = -1",
+        params![
+            fresh.due_at.to_rfc3339(),
+            fresh.interval_days,
+            fresh.ease,
+            fresh.reps,
+            fresh.lapses
+        ],
+    )?;
+    tx.execute("UPDATE words SET notes = NULL", [])?;
+    tx.execute("UPDATE sentences SET last_shown_at = NULL", [])?;
+    tx.execute("DELETE FROM batch_meta WHERE key <> 'active_batch'", [])?;
+    tx.execute(
+        "UPDATE batch_meta SET value = '0' WHERE key = 'active_batch'",
+        [],
+    )?;
+    tx.commit()?;
+    copy.execute_batch("VACUUM")?;
+    let words = copy.query_row("SELECT COUNT(*) FROM words", [], |row| row.get(0))?;
+    let cards = copy.query_row("SELECT COUNT(*) FROM cards", [], |row| row.get(0))?;
+    Ok((words, cards))
+}
@@ -0,0 +1,189 @@
+//! System-wide review shortcuts, for foot pedals and media keys. Presses are
+//! forwarded to the frontend as `review-shortcut` events carrying the action,
+//! so revealing and grading go through the same code as the on-screen
+//! buttons.
+
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager, command};
+
+use crate::{emit_settings_changed, get_setting, local_db, log_error, perf, set_setting};
+
+pub const SETTINGS_KEY: &str = "shortcuts";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShortcutAction {
+    Reveal,
+    Again,
+    Hard,
+    Good,
+    Easy,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ShortcutBinding {
+    action: ShortcutAction,
+    /// Accelerator such as `F13`, `CmdOrCtrl+Shift+G`, or `MediaPlayPause`.
+    accelerator: String,
+}
+
+/// Off until enabled, since the default media keys would otherwise stop
+/// controlling music player playback.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ShortcutSettings {
+    #[serde(default)]
+    enabled: bool,
+    /// Also act while another application has focus.
+    #[serde(default)]
+    background: bool,
+    #[serde(default = "default_bindings")]
+    bindings: Vec<ShortcutBinding>,
+}
+
+impl Default for ShortcutSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            background: false,
+            bindings: default_bindings(),
+        }
+    }
+}
+
+fn default_bindings() -> Vec<ShortcutBinding> {
+    [
+        (ShortcutAction::Reveal, "MediaPlayPause"),
+        (ShortcutAction::Again, "MediaTrackPrevious"),
+        (ShortcutAction::Good, "MediaTrackNext"),
+    ]
+    .into_iter()
+    .map(|(action, accelerator)| ShortcutBinding {
+        action,
+        accelerator: accelerator.to_string(),
+    })
+    .collect()
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ShortcutPressed {
+    action: ShortcutAction,
+}
+
+#[command]
+pub fn get_shortcut_settings(app: tauri::AppHandle) -> Result<ShortcutSettings, String> {
+    let _perf = perf::track("get_shortcut_settings");
+    let conn = local_db(&app)?;
+    Ok(get_setting(&conn, SETTINGS_KEY)?.unwrap_or_default())
+}
+
+/// Validates the accelerators, re-registers them, and saves the settings.
+/// Nothing is saved if a key cannot be registered.
+#[command]
+pub fn set_shortcut_settings(
+    app: tauri::AppHandle,
+    input: ShortcutSettings,
+) -> Result<ShortcutSettings, String> {
+    let _perf = perf::track("set_shortcut_settings");
+    let mut seen: Vec<String> = Vec::new();
+    for binding in &input.bindings {
+        let key = binding.accelerator.trim().to_lowercase();
+        if key.is_empty() {
+            return Err(format!("No key set for {:?}", binding.action));
+        }
+        if seen.contains(&key) {
+            return Err(format!("{} is bound twice", binding.accelerator));
+        }
+        seen.push(key);
+    }
+    let conn = local_db(&app)?;
+    let previous: ShortcutSettings = get_setting(&conn, SETTINGS_KEY)?.unwrap_or_default();
+    if let Err(err) = register(&app, &input) {
+        // Put the old keys back so a typo does not leave the pedal dead.
+        if let Err(restore) = register(&app, &previous) {
+            log_error(&format!("Restoring shortcuts failed: {restore}"));
+        }
+        return Err(err);
+    }
+    set_setting(&conn, SETTINGS_KEY, &input)?;
+    emit_settings_changed(&app, SETTINGS_KEY);
+    Ok(input)
+}
+
+/// Registers the saved shortcuts at startup.
+pub fn register_saved(app: &tauri::AppHandle) {
+    let result = local_db(app).and_then(|conn| {
+        let settings: ShortcutSettings = get_setting(&conn, SETTINGS_KEY)?.unwrap_or_default();
+        register(app, &settings)
+    });
+    if let Err(err) = result {
+        log_error(&format!("Registering shortcuts failed: {err}"));
+    }
+}
+
+#[cfg(desktop)]
+fn register(app: &tauri::AppHandle, settings: &ShortcutSettings) -> Result<(), String> {
+    use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+
+    let manager = app.global_shortcut();
+    manager.unregister_all().map_err(|err| err.to_string())?;
+    if !settings.enabled {
+        return Ok(());
+    }
+    for binding in &settings.bindings {
+        let shortcut: Shortcut = binding
+            .accelerator
+            .trim()
+            .parse()
+            .map_err(|err| format!("Invalid key '{}': {err}", binding.accelerator))?;
+        manager
+            .register(shortcut)
+            .map_err(|err| format!("Could not register '{}': {err}", binding.accelerator))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(desktop))]
+fn register(_app: &tauri::AppHandle, settings: &ShortcutSettings) -> Result<(), String> {
+    if settings.enabled {
+        return Err("Global shortcuts are only available on desktop".to_string());
+    }
+    Ok(())
+}
+
+/// Global-shortcut plugin handler: maps a pressed key back to its action.
+#[cfg(desktop)]
+pub fn handle(
+    app: &tauri::AppHandle,
+    shortcut: &tauri_plugin_global_shortcut::Shortcut,
+    event: tauri_plugin_global_shortcut::ShortcutEvent,
+) {
+    use tauri_plugin_global_shortcut::{Shortcut, ShortcutState};
+
+    if event.state() != ShortcutState::Pressed {
+        return;
+    }
+    let settings: ShortcutSettings =
+        match local_db(app).and_then(|conn| get_setting(&conn, SETTINGS_KEY)) {
+            Ok(settings) => settings.unwrap_or_default(),
+            Err(err) => {
+                log_error(&err);
+                return;
+            }
+        };
+    if !settings.background {
+        let focused = app
+            .get_webview_window("main")
+            .and_then(|window| window.is_focused().ok())
+            .unwrap_or(false);
+        if !focused {
+            return;
+        }
+    }
+    let action = settings.bindings.iter().find_map(|binding| {
+        let bound: Shortcut = binding.accelerator.trim().parse().ok()?;
+        (bound == *shortcut).then_some(binding.action)
+    });
+    if let Some(action) = action {
+        let _ = app.emit("review-shortcut", ShortcutPressed { action });
+    }
+}
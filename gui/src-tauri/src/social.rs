@@ -0,0 +1,151 @@
+//! Opt-in weekly leaderboard on the auth server. Nothing is shared until the
+//! leaderboard is turned on; from then on each `leaderboard` call reports this
+//! week's review count and the current streak under the chosen display name.
+//! Turning it off removes the row from the server.
+
+use chrono::{Datelike, Duration, NaiveDate, Utc};
+use le_client::{LeaderboardStats, WeeklyLeaderboard};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use tauri::command;
+
+use crate::{emit_settings_changed, get_setting, local_db, perf, run_blocking, set_setting};
+
+pub const SETTINGS_KEY: &str = "social";
+const MAX_DISPLAY_NAME_CHARS: usize = 64;
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SocialSettings {
+    #[serde(default)]
+    leaderboard: bool,
+    #[serde(default)]
+    display_name: String,
+}
+
+#[command]
+pub fn get_social_settings(app: tauri::AppHandle) -> Result<SocialSettings, String> {
+    let _perf = perf::track("get_social_settings");
+    let conn = local_db(&app)?;
+    Ok(get_setting(&conn, SETTINGS_KEY)?.unwrap_or_default())
+}
+
+/// Joins or leaves the leaderboard on the server first, and only stores the
+/// choice once the server has accepted it.
+#[command]
+pub async fn set_social_settings(
+    app: tauri::AppHandle,
+    input: SocialSettings,
+    server_url: String,
+    access_token: String,
+) -> Result<SocialSettings, String> {
+    run_blocking(move || {
+        let _perf = perf::track("set_social_settings");
+        let settings = SocialSettings {
+            display_name: input.display_name.trim().to_string(),
+            ..input
+        };
+        let client = client(server_url, access_token)?;
+        if settings.leaderboard {
+            let name_chars = settings.display_name.chars().count();
+            if name_chars == 0 || name_chars > MAX_DISPLAY_NAME_CHARS {
+                return Err(format!(
+                    "Display name must be 1 to {MAX_DISPLAY_NAME_CHARS} characters"
+                ));
+            }
+            let stats = local_stats(&*local_db(&app)?, &settings.display_name)?;
+            perf::network(|| client.join_leaderboard(&stats))
+                .map_err(|err| format!("Leaderboard error: {err}"))?;
+        } else {
+            perf::network(|| client.leave_leaderboard())
+                .map_err(|err| format!("Leaderboard error: {err}"))?;
+        }
+        let conn = local_db(&app)?;
+        set_setting(&conn, SETTINGS_KEY, &settings)?;
+        emit_settings_changed(&app, SETTINGS_KEY);
+        Ok(settings)
+    })
+    .await
+}
+
+/// Reports this week's numbers and returns the weekly board.
+#[command]
+pub async fn leaderboard(
+    app: tauri::AppHandle,
+    server_url: String,
+    access_token: String,
+) -> Result<WeeklyLeaderboard, String> {
+    run_blocking(move || {
+        let _perf = perf::track("leaderboard");
+        let conn = local_db(&app)?;
+        let settings: SocialSettings = get_setting(&conn, SETTINGS_KEY)?.unwrap_or_default();
+        if !settings.leaderboard {
+            return Err("Join the leaderboard in settings first".to_string());
+        }
+        let stats = local_stats(&conn, &settings.display_name)?;
+        // Let grading carry on while the requests are in flight.
+        drop(conn);
+        let client = client(server_url, access_token)?;
+        perf::network(|| client.join_leaderboard(&stats))
+            .map_err(|err| format!("Leaderboard error: {err}"))?;
+        perf::network(|| client.weekly_leaderboard())
+            .map_err(|err| format!("Leaderboard error: {err}"))
+    })
+    .await
+}
+
+fn client(server_url: String, access_token: String) -> Result<le_client::Client, String> {
+    Ok(le_client::Client::new(server_url)
+        .map_err(|err| err.to_string())?
+        .with_access_token(access_token))
+}
+
+/// Reviews since Monday and the run of consecutive days with reviews, both in
+/// UTC to match the server's week. A streak still counts until a day passes
+/// without a review, so it includes yesterday when nothing is reviewed today.
+fn local_stats(conn: &Connection, display_name: &str) -> Result<LeaderboardStats, String> {
+    let today = Utc::now().date_naive();
+    let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    let since = monday.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+    let reviews: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM reviews WHERE reviewed_at >= ?1",
+            [since.to_rfc3339()],
+            |row| row.get(0),
+        )
+        .map_err(|err| err.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT DISTINCT substr(reviewed_at, 1, 10) FROM reviews
+             ORDER BY 1 DESC",
+        )
+        .map_err(|err| err.to_string())?;
+    let days = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|err| err.to_string())?;
+    let mut streak_days = 0;
+    let mut expected = today;
+    for day in days {
+        let day = day.map_err(|err| err.to_string())?;
+        let Ok(day) = NaiveDate::parse_from_str(&day, "%Y-%m-%d") else {
+            continue;
+        };
+        if day > today {
+            continue;
+        }
+        if streak_days == 0 && day == today - Duration::days(1) {
+            expected = day;
+        }
+        if day != expected {
+            break;
+        }
+        streak_days += 1;
+        expected = day - Duration::days(1);
+    }
+
+    Ok(LeaderboardStats {
+        display_name: display_name.to_string(),
+        reviews,
+        streak_days,
+    })
+}
@@ -0,0 +1,95 @@
+//! Hiding cards from review without deleting their words. A suspended card
+//! stays out of sessions until `unsuspend_card`; a buried one only until the
+//! next local midnight. Both are local to this device and survive a full
+//! refresh. Cards already queued in the running session are taken out too.
+
+use std::sync::Mutex;
+
+use chrono::{Duration, Local, Utc};
+use rusqlite::{Connection, params};
+use tauri::{State, command};
+
+use crate::{ReviewState, local_db, perf, usage};
+
+#[command]
+pub fn suspend_card(
+    app: tauri::AppHandle,
+    state: State<'_, Mutex<ReviewState>>,
+    card_id: String,
+) -> Result<(), String> {
+    let _perf = perf::track("suspend_card");
+    let conn = local_db(&app)?;
+    update_card(
+        &conn,
+        "UPDATE cards SET suspended = 1 WHERE id = ?1",
+        &card_id,
+        None,
+    )?;
+    dequeue(&state, &card_id)?;
+    usage::feature_used(&conn, "suspend_card");
+    Ok(())
+}
+
+/// Hides the card until the next local midnight.
+#[command]
+pub fn bury_card(
+    app: tauri::AppHandle,
+    state: State<'_, Mutex<ReviewState>>,
+    card_id: String,
+) -> Result<(), String> {
+    let _perf = perf::track("bury_card");
+    let conn = local_db(&app)?;
+    let until = (Local::now().date_naive() + Duration::days(1))
+        .and_hms_opt(0, 0, 0)
+        .and_then(|time| time.and_local_timezone(Local).earliest())
+        .map(|time| time.with_timezone(&Utc).to_rfc3339())
+        .ok_or_else(|| "Could not work out local midnight".to_string())?;
+    update_card(
+        &conn,
+        "UPDATE cards SET buried_until = ?2 WHERE id = ?1",
+        &card_id,
+        Some(&until),
+    )?;
+    dequeue(&state, &card_id)?;
+    usage::feature_used(&conn, "bury_card");
+    Ok(())
+}
+
+/// Lifts both a suspension and a burial.
+#[command]
+pub fn unsuspend_card(app: tauri::AppHandle, card_id: String) -> Result<(), String> {
+    let _perf = perf::track("unsuspend_card");
+    let conn = local_db(&app)?;
+    update_card(
+        &conn,
+        "UPDATE cards SET suspended = 0, buried_until = NULL WHERE id = ?1",
+        &card_id,
+        None,
+    )
+}
+
+fn update_card(
+    conn: &Connection,
+    sql: &str,
+    card_id: &str,
+    until: Option<&str>,
+) -> Result<(), String> {
+    let updated = match until {
+        Some(until) => conn.execute(sql, params![card_id, until]),
+        None => conn.execute(sql, params![card_id]),
+    }
+    .map_err(|err| err.to_string())?;
+    if updated == 0 {
+        return Err(format!("No card with id {card_id}"));
+    }
+    Ok(())
+}
+
+fn dequeue(state: &State<'_, Mutex<ReviewState>>, card_id: &str) -> Result<(), String> {
+    state
+        .lock()
+        .map_err(|_| "Failed to lock review state".to_string())?
+        .session
+        .remove(|queued| queued == card_id);
+    Ok(())
+}
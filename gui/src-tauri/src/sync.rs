@@ -0,0 +1,473 @@
+//! Two-way sync of decks, words, cards, and reviews with Postgres. Unlike
+//! `refresh_from_postgres`, nothing is wiped: every row is merged three ways
+//! between the copy both sides agreed on at the last sync (the local
+//! `sync_base` table), the local row, and the Postgres row.
+//!
+//! - A row changed on one side only takes that side's version, so grades
+//!   recorded offline are pushed up and edits from other devices come down.
+//! - A row in the base but gone from one side was deleted there (the base
+//!   acts as the tombstone) and is deleted on the other side too, unless the
+//!   other side changed it since, in which case the edit wins and the row is
+//!   restored.
+//! - When both sides changed a row, a card takes the state of the side with
+//!   the later review, and a word keeps the fields only one side changed and
+//!   takes Postgres's value where both did.
+//!
+//! Local-only columns (`seen_count`, FSRS stability and difficulty) are not
+//! compared and never overwritten. The first sync, with no base yet, treats
+//! every row present on only one side as new there.
+//!
+//! Concepts, tags, and word tags are one-way: they only come down with
+//! `refresh_from_postgres` and are neither merged nor pushed here.
+//!
+//! Postgres is reached through [`remote::RemoteSync`]; the merge itself, and
+//! [`reset_base`] after a refresh from either remote, is the same in every
+//! build.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Mutex;
+
+use chrono::Utc;
+use le_client::connectivity;
+use rusqlite::types::{ToSqlOutput, Value};
+use rusqlite::{Connection, params};
+use serde::{Deserialize, Serialize};
+use tauri::{Manager, command};
+
+use crate::remote;
+use crate::{ReviewState, local_db, log_error, perf, run_blocking, usage};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Kind {
+    Text,
+    Int,
+    Real,
+}
+
+/// A synced table; `id` comes first in `columns`.
+pub struct Table {
+    pub name: &'static str,
+    pub columns: &'static [(&'static str, Kind)],
+}
+
+const DECKS: Table = Table {
+    name: "decks",
+    columns: &[
+        ("id", Kind::Text),
+        ("name", Kind::Text),
+        ("created_at", Kind::Text),
+    ],
+};
+
+const WORDS: Table = Table {
+    name: "words",
+    columns: &[
+        ("id", Kind::Text),
+        ("text", Kind::Text),
+        ("language", Kind::Text),
+        ("translation", Kind::Text),
+        ("chapter", Kind::Text),
+        ("group_name", Kind::Text),
+        ("notes", Kind::Text),
+        ("created_at", Kind::Text),
+        ("source_type", Kind::Text),
+        ("source_name", Kind::Text),
+        ("source_page", Kind::Text),
+        ("captured_at", Kind::Text),
+        ("source_region", Kind::Text),
+        ("alt_answers", Kind::Text),
+        ("kind", Kind::Text),
+        ("deck_id", Kind::Text),
+    ],
+};
+
+const CARDS: Table = Table {
+    name: "cards",
+    columns: &[
+        ("id", Kind::Text),
+        ("word_id", Kind::Text),
+        ("due_at", Kind::Text),
+        ("interval_days", Kind::Int),
+        ("ease", Kind::Real),
+        ("reps", Kind::Int),
+        ("lapses", Kind::Int),
+        ("deck_id", Kind::Text),
+        ("card_type", Kind::Text),
+    ],
+};
+
+const REVIEWS: Table = Table {
+    name: "reviews",
+    columns: &[
+        ("id", Kind::Text),
+        ("card_id", Kind::Text),
+        ("grade", Kind::Int),
+        ("reviewed_at", Kind::Text),
+    ],
+};
+
+/// Parents first, so inserts never point at a missing row; deletes run in
+/// reverse.
+const TABLES: [&Table; 4] = [&DECKS, &WORDS, &CARDS, &REVIEWS];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Field {
+    Int(i64),
+    Real(f64),
+    Text(String),
+}
+
+impl rusqlite::ToSql for Field {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::Owned(match self {
+            Field::Int(value) => Value::Integer(*value),
+            Field::Real(value) => Value::Real(*value),
+            Field::Text(value) => Value::Text(value.clone()),
+        }))
+    }
+}
+
+pub type Row = Vec<Option<Field>>;
+pub type Rows = BTreeMap<String, Row>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Local,
+    Remote,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct SyncReport {
+    /// Rows written to Postgres.
+    pushed: usize,
+    /// Rows written locally.
+    pulled: usize,
+    deleted_remote: usize,
+    deleted_local: usize,
+    /// Rows both sides had changed.
+    conflicts: usize,
+}
+
+/// What one table needs on each side after the merge.
+#[derive(Default)]
+pub struct TableMerge {
+    pub upload: Vec<Row>,
+    pub remove_remote: Vec<String>,
+    download: Vec<Row>,
+    remove_local: Vec<String>,
+    /// The agreed state, stored as the next sync's base.
+    base: Rows,
+    conflicts: usize,
+}
+
+/// Merges local and Postgres decks, words, cards, and reviews both ways. Offline,
+/// nothing is touched and local changes wait for the next sync.
+#[command]
+pub async fn sync_with_postgres(app: tauri::AppHandle) -> Result<SyncReport, String> {
+    run_blocking(move || {
+        let _perf = perf::track("sync_with_postgres");
+        let mut client = remote::open()?;
+        if client.is_offline() {
+            return Err(connectivity::OFFLINE_QUEUED.to_string());
+        }
+        let mut remote = Vec::new();
+        for table in TABLES {
+            remote.push(client.load_rows(table)?);
+        }
+        // Held until the merge is applied, so a grade given meanwhile is not
+        // overwritten by a merge that never saw it.
+        let mut conn = local_db(&app)?;
+        let mut local = Vec::new();
+        let mut base = Vec::new();
+        for table in TABLES {
+            local.push(load_local(&conn, table).map_err(|err| fail("read local", err))?);
+            base.push(load_base(&conn, table)?);
+        }
+
+        // Cards both sides rescheduled keep the state of the later review.
+        let local_last = last_reviews(&local[3]);
+        let remote_last = last_reviews(&remote[3]);
+        let merges: Vec<TableMerge> = TABLES
+            .iter()
+            .enumerate()
+            .map(|(index, table)| {
+                merge_table(
+                    &base[index],
+                    &local[index],
+                    &remote[index],
+                    |id| match table.name {
+                        "cards" if local_last.get(id) > remote_last.get(id) => Side::Local,
+                        _ => Side::Remote,
+                    },
+                    table.name == "cards",
+                )
+            })
+            .collect();
+
+        // Postgres first: if the push fails nothing changes locally, and the
+        // next sync starts from the same base.
+        let mut report = SyncReport::default();
+        let pairs: Vec<(&Table, &TableMerge)> = TABLES.into_iter().zip(&merges).collect();
+        (report.pushed, report.deleted_remote) = client.push_merge(&pairs)?;
+        drop(client);
+
+        let tx = conn.transaction().map_err(|err| fail("begin local", err))?;
+        for (table, merge) in TABLES.iter().zip(&merges) {
+            let query = upsert_sql(table, '?');
+            for row in &merge.download {
+                tx.execute(&query, rusqlite::params_from_iter(row))
+                    .map_err(|err| fail(&format!("pull {}", table.name), err))?;
+                report.pulled += 1;
+            }
+        }
+        let mut deleted_cards = Vec::new();
+        for (table, merge) in TABLES.iter().zip(&merges).rev() {
+            let query = format!("DELETE FROM {} WHERE id = ?1", table.name);
+            for id in &merge.remove_local {
+                tx.execute(&query, params![id])
+                    .map_err(|err| fail(&format!("delete local {}", table.name), err))?;
+                report.deleted_local += 1;
+                if table.name == "cards" {
+                    deleted_cards.push(id.clone());
+                }
+            }
+        }
+        for (table, merge) in TABLES.iter().zip(&merges) {
+            store_base(&tx, table, &merge.base)?;
+            report.conflicts += merge.conflicts;
+        }
+        tx.commit().map_err(|err| fail("commit local", err))?;
+        usage::feature_used(&conn, "two_way_sync");
+        drop(conn);
+
+        if !deleted_cards.is_empty()
+            && let Ok(mut guard) = app.state::<Mutex<ReviewState>>().lock()
+        {
+            guard.session.remove(|id| deleted_cards.contains(id));
+        }
+        Ok(report)
+    })
+    .await
+}
+
+/// Makes the current local rows the base, for after a full refresh has made
+/// them equal to Postgres.
+pub fn reset_base(conn: &Connection) -> Result<(), String> {
+    for table in TABLES {
+        let rows = load_local(conn, table).map_err(|err| fail("read local", err))?;
+        store_base(conn, table, &rows)?;
+    }
+    Ok(())
+}
+
+fn merge_table(
+    base: &Rows,
+    local: &Rows,
+    remote: &Rows,
+    winner: impl Fn(&str) -> Side,
+    whole_row: bool,
+) -> TableMerge {
+    let mut merge = TableMerge::default();
+    let ids: BTreeSet<&String> = base
+        .keys()
+        .chain(local.keys())
+        .chain(remote.keys())
+        .collect();
+    for id in ids {
+        let (base_row, local_row, remote_row) = (base.get(id), local.get(id), remote.get(id));
+        let merged = match (local_row, remote_row) {
+            (None, None) => continue,
+            (Some(local_row), None) => match base_row {
+                // Deleted in Postgres and untouched here.
+                Some(base_row) if base_row == local_row => {
+                    merge.remove_local.push(id.clone());
+                    continue;
+                }
+                _ => local_row.clone(),
+            },
+            (None, Some(remote_row)) => match base_row {
+                Some(base_row) if base_row == remote_row => {
+                    merge.remove_remote.push(id.clone());
+                    continue;
+                }
+                _ => remote_row.clone(),
+            },
+            (Some(local_row), Some(remote_row)) => {
+                let (merged, conflict) =
+                    merge_row(base_row, local_row, remote_row, winner(id), whole_row);
+                merge.conflicts += usize::from(conflict);
+                merged
+            }
+        };
+        if local_row != Some(&merged) {
+            merge.download.push(merged.clone());
+        }
+        if remote_row != Some(&merged) {
+            merge.upload.push(merged.clone());
+        }
+        merge.base.insert(id.clone(), merged);
+    }
+    merge
+}
+
+/// Field by field, a change on one side wins. Fields both sides changed go
+/// to `winner`; with `whole_row`, any such field hands `winner` the whole
+/// row, since a card's scheduling fields only make sense together.
+fn merge_row(
+    base: Option<&Row>,
+    local: &Row,
+    remote: &Row,
+    winner: Side,
+    whole_row: bool,
+) -> (Row, bool) {
+    if local == remote {
+        return (local.clone(), false);
+    }
+    let pick = |side: Side| match side {
+        Side::Local => local.clone(),
+        Side::Remote => remote.clone(),
+    };
+    let Some(base) = base else {
+        return (pick(winner), true);
+    };
+    let mut merged = Vec::with_capacity(local.len());
+    let mut conflict = false;
+    for ((base_value, local_value), remote_value) in base.iter().zip(local).zip(remote) {
+        merged.push(if local_value == base_value {
+            remote_value.clone()
+        } else if remote_value == base_value || remote_value == local_value {
+            local_value.clone()
+        } else {
+            conflict = true;
+            match winner {
+                Side::Local => local_value.clone(),
+                Side::Remote => remote_value.clone(),
+            }
+        });
+    }
+    if conflict && whole_row {
+        return (pick(winner), true);
+    }
+    (merged, conflict)
+}
+
+/// Latest `reviewed_at` per card id.
+fn last_reviews(reviews: &Rows) -> BTreeMap<String, String> {
+    let mut last: BTreeMap<String, String> = BTreeMap::new();
+    for row in reviews.values() {
+        if let (Some(Field::Text(card_id)), Some(Field::Text(reviewed_at))) = (&row[1], &row[3]) {
+            let entry = last.entry(card_id.clone()).or_default();
+            if *reviewed_at > *entry {
+                *entry = reviewed_at.clone();
+            }
+        }
+    }
+    last
+}
+
+pub fn row_id(row: &Row) -> String {
+    match row.first() {
+        Some(Some(Field::Text(id))) => id.clone(),
+        _ => String::new(),
+    }
+}
+
+pub fn columns(table: &Table) -> String {
+    table
+        .columns
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// `INSERT .. ON CONFLICT (id) DO UPDATE` with `?N` (SQLite) or `$N`
+/// (Postgres) placeholders.
+pub fn upsert_sql(table: &Table, placeholder: char) -> String {
+    let values = (1..=table.columns.len())
+        .map(|index| format!("{placeholder}{index}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let updates = table.columns[1..]
+        .iter()
+        .map(|(name, _)| format!("{name} = excluded.{name}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "INSERT INTO {} ({}) VALUES ({values}) ON CONFLICT (id) DO UPDATE SET {updates}",
+        table.name,
+        columns(table)
+    )
+}
+
+fn load_local(conn: &Connection, table: &Table) -> rusqlite::Result<Rows> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM {}", columns(table), table.name))?;
+    let mut rows = stmt.query([])?;
+    let mut loaded = Rows::new();
+    while let Some(row) = rows.next()? {
+        let mut fields = Row::with_capacity(table.columns.len());
+        for (index, (_, kind)) in table.columns.iter().enumerate() {
+            fields.push(match kind {
+                Kind::Text => row.get::<_, Option<String>>(index)?.map(Field::Text),
+                Kind::Int => row.get::<_, Option<i64>>(index)?.map(Field::Int),
+                Kind::Real => row.get::<_, Option<f64>>(index)?.map(Field::Real),
+            });
+        }
+        loaded.insert(row_id(&fields), fields);
+    }
+    Ok(loaded)
+}
+
+fn load_base(conn: &Connection, table: &Table) -> Result<Rows, String> {
+    let mut stmt = conn
+        .prepare("SELECT row_id, row FROM sync_base WHERE table_name = ?1")
+        .map_err(|err| fail("read base", err))?;
+    let rows = stmt
+        .query_map(params![table.name], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|err| fail("read base", err))?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|err| fail("read base", err))?;
+    let mut base = Rows::new();
+    for (id, json) in rows {
+        match serde_json::from_str::<Row>(&json) {
+            Ok(mut row) => {
+                // Rows stored before a column was added lack it; it was
+                // empty on both sides then.
+                row.resize(table.columns.len(), None);
+                base.insert(id, row);
+            }
+            // Treated as never synced: the row is merged as new on both sides.
+            Err(err) => log_error(&format!("sync: unreadable base row {id}: {err}")),
+        }
+    }
+    Ok(base)
+}
+
+fn store_base(conn: &Connection, table: &Table, rows: &Rows) -> Result<(), String> {
+    let synced_at = Utc::now().to_rfc3339();
+    conn.execute(
+        "DELETE FROM sync_base WHERE table_name = ?1",
+        params![table.name],
+    )
+    .map_err(|err| fail("write base", err))?;
+    let mut stmt = conn
+        .prepare(
+            "INSERT INTO sync_base (table_name, row_id, row, synced_at) VALUES (?1, ?2, ?3, ?4)",
+        )
+        .map_err(|err| fail("write base", err))?;
+    for (id, row) in rows {
+        let json = serde_json::to_string(row).map_err(|err| err.to_string())?;
+        stmt.execute(params![table.name, id, json, synced_at])
+            .map_err(|err| fail("write base", err))?;
+    }
+    Ok(())
+}
+
+pub fn fail(step: &str, err: impl std::fmt::Display) -> String {
+    let message = format!("sync_with_postgres: {step} failed: {err}");
+    log_error(&message);
+    message
+}
@@ -0,0 +1,205 @@
+//! Opt-in usage metrics for reviewing your own study habits. Off until
+//! enabled; while on, review sessions and feature use are counted in local
+//! tables that never sync and are never sent anywhere. `export_usage` writes
+//! the summary to a file of your choosing.
+
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, params};
+use serde::{Deserialize, Serialize};
+use tauri::command;
+
+use crate::{emit_settings_changed, get_setting, local_db, log_error, perf, set_setting};
+
+pub const SETTINGS_KEY: &str = "usage";
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct UsageSettings {
+    #[serde(default)]
+    enabled: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FeatureUse {
+    feature: String,
+    uses: i64,
+    last_used_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UsageReport {
+    enabled: bool,
+    sessions_started: i64,
+    /// Sessions with at least one review.
+    sessions_reviewed: i64,
+    /// From session start to its last review, over `sessions_reviewed`.
+    average_session_minutes: f64,
+    reviews: i64,
+    first_session_at: Option<String>,
+    features: Vec<FeatureUse>,
+}
+
+#[command]
+pub fn get_usage_settings(app: tauri::AppHandle) -> Result<UsageSettings, String> {
+    let _perf = perf::track("get_usage_settings");
+    let conn = local_db(&app)?;
+    Ok(get_setting(&conn, SETTINGS_KEY)?.unwrap_or_default())
+}
+
+/// Turning metrics off stops counting but keeps what was recorded; use
+/// `clear_usage` to delete it.
+#[command]
+pub fn set_usage_settings(app: tauri::AppHandle, enabled: bool) -> Result<UsageSettings, String> {
+    let _perf = perf::track("set_usage_settings");
+    let conn = local_db(&app)?;
+    let settings = UsageSettings { enabled };
+    set_setting(&conn, SETTINGS_KEY, &settings)?;
+    emit_settings_changed(&app, SETTINGS_KEY);
+    Ok(settings)
+}
+
+#[command]
+pub fn usage_report(app: tauri::AppHandle) -> Result<UsageReport, String> {
+    let _perf = perf::track("usage_report");
+    let conn = local_db(&app)?;
+    report(&conn).map_err(|err| err.to_string())
+}
+
+/// Writes the usage report as JSON to `path` and returns it.
+#[command]
+pub fn export_usage(app: tauri::AppHandle, path: String) -> Result<UsageReport, String> {
+    let _perf = perf::track("export_usage");
+    let conn = local_db(&app)?;
+    let report = report(&conn).map_err(|err| err.to_string())?;
+    let json = serde_json::to_string_pretty(&report).map_err(|err| err.to_string())?;
+    std::fs::write(&path, json).map_err(|err| format!("Failed to write {path}: {err}"))?;
+    Ok(report)
+}
+
+/// Deletes every recorded session and feature count.
+#[command]
+pub fn clear_usage(app: tauri::AppHandle) -> Result<(), String> {
+    let _perf = perf::track("clear_usage");
+    let conn = local_db(&app)?;
+    conn.execute_batch("DELETE FROM usage_sessions; DELETE FROM usage_features;")
+        .map_err(|err| err.to_string())
+}
+
+/// Called by `start_session`.
+pub fn session_started(conn: &Connection) {
+    record(conn, |now| {
+        conn.execute(
+            "INSERT INTO usage_sessions (started_at, last_review_at, reviews)
+             VALUES (?1, NULL, 0)",
+            params![now],
+        )
+    });
+}
+
+/// Called for every graded card; extends the latest session.
+pub fn card_reviewed(conn: &Connection) {
+    record(conn, |now| {
+        conn.execute(
+            "UPDATE usage_sessions SET last_review_at = ?1, reviews = reviews + 1
+             WHERE id = (SELECT MAX(id) FROM usage_sessions)",
+            params![now],
+        )
+    });
+}
+
+/// Counts one use of `feature`, e.g. `compact_mode` or `calendar_export`.
+pub fn feature_used(conn: &Connection, feature: &str) {
+    record(conn, |now| {
+        conn.execute(
+            "INSERT INTO usage_features (feature, uses, last_used_at) VALUES (?1, 1, ?2)
+             ON CONFLICT(feature) DO UPDATE SET
+                uses = uses + 1, last_used_at = excluded.last_used_at",
+            params![feature, now],
+        )
+    });
+}
+
+/// Runs `write` only while metrics are enabled. Failures are logged rather
+/// than returned so counting never breaks the command that triggered it.
+fn record(conn: &Connection, write: impl FnOnce(&str) -> rusqlite::Result<usize>) {
+    let enabled = match get_setting::<UsageSettings>(conn, SETTINGS_KEY) {
+        Ok(settings) => settings.is_some_and(|settings| settings.enabled),
+        Err(err) => {
+            log_error(&format!("usage metrics: {err}"));
+            return;
+        }
+    };
+    if !enabled {
+        return;
+    }
+    if let Err(err) = write(&Utc::now().to_rfc3339()) {
+        log_error(&format!("usage metrics: {err}"));
+    }
+}
+
+fn report(conn: &Connection) -> rusqlite::Result<UsageReport> {
+    let enabled = get_setting::<UsageSettings>(conn, SETTINGS_KEY)
+        .ok()
+        .flatten()
+        .is_some_and(|settings| settings.enabled);
+
+    let mut stmt =
+        conn.prepare("SELECT started_at, last_review_at, reviews FROM usage_sessions ORDER BY id")?;
+    let sessions = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    let mut reviews = 0;
+    let mut reviewed = 0;
+    let mut total_minutes = 0.0;
+    for (started_at, last_review_at, count) in &sessions {
+        reviews += count;
+        let (Some(started), Some(ended)) = (
+            parse_time(started_at),
+            last_review_at.as_deref().and_then(parse_time),
+        ) else {
+            continue;
+        };
+        reviewed += 1;
+        total_minutes += (ended - started).num_seconds().max(0) as f64 / 60.0;
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT feature, uses, last_used_at FROM usage_features ORDER BY uses DESC, feature",
+    )?;
+    let features = stmt
+        .query_map([], |row| {
+            Ok(FeatureUse {
+                feature: row.get(0)?,
+                uses: row.get(1)?,
+                last_used_at: row.get(2)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    Ok(UsageReport {
+        enabled,
+        sessions_started: sessions.len() as i64,
+        sessions_reviewed: reviewed,
+        average_session_minutes: if reviewed > 0 {
+            total_minutes / reviewed as f64
+        } else {
+            0.0
+        },
+        reviews,
+        first_session_at: sessions
+            .first()
+            .map(|(started_at, _, _)| started_at.clone()),
+        features,
+    })
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
@@ -0,0 +1,92 @@
+//! App-wide actions and the Ctrl shortcuts bound to them. `handle_key` maps a
+//! key to an [`Action`] here and hands it to `dispatch`, so a new shortcut is
+//! one row in [`CTRL_BINDINGS`] plus one arm in `dispatch`, not an arm in
+//! every mode's handler.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::Mode;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    AddWord,
+    ReviewList,
+    Import,
+    CleanupReview,
+    FixTranslations,
+    AddReverseCards,
+}
+
+/// Ctrl+key bindings. `Ctrl+I` stays as an alias for `Ctrl+O`, but most
+/// terminals send it as Tab, so hints only show `Ctrl+O`.
+pub const CTRL_BINDINGS: [(char, Action); 9] = [
+    ('c', Action::Quit),
+    ('q', Action::Quit),
+    ('a', Action::AddWord),
+    ('v', Action::ReviewList),
+    ('o', Action::Import),
+    ('i', Action::Import),
+    ('k', Action::CleanupReview),
+    ('t', Action::FixTranslations),
+    ('r', Action::AddReverseCards),
+];
+
+impl Action {
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::AddWord => "add word",
+            Action::ReviewList => "review list",
+            Action::Import => "import images",
+            Action::CleanupReview => "AI cleanup review",
+            Action::FixTranslations => "fix missing translations",
+            Action::AddReverseCards => "add reverse cards to existing words",
+        }
+    }
+
+    /// Whether the action may interrupt `mode`. Modes holding an unanswered
+    /// decision (a delete confirmation, an import preview, a review of
+    /// suggested translations) or a running import only let the user quit,
+    /// so their pending state is never silently abandoned.
+    pub fn allowed_in(self, mode: Mode) -> bool {
+        match mode {
+            Mode::Confirm
+            | Mode::ImportPreview
+            | Mode::ImportCsvPreview
+            | Mode::ImportCsvColumns
+            | Mode::CleanupReview
+            | Mode::TranslationFix
+            | Mode::Importing => self == Action::Quit,
+            Mode::Menu
+            | Mode::AddWord
+            | Mode::ReviewList
+            | Mode::Import
+            | Mode::ImportCsv
+            | Mode::ChapterSelect
+            | Mode::TagPrompt
+            | Mode::ReviewSearch
+            | Mode::ReviewSession
+            | Mode::DeckSelect
+            | Mode::Stats
+            | Mode::RecentAdditions
+            | Mode::Glossary
+            | Mode::Message => true,
+        }
+    }
+}
+
+/// The action bound to a Ctrl+key press, if any.
+pub fn ctrl_action(key: &KeyEvent) -> Option<Action> {
+    if !key.modifiers.contains(KeyModifiers::CONTROL) {
+        return None;
+    }
+    let KeyCode::Char(pressed) = key.code else {
+        return None;
+    };
+    let pressed = pressed.to_ascii_lowercase();
+    CTRL_BINDINGS
+        .iter()
+        .find(|(bound, _)| *bound == pressed)
+        .map(|(_, action)| *action)
+}
@@ -0,0 +1,249 @@
+//! Word lists exported from a spreadsheet as `text,translation,chapter,group`
+//! rows. Fields may be quoted the way spreadsheets write them ("a, b" and
+//! doubled `""` quotes), and files saved with `;` or tab separators by
+//! localized spreadsheet apps are read too. Columns in another order are
+//! read through a [`ColumnMapping`], taken from a header row naming them or
+//! given by the caller.
+
+use std::fmt;
+
+/// One word read from the file. Empty cells become `None`.
+#[derive(Debug, Clone)]
+pub struct CsvRow {
+    /// 1-based line number, for the preview and the word's source.
+    pub line: usize,
+    pub text: String,
+    pub translation: Option<String>,
+    pub chapter: Option<String>,
+    pub group: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvField {
+    Text,
+    Translation,
+    Chapter,
+    Group,
+}
+
+impl CsvField {
+    fn name(self) -> &'static str {
+        match self {
+            CsvField::Text => "text",
+            CsvField::Translation => "translation",
+            CsvField::Chapter => "chapter",
+            CsvField::Group => "group",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "text" | "word" => Some(CsvField::Text),
+            "translation" | "meaning" => Some(CsvField::Translation),
+            "chapter" => Some(CsvField::Chapter),
+            "group" => Some(CsvField::Group),
+            _ => None,
+        }
+    }
+}
+
+/// What each column holds, by position; `None` columns are ignored. Written
+/// as the field names separated by commas, with an empty name for a skipped
+/// column: `translation,text,,chapter`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnMapping(Vec<Option<CsvField>>);
+
+impl Default for ColumnMapping {
+    fn default() -> Self {
+        ColumnMapping(vec![
+            Some(CsvField::Text),
+            Some(CsvField::Translation),
+            Some(CsvField::Chapter),
+            Some(CsvField::Group),
+        ])
+    }
+}
+
+impl ColumnMapping {
+    /// Parses the comma-separated form. Every column must be a known field
+    /// or empty, no field may appear twice, and `text` is required.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let mut columns = Vec::new();
+        for name in value.split(',') {
+            let field = if name.trim().is_empty() {
+                None
+            } else {
+                let field = CsvField::parse(name).ok_or_else(|| {
+                    format!(
+                        "Unknown column '{}' (expected text, translation, chapter, or group)",
+                        name.trim()
+                    )
+                })?;
+                if columns.contains(&Some(field)) {
+                    return Err(format!("Column '{}' is mapped twice", field.name()));
+                }
+                Some(field)
+            };
+            columns.push(field);
+        }
+        if !columns.contains(&Some(CsvField::Text)) {
+            return Err("One column must be text".to_string());
+        }
+        Ok(ColumnMapping(columns))
+    }
+
+    /// The mapping a header row spells out, when every named cell is a
+    /// known field and one of them is `text`.
+    fn from_header(fields: &[String]) -> Option<Self> {
+        let columns = fields
+            .iter()
+            .map(|cell| {
+                if cell.trim().is_empty() {
+                    Some(None)
+                } else {
+                    CsvField::parse(cell).map(Some)
+                }
+            })
+            .collect::<Option<Vec<_>>>()?;
+        columns
+            .contains(&Some(CsvField::Text))
+            .then_some(ColumnMapping(columns))
+    }
+
+    fn read(&self, fields: &[String], line: usize) -> Option<CsvRow> {
+        let cell = |wanted: CsvField| {
+            let index = self.0.iter().position(|field| *field == Some(wanted))?;
+            let value = fields.get(index)?.trim();
+            (!value.is_empty()).then(|| value.to_string())
+        };
+        Some(CsvRow {
+            line,
+            text: cell(CsvField::Text)?,
+            translation: cell(CsvField::Translation),
+            chapter: cell(CsvField::Chapter),
+            group: cell(CsvField::Group),
+        })
+    }
+}
+
+impl fmt::Display for ColumnMapping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<&str> = self
+            .0
+            .iter()
+            .map(|field| field.map_or("", CsvField::name))
+            .collect();
+        write!(f, "{}", names.join(","))
+    }
+}
+
+/// Rows read from a file, and how its columns were read.
+#[derive(Debug, Clone)]
+pub struct CsvTable {
+    pub rows: Vec<CsvRow>,
+    pub columns: ColumnMapping,
+    /// The first row named the columns and was skipped.
+    pub header: bool,
+}
+
+/// Parses `content` into rows, skipping rows without a word. A first row
+/// that names the columns (`text,translation,...`) is skipped; its mapping is
+/// used unless `columns` is given. Without either, columns are read as
+/// `text,translation,chapter,group`.
+pub fn parse_rows(content: &str, columns: Option<&ColumnMapping>) -> Result<CsvTable, String> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let delimiter = detect_delimiter(content.lines().next().unwrap_or(""));
+    let mut records = split_records(content, delimiter)?.into_iter().peekable();
+    let header = records
+        .peek()
+        .and_then(|(_, fields)| ColumnMapping::from_header(fields));
+    if header.is_some() {
+        records.next();
+    }
+    let is_header = header.is_some();
+    let columns = match (columns, header) {
+        (Some(columns), _) => columns.clone(),
+        (None, Some(header)) => header,
+        (None, None) => ColumnMapping::default(),
+    };
+    let rows = records
+        .filter_map(|(line, fields)| columns.read(&fields, line))
+        .collect();
+    Ok(CsvTable {
+        rows,
+        columns,
+        header: is_header,
+    })
+}
+
+/// The separator used most often outside quotes on the first line, preferring
+/// a comma on a tie.
+fn detect_delimiter(first_line: &str) -> char {
+    let mut in_quotes = false;
+    let mut counts = [(',', 0), (';', 0), ('\t', 0)];
+    for ch in first_line.chars() {
+        if ch == '"' {
+            in_quotes = !in_quotes;
+        } else if !in_quotes && let Some(entry) = counts.iter_mut().find(|(sep, _)| *sep == ch) {
+            entry.1 += 1;
+        }
+    }
+    // `max_by_key` keeps the last of equal counts, so a tie goes to the comma.
+    counts
+        .iter()
+        .rev()
+        .max_by_key(|(_, count)| *count)
+        .map_or(',', |(sep, _)| *sep)
+}
+
+/// Splits `content` into records of fields, each with the line it starts on.
+/// Quoted fields may contain the delimiter, doubled quotes, and line breaks.
+fn split_records(content: &str, delimiter: char) -> Result<Vec<(usize, Vec<String>)>, String> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut chars = content.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if in_quotes => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    in_quotes = false;
+                }
+            }
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                in_quotes = true;
+            }
+            '\n' if !in_quotes => {
+                fields.push(std::mem::take(&mut field));
+                records.push((record_line, std::mem::take(&mut fields)));
+                line += 1;
+                record_line = line;
+            }
+            '\r' if !in_quotes => {}
+            ch if ch == delimiter && !in_quotes => fields.push(std::mem::take(&mut field)),
+            ch => {
+                if ch == '\n' {
+                    line += 1;
+                }
+                field.push(ch);
+            }
+        }
+    }
+    if in_quotes {
+        return Err(format!(
+            "Unterminated quote in the row on line {record_line}"
+        ));
+    }
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push((record_line, fields));
+    }
+    Ok(records)
+}
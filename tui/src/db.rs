@@ -0,0 +1,35 @@
+use std::path::Path;
+
+#[cfg(feature = "remote-sync")]
+use native_tls::TlsConnector;
+#[cfg(feature = "remote-sync")]
+use postgres_native_tls::MakeTlsConnector;
+
+#[cfg(feature = "remote-sync")]
+pub use le_db::PostgresDb;
+pub use le_db::{
+    Db, DbError, DbResult, Deck, ImportProfile, NewWord, SqliteDb, find_import_profile, log_error,
+    log_import, normalize_tag, source_pattern,
+};
+
+pub fn get_db_backend(path: &Path) -> DbResult<Box<dyn Db>> {
+    let backend = std::env::var("BACKEND").expect("Must define a BACKEND. postgres/sqlite");
+    match backend.as_str() {
+        "sqlite" => Ok(Box::new(SqliteDb::open(path)?)),
+        #[cfg(feature = "remote-sync")]
+        "postgres" => {
+            let url = std::env::var("DATABASE_URL").map_err(|_| {
+                DbError::Config("DATABASE_URL is required for postgres".to_string())
+            })?;
+            let connector = TlsConnector::new()
+                .map_err(|err| DbError::Config(format!("Failed to create TLS connector: {err}")))?;
+            let connector = MakeTlsConnector::new(connector);
+            Ok(Box::new(PostgresDb::connect(&url, connector)?))
+        }
+        #[cfg(not(feature = "remote-sync"))]
+        "postgres" => Err(DbError::Config(
+            "This build has no Postgres backend; rebuild with --features remote-sync".to_string(),
+        )),
+        other => Err(DbError::Config(format!("Unknown BACKEND '{other}'"))),
+    }
+}
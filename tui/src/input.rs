@@ -0,0 +1,293 @@
+//! Single-line text fields edited by grapheme cluster, so "één" typed with
+//! combining accents or an emoji with modifiers moves and deletes as one unit.
+
+use std::ops::Range;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// What a key press did to a [`TextInput`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputOutcome {
+    Ignored,
+    Moved,
+    Edited,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TextInput {
+    value: String,
+    /// Byte offset into `value`, always on a grapheme boundary.
+    cursor: usize,
+    /// Other end of the selection while Shift is held; the selection spans
+    /// from here to `cursor`.
+    anchor: Option<usize>,
+}
+
+impl TextInput {
+    pub fn as_str(&self) -> &str {
+        &self.value
+    }
+
+    pub fn clear(&mut self) {
+        self.value.clear();
+        self.cursor = 0;
+        self.anchor = None;
+    }
+
+    /// Replaces the contents and puts the cursor at the end.
+    pub fn set(&mut self, value: impl Into<String>) {
+        self.value = value.into();
+        self.cursor = self.value.len();
+        self.anchor = None;
+    }
+
+    /// Applies the editing keys shared by every field: Left/Right/Home/End
+    /// (with Shift to select, Ctrl or Alt to jump words), Backspace/Delete,
+    /// Ctrl+W or Alt+Backspace to delete a word, and plain characters.
+    pub fn handle_key(&mut self, key: KeyEvent) -> InputOutcome {
+        let select = key.modifiers.contains(KeyModifiers::SHIFT);
+        let word = key
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        match key.code {
+            KeyCode::Left if word => self.move_word_left(select),
+            KeyCode::Left => self.move_left(select),
+            KeyCode::Right if word => self.move_word_right(select),
+            KeyCode::Right => self.move_right(select),
+            KeyCode::Home => self.move_home(select),
+            KeyCode::End => self.move_end(select),
+            KeyCode::Backspace if word => {
+                self.delete_word_back();
+                return InputOutcome::Edited;
+            }
+            KeyCode::Char('w') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.delete_word_back();
+                return InputOutcome::Edited;
+            }
+            KeyCode::Backspace => {
+                self.backspace();
+                return InputOutcome::Edited;
+            }
+            KeyCode::Delete => {
+                self.delete();
+                return InputOutcome::Edited;
+            }
+            KeyCode::Char(ch) if !word => {
+                self.insert_char(ch);
+                return InputOutcome::Edited;
+            }
+            _ => return InputOutcome::Ignored,
+        }
+        InputOutcome::Moved
+    }
+
+    pub fn insert_char(&mut self, ch: char) {
+        self.delete_selection();
+        self.value.insert(self.cursor, ch);
+        self.cursor += ch.len_utf8();
+        self.snap_cursor();
+    }
+
+    /// Inserts pasted text at the cursor. Line breaks become spaces since
+    /// every field holds a single line.
+    pub fn insert_str(&mut self, text: &str) {
+        self.delete_selection();
+        let text = text
+            .trim_end_matches(['\r', '\n'])
+            .replace("\r\n", " ")
+            .replace(['\r', '\n'], " ");
+        self.value.insert_str(self.cursor, &text);
+        self.cursor += text.len();
+        self.snap_cursor();
+    }
+
+    /// Removes the selection, or the grapheme before the cursor.
+    pub fn backspace(&mut self) {
+        if self.delete_selection() {
+            return;
+        }
+        if let Some(start) = self.prev_boundary() {
+            self.value.replace_range(start..self.cursor, "");
+            self.cursor = start;
+        }
+    }
+
+    /// Removes the selection, or the grapheme under the cursor.
+    pub fn delete(&mut self) {
+        if self.delete_selection() {
+            return;
+        }
+        if let Some(end) = self.next_boundary() {
+            self.value.replace_range(self.cursor..end, "");
+        }
+    }
+
+    /// Removes the selection, or the word before the cursor along with any
+    /// spaces between it and the cursor.
+    pub fn delete_word_back(&mut self) {
+        if self.delete_selection() {
+            return;
+        }
+        let start = self.prev_word_start();
+        self.value.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+
+    pub fn move_left(&mut self, select: bool) {
+        let target = self.prev_boundary().unwrap_or(self.cursor);
+        self.move_to(target, select);
+    }
+
+    pub fn move_right(&mut self, select: bool) {
+        let target = self.next_boundary().unwrap_or(self.cursor);
+        self.move_to(target, select);
+    }
+
+    pub fn move_word_left(&mut self, select: bool) {
+        let target = self.prev_word_start();
+        self.move_to(target, select);
+    }
+
+    pub fn move_word_right(&mut self, select: bool) {
+        let target = self.next_word_end();
+        self.move_to(target, select);
+    }
+
+    pub fn move_home(&mut self, select: bool) {
+        self.move_to(0, select);
+    }
+
+    pub fn move_end(&mut self, select: bool) {
+        self.move_to(self.value.len(), select);
+    }
+
+    /// Draws the field inside `block`, wrapping by display width, and places
+    /// the terminal cursor when `focused`.
+    pub fn render(&self, frame: &mut Frame, area: Rect, block: Block, focused: bool) {
+        let inner = block.inner(area);
+        let width = usize::from(inner.width.max(1));
+        let selection = self.selection().unwrap_or_default();
+        let selected = Style::default().add_modifier(Modifier::REVERSED);
+
+        let mut lines = Vec::new();
+        let mut spans: Vec<Span> = Vec::new();
+        let mut row_width = 0;
+        let mut cursor_at = None;
+        for (offset, grapheme) in self.value.grapheme_indices(true) {
+            let grapheme_width = grapheme.width();
+            if row_width + grapheme_width > width && row_width > 0 {
+                lines.push(Line::from(std::mem::take(&mut spans)));
+                row_width = 0;
+            }
+            if offset == self.cursor {
+                cursor_at = Some((lines.len(), row_width));
+            }
+            let style = if selection.contains(&offset) {
+                selected
+            } else {
+                Style::default()
+            };
+            match spans.last_mut() {
+                Some(span) if span.style == style => span.content.to_mut().push_str(grapheme),
+                _ => spans.push(Span::styled(grapheme.to_string(), style)),
+            }
+            row_width += grapheme_width;
+        }
+        if cursor_at.is_none() && row_width >= width {
+            lines.push(Line::from(std::mem::take(&mut spans)));
+            row_width = 0;
+        }
+        let (cursor_row, cursor_col) = cursor_at.unwrap_or((lines.len(), row_width));
+        lines.push(Line::from(spans));
+
+        let scroll = cursor_row.saturating_sub(usize::from(inner.height.max(1)) - 1);
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(block)
+                .scroll((scroll as u16, 0)),
+            area,
+        );
+        if focused {
+            frame.set_cursor(
+                inner.x + cursor_col as u16,
+                inner.y + (cursor_row - scroll) as u16,
+            );
+        }
+    }
+
+    fn selection(&self) -> Option<Range<usize>> {
+        let anchor = self.anchor?;
+        (anchor != self.cursor).then(|| anchor.min(self.cursor)..anchor.max(self.cursor))
+    }
+
+    fn delete_selection(&mut self) -> bool {
+        let Some(range) = self.selection() else {
+            self.anchor = None;
+            return false;
+        };
+        self.value.replace_range(range.clone(), "");
+        self.cursor = range.start;
+        self.anchor = None;
+        true
+    }
+
+    fn move_to(&mut self, target: usize, select: bool) {
+        if select {
+            self.anchor.get_or_insert(self.cursor);
+        } else {
+            self.anchor = None;
+        }
+        self.cursor = target;
+    }
+
+    fn prev_boundary(&self) -> Option<usize> {
+        self.value[..self.cursor]
+            .grapheme_indices(true)
+            .next_back()
+            .map(|(idx, _)| idx)
+    }
+
+    fn next_boundary(&self) -> Option<usize> {
+        self.value[self.cursor..]
+            .graphemes(true)
+            .next()
+            .map(|grapheme| self.cursor + grapheme.len())
+    }
+
+    fn prev_word_start(&self) -> usize {
+        self.value[..self.cursor]
+            .split_word_bound_indices()
+            .rev()
+            .find(|(_, segment)| !segment.trim().is_empty())
+            .map(|(idx, _)| idx)
+            .unwrap_or(0)
+    }
+
+    fn next_word_end(&self) -> usize {
+        self.value[self.cursor..]
+            .split_word_bound_indices()
+            .find(|(_, segment)| !segment.trim().is_empty())
+            .map(|(idx, segment)| self.cursor + idx + segment.len())
+            .unwrap_or(self.value.len())
+    }
+
+    /// A combining mark typed after the cursor's grapheme joins it; move the
+    /// cursor past the whole cluster so the next edit does not split it.
+    fn snap_cursor(&mut self) {
+        let mut offset = 0;
+        for grapheme in self.value.graphemes(true) {
+            if offset >= self.cursor {
+                break;
+            }
+            offset += grapheme.len();
+        }
+        self.cursor = offset;
+    }
+}
@@ -1,20 +1,33 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
 
 use arboard::Clipboard;
-use chrono::{Duration as ChronoDuration, Utc};
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use chrono::{Datelike, Days, Duration as ChronoDuration, NaiveDate, Utc};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
 use directories::ProjectDirs;
 use dotenvy::dotenv;
-use le_core::{Language, SessionConfig, Word};
+use le_client::CleanupEntry;
+use le_client::connectivity;
+use le_core::glossary::{Glossary, GlossaryEntry};
+use le_core::import::{AdditionGroup, ImportPlan, ImportPlanBuilder, group_additions};
+use le_core::learning::{self, LearningStep};
+use le_core::rules::{self, LanguageRules};
+use le_core::session::{Session, SessionBuilder, SessionEnd, SessionQueue, local_day};
+use le_core::stats::{ChapterAccuracy, ReviewDay, Streak, review_streak};
+use le_core::{
+    Card, CardType, EntryKind, Grade, Language, LanguagePair, SchedulerConfig, SchedulerKind,
+    SessionConfig, Sm2Params, SourceKind, SourceRegion, UiPrefs, Word, WordSource,
+    default_new_card,
+};
 use ratatui::Terminal;
 use ratatui::backend::CrosstermBackend;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
@@ -24,11 +37,30 @@ use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+mod actions;
+mod csv_import;
 mod db;
-use crate::db::{Db, DbResult, get_db_backend};
+mod input;
+mod ocr_preprocess;
+use crate::actions::{Action, ctrl_action};
+use crate::csv_import::{ColumnMapping, CsvRow};
+use crate::db::{
+    Db, DbResult, Deck, ImportProfile, NewWord, find_import_profile, get_db_backend, normalize_tag,
+    source_pattern,
+};
+use crate::input::{InputOutcome, TextInput};
+use crate::ocr_preprocess::PreprocessSteps;
 
 const TICK_MS: u64 = 100;
 const TRANSLATE_DEBOUNCE_MS: u64 = 400;
+const DEDUPE_DEBOUNCE_MS: u64 = 250;
+const TRANSLATE_MAX_ATTEMPTS: u32 = 3;
+const TRANSLATE_BACKOFF_MS: u64 = 500;
+const BREAKER_FAILURE_THRESHOLD: u32 = 3;
+const BREAKER_COOLDOWN_SECS: u64 = 60;
+const IMPORT_CHUNK_SIZE: usize = 25;
+const TRANSLATE_OFFLINE: &str =
+    "Offline — translation skipped; fill it in later with Ctrl+T (fix translations)";
 
 fn main() -> io::Result<()> {
     dotenv().ok();
@@ -50,23 +82,47 @@ fn main() -> io::Result<()> {
 
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    crossterm::execute!(stdout, crossterm::terminal::EnterAlternateScreen)?;
+    crossterm::execute!(
+        stdout,
+        crossterm::terminal::EnterAlternateScreen,
+        crossterm::event::EnableBracketedPaste
+    )?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
     let (translation_tx, translation_rx) = mpsc::channel();
     let mut app = App::new(
         config.session,
+        config.languages,
         translation_api,
         translation_tx,
         translation_rx,
     );
 
+    app.ocr_provider = ocr_provider(&config.ocr);
+    (app.ocr_profile, app.ocr_profile_forced) = ocr_profile_name(&config.ocr);
+    app.ocr_profiles = config.ocr.profiles;
+    app.scheduler = SchedulerConfig {
+        algorithm: config.scheduler.algorithm,
+        decks: config.scheduler.decks,
+        sm2: sm2_params(config.sm2),
+        learning_steps: learning_steps(config.learning_steps),
+    };
+    match db.ui_prefs() {
+        Ok(prefs) => app.ui_prefs = prefs,
+        Err(err) => crate::db::log_error(&format!("Loading ui prefs failed: {err}")),
+    }
+    match db.glossary() {
+        Ok(entries) => app.glossary = Arc::new(Glossary::new(entries)),
+        Err(err) => crate::db::log_error(&format!("Loading glossary failed: {err}")),
+    }
+
     let res = run_app(&mut terminal, db.as_ref(), &mut app);
 
     disable_raw_mode()?;
     crossterm::execute!(
         terminal.backend_mut(),
+        crossterm::event::DisableBracketedPaste,
         crossterm::terminal::LeaveAlternateScreen
     )?;
     terminal.show_cursor()?;
@@ -90,61 +146,38 @@ fn run_app(
 
         let timeout = TICK_MS.saturating_sub(last_tick.elapsed().as_millis() as u64);
         if event::poll(Duration::from_millis(timeout))? {
-            if let Event::Key(key) = event::read()? {
-                if handle_key(db, app, key)? {
+            match event::read()? {
+                Event::Key(key)
+                    if key.kind != KeyEventKind::Release && handle_key(db, app, key)? =>
+                {
                     return Ok(());
                 }
+                Event::Paste(text) => app.paste(&text),
+                _ => {}
             }
         }
 
         if last_tick.elapsed() >= Duration::from_millis(TICK_MS) {
             app.tick();
+            refresh_duplicate_hint(db, app);
+            poll_import(db, app);
             last_tick = Instant::now();
         }
     }
 }
 
 fn handle_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Result<bool> {
-    if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
-        return Ok(true);
-    }
-    if key.code == KeyCode::Char('q') && key.modifiers.contains(KeyModifiers::CONTROL) {
-        return Ok(true);
+    if let Some(action) = ctrl_action(&key) {
+        if action.allowed_in(app.mode) {
+            return dispatch(db, app, action);
+        }
+        return Ok(false);
     }
-
-    if key.modifiers.contains(KeyModifiers::CONTROL) {
-        match key.code {
-            KeyCode::Char('a') => {
-                app.mode = Mode::AddWord;
-                return Ok(false);
-            }
-            KeyCode::Char('v') => {
-                match start_review_list(db, app) {
-                    Ok(()) => app.mode = Mode::ReviewList,
-                    Err(err) => {
-                        app.set_message(format!("Failed to load review list: {err}"));
-                        app.mode = Mode::Message;
-                    }
-                }
-                return Ok(false);
-            }
-            KeyCode::Char('i') => {
-                app.start_import();
-                return Ok(false);
-            }
-            KeyCode::Char('o') => {
-                app.start_import();
-                return Ok(false);
-            }
-            KeyCode::Char('k') => {
-                if let Err(err) = begin_cleanup_review(db, app) {
-                    app.set_message(err);
-                    app.mode = Mode::Message;
-                }
-                return Ok(false);
-            }
-            _ => {}
+    if app.mode == Mode::Importing {
+        if key.code == KeyCode::Esc {
+            cancel_import(db, app);
         }
+        return Ok(false);
     }
 
     match app.mode {
@@ -153,6 +186,9 @@ fn handle_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Result<bool> {
         Mode::ReviewList => handle_review_list_key(db, app, key),
         Mode::Import => handle_import_key(db, app, key),
         Mode::ImportPreview => handle_import_preview_key(db, app, key),
+        Mode::ImportCsv => handle_import_csv_key(db, app, key),
+        Mode::ImportCsvPreview => handle_import_csv_preview_key(db, app, key),
+        Mode::ImportCsvColumns => handle_import_csv_columns_key(db, app, key),
         Mode::ChapterSelect => handle_chapter_select_key(db, app, key),
         Mode::Confirm => handle_confirm_key(db, app, key),
         Mode::Message => {
@@ -160,12 +196,58 @@ fn handle_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Result<bool> {
             Ok(false)
         }
         Mode::CleanupReview => handle_cleanup_key(db, app, key),
+        Mode::TranslationFix => handle_translation_fix_key(db, app, key),
+        Mode::TagPrompt => handle_tag_prompt_key(db, app, key),
+        Mode::ReviewSearch => handle_review_search_key(db, app, key),
+        Mode::ReviewSession => handle_review_session_key(db, app, key),
+        Mode::DeckSelect => handle_deck_select_key(db, app, key),
+        Mode::Importing => Ok(false),
+        Mode::Stats => handle_stats_key(db, app, key),
+        Mode::RecentAdditions => handle_recent_additions_key(db, app, key),
+        Mode::Glossary => handle_glossary_key(db, app, key),
     }
 }
 
+/// Runs an app-wide action. Returns `true` when the app should exit.
+fn dispatch(db: &dyn Db, app: &mut App, action: Action) -> io::Result<bool> {
+    match action {
+        Action::Quit => return Ok(true),
+        Action::AddWord => app.mode = Mode::AddWord,
+        Action::ReviewList => match start_review_list(db, app) {
+            Ok(()) => app.mode = Mode::ReviewList,
+            Err(err) => {
+                app.set_message(format!("Failed to load review list: {err}"));
+                app.mode = Mode::Message;
+            }
+        },
+        Action::Import => app.start_import(),
+        Action::CleanupReview => {
+            if let Err(err) = begin_cleanup_review(db, app) {
+                app.set_message(err);
+                app.mode = Mode::Message;
+            }
+        }
+        Action::FixTranslations => {
+            if let Err(err) = begin_translation_fix(db, app) {
+                app.set_message(err);
+                app.mode = Mode::Message;
+            }
+        }
+        Action::AddReverseCards => {
+            match db.add_reverse_cards() {
+                Ok(0) => app.set_message("Every word already has a reverse card".to_string()),
+                Ok(added) => app.set_message(format!("Added {added} reverse cards, due now")),
+                Err(err) => app.set_message(format!("Failed to add reverse cards: {err}")),
+            }
+            app.mode = Mode::Message;
+        }
+    }
+    Ok(false)
+}
+
 fn handle_menu_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Result<bool> {
     match key.code {
-        KeyCode::Char('q') => Ok(true),
+        KeyCode::Char('q') => dispatch(db, app, Action::Quit),
         KeyCode::Char('a') => {
             app.start_add(None);
             Ok(false)
@@ -177,32 +259,294 @@ fn handle_menu_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Result<bool
                 .and_then(|cb| cb.get_text().ok())
                 .map(|t| t.trim().to_string())
                 .filter(|t| !t.is_empty());
-            if text.is_none() {
-                app.set_message("Clipboard is empty or unavailable".to_string());
-                app.mode = Mode::Message;
-            } else {
+            if text.is_some() {
                 app.start_add(text);
+                return Ok(false);
             }
-            Ok(false)
-        }
-        KeyCode::Char('v') => {
-            match start_review_list(db, app) {
-                Ok(()) => app.mode = Mode::ReviewList,
-                Err(err) => {
-                    app.set_message(format!("Failed to load review list: {err}"));
+            // A screenshot of a vocab list goes through OCR like a photo.
+            match clipboard.as_mut().and_then(|cb| cb.get_image().ok()) {
+                Some(image) => match save_clipboard_image(image) {
+                    Ok(name) => app.start_clipboard_import(name),
+                    Err(err) => {
+                        app.set_message(err);
+                        app.mode = Mode::Message;
+                    }
+                },
+                None => {
+                    app.set_message("Clipboard is empty or unavailable".to_string());
                     app.mode = Mode::Message;
                 }
             }
             Ok(false)
         }
-        KeyCode::Char('i') => {
-            app.start_import();
+        KeyCode::Char('v') => dispatch(db, app, Action::ReviewList),
+        KeyCode::Char('i') => dispatch(db, app, Action::Import),
+        KeyCode::Char('f') => {
+            app.start_csv_import();
+            Ok(false)
+        }
+        KeyCode::Char('s') => {
+            open_stats(db, app);
+            Ok(false)
+        }
+        KeyCode::Char('w') => {
+            open_recent_additions(db, app, RECENT_ADDITION_DAYS);
+            Ok(false)
+        }
+        KeyCode::Char('g') => {
+            app.glossary_editor = Some(GlossaryEditor::default());
+            app.mode = Mode::Glossary;
             Ok(false)
         }
         _ => Ok(false),
     }
 }
 
+/// `a` adds an entry for a term in the learned language, `e` edits the
+/// selected one, and `x` deletes it. Entries are typed as
+/// `term = translation`.
+fn handle_glossary_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Result<bool> {
+    let Some(editor) = app.glossary_editor.as_mut() else {
+        app.mode = Mode::Menu;
+        return Ok(false);
+    };
+    if let Some(language) = &editor.editing {
+        match key.code {
+            KeyCode::Esc => editor.editing = None,
+            KeyCode::Enter => {
+                let Some((term, translation)) = editor.input.as_str().split_once('=') else {
+                    app.set_message("Type the entry as: term = translation".to_string());
+                    app.mode = Mode::Message;
+                    return Ok(false);
+                };
+                let entry = GlossaryEntry {
+                    term: term.trim().to_string(),
+                    language: language.clone(),
+                    translation: translation.trim().to_string(),
+                    updated_at: Utc::now(),
+                };
+                let result = db.save_glossary_entry(&entry);
+                editor.editing = None;
+                reload_glossary(db, app, result, Some(&entry));
+            }
+            _ => {
+                editor.input.handle_key(key);
+            }
+        }
+        return Ok(false);
+    }
+    let selected = app.glossary.entries().get(editor.selection).cloned();
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.glossary_editor = None;
+            app.mode = Mode::Menu;
+        }
+        KeyCode::Up | KeyCode::Char('k') => editor.selection = editor.selection.saturating_sub(1),
+        KeyCode::Down | KeyCode::Char('j') => {
+            editor.selection =
+                (editor.selection + 1).min(app.glossary.entries().len().saturating_sub(1));
+        }
+        KeyCode::Char('a') | KeyCode::Char('n') => {
+            editor.input.clear();
+            editor.editing = Some(app.languages.source.clone());
+        }
+        KeyCode::Char('e') => {
+            if let Some(entry) = selected {
+                editor
+                    .input
+                    .set(format!("{} = {}", entry.term, entry.translation));
+                editor.editing = Some(entry.language);
+            }
+        }
+        KeyCode::Char('x') => {
+            if let Some(entry) = selected {
+                let result = db
+                    .delete_glossary_entry(&entry.language, &entry.term)
+                    .map(drop);
+                reload_glossary(db, app, result, None);
+            }
+        }
+        _ => {}
+    }
+    Ok(false)
+}
+
+/// Re-reads the glossary after `result`, an edit, selecting `saved` when
+/// given; a failed edit or read is shown as a message.
+fn reload_glossary(
+    db: &dyn Db,
+    app: &mut App,
+    result: DbResult<()>,
+    saved: Option<&GlossaryEntry>,
+) {
+    match result.and_then(|()| db.glossary()) {
+        Ok(entries) => {
+            let glossary = Glossary::new(entries);
+            if let Some(editor) = app.glossary_editor.as_mut() {
+                editor.selection = saved
+                    .and_then(|saved| glossary.lookup(&saved.term, &saved.language))
+                    .and_then(|found| {
+                        glossary
+                            .entries()
+                            .iter()
+                            .position(|entry| std::ptr::eq(entry, found))
+                    })
+                    .unwrap_or(editor.selection)
+                    .min(glossary.entries().len().saturating_sub(1));
+            }
+            app.glossary = Arc::new(glossary);
+        }
+        Err(err) => {
+            app.set_message(format!("Glossary update failed: {err}"));
+            app.mode = Mode::Message;
+        }
+    }
+}
+
+/// Lists the words added in the last `days` days, grouped by source and
+/// chapter, or shows a message when they cannot be read.
+fn open_recent_additions(db: &dyn Db, app: &mut App, days: u32) {
+    let since = Utc::now() - ChronoDuration::days(i64::from(days));
+    match db.words_added_since(since) {
+        Ok(words) => {
+            let groups = group_additions(words);
+            let selection = app
+                .recent_additions
+                .as_ref()
+                .map_or(0, |recent| recent.selection)
+                .min(groups.len().saturating_sub(1));
+            app.recent_additions = Some(RecentAdditions {
+                days,
+                groups,
+                selection,
+            });
+            app.mode = Mode::RecentAdditions;
+        }
+        Err(err) => {
+            app.set_message(format!("Failed to load recent additions: {err}"));
+            app.mode = Mode::Message;
+        }
+    }
+}
+
+/// `+` and `-` widen or narrow the window a day at a time; `p` previews the
+/// selected group and `P` every listed word.
+fn handle_recent_additions_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Result<bool> {
+    let Some(recent) = app.recent_additions.as_mut() else {
+        app.mode = Mode::Menu;
+        return Ok(false);
+    };
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.recent_additions = None;
+            app.mode = Mode::Menu;
+        }
+        KeyCode::Up | KeyCode::Char('k') => recent.selection = recent.selection.saturating_sub(1),
+        KeyCode::Down | KeyCode::Char('j') => {
+            recent.selection = (recent.selection + 1).min(recent.groups.len().saturating_sub(1));
+        }
+        KeyCode::Char('+') | KeyCode::Char('=') => {
+            let days = recent.days + 1;
+            open_recent_additions(db, app, days);
+        }
+        KeyCode::Char('-') => {
+            let days = recent.days.saturating_sub(1).max(1);
+            open_recent_additions(db, app, days);
+        }
+        KeyCode::Char('p') => {
+            if let Some(group) = recent.groups.get(recent.selection) {
+                let title = group.label();
+                let words = group.words.clone();
+                start_preview_session(app, title, words);
+            }
+        }
+        KeyCode::Char('P') => {
+            let title = format!("added in the last {} days", recent.days);
+            let words = recent
+                .groups
+                .iter()
+                .flat_map(|group| group.words.iter().cloned())
+                .collect();
+            start_preview_session(app, title, words);
+        }
+        _ => {}
+    }
+    Ok(false)
+}
+
+/// A session over `words` in the order given, one basic card each, with no
+/// limits. Grades only steer the session; nothing is scheduled or saved.
+fn start_preview_session(app: &mut App, title: String, words: Vec<Word>) {
+    let now = Utc::now();
+    let queue = SessionQueue {
+        cards: words
+            .iter()
+            .map(|word| default_new_card(word.id, now))
+            .collect(),
+        stop_after_correct: None,
+        max_minutes: None,
+    };
+    let mut session = queue.start(now);
+    if session.next(now).is_none() {
+        return;
+    }
+    app.review_session = Some(ReviewSession {
+        title,
+        session,
+        words: words.into_iter().map(|word| (word.id, word)).collect(),
+        revealed: false,
+        preview: true,
+    });
+    app.mode = Mode::ReviewSession;
+}
+
+/// Loads the review history and shows the statistics screen, or a message
+/// when it cannot be read.
+fn open_stats(db: &dyn Db, app: &mut App) {
+    let loaded = db
+        .reviews_per_day()
+        .and_then(|days| Ok((days, db.chapter_accuracy()?)));
+    match loaded {
+        Ok((days, chapters)) => {
+            let today = Utc::now().date_naive();
+            app.stats = Some(StatsView {
+                streak: review_streak(&days, today),
+                days,
+                chapters,
+                today,
+                scroll: 0,
+            });
+            app.mode = Mode::Stats;
+        }
+        Err(err) => {
+            app.set_message(format!("Failed to load statistics: {err}"));
+            app.mode = Mode::Message;
+        }
+    }
+}
+
+fn handle_stats_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Result<bool> {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.stats = None;
+            app.mode = Mode::Menu;
+        }
+        KeyCode::Char('r') => open_stats(db, app),
+        KeyCode::Up | KeyCode::Char('k') => {
+            if let Some(stats) = &mut app.stats {
+                stats.scroll = stats.scroll.saturating_sub(1);
+            }
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            if let Some(stats) = &mut app.stats {
+                stats.scroll = (stats.scroll + 1).min(stats.chapters.len().saturating_sub(1));
+            }
+        }
+        _ => {}
+    }
+    Ok(false)
+}
+
 fn handle_cleanup_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Result<bool> {
     match key.code {
         KeyCode::Char('q') => {
@@ -234,12 +578,47 @@ fn handle_cleanup_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Result<b
     }
 }
 
+fn handle_translation_fix_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Result<bool> {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Char('n') | KeyCode::Esc => {
+            app.translation_fixes.clear();
+            app.set_message("Missing translations left unchanged".to_string());
+            app.mode = Mode::Message;
+            Ok(false)
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.translation_fix_move(-1);
+            Ok(false)
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.translation_fix_move(1);
+            Ok(false)
+        }
+        KeyCode::Char('d') => {
+            app.drop_translation_fix();
+            Ok(false)
+        }
+        KeyCode::Char('y') => {
+            let fixes = std::mem::take(&mut app.translation_fixes);
+            for fix in &fixes {
+                db.update_translation(fix.word_id, &fix.translation, fix.notes.as_deref())
+                    .map_err(|err| io::Error::other(err.to_string()))?;
+            }
+            app.word_count = None;
+            app.set_message(format!("Filled in {} missing translations", fixes.len()));
+            app.mode = Mode::Message;
+            Ok(false)
+        }
+        _ => Ok(false),
+    }
+}
+
 fn mark_cleanup_reviewed(db: &dyn Db, word_id: Uuid) -> io::Result<()> {
     db.record_cleanup(word_id, Utc::now())
         .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()))
 }
 
-fn handle_review_list_key(_db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Result<bool> {
+fn handle_review_list_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Result<bool> {
     match key.code {
         KeyCode::Char('q') | KeyCode::Esc => {
             app.mode = Mode::AddWord;
@@ -257,6 +636,10 @@ fn handle_review_list_key(_db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Res
             app.toggle_review_group();
             Ok(false)
         }
+        KeyCode::Char('r') => {
+            start_review_session(db, app);
+            Ok(false)
+        }
         KeyCode::Char('d') => {
             if let Some(word) = app.current_review_word() {
                 let message = format!(
@@ -267,6 +650,48 @@ fn handle_review_list_key(_db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Res
             }
             Ok(false)
         }
+        KeyCode::Char('o') => {
+            if let Some(word) = app.current_review_word()
+                && let Err(err) = open_source_image(word)
+            {
+                app.set_message(format!("Cannot open source image: {err}"));
+                app.mode = Mode::Message;
+            }
+            Ok(false)
+        }
+        KeyCode::Char('t') => {
+            if let Some(word) = app.current_review_word() {
+                let prompt = TagPrompt::Word(word.id, word.text.clone());
+                app.start_tag_prompt(prompt);
+            }
+            Ok(false)
+        }
+        KeyCode::Char('f') => {
+            app.start_tag_prompt(TagPrompt::Filter);
+            Ok(false)
+        }
+        KeyCode::Char('b') => {
+            open_deck_picker(db, app, DeckPurpose::Filter);
+            Ok(false)
+        }
+        KeyCode::Char('m') => {
+            let purpose = match app.current_review_word() {
+                Some(word) => Some(DeckPurpose::Move(format!("'{}'", word.text), vec![word.id])),
+                None => app.current_review_group().map(|(group, word_ids)| {
+                    DeckPurpose::Move(format!("the words of {group}"), word_ids)
+                }),
+            };
+            if let Some(purpose) = purpose {
+                open_deck_picker(db, app, purpose);
+            }
+            Ok(false)
+        }
+        KeyCode::Char('/') => {
+            app.search_input
+                .set(app.review_search.clone().unwrap_or_default());
+            app.mode = Mode::ReviewSearch;
+            Ok(false)
+        }
         KeyCode::Char('D') => {
             if !app.review_list.is_empty() {
                 let message =
@@ -280,7 +705,329 @@ fn handle_review_list_key(_db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Res
     }
 }
 
+/// Searches as the query is typed. Enter keeps the results listed; Esc
+/// drops the search and lists every word again.
+fn handle_review_search_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Result<bool> {
+    match key.code {
+        KeyCode::Enter => {
+            app.mode = Mode::ReviewList;
+            return Ok(false);
+        }
+        KeyCode::Esc => {
+            app.search_input.clear();
+            app.mode = Mode::ReviewList;
+        }
+        KeyCode::Up => {
+            app.review_list_move(-1);
+            return Ok(false);
+        }
+        KeyCode::Down => {
+            app.review_list_move(1);
+            return Ok(false);
+        }
+        _ => {
+            app.search_input.handle_key(key);
+        }
+    }
+    let query = app.search_input.as_str().trim();
+    app.review_search = (!query.is_empty()).then(|| query.to_string());
+    app.review_list_selection = 0;
+    if let Err(err) = reload_review_list(db, app) {
+        app.set_message(format!("Search failed: {err}"));
+        app.mode = Mode::Message;
+    }
+    Ok(false)
+}
+
+/// Builds a session from the due cards of the words under the selected
+/// group header, as the list currently shows them (deck and tag filters and
+/// search included), with the configured session limits. `max_new_cards`
+/// is a daily limit: new cards already answered today count against it.
+fn start_review_session(db: &dyn Db, app: &mut App) {
+    let Some((title, word_ids)) = app.current_review_group() else {
+        return;
+    };
+    let now = Utc::now();
+    let loaded = db.due_cards(&word_ids, now).and_then(|cards| {
+        let new_today = db.new_cards_on(local_day(now))?;
+        Ok((cards, new_today))
+    });
+    let (cards, new_today) = match loaded {
+        Ok(loaded) => loaded,
+        Err(err) => {
+            app.set_message(format!("Failed to load cards: {err}"));
+            app.mode = Mode::Message;
+            return;
+        }
+    };
+    let (new, due): (Vec<_>, Vec<_>) = cards
+        .into_iter()
+        .partition(|card| card.reps == 0 && card.lapses == 0 && card.learning_step.is_none());
+    let config = SessionConfig {
+        max_new_cards: app.session_config.max_new_cards.saturating_sub(new_today),
+        ..app.session_config.clone()
+    };
+    let mut session = SessionBuilder::new(config)
+        .due(due)
+        .new_cards(new)
+        .build()
+        .start(Utc::now());
+    if session.next(Utc::now()).is_none() {
+        app.set_message(format!("Nothing due in {title}"));
+        app.mode = Mode::Message;
+        return;
+    }
+    let words = app
+        .review_list
+        .iter()
+        .filter(|word| word_ids.contains(&word.id))
+        .map(|word| (word.id, word.clone()))
+        .collect();
+    app.review_session = Some(ReviewSession {
+        title,
+        session,
+        words,
+        revealed: false,
+        preview: false,
+    });
+    app.mode = Mode::ReviewSession;
+}
+
+/// Space or Enter shows the answer, then 0-5 grades it with its deck's
+/// scheduler, or moves a new card along its learning steps, bringing it back
+/// once the step is up. A failed card comes back a few cards later until it
+/// is answered correctly. Once the session has ended, any key returns to
+/// the list; Esc leaves early, keeping the grades already saved. Preview
+/// sessions save nothing, skip the learning steps, and return to the recent
+/// additions digest.
+fn handle_review_session_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Result<bool> {
+    let Some(review) = app.review_session.as_mut() else {
+        app.mode = Mode::ReviewList;
+        return Ok(false);
+    };
+    if key.code == KeyCode::Esc || review.session.current().is_none() {
+        app.mode = if review.preview {
+            Mode::RecentAdditions
+        } else {
+            Mode::ReviewList
+        };
+        app.review_session = None;
+        return Ok(false);
+    }
+    match key.code {
+        KeyCode::Char(' ') | KeyCode::Enter => review.revealed = true,
+        KeyCode::Char(digit) if review.revealed => {
+            let Some(grade) = digit
+                .to_digit(10)
+                .and_then(|value| Grade::new(value as u8).ok())
+            else {
+                return Ok(false);
+            };
+            let Some(mut card) = review.session.current().cloned() else {
+                return Ok(false);
+            };
+            let now = Utc::now();
+            let deck = review
+                .words
+                .get(&card.word_id)
+                .and_then(|word| word.deck_id)
+                .map(|deck_id| deck_id.to_string());
+            if review.preview {
+                app.scheduler
+                    .scheduler(deck.as_deref())
+                    .schedule(&mut card, grade, now);
+            } else {
+                app.scheduler
+                    .schedule_card(&mut card, grade, now, deck.as_deref());
+            }
+            let learning_due_at = card.learning_due_at;
+            if !review.preview
+                && let Err(err) = db.record_review(&card, grade, now)
+            {
+                app.review_session = None;
+                app.set_message(format!("Failed to save review: {err}"));
+                app.mode = Mode::Message;
+                return Ok(false);
+            }
+            match learning_due_at {
+                Some(due_at) => review.session.grade_learning(card, grade, due_at, now),
+                None => review.session.grade(card, grade),
+            };
+            review.session.next(Utc::now());
+            review.revealed = false;
+        }
+        _ => {}
+    }
+    Ok(false)
+}
+
+/// `t` tags the selected word (a leading `-` removes the tag instead); `f`
+/// restricts the list to one tag, and an empty filter shows every word again.
+fn handle_tag_prompt_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Result<bool> {
+    match key.code {
+        KeyCode::Esc => {
+            app.tag_prompt = None;
+            app.mode = Mode::ReviewList;
+        }
+        KeyCode::Enter => {
+            let input = app.tag_input.as_str().trim().to_string();
+            let result = match app.tag_prompt.take() {
+                Some(TagPrompt::Word(word_id, _)) => match input.strip_prefix('-') {
+                    Some(tag) => db.remove_tag(word_id, tag),
+                    None => db.add_tag(word_id, &input),
+                },
+                Some(TagPrompt::Filter) => {
+                    app.review_tag_filter = (!input.is_empty()).then(|| input.to_lowercase());
+                    app.review_list_selection = 0;
+                    Ok(())
+                }
+                None => Ok(()),
+            };
+            let result = result.and_then(|()| reload_review_list(db, app));
+            match result {
+                Ok(()) => app.mode = Mode::ReviewList,
+                Err(err) => {
+                    app.set_message(format!("Tag update failed: {err}"));
+                    app.mode = Mode::Message;
+                }
+            }
+        }
+        _ => {
+            app.tag_input.handle_key(key);
+        }
+    }
+    Ok(false)
+}
+
+fn open_deck_picker(db: &dyn Db, app: &mut App, purpose: DeckPurpose) {
+    let decks = match db.list_decks() {
+        Ok(decks) => decks,
+        Err(err) => {
+            app.set_message(format!("Failed to load decks: {err}"));
+            app.mode = Mode::Message;
+            return;
+        }
+    };
+    let selection = match (&purpose, &app.review_deck) {
+        (DeckPurpose::Filter, Some(current)) => decks
+            .iter()
+            .position(|deck| deck.id == current.id)
+            .map_or(0, |index| index + 1),
+        _ => 0,
+    };
+    app.deck_picker = Some(DeckPicker {
+        purpose,
+        decks,
+        selection,
+        naming: None,
+        input: TextInput::default(),
+    });
+    app.mode = Mode::DeckSelect;
+}
+
+/// Enter picks the highlighted deck, either as the review list's filter or
+/// as where `m` moves the selected words; `n`, `e`, and `x` create, rename,
+/// and delete decks. Deleting a deck keeps its words.
+fn handle_deck_select_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Result<bool> {
+    let Some(picker) = app.deck_picker.as_mut() else {
+        app.mode = Mode::ReviewList;
+        return Ok(false);
+    };
+    if let Some(naming) = picker.naming {
+        match key.code {
+            KeyCode::Esc => picker.naming = None,
+            KeyCode::Enter => {
+                let name = picker.input.as_str().trim().to_string();
+                let result = match naming {
+                    DeckNaming::New => db.create_deck(&name).map(drop),
+                    DeckNaming::Rename(deck_id) => db.rename_deck(deck_id, &name),
+                };
+                match result.and_then(|()| db.list_decks()) {
+                    Ok(decks) => {
+                        picker.selection = decks
+                            .iter()
+                            .position(|deck| deck.name == name)
+                            .map_or(0, |index| index + 1);
+                        picker.decks = decks;
+                        picker.naming = None;
+                    }
+                    Err(err) => {
+                        app.deck_picker = None;
+                        app.set_message(format!("Deck update failed: {err}"));
+                        app.mode = Mode::Message;
+                    }
+                }
+            }
+            _ => {
+                picker.input.handle_key(key);
+            }
+        }
+        return Ok(false);
+    }
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.deck_picker = None;
+            app.mode = Mode::ReviewList;
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            picker.selection = picker.selection.saturating_sub(1);
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            picker.selection = (picker.selection + 1).min(picker.decks.len());
+        }
+        KeyCode::Char('n') => {
+            picker.input.clear();
+            picker.naming = Some(DeckNaming::New);
+        }
+        KeyCode::Char('e') => {
+            if let Some(deck) = picker.selected() {
+                let (deck_id, name) = (deck.id, deck.name.clone());
+                picker.input.set(name);
+                picker.naming = Some(DeckNaming::Rename(deck_id));
+            }
+        }
+        KeyCode::Char('x') => {
+            if let Some(deck) = picker.selected() {
+                let message = format!(
+                    "Delete the deck '{}'? Its {} words are kept, outside every deck. (y/n)",
+                    deck.name, deck.words
+                );
+                let action = ConfirmAction::DeleteDeck(deck.id);
+                app.set_confirm(action, message);
+            }
+        }
+        KeyCode::Enter => {
+            let Some(picker) = app.deck_picker.take() else {
+                return Ok(false);
+            };
+            let deck = picker.selected().cloned();
+            let result = match picker.purpose {
+                DeckPurpose::Filter => {
+                    app.review_deck = deck;
+                    app.review_list_selection = 0;
+                    Ok(())
+                }
+                DeckPurpose::Move(_, word_ids) => {
+                    db.move_to_deck(&word_ids, deck.map(|deck| deck.id))
+                }
+            };
+            match result.and_then(|()| reload_review_list(db, app)) {
+                Ok(()) => app.mode = Mode::ReviewList,
+                Err(err) => {
+                    app.set_message(format!("Deck update failed: {err}"));
+                    app.mode = Mode::Message;
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(false)
+}
+
 fn handle_confirm_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Result<bool> {
+    if let Some(ConfirmAction::AttachForm(word_id)) = app.confirm_action {
+        return handle_attach_form_key(db, app, word_id, key);
+    }
     match key.code {
         KeyCode::Char('y') | KeyCode::Char('Y') => {
             if let Some(action) = app.confirm_action.take() {
@@ -288,14 +1035,40 @@ fn handle_confirm_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Result<b
                 let result = match action {
                     ConfirmAction::DeleteWord(word_id) => db.delete_word(word_id),
                     ConfirmAction::DeleteAll => db.delete_all_words(),
+                    ConfirmAction::AttachForm(_) => unreachable!("handled above"),
+                    ConfirmAction::DeleteDeck(deck_id) => {
+                        if app
+                            .review_deck
+                            .as_ref()
+                            .is_some_and(|deck| deck.id == deck_id)
+                        {
+                            app.review_deck = None;
+                        }
+                        db.delete_deck(deck_id)
+                    }
                 };
                 if let Err(err) = result {
+                    app.deck_picker = None;
                     app.set_message(format!("Delete failed: {err}"));
                     app.mode = Mode::Message;
                 } else {
                     if let Err(err) = reload_review_list(db, app) {
+                        app.deck_picker = None;
                         app.set_message(format!("Failed to load review list: {err}"));
                         app.mode = Mode::Message;
+                    } else if let Some(picker) = app.deck_picker.as_mut() {
+                        match db.list_decks() {
+                            Ok(decks) => {
+                                picker.selection = picker.selection.min(decks.len());
+                                picker.decks = decks;
+                                app.mode = Mode::DeckSelect;
+                            }
+                            Err(err) => {
+                                app.deck_picker = None;
+                                app.set_message(format!("Failed to load decks: {err}"));
+                                app.mode = Mode::Message;
+                            }
+                        }
                     } else {
                         app.mode = Mode::ReviewList;
                     }
@@ -308,7 +1081,11 @@ fn handle_confirm_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Result<b
         KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
             app.confirm_action = None;
             app.confirm_message = None;
-            app.mode = Mode::ReviewList;
+            app.mode = if app.deck_picker.is_some() {
+                Mode::DeckSelect
+            } else {
+                Mode::ReviewList
+            };
             Ok(false)
         }
         _ => Ok(false),
@@ -338,7 +1115,7 @@ fn handle_add_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Result<bool>
                 return Ok(false);
             }
 
-            match db.word_exists(text, app.active_language()) {
+            match db.word_exists(text, &app.active_language()) {
                 Ok(true) => {
                     app.set_message("Word already exists".to_string());
                     return Ok(false);
@@ -350,30 +1127,81 @@ fn handle_add_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Result<bool>
                 }
             }
 
-            if let Err(err) = db.save_word(
-                text,
-                translation,
-                app.active_language(),
-                Some("Manual"),
-                Some("Vocabulaire"),
-            ) {
-                app.set_message(format!("Failed to save word: {err}"));
-            } else {
-                app.set_message("Word saved".to_string());
-                app.clear_add_inputs();
+            match find_inflected_form(db, text, &app.active_language()) {
+                Ok(Some(found)) => {
+                    let message = format!(
+                        "'{}' looks like a form of '{}'. Attach it there? (y attach / n save as new word / Esc back)",
+                        found.form, found.lemma
+                    );
+                    app.set_confirm(ConfirmAction::AttachForm(found.word_id), message);
+                }
+                Ok(None) => save_added_word(db, app),
+                Err(err) => app.set_message(format!("Failed to check forms: {err}")),
             }
             Ok(false)
         }
-        KeyCode::Backspace => {
-            app.pop_add_char();
+        _ => {
+            if app.active_input_mut().handle_key(key) == InputOutcome::Edited {
+                app.mark_edit(app.add_field);
+            }
             Ok(false)
         }
-        KeyCode::Char(ch) => {
-            app.push_add_char(ch);
-            Ok(false)
+    }
+}
+
+/// Saves the word typed on the add screen as a new entry.
+fn save_added_word(db: &dyn Db, app: &mut App) {
+    if let Err(err) = db.save_word(
+        app.active_input().trim(),
+        app.inactive_input().trim(),
+        &app.active_language(),
+        Some("Manual"),
+        Some("Vocabulaire"),
+        Some(&WordSource {
+            kind: app.add_source,
+            name: None,
+            page: None,
+            captured_at: Utc::now(),
+            region: None,
+        }),
+    ) {
+        app.set_message(format!("Failed to save word: {err}"));
+    } else {
+        app.set_message("Word saved".to_string());
+        app.clear_add_inputs();
+        app.add_source = SourceKind::Manual;
+        app.word_count = None;
+    }
+}
+
+/// Answers the prompt raised when a word being added inflects a saved one:
+/// attach it as a form of that word, save it as a new word, or go back.
+fn handle_attach_form_key(
+    db: &dyn Db,
+    app: &mut App,
+    word_id: Uuid,
+    key: KeyEvent,
+) -> io::Result<bool> {
+    match key.code {
+        KeyCode::Char('y') | KeyCode::Char('Y') => {
+            let form = app.active_input().trim().to_string();
+            match db.add_word_forms(word_id, std::slice::from_ref(&form)) {
+                Ok(()) => {
+                    app.set_message(format!("Attached '{form}' to the saved word"));
+                    app.clear_add_inputs();
+                    app.add_source = SourceKind::Manual;
+                }
+                Err(err) => app.set_message(format!("Failed to attach form: {err}")),
+            }
         }
-        _ => Ok(false),
+        KeyCode::Char('n') | KeyCode::Char('N') => save_added_word(db, app),
+        KeyCode::Esc => {}
+        _ => return Ok(false),
     }
+    app.confirm_action = None;
+    app.confirm_message = None;
+    app.mode = Mode::AddWord;
+    Ok(false)
 }
 
 fn handle_import_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Result<bool> {
@@ -387,74 +1215,118 @@ fn handle_import_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Result<bo
             Ok(false)
         }
         KeyCode::Enter => {
-            let chapter = app.import_chapter.trim();
+            app.import_profile = app
+                .selected_import_images()
+                .first()
+                .and_then(|name| remembered_import_profile(db, name));
+            let chapter = app.import_chapter.as_str().trim();
             if app.import_images.is_empty() {
                 app.set_message("No images found in img/".to_string());
                 return Ok(false);
             }
-            let image_name = match app.import_images.get(app.import_selection) {
-                Some(name) => name.clone(),
-                None => {
-                    app.set_message("No image selected".to_string());
+            let image_names = app.selected_import_images();
+            if image_names.is_empty() {
+                app.set_message("No image selected".to_string());
+                return Ok(false);
+            }
+            if chapter.is_empty() {
+                let chapters = db.list_chapters().map_err(io::Error::other)?;
+                let pages = match ocr_pages(
+                    app.import_ocr(),
+                    &image_names,
+                    &app.languages.source,
+                    |_| {},
+                ) {
+                    Ok(pages) => pages,
+                    Err(err) => {
+                        app.set_message(format!("Preview failed: {err}"));
+                        return Ok(false);
+                    }
+                };
+                if let Some(detected) = detect_chapter(&pages, &chapters) {
+                    let initial_group = db
+                        .last_group_for_chapter(&detected)
+                        .map_err(io::Error::other)?;
+                    app.import_chapter.set(detected);
+                    app.import_chapter_detected = true;
+                    match group_pages(&pages, &app.languages.source, initial_group) {
+                        Ok(items) => show_import_preview(db, app, items, image_names),
+                        Err(err) => app.set_message(format!("Preview failed: {err}")),
+                    }
+                    return Ok(false);
+                }
+                if let Some(remembered) = app
+                    .import_profile
+                    .as_ref()
+                    .and_then(|profile| profile.chapter.clone())
+                {
+                    let initial_group = db
+                        .last_group_for_chapter(&remembered)
+                        .map_err(io::Error::other)?;
+                    app.import_chapter.set(remembered);
+                    match group_pages(&pages, &app.languages.source, initial_group) {
+                        Ok(items) => show_import_preview(db, app, items, image_names),
+                        Err(err) => app.set_message(format!("Preview failed: {err}")),
+                    }
                     return Ok(false);
                 }
-            };
-            if chapter.is_empty() {
-                let chapters = db
-                    .list_chapters()
-                    .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
                 if chapters.is_empty() {
                     app.set_message(
-                        "No existing chapters found. Enter a chapter first.".to_string(),
+                        "No chapter line found in the photo and no existing chapters. Enter a chapter first."
+                            .to_string(),
                     );
                     return Ok(false);
                 }
                 app.chapter_select_list = chapters;
                 app.chapter_select_index = 0;
-                app.import_pending_image = Some(image_name);
+                app.import_pending_images = image_names;
                 app.mode = Mode::ChapterSelect;
                 return Ok(false);
             }
-            let image_path = PathBuf::from("img").join(&image_name);
             let initial_group = db
                 .last_group_for_chapter(chapter)
-                .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
-            match run_ocr(OcrProviderKind::Vision, &image_path) {
-                Ok(lines) => match parse_grouped_items(&lines, initial_group) {
-                    Ok(items) => {
-                        app.import_preview_items = items;
-                        app.import_preview_scroll = 0;
-                        app.import_preview_path = Some(image_name);
-                        app.mode = Mode::ImportPreview;
-                    }
-                    Err(err) => app.set_message(format!("Preview failed: {err}")),
-                },
+                .map_err(io::Error::other)?;
+            match ocr_import_items(
+                app.import_ocr(),
+                &image_names,
+                &app.languages.source,
+                initial_group,
+                |_| {},
+            ) {
+                Ok(items) => show_import_preview(db, app, items, image_names),
                 Err(err) => app.set_message(format!("Preview failed: {err}")),
             }
             Ok(false)
         }
-        KeyCode::Backspace => {
-            app.pop_import_char();
+        KeyCode::Char(' ') if app.import_field == ImportField::List => {
+            app.toggle_import_mark();
             Ok(false)
         }
-        KeyCode::Up | KeyCode::Char('k') => {
-            if app.import_field == ImportField::List && !app.import_images.is_empty() {
+        KeyCode::Char('a') if app.import_field == ImportField::List => {
+            app.toggle_import_mark_all();
+            Ok(false)
+        }
+        KeyCode::Up | KeyCode::Char('k') if app.import_field == ImportField::List => {
+            if !app.import_images.is_empty() {
                 app.import_selection = app.import_selection.saturating_sub(1);
             }
             Ok(false)
         }
-        KeyCode::Down | KeyCode::Char('j') => {
-            if app.import_field == ImportField::List && !app.import_images.is_empty() {
+        KeyCode::Down | KeyCode::Char('j') if app.import_field == ImportField::List => {
+            if !app.import_images.is_empty() {
                 let max = app.import_images.len().saturating_sub(1);
                 app.import_selection = (app.import_selection + 1).min(max);
             }
             Ok(false)
         }
-        KeyCode::Char(ch) => {
-            app.push_import_char(ch);
+        _ => {
+            if app.import_field == ImportField::Chapter
+                && app.import_chapter.handle_key(key) == InputOutcome::Edited
+            {
+                app.import_chapter_detected = false;
+            }
             Ok(false)
         }
-        _ => Ok(false),
     }
 }
 
@@ -465,37 +1337,53 @@ fn handle_import_preview_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::R
             Ok(false)
         }
         KeyCode::Char('y') | KeyCode::Char('Y') => {
-            let chapter = app.import_chapter.trim();
-            let Some(image_name) = app.import_preview_path.clone() else {
+            let chapter = app.import_chapter.as_str().trim();
+            if app.import_preview_images.is_empty() {
                 app.set_message("Missing preview image".to_string());
                 app.mode = Mode::Import;
                 return Ok(false);
-            };
-            let image_path = PathBuf::from("img").join(image_name);
-            let Some(api) = app.translation_api.as_deref() else {
+            }
+            let image_names = app.import_preview_images.clone();
+            let Some(api) = app.translation_api.clone() else {
                 app.set_message("Missing TRANSLATION_API_URL for translation".to_string());
                 return Ok(false);
             };
             let initial_group = db
                 .last_group_for_chapter(chapter)
-                .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
-            match import_from_image(
+                .map_err(io::Error::other)?;
+            let chapter = chapter.to_string();
+            remember_import_profile(
                 db,
+                ImportProfile {
+                    pattern: source_pattern(&image_names[0]),
+                    columns: None,
+                    ocr_profile: app.import_ocr_profile(),
+                    chapter: Some(chapter.clone()).filter(|chapter| !chapter.is_empty()),
+                    updated_at: Utc::now(),
+                },
+            );
+            let forms = if app.import_attach_forms {
+                app.import_form_matches.clone()
+            } else {
+                Vec::new()
+            };
+            app.import_chapter_detected = false;
+            app.import_job = Some(spawn_import(
                 api,
-                &image_path,
+                Arc::clone(&app.glossary),
+                image_names,
                 chapter,
-                OcrProviderKind::Vision,
+                app.languages.clone(),
+                app.import_ocr(),
                 initial_group,
-            ) {
-                Ok(count) => {
-                    app.set_message(format!("Imported {count} words"));
-                    app.mode = Mode::Message;
-                }
-                Err(err) => {
-                    app.set_message(format!("Import failed: {err}"));
-                    app.mode = Mode::Import;
-                }
-            }
+                forms,
+            ));
+            app.mode = Mode::Importing;
+            Ok(false)
+        }
+        KeyCode::Char('a') => {
+            app.import_attach_forms = !app.import_attach_forms;
+            app.import_plan = plan_import(db, app).map_err(io::Error::other)?;
             Ok(false)
         }
         KeyCode::Up | KeyCode::Char('k') => {
@@ -510,6 +1398,107 @@ fn handle_import_preview_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::R
     }
 }
 
+fn handle_import_csv_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Result<bool> {
+    match key.code {
+        KeyCode::Esc => {
+            app.message = None;
+            app.mode = Mode::Menu;
+        }
+        KeyCode::Enter => match load_csv_preview(db, app, None) {
+            Ok(preview) => {
+                app.message = None;
+                app.csv_preview = Some(preview);
+                app.mode = Mode::ImportCsvPreview;
+            }
+            Err(err) => app.set_message(err),
+        },
+        _ => {
+            app.csv_path.handle_key(key);
+        }
+    }
+    Ok(false)
+}
+
+fn handle_import_csv_preview_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Result<bool> {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('N') => {
+            app.csv_preview = None;
+            app.mode = Mode::ImportCsv;
+        }
+        KeyCode::Char('y') | KeyCode::Char('Y') => {
+            let Some(preview) = app.csv_preview.take() else {
+                app.mode = Mode::ImportCsv;
+                return Ok(false);
+            };
+            let words = csv_new_words(&preview, &app.languages.source);
+            match db.save_words_bulk(&words) {
+                Ok(inserted) => {
+                    remember_import_profile(db, csv_import_profile(&preview));
+                    app.set_message(format!(
+                        "Imported {inserted} words from {} ({} skipped)",
+                        preview.path,
+                        preview.rows.len() - inserted
+                    ));
+                }
+                Err(err) => {
+                    crate::db::log_error(&format!(
+                        "CSV import save_words_bulk failed: {err} (path='{}', words={})",
+                        preview.path,
+                        words.len()
+                    ));
+                    app.set_message(format!("Failed to save words: {err}"));
+                }
+            }
+            app.word_count = None;
+            app.mode = Mode::Message;
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            if let Some(preview) = app.csv_preview.as_mut() {
+                preview.scroll = preview.scroll.saturating_sub(1);
+            }
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            if let Some(preview) = app.csv_preview.as_mut() {
+                preview.scroll = preview.scroll.saturating_add(1);
+            }
+        }
+        KeyCode::Char('c') => {
+            if let Some(preview) = &app.csv_preview {
+                app.csv_columns.set(preview.columns.to_string());
+                app.message = None;
+                app.mode = Mode::ImportCsvColumns;
+            }
+        }
+        _ => {}
+    }
+    Ok(false)
+}
+
+fn handle_import_csv_columns_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Result<bool> {
+    match key.code {
+        KeyCode::Esc => {
+            app.message = None;
+            app.mode = Mode::ImportCsvPreview;
+        }
+        KeyCode::Enter => {
+            let preview = ColumnMapping::parse(app.csv_columns.as_str())
+                .and_then(|columns| load_csv_preview(db, app, Some(&columns)));
+            match preview {
+                Ok(preview) => {
+                    app.message = None;
+                    app.csv_preview = Some(preview);
+                    app.mode = Mode::ImportCsvPreview;
+                }
+                Err(err) => app.set_message(err),
+            }
+        }
+        _ => {
+            app.csv_columns.handle_key(key);
+        }
+    }
+    Ok(false)
+}
+
 fn handle_chapter_select_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::Result<bool> {
     match key.code {
         KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('N') => {
@@ -528,11 +1517,12 @@ fn handle_chapter_select_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::R
             Ok(false)
         }
         KeyCode::Enter => {
-            let Some(image_name) = app.import_pending_image.clone() else {
+            if app.import_pending_images.is_empty() {
                 app.set_message("Missing pending image".to_string());
                 app.mode = Mode::Import;
                 return Ok(false);
-            };
+            }
+            let image_names = app.import_pending_images.clone();
             let Some(chapter) = app
                 .chapter_select_list
                 .get(app.chapter_select_index)
@@ -542,22 +1532,21 @@ fn handle_chapter_select_key(db: &dyn Db, app: &mut App, key: KeyEvent) -> io::R
                 app.mode = Mode::Import;
                 return Ok(false);
             };
-            app.import_chapter = chapter.clone();
-            let image_path = PathBuf::from("img").join(&image_name);
+            app.import_chapter.set(chapter.clone());
             let initial_group = db
                 .last_group_for_chapter(&chapter)
-                .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
-            match run_ocr(OcrProviderKind::Vision, &image_path) {
-                Ok(lines) => match parse_grouped_items(&lines, initial_group) {
-                    Ok(items) => {
-                        app.import_preview_items = items;
-                        app.import_preview_scroll = 0;
-                        app.import_preview_path = Some(image_name);
-                        app.import_pending_image = None;
-                        app.mode = Mode::ImportPreview;
-                    }
-                    Err(err) => app.set_message(format!("Preview failed: {err}")),
-                },
+                .map_err(io::Error::other)?;
+            match ocr_import_items(
+                app.import_ocr(),
+                &image_names,
+                &app.languages.source,
+                initial_group,
+                |_| {},
+            ) {
+                Ok(items) => {
+                    app.import_pending_images.clear();
+                    show_import_preview(db, app, items, image_names);
+                }
                 Err(err) => app.set_message(format!("Preview failed: {err}")),
             }
             Ok(false)
@@ -578,12 +1567,30 @@ fn ui(frame: &mut ratatui::Frame, app: &mut App) {
         Mode::ReviewList => render_review_list(frame, app, chunks[0]),
         Mode::Import => render_import(frame, app, chunks[0]),
         Mode::ImportPreview => render_import_preview(frame, app, chunks[0]),
+        Mode::ImportCsv => render_import_csv(frame, app, chunks[0]),
+        Mode::ImportCsvPreview => render_import_csv_preview(frame, app, chunks[0]),
+        Mode::ImportCsvColumns => render_import_csv_columns(frame, app, chunks[0]),
         Mode::ChapterSelect => render_chapter_select(frame, app, chunks[0]),
         Mode::Confirm => frame.render_widget(render_confirm(app), chunks[0]),
         Mode::Message => frame.render_widget(render_message(app), chunks[0]),
         Mode::CleanupReview => render_cleanup_review(frame, app, chunks[0]),
+        Mode::TranslationFix => render_translation_fix(frame, app, chunks[0]),
+        Mode::TagPrompt => render_tag_prompt(frame, app, chunks[0]),
+        Mode::ReviewSearch => render_review_search(frame, app, chunks[0]),
+        Mode::ReviewSession => render_review_session(frame, app, chunks[0]),
+        Mode::DeckSelect => render_deck_select(frame, app, chunks[0]),
+        Mode::Importing => frame.render_widget(render_importing(app), chunks[0]),
+        Mode::Stats => render_stats(frame, app, chunks[0]),
+        Mode::RecentAdditions => render_recent_additions(frame, app, chunks[0]),
+        Mode::Glossary => render_glossary(frame, app, chunks[0]),
     }
     frame.render_widget(render_footer(app), chunks[1]);
+    if app.ui_prefs.bold_text() {
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        for cell in frame.buffer_mut().content.iter_mut() {
+            cell.set_style(bold);
+        }
+    }
 }
 
 fn render_menu(_app: &App) -> Paragraph<'_> {
@@ -592,9 +1599,20 @@ fn render_menu(_app: &App) -> Paragraph<'_> {
     text.lines.push(Line::from(""));
     text.lines.push(Line::from("a - add word"));
     text.lines.push(Line::from("c - add from clipboard"));
-    text.lines.push(Line::from("i - import image"));
+    text.lines.push(Line::from("i - import images"));
+    text.lines.push(Line::from("f - import CSV file"));
     text.lines.push(Line::from("v - review list"));
-    text.lines.push(Line::from("Ctrl+k - AI cleanup review"));
+    text.lines.push(Line::from("s - review statistics"));
+    text.lines.push(Line::from("w - words added this week"));
+    text.lines.push(Line::from("g - translation glossary"));
+    for (key, action) in [
+        ('k', Action::CleanupReview),
+        ('t', Action::FixTranslations),
+        ('r', Action::AddReverseCards),
+    ] {
+        text.lines
+            .push(Line::from(format!("Ctrl+{key} - {}", action.label())));
+    }
     text.lines.push(Line::from("q - quit"));
 
     Paragraph::new(text)
@@ -605,11 +1623,16 @@ fn render_menu(_app: &App) -> Paragraph<'_> {
 fn render_add(frame: &mut ratatui::Frame, app: &App, area: Rect) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(5), Constraint::Min(3)].as_ref())
+        .constraints([Constraint::Length(6), Constraint::Min(3)].as_ref())
         .split(area);
 
     let mut text = Text::default();
-    text.lines.push(Line::from("Add Word"));
+    match app.word_count {
+        Some(count) => text
+            .lines
+            .push(Line::from(format!("Add Word ({count} words saved)"))),
+        None => text.lines.push(Line::from("Add Word")),
+    }
     if let Some(message) = &app.message {
         text.lines.push(Line::from(""));
         text.lines.push(Line::from(Span::styled(
@@ -617,6 +1640,12 @@ fn render_add(frame: &mut ratatui::Frame, app: &App, area: Rect) {
             Style::default().add_modifier(Modifier::BOLD),
         )));
     }
+    if let Some(hint) = &app.duplicate_hint {
+        text.lines.push(Line::from(Span::styled(
+            hint,
+            Style::default().fg(Color::Yellow),
+        )));
+    }
 
     let header = Paragraph::new(text)
         .block(Block::default().borders(Borders::ALL).title("Add"))
@@ -631,36 +1660,30 @@ fn render_add(frame: &mut ratatui::Frame, app: &App, area: Rect) {
     let active_style = Style::default()
         .fg(Color::Blue)
         .add_modifier(Modifier::BOLD);
-    let dutch_active = app.add_field == AddField::Dutch;
-    let english_active = app.add_field == AddField::English;
-
-    let dutch = Paragraph::new(app.dutch_input.as_str())
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("Dutch")
-                .border_style(if dutch_active {
-                    active_style
-                } else {
-                    Style::default()
-                }),
-        )
-        .wrap(Wrap { trim: false });
-    let english = Paragraph::new(app.english_input.as_str())
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("English")
-                .border_style(if english_active {
-                    active_style
-                } else {
-                    Style::default()
-                }),
-        )
-        .wrap(Wrap { trim: false });
+    let source_active = app.add_field == AddField::Source;
+    let target_active = app.add_field == AddField::Target;
+
+    let source = Block::default()
+        .borders(Borders::ALL)
+        .title(app.languages.source.to_column())
+        .border_style(if source_active {
+            active_style
+        } else {
+            Style::default()
+        });
+    let target = Block::default()
+        .borders(Borders::ALL)
+        .title(app.languages.target.to_column())
+        .border_style(if target_active {
+            active_style
+        } else {
+            Style::default()
+        });
 
-    frame.render_widget(dutch, boxes[0]);
-    frame.render_widget(english, boxes[1]);
+    app.source_input
+        .render(frame, boxes[0], source, source_active);
+    app.target_input
+        .render(frame, boxes[1], target, target_active);
 }
 
 fn render_import(frame: &mut ratatui::Frame, app: &App, area: Rect) {
@@ -670,7 +1693,7 @@ fn render_import(frame: &mut ratatui::Frame, app: &App, area: Rect) {
         .split(area);
 
     let mut text = Text::default();
-    text.lines.push(Line::from("Import Image"));
+    text.lines.push(Line::from("Import Images"));
     if let Some(message) = &app.message {
         text.lines.push(Line::from(""));
         text.lines.push(Line::from(Span::styled(
@@ -690,23 +1713,19 @@ fn render_import(frame: &mut ratatui::Frame, app: &App, area: Rect) {
         .split(chunks[1]);
 
     let active_style = Style::default()
-        .fg(Color::Blue)
-        .add_modifier(Modifier::BOLD);
-    let chapter_active = app.import_field == ImportField::Chapter;
-    let list_active = app.import_field == ImportField::List;
-
-    let chapter = Paragraph::new(app.import_chapter.as_str())
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("Chapter")
-                .border_style(if chapter_active {
-                    active_style
-                } else {
-                    Style::default()
-                }),
-        )
-        .wrap(Wrap { trim: false });
+        .fg(Color::Blue)
+        .add_modifier(Modifier::BOLD);
+    let chapter_active = app.import_field == ImportField::Chapter;
+    let list_active = app.import_field == ImportField::List;
+
+    let chapter = Block::default()
+        .borders(Borders::ALL)
+        .title("Chapter")
+        .border_style(if chapter_active {
+            active_style
+        } else {
+            Style::default()
+        });
 
     let mut list_text = Text::default();
     if app.import_images.is_empty() {
@@ -722,12 +1741,17 @@ fn render_import(frame: &mut ratatui::Frame, app: &App, area: Rect) {
         for (idx, name) in app.import_images[start..end].iter().enumerate() {
             let global_idx = start + idx;
             let line = format!(
-                "{} {}",
+                "{} {} {}",
                 if global_idx == app.import_selection {
                     ">"
                 } else {
                     " "
                 },
+                if app.import_marked.contains(name) {
+                    "[x]"
+                } else {
+                    "[ ]"
+                },
                 name
             );
             if global_idx == app.import_selection {
@@ -747,7 +1771,11 @@ fn render_import(frame: &mut ratatui::Frame, app: &App, area: Rect) {
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Images (img/)")
+                .title(if app.import_marked.is_empty() {
+                    "Images (img/)".to_string()
+                } else {
+                    format!("Images (img/) - {} marked", app.import_marked.len())
+                })
                 .border_style(if list_active {
                     active_style
                 } else {
@@ -756,37 +1784,124 @@ fn render_import(frame: &mut ratatui::Frame, app: &App, area: Rect) {
         )
         .wrap(Wrap { trim: false });
 
-    frame.render_widget(chapter, boxes[0]);
+    app.import_chapter
+        .render(frame, boxes[0], chapter, chapter_active);
     frame.render_widget(list, boxes[1]);
 }
 
+fn render_importing(app: &App) -> Paragraph<'_> {
+    let mut text = Text::default();
+    if let Some(job) = &app.import_job {
+        text.lines.push(Line::from(format!(
+            "Importing {}",
+            job.image_names.join(", ")
+        )));
+        text.lines
+            .push(Line::from(format!("Chapter: {}", job.chapter)));
+        text.lines.push(Line::from(""));
+        match job.parsed {
+            Some(parsed) => {
+                text.lines.push(Line::from(format!("Parsed: {parsed}")));
+                text.lines.push(Line::from(format!(
+                    "Translated: {}/{parsed}",
+                    job.translated
+                )));
+            }
+            None => text.lines.push(Line::from(format!(
+                "Running OCR ({}/{} images)...",
+                job.scanned,
+                job.image_names.len()
+            ))),
+        }
+        text.lines.push(Line::from(format!(
+            "Waiting to save: {} (saved together when the import ends)",
+            job.pending.len()
+        )));
+        if job.queued > 0 {
+            text.lines.push(Line::from(format!(
+                "Offline — {} saved without translation",
+                job.queued
+            )));
+        }
+    }
+    Paragraph::new(text)
+        .block(Block::default().borders(Borders::ALL).title("Import"))
+        .wrap(Wrap { trim: false })
+}
+
 fn render_import_preview(frame: &mut ratatui::Frame, app: &mut App, area: Rect) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(6), Constraint::Min(3)].as_ref())
+        .constraints([Constraint::Length(8), Constraint::Min(3)].as_ref())
         .split(area);
 
     let mut header = Text::default();
     header.lines.push(Line::from("Import Preview"));
-    if let Some(path) = &app.import_preview_path {
-        header.lines.push(Line::from(format!("Image: {}", path)));
+    match app.import_preview_images.as_slice() {
+        [] => {}
+        [image] => header.lines.push(Line::from(format!("Image: {image}"))),
+        images => header.lines.push(Line::from(format!(
+            "Images ({}): {}",
+            images.len(),
+            images.join(", ")
+        ))),
     }
-    if !app.import_chapter.trim().is_empty() {
-        header
-            .lines
-            .push(Line::from(format!("Chapter: {}", app.import_chapter)));
+    if !app.import_chapter.as_str().trim().is_empty() {
+        let remembered = app.import_profile.as_ref().filter(|profile| {
+            profile.chapter.as_deref() == Some(app.import_chapter.as_str().trim())
+        });
+        let detected = if app.import_chapter_detected {
+            " (read from the photo; y to use it, n to change it)".to_string()
+        } else if let Some(profile) = remembered {
+            format!(" (remembered for {}; n to change it)", profile.pattern)
+        } else {
+            String::new()
+        };
+        header.lines.push(Line::from(format!(
+            "Chapter: {}{detected}",
+            app.import_chapter.as_str()
+        )));
+    }
+    let mut items_line = format!("Items: {}", app.import_preview_items.len());
+    if !app.import_form_matches.is_empty() {
+        let action = if app.import_attach_forms {
+            "attach to saved words"
+        } else {
+            "import as new words"
+        };
+        items_line.push_str(&format!(
+            " | inflected forms: {} ({action})",
+            app.import_form_matches.len()
+        ));
     }
+    header.lines.push(Line::from(items_line));
+    let plan = &app.import_plan;
     header.lines.push(Line::from(format!(
-        "Items: {}",
-        app.import_preview_items.len()
+        "New: {} | duplicates skipped: {} | to translate: {}",
+        plan.new_words, plan.duplicates, plan.missing_translation
     )));
+    let mut load_line = format!("Groups ({}): {}", plan.groups.len(), plan.groups.join(", "));
+    if let Some(per_day) = plan.new_per_day
+        && plan.new_words > 0
+    {
+        let days = plan.days_to_introduce;
+        load_line.push_str(&format!(
+            " | {per_day} new/day, all introduced in {days} day{}",
+            if days == 1 { "" } else { "s" }
+        ));
+    }
+    header.lines.push(Line::from(load_line));
 
     let header_widget = Paragraph::new(header)
         .block(Block::default().borders(Borders::ALL).title("Preview"))
         .wrap(Wrap { trim: false });
     frame.render_widget(header_widget, chunks[0]);
 
-    let lines = build_preview_lines(&app.import_preview_items);
+    let lines = build_preview_lines(
+        &app.import_preview_items,
+        &app.import_form_matches,
+        app.import_preview_images.len() > 1,
+    );
     if lines.is_empty() {
         let empty = Paragraph::new("No items parsed.")
             .block(Block::default().borders(Borders::ALL))
@@ -924,16 +2039,188 @@ fn render_cleanup_review(frame: &mut ratatui::Frame, app: &App, area: Rect) {
     frame.render_widget(paragraph, area);
 }
 
+fn render_translation_fix(frame: &mut ratatui::Frame, app: &App, area: Rect) {
+    let mut text = Text::default();
+    text.lines.push(Line::from(format!(
+        "Missing Translations ({} to fill in)",
+        app.translation_fixes.len()
+    )));
+    text.lines.push(Line::from(""));
+    if app.translation_fixes.is_empty() {
+        text.lines
+            .push(Line::from("Nothing left to apply. Press n to go back."));
+    } else {
+        let spacing = app.ui_prefs.line_spacing();
+        let item_lines = area.height.saturating_sub(4) as usize / (1 + spacing);
+        let total = app.translation_fixes.len();
+        let mut start = app.translation_fix_selection.saturating_sub(item_lines / 2);
+        if item_lines > 0 && start + item_lines > total {
+            start = total.saturating_sub(item_lines);
+        }
+        let end = (start + item_lines).min(total);
+        for (idx, fix) in app.translation_fixes[start..end].iter().enumerate() {
+            let selected = start + idx == app.translation_fix_selection;
+            let line = format!(
+                "{} [{}] {} -> {}",
+                if selected { ">" } else { " " },
+                fix.language,
+                fix.text,
+                fix.translation
+            );
+            if selected {
+                text.lines.push(Line::from(Span::styled(
+                    line,
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                )));
+            } else {
+                text.lines.push(Line::from(line));
+            }
+            for _ in 0..spacing {
+                text.lines.push(Line::from(""));
+            }
+        }
+    }
+    let paragraph = Paragraph::new(text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Fix Translations"),
+        )
+        .wrap(Wrap { trim: false });
+    frame.render_widget(paragraph, area);
+}
+
+fn render_review_session(frame: &mut ratatui::Frame, app: &App, area: Rect) {
+    let mut text = Text::default();
+    let Some(review) = &app.review_session else {
+        return;
+    };
+    let progress = review.session.progress();
+    let tally = match progress.goal {
+        Some(goal) => format!(
+            "{} reviewed, {}/{goal} correct",
+            progress.reviewed, progress.correct
+        ),
+        None => format!(
+            "{} reviewed, {} correct",
+            progress.reviewed, progress.correct
+        ),
+    };
+    text.lines.push(Line::from(tally));
+    if review.preview {
+        text.lines
+            .push(Line::from("Preview: grades are not saved."));
+    }
+    text.lines.push(Line::from(""));
+    let summary = review.session.summary(Utc::now());
+    match review.session.current() {
+        None => {
+            let done = match summary.ended {
+                Some(SessionEnd::GoalReached) => "Goal reached.",
+                Some(SessionEnd::TimeUp) => "Time is up.",
+                _ => "No cards left.",
+            };
+            text.lines.push(Line::from(done));
+            if summary.relearned > 0 {
+                text.lines.push(Line::from(format!(
+                    "{} answers sent back for relearning.",
+                    summary.relearned
+                )));
+            }
+            text.lines.push(Line::from(if review.preview {
+                "Press any key to return to the recent additions."
+            } else {
+                "Press any key to return to the list."
+            }));
+        }
+        Some(card) => {
+            text.lines
+                .push(Line::from(format!("{} cards left", summary.remaining)));
+            text.lines.push(Line::from(""));
+            let word = review.words.get(&card.word_id);
+            let text_side = word.map_or("?", |word| word.text.as_str());
+            let translation_side = word
+                .and_then(|word| word.translation.as_deref())
+                .unwrap_or("?");
+            let (prompt, answer) = match card.card_type {
+                CardType::Basic | CardType::Cloze => (text_side, translation_side),
+                CardType::Reverse => (translation_side, text_side),
+            };
+            text.lines.push(Line::from(Span::styled(
+                prompt,
+                Style::default().add_modifier(Modifier::BOLD),
+            )));
+            text.lines.push(Line::from(""));
+            if review.revealed {
+                text.lines.push(Line::from(answer));
+                if let Some(notes) = word.and_then(|word| word.notes.as_deref()) {
+                    text.lines.push(Line::from(notes));
+                }
+                text.lines.push(Line::from(""));
+                text.lines
+                    .push(Line::from("0-2 forgot | 3 hard | 4 good | 5 easy"));
+            } else {
+                text.lines.push(Line::from("Space to show the answer"));
+            }
+        }
+    }
+    let paragraph = Paragraph::new(text)
+        .block(Block::default().borders(Borders::ALL).title(format!(
+            "{} — {}",
+            if review.preview { "Preview" } else { "Review" },
+            review.title
+        )))
+        .wrap(Wrap { trim: false });
+    frame.render_widget(paragraph, area);
+}
+
+fn render_review_search(frame: &mut ratatui::Frame, app: &App, area: Rect) {
+    let boxes = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
+        .split(area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Search text, translations, and notes")
+        .border_style(Style::default().fg(Color::Yellow));
+    app.search_input.render(frame, boxes[0], block, true);
+    render_review_list(frame, app, boxes[1]);
+}
+
 fn render_review_list(frame: &mut ratatui::Frame, app: &App, area: Rect) {
     let mut text = Text::default();
-    text.lines.push(Line::from("Review List"));
+    let mut title = "Review List".to_string();
+    if let Some(deck) = &app.review_deck {
+        title.push_str(&format!(" — deck: {}", deck.name));
+    }
+    if let Some(tag) = &app.review_tag_filter {
+        title.push_str(&format!(" — tag: {tag}"));
+    }
+    if let Some(query) = &app.review_search {
+        title.push_str(&format!(" — search: {query}"));
+    }
+    text.lines.push(Line::from(title));
     text.lines.push(Line::from(""));
     let items = app.review_list_items();
     if items.is_empty() {
-        text.lines.push(Line::from("No words saved yet"));
+        match (&app.review_search, &app.review_tag_filter, &app.review_deck) {
+            (Some(query), _, _) => text
+                .lines
+                .push(Line::from(format!("No words match '{query}'"))),
+            (None, Some(tag), _) => text
+                .lines
+                .push(Line::from(format!("No words tagged '{tag}'"))),
+            (None, None, Some(deck)) => text
+                .lines
+                .push(Line::from(format!("No words in the deck '{}'", deck.name))),
+            (None, None, None) => text.lines.push(Line::from("No words saved yet")),
+        }
     } else {
         let available_lines = area.height.saturating_sub(2) as usize;
-        let item_lines = available_lines.saturating_sub(2);
+        let spacing = app.ui_prefs.line_spacing();
+        let item_lines = available_lines.saturating_sub(2) / (1 + spacing);
         let total = items.len();
         let mut start = app.review_list_selection.saturating_sub(item_lines / 2);
         if item_lines > 0 && start + item_lines > total {
@@ -969,38 +2256,552 @@ fn render_review_list(frame: &mut ratatui::Frame, app: &App, area: Rect) {
                     let translation = word.translation.as_deref().unwrap_or("?");
                     (
                         format!(
-                            "{}   [{}] {} -> {}",
+                            "{}   [{}] {} -> {}{}",
                             if global_idx == app.review_list_selection {
                                 ">"
                             } else {
                                 " "
                             },
-                            language_label(word.language),
+                            word.language,
                             word.text,
-                            translation
+                            translation,
+                            if word.kind == EntryKind::Sentence {
+                                "  (sentence)"
+                            } else {
+                                ""
+                            }
                         ),
                         false,
                     )
                 }
             };
-            let styled = if global_idx == app.review_list_selection {
-                let mut style = Style::default()
+            let styled = if global_idx == app.review_list_selection {
+                let mut style = Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD);
+                if is_group {
+                    style = style.add_modifier(Modifier::UNDERLINED);
+                }
+                Line::from(Span::styled(line, style))
+            } else {
+                Line::from(line)
+            };
+            text.lines.push(styled);
+            if global_idx == app.review_list_selection
+                && let ReviewListItem::Word { index } = item
+                && let Some(source) = &app.review_list[*index].source
+            {
+                text.lines.push(Line::from(Span::styled(
+                    format!("      source: {}", describe_source(source)),
+                    Style::default().fg(Color::DarkGray),
+                )));
+            }
+            if global_idx == app.review_list_selection
+                && let ReviewListItem::Word { index } = item
+                && !app.review_list[*index].forms.is_empty()
+            {
+                text.lines.push(Line::from(Span::styled(
+                    format!("      forms: {}", app.review_list[*index].forms.join(", ")),
+                    Style::default().fg(Color::DarkGray),
+                )));
+            }
+            if global_idx == app.review_list_selection
+                && let ReviewListItem::Word { index } = item
+                && !app.review_list[*index].alt_answers.is_empty()
+            {
+                text.lines.push(Line::from(Span::styled(
+                    format!(
+                        "      also accepted: {}",
+                        app.review_list[*index].alt_answers.join(", ")
+                    ),
+                    Style::default().fg(Color::DarkGray),
+                )));
+            }
+            if global_idx == app.review_list_selection
+                && let ReviewListItem::Word { index } = item
+                && let Some(tags) = app.review_list_tags.get(&app.review_list[*index].id)
+            {
+                text.lines.push(Line::from(Span::styled(
+                    format!("      tags: {}", tags.join(", ")),
+                    Style::default().fg(Color::DarkGray),
+                )));
+            }
+            for _ in 0..spacing {
+                text.lines.push(Line::from(""));
+            }
+        }
+    }
+
+    let paragraph = Paragraph::new(text)
+        .block(Block::default().borders(Borders::ALL).title("Review"))
+        .wrap(Wrap { trim: true });
+    frame.render_widget(paragraph, area);
+}
+
+fn render_import_csv(frame: &mut ratatui::Frame, app: &App, area: Rect) {
+    let boxes = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
+        .split(area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("CSV file")
+        .border_style(Style::default().fg(Color::Yellow));
+    app.csv_path.render(frame, boxes[0], block, true);
+    let mut text = Text::default();
+    text.lines.push(Line::from(
+        "Path to a CSV file with text,translation,chapter,group rows. Only the text column is required; ; and tab separators work too.",
+    ));
+    if let Some(message) = &app.message {
+        text.lines.push(Line::from(""));
+        text.lines.push(Line::from(Span::styled(
+            message,
+            Style::default().add_modifier(Modifier::BOLD),
+        )));
+    }
+    let paragraph = Paragraph::new(text)
+        .block(Block::default().borders(Borders::ALL).title("Import CSV"))
+        .wrap(Wrap { trim: true });
+    frame.render_widget(paragraph, boxes[1]);
+}
+
+fn render_import_csv_columns(frame: &mut ratatui::Frame, app: &App, area: Rect) {
+    let boxes = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
+        .split(area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Columns")
+        .border_style(Style::default().fg(Color::Yellow));
+    app.csv_columns.render(frame, boxes[0], block, true);
+    let mut text = Text::default();
+    text.lines.push(Line::from(
+        "What each column holds, in order: text, translation, chapter, or group. Leave a name empty to skip that column, e.g. translation,text,,chapter. Remembered for files named like this one.",
+    ));
+    if let Some(message) = &app.message {
+        text.lines.push(Line::from(""));
+        text.lines.push(Line::from(Span::styled(
+            message,
+            Style::default().add_modifier(Modifier::BOLD),
+        )));
+    }
+    let paragraph = Paragraph::new(text)
+        .block(Block::default().borders(Borders::ALL).title("Import CSV"))
+        .wrap(Wrap { trim: true });
+    frame.render_widget(paragraph, boxes[1]);
+}
+
+fn render_import_csv_preview(frame: &mut ratatui::Frame, app: &mut App, area: Rect) {
+    let Some(preview) = app.csv_preview.as_mut() else {
+        return;
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(6), Constraint::Min(3)].as_ref())
+        .split(area);
+
+    let plan = &preview.plan;
+    let mut header = Text::default();
+    header.lines.push(Line::from("Import Preview"));
+    let mut file_line = format!("File: {} | columns: {}", preview.path, preview.columns);
+    if let Some(profile) = &preview.profile {
+        file_line.push_str(&format!(" | remembered for {}", profile.pattern));
+    }
+    header.lines.push(Line::from(file_line));
+    header.lines.push(Line::from(format!(
+        "Rows: {} | new: {} | duplicates skipped: {} | without translation: {}",
+        plan.items, plan.new_words, plan.duplicates, plan.missing_translation
+    )));
+    let mut load_line = format!("Groups ({}): {}", plan.groups.len(), plan.groups.join(", "));
+    if let Some(per_day) = plan.new_per_day
+        && plan.new_words > 0
+    {
+        let days = plan.days_to_introduce;
+        load_line.push_str(&format!(
+            " | {per_day} new/day, all introduced in {days} day{}",
+            if days == 1 { "" } else { "s" }
+        ));
+    }
+    header.lines.push(Line::from(load_line));
+    let header_widget = Paragraph::new(header)
+        .block(Block::default().borders(Borders::ALL).title("Preview"))
+        .wrap(Wrap { trim: false });
+    frame.render_widget(header_widget, chunks[0]);
+
+    if preview.rows.is_empty() {
+        let empty = Paragraph::new("No rows with a word found.")
+            .block(Block::default().borders(Borders::ALL))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(empty, chunks[1]);
+        return;
+    }
+    let body_height = chunks[1].height.saturating_sub(2) as usize;
+    let max_start = preview.rows.len().saturating_sub(body_height.max(1));
+    preview.scroll = preview.scroll.min(max_start);
+    let mut text = Text::default();
+    for (row, new) in preview
+        .rows
+        .iter()
+        .zip(&preview.new)
+        .skip(preview.scroll)
+        .take(body_height)
+    {
+        let mut line = format!(
+            "{:>4}  {} — {}",
+            row.line,
+            row.text,
+            row.translation.as_deref().unwrap_or("(no translation)")
+        );
+        let place: Vec<&str> = [row.chapter.as_deref(), row.group.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect();
+        if !place.is_empty() {
+            line.push_str(&format!("  [{}]", place.join(" / ")));
+        }
+        let style = if *new {
+            Style::default()
+        } else {
+            line.push_str("  (duplicate)");
+            Style::default().fg(Color::DarkGray)
+        };
+        text.lines.push(Line::from(Span::styled(line, style)));
+    }
+    let body = Paragraph::new(text).block(Block::default().borders(Borders::ALL));
+    frame.render_widget(body, chunks[1]);
+}
+
+fn render_tag_prompt(frame: &mut ratatui::Frame, app: &App, area: Rect) {
+    let boxes = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
+        .split(area);
+    let (title, help) = match &app.tag_prompt {
+        Some(TagPrompt::Word(_, text)) => (
+            format!("Tag '{text}'"),
+            "Type a tag such as \"verbs\" or \"exam-week-3\". Prefix it with - to remove it.",
+        ),
+        _ => (
+            "Filter by tag".to_string(),
+            "Only words with this tag are listed. Leave it empty to show every word.",
+        ),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(Style::default().fg(Color::Yellow));
+    app.tag_input.render(frame, boxes[0], block, true);
+    let paragraph = Paragraph::new(help)
+        .block(Block::default().borders(Borders::ALL))
+        .wrap(Wrap { trim: true });
+    frame.render_widget(paragraph, boxes[1]);
+}
+
+fn render_glossary(frame: &mut ratatui::Frame, app: &App, area: Rect) {
+    let Some(editor) = &app.glossary_editor else {
+        return;
+    };
+    let area = match &editor.editing {
+        Some(language) => {
+            let boxes = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
+                .split(area);
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .title(format!("{language} term = translation"))
+                .border_style(Style::default().fg(Color::Yellow));
+            editor.input.render(frame, boxes[0], block, true);
+            boxes[1]
+        }
+        None => area,
+    };
+    let mut text = Text::default();
+    for (index, entry) in app.glossary.entries().iter().enumerate() {
+        let row = format!(
+            "{} ({}) = {}",
+            entry.term,
+            entry.language.code(),
+            entry.translation
+        );
+        if index == editor.selection {
+            text.lines.push(Line::from(Span::styled(
+                format!("> {row}"),
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            )));
+        } else {
+            text.lines.push(Line::from(format!("  {row}")));
+        }
+    }
+    if app.glossary.entries().is_empty() {
+        text.lines.push(Line::from(
+            "No entries yet. Press a to add a term whose translation should always be the same.",
+        ));
+    }
+    let paragraph = Paragraph::new(text)
+        .block(Block::default().borders(Borders::ALL).title("Glossary"))
+        .wrap(Wrap { trim: true });
+    frame.render_widget(paragraph, area);
+}
+
+fn render_deck_select(frame: &mut ratatui::Frame, app: &App, area: Rect) {
+    let Some(picker) = &app.deck_picker else {
+        return;
+    };
+    let area = match picker.naming {
+        Some(naming) => {
+            let boxes = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
+                .split(area);
+            let title = match naming {
+                DeckNaming::New => "New deck name",
+                DeckNaming::Rename(_) => "Rename deck",
+            };
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(Color::Yellow));
+            picker.input.render(frame, boxes[0], block, true);
+            boxes[1]
+        }
+        None => area,
+    };
+    let (title, first) = match &picker.purpose {
+        DeckPurpose::Filter => ("Filter by deck".to_string(), "All decks"),
+        DeckPurpose::Move(label, _) => (format!("Move {label} to deck"), "No deck"),
+    };
+    let rows = std::iter::once(first.to_string()).chain(
+        picker
+            .decks
+            .iter()
+            .map(|deck| format!("{} ({} words)", deck.name, deck.words)),
+    );
+    let mut text = Text::default();
+    for (index, row) in rows.enumerate() {
+        if index == picker.selection {
+            text.lines.push(Line::from(Span::styled(
+                format!("> {row}"),
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            )));
+        } else {
+            text.lines.push(Line::from(format!("  {row}")));
+        }
+    }
+    if picker.decks.is_empty() {
+        text.lines.push(Line::from(""));
+        text.lines
+            .push(Line::from("No decks yet. Press n to create one."));
+    }
+    let paragraph = Paragraph::new(text)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .wrap(Wrap { trim: true });
+    frame.render_widget(paragraph, area);
+}
+
+fn describe_source(source: &WordSource) -> String {
+    let mut parts = vec![format!("{:?}", source.kind)];
+    if let Some(name) = &source.name {
+        parts.push(name.clone());
+    }
+    if let Some(page) = &source.page {
+        parts.push(page.clone());
+    }
+    parts.push(format!(
+        "captured {}",
+        source.captured_at.format("%Y-%m-%d %H:%M")
+    ));
+    parts.join(" | ")
+}
+
+/// Heatmap shades from no reviews to the busiest day shown.
+const HEATMAP_SHADES: [&str; 5] = ["·", "░", "▒", "▓", "█"];
+/// Weeks the heatmap shows at most, about a year.
+const HEATMAP_MAX_WEEKS: usize = 53;
+/// Width of the chapter name column in the accuracy list.
+const CHAPTER_COLUMN: usize = 24;
+const ACCURACY_BAR: usize = 20;
+
+fn render_stats(frame: &mut ratatui::Frame, app: &App, area: Rect) {
+    let Some(stats) = &app.stats else {
+        return;
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(13), Constraint::Min(3)].as_ref())
+        .split(area);
+
+    // Two columns per week plus the weekday labels and borders.
+    let weeks = (chunks[0].width.saturating_sub(6) as usize / 2).clamp(1, HEATMAP_MAX_WEEKS);
+    let this_monday = stats
+        .today
+        .checked_sub_days(Days::new(u64::from(
+            stats.today.weekday().num_days_from_monday(),
+        )))
+        .unwrap_or(stats.today);
+    let start = this_monday
+        .checked_sub_days(Days::new(7 * (weeks as u64 - 1)))
+        .unwrap_or(this_monday);
+    let counts: HashMap<NaiveDate, usize> = stats
+        .days
+        .iter()
+        .filter(|day| day.date >= start)
+        .map(|day| (day.date, day.reviews))
+        .collect();
+    let busiest = counts.values().copied().max().unwrap_or(0);
+    let shade = |count: usize| -> &'static str {
+        if count == 0 || busiest == 0 {
+            HEATMAP_SHADES[0]
+        } else {
+            HEATMAP_SHADES[(count * 4).div_ceil(busiest).clamp(1, 4)]
+        }
+    };
+
+    let mut text = Text::default();
+    let total: usize = counts.values().sum();
+    text.lines.push(Line::from(format!(
+        "{total} reviews on {} days in the last {weeks} weeks | today {}",
+        counts.len(),
+        counts.get(&stats.today).copied().unwrap_or(0)
+    )));
+    text.lines.push(Line::from(format!(
+        "Streak: {} days (longest {})",
+        stats.streak.current, stats.streak.longest
+    )));
+    // Month names over the week each month starts in, where they fit.
+    let mut months = String::from("    ");
+    for week in 0..weeks {
+        let monday = start + Days::new(7 * week as u64);
+        let column = 4 + 2 * week;
+        if (week == 0 || monday.day() <= 7) && months.len() <= column && column + 3 <= 4 + 2 * weeks
+        {
+            months.push_str(&" ".repeat(column - months.len()));
+            months.push_str(&monday.format("%b").to_string());
+        }
+    }
+    text.lines.push(Line::from(months));
+    for (row, weekday) in ["Mon", "", "Wed", "", "Fri", "", "Sun"].iter().enumerate() {
+        let mut spans = vec![Span::raw(format!("{weekday:<4}"))];
+        for week in 0..weeks {
+            let date = start + Days::new((7 * week + row) as u64);
+            if date > stats.today {
+                spans.push(Span::raw("  "));
+                continue;
+            }
+            let count = counts.get(&date).copied().unwrap_or(0);
+            let style = if count == 0 {
+                Style::default().fg(Color::DarkGray)
+            } else {
+                Style::default().fg(Color::Green)
+            };
+            spans.push(Span::styled(format!("{} ", shade(count)), style));
+        }
+        text.lines.push(Line::from(spans));
+    }
+    text.lines.push(Line::from(format!(
+        "    less {} more",
+        HEATMAP_SHADES.join(" ")
+    )));
+    frame.render_widget(
+        Paragraph::new(text).block(Block::default().borders(Borders::ALL).title("Reviews")),
+        chunks[0],
+    );
+
+    let mut text = Text::default();
+    if stats.chapters.is_empty() {
+        text.lines.push(Line::from("No reviews yet."));
+    }
+    let available = chunks[1].height.saturating_sub(2) as usize;
+    for chapter in stats.chapters.iter().skip(stats.scroll).take(available) {
+        let name = chapter.chapter.as_deref().unwrap_or("Unassigned");
+        let name: String = name.chars().take(CHAPTER_COLUMN).collect();
+        let accuracy = chapter.accuracy();
+        let filled = (accuracy * ACCURACY_BAR as f64).round() as usize;
+        text.lines.push(Line::from(vec![
+            Span::raw(format!("{name:<width$} ", width = CHAPTER_COLUMN)),
+            Span::styled("█".repeat(filled), Style::default().fg(Color::Green)),
+            Span::styled(
+                "░".repeat(ACCURACY_BAR - filled),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::raw(format!(
+                " {:>3}% of {}",
+                (accuracy * 100.0).round(),
+                chapter.reviews
+            )),
+        ]));
+    }
+    frame.render_widget(
+        Paragraph::new(text).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Accuracy by chapter"),
+        ),
+        chunks[1],
+    );
+}
+
+fn render_recent_additions(frame: &mut ratatui::Frame, app: &App, area: Rect) {
+    let Some(recent) = &app.recent_additions else {
+        return;
+    };
+    let total: usize = recent.groups.iter().map(|group| group.words.len()).sum();
+    let mut lines = vec![Line::from(format!(
+        "{total} words added in the last {} days, in {} groups",
+        recent.days,
+        recent.groups.len()
+    ))];
+    if recent.groups.is_empty() {
+        lines.push(Line::from("Nothing added in this window."));
+    }
+    // Header line of the selected group, to keep it on screen.
+    let mut selected_line = 0;
+    for (index, group) in recent.groups.iter().enumerate() {
+        lines.push(Line::from(""));
+        let header = format!(
+            "{} {} ({} words)",
+            if index == recent.selection { ">" } else { " " },
+            group.label(),
+            group.words.len()
+        );
+        if index == recent.selection {
+            selected_line = lines.len();
+            lines.push(Line::from(Span::styled(
+                header,
+                Style::default()
                     .fg(Color::Cyan)
-                    .add_modifier(Modifier::BOLD);
-                if is_group {
-                    style = style.add_modifier(Modifier::UNDERLINED);
-                }
-                Line::from(Span::styled(line, style))
+                    .add_modifier(Modifier::BOLD),
+            )));
+        } else {
+            lines.push(Line::from(header));
+        }
+        for word in &group.words {
+            let translation = word.translation.as_deref().unwrap_or("—");
+            let style = if word.translation.is_none() {
+                Style::default().fg(Color::Yellow)
             } else {
-                Line::from(line)
+                Style::default()
             };
-            text.lines.push(styled);
+            lines.push(Line::from(vec![
+                Span::raw(format!("    {} ", word.created_at.format("%a %d %b"))),
+                Span::raw(format!("{} = ", word.text)),
+                Span::styled(translation.to_string(), style),
+            ]));
         }
     }
-
-    let paragraph = Paragraph::new(text)
-        .block(Block::default().borders(Borders::ALL).title("Review"))
-        .wrap(Wrap { trim: true });
+    let available = area.height.saturating_sub(2) as usize;
+    let start = selected_line.saturating_sub(available / 3);
+    let text = Text::from(lines.into_iter().skip(start).collect::<Vec<_>>());
+    let paragraph = Paragraph::new(text).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Recent additions"),
+    );
     frame.render_widget(paragraph, area);
 }
 
@@ -1019,20 +2820,76 @@ fn render_confirm(app: &App) -> Paragraph<'_> {
 fn render_footer(app: &App) -> Paragraph<'_> {
     let info = match app.mode {
         Mode::Menu => {
-            "a add | c clipboard | i import | v review list | Ctrl+K cleanup | q quit | Ctrl+A add | Ctrl+O import | Ctrl+V list | Ctrl+Q quit"
+            "a add | c clipboard | i import | f CSV | v review list | s statistics | w recent words | g glossary | Ctrl+K cleanup | Ctrl+T fix translations | Ctrl+R reverse cards | q quit | Ctrl+A add | Ctrl+O import | Ctrl+V list | Ctrl+Q quit"
         }
         Mode::AddWord => {
             "Enter save | Tab switch | Esc clear | Ctrl+A add | Ctrl+O import | Ctrl+V list | Ctrl+Q quit"
         }
         Mode::ReviewList => {
-            "Up/Down or j/k move | Enter/Space toggle | d delete | D delete all | q back | Ctrl+A add | Ctrl+O import | Ctrl+V list | Ctrl+Q quit"
+            "Up/Down or j/k move | Enter/Space toggle | r review group | o open source | t tag | f filter by tag | b filter by deck | m move to deck | / search | d delete | D delete all | q back | Ctrl+A add | Ctrl+O import | Ctrl+V list | Ctrl+Q quit"
+        }
+        Mode::Import => {
+            "Up/Down or j/k move | Space mark | a mark all | Tab focus | Enter preview | Esc cancel"
         }
-        Mode::Import => "Up/Down or j/k move | Tab focus | Enter preview | Esc cancel",
-        Mode::ImportPreview => "Up/Down or j/k scroll | y confirm import | n back | Esc back",
+        Mode::ImportPreview => {
+            "Up/Down or j/k scroll | y confirm import | a toggle attaching forms | n back | Esc back"
+        }
+        Mode::ImportCsv => "Enter preview | Esc back",
+        Mode::ImportCsvPreview => {
+            "Up/Down or j/k scroll | y confirm import | c columns | n back | Esc back"
+        }
+        Mode::ImportCsvColumns => "Enter apply | Esc back",
         Mode::ChapterSelect => "Up/Down or j/k move | Enter select | Esc back",
         Mode::Confirm => "y confirm | n cancel",
         Mode::Message => "Any key back | Ctrl+A add | Ctrl+O import | Ctrl+V list | Ctrl+Q quit",
         Mode::CleanupReview => "y accept | n reject | s skip | q cancel",
+        Mode::TranslationFix => "Up/Down or j/k move | d drop | y apply all | n cancel",
+        Mode::TagPrompt => "Enter apply | Esc back",
+        Mode::ReviewSearch => "Type to search | Up/Down move | Enter keep results | Esc clear",
+        Mode::ReviewSession
+            if app
+                .review_session
+                .as_ref()
+                .is_some_and(|review| review.preview) =>
+        {
+            "Space/Enter show answer | 0-5 grade (not saved) | Esc back to recent words"
+        }
+        Mode::ReviewSession => "Space/Enter show answer | 0-5 grade | Esc back to list",
+        Mode::DeckSelect
+            if app
+                .deck_picker
+                .as_ref()
+                .is_some_and(|picker| picker.naming.is_some()) =>
+        {
+            "Enter save | Esc cancel"
+        }
+        Mode::DeckSelect => {
+            "Up/Down or j/k move | Enter choose | n new deck | e rename | x delete | Esc back"
+        }
+        Mode::Importing => "Esc cancel (keeps words already inserted) | Ctrl+Q quit",
+        Mode::Stats => {
+            "Up/Down or j/k scroll chapters | r reload | Esc back | Ctrl+A add | Ctrl+O import | Ctrl+V list | Ctrl+Q quit"
+        }
+        Mode::RecentAdditions => {
+            "Up/Down or j/k move | +/- days | p preview group | P preview all | Esc back | Ctrl+V list | Ctrl+Q quit"
+        }
+        Mode::Glossary
+            if app
+                .glossary_editor
+                .as_ref()
+                .is_some_and(|editor| editor.editing.is_some()) =>
+        {
+            "Enter save | Esc cancel"
+        }
+        Mode::Glossary => {
+            "Up/Down or j/k move | a add | e edit | x delete | Esc back | Ctrl+Q quit"
+        }
+    };
+    let info = match (app.mode, translation_status(app)) {
+        (Mode::AddWord | Mode::Import | Mode::Importing, Some(status)) => {
+            format!("{info} | {status}")
+        }
+        _ => info.to_string(),
     };
 
     Paragraph::new(info).block(
@@ -1042,11 +2899,55 @@ fn render_footer(app: &App) -> Paragraph<'_> {
     )
 }
 
-fn language_label(language: Language) -> &'static str {
-    match language {
-        Language::Dutch => "Dutch",
-        Language::English => "English",
+fn translation_status(app: &App) -> Option<String> {
+    let api = app.translation_api.as_deref()?;
+    if let Some(remaining) = api.paused_for() {
+        return Some(format!("Translation paused ({remaining}s)"));
+    }
+    match api.consecutive_failures() {
+        0 => None,
+        failures => Some(format!("Translation degraded ({failures} failed)")),
+    }
+}
+
+fn refresh_duplicate_hint(db: &dyn Db, app: &mut App) {
+    if app.mode != Mode::AddWord {
+        return;
+    }
+    if app.word_count.is_none() {
+        app.word_count = db.count_words().ok();
+    }
+
+    let Some(due_at) = app.dedupe_due_at else {
+        return;
+    };
+    if due_at.elapsed() < Duration::from_millis(DEDUPE_DEBOUNCE_MS) {
+        return;
+    }
+    app.dedupe_due_at = None;
+
+    let field = app.add_field;
+    let source = app.active_input().trim().to_string();
+    if app.last_dedupe_source.as_ref() == Some(&(field, source.clone())) {
+        return;
     }
+    app.last_dedupe_source = Some((field, source.clone()));
+    if source.is_empty() {
+        app.duplicate_hint = None;
+        return;
+    }
+
+    app.duplicate_hint = match db.find_duplicates(&source, &app.active_language()) {
+        Ok(matches) => matches.first().map(|existing| {
+            let chapter = existing.chapter.as_deref().unwrap_or("Unassigned");
+            if existing.text.eq_ignore_ascii_case(&source) {
+                format!("Already saved in {chapter} as '{}'", existing.text)
+            } else {
+                format!("Similar word saved in {chapter} as '{}'", existing.text)
+            }
+        }),
+        Err(err) => Some(format!("Duplicate check failed: {err}")),
+    };
 }
 
 const CLEANUP_BATCH_SIZE: usize = 10;
@@ -1062,6 +2963,60 @@ fn begin_cleanup_review(db: &dyn Db, app: &mut App) -> Result<(), String> {
     Ok(())
 }
 
+/// Translates every word with a NULL or blank translation and opens the
+/// preview; nothing is written until the batch is confirmed.
+fn begin_translation_fix(db: &dyn Db, app: &mut App) -> Result<(), String> {
+    let Some(api) = app.translation_api.clone() else {
+        return Err("Set TRANSLATION_API_URL to translate missing words.".to_string());
+    };
+    let words = db
+        .words_missing_translation()
+        .map_err(|err| err.to_string())?;
+    if words.is_empty() {
+        return Err("No words are missing a translation.".to_string());
+    }
+    let mut fixes = Vec::new();
+    for direction in [
+        TranslateDirection::SourceToTarget,
+        TranslateDirection::TargetToSource,
+    ] {
+        let (source, _) = direction.languages(&app.languages);
+        let (source_lang, target_lang) = direction.language_codes(&app.languages);
+        let batch: Vec<&Word> = words
+            .iter()
+            .filter(|word| word.language == *source)
+            .collect();
+        for chunk in batch.chunks(IMPORT_CHUNK_SIZE) {
+            let texts: Vec<&str> = chunk.iter().map(|word| word.text.as_str()).collect();
+            let translations = translate_batch_via_api(&api, &texts, &source_lang, &target_lang)?;
+            for (word, translation) in chunk.iter().zip(translations) {
+                let translation = app
+                    .glossary
+                    .apply(&word.text, &word.language, translation)
+                    .trim()
+                    .to_string();
+                if translation.is_empty() {
+                    continue;
+                }
+                fixes.push(TranslationFix {
+                    word_id: word.id,
+                    text: word.text.clone(),
+                    language: word.language.clone(),
+                    translation,
+                    notes: word.notes.clone(),
+                });
+            }
+        }
+    }
+    if fixes.is_empty() {
+        return Err("The translation API returned nothing for the missing words.".to_string());
+    }
+    app.translation_fixes = fixes;
+    app.translation_fix_selection = 0;
+    app.mode = Mode::TranslationFix;
+    Ok(())
+}
+
 fn collect_cleanup_entries(db: &dyn Db, limit: usize) -> Result<Vec<CleanupEntry>, String> {
     let cutoff = Utc::now() - ChronoDuration::hours(CLEANUP_REVIEW_COOLDOWN_HOURS);
     let rows = db
@@ -1084,26 +3039,13 @@ fn collect_cleanup_entries(db: &dyn Db, limit: usize) -> Result<Vec<CleanupEntry
 }
 
 fn request_cleanup_suggestions(entries: &[CleanupEntry]) -> Result<Vec<CleanupSuggestion>, String> {
-    let client = reqwest::blocking::Client::builder()
-        .timeout(Duration::from_secs(30))
-        .build()
+    let client = le_client::Client::from_env()
         .map_err(|err| format!("Failed to build AI request client: {err}"))?;
-    let url = format!("{}/ai/cleanup", cleanup_server_base_url());
-    let response = client
-        .post(&url)
-        .json(&CleanupRequest {
+    let payload = client
+        .cleanup(&le_client::CleanupRequest {
             entries: entries.to_vec(),
         })
-        .send()
         .map_err(|err| format!("AI cleanup request failed: {err}"))?;
-    let status = response.status();
-    if !status.is_success() {
-        let body = response.text().unwrap_or_default();
-        return Err(format!("AI cleanup request failed: {status} {body}"));
-    }
-    let payload = response
-        .json::<CleanupResponse>()
-        .map_err(|err| format!("Failed to parse AI cleanup response: {err}"))?;
     let mut suggestions = Vec::new();
     for item in payload.suggestions {
         suggestions.push(convert_cleanup_item(item)?);
@@ -1111,11 +3053,7 @@ fn request_cleanup_suggestions(entries: &[CleanupEntry]) -> Result<Vec<CleanupSu
     Ok(suggestions)
 }
 
-fn cleanup_server_base_url() -> String {
-    std::env::var("AUTH_SERVER_URL").unwrap_or_else(|_| "http://127.0.0.1:8787".to_string())
-}
-
-fn convert_cleanup_item(item: CleanupResponseItem) -> Result<CleanupSuggestion, String> {
+fn convert_cleanup_item(item: le_client::CleanupSuggestion) -> Result<CleanupSuggestion, String> {
     let word_id = Uuid::parse_str(&item.word_id)
         .map_err(|err| format!("Invalid word_id from AI cleanup response: {err}"))?;
     Ok(CleanupSuggestion {
@@ -1131,79 +3069,178 @@ fn convert_cleanup_item(item: CleanupResponseItem) -> Result<CleanupSuggestion,
 #[derive(Debug)]
 struct App {
     mode: Mode,
-    dutch_input: String,
-    english_input: String,
+    source_input: TextInput,
+    target_input: TextInput,
     add_field: AddField,
-    import_chapter: String,
+    import_chapter: TextInput,
+    /// The chapter was read from a photo's chapter line rather than typed,
+    /// and still waits for the preview to confirm it.
+    import_chapter_detected: bool,
     import_field: ImportField,
     import_images: Vec<String>,
     import_selection: usize,
     import_preview_items: Vec<ImportItem>,
     import_preview_scroll: usize,
-    import_preview_path: Option<String>,
-    import_pending_image: Option<String>,
+    /// Images ticked for a combined import; empty means the highlighted one.
+    import_marked: Vec<String>,
+    import_preview_images: Vec<String>,
+    import_pending_images: Vec<String>,
+    /// Preview items that look like inflections of saved words.
+    import_form_matches: Vec<FormMatch>,
+    /// Attach `import_form_matches` to the saved words instead of importing them.
+    import_attach_forms: bool,
+    /// Dry-run numbers for the preview, redone when `import_attach_forms` flips.
+    import_plan: ImportPlan,
+    /// Settings remembered for the source of the images being imported.
+    import_profile: Option<ImportProfile>,
+    csv_path: TextInput,
+    csv_preview: Option<CsvPreview>,
+    /// The column mapping being edited for the CSV preview.
+    csv_columns: TextInput,
     chapter_select_list: Vec<String>,
     chapter_select_index: usize,
     message: Option<String>,
     confirm_message: Option<String>,
     confirm_action: Option<ConfirmAction>,
     cleanup_state: Option<CleanupState>,
+    /// Shared with the GUI through the `ui` setting; read once at startup.
+    ui_prefs: UiPrefs,
+    /// Proposed translations for words saved without one, awaiting confirmation.
+    translation_fixes: Vec<TranslationFix>,
+    translation_fix_selection: usize,
     review_list: Vec<Word>,
     review_list_selection: usize,
     review_list_collapsed: HashSet<String>,
+    /// Tags per word in `review_list`, for the selected word's detail line.
+    review_list_tags: HashMap<Uuid, Vec<String>>,
+    /// Only words with this tag are listed.
+    review_tag_filter: Option<String>,
+    /// Only words in this deck are listed.
+    review_deck: Option<Deck>,
+    deck_picker: Option<DeckPicker>,
+    /// Only words matching this full-text search are listed, best match
+    /// first.
+    review_search: Option<String>,
+    search_input: TextInput,
+    /// The session started with `r` on a review list group.
+    review_session: Option<ReviewSession>,
+    stats: Option<StatsView>,
+    recent_additions: Option<RecentAdditions>,
+    /// Preferred translations applied over the translation API's; shared
+    /// with the threads that call it.
+    glossary: Arc<Glossary>,
+    glossary_editor: Option<GlossaryEditor>,
+    tag_prompt: Option<TagPrompt>,
+    tag_input: TextInput,
     session_config: SessionConfig,
+    /// `[scheduler]`, `[sm2]`, and `learning_steps` from the config; see
+    /// [`sm2_params`] and [`learning_steps`].
+    scheduler: SchedulerConfig,
+    languages: LanguagePair,
     translation_api: Option<Arc<TranslationApi>>,
     translation_tx: Sender<TranslationResult>,
     translation_rx: Receiver<TranslationResult>,
     translation_in_flight: bool,
     pending_translation: Option<PendingTranslation>,
     last_edit_field: Option<AddField>,
-    last_edit_dutch_at: Option<Instant>,
-    last_edit_english_at: Option<Instant>,
-    last_translated_dutch_source: Option<String>,
-    last_translated_english_source: Option<String>,
+    last_edit_source_at: Option<Instant>,
+    last_edit_target_at: Option<Instant>,
+    last_translated_from_source: Option<String>,
+    last_translated_from_target: Option<String>,
+    duplicate_hint: Option<String>,
+    last_dedupe_source: Option<(AddField, String)>,
+    dedupe_due_at: Option<Instant>,
+    word_count: Option<usize>,
+    /// Reads photos and screenshots for imports; see [`ocr_provider`].
+    ocr_provider: OcrProviderKind,
+    /// Pre-processing profile from the config; see [`ocr_profile_name`].
+    ocr_profile: Option<String>,
+    /// `ocr_profile` came from `OCR_PROFILE` and wins over remembered ones.
+    ocr_profile_forced: bool,
+    /// `[ocr.profiles]` from the config.
+    ocr_profiles: HashMap<String, PreprocessSteps>,
+    import_job: Option<ImportJob>,
+    add_source: SourceKind,
 }
 
 impl App {
     fn new(
         session_config: SessionConfig,
+        languages: LanguagePair,
         translation_api: Option<Arc<TranslationApi>>,
         translation_tx: Sender<TranslationResult>,
         translation_rx: Receiver<TranslationResult>,
     ) -> Self {
         let mut app = Self {
             mode: Mode::Import,
-            dutch_input: String::new(),
-            english_input: String::new(),
-            add_field: AddField::Dutch,
-            import_chapter: String::new(),
+            source_input: TextInput::default(),
+            target_input: TextInput::default(),
+            add_field: AddField::Source,
+            import_chapter: TextInput::default(),
+            import_chapter_detected: false,
             import_field: ImportField::Chapter,
             import_images: Vec::new(),
             import_selection: 0,
             import_preview_items: Vec::new(),
             import_preview_scroll: 0,
-            import_preview_path: None,
-            import_pending_image: None,
+            import_marked: Vec::new(),
+            import_preview_images: Vec::new(),
+            import_pending_images: Vec::new(),
+            import_form_matches: Vec::new(),
+            import_plan: ImportPlan::default(),
+            import_attach_forms: true,
+            csv_path: TextInput::default(),
+            csv_preview: None,
             chapter_select_list: Vec::new(),
             chapter_select_index: 0,
             message: None,
             confirm_message: None,
             confirm_action: None,
             cleanup_state: None,
+            ui_prefs: UiPrefs::default(),
+            translation_fixes: Vec::new(),
+            translation_fix_selection: 0,
             review_list: Vec::new(),
             review_list_selection: 0,
             review_list_collapsed: HashSet::new(),
+            review_list_tags: HashMap::new(),
+            review_tag_filter: None,
+            review_deck: None,
+            deck_picker: None,
+            review_search: None,
+            search_input: TextInput::default(),
+            review_session: None,
+            stats: None,
+            recent_additions: None,
+            glossary: Arc::default(),
+            glossary_editor: None,
+            tag_prompt: None,
+            tag_input: TextInput::default(),
             session_config,
+            scheduler: SchedulerConfig::default(),
+            languages,
             translation_api,
             translation_tx,
             translation_rx,
             translation_in_flight: false,
             pending_translation: None,
             last_edit_field: None,
-            last_edit_dutch_at: None,
-            last_edit_english_at: None,
-            last_translated_dutch_source: None,
-            last_translated_english_source: None,
+            last_edit_source_at: None,
+            last_edit_target_at: None,
+            last_translated_from_source: None,
+            last_translated_from_target: None,
+            duplicate_hint: None,
+            last_dedupe_source: None,
+            dedupe_due_at: None,
+            word_count: None,
+            ocr_provider: OcrProviderKind::platform_default(),
+            ocr_profile: None,
+            ocr_profile_forced: false,
+            ocr_profiles: HashMap::new(),
+            import_profile: None,
+            csv_columns: TextInput::default(),
+            import_job: None,
+            add_source: SourceKind::Manual,
         };
         app.start_import();
         app
@@ -1262,6 +3299,22 @@ impl App {
         }
     }
 
+    fn translation_fix_move(&mut self, delta: isize) {
+        let last = self.translation_fixes.len().saturating_sub(1);
+        self.translation_fix_selection = self
+            .translation_fix_selection
+            .saturating_add_signed(delta)
+            .min(last);
+    }
+
+    fn drop_translation_fix(&mut self) {
+        if self.translation_fix_selection < self.translation_fixes.len() {
+            self.translation_fixes
+                .remove(self.translation_fix_selection);
+            self.translation_fix_move(0);
+        }
+    }
+
     fn cancel_cleanup(&mut self, note: Option<String>) {
         self.cleanup_state = None;
         self.mode = Mode::Menu;
@@ -1272,45 +3325,130 @@ impl App {
 
     fn start_add(&mut self, prefilling: Option<String>) {
         self.reset_add();
+        self.add_source = SourceKind::Manual;
         if let Some(text) = prefilling {
-            *self.active_input_mut() = text;
+            self.add_source = SourceKind::Clipboard;
+            self.active_input_mut().set(text);
             self.mark_edit(self.add_field);
         }
         self.mode = Mode::AddWord;
     }
 
+    /// The pre-processing profile for the images being imported: the one
+    /// remembered for their source, unless `OCR_PROFILE` is set.
+    fn import_ocr_profile(&self) -> Option<String> {
+        let remembered = self
+            .import_profile
+            .as_ref()
+            .and_then(|profile| profile.ocr_profile.clone());
+        match remembered {
+            Some(name) if !self.ocr_profile_forced => Some(name),
+            _ => self.ocr_profile.clone(),
+        }
+    }
+
+    fn import_ocr(&self) -> OcrSettings {
+        OcrSettings {
+            provider: self.ocr_provider,
+            preprocess: ocr_profile_steps(self.import_ocr_profile().as_deref(), &self.ocr_profiles),
+        }
+    }
+
+    fn start_csv_import(&mut self) {
+        self.csv_preview = None;
+        self.message = None;
+        self.mode = Mode::ImportCsv;
+    }
+
     fn start_import(&mut self) {
+        self.import_profile = None;
         self.import_chapter.clear();
+        self.import_chapter_detected = false;
         self.import_field = ImportField::Chapter;
         self.import_images = list_import_images();
         self.import_selection = 0;
         self.import_preview_items.clear();
         self.import_preview_scroll = 0;
-        self.import_preview_path = None;
-        self.import_pending_image = None;
+        self.import_marked.clear();
+        self.import_preview_images.clear();
+        self.import_pending_images.clear();
+        self.import_form_matches.clear();
+        self.import_attach_forms = true;
         self.chapter_select_list.clear();
         self.chapter_select_index = 0;
         self.mode = Mode::Import;
     }
 
+    /// Marked images in folder order, or the highlighted image when none are
+    /// marked.
+    fn selected_import_images(&self) -> Vec<String> {
+        if self.import_marked.is_empty() {
+            return self
+                .import_images
+                .get(self.import_selection)
+                .cloned()
+                .into_iter()
+                .collect();
+        }
+        self.import_images
+            .iter()
+            .filter(|name| self.import_marked.contains(name))
+            .cloned()
+            .collect()
+    }
+
+    fn toggle_import_mark(&mut self) {
+        let Some(name) = self.import_images.get(self.import_selection) else {
+            return;
+        };
+        if let Some(pos) = self.import_marked.iter().position(|marked| marked == name) {
+            self.import_marked.remove(pos);
+        } else {
+            self.import_marked.push(name.clone());
+        }
+    }
+
+    /// Marks the whole folder, or clears the marks if everything is marked.
+    fn toggle_import_mark_all(&mut self) {
+        if self.import_marked.len() == self.import_images.len() {
+            self.import_marked.clear();
+        } else {
+            self.import_marked = self.import_images.clone();
+        }
+    }
+
+    /// Opens the import screen with a pasted image selected, waiting for a
+    /// chapter before the OCR preview runs.
+    fn start_clipboard_import(&mut self, image_name: String) {
+        self.start_import();
+        self.import_selection = self
+            .import_images
+            .iter()
+            .position(|name| *name == image_name)
+            .unwrap_or(0);
+        self.set_message(format!(
+            "Clipboard image saved as img/{image_name}. Enter a chapter and press Enter to preview."
+        ));
+    }
+
     fn reset_add(&mut self) {
         self.reset_add_fields();
-        self.dutch_input.clear();
-        self.english_input.clear();
-        self.add_field = AddField::Dutch;
+        self.source_input.clear();
+        self.target_input.clear();
+        self.add_field = AddField::Source;
     }
 
     fn reset_add_fields(&mut self) {
-        self.dutch_input.clear();
-        self.english_input.clear();
+        self.source_input.clear();
+        self.target_input.clear();
         self.message = None;
         self.reset_translation_state();
     }
 
     fn toggle_add_field(&mut self) {
         self.add_field = match self.add_field {
-            AddField::Dutch => AddField::English,
-            AddField::English => AddField::Dutch,
+            AddField::Source => AddField::Target,
+            AddField::Target => AddField::Source,
         };
     }
 
@@ -1321,62 +3459,84 @@ impl App {
         };
     }
 
-    fn push_add_char(&mut self, ch: char) {
-        self.active_input_mut().push(ch);
-        self.mark_edit(self.add_field);
-    }
-
-    fn pop_add_char(&mut self) {
-        self.active_input_mut().pop();
-        self.mark_edit(self.add_field);
-    }
-
-    fn push_import_char(&mut self, ch: char) {
-        if self.import_field == ImportField::Chapter {
-            self.import_chapter.push(ch);
-        }
-    }
-
-    fn pop_import_char(&mut self) {
-        if self.import_field == ImportField::Chapter {
-            self.import_chapter.pop();
+    /// Inserts bracketed-paste text into whichever field has focus.
+    fn paste(&mut self, text: &str) {
+        match self.mode {
+            Mode::AddWord => {
+                self.active_input_mut().insert_str(text);
+                self.mark_edit(self.add_field);
+            }
+            Mode::Import if self.import_field == ImportField::Chapter => {
+                self.import_chapter.insert_str(text);
+                self.import_chapter_detected = false;
+            }
+            Mode::TagPrompt => self.tag_input.insert_str(text),
+            Mode::DeckSelect => {
+                if let Some(picker) = &mut self.deck_picker
+                    && picker.naming.is_some()
+                {
+                    picker.input.insert_str(text);
+                }
+            }
+            Mode::ReviewSearch => self.search_input.insert_str(text),
+            Mode::Glossary => {
+                if let Some(editor) = &mut self.glossary_editor
+                    && editor.editing.is_some()
+                {
+                    editor.input.insert_str(text);
+                }
+            }
+            Mode::ImportCsv => self.csv_path.insert_str(text),
+            Mode::ImportCsvColumns => self.csv_columns.insert_str(text),
+            _ => {}
         }
     }
 
     fn active_input(&self) -> &str {
         match self.add_field {
-            AddField::Dutch => &self.dutch_input,
-            AddField::English => &self.english_input,
+            AddField::Source => self.source_input.as_str(),
+            AddField::Target => self.target_input.as_str(),
         }
     }
 
-    fn active_input_mut(&mut self) -> &mut String {
+    fn active_input_mut(&mut self) -> &mut TextInput {
         match self.add_field {
-            AddField::Dutch => &mut self.dutch_input,
-            AddField::English => &mut self.english_input,
+            AddField::Source => &mut self.source_input,
+            AddField::Target => &mut self.target_input,
         }
     }
 
     fn inactive_input(&self) -> &str {
         match self.add_field {
-            AddField::Dutch => &self.english_input,
-            AddField::English => &self.dutch_input,
+            AddField::Source => self.target_input.as_str(),
+            AddField::Target => self.source_input.as_str(),
         }
     }
 
     fn active_language(&self) -> Language {
         match self.add_field {
-            AddField::Dutch => Language::Dutch,
-            AddField::English => Language::English,
+            AddField::Source => self.languages.source.clone(),
+            AddField::Target => self.languages.target.clone(),
         }
     }
 
     fn clear_add_inputs(&mut self) {
-        self.dutch_input.clear();
-        self.english_input.clear();
+        self.source_input.clear();
+        self.target_input.clear();
         self.reset_translation_state();
     }
 
+    fn start_tag_prompt(&mut self, prompt: TagPrompt) {
+        self.tag_input.clear();
+        if matches!(prompt, TagPrompt::Filter)
+            && let Some(tag) = &self.review_tag_filter
+        {
+            self.tag_input.set(tag.clone());
+        }
+        self.tag_prompt = Some(prompt);
+        self.mode = Mode::TagPrompt;
+    }
+
     fn review_list_move(&mut self, delta: i32) {
         let items = self.review_list_items();
         if items.is_empty() {
@@ -1401,6 +3561,22 @@ impl App {
         }
     }
 
+    /// The selected group header's name and the ids of the words listed
+    /// under it, collapsed or not.
+    fn current_review_group(&self) -> Option<(String, Vec<Uuid>)> {
+        let items = self.review_list_items();
+        let ReviewListItem::Group { key, .. } = items.get(self.review_list_selection)? else {
+            return None;
+        };
+        let word_ids = self
+            .review_list
+            .iter()
+            .filter(|word| review_group_key(word) == *key)
+            .map(|word| word.id)
+            .collect();
+        Some((key.clone(), word_ids))
+    }
+
     fn toggle_review_group(&mut self) {
         let items = self.review_list_items();
         let item = match items.get(self.review_list_selection) {
@@ -1426,16 +3602,15 @@ impl App {
         if self.review_list.is_empty() {
             return Vec::new();
         }
+        // Groups appear in the order of their first word, so search results
+        // keep the best-matching group on top.
         let mut groups: Vec<(String, Vec<usize>)> = Vec::new();
         for (idx, word) in self.review_list.iter().enumerate() {
             let key = review_group_key(word);
-            if let Some((last_key, items)) = groups.last_mut() {
-                if *last_key == key {
-                    items.push(idx);
-                    continue;
-                }
+            match groups.iter_mut().find(|(group_key, _)| *group_key == key) {
+                Some((_, items)) => items.push(idx),
+                None => groups.push((key, vec![idx])),
             }
-            groups.push((key, vec![idx]));
         }
 
         let mut items = Vec::new();
@@ -1460,19 +3635,23 @@ impl App {
         let now = Instant::now();
         self.last_edit_field = Some(field);
         match field {
-            AddField::Dutch => self.last_edit_dutch_at = Some(now),
-            AddField::English => self.last_edit_english_at = Some(now),
+            AddField::Source => self.last_edit_source_at = Some(now),
+            AddField::Target => self.last_edit_target_at = Some(now),
         }
+        self.dedupe_due_at = Some(now);
     }
 
     fn reset_translation_state(&mut self) {
         self.translation_in_flight = false;
         self.pending_translation = None;
         self.last_edit_field = None;
-        self.last_edit_dutch_at = None;
-        self.last_edit_english_at = None;
-        self.last_translated_dutch_source = None;
-        self.last_translated_english_source = None;
+        self.last_edit_source_at = None;
+        self.last_edit_target_at = None;
+        self.last_translated_from_source = None;
+        self.last_translated_from_target = None;
+        self.duplicate_hint = None;
+        self.last_dedupe_source = None;
+        self.dedupe_due_at = None;
     }
 
     fn process_translation(&mut self) {
@@ -1500,17 +3679,17 @@ impl App {
         };
 
         let (source_text, direction, last_edit_at, last_translated_source) = match field {
-            AddField::Dutch => (
-                self.dutch_input.clone(),
-                TranslateDirection::DutchToEnglish,
-                self.last_edit_dutch_at,
-                self.last_translated_dutch_source.as_deref(),
+            AddField::Source => (
+                self.source_input.as_str().to_string(),
+                TranslateDirection::SourceToTarget,
+                self.last_edit_source_at,
+                self.last_translated_from_source.as_deref(),
             ),
-            AddField::English => (
-                self.english_input.clone(),
-                TranslateDirection::EnglishToDutch,
-                self.last_edit_english_at,
-                self.last_translated_english_source.as_deref(),
+            AddField::Target => (
+                self.target_input.as_str().to_string(),
+                TranslateDirection::TargetToSource,
+                self.last_edit_target_at,
+                self.last_translated_from_target.as_deref(),
             ),
         };
 
@@ -1535,7 +3714,13 @@ impl App {
             Some(api) => Arc::clone(api),
             None => return,
         };
+        if api.paused_for().is_some() {
+            return;
+        }
         let tx = self.translation_tx.clone();
+        let glossary = Arc::clone(&self.glossary);
+        let language = direction.languages(&self.languages).0.clone();
+        let (source_lang, target_lang) = direction.language_codes(&self.languages);
         let source_owned = source_trimmed.to_string();
         let started_at = Instant::now();
         self.translation_in_flight = true;
@@ -1546,8 +3731,8 @@ impl App {
         });
 
         thread::spawn(move || {
-            let (source_lang, target_lang) = direction.language_codes();
-            let result = translate_via_api(&api, &source_owned, source_lang, target_lang);
+            let result = translate_via_api(&api, &source_owned, &source_lang, &target_lang)
+                .map(|translated| glossary.apply(&source_owned, &language, translated));
             let _ = tx.send(TranslationResult {
                 direction,
                 source_text: source_owned,
@@ -1567,8 +3752,8 @@ impl App {
         }
 
         let target_was_edited = match result.direction {
-            TranslateDirection::DutchToEnglish => self.last_edit_english_at,
-            TranslateDirection::EnglishToDutch => self.last_edit_dutch_at,
+            TranslateDirection::SourceToTarget => self.last_edit_target_at,
+            TranslateDirection::TargetToSource => self.last_edit_source_at,
         }
         .map(|edited_at| edited_at > pending.started_at)
         .unwrap_or(false);
@@ -1578,8 +3763,8 @@ impl App {
         }
 
         let current_source = match result.direction {
-            TranslateDirection::DutchToEnglish => self.dutch_input.trim(),
-            TranslateDirection::EnglishToDutch => self.english_input.trim(),
+            TranslateDirection::SourceToTarget => self.source_input.as_str().trim(),
+            TranslateDirection::TargetToSource => self.target_input.as_str().trim(),
         };
 
         if current_source != pending.source_text {
@@ -1588,15 +3773,16 @@ impl App {
 
         match result.result {
             Ok(translated) => match result.direction {
-                TranslateDirection::DutchToEnglish => {
-                    self.english_input = translated;
-                    self.last_translated_dutch_source = Some(pending.source_text);
+                TranslateDirection::SourceToTarget => {
+                    self.target_input.set(translated);
+                    self.last_translated_from_source = Some(pending.source_text);
                 }
-                TranslateDirection::EnglishToDutch => {
-                    self.dutch_input = translated;
-                    self.last_translated_english_source = Some(pending.source_text);
+                TranslateDirection::TargetToSource => {
+                    self.source_input.set(translated);
+                    self.last_translated_from_target = Some(pending.source_text);
                 }
             },
+            Err(err) if err == TRANSLATE_OFFLINE => self.set_message(err),
             Err(err) => {
                 self.set_message(format!("Translation failed: {err}"));
             }
@@ -1612,9 +3798,130 @@ enum Mode {
     Confirm,
     Import,
     ImportPreview,
+    ImportCsv,
+    ImportCsvPreview,
+    ImportCsvColumns,
     ChapterSelect,
     Message,
     CleanupReview,
+    TranslationFix,
+    TagPrompt,
+    ReviewSearch,
+    ReviewSession,
+    DeckSelect,
+    Importing,
+    Stats,
+    RecentAdditions,
+    Glossary,
+}
+
+/// A CSV word list read for the import preview.
+#[derive(Debug)]
+struct CsvPreview {
+    /// The path as typed, for the preview and the words' source.
+    path: String,
+    rows: Vec<CsvRow>,
+    /// Per row of `rows`: neither saved already nor repeated earlier in the
+    /// file, so it will be imported.
+    new: Vec<bool>,
+    plan: ImportPlan,
+    scroll: usize,
+    /// How the columns were read; `c` in the preview changes it.
+    columns: ColumnMapping,
+    /// Settings remembered for files like this one, already applied.
+    profile: Option<ImportProfile>,
+}
+
+/// A review session over one review list group, in serving order.
+#[derive(Debug)]
+struct ReviewSession {
+    /// The group's name, as its header shows it.
+    title: String,
+    /// The card on screen is the session's current one.
+    session: Session<Card>,
+    words: HashMap<Uuid, Word>,
+    /// Whether the answer to the current card is shown.
+    revealed: bool,
+    /// Started from the recent additions digest: grades are not saved, and
+    /// leaving returns to the digest.
+    preview: bool,
+}
+
+/// The glossary editor opened with `g` from the menu.
+#[derive(Debug, Default)]
+struct GlossaryEditor {
+    /// Index into the glossary's entries.
+    selection: usize,
+    /// Set while `term = translation` is typed into `input`; holds the
+    /// language of the term being added or edited.
+    editing: Option<Language>,
+    input: TextInput,
+}
+
+/// Days the recent additions digest looks back when opened.
+const RECENT_ADDITION_DAYS: u32 = 7;
+
+/// The digest of recently added words opened with `w` from the menu.
+#[derive(Debug)]
+struct RecentAdditions {
+    days: u32,
+    groups: Vec<AdditionGroup>,
+    /// Index into `groups`.
+    selection: usize,
+}
+
+/// The review statistics screen opened with `s` from the menu.
+#[derive(Debug)]
+struct StatsView {
+    days: Vec<ReviewDay>,
+    chapters: Vec<ChapterAccuracy>,
+    streak: Streak,
+    today: NaiveDate,
+    /// First chapter row shown.
+    scroll: usize,
+}
+
+/// What choosing a deck in the deck picker does.
+#[derive(Debug, Clone)]
+enum DeckPurpose {
+    /// Sets the review list's deck filter.
+    Filter,
+    /// Moves these words, described by the label, into the chosen deck.
+    Move(String, Vec<Uuid>),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum DeckNaming {
+    New,
+    Rename(Uuid),
+}
+
+/// The deck list opened with `b` or `m` in the review list.
+#[derive(Debug)]
+struct DeckPicker {
+    purpose: DeckPurpose,
+    decks: Vec<Deck>,
+    /// 0 is "All decks" when filtering and "No deck" when moving; the deck
+    /// at `decks[i]` is at `i + 1`.
+    selection: usize,
+    /// Set while a deck name is typed into `input`.
+    naming: Option<DeckNaming>,
+    input: TextInput,
+}
+
+impl DeckPicker {
+    fn selected(&self) -> Option<&Deck> {
+        self.decks.get(self.selection.checked_sub(1)?)
+    }
+}
+
+/// What the tag prompt's input is for.
+#[derive(Debug, Clone)]
+enum TagPrompt {
+    /// Adds a tag to (or removes one from) the word with this id and text.
+    Word(Uuid, String),
+    /// Sets the review list's tag filter.
+    Filter,
 }
 
 #[derive(Debug, Clone)]
@@ -1634,39 +3941,21 @@ struct CleanupSuggestion {
     notes: Option<String>,
 }
 
-#[derive(Debug, Serialize, Clone)]
-struct CleanupEntry {
-    word_id: String,
-    text: String,
-    translation: Option<String>,
-    language: String,
-    notes: Option<String>,
-}
-
-#[derive(Debug, Serialize)]
-struct CleanupRequest {
-    entries: Vec<CleanupEntry>,
-}
-
-#[derive(Debug, Deserialize)]
-struct CleanupResponse {
-    suggestions: Vec<CleanupResponseItem>,
-}
-
-#[derive(Debug, Deserialize)]
-struct CleanupResponseItem {
-    word_id: String,
+#[derive(Debug, Clone)]
+struct TranslationFix {
+    word_id: Uuid,
     text: String,
-    language: String,
-    current_translation: Option<String>,
-    suggestion: String,
+    language: Language,
+    translation: String,
     notes: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum AddField {
-    Dutch,
-    English,
+    /// The word in the language being learned.
+    Source,
+    /// Its translation.
+    Target,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -1679,11 +3968,44 @@ enum ImportField {
 enum ConfirmAction {
     DeleteWord(Uuid),
     DeleteAll,
+    DeleteDeck(Uuid),
+    /// Attach the word on the add screen to this saved word as a form.
+    AttachForm(Uuid),
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 enum OcrProviderKind {
+    /// Apple's Vision framework through `scripts/vision_ocr.swift`; macOS only.
     Vision,
+    /// The `tesseract` command line tool, for Linux and Windows.
+    Tesseract,
+}
+
+impl OcrProviderKind {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "vision" => Some(OcrProviderKind::Vision),
+            "tesseract" => Some(OcrProviderKind::Tesseract),
+            _ => None,
+        }
+    }
+
+    fn platform_default() -> Self {
+        if cfg!(target_os = "macos") {
+            OcrProviderKind::Vision
+        } else {
+            OcrProviderKind::Tesseract
+        }
+    }
+}
+
+/// How import photos are read: the provider, and the clean-up applied to
+/// each photo first.
+#[derive(Debug, Clone, Copy)]
+struct OcrSettings {
+    provider: OcrProviderKind,
+    preprocess: PreprocessSteps,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -1706,6 +4028,7 @@ struct LineEntry {
     text: String,
     x: f32,
     y_top: f32,
+    width: f32,
     height: f32,
 }
 
@@ -1734,6 +4057,57 @@ impl ColumnBucket {
 struct ImportItem {
     text: String,
     group: String,
+    /// The `img/` file the item was read from; `region` is relative to it.
+    image: String,
+    region: Option<SourceRegion>,
+}
+
+#[derive(Debug, Clone)]
+struct FormMatch {
+    form: String,
+    word_id: Uuid,
+    lemma: String,
+}
+
+#[derive(Debug)]
+enum ImportProgress {
+    /// Images OCR'd so far.
+    Scanned(usize),
+    Parsed(usize),
+    Translated(Vec<(ImportItem, String)>),
+    Failed(String),
+    Done,
+}
+
+enum ImportOutcome {
+    Completed,
+    Cancelled,
+    Failed(String),
+}
+
+/// An import running on a worker thread. The worker does OCR and translation;
+/// rows are inserted on the UI thread as translated chunks arrive.
+#[derive(Debug)]
+struct ImportJob {
+    image_names: Vec<String>,
+    chapter: String,
+    /// Language the imported words are saved in.
+    language: Language,
+    rx: Receiver<ImportProgress>,
+    cancel: Arc<AtomicBool>,
+    scanned: usize,
+    parsed: Option<usize>,
+    translated: usize,
+    /// Translated words waiting to be saved together when the job ends.
+    pending: Vec<NewWord>,
+    inserted: usize,
+    skipped: usize,
+    /// Inflected forms left out of the import, attached to their saved words
+    /// when it completes.
+    forms: Vec<FormMatch>,
+    attached: usize,
+    /// Words saved without a translation because the API was unreachable.
+    queued: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -1750,17 +4124,24 @@ enum ReviewListItem {
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum TranslateDirection {
-    DutchToEnglish,
-    EnglishToDutch,
+    SourceToTarget,
+    TargetToSource,
 }
 
 impl TranslateDirection {
-    fn language_codes(self) -> (&'static str, &'static str) {
+    /// The languages translated from and into.
+    fn languages(self, pair: &LanguagePair) -> (&Language, &Language) {
         match self {
-            TranslateDirection::DutchToEnglish => ("NL", "EN"),
-            TranslateDirection::EnglishToDutch => ("EN", "NL"),
+            TranslateDirection::SourceToTarget => (&pair.source, &pair.target),
+            TranslateDirection::TargetToSource => (&pair.target, &pair.source),
         }
     }
+
+    /// Upper-case tags as DeepL-style APIs expect them ("NL", "EN-GB").
+    fn language_codes(self, pair: &LanguagePair) -> (String, String) {
+        let (source, target) = self.languages(pair);
+        (source.code().to_uppercase(), target.code().to_uppercase())
+    }
 }
 
 #[derive(Debug)]
@@ -1784,6 +4165,22 @@ struct TranslationApi {
     url: String,
     auth_header: Option<String>,
     auth_value: Option<String>,
+    breaker: Mutex<CircuitBreaker>,
+}
+
+/// Stops calling the translation API for `BREAKER_COOLDOWN_SECS` once
+/// `BREAKER_FAILURE_THRESHOLD` calls in a row have failed after retries.
+#[derive(Debug, Default)]
+struct CircuitBreaker {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+}
+
+enum TranslateFailure {
+    /// The API host or proxy cannot be reached; retrying will not help.
+    Offline,
+    Transient(String),
+    Fatal(String),
 }
 
 impl TranslationApi {
@@ -1806,9 +4203,7 @@ impl TranslationApi {
             None => (None, None),
         };
 
-        let client = reqwest::blocking::Client::builder()
-            .timeout(Duration::from_secs(15))
-            .build()
+        let client = connectivity::http_client(Duration::from_secs(15))
             .map_err(|err| format!("Failed to build HTTP client: {err}"))?;
 
         Ok(Self {
@@ -1816,8 +4211,39 @@ impl TranslationApi {
             url,
             auth_header: header_name,
             auth_value: header_value,
+            breaker: Mutex::new(CircuitBreaker::default()),
         })
     }
+
+    /// Seconds left before calls resume, if the breaker is open.
+    fn paused_for(&self) -> Option<u64> {
+        let breaker = self.breaker.lock().ok()?;
+        let remaining = breaker.open_until?.checked_duration_since(Instant::now())?;
+        Some(remaining.as_secs() + 1)
+    }
+
+    fn consecutive_failures(&self) -> u32 {
+        self.breaker
+            .lock()
+            .map(|breaker| breaker.consecutive_failures)
+            .unwrap_or(0)
+    }
+
+    fn record_success(&self) {
+        if let Ok(mut breaker) = self.breaker.lock() {
+            *breaker = CircuitBreaker::default();
+        }
+    }
+
+    fn record_failure(&self) {
+        if let Ok(mut breaker) = self.breaker.lock() {
+            breaker.consecutive_failures += 1;
+            if breaker.consecutive_failures >= BREAKER_FAILURE_THRESHOLD {
+                breaker.open_until =
+                    Some(Instant::now() + Duration::from_secs(BREAKER_COOLDOWN_SECS));
+            }
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -1850,6 +4276,9 @@ fn translate_via_api(
         .ok_or_else(|| "API response missing translations".to_string())
 }
 
+/// Calls the translation API, retrying 429/5xx responses and network errors
+/// with exponential backoff. Failures feed the API's circuit breaker; being
+/// offline does not, and fails at once with [`TRANSLATE_OFFLINE`].
 fn translate_batch_via_api(
     api: &TranslationApi,
     texts: &[&str],
@@ -1859,6 +4288,39 @@ fn translate_batch_via_api(
     if texts.is_empty() {
         return Ok(Vec::new());
     }
+    if let Some(remaining) = api.paused_for() {
+        return Err(format!(
+            "Translation paused after repeated failures; retrying in {remaining}s"
+        ));
+    }
+    let mut attempt = 1;
+    loop {
+        match send_translate_request(api, texts, source_lang, target_lang) {
+            Ok(translations) => {
+                api.record_success();
+                return Ok(translations);
+            }
+            Err(TranslateFailure::Offline) => return Err(TRANSLATE_OFFLINE.to_string()),
+            Err(TranslateFailure::Transient(_)) if attempt < TRANSLATE_MAX_ATTEMPTS => {
+                thread::sleep(Duration::from_millis(
+                    TRANSLATE_BACKOFF_MS * 2u64.pow(attempt - 1),
+                ));
+                attempt += 1;
+            }
+            Err(TranslateFailure::Transient(err)) | Err(TranslateFailure::Fatal(err)) => {
+                api.record_failure();
+                return Err(err);
+            }
+        }
+    }
+}
+
+fn send_translate_request(
+    api: &TranslationApi,
+    texts: &[&str],
+    source_lang: &str,
+    target_lang: &str,
+) -> Result<Vec<String>, TranslateFailure> {
     let payload = TranslateRequest {
         text: texts.to_vec(),
         source_lang,
@@ -1868,21 +4330,32 @@ fn translate_batch_via_api(
     if let (Some(header), Some(value)) = (&api.auth_header, &api.auth_value) {
         request = request.header(header, value);
     }
-    let response = request
-        .send()
-        .map_err(|err| format!("Failed to call translation API: {err}"))?;
+    let response = request.send().map_err(|err| {
+        if connectivity::is_offline_error(&err) && connectivity::is_offline(&api.url) {
+            TranslateFailure::Offline
+        } else {
+            TranslateFailure::Transient(format!("Failed to call translation API: {err}"))
+        }
+    })?;
 
     if !response.status().is_success() {
         let status = response.status();
         let body = response.text().unwrap_or_default();
-        return Err(format!("Translation API error ({status}): {body}"));
+        let message = format!("Translation API error ({status}): {body}");
+        return Err(if status.as_u16() == 429 || status.is_server_error() {
+            TranslateFailure::Transient(message)
+        } else {
+            TranslateFailure::Fatal(message)
+        });
     }
 
     let response: TranslateResponse = response
         .json()
-        .map_err(|err| format!("Invalid API response: {err}"))?;
+        .map_err(|err| TranslateFailure::Fatal(format!("Invalid API response: {err}")))?;
     if response.translations.len() != texts.len() {
-        return Err("Translation API response count mismatch".to_string());
+        return Err(TranslateFailure::Fatal(
+            "Translation API response count mismatch".to_string(),
+        ));
     }
     Ok(response
         .translations
@@ -1893,7 +4366,128 @@ fn translate_batch_via_api(
 
 #[derive(Debug, Serialize, Deserialize)]
 struct ConfigFile {
+    /// Delays between a new card's first answers, such as `["1m", "10m"]`;
+    /// an empty list sends new cards straight to the scheduler.
+    #[serde(default = "learning::default_learning_steps")]
+    learning_steps: Vec<LearningStep>,
     session: SessionConfig,
+    /// Language learned and language translated into, as BCP-47 tags.
+    #[serde(default)]
+    languages: LanguagePair,
+    #[serde(default)]
+    ocr: OcrConfig,
+    /// SM-2 tuning; the classic values when the section is missing.
+    #[serde(default)]
+    sm2: Sm2Params,
+    /// Which algorithm schedules reviews; SM-2 everywhere when missing.
+    #[serde(default)]
+    scheduler: SchedulerChoice,
+}
+
+/// `[scheduler]` in the config: `algorithm` for every card, with overrides
+/// in `[scheduler.decks]` keyed by deck id.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SchedulerChoice {
+    #[serde(default)]
+    algorithm: SchedulerKind,
+    #[serde(default)]
+    decks: BTreeMap<String, SchedulerKind>,
+}
+
+/// `params` when every value is in range; otherwise each problem is logged
+/// and reviews use the defaults.
+fn sm2_params(params: Sm2Params) -> Sm2Params {
+    let problems = params.problems();
+    if problems.is_empty() {
+        return params;
+    }
+    for (field, message) in problems {
+        crate::db::log_error(&format!(
+            "Ignoring [sm2] in config.toml, {field}: {message}"
+        ));
+    }
+    Sm2Params::default()
+}
+
+/// `steps` when they can be used; otherwise the problem is logged and new
+/// cards go through the default steps.
+fn learning_steps(steps: Vec<LearningStep>) -> Vec<LearningStep> {
+    match learning::learning_steps_problem(&steps) {
+        None => steps,
+        Some(message) => {
+            crate::db::log_error(&format!(
+                "Ignoring learning_steps in config.toml: {message}"
+            ));
+            learning::default_learning_steps()
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct OcrConfig {
+    /// `vision` or `tesseract`; the platform's default when unset.
+    provider: Option<OcrProviderKind>,
+    /// Pre-processing profile applied to photos before OCR: `photo`, `scan`,
+    /// or a name under `[ocr.profiles]`. Photos are read as they are when
+    /// unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    profile: Option<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    profiles: HashMap<String, PreprocessSteps>,
+}
+
+/// `OCR_PROVIDER` wins over `[ocr] provider` in config.toml; without either,
+/// macOS uses Vision and other platforms Tesseract.
+fn ocr_provider(config: &OcrConfig) -> OcrProviderKind {
+    if let Ok(value) = std::env::var("OCR_PROVIDER") {
+        match OcrProviderKind::parse(&value) {
+            Some(provider) => return provider,
+            None => crate::db::log_error(&format!(
+                "Unknown OCR_PROVIDER '{value}' (expected vision or tesseract)"
+            )),
+        }
+    }
+    config
+        .provider
+        .unwrap_or_else(OcrProviderKind::platform_default)
+}
+
+/// The pre-processing profile named by `OCR_PROFILE`, or else by `[ocr]
+/// profile`, and whether it came from the environment.
+fn ocr_profile_name(config: &OcrConfig) -> (Option<String>, bool) {
+    let normalize = |name: &str| Some(name.trim().to_lowercase()).filter(|name| !name.is_empty());
+    match std::env::var("OCR_PROFILE")
+        .ok()
+        .and_then(|name| normalize(&name))
+    {
+        Some(name) => (Some(name), true),
+        None => (config.profile.as_deref().and_then(normalize), false),
+    }
+}
+
+/// The steps of the profile `name`. Profiles in `[ocr.profiles]` win over
+/// the built-in `photo` (every step) and `scan` (none); without a name, or
+/// with an unknown one (which is logged), nothing is applied.
+fn ocr_profile_steps(
+    name: Option<&str>,
+    profiles: &HashMap<String, PreprocessSteps>,
+) -> PreprocessSteps {
+    let Some(name) = name else {
+        return PreprocessSteps::default();
+    };
+    if let Some(steps) = profiles.get(name) {
+        return *steps;
+    }
+    match name {
+        "photo" => PreprocessSteps::photo(),
+        "scan" => PreprocessSteps::default(),
+        _ => {
+            crate::db::log_error(&format!(
+                "Unknown OCR profile '{name}'; photos are read without pre-processing"
+            ));
+            PreprocessSteps::default()
+        }
+    }
 }
 
 fn load_config(path: &Path) -> io::Result<ConfigFile> {
@@ -1904,7 +4498,12 @@ fn load_config(path: &Path) -> io::Result<ConfigFile> {
         Ok(cfg)
     } else {
         let cfg = ConfigFile {
+            learning_steps: learning::default_learning_steps(),
             session: SessionConfig::default(),
+            languages: LanguagePair::default(),
+            ocr: OcrConfig::default(),
+            sm2: Sm2Params::default(),
+            scheduler: SchedulerChoice::default(),
         };
         let content = toml::to_string_pretty(&cfg)
             .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
@@ -1914,14 +4513,26 @@ fn load_config(path: &Path) -> io::Result<ConfigFile> {
 }
 
 fn start_review_list(db: &dyn Db, app: &mut App) -> DbResult<()> {
-    app.review_list = db.load_all_words()?;
+    app.review_list = load_review_words(
+        db,
+        app.review_deck.as_ref().map(|deck| deck.id),
+        app.review_tag_filter.as_deref(),
+        app.review_search.as_deref(),
+    )?;
+    app.review_list_tags = db.word_tags()?;
     app.review_list_selection = 0;
     Ok(())
 }
 
 fn reload_review_list(db: &dyn Db, app: &mut App) -> DbResult<()> {
-    let words = db.load_all_words()?;
+    let words = load_review_words(
+        db,
+        app.review_deck.as_ref().map(|deck| deck.id),
+        app.review_tag_filter.as_deref(),
+        app.review_search.as_deref(),
+    )?;
     app.review_list = words;
+    app.review_list_tags = db.word_tags()?;
     if app.review_list.is_empty() {
         app.review_list_selection = 0;
     } else {
@@ -1935,6 +4546,33 @@ fn reload_review_list(db: &dyn Db, app: &mut App) -> DbResult<()> {
     Ok(())
 }
 
+fn load_review_words(
+    db: &dyn Db,
+    deck: Option<Uuid>,
+    tag: Option<&str>,
+    search: Option<&str>,
+) -> DbResult<Vec<Word>> {
+    let mut words = match search {
+        None => match tag {
+            Some(tag) => db.list_by_tag(tag)?,
+            None => db.load_all_words()?,
+        },
+        Some(query) => {
+            let mut words = db.search_words(query)?;
+            if let Some(tag) = tag {
+                let tag = normalize_tag(tag)?;
+                let tags = db.word_tags()?;
+                words.retain(|word| tags.get(&word.id).is_some_and(|names| names.contains(&tag)));
+            }
+            words
+        }
+    };
+    if let Some(deck) = deck {
+        words.retain(|word| word.deck_id == Some(deck));
+    }
+    Ok(words)
+}
+
 fn review_group_key(word: &Word) -> String {
     let chapter = word.chapter.as_deref().unwrap_or("Unassigned");
     let group = word.group.as_deref().unwrap_or("Ungrouped");
@@ -1969,88 +4607,702 @@ fn list_import_images() -> Vec<String> {
     images
 }
 
-fn build_preview_lines(items: &[ImportItem]) -> Vec<String> {
+/// Writes a clipboard image to `img/` as a PNG so it shows up in the import
+/// list and stays available to open from the review list afterwards.
+fn save_clipboard_image(image: arboard::ImageData) -> Result<String, String> {
+    let width = u32::try_from(image.width).map_err(|err| err.to_string())?;
+    let height = u32::try_from(image.height).map_err(|err| err.to_string())?;
+    let buffer = image::RgbaImage::from_raw(width, height, image.bytes.into_owned())
+        .ok_or_else(|| "Clipboard image has an unexpected size".to_string())?;
+    fs::create_dir_all("img").map_err(|err| format!("Failed to create img/: {err}"))?;
+    let name = format!("clipboard-{}.png", Utc::now().format("%Y%m%d-%H%M%S"));
+    let path = PathBuf::from("img").join(&name);
+    buffer
+        .save(&path)
+        .map_err(|err| format!("Failed to write {}: {err}", path.display()))?;
+    Ok(name)
+}
+
+/// One line per item under its group; with `by_image`, groups are also
+/// restarted under a header for each image.
+fn build_preview_lines(
+    items: &[ImportItem],
+    form_matches: &[FormMatch],
+    by_image: bool,
+) -> Vec<String> {
     let mut lines = Vec::new();
+    let mut last_image: Option<&str> = None;
     let mut last_group: Option<&str> = None;
     for item in items {
+        if by_image && last_image != Some(item.image.as_str()) {
+            last_image = Some(item.image.as_str());
+            last_group = None;
+            lines.push(format!("== {} ==", item.image));
+        }
         if last_group != Some(item.group.as_str()) {
             last_group = Some(item.group.as_str());
             lines.push(format!("[{}]", item.group));
         }
-        lines.push(format!("  - {}", item.text));
+        match form_matches.iter().find(|found| found.form == item.text) {
+            Some(found) => lines.push(format!("  - {} -> {}", item.text, found.lemma)),
+            None => lines.push(format!("  - {}", item.text)),
+        }
     }
     lines
 }
 
-fn import_from_image(
+fn show_import_preview(
     db: &dyn Db,
-    api: &TranslationApi,
-    image_path: &Path,
-    chapter: &str,
-    provider: OcrProviderKind,
+    app: &mut App,
+    items: Vec<ImportItem>,
+    image_names: Vec<String>,
+) {
+    app.import_form_matches = match detect_inflected_forms(db, &items, &app.languages.source) {
+        Ok(matches) => matches,
+        Err(err) => {
+            crate::db::log_error(&format!("Inflected form lookup failed: {err}"));
+            Vec::new()
+        }
+    };
+    app.import_attach_forms = true;
+    app.import_preview_items = items;
+    app.import_preview_scroll = 0;
+    app.import_preview_images = image_names;
+    app.import_plan = plan_import(db, app).unwrap_or_else(|err| {
+        crate::db::log_error(&format!("Import plan failed: {err}"));
+        ImportPlan::default()
+    });
+    app.mode = Mode::ImportPreview;
+}
+
+/// The settings remembered for files like `file_name`. A failed lookup only
+/// costs the convenience, so it is logged rather than shown.
+fn remembered_import_profile(db: &dyn Db, file_name: &str) -> Option<ImportProfile> {
+    match db.import_profiles() {
+        Ok(profiles) => find_import_profile(&profiles, file_name).cloned(),
+        Err(err) => {
+            crate::db::log_error(&format!("Loading import profiles failed: {err}"));
+            None
+        }
+    }
+}
+
+fn remember_import_profile(db: &dyn Db, profile: ImportProfile) {
+    if let Err(err) = db.save_import_profile(&profile) {
+        crate::db::log_error(&format!(
+            "Saving import profile '{}' failed: {err}",
+            profile.pattern
+        ));
+    }
+}
+
+/// Reads the file at the typed path and checks each row against the saved
+/// words of the source language. The path may start with `~/`. Columns are
+/// read with `columns` when given, else as a header row names them, else as
+/// remembered for files like this one; rows without a chapter get the
+/// remembered chapter.
+fn load_csv_preview(
+    db: &dyn Db,
+    app: &App,
+    columns: Option<&ColumnMapping>,
+) -> Result<CsvPreview, String> {
+    let path = app.csv_path.as_str().trim().to_string();
+    if path.is_empty() {
+        return Err("Enter the path of a CSV file".to_string());
+    }
+    let resolved = match path.strip_prefix("~/") {
+        Some(rest) => directories::BaseDirs::new()
+            .map(|dirs| dirs.home_dir().join(rest))
+            .unwrap_or_else(|| PathBuf::from(&path)),
+        None => PathBuf::from(&path),
+    };
+    let content =
+        fs::read_to_string(&resolved).map_err(|err| format!("Failed to read {path}: {err}"))?;
+    let profile = remembered_import_profile(db, &path);
+    let mut table =
+        csv_import::parse_rows(&content, columns).map_err(|err| format!("{path}: {err}"))?;
+    if columns.is_none()
+        && !table.header
+        && let Some(remembered) = profile
+            .as_ref()
+            .and_then(|profile| profile.columns.as_deref())
+    {
+        match ColumnMapping::parse(remembered) {
+            Ok(remembered) => {
+                table = csv_import::parse_rows(&content, Some(&remembered))
+                    .map_err(|err| format!("{path}: {err}"))?;
+            }
+            Err(err) => crate::db::log_error(&format!(
+                "Ignoring remembered columns '{remembered}' for {path}: {err}"
+            )),
+        }
+    }
+    let mut rows = table.rows;
+    if let Some(chapter) = profile
+        .as_ref()
+        .and_then(|profile| profile.chapter.as_ref())
+    {
+        for row in rows.iter_mut().filter(|row| row.chapter.is_none()) {
+            row.chapter = Some(chapter.clone());
+        }
+    }
+    let mut plan = ImportPlanBuilder::new().new_per_day(Some(app.session_config.max_new_cards));
+    let mut new = Vec::with_capacity(rows.len());
+    for row in &rows {
+        let saved = db
+            .word_exists(&row.text, &app.languages.source)
+            .map_err(|err| format!("Duplicate check failed: {err}"))?;
+        new.push(plan.add(
+            &row.text,
+            row.translation.as_deref(),
+            row.group.as_deref(),
+            saved,
+        ));
+    }
+    Ok(CsvPreview {
+        path,
+        rows,
+        new,
+        plan: plan.build(),
+        scroll: 0,
+        columns: table.columns,
+        profile,
+    })
+}
+
+/// What to remember about the preview's source once it is imported: its
+/// columns, and its chapter when all rows share one.
+fn csv_import_profile(preview: &CsvPreview) -> ImportProfile {
+    let mut chapters = preview.rows.iter().filter_map(|row| row.chapter.as_deref());
+    let shared = chapters
+        .next()
+        .filter(|first| chapters.all(|chapter| chapter == *first))
+        .map(str::to_string);
+    ImportProfile {
+        pattern: source_pattern(&preview.path),
+        columns: Some(preview.columns.to_string()),
+        ocr_profile: None,
+        chapter: shared.or_else(|| {
+            preview
+                .profile
+                .as_ref()
+                .and_then(|profile| profile.chapter.clone())
+        }),
+        updated_at: Utc::now(),
+    }
+}
+
+/// The preview's new rows as words to save. Rows without a translation are
+/// saved with an empty one, so Ctrl+T (fix translations) picks them up.
+fn csv_new_words(preview: &CsvPreview, language: &Language) -> Vec<NewWord> {
+    let captured_at = Utc::now();
+    let name = Path::new(&preview.path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| preview.path.clone());
+    preview
+        .rows
+        .iter()
+        .zip(&preview.new)
+        .filter(|(_, new)| **new)
+        .map(|(row, _)| NewWord {
+            text: row.text.clone(),
+            translation: row.translation.clone().unwrap_or_default(),
+            language: language.clone(),
+            chapter: row.chapter.clone(),
+            group: row.group.clone(),
+            source: Some(WordSource {
+                kind: SourceKind::Csv,
+                name: Some(name.clone()),
+                page: Some(format!("row {}", row.line)),
+                captured_at,
+                region: None,
+            }),
+        })
+        .collect()
+}
+
+/// Dry-run numbers for the preview. Forms about to be attached to saved
+/// words are left out: they are neither new words nor skipped. Translations
+/// are fetched during the import, so every new word counts as untranslated.
+fn plan_import(db: &dyn Db, app: &App) -> DbResult<ImportPlan> {
+    let mut plan = ImportPlanBuilder::new().new_per_day(Some(app.session_config.max_new_cards));
+    for item in &app.import_preview_items {
+        if app.import_attach_forms
+            && app
+                .import_form_matches
+                .iter()
+                .any(|found| found.form == item.text)
+        {
+            continue;
+        }
+        let saved = db.word_exists(&item.text, &app.languages.source)?;
+        plan.add(&item.text, None, Some(&item.group), saved);
+    }
+    Ok(plan.build())
+}
+
+/// Finds preview items that are not saved themselves but inflect a saved
+/// word of `language` (loopt/liep -> lopen).
+fn detect_inflected_forms(
+    db: &dyn Db,
+    items: &[ImportItem],
+    language: &Language,
+) -> DbResult<Vec<FormMatch>> {
+    let mut matches = Vec::new();
+    for item in items {
+        if db.word_exists(&item.text, language)? {
+            continue;
+        }
+        matches.extend(find_inflected_form(db, &item.text, language)?);
+    }
+    Ok(matches)
+}
+
+/// The saved word `text` inflects, trying the lemmas `language`'s rules
+/// suggest, most likely first.
+fn find_inflected_form(
+    db: &dyn Db,
+    text: &str,
+    language: &Language,
+) -> DbResult<Option<FormMatch>> {
+    for lemma in language.rules().lemma_candidates(text) {
+        if let Some(word) = db.find_lemma(&lemma, language)? {
+            return Ok(Some(FormMatch {
+                form: text.to_string(),
+                word_id: word.id,
+                lemma: word.text,
+            }));
+        }
+    }
+    Ok(None)
+}
+
+/// Stores each matched form on its saved word. Only called once the import
+/// has saved its words, so a cancelled or failed import attaches nothing.
+fn attach_import_forms(db: &dyn Db, matches: &[FormMatch]) -> DbResult<()> {
+    for found in matches {
+        db.add_word_forms(found.word_id, std::slice::from_ref(&found.form))?;
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_import(
+    api: Arc<TranslationApi>,
+    glossary: Arc<Glossary>,
+    image_names: Vec<String>,
+    chapter: String,
+    languages: LanguagePair,
+    ocr: OcrSettings,
     initial_group: Option<String>,
-) -> Result<usize, String> {
-    let lines = run_ocr(provider, image_path)?;
-    let items = parse_grouped_items(&lines, initial_group)?;
-    if items.is_empty() {
-        return Ok(0);
+    forms: Vec<FormMatch>,
+) -> ImportJob {
+    let attached_forms: HashSet<String> = forms
+        .iter()
+        .map(|found| found.form.to_lowercase())
+        .collect();
+    let (tx, rx) = mpsc::channel();
+    let cancel = Arc::new(AtomicBool::new(false));
+    let worker_cancel = Arc::clone(&cancel);
+    let worker_images = image_names.clone();
+    let worker_languages = languages.clone();
+    thread::spawn(move || {
+        let result = run_import_worker(
+            &api,
+            &glossary,
+            &worker_images,
+            &worker_languages,
+            ocr,
+            initial_group,
+            &attached_forms,
+            &tx,
+            &worker_cancel,
+        );
+        let _ = tx.send(match result {
+            Ok(()) => ImportProgress::Done,
+            Err(err) => ImportProgress::Failed(err),
+        });
+    });
+    ImportJob {
+        image_names,
+        chapter,
+        language: languages.source,
+        rx,
+        cancel,
+        scanned: 0,
+        parsed: None,
+        translated: 0,
+        pending: Vec::new(),
+        inserted: 0,
+        skipped: 0,
+        forms,
+        attached: 0,
+        queued: 0,
     }
+}
 
-    let mut inserted = 0usize;
-    let mut skipped = 0usize;
-    let chunk_size = 25usize;
-    let mut index = 0usize;
-    while index < items.len() {
-        let end = (index + chunk_size).min(items.len());
-        let chunk = &items[index..end];
+#[allow(clippy::too_many_arguments)]
+fn run_import_worker(
+    api: &TranslationApi,
+    glossary: &Glossary,
+    image_names: &[String],
+    languages: &LanguagePair,
+    ocr: OcrSettings,
+    initial_group: Option<String>,
+    attached_forms: &HashSet<String>,
+    tx: &Sender<ImportProgress>,
+    cancel: &AtomicBool,
+) -> Result<(), String> {
+    let mut items = ocr_import_items(
+        ocr,
+        image_names,
+        &languages.source,
+        initial_group,
+        |scanned| {
+            let _ = tx.send(ImportProgress::Scanned(scanned));
+        },
+    )?;
+    items.retain(|item| !attached_forms.contains(&item.text.to_lowercase()));
+    let _ = tx.send(ImportProgress::Parsed(items.len()));
+
+    let (source_lang, target_lang) = TranslateDirection::SourceToTarget.language_codes(languages);
+    let mut offline = false;
+    for chunk in items.chunks(IMPORT_CHUNK_SIZE) {
+        if cancel.load(Ordering::Relaxed) {
+            return Ok(());
+        }
         let texts: Vec<&str> = chunk.iter().map(|item| item.text.as_str()).collect();
-        let translations = translate_batch_via_api(api, &texts, "NL", "EN")?;
-        for (item, translation) in chunk.iter().zip(translations) {
-            if db
-                .word_exists(&item.text, Language::Dutch)
-                .map_err(|err| format!("Failed to check duplicates: {err}"))?
-            {
-                skipped += 1;
-                continue;
+        // Offline, the words are still saved; blank translations are what
+        // Ctrl+T picks up once the connection is back.
+        let translations = if offline {
+            vec![String::new(); chunk.len()]
+        } else {
+            match translate_batch_via_api(api, &texts, &source_lang, &target_lang) {
+                Ok(translations) => translations,
+                Err(err) if err == TRANSLATE_OFFLINE => {
+                    offline = true;
+                    vec![String::new(); chunk.len()]
+                }
+                Err(err) => return Err(err),
             }
-            db.save_word(
-                &item.text,
-                &translation,
-                Language::Dutch,
-                Some(chapter),
-                Some(&item.group),
-            )
-            .map_err(|err| {
-                let detail = format!(
-                    "Import save_word failed: {err} (word='{}', translation='{}', chapter='{}', group='{}')",
-                    item.text,
-                    translation,
-                    chapter,
-                    item.group
-                );
-                crate::db::log_error(&detail);
-                format!("Failed to save word: {err}")
-            })?;
-            inserted += 1;
+        };
+        let translated = chunk
+            .iter()
+            .cloned()
+            .zip(translations)
+            .map(|(item, translation)| {
+                let translation = glossary.apply(&item.text, &languages.source, translation);
+                (item, translation)
+            })
+            .collect();
+        if tx.send(ImportProgress::Translated(translated)).is_err() {
+            return Ok(());
+        }
+    }
+    Ok(())
+}
+
+/// Drains worker progress, collecting translated rows until the worker is
+/// done; they are then saved together by [`commit_import`].
+fn poll_import(db: &dyn Db, app: &mut App) {
+    let Some(job) = app.import_job.as_mut() else {
+        return;
+    };
+    let mut outcome = None;
+    loop {
+        match job.rx.try_recv() {
+            Ok(ImportProgress::Scanned(count)) => job.scanned = count,
+            Ok(ImportProgress::Parsed(count)) => job.parsed = Some(count),
+            Ok(ImportProgress::Translated(items)) => {
+                job.translated += items.len();
+                job.queued += items
+                    .iter()
+                    .filter(|(_, translation)| translation.is_empty())
+                    .count();
+                let captured_at = Utc::now();
+                job.pending
+                    .extend(items.into_iter().map(|(item, translation)| NewWord {
+                        text: item.text,
+                        translation,
+                        language: job.language.clone(),
+                        chapter: Some(job.chapter.clone()),
+                        group: Some(item.group),
+                        source: Some(WordSource {
+                            kind: SourceKind::Photo,
+                            name: Some(item.image),
+                            page: Some(job.chapter.clone()),
+                            captured_at,
+                            region: item.region,
+                        }),
+                    }));
+            }
+            Ok(ImportProgress::Failed(err)) => {
+                outcome = Some(ImportOutcome::Failed(err));
+                break;
+            }
+            Ok(ImportProgress::Done) | Err(TryRecvError::Disconnected) => {
+                outcome = Some(commit_import(db, job, ImportOutcome::Completed));
+                break;
+            }
+            Err(TryRecvError::Empty) => break,
+        }
+    }
+    if let Some(outcome) = outcome {
+        finish_import(app, outcome);
+    }
+}
+
+/// Saves every collected word in one transaction. A failed save keeps
+/// nothing, so the chapter is never left half imported. Inflected forms are
+/// attached only when the import completed.
+fn commit_import(db: &dyn Db, job: &mut ImportJob, outcome: ImportOutcome) -> ImportOutcome {
+    let words = std::mem::take(&mut job.pending);
+    match db.save_words_bulk(&words) {
+        Ok(inserted) => {
+            job.inserted = inserted;
+            job.skipped = words.len() - inserted;
+            if !matches!(outcome, ImportOutcome::Completed) {
+                return outcome;
+            }
+            let forms = std::mem::take(&mut job.forms);
+            if let Err(err) = attach_import_forms(db, &forms) {
+                crate::db::log_error(&format!(
+                    "Import add_word_forms failed: {err} (chapter='{}', forms={})",
+                    job.chapter,
+                    forms.len()
+                ));
+                return ImportOutcome::Failed(format!("Failed to attach forms: {err}"));
+            }
+            job.attached = forms.len();
+            outcome
+        }
+        Err(err) => {
+            crate::db::log_error(&format!(
+                "Import save_words_bulk failed: {err} (chapter='{}', words={})",
+                job.chapter,
+                words.len()
+            ));
+            ImportOutcome::Failed(format!("Failed to save words: {err}"))
+        }
+    }
+}
+
+/// Stops the worker and keeps the words translated so far.
+fn cancel_import(db: &dyn Db, app: &mut App) {
+    let Some(job) = app.import_job.as_mut() else {
+        return;
+    };
+    job.cancel.store(true, Ordering::Relaxed);
+    let outcome = commit_import(db, job, ImportOutcome::Cancelled);
+    finish_import(app, outcome);
+}
+
+fn finish_import(app: &mut App, outcome: ImportOutcome) {
+    let Some(job) = app.import_job.take() else {
+        return;
+    };
+    let status = match &outcome {
+        ImportOutcome::Completed => "completed".to_string(),
+        ImportOutcome::Cancelled => "cancelled".to_string(),
+        ImportOutcome::Failed(err) => format!("failed: {err}"),
+    };
+    crate::db::log_import(&format!(
+        "image='{}' chapter='{}' parsed={} translated={} inserted={} skipped={} attached={} status={}",
+        job.image_names.join(", "),
+        job.chapter,
+        job.parsed.unwrap_or(0),
+        job.translated,
+        job.inserted,
+        job.skipped,
+        job.attached,
+        status
+    ));
+    let mut summary = format!("{} words (skipped {} duplicates", job.inserted, job.skipped);
+    if job.attached > 0 {
+        summary.push_str(&format!(", attached {} forms", job.attached));
+    }
+    summary.push(')');
+    if job.queued > 0 {
+        summary.push_str(&format!(
+            "; offline — {} queued for Ctrl+T to translate",
+            job.queued
+        ));
+    }
+    match outcome {
+        ImportOutcome::Completed => {
+            app.set_message(format!("Imported {summary}"));
+            app.mode = Mode::Message;
+        }
+        ImportOutcome::Cancelled => {
+            app.set_message(format!("Import cancelled after {summary}"));
+            app.mode = Mode::Message;
+        }
+        ImportOutcome::Failed(err) => {
+            app.set_message(format!("Import failed after {summary}: {err}"));
+            app.mode = Mode::Import;
+        }
+    }
+}
+
+/// Opens the photo a word was imported from, cropped around the OCR region
+/// when one was recorded so the printed word is easy to spot.
+fn open_source_image(word: &Word) -> Result<(), String> {
+    let source = word
+        .source
+        .as_ref()
+        .filter(|source| source.kind == SourceKind::Photo)
+        .ok_or_else(|| format!("'{}' was not imported from a photo", word.text))?;
+    let name = source
+        .name
+        .as_deref()
+        .ok_or_else(|| "No image name recorded".to_string())?;
+    let image_path = PathBuf::from("img").join(name);
+    if !image_path.exists() {
+        return Err(format!("{} no longer exists", image_path.display()));
+    }
+
+    let path = match source.region {
+        Some(region) => crop_source_region(&image_path, region, word.id)?,
+        None => image_path,
+    };
+    let opener = if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    Command::new(opener)
+        .arg(&path)
+        .spawn()
+        .map_err(|err| format!("Failed to run {opener}: {err}"))?;
+    Ok(())
+}
+
+fn crop_source_region(
+    image_path: &Path,
+    region: SourceRegion,
+    word_id: Uuid,
+) -> Result<PathBuf, String> {
+    let image = image::open(image_path)
+        .map_err(|err| format!("Failed to read {}: {err}", image_path.display()))?;
+    let (width, height) = (image.width() as f32, image.height() as f32);
+    // Pad generously so neighbouring lines give context for the word.
+    let pad_x = 0.05;
+    let pad_y = region.h * 3.0;
+    let left = ((region.x - pad_x).max(0.0) * width) as u32;
+    let top = ((region.y - pad_y).max(0.0) * height) as u32;
+    let right = ((region.x + region.w + pad_x).min(1.0) * width) as u32;
+    let bottom = ((region.y + region.h + pad_y).min(1.0) * height) as u32;
+    if right <= left || bottom <= top {
+        return Ok(image_path.to_path_buf());
+    }
+    let cropped = image.crop_imm(left, top, right - left, bottom - top);
+    let out = std::env::temp_dir().join(format!("le-source-{word_id}.png"));
+    cropped
+        .save(&out)
+        .map_err(|err| format!("Failed to write {}: {err}", out.display()))?;
+    Ok(out)
+}
+
+/// OCRs `image_names` from `img/` in order into one item list, calling
+/// `on_scanned` after each image.
+fn ocr_import_items(
+    ocr: OcrSettings,
+    image_names: &[String],
+    language: &Language,
+    initial_group: Option<String>,
+    on_scanned: impl FnMut(usize),
+) -> Result<Vec<ImportItem>, String> {
+    group_pages(
+        &ocr_pages(ocr, image_names, language, on_scanned)?,
+        language,
+        initial_group,
+    )
+}
+
+/// The OCR lines of each image, paired with its name.
+fn ocr_pages(
+    ocr: OcrSettings,
+    image_names: &[String],
+    language: &Language,
+    mut on_scanned: impl FnMut(usize),
+) -> Result<Vec<(String, Vec<OcrLine>)>, String> {
+    let mut pages = Vec::new();
+    for (idx, name) in image_names.iter().enumerate() {
+        let lines = run_ocr(ocr, &PathBuf::from("img").join(name), language)
+            .map_err(|err| format!("{name}: {err}"))?;
+        pages.push((name.clone(), lines));
+        on_scanned(idx + 1);
+    }
+    Ok(pages)
+}
+
+/// Parses OCR'd pages into one item list. A page continues in the group the
+/// previous page ended in, since a chapter's word lists run across pages.
+fn group_pages(
+    pages: &[(String, Vec<OcrLine>)],
+    language: &Language,
+    initial_group: Option<String>,
+) -> Result<Vec<ImportItem>, String> {
+    let mut group = initial_group;
+    let mut items = Vec::new();
+    for (name, lines) in pages {
+        let page = parse_grouped_items(lines, language.rules(), group.clone(), name)?;
+        if let Some(last) = page.last() {
+            group = Some(last.group.clone());
         }
-        index = end;
+        items.extend(page);
     }
+    Ok(items)
+}
 
-    if skipped > 0 {
-        println!("Skipped {skipped} duplicate words.");
+/// The first chapter line printed on the pages ("Hoofdstuk 3"), spelled like
+/// an existing chapter when one matches so the words join it.
+fn detect_chapter(pages: &[(String, Vec<OcrLine>)], chapters: &[String]) -> Option<String> {
+    let detected = pages
+        .iter()
+        .flat_map(|(_, lines)| lines)
+        .map(|line| line.text.trim())
+        .find(|text| looks_like_chapter_line(text))?
+        .trim_end_matches(':')
+        .trim();
+    if detected.is_empty() {
+        return None;
     }
-    Ok(inserted)
+    Some(
+        chapters
+            .iter()
+            .find(|chapter| chapter.trim().eq_ignore_ascii_case(detected))
+            .cloned()
+            .unwrap_or_else(|| detected.to_string()),
+    )
 }
 
-fn run_ocr(provider: OcrProviderKind, image_path: &Path) -> Result<Vec<OcrLine>, String> {
-    match provider {
-        OcrProviderKind::Vision => run_vision_ocr(image_path),
+/// Reads `image_path` after the configured pre-processing, with boxes
+/// relative to the original photo.
+fn run_ocr(
+    ocr: OcrSettings,
+    image_path: &Path,
+    language: &Language,
+) -> Result<Vec<OcrLine>, String> {
+    let prepared = ocr_preprocess::prepare(image_path, ocr.preprocess)?;
+    let mut lines = match ocr.provider {
+        OcrProviderKind::Vision => run_vision_ocr(prepared.path())?,
+        OcrProviderKind::Tesseract => run_tesseract_ocr(prepared.path(), language)?,
+    };
+    for line in &mut lines {
+        let OcrBBox { x, y, w, h } = line.bbox;
+        let [x, y, w, h] = prepared.to_original([x, y, w, h]);
+        line.bbox = OcrBBox { x, y, w, h };
     }
+    Ok(lines)
 }
 
 fn run_vision_ocr(image_path: &Path) -> Result<Vec<OcrLine>, String> {
     if !cfg!(target_os = "macos") {
-        return Err("Vision OCR is only supported on macOS".to_string());
+        return Err(
+            "Vision OCR is only supported on macOS; set OCR_PROVIDER=tesseract".to_string(),
+        );
     }
     let script_path = PathBuf::from("scripts/vision_ocr.swift");
     if !script_path.exists() {
@@ -2073,9 +5325,119 @@ fn run_vision_ocr(image_path: &Path) -> Result<Vec<OcrLine>, String> {
         .map_err(|err| format!("Failed to parse OCR output: {err}"))
 }
 
+/// Runs `tesseract` with the source language's model, or the models named in
+/// `TESSERACT_LANG` (e.g. `nld+eng`), and groups its words into lines.
+fn run_tesseract_ocr(image_path: &Path, language: &Language) -> Result<Vec<OcrLine>, String> {
+    let models = std::env::var("TESSERACT_LANG")
+        .ok()
+        .filter(|models| !models.trim().is_empty())
+        .unwrap_or_else(|| tesseract_language(language).to_string());
+    let output = Command::new("tesseract")
+        .arg(image_path)
+        .arg("stdout")
+        .args(["-l", models.trim(), "tsv"])
+        .output()
+        .map_err(|err| format!("Failed to run tesseract: {err}"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Tesseract OCR failed: {stderr}"));
+    }
+
+    parse_tesseract_tsv(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Tesseract's model name for `language`, falling back to English.
+fn tesseract_language(language: &Language) -> &'static str {
+    match language.primary() {
+        "nl" => "nld",
+        "de" => "deu",
+        "fr" => "fra",
+        "es" => "spa",
+        "it" => "ita",
+        "pt" => "por",
+        "sv" => "swe",
+        "da" => "dan",
+        "nb" | "nn" | "no" => "nor",
+        "pl" => "pol",
+        _ => "eng",
+    }
+}
+
+/// Turns Tesseract's TSV output (one row per word, in pixels from the top
+/// left) into the lines Vision reports: one box per line, normalized to the
+/// page and measured from the bottom left.
+fn parse_tesseract_tsv(tsv: &str) -> Result<Vec<OcrLine>, String> {
+    struct Word<'a> {
+        line: (u32, u32, u32, u32),
+        left: f32,
+        top: f32,
+        right: f32,
+        bottom: f32,
+        confidence: f32,
+        text: &'a str,
+    }
+
+    let mut page = None;
+    let mut words = Vec::new();
+    for row in tsv.lines().skip(1) {
+        let fields: Vec<&str> = row.splitn(12, '\t').collect();
+        if fields.len() < 11 {
+            continue;
+        }
+        let number = |index: usize| fields[index].trim().parse::<f32>().unwrap_or(-1.0);
+        let id = |index: usize| fields[index].trim().parse::<u32>().unwrap_or(0);
+        match fields[0] {
+            "1" => page = Some((number(8), number(9))),
+            "5" => {
+                let text = fields.get(11).map_or("", |text| text.trim());
+                if text.is_empty() || number(10) < 0.0 {
+                    continue;
+                }
+                words.push(Word {
+                    line: (id(1), id(2), id(3), id(4)),
+                    left: number(6),
+                    top: number(7),
+                    right: number(6) + number(8),
+                    bottom: number(7) + number(9),
+                    confidence: number(10) / 100.0,
+                    text,
+                });
+            }
+            _ => {}
+        }
+    }
+    let Some((page_width, page_height)) = page.filter(|(w, h)| *w > 0.0 && *h > 0.0) else {
+        return Err("Tesseract output has no page size".to_string());
+    };
+
+    let mut lines = Vec::new();
+    for line_words in words.chunk_by(|a, b| a.line == b.line) {
+        let left = line_words.iter().map(|w| w.left).fold(f32::MAX, f32::min);
+        let top = line_words.iter().map(|w| w.top).fold(f32::MAX, f32::min);
+        let right = line_words.iter().map(|w| w.right).fold(0.0, f32::max);
+        let bottom = line_words.iter().map(|w| w.bottom).fold(0.0, f32::max);
+        let text: Vec<&str> = line_words.iter().map(|w| w.text).collect();
+        lines.push(OcrLine {
+            text: text.join(" "),
+            bbox: OcrBBox {
+                x: left / page_width,
+                y: 1.0 - bottom / page_height,
+                w: (right - left) / page_width,
+                h: (bottom - top) / page_height,
+            },
+            confidence: line_words.iter().map(|w| w.confidence).sum::<f32>()
+                / line_words.len() as f32,
+        });
+    }
+    Ok(lines)
+}
+
 fn parse_grouped_items(
     lines: &[OcrLine],
+    rules: &dyn LanguageRules,
     initial_group: Option<String>,
+    image_name: &str,
 ) -> Result<Vec<ImportItem>, String> {
     let mut entries: Vec<LineEntry> = lines
         .iter()
@@ -2093,6 +5455,7 @@ fn parse_grouped_items(
                 text: text.to_string(),
                 x,
                 y_top,
+                width: line.bbox.w,
                 height: line.bbox.h,
             })
         })
@@ -2118,7 +5481,7 @@ fn parse_grouped_items(
             if normalized.is_empty() {
                 continue;
             }
-            if is_heading(&entry, median_height) {
+            if is_heading(rules, &entry, median_height) {
                 current_group = Some(normalize_heading(&normalized));
                 continue;
             }
@@ -2128,6 +5491,13 @@ fn parse_grouped_items(
             items.push(ImportItem {
                 text: normalized,
                 group,
+                image: image_name.to_string(),
+                region: Some(SourceRegion {
+                    x: entry.x,
+                    y: entry.y_top,
+                    w: entry.width,
+                    h: entry.height,
+                }),
             });
         }
     }
@@ -2168,22 +5538,9 @@ fn split_into_columns(entries: &mut [LineEntry]) -> Vec<Vec<LineEntry>> {
     columns.into_iter().map(|column| column.lines).collect()
 }
 
-fn is_heading(entry: &LineEntry, median_height: f32) -> bool {
+fn is_heading(rules: &dyn LanguageRules, entry: &LineEntry, median_height: f32) -> bool {
     let text = entry.text.trim();
-    if text.is_empty() {
-        return false;
-    }
-    if text.contains(',') || text.contains('-') || text.contains('(') || text.contains(')') {
-        return false;
-    }
-    let mut chars = text.chars();
-    let Some(first) = chars.next() else {
-        return false;
-    };
-    if !first.is_uppercase() {
-        return false;
-    }
-    if chars.clone().any(|c| c.is_uppercase()) {
+    if !rules.is_heading_text(text) {
         return false;
     }
     if median_height > 0.0 {
@@ -2206,16 +5563,13 @@ fn normalize_item_text(text: &str) -> String {
     trimmed.trim().replace('.', ",")
 }
 
+/// Textbooks title chapters in either language, so every language's
+/// markers are checked.
 fn looks_like_chapter_line(text: &str) -> bool {
     let lowered = text.to_lowercase();
-    if lowered.contains("hoofdstuk") || lowered.contains("chapter") || lowered.contains("hoolastuk")
-    {
-        return true;
-    }
-    if lowered.starts_with("hoo") && lowered.contains("stuk") {
-        return true;
-    }
-    false
+    rules::ALL
+        .iter()
+        .any(|rules| rules.is_chapter_line(&lowered))
 }
 
 fn looks_like_page_number(text: &str) -> bool {
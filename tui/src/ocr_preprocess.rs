@@ -0,0 +1,384 @@
+//! Clean-up applied to photos before OCR: grayscale, a contrast stretch,
+//! deskewing, and cropping the empty margins. Which steps run comes from the
+//! OCR profile in config.toml. The OCR provider reads a temporary copy, and
+//! the boxes it reports are mapped back onto the original photo so source
+//! crops still line up.
+
+use std::path::{Path, PathBuf};
+
+use image::{DynamicImage, GrayImage, ImageBuffer, Pixel, imageops};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Steepest tilt `deskew` looks for, either way.
+const MAX_SKEW_DEGREES: f32 = 6.0;
+const SKEW_STEP_DEGREES: f32 = 0.2;
+/// Smaller corrections are not worth resampling the photo for.
+const MIN_SKEW_DEGREES: f32 = 0.2;
+/// Width the photo is scaled to while measuring skew.
+const ANALYSIS_WIDTH: u32 = 1000;
+/// Share of the darkest and lightest pixels ignored by the contrast stretch,
+/// so a glare spot or a shadow does not set the range.
+const CONTRAST_CLIP: f32 = 0.01;
+/// A row or column holds content when at least this share of it is dark.
+const CONTENT_SHARE: f32 = 0.005;
+/// Kept around the content when cropping, as a share of each side.
+const CROP_PADDING: f32 = 0.02;
+
+/// Pre-processing toggles of one OCR profile; all off by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PreprocessSteps {
+    pub grayscale: bool,
+    /// Stretches the brightness range so faded print under classroom
+    /// lighting reads as black on white.
+    pub contrast: bool,
+    /// Straightens a page photographed at an angle, up to 6°.
+    pub deskew: bool,
+    pub crop_margins: bool,
+}
+
+impl PreprocessSteps {
+    /// The built-in `photo` profile: everything on.
+    pub fn photo() -> Self {
+        PreprocessSteps {
+            grayscale: true,
+            contrast: true,
+            deskew: true,
+            crop_margins: true,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        *self == PreprocessSteps::default()
+    }
+}
+
+/// The image handed to the OCR provider. A pre-processed copy is deleted
+/// when this is dropped.
+pub struct Prepared {
+    path: PathBuf,
+    temporary: bool,
+    /// Original width and height.
+    size: (f32, f32),
+    /// Rotation applied about the center, in radians.
+    angle: f32,
+    /// Left, top, width, and height of the crop, in the rotated image.
+    crop: (f32, f32, f32, f32),
+}
+
+impl Prepared {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Maps a box the provider reported on the prepared image onto the
+    /// original. Boxes are `[x, y, w, h]`, normalized and measured from the
+    /// bottom left like Vision's.
+    pub fn to_original(&self, bbox: [f32; 4]) -> [f32; 4] {
+        if !self.temporary {
+            return bbox;
+        }
+        let [x, y, w, h] = bbox;
+        let (left, top, width, height) = self.crop;
+        let (original_width, original_height) = self.size;
+        let corners = [(x, y), (x + w, y), (x, y + h), (x + w, y + h)].map(|(cx, cy)| {
+            let px = left + cx * width;
+            let py = top + (1.0 - cy) * height;
+            source_point(px, py, self.angle, self.size)
+        });
+        let min_x = corners.iter().map(|c| c.0).fold(f32::MAX, f32::min);
+        let max_x = corners.iter().map(|c| c.0).fold(f32::MIN, f32::max);
+        let min_y = corners.iter().map(|c| c.1).fold(f32::MAX, f32::min);
+        let max_y = corners.iter().map(|c| c.1).fold(f32::MIN, f32::max);
+        let x0 = (min_x / original_width).clamp(0.0, 1.0);
+        let x1 = (max_x / original_width).clamp(0.0, 1.0);
+        let y0 = (1.0 - max_y / original_height).clamp(0.0, 1.0);
+        let y1 = (1.0 - min_y / original_height).clamp(0.0, 1.0);
+        [x0, y0, x1 - x0, y1 - y0]
+    }
+}
+
+impl Drop for Prepared {
+    fn drop(&mut self) {
+        if self.temporary {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Runs `steps` on the image at `path`. With no steps the original is used
+/// as it is, without decoding it.
+pub fn prepare(path: &Path, steps: PreprocessSteps) -> Result<Prepared, String> {
+    if steps.is_empty() {
+        return Ok(Prepared {
+            path: path.to_path_buf(),
+            temporary: false,
+            size: (1.0, 1.0),
+            angle: 0.0,
+            crop: (0.0, 0.0, 1.0, 1.0),
+        });
+    }
+    let image =
+        image::open(path).map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+    let size = (image.width() as f32, image.height() as f32);
+    let mut luma = image.to_luma8();
+    let mut output = if steps.grayscale {
+        DynamicImage::ImageLuma8(luma.clone())
+    } else {
+        DynamicImage::ImageRgb8(image.to_rgb8())
+    };
+
+    if steps.contrast
+        && let Some(levels) = contrast_levels(&luma)
+    {
+        luma.pixels_mut()
+            .for_each(|pixel| pixel.0[0] = levels[pixel.0[0] as usize]);
+        match &mut output {
+            DynamicImage::ImageLuma8(gray) => *gray = luma.clone(),
+            other => {
+                if let Some(rgb) = other.as_mut_rgb8() {
+                    rgb.iter_mut()
+                        .for_each(|value| *value = levels[*value as usize]);
+                }
+            }
+        }
+    }
+
+    let mut angle = 0.0;
+    if steps.deskew
+        && let Some(skew) = estimate_skew(&luma)
+    {
+        angle = skew;
+        luma = rotate(&luma, angle, image::Luma([255]));
+        output = match output {
+            DynamicImage::ImageLuma8(gray) => {
+                DynamicImage::ImageLuma8(rotate(&gray, angle, image::Luma([255])))
+            }
+            other => DynamicImage::ImageRgb8(rotate(
+                &other.to_rgb8(),
+                angle,
+                image::Rgb([255, 255, 255]),
+            )),
+        };
+    }
+
+    let mut crop = (0.0, 0.0, size.0, size.1);
+    if steps.crop_margins
+        && let Some((left, top, width, height)) = content_bounds(&luma)
+    {
+        output = output.crop_imm(left, top, width, height);
+        crop = (left as f32, top as f32, width as f32, height as f32);
+    }
+
+    let out = std::env::temp_dir().join(format!("le-ocr-{}.png", Uuid::new_v4()));
+    output
+        .save(&out)
+        .map_err(|err| format!("Failed to write {}: {err}", out.display()))?;
+    Ok(Prepared {
+        path: out,
+        temporary: true,
+        size,
+        angle,
+        crop,
+    })
+}
+
+/// Lookup table stretching the clipped brightness range to 0–255, or `None`
+/// when the photo already uses nearly the full range.
+fn contrast_levels(luma: &GrayImage) -> Option<[u8; 256]> {
+    let histogram = histogram(luma);
+    let total: u64 = histogram.iter().sum();
+    let clip = (total as f32 * CONTRAST_CLIP) as u64;
+    let mut seen = 0;
+    let low = histogram
+        .iter()
+        .position(|count| {
+            seen += count;
+            seen > clip
+        })
+        .unwrap_or(0);
+    seen = 0;
+    let high = 255
+        - histogram
+            .iter()
+            .rev()
+            .position(|count| {
+                seen += count;
+                seen > clip
+            })
+            .unwrap_or(0);
+    if high <= low + 8 || (low < 8 && high > 247) {
+        return None;
+    }
+    let mut levels = [0; 256];
+    for (value, level) in levels.iter_mut().enumerate() {
+        let stretched = (value as f32 - low as f32) * 255.0 / (high - low) as f32;
+        *level = stretched.round().clamp(0.0, 255.0) as u8;
+    }
+    Some(levels)
+}
+
+/// The tilt of the text lines in radians, found by rotating the dark pixels
+/// of a scaled-down copy and keeping the angle whose row profile has the
+/// sharpest edges: when lines are level, rows alternate cleanly between ink
+/// and gaps.
+fn estimate_skew(luma: &GrayImage) -> Option<f32> {
+    let small = scaled_for_analysis(luma);
+    let threshold = otsu_threshold(&small);
+    let dark: Vec<(f32, f32)> = small
+        .enumerate_pixels()
+        .filter(|(_, _, pixel)| pixel.0[0] <= threshold)
+        .map(|(x, y, _)| (x as f32, y as f32))
+        .collect();
+    let pixels = (small.width() * small.height()) as usize;
+    // A mostly dark frame is background, not a page.
+    if dark.len() < 100 || dark.len() > pixels / 2 {
+        return None;
+    }
+    let diagonal = (small.width() as f32).hypot(small.height() as f32);
+    let rows = (diagonal * 2.0) as usize + 1;
+    let steps = (MAX_SKEW_DEGREES / SKEW_STEP_DEGREES).round() as i32;
+    let mut best = (0.0, f64::MIN);
+    for step in -steps..=steps {
+        let angle = (step as f32 * SKEW_STEP_DEGREES).to_radians();
+        let (sin, cos) = angle.sin_cos();
+        let mut profile = vec![0u32; rows];
+        for (x, y) in &dark {
+            let row = (y * cos - x * sin + diagonal) as usize;
+            profile[row.min(rows - 1)] += 1;
+        }
+        let score: f64 = profile
+            .windows(2)
+            .map(|pair| (f64::from(pair[1]) - f64::from(pair[0])).powi(2))
+            .sum();
+        if score > best.1 {
+            best = (angle, score);
+        }
+    }
+    (best.0.to_degrees().abs() >= MIN_SKEW_DEGREES).then_some(best.0)
+}
+
+/// The box holding the page content, padded, or `None` when cropping would
+/// keep nearly everything or find almost nothing.
+fn content_bounds(luma: &GrayImage) -> Option<(u32, u32, u32, u32)> {
+    let threshold = otsu_threshold(luma);
+    let (width, height) = luma.dimensions();
+    let mut row_counts = vec![0u32; height as usize];
+    let mut column_counts = vec![0u32; width as usize];
+    for (x, y, pixel) in luma.enumerate_pixels() {
+        if pixel.0[0] <= threshold {
+            row_counts[y as usize] += 1;
+            column_counts[x as usize] += 1;
+        }
+    }
+    let span = |counts: &[u32], length: u32| {
+        let minimum = ((length as f32 * CONTENT_SHARE) as u32).max(2);
+        let first = counts.iter().position(|count| *count >= minimum)?;
+        let last = counts.iter().rposition(|count| *count >= minimum)?;
+        Some((first as u32, last as u32))
+    };
+    let (top, bottom) = span(&row_counts, width)?;
+    let (left, right) = span(&column_counts, height)?;
+    let pad_x = (width as f32 * CROP_PADDING) as u32;
+    let pad_y = (height as f32 * CROP_PADDING) as u32;
+    let left = left.saturating_sub(pad_x);
+    let top = top.saturating_sub(pad_y);
+    let right = (right + pad_x).min(width - 1);
+    let bottom = (bottom + pad_y).min(height - 1);
+    let (crop_width, crop_height) = (right - left + 1, bottom - top + 1);
+    let kept = (crop_width * crop_height) as f32 / (width * height) as f32;
+    (0.1..0.95)
+        .contains(&kept)
+        .then_some((left, top, crop_width, crop_height))
+}
+
+fn scaled_for_analysis(luma: &GrayImage) -> GrayImage {
+    if luma.width() <= ANALYSIS_WIDTH {
+        return luma.clone();
+    }
+    let height = (luma.height() as f32 * ANALYSIS_WIDTH as f32 / luma.width() as f32).max(1.0);
+    imageops::resize(
+        luma,
+        ANALYSIS_WIDTH,
+        height as u32,
+        imageops::FilterType::Triangle,
+    )
+}
+
+fn histogram(luma: &GrayImage) -> [u64; 256] {
+    let mut histogram = [0u64; 256];
+    for pixel in luma.pixels() {
+        histogram[pixel.0[0] as usize] += 1;
+    }
+    histogram
+}
+
+/// Otsu's threshold: the brightness that best separates ink from paper.
+fn otsu_threshold(luma: &GrayImage) -> u8 {
+    let histogram = histogram(luma);
+    let total: u64 = histogram.iter().sum();
+    let sum: f64 = histogram
+        .iter()
+        .enumerate()
+        .map(|(value, count)| value as f64 * *count as f64)
+        .sum();
+    let (mut below, mut below_sum) = (0u64, 0f64);
+    let mut best = (0u8, f64::MIN);
+    for (value, count) in histogram.iter().enumerate() {
+        below += count;
+        below_sum += value as f64 * *count as f64;
+        let above = total - below;
+        if below == 0 || above == 0 {
+            continue;
+        }
+        let mean_below = below_sum / below as f64;
+        let mean_above = (sum - below_sum) / above as f64;
+        let variance = below as f64 * above as f64 * (mean_below - mean_above).powi(2);
+        if variance > best.1 {
+            best = (value as u8, variance);
+        }
+    }
+    best.0
+}
+
+/// Where a pixel of the image rotated by `angle` about its center was taken
+/// from in the original.
+fn source_point(x: f32, y: f32, angle: f32, (width, height): (f32, f32)) -> (f32, f32) {
+    let (cx, cy) = (width / 2.0, height / 2.0);
+    let (sin, cos) = angle.sin_cos();
+    let (dx, dy) = (x - cx, y - cy);
+    (cx + dx * cos - dy * sin, cy + dx * sin + dy * cos)
+}
+
+/// Rotates `image` by `angle` about its center on a canvas of the same size,
+/// with bilinear sampling; corners that come from outside are `fill`.
+fn rotate<P: Pixel<Subpixel = u8>>(
+    image: &ImageBuffer<P, Vec<u8>>,
+    angle: f32,
+    fill: P,
+) -> ImageBuffer<P, Vec<u8>> {
+    let (width, height) = image.dimensions();
+    let size = (width as f32, height as f32);
+    let channels = P::CHANNEL_COUNT as usize;
+    let source = image.as_raw();
+    let mut rotated = ImageBuffer::from_pixel(width, height, fill);
+    for (x, y, pixel) in rotated.enumerate_pixels_mut() {
+        let (sx, sy) = source_point(x as f32, y as f32, angle, size);
+        if sx < 0.0 || sy < 0.0 || sx > size.0 - 1.0 || sy > size.1 - 1.0 {
+            continue;
+        }
+        let (x0, y0) = (sx.floor() as u32, sy.floor() as u32);
+        let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+        let (fx, fy) = (sx - x0 as f32, sy - y0 as f32);
+        let at = |px: u32, py: u32, channel: usize| {
+            f32::from(source[(py as usize * width as usize + px as usize) * channels + channel])
+        };
+        for (channel, value) in pixel.channels_mut().iter_mut().enumerate() {
+            let top = at(x0, y0, channel) * (1.0 - fx) + at(x1, y0, channel) * fx;
+            let bottom = at(x0, y1, channel) * (1.0 - fx) + at(x1, y1, channel) * fx;
+            *value = (top * (1.0 - fy) + bottom * fy).round() as u8;
+        }
+    }
+    rotated
+}